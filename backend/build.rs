@@ -0,0 +1,13 @@
+/// Build script: generates the gRPC messages and service from backend/proto
+fn main() {
+    println!("cargo:rerun-if-changed=backend/build.rs");
+
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=backend/proto/logjam.proto");
+        // Use the vendored protoc so building needs no system install
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+        std::env::set_var("PROTOC", protoc);
+        tonic_prost_build::compile_protos("backend/proto/logjam.proto").expect("compile backend/proto/logjam.proto");
+    }
+}
@@ -0,0 +1,142 @@
+use super::Backlink;
+use crate::application::services::LinkNode;
+use crate::domain::value_objects::PageId;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Two references that appear together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cooccurrence {
+    /// The reference that sorts first, case-insensitively
+    pub first: String,
+    pub second: String,
+    /// Blocks (or pages) where both appear
+    pub count: usize,
+    /// `count` over the blocks (or pages) where either appears (Jaccard
+    /// index), so pairs of rare references aren't drowned out by common ones
+    pub weight: f64,
+}
+
+/// A reference suggested because it often appears with the ones already written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceSuggestion {
+    pub title: String,
+    /// Blocks (or pages) where it appears with any of the references given
+    pub count: usize,
+    /// Sum of its co-occurrence weights with each reference given
+    pub score: f64,
+}
+
+/// Writing activity over a range of days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityStats {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Dated blocks written in the range
+    pub total_blocks: usize,
+    /// Days with at least one block, in date order
+    pub days: Vec<DailyActivity>,
+    /// The longest run of consecutive active days; the earliest if several tie
+    pub longest_streak: Option<Streak>,
+    /// The run of active days ending on the range's last day, or the day
+    /// before it (today's journal may not be written yet)
+    pub current_streak: Option<Streak>,
+    /// Most referenced pages of each week with activity, in date order
+    pub weeks: Vec<WeeklyTopics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyActivity {
+    pub date: NaiveDate,
+    pub blocks: usize,
+}
+
+/// Consecutive days with activity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Streak {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: usize,
+}
+
+/// Pages referenced most in one week's blocks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyTopics {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub blocks: usize,
+    /// Titles with their reference counts, most referenced first
+    pub pages: Vec<(String, usize)>,
+}
+
+/// Pages more linked to each other than to the rest of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageCluster {
+    /// Position among the clusters, largest first
+    pub id: usize,
+    /// Every page of the cluster, in title order
+    pub pages: Vec<LinkNode>,
+    /// The pages that best stand for the cluster, most central first
+    pub representatives: Vec<LinkNode>,
+}
+
+/// What [`AliasSuggestion`] suggests doing with two pages about one thing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasAction {
+    /// List the other title in the page's `alias::` property, as one
+    /// title abbreviates the other
+    Alias,
+    /// Move the other page's blocks into the page
+    Merge,
+}
+
+/// Two pages whose content embeddings are near-identical, such as `ML` and
+/// `Machine Learning`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AliasSuggestion {
+    /// The page to keep: the full title of an abbreviation, else the page
+    /// with more blocks
+    pub page_id: PageId,
+    pub title: String,
+    /// The page to make an alias of it or merge into it
+    pub other_id: PageId,
+    pub other_title: String,
+    pub action: AliasAction,
+    /// Cosine similarity of the two pages' embeddings
+    pub similarity: f32,
+    /// How likely the pages are the same thing, 0.0-1.0
+    pub confidence: f32,
+}
+
+/// A page with the references to it from other pages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferencedPage {
+    pub title: String,
+    /// None if the page is only referenced, never written
+    pub page_id: Option<PageId>,
+    /// References from blocks of other pages, dated or not
+    pub references: usize,
+    /// References from blocks dated within the window
+    pub recent_references: usize,
+}
+
+/// The pages referenced most, overall and lately
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopReferencedPages {
+    pub window_start: NaiveDate,
+    pub window_end: NaiveDate,
+    /// Most referenced first
+    pub overall: Vec<ReferencedPage>,
+    /// Most referenced within the window first; pages whose references are
+    /// mostly recent come first among equals
+    pub trending: Vec<ReferencedPage>,
+}
+
+/// A journal block mentioning a page, on its journal's date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineMention {
+    pub date: NaiveDate,
+    /// The block, on its journal page
+    pub mention: Backlink,
+}
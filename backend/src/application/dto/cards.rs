@@ -0,0 +1,17 @@
+use crate::domain::value_objects::{BlockId, PageId};
+use serde::{Deserialize, Serialize};
+
+/// A flashcard: a block tagged `#card` and, as its answer, the blocks under
+/// it, the way Logseq's spaced repetition reads them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub page_id: PageId,
+    pub page_title: String,
+    pub block_id: BlockId,
+    /// The block's content without the `#card` tag
+    pub front: String,
+    /// The blocks under it, one per line, indented two spaces a level
+    pub back: Vec<String>,
+    /// Contents of the blocks above the card, from the root down
+    pub hierarchy_path: Vec<String>,
+}
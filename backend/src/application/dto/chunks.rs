@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Local record of a chunk stored in the vector database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkRecord {
+    pub chunk_id: String,
+    pub block_id: String,
+    pub page_id: String,
+    /// Hash of the embedded (preprocessed) text
+    pub content_hash: String,
+    /// Name of the model that produced the vector
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+}
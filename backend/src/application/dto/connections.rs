@@ -0,0 +1,41 @@
+use super::Backlink;
+use crate::application::services::{EdgeType, LinkNode};
+use serde::{Deserialize, Serialize};
+
+/// How two pages are related: a chain of links from one to the other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionPath {
+    /// Links from the first page to the second, in order; none when both are the same page
+    pub steps: Vec<ConnectionStep>,
+}
+
+impl ConnectionPath {
+    /// Pages along the path, both ends included
+    pub fn pages(&self) -> Vec<&LinkNode> {
+        let mut pages: Vec<&LinkNode> = self.steps.iter().map(|step| &step.from).collect();
+        pages.extend(self.steps.last().map(|step| &step.to));
+        pages
+    }
+
+    /// Number of links between the two pages
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// One link of a connection, between neighboring pages of the path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionStep {
+    pub from: LinkNode,
+    pub to: LinkNode,
+    /// Ways the two pages link, in either direction
+    pub edge_types: Vec<EdgeType>,
+    /// Blocks of either page that make the link: the references, tags and
+    /// block references to the other page, and the blocks holding a URL the
+    /// other page holds too
+    pub blocks: Vec<Backlink>,
+}
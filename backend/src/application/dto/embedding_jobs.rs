@@ -0,0 +1,89 @@
+use crate::domain::value_objects::PageId;
+use serde::{Deserialize, Serialize};
+
+/// What an embedding job does to a page's vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingJobKind {
+    /// (Re-)embed the page
+    Embed,
+    /// Remove the page's embeddings
+    Delete,
+}
+
+impl EmbeddingJobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingJobKind::Embed => "embed",
+            EmbeddingJobKind::Delete => "delete",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "embed" => Some(EmbeddingJobKind::Embed),
+            "delete" => Some(EmbeddingJobKind::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle state of an embedding job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingJobStatus {
+    Pending,
+    Running,
+    Completed,
+    /// Gave up after exhausting all attempts
+    Failed,
+}
+
+impl EmbeddingJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingJobStatus::Pending => "pending",
+            EmbeddingJobStatus::Running => "running",
+            EmbeddingJobStatus::Completed => "completed",
+            EmbeddingJobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(EmbeddingJobStatus::Pending),
+            "running" => Some(EmbeddingJobStatus::Running),
+            "completed" => Some(EmbeddingJobStatus::Completed),
+            "failed" => Some(EmbeddingJobStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A queued embed/delete task for one page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingJob {
+    pub id: i64,
+    pub kind: EmbeddingJobKind,
+    pub page_id: PageId,
+    pub status: EmbeddingJobStatus,
+    /// Number of times the job has been attempted
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+/// Number of jobs in each state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingQueueStats {
+    pub pending: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+impl EmbeddingQueueStats {
+    /// Whether there is still work waiting or in flight
+    pub fn has_outstanding_work(&self) -> bool {
+        self.pending > 0 || self.running > 0
+    }
+}
@@ -1,3 +1,17 @@
+pub mod analytics;
+pub mod cards;
+pub mod chunks;
+pub mod connections;
+pub mod embedding_jobs;
+pub mod pages;
+pub mod reports;
 pub mod search;
 
+pub use analytics::*;
+pub use cards::*;
+pub use chunks::*;
+pub use connections::*;
+pub use embedding_jobs::*;
+pub use pages::*;
+pub use reports::*;
 pub use search::*;
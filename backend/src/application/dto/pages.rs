@@ -0,0 +1,326 @@
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::entities::Block;
+use crate::domain::value_objects::{BlockId, BlockPath, PageId};
+use crate::domain::{DomainError, DomainResult};
+use crate::infrastructure::parsers::{LogseqMarkdownParser, LogseqMarkdownWriter};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Root blocks shown in a page summary's preview
+const PREVIEW_BLOCKS: usize = 3;
+/// Longest preview line, in characters
+const PREVIEW_LENGTH: usize = 120;
+
+/// Page and block properties holding when something was last written
+const UPDATED_PROPERTIES: [&str; 4] = ["updated-at", "updated_at", "created-at", "created_at"];
+
+/// What a page list shows of a page, without its block tree
+///
+/// See [`PageRepository::find_summaries`](crate::application::repositories::PageRepository::find_summaries).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageSummary {
+    pub page_id: PageId,
+    pub title: String,
+    pub block_count: usize,
+    /// The latest `updated-at` (or `created-at`) property of the page or any
+    /// of its blocks, if it has one
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The first few top-level blocks, one line each and shortened
+    pub preview: Vec<String>,
+    /// When the page's content last changed in the index, if timestamps are kept
+    #[serde(default)]
+    pub indexed_at: Option<DateTime<Utc>>,
+    /// When the page was last embedded, if timestamps are kept
+    #[serde(default)]
+    pub embedded_at: Option<DateTime<Utc>>,
+}
+
+impl PageSummary {
+    /// Add the times a [`PageTimestampRepository`](crate::application::repositories::PageTimestampRepository) recorded
+    pub fn with_timestamps(mut self, timestamps: &PageTimestamps) -> Self {
+        self.indexed_at = timestamps.indexed_at;
+        self.embedded_at = timestamps.embedded_at;
+        self
+    }
+}
+
+impl From<&Page> for PageSummary {
+    fn from(page: &Page) -> Self {
+        PageSummary {
+            page_id: page.id().clone(),
+            title: page.title().to_string(),
+            block_count: page.all_blocks().count(),
+            updated_at: last_updated(page),
+            preview: page
+                .root_blocks()
+                .into_iter()
+                .filter(|block| !block.content().is_empty())
+                .take(PREVIEW_BLOCKS)
+                .map(|block| preview_line(block.content().as_str()))
+                .collect(),
+            indexed_at: None,
+            embedded_at: None,
+        }
+    }
+}
+
+/// When a page's content last changed in the index, and when it was last embedded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageTimestamps {
+    pub page_id: PageId,
+    pub indexed_at: Option<DateTime<Utc>>,
+    pub embedded_at: Option<DateTime<Utc>>,
+}
+
+impl PageTimestamps {
+    /// Whether the page changed since it was last embedded, or never was;
+    /// pages not known to the index are never out of date
+    pub fn embeddings_out_of_date(&self) -> bool {
+        match (self.indexed_at, self.embedded_at) {
+            (Some(indexed_at), Some(embedded_at)) => embedded_at < indexed_at,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Which pages a bulk operation applies to; a page must match every
+/// criterion given
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageFilter {
+    /// Pages under a namespace, like `imports` for `imports/2023/notes`
+    pub namespace: Option<String>,
+    /// Pages whose `tags::` property lists a tag
+    pub tag: Option<String>,
+    /// Titles matching a pattern, where `*` stands for any text and `?` for
+    /// any one character; case is ignored
+    pub title_pattern: Option<String>,
+}
+
+impl PageFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn with_title_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.title_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Whether no criterion is given, so every page would match
+    pub fn is_empty(&self) -> bool {
+        self.namespace.is_none() && self.tag.is_none() && self.title_pattern.is_none()
+    }
+}
+
+/// A page's block tree with only what an outline shows
+///
+/// See [`GetPageOutline`](crate::application::use_cases::GetPageOutline).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageOutline {
+    pub page_id: PageId,
+    pub title: String,
+    pub blocks: Vec<OutlineBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutlineBlock {
+    pub block_id: BlockId,
+    pub content: String,
+    pub children: Vec<OutlineBlock>,
+    /// Children left out below the depth limit, so a UI can offer to expand
+    /// the block
+    #[serde(default)]
+    pub hidden_children: usize,
+}
+
+/// A block's ID and its [`BlockPath`], either of which addresses it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockAddress {
+    pub page_id: PageId,
+    pub block_id: BlockId,
+    pub block_path: BlockPath,
+    /// The block's whole content, however long
+    pub content: String,
+}
+
+/// A page's content at one point in time
+///
+/// Blocks are kept as the outline markdown the page's file holds, since block
+/// IDs change on every parse; the hash covers the title, outline and page
+/// properties, so saving a page unchanged gives the same hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub page_id: PageId,
+    pub title: String,
+    /// The blocks as Logseq outline markdown
+    pub content: String,
+    pub properties: BTreeMap<String, String>,
+    /// SHA-256 of the title, content and properties
+    pub content_hash: String,
+}
+
+impl From<&Page> for PageSnapshot {
+    fn from(page: &Page) -> Self {
+        let title = page.title().to_string();
+        let content = LogseqMarkdownWriter::render(page);
+        let properties = page.properties().clone();
+
+        let mut hasher = Sha256::new();
+        for part in [&title, &content] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for (key, value) in &properties {
+            hasher.update(format!("{}\0{}\0", key, value).as_bytes());
+        }
+
+        PageSnapshot {
+            page_id: page.id().clone(),
+            title,
+            content,
+            properties,
+            content_hash: format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+impl PageSnapshot {
+    /// The page as it was, with new block IDs
+    pub fn to_page(&self) -> DomainResult<Page> {
+        let mut page = LogseqMarkdownParser::parse_content(&self.content, self.page_id.clone(), self.title.clone())
+            .map_err(|e| DomainError::InvalidValue(format!("Snapshot of page {} doesn't parse: {}", self.page_id, e)))?;
+        for (key, value) in &self.properties {
+            page.set_property(key.clone(), value.clone());
+        }
+        Ok(page)
+    }
+}
+
+/// A recorded version of a page; versions count up from 1 per page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageVersion {
+    pub version: u32,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub snapshot: PageSnapshot,
+}
+
+/// A block on one side of a [`PageDiff`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffBlock {
+    pub block_id: BlockId,
+    pub content: String,
+    /// Hierarchical path from root to this block (block contents)
+    pub hierarchy_path: Vec<String>,
+}
+
+/// How one block differs between two states of a page, tagged by `change`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum BlockChange {
+    Added { block: DiffBlock },
+    Removed { block: DiffBlock },
+    /// The block's content was edited in place
+    Modified { before: DiffBlock, after: DiffBlock },
+    /// The same content under another parent or elsewhere in the page
+    Moved { before: DiffBlock, after: DiffBlock },
+}
+
+/// Block-level differences between two states of a page
+///
+/// Changes are in the order of the newer page, with removed blocks where
+/// they used to be.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageDiff {
+    pub changes: Vec<BlockChange>,
+    /// Blocks in both, with the same content under the same parents
+    pub unchanged: usize,
+}
+
+impl PageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// The latest `updated-at` (or `created-at`) property of the page or any of
+/// its blocks
+pub(crate) fn last_updated(page: &Page) -> Option<DateTime<Utc>> {
+    let page_updated = UPDATED_PROPERTIES
+        .iter()
+        .filter_map(|key| page.properties().get(*key))
+        .filter_map(|value| parse_timestamp(value));
+    page.all_blocks().flat_map(block_timestamps).chain(page_updated).max()
+}
+
+fn block_timestamps(block: &Block) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+    UPDATED_PROPERTIES
+        .iter()
+        .filter_map(|key| block.properties().get(*key))
+        .filter_map(|value| parse_timestamp(value))
+}
+
+/// A Unix timestamp (Logseq writes milliseconds), an RFC 3339 time or a `YYYY-MM-DD` date
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        // Anything this small must be seconds
+        let millis = if timestamp.abs() < 100_000_000_000 { timestamp * 1000 } else { timestamp };
+        return DateTime::from_timestamp_millis(millis);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+        })
+}
+
+/// The first line of a block, shortened to the preview length
+fn preview_line(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(PREVIEW_LENGTH) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    #[test]
+    fn test_summary_previews_top_level_blocks_and_finds_the_latest_update() {
+        let content = format!("- First\n  - Nested\n-\n- {}\n- Third\n- Fourth", "x".repeat(200));
+        let mut page = LogseqMarkdownParser::parse_content(&content, PageId::new("p").unwrap(), "Page".to_string()).unwrap();
+        let first = page.root_blocks()[0].id().clone();
+        let nested = page.get_descendants(&first)[0].id().clone();
+        page.get_block_mut(&first).unwrap().set_property("updated-at", "1705312800000");
+        page.get_block_mut(&nested).unwrap().set_property("updated-at", "2024-02-01T08:00:00Z");
+
+        let summary = PageSummary::from(&page);
+        assert_eq!(summary.block_count, 5);
+        assert_eq!(summary.preview.len(), 3);
+        assert_eq!(summary.preview[0], "First");
+        assert_eq!(summary.preview[1].chars().count(), PREVIEW_LENGTH + 1);
+        assert_eq!(summary.preview[2], "Third");
+        assert_eq!(summary.updated_at, Some("2024-02-01T08:00:00Z".parse().unwrap()));
+    }
+}
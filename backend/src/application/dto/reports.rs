@@ -0,0 +1,162 @@
+use crate::application::services::{
+    DuplicateTitleAction, DuplicateTitleResolution, GraphEvent, ImportSummary, SyncErrorRecord, SyncEvent,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How an import went
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummaryDto {
+    pub total_files: usize,
+    pub pages_imported: usize,
+    pub files_skipped: usize,
+    pub pages_queued: usize,
+    pub success_rate: f64,
+    pub duplicate_titles: Vec<DuplicateTitleDto>,
+    pub errors: Vec<FileErrorDto>,
+    pub duration_ms: u64,
+    pub cancelled: bool,
+}
+
+impl From<ImportSummary> for ImportSummaryDto {
+    fn from(summary: ImportSummary) -> Self {
+        ImportSummaryDto {
+            total_files: summary.total_files,
+            pages_imported: summary.pages_imported,
+            files_skipped: summary.files_skipped,
+            pages_queued: summary.pages_queued,
+            success_rate: summary.success_rate(),
+            duplicate_titles: summary.duplicate_titles.into_iter().map(Into::into).collect(),
+            errors: summary
+                .errors
+                .into_iter()
+                .map(|(file_path, error)| FileErrorDto {
+                    file_path,
+                    error,
+                    occurred_at: None,
+                })
+                .collect(),
+            duration_ms: summary.duration_ms,
+            cancelled: summary.cancelled,
+        }
+    }
+}
+
+/// A file whose page title another file's page already had
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateTitleDto {
+    pub file_path: PathBuf,
+    pub title: String,
+    pub kept_by: PathBuf,
+    /// `renamed`, `merged` or `rejected`
+    pub action: &'static str,
+    /// The title the file's page was given, when it was renamed
+    pub renamed_to: Option<String>,
+}
+
+impl From<DuplicateTitleResolution> for DuplicateTitleDto {
+    fn from(resolution: DuplicateTitleResolution) -> Self {
+        let (action, renamed_to) = match resolution.action {
+            DuplicateTitleAction::Renamed { title } => ("renamed", Some(title)),
+            DuplicateTitleAction::Merged => ("merged", None),
+            DuplicateTitleAction::Rejected => ("rejected", None),
+        };
+        DuplicateTitleDto {
+            file_path: resolution.file_path,
+            title: resolution.title,
+            kept_by: resolution.kept_by,
+            action,
+            renamed_to,
+        }
+    }
+}
+
+/// A file that failed to import or sync
+#[derive(Debug, Clone, Serialize)]
+pub struct FileErrorDto {
+    pub file_path: PathBuf,
+    pub error: String,
+    /// Seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurred_at: Option<u64>,
+}
+
+impl From<SyncErrorRecord> for FileErrorDto {
+    fn from(record: SyncErrorRecord) -> Self {
+        FileErrorDto {
+            file_path: record.file_path,
+            error: record.error,
+            occurred_at: Some(unix_seconds(record.occurred_at)),
+        }
+    }
+}
+
+/// A graph's sync event, as the API's `sync` server-sent events and the
+/// desktop app's events carry it
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncEventDto {
+    pub graph_id: String,
+    #[serde(flatten)]
+    pub event: SyncEventKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncEventKind {
+    SyncStarted,
+    FileCreated { file_path: PathBuf },
+    FileUpdated { file_path: PathBuf },
+    FileDeleted { file_path: PathBuf },
+    FileRenamed { from: PathBuf, to: PathBuf },
+    SyncCompleted {
+        files_created: usize,
+        files_updated: usize,
+        files_deleted: usize,
+        files_renamed: usize,
+    },
+    Error { file_path: PathBuf, error: String },
+    FileWritten { file_path: PathBuf },
+    Conflict { file_path: PathBuf },
+    Progress { processed: usize, total: usize },
+    DuplicateTitle(DuplicateTitleDto),
+}
+
+impl From<GraphEvent> for SyncEventDto {
+    fn from(event: GraphEvent) -> Self {
+        let kind = match event.event {
+            SyncEvent::SyncStarted => SyncEventKind::SyncStarted,
+            SyncEvent::FileCreated { file_path } => SyncEventKind::FileCreated { file_path },
+            SyncEvent::FileUpdated { file_path } => SyncEventKind::FileUpdated { file_path },
+            SyncEvent::FileDeleted { file_path } => SyncEventKind::FileDeleted { file_path },
+            SyncEvent::FileRenamed { from, to } => SyncEventKind::FileRenamed { from, to },
+            SyncEvent::SyncCompleted {
+                files_created,
+                files_updated,
+                files_deleted,
+                files_renamed,
+            } => SyncEventKind::SyncCompleted {
+                files_created,
+                files_updated,
+                files_deleted,
+                files_renamed,
+            },
+            SyncEvent::Error { file_path, error } => SyncEventKind::Error { file_path, error },
+            SyncEvent::FileWritten { file_path } => SyncEventKind::FileWritten { file_path },
+            SyncEvent::Conflict(conflict) => SyncEventKind::Conflict {
+                file_path: conflict.file_path,
+            },
+            SyncEvent::Progress { processed, total } => SyncEventKind::Progress { processed, total },
+            SyncEvent::DuplicateTitle(resolution) => SyncEventKind::DuplicateTitle(resolution.into()),
+        };
+        SyncEventDto {
+            graph_id: event.graph_id,
+            event: kind,
+        }
+    }
+}
+
+/// Whole seconds since the Unix epoch
+pub(crate) fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
@@ -1,7 +1,12 @@
-use crate::domain::value_objects::{BlockId, PageId, PageReference, Url};
+use crate::application::services::UrlMetadata;
+use crate::domain::value_objects::{BlockId, BlockPath, GraphId, PageId, PageReference, SimilarityScore, Url};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 
 /// Type of search to perform
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SearchType {
     /// Keyword-based traditional search
     Traditional,
@@ -10,7 +15,8 @@ pub enum SearchType {
 }
 
 /// Type of results to return
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ResultType {
     /// Return only pages
     PagesOnly,
@@ -23,7 +29,7 @@ pub enum ResultType {
 }
 
 /// Search request parameters
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchRequest {
     /// The search query text
     pub query: String,
@@ -33,6 +39,28 @@ pub struct SearchRequest {
     pub result_type: ResultType,
     /// Optional filter to limit results to specific pages
     pub page_filters: Option<Vec<PageId>>,
+    /// Minimum score (0.0-1.0) a result needs, whatever kind of search found it
+    pub score_threshold: Option<f32>,
+    /// Graph to search in; semantic search rejects requests for a graph
+    /// the configured embedding service doesn't index
+    pub graph_id: Option<GraphId>,
+    /// Also give URL results the page references of the block holding the URL
+    #[serde(default)]
+    pub same_block_refs: bool,
+    /// Attach a [`ScoreExplanation`] to every result
+    #[serde(default)]
+    pub explain: bool,
+    /// Only search journal pages dated within this range
+    #[serde(default)]
+    pub journal_dates: Option<RangeInclusive<NaiveDate>>,
+    /// Read a date expression like "last week" in the query as
+    /// `journal_dates`, searching for the remaining words
+    #[serde(default)]
+    pub parse_dates: bool,
+    /// Cut the content of longer block results down to this many
+    /// characters around the match
+    #[serde(default)]
+    pub excerpt_length: Option<usize>,
 }
 
 impl SearchRequest {
@@ -42,6 +70,13 @@ impl SearchRequest {
             search_type: SearchType::Traditional,
             result_type: ResultType::All,
             page_filters: None,
+            score_threshold: None,
+            graph_id: None,
+            same_block_refs: false,
+            explain: false,
+            journal_dates: None,
+            parse_dates: false,
+            excerpt_length: None,
         }
     }
 
@@ -59,27 +94,168 @@ impl SearchRequest {
         self.page_filters = Some(page_filters);
         self
     }
+
+    pub fn with_score_threshold(mut self, score_threshold: f32) -> Self {
+        self.score_threshold = Some(score_threshold);
+        self
+    }
+
+    pub fn with_graph(mut self, graph_id: GraphId) -> Self {
+        self.graph_id = Some(graph_id);
+        self
+    }
+
+    /// Include references like `[[programming]]` written next to a URL,
+    /// not just those above and below its block
+    pub fn with_same_block_refs(mut self) -> Self {
+        self.same_block_refs = true;
+        self
+    }
+
+    /// Explain how each result's score came about, for tuning ranking
+    pub fn with_explain(mut self) -> Self {
+        self.explain = true;
+        self
+    }
+
+    pub fn with_journal_dates(mut self, journal_dates: RangeInclusive<NaiveDate>) -> Self {
+        self.journal_dates = Some(journal_dates);
+        self
+    }
+
+    /// Turn "meeting notes from last week" into a search for "meeting notes"
+    /// in last week's journals
+    pub fn with_date_expressions(mut self) -> Self {
+        self.parse_dates = true;
+        self
+    }
+
+    /// Return `chars` characters of long blocks around where they match,
+    /// rather than pasted articles in full
+    pub fn with_excerpt_length(mut self, chars: usize) -> Self {
+        self.excerpt_length = Some(chars);
+        self
+    }
 }
 
 /// A search result with matched item and context
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchResult {
-    /// The matched item (page, block, or URL)
+    /// The matched item (page, block, URL or archived web page)
     pub item: SearchItem,
-    /// Relevance score (higher is more relevant)
-    pub score: f64,
+    /// Relevance from 0.0 to 1.0 (higher is more relevant), on the same
+    /// scale for keyword, semantic and hybrid matches
+    pub score: SimilarityScore,
+    /// How the score came about, if the request asked to explain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Breakdown of a result's score
+///
+/// The score is `base_score` multiplied by each adjustment's factor, capped
+/// at 1.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// What part of the item matched
+    pub field: MatchedField,
+    pub kind: MatchKind,
+    /// Score of the match itself: its keyword score, or the normalized
+    /// similarity of a semantic match
+    pub base_score: f32,
+    /// Weights and boosts the score was multiplied by, in order
+    pub adjustments: Vec<ScoreAdjustment>,
+}
+
+impl ScoreExplanation {
+    pub fn new(field: MatchedField, kind: MatchKind, base_score: f32) -> Self {
+        ScoreExplanation {
+            field,
+            kind,
+            base_score,
+            adjustments: Vec::new(),
+        }
+    }
+
+    /// The score this breakdown adds up to
+    pub fn score(&self) -> SimilarityScore {
+        let score = self.adjustments.iter().fold(self.base_score, |score, adjustment| score * adjustment.factor);
+        SimilarityScore::saturating(score)
+    }
+}
+
+/// Part of a search result the query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedField {
+    /// A page's title
+    Title,
+    /// A block's content
+    Content,
+    /// A page's embedding, of its title and leading blocks
+    Page,
+    Url,
+    /// An archived web page's title
+    ArchiveTitle,
+    /// An archived web page's text
+    ArchiveText,
+    /// A journal page's date, when only dates were asked for
+    JournalDate,
+}
+
+/// How the query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The text is the query
+    Exact,
+    /// The text starts with the query
+    Prefix,
+    /// The text contains the query
+    Contains,
+    /// The text holds every word of the query, but not the query as typed
+    AllTerms,
+    /// The embeddings are similar
+    Semantic,
+    /// Semantic and keyword (BM25) rankings, fused
+    Hybrid,
+}
+
+/// A weight or boost applied to a result's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreAdjustment {
+    pub kind: AdjustmentKind,
+    /// The page's recency or centrality (0.0-1.0) a boost is based on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<f32>,
+    /// What the score was multiplied by
+    pub factor: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentKind {
+    TitleWeight,
+    /// How recently the result's page was updated
+    Recency,
+    /// How many blocks of other pages reference the result's page
+    PageCentrality,
+    /// The block is repeated by a journal template
+    JournalTemplate,
 }
 
-/// The type of item that was matched in a search
-#[derive(Debug, Clone, PartialEq)]
+/// The type of item that was matched in a search, tagged by `type`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum SearchItem {
     Page(PageResult),
     Block(BlockResult),
     Url(UrlResult),
+    Archive(ArchiveResult),
 }
 
 /// A page search result
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PageResult {
     pub page_id: PageId,
     pub title: String,
@@ -92,7 +268,7 @@ pub struct PageResult {
 }
 
 /// A block search result with hierarchical context
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlockResult {
     pub block_id: BlockId,
     pub content: String,
@@ -100,14 +276,22 @@ pub struct BlockResult {
     pub page_title: String,
     /// Hierarchical path from root to this block (block contents)
     pub hierarchy_path: Vec<String>,
+    /// Where the block is, as `Page > parent > block`; unknown for semantic
+    /// matches, which don't load the page
+    #[serde(default)]
+    pub block_path: Option<BlockPath>,
     /// Page references in ancestor and descendant blocks
     pub related_pages: Vec<PageReference>,
     /// URLs in ancestor and descendant blocks
     pub related_urls: Vec<Url>,
+    /// Whether `content` is an excerpt of a longer block, marked with `…`
+    /// where it was cut; the block's ID fetches it whole
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// A URL search result with hierarchical context
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UrlResult {
     pub url: Url,
     pub containing_block_id: BlockId,
@@ -118,10 +302,116 @@ pub struct UrlResult {
     pub ancestor_page_refs: Vec<PageReference>,
     /// Page references in descendant blocks
     pub descendant_page_refs: Vec<PageReference>,
+    /// Page references in the block holding the URL; empty unless the
+    /// request asked for them
+    #[serde(default)]
+    pub same_block_page_refs: Vec<PageReference>,
+    /// The URL's title and description, if they were fetched
+    pub metadata: Option<UrlMetadata>,
+}
+
+/// An archived web page search result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveResult {
+    pub url: Url,
+    pub title: Option<String>,
+    /// The matching passage of the page's archived text
+    pub excerpt: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// What kind of item a [`SearchResultRow`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultKind {
+    Page,
+    Block,
+    Url,
+    Archive,
+}
+
+impl ResultKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResultKind::Page => "page",
+            ResultKind::Block => "block",
+            ResultKind::Url => "url",
+            ResultKind::Archive => "archive",
+        }
+    }
+}
+
+/// A search result flattened into one row, the same columns whatever was
+/// matched, for spreadsheets and scripts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResultRow {
+    pub kind: ResultKind,
+    /// The page matched or holding the match; archived web pages have none
+    pub page_id: Option<PageId>,
+    /// The page's title, or an archived web page's
+    pub page_title: Option<String>,
+    /// The block matched or holding the matched URL
+    pub block_id: Option<BlockId>,
+    /// The block's content, or an archived web page's excerpt
+    pub content: String,
+    pub score: f32,
+    /// Hierarchical path from root to the block (block contents)
+    pub hierarchy_path: Vec<String>,
+    /// The URL matched, the page's URLs, or those in the blocks above and
+    /// below the block
+    pub urls: Vec<Url>,
+}
+
+impl From<&SearchResult> for SearchResultRow {
+    fn from(result: &SearchResult) -> Self {
+        let score = result.score.value();
+        match &result.item {
+            SearchItem::Page(page) => SearchResultRow {
+                kind: ResultKind::Page,
+                page_id: Some(page.page_id.clone()),
+                page_title: Some(page.title.clone()),
+                block_id: None,
+                content: String::new(),
+                score,
+                hierarchy_path: Vec::new(),
+                urls: page.urls.clone(),
+            },
+            SearchItem::Block(block) => SearchResultRow {
+                kind: ResultKind::Block,
+                page_id: Some(block.page_id.clone()),
+                page_title: Some(block.page_title.clone()),
+                block_id: Some(block.block_id.clone()),
+                content: block.content.clone(),
+                score,
+                hierarchy_path: block.hierarchy_path.clone(),
+                urls: block.related_urls.clone(),
+            },
+            SearchItem::Url(url) => SearchResultRow {
+                kind: ResultKind::Url,
+                page_id: Some(url.page_id.clone()),
+                page_title: Some(url.page_title.clone()),
+                block_id: Some(url.containing_block_id.clone()),
+                content: url.containing_block_content.clone(),
+                score,
+                hierarchy_path: Vec::new(),
+                urls: vec![url.url.clone()],
+            },
+            SearchItem::Archive(archive) => SearchResultRow {
+                kind: ResultKind::Archive,
+                page_id: None,
+                page_title: archive.title.clone(),
+                block_id: None,
+                content: archive.excerpt.clone(),
+                score,
+                hierarchy_path: Vec::new(),
+                urls: vec![archive.url.clone()],
+            },
+        }
+    }
 }
 
 /// Result for URL-to-pages connection query
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PageConnection {
     pub page_id: PageId,
     pub page_title: String,
@@ -130,7 +420,7 @@ pub struct PageConnection {
 }
 
 /// Result for page-to-links query
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UrlWithContext {
     pub url: Url,
     pub block_id: BlockId,
@@ -139,4 +429,76 @@ pub struct UrlWithContext {
     pub hierarchy_path: Vec<String>,
     /// Page references related to this URL (from ancestors and descendants)
     pub related_page_refs: Vec<PageReference>,
+    /// Page references in the block holding the URL; empty unless asked for
+    #[serde(default)]
+    pub same_block_page_refs: Vec<PageReference>,
+    /// The URL's title and description, if they were fetched
+    pub metadata: Option<UrlMetadata>,
+}
+
+/// A block on another page that references a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backlink {
+    pub page_id: PageId,
+    pub page_title: String,
+    pub block_id: BlockId,
+    pub block_content: String,
+    /// Hierarchical path from root to the referencing block
+    pub hierarchy_path: Vec<String>,
+    /// Where the referencing block is, as `Page > parent > block`
+    #[serde(default)]
+    pub block_path: Option<BlockPath>,
+}
+
+/// A block and its surroundings, as context for answering a question
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextPassage {
+    pub page_id: PageId,
+    pub page_title: String,
+    pub block_id: BlockId,
+    /// Contents of the block's ancestors, from the root down
+    pub breadcrumbs: Vec<String>,
+    /// The block and its descendants as an indented outline
+    pub content: String,
+    /// Relevance score of the block's search match
+    pub score: SimilarityScore,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_result_serde_round_trip() {
+        let result = SearchResult {
+            item: SearchItem::Block(BlockResult {
+                block_id: BlockId::new("b1").unwrap(),
+                content: "Learning [[Rust]]".to_string(),
+                page_id: PageId::new("p1").unwrap(),
+                page_title: "Journal".to_string(),
+                hierarchy_path: vec!["Learning [[Rust]]".to_string()],
+                block_path: Some(BlockPath::parse("Journal > Learning [[Rust]]").unwrap()),
+                related_pages: vec![PageReference::from_brackets("Rust").unwrap()],
+                related_urls: vec![Url::new("https://rust-lang.org").unwrap()],
+                truncated: false,
+            }),
+            score: SimilarityScore::new(0.75).unwrap(),
+            explanation: None,
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["item"]["type"], "block");
+        assert_eq!(json["item"]["block_id"], "b1");
+        assert_eq!(json["item"]["related_urls"][0], "https://rust-lang.org");
+        assert_eq!(json["item"]["block_path"], "Journal > Learning [[Rust]]");
+        assert_eq!(json["score"], 0.75);
+        assert!(json.get("explanation").is_none());
+        assert_eq!(serde_json::from_value::<SearchResult>(json).unwrap(), result);
+
+        let request: SearchRequest =
+            serde_json::from_str(r#"{"query":"rust","search_type":"semantic","result_type":"blocks_only"}"#).unwrap();
+        assert_eq!(request.search_type, SearchType::Semantic);
+        assert_eq!(request.result_type, ResultType::BlocksOnly);
+        assert_eq!(request.page_filters, None);
+    }
 }
@@ -5,7 +5,7 @@ pub mod use_cases;
 
 // Re-export key types to avoid naming conflicts
 pub use dto::{
-    PageConnection, SearchItem, SearchRequest, SearchResult, SearchType, UrlWithContext,
+    Backlink, ContextPassage, PageConnection, SearchItem, SearchRequest, SearchResult, SearchType, UrlWithContext,
 };
 pub use repositories::PageRepository;
 pub use services::{
@@ -13,5 +13,6 @@ pub use services::{
     ProgressCallback, SyncCallback, SyncError, SyncEvent, SyncResult, SyncService,
 };
 pub use use_cases::{
-    BatchIndexPages, GetLinksForPage, GetPagesForUrl, IndexPage, SearchPagesAndBlocks,
+    BatchIndexPages, GetBacklinks, GetLinksForPage, GetPagesForUrl, GetRagContext, IndexPage,
+    SearchPagesAndBlocks,
 };
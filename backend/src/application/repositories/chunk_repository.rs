@@ -0,0 +1,43 @@
+use crate::application::dto::ChunkRecord;
+use crate::domain::{
+    value_objects::{BlockId, PageId},
+    DomainResult,
+};
+
+/// Repository trait for the local index of embedded chunks.
+///
+/// Mirrors what has been written to the vector database, so questions like
+/// "what's embedded?" or "does this block need re-embedding?" can be answered
+/// without querying it.
+pub trait ChunkRepository {
+    /// Inserts or replaces chunk records, keyed by chunk ID.
+    fn upsert(&mut self, chunks: &[ChunkRecord]) -> DomainResult<()>;
+
+    /// Returns all chunk records of a page.
+    fn find_by_page(&self, page_id: &PageId) -> DomainResult<Vec<ChunkRecord>>;
+
+    /// Returns every chunk record.
+    fn find_all(&self) -> DomainResult<Vec<ChunkRecord>>;
+
+    /// Replaces every chunk record with `chunks` at once, so readers see
+    /// either the old records or the new ones.
+    fn replace_all(&mut self, chunks: &[ChunkRecord]) -> DomainResult<()>;
+
+    /// Deletes chunk records by chunk ID.
+    ///
+    /// Returns the number of records deleted.
+    fn delete(&mut self, chunk_ids: &[String]) -> DomainResult<usize>;
+
+    /// Deletes all chunk records of a page.
+    ///
+    /// Returns the number of records deleted.
+    fn delete_by_page(&mut self, page_id: &PageId) -> DomainResult<usize>;
+
+    /// Deletes all chunk records of a block.
+    ///
+    /// Returns the number of records deleted.
+    fn delete_by_block(&mut self, block_id: &BlockId) -> DomainResult<usize>;
+
+    /// Returns the total number of chunk records.
+    fn count(&self) -> DomainResult<usize>;
+}
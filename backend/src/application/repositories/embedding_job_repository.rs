@@ -0,0 +1,46 @@
+use crate::application::dto::{EmbeddingJob, EmbeddingJobKind, EmbeddingQueueStats};
+use crate::domain::{value_objects::PageId, DomainResult};
+
+/// Repository trait for the durable queue of pending embedding work.
+///
+/// Jobs survive restarts: anything still pending (or interrupted while running)
+/// is picked up again by the next worker.
+pub trait EmbeddingJobRepository {
+    /// Queues a job for a page.
+    ///
+    /// If an identical job for the same page is already pending, its ID is
+    /// returned instead of creating a duplicate. A pending job of the other
+    /// kind for the page is superseded and dropped, so jobs never run in an
+    /// order that contradicts the latest change.
+    fn enqueue(&mut self, kind: EmbeddingJobKind, page_id: &PageId) -> DomainResult<i64>;
+
+    /// Marks the oldest pending job as running and returns it.
+    ///
+    /// Returns `Ok(None)` if no job is pending.
+    fn claim_next(&mut self) -> DomainResult<Option<EmbeddingJob>>;
+
+    /// Marks a running job as completed.
+    fn complete(&mut self, id: i64) -> DomainResult<()>;
+
+    /// Records a failed attempt.
+    ///
+    /// The job goes back to pending while it has attempts left, and is marked
+    /// failed once `max_attempts` is reached.
+    fn fail(&mut self, id: i64, error: &str, max_attempts: u32) -> DomainResult<()>;
+
+    /// Puts every failed job back in the queue with a fresh attempt count.
+    ///
+    /// Returns the number of jobs re-queued.
+    fn retry_failed(&mut self) -> DomainResult<usize>;
+
+    /// Returns jobs left running by an interrupted worker to pending.
+    ///
+    /// Returns the number of jobs recovered.
+    fn recover_running(&mut self) -> DomainResult<usize>;
+
+    /// Finds a job by its ID.
+    fn find_by_id(&self, id: i64) -> DomainResult<Option<EmbeddingJob>>;
+
+    /// Counts jobs per status.
+    fn stats(&self) -> DomainResult<EmbeddingQueueStats>;
+}
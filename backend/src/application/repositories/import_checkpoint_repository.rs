@@ -0,0 +1,26 @@
+use crate::domain::DomainResult;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Repository trait for import checkpoints.
+///
+/// Records which files of a graph an import has already saved, so an
+/// interrupted import can be resumed instead of started over. Checkpoints are
+/// kept per graph root, so one store can serve several graphs.
+pub trait ImportCheckpointRepository {
+    /// Records files as imported, each with the hash of the contents imported.
+    fn mark_imported(&mut self, graph_root: &Path, files: &[(PathBuf, String)]) -> DomainResult<()>;
+
+    /// Returns the content hash of every file imported from a graph, keyed by path.
+    fn imported_files(&self, graph_root: &Path) -> DomainResult<HashMap<PathBuf, String>>;
+
+    /// Forgets the checkpoints of the given files of a graph.
+    ///
+    /// Returns the number of checkpoints removed.
+    fn forget(&mut self, graph_root: &Path, files: &[PathBuf]) -> DomainResult<usize>;
+
+    /// Forgets every checkpoint of a graph.
+    ///
+    /// Returns the number of checkpoints removed.
+    fn clear(&mut self, graph_root: &Path) -> DomainResult<usize>;
+}
@@ -0,0 +1,19 @@
+use crate::application::services::LinkGraph;
+use crate::domain::value_objects::GraphId;
+use crate::domain::DomainResult;
+
+/// Repository trait for link graphs.
+///
+/// Keeps the [`LinkGraph`] built for a graph, so it can be queried without
+/// reading every page again. Graphs are kept per graph id, so one store can
+/// serve several graphs.
+pub trait LinkGraphRepository {
+    /// Replaces the stored link graph of a graph.
+    fn save(&mut self, graph_id: &GraphId, graph: &LinkGraph) -> DomainResult<()>;
+
+    /// Returns the stored link graph of a graph, if one was saved.
+    fn load(&self, graph_id: &GraphId) -> DomainResult<Option<LinkGraph>>;
+
+    /// Removes the stored link graph of a graph, returning whether there was one.
+    fn delete(&mut self, graph_id: &GraphId) -> DomainResult<bool>;
+}
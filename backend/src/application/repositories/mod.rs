@@ -1,3 +1,25 @@
+pub mod chunk_repository;
+pub mod embedding_job_repository;
+pub mod import_checkpoint_repository;
+pub mod link_graph_repository;
+pub mod page_history_repository;
 pub mod page_repository;
+pub mod page_timestamp_repository;
+pub mod url_check_repository;
+pub mod url_metadata_repository;
+pub mod web_archive_repository;
 
+pub use chunk_repository::ChunkRepository;
+pub use embedding_job_repository::EmbeddingJobRepository;
+pub use import_checkpoint_repository::ImportCheckpointRepository;
+pub use link_graph_repository::LinkGraphRepository;
+pub use page_history_repository::PageHistoryRepository;
 pub use page_repository::PageRepository;
+pub use page_timestamp_repository::PageTimestampRepository;
+pub use url_check_repository::UrlCheckRepository;
+pub use url_metadata_repository::UrlMetadataRepository;
+pub use web_archive_repository::WebArchiveRepository;
+
+/// Failures of the stores behind these traits, as they come wrapped in
+/// [`DomainError::Repository`](crate::domain::DomainError::Repository)
+pub use crate::domain::base::RepositoryError;
@@ -0,0 +1,19 @@
+use crate::application::dto::{PageSnapshot, PageVersion};
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+
+/// Repository trait for the version history of pages.
+///
+/// Keeps every distinct state a page was saved in, so earlier content can be
+/// looked at and recovered after it was overwritten or the page deleted.
+pub trait PageHistoryRepository {
+    /// Records a snapshot as the page's next version, unless its content hash
+    /// matches the latest version's; returns the latest version either way.
+    fn record(&mut self, snapshot: &PageSnapshot) -> DomainResult<PageVersion>;
+
+    /// Returns every version of a page, oldest first.
+    fn versions(&self, page_id: &PageId) -> DomainResult<Vec<PageVersion>>;
+
+    /// Returns one version of a page, if it was recorded.
+    fn version(&self, page_id: &PageId, version: u32) -> DomainResult<Option<PageVersion>>;
+}
@@ -1,4 +1,6 @@
+use crate::application::dto::PageSummary;
 use crate::domain::{aggregates::Page, value_objects::PageId, DomainResult};
+use std::path::Path;
 
 /// Repository trait for managing Page aggregates.
 ///
@@ -12,6 +14,14 @@ pub trait PageRepository {
     /// Otherwise, a new page should be created.
     fn save(&mut self, page: Page) -> DomainResult<()>;
 
+    /// Saves several pages at once.
+    ///
+    /// The default implementation saves them one by one; implementations
+    /// backed by a database should override it to use a single transaction.
+    fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+        pages.into_iter().try_for_each(|page| self.save(page))
+    }
+
     /// Finds a page by its unique identifier.
     ///
     /// Returns `Ok(Some(page))` if found, `Ok(None)` if not found,
@@ -27,9 +37,45 @@ pub trait PageRepository {
     /// Returns all pages in the repository.
     fn find_all(&self) -> DomainResult<Vec<Page>>;
 
+    /// Returns a summary of every page, for listing pages.
+    ///
+    /// The default implementation summarizes the pages `find_all` returns;
+    /// implementations that have to load pages to return them should
+    /// override it to read only what a summary needs.
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        Ok(self.find_all()?.iter().map(PageSummary::from).collect())
+    }
+
+    /// Finds the page parsed from the given markdown file.
+    ///
+    /// The default implementation scans all pages; implementations with an
+    /// index on file paths should override it.
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        Ok(self
+            .find_all()?
+            .into_iter()
+            .find(|page| page.file_path() == Some(file_path)))
+    }
+
     /// Deletes a page by its unique identifier.
     ///
     /// Returns `Ok(true)` if the page was deleted, `Ok(false)` if the page
     /// was not found, or an error if the operation fails.
     fn delete(&mut self, id: &PageId) -> DomainResult<bool>;
+
+    /// Deletes several pages at once.
+    ///
+    /// Returns the number of pages deleted; IDs of pages that don't exist
+    /// are skipped. The default implementation deletes them one by one;
+    /// implementations backed by a database should override it to use a
+    /// single transaction.
+    fn delete_all(&mut self, ids: &[PageId]) -> DomainResult<usize> {
+        let mut deleted = 0;
+        for id in ids {
+            if self.delete(id)? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
 }
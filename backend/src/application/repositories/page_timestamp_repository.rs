@@ -0,0 +1,38 @@
+use crate::application::dto::PageTimestamps;
+use crate::domain::{value_objects::PageId, DomainResult};
+use chrono::{DateTime, Utc};
+
+/// Repository trait for when pages were last indexed and last embedded.
+///
+/// Comparing the two tells which pages' embeddings are out of date, so only
+/// those need embedding again.
+pub trait PageTimestampRepository {
+    /// Records a page as saved at `at` with content hashing to `content_hash`.
+    ///
+    /// The indexed time only moves when the hash differs from the last one
+    /// recorded, so saving a page unchanged keeps its embeddings current.
+    /// Returns whether it moved.
+    fn mark_indexed(&mut self, page_id: &PageId, content_hash: &str, at: DateTime<Utc>) -> DomainResult<bool>;
+
+    /// Records a page as embedded at `at`.
+    fn mark_embedded(&mut self, page_id: &PageId, at: DateTime<Utc>) -> DomainResult<()>;
+
+    /// Returns a page's timestamps, if any were recorded.
+    fn find(&self, page_id: &PageId) -> DomainResult<Option<PageTimestamps>>;
+
+    /// Returns the timestamps of every page.
+    fn find_all(&self) -> DomainResult<Vec<PageTimestamps>>;
+
+    /// Returns the pages that changed since they were last embedded, or
+    /// were never embedded.
+    fn find_out_of_date(&self) -> DomainResult<Vec<PageTimestamps>> {
+        let mut pages = self.find_all()?;
+        pages.retain(PageTimestamps::embeddings_out_of_date);
+        Ok(pages)
+    }
+
+    /// Deletes the timestamps of pages.
+    ///
+    /// Returns the number of pages forgotten.
+    fn forget(&mut self, page_ids: &[PageId]) -> DomainResult<usize>;
+}
@@ -0,0 +1,18 @@
+use crate::application::services::UrlCheck;
+use crate::domain::DomainResult;
+
+/// Repository trait for URL checks.
+///
+/// Keeps the latest result of checking each URL, so the dead link checker
+/// can skip URLs checked recently and report broken links without
+/// requesting them again.
+pub trait UrlCheckRepository {
+    /// Records the result of checking a URL, replacing any earlier result.
+    fn save(&mut self, check: &UrlCheck) -> DomainResult<()>;
+
+    /// Returns the latest check of a URL, if it was ever checked.
+    fn get(&self, url: &str) -> DomainResult<Option<UrlCheck>>;
+
+    /// Returns the latest check of every URL checked, by URL.
+    fn all(&self) -> DomainResult<Vec<UrlCheck>>;
+}
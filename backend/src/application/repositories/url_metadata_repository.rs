@@ -0,0 +1,14 @@
+use crate::application::services::UrlMetadata;
+use crate::domain::DomainResult;
+
+/// Repository trait for URL metadata.
+///
+/// Keeps the title, description and icon fetched for each URL, so link
+/// lists can show them without fetching the pages again.
+pub trait UrlMetadataRepository {
+    /// Records the metadata fetched for a URL, replacing any fetched earlier.
+    fn save(&mut self, metadata: &UrlMetadata) -> DomainResult<()>;
+
+    /// Returns the metadata of a URL, if it was ever fetched.
+    fn get(&self, url: &str) -> DomainResult<Option<UrlMetadata>>;
+}
@@ -0,0 +1,17 @@
+use crate::application::services::WebArchive;
+use crate::domain::DomainResult;
+
+/// Repository trait for archived web pages.
+///
+/// Keeps a readable snapshot of each archived URL, so linked pages can be
+/// read and searched after they change or disappear.
+pub trait WebArchiveRepository {
+    /// Stores the snapshot of a URL, replacing any taken earlier.
+    fn save(&mut self, archive: &WebArchive) -> DomainResult<()>;
+
+    /// Returns the snapshot of a URL, if it was ever archived.
+    fn get(&self, url: &str) -> DomainResult<Option<WebArchive>>;
+
+    /// Returns the snapshot of every archived URL, by URL.
+    fn all(&self) -> DomainResult<Vec<WebArchive>>;
+}
@@ -0,0 +1,96 @@
+/// Policy for files that produce the same page title
+use crate::domain::aggregates::Page;
+use crate::domain::base::DomainResult;
+use crate::domain::entities::Block;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// What to do when a file's page would take a title another file's page already has
+///
+/// Titles come from file names, so `pages/Rust.md` and `journals/Rust.md`, or
+/// `a___b.md` and `a%2Fb.md`, both produce one title. Without a policy the
+/// page saved last replaces the other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateTitlePolicy {
+    /// Keep both pages, giving the later file's page a numbered title, e.g. `Rust (2)`
+    #[default]
+    Rename,
+    /// Append the later file's blocks to the page that has the title
+    Merge,
+    /// Leave the later file out and report it as failed
+    Error,
+}
+
+/// What a [`DuplicateTitlePolicy`] did with one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuplicateTitleAction {
+    Renamed { title: String },
+    Merged,
+    Rejected,
+}
+
+/// A file whose page title was already taken, and how that was resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateTitleResolution {
+    pub file_path: PathBuf,
+    /// The title both files produce
+    pub title: String,
+    /// The file whose page keeps the title
+    pub kept_by: PathBuf,
+    pub action: DuplicateTitleAction,
+}
+
+/// The first `title (n)`, counting from 2, that isn't taken
+pub(crate) fn numbered_title(title: &str, taken: impl Fn(&str) -> bool) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|candidate| !taken(candidate))
+        .expect("some numbered title is free")
+}
+
+/// Append `source`'s blocks, in outline order, after `target`'s own
+pub(crate) fn merge_blocks(target: &mut Page, source: &Page) -> DomainResult<()> {
+    fn copy(target: &mut Page, source: &Page, block: &Block) -> DomainResult<()> {
+        target.add_block(block.clone())?;
+        for child_id in block.child_ids() {
+            if let Some(child) = source.get_block(child_id) {
+                copy(target, source, child)?;
+            }
+        }
+        Ok(())
+    }
+
+    for block in source.root_blocks() {
+        copy(target, source, block)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::{LogseqMarkdownParser, LogseqMarkdownWriter};
+
+    #[test]
+    fn test_numbered_title_skips_taken_titles() {
+        let taken = ["Rust (2)", "Rust (3)"];
+        assert_eq!(numbered_title("Rust", |title| taken.contains(&title)), "Rust (4)");
+        assert_eq!(numbered_title("Go", |title| taken.contains(&title)), "Go (2)");
+    }
+
+    #[test]
+    fn test_merge_blocks_appends_outline() {
+        let parse = |content| {
+            LogseqMarkdownParser::parse_content(content, PageId::new("p").unwrap(), "P".into()).unwrap()
+        };
+        let mut target = parse("- One\n\t- Nested");
+        merge_blocks(&mut target, &parse("- Two\n\t- Child\n- Three")).unwrap();
+
+        assert_eq!(
+            LogseqMarkdownWriter::render(&target),
+            "- One\n\t- Nested\n- Two\n\t- Child\n- Three\n"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+/// Background worker that drains the durable embedding job queue
+use crate::application::dto::{EmbeddingJob, EmbeddingJobKind, EmbeddingQueueStats};
+use crate::application::repositories::{EmbeddingJobRepository, PageRepository};
+use crate::application::services::{EmbeddingError, EmbeddingResult, EmbeddingService, EmbeddingStats};
+use crate::domain::value_objects::PageId;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+#[derive(Error, Debug)]
+pub enum EmbeddingQueueError {
+    #[error("Repository error: {0}")]
+    Repository(#[from] crate::domain::base::DomainError),
+
+    #[error("Embedding error: {0}")]
+    Embedding(#[from] EmbeddingError),
+}
+
+pub type EmbeddingQueueResult<T> = Result<T, EmbeddingQueueError>;
+
+/// Configuration for the embedding queue worker
+#[derive(Debug, Clone)]
+pub struct EmbeddingQueueConfig {
+    /// Attempts per job before it is marked failed
+    pub max_attempts: u32,
+    /// How long an idle worker waits before checking for new jobs
+    pub poll_interval: Duration,
+}
+
+impl Default for EmbeddingQueueConfig {
+    fn default() -> Self {
+        EmbeddingQueueConfig {
+            max_attempts: 3,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Snapshot of indexing throughput and backlog
+#[derive(Debug, Clone)]
+pub struct EmbeddingMetrics {
+    /// Counts and timings accumulated by the embedding service
+    pub embedding: EmbeddingStats,
+    /// Jobs in each queue state
+    pub queue: EmbeddingQueueStats,
+}
+
+impl EmbeddingMetrics {
+    /// Jobs waiting or in flight
+    pub fn queue_depth(&self) -> usize {
+        self.queue.pending + self.queue.running
+    }
+}
+
+/// Queues embed/delete work and processes it off the caller's path
+///
+/// Callers enqueue jobs and return immediately; a background worker (see
+/// [`EmbeddingQueueService::spawn_worker`]) or an explicit [`flush`](Self::flush)
+/// performs the actual embedding.
+pub struct EmbeddingQueueService<R: PageRepository, Q: EmbeddingJobRepository> {
+    repository: Arc<Mutex<R>>,
+    queue: Arc<Mutex<Q>>,
+    embedding_service: Arc<EmbeddingService>,
+    config: EmbeddingQueueConfig,
+}
+
+impl<R, Q> EmbeddingQueueService<R, Q>
+where
+    R: PageRepository + Send + Sync + 'static,
+    Q: EmbeddingJobRepository + Send + 'static,
+{
+    /// Create a new queue service
+    ///
+    /// Jobs left running by a previous, interrupted worker are returned to the queue.
+    pub async fn new(
+        repository: Arc<Mutex<R>>,
+        queue: Q,
+        embedding_service: Arc<EmbeddingService>,
+        config: EmbeddingQueueConfig,
+    ) -> EmbeddingQueueResult<Self> {
+        let mut queue = queue;
+        let recovered = queue.recover_running()?;
+        if recovered > 0 {
+            tracing::info!("Recovered {} interrupted embedding jobs", recovered);
+        }
+
+        Ok(EmbeddingQueueService {
+            repository,
+            queue: Arc::new(Mutex::new(queue)),
+            embedding_service,
+            config,
+        })
+    }
+
+    /// Queue a page to be (re-)embedded
+    pub async fn enqueue_embed(&self, page_id: &PageId) -> EmbeddingQueueResult<i64> {
+        Ok(self
+            .queue
+            .lock()
+            .await
+            .enqueue(EmbeddingJobKind::Embed, page_id)?)
+    }
+
+    /// Queue a page's embeddings for deletion
+    pub async fn enqueue_delete(&self, page_id: &PageId) -> EmbeddingQueueResult<i64> {
+        Ok(self
+            .queue
+            .lock()
+            .await
+            .enqueue(EmbeddingJobKind::Delete, page_id)?)
+    }
+
+    /// The underlying job queue, for producers that enqueue jobs themselves
+    ///
+    /// See [`ImportService::with_embedding_queue`](crate::application::services::ImportService::with_embedding_queue).
+    pub fn jobs(&self) -> Arc<Mutex<Q>> {
+        Arc::clone(&self.queue)
+    }
+
+    /// Look up a single job
+    pub async fn job(&self, id: i64) -> EmbeddingQueueResult<Option<EmbeddingJob>> {
+        Ok(self.queue.lock().await.find_by_id(id)?)
+    }
+
+    /// Number of jobs in each state
+    pub async fn stats(&self) -> EmbeddingQueueResult<EmbeddingQueueStats> {
+        Ok(self.queue.lock().await.stats()?)
+    }
+
+    /// Embedding throughput and timings together with the current queue depth
+    pub async fn metrics(&self) -> EmbeddingQueueResult<EmbeddingMetrics> {
+        Ok(EmbeddingMetrics {
+            embedding: self.embedding_service.cumulative_stats(),
+            queue: self.stats().await?,
+        })
+    }
+
+    /// Put every failed job back in the queue
+    pub async fn retry_failed(&self) -> EmbeddingQueueResult<usize> {
+        Ok(self.queue.lock().await.retry_failed()?)
+    }
+
+    /// Claim and run the next pending job
+    ///
+    /// Returns `Ok(false)` if the queue was empty. A failing job is recorded
+    /// on the job (and retried later) rather than returned as an error.
+    pub async fn process_next(&self) -> EmbeddingQueueResult<bool> {
+        let Some(job) = self.queue.lock().await.claim_next()? else {
+            return Ok(false);
+        };
+
+        tracing::debug!("Running embedding job {} ({:?} {})", job.id, job.kind, job.page_id);
+
+        let outcome = self.run_job(&job).await;
+
+        let mut queue = self.queue.lock().await;
+        match outcome {
+            Ok(()) => queue.complete(job.id)?,
+            Err(e) => {
+                tracing::warn!(
+                    "Embedding job {} failed (attempt {}): {}",
+                    job.id,
+                    job.attempts,
+                    e
+                );
+                queue.fail(job.id, &e.to_string(), self.config.max_attempts)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Process jobs until nothing is pending
+    ///
+    /// Jobs that keep failing are retried up to `max_attempts` times, so this
+    /// always terminates. Returns the number of jobs run.
+    pub async fn flush(&self) -> EmbeddingQueueResult<usize> {
+        let mut processed = 0;
+        while self.process_next().await? {
+            processed += 1;
+        }
+        Ok(processed)
+    }
+
+    /// Start a background task that processes jobs as they arrive
+    pub fn spawn_worker(self: &Arc<Self>) -> EmbeddingWorkerHandle {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let service = Arc::clone(self);
+
+        let task = tokio::spawn(async move {
+            tracing::info!("Embedding queue worker started");
+            loop {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+
+                match service.process_next().await {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Embedding queue worker error: {}", e),
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(service.config.poll_interval) => {}
+                    _ = shutdown_rx.changed() => {}
+                }
+            }
+            tracing::info!("Embedding queue worker stopped");
+        });
+
+        EmbeddingWorkerHandle { shutdown_tx, task }
+    }
+
+    async fn run_job(&self, job: &EmbeddingJob) -> EmbeddingResult<()> {
+        match job.kind {
+            EmbeddingJobKind::Embed => {
+                // Read what's needed and release the repository, which the API
+                // and sync share, before the model and vector store are called
+                let repository = self.repository.lock().await;
+                let Some(page) = repository.find_by_id(&job.page_id)? else {
+                    // The page was removed after the job was queued; nothing to embed
+                    tracing::debug!("Skipping embed of missing page {}", job.page_id);
+                    return Ok(());
+                };
+                let templates = self.embedding_service.journal_templates(&*repository)?;
+                drop(repository);
+                self.embedding_service.embed_page_without(&page, templates.as_ref()).await?;
+            }
+            EmbeddingJobKind::Delete => {
+                self.embedding_service
+                    .delete_page_embeddings(&job.page_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handle to a running background embedding worker
+pub struct EmbeddingWorkerHandle {
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl EmbeddingWorkerHandle {
+    /// Stop the worker after its current job and wait for it to exit
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
@@ -1,104 +1,489 @@
 /// Service for managing semantic search embeddings
-use anyhow::{Context, Result};
-use std::sync::Arc;
-use tracing::{debug, info, warn};
+use anyhow::Context;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, instrument, warn};
 
-use crate::application::repositories::PageRepository;
+use crate::application::dto::{ChunkRecord, PageTimestamps};
+use crate::application::repositories::{ChunkRepository, PageRepository, PageTimestampRepository, RepositoryError};
+use crate::application::services::journal_templates::JournalTemplates;
+use crate::application::services::progress::{OperationKind, ProgressBus, ProgressOperation};
+use crate::application::services::search_cache::IndexGeneration;
+use crate::application::services::visibility::VisibilityPolicy;
 use crate::domain::aggregates::Page;
 use crate::domain::base::Entity;
-use crate::domain::value_objects::{BlockId, ChunkId, EmbeddingModel, PageId};
+use crate::domain::{DomainError, DomainResult};
+use crate::domain::value_objects::{
+    BlockId, ChunkId, EmbeddingModel, EmbeddingVector, GraphId, PageId, SimilarityScore,
+};
 use crate::infrastructure::embeddings::{
-    ChunkMetadata, FastEmbedService, QdrantVectorStore, TextPreprocessor,
+    mmr_rerank, ChunkMetadata, ChunkingStrategy, FastEmbedOptions, FastEmbedService,
+    ModelLoadCallback, ModelTokenizer, PageEmbeddingMetadata, PageSearchResult, PreprocessPipeline,
+    QdrantConnectionConfig, QdrantVectorStore, TextPreprocessor,
 };
+use crate::infrastructure::text::KeywordTokenizer;
+
+#[derive(Error, Debug)]
+pub enum EmbeddingError {
+    /// Loading the embedding model or generating embeddings failed
+    #[error("Embedding model error: {0}")]
+    Model(#[source] anyhow::Error),
+
+    /// The vector store is unreachable or rejected a request
+    #[error("Vector store error: {0}")]
+    VectorStore(#[source] anyhow::Error),
+
+    /// The service was configured without the named embeddings
+    #[error("{0} embeddings are disabled")]
+    Disabled(&'static str),
+
+    /// An invalid value, such as a score threshold, or a failing repository
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+}
+
+pub type EmbeddingResult<T> = Result<T, EmbeddingError>;
+
+/// How many candidates to fetch per requested result when MMR re-ranking is enabled
+const MMR_CANDIDATE_MULTIPLIER: usize = 4;
 
 /// Configuration for the embedding service
 #[derive(Debug, Clone)]
 pub struct EmbeddingServiceConfig {
     /// Embedding model to use
     pub model: EmbeddingModel,
-    /// Qdrant server URL
-    pub qdrant_url: String,
+    /// Execution provider, thread count and model cache directory for FastEmbed
+    pub fastembed: FastEmbedOptions,
+    /// Qdrant server URL, API key, TLS and timeouts
+    pub qdrant: QdrantConnectionConfig,
     /// Collection name in Qdrant
     pub collection_name: String,
-    /// Maximum words per chunk
+    /// Graph this service indexes. When set, the collection name is namespaced
+    /// per graph so chunks from different graphs don't collide.
+    pub graph_id: Option<GraphId>,
+    /// How block text is split into chunks
+    pub chunking: ChunkingStrategy,
+    /// Embed each block together with a summary line of its parent and direct
+    /// children, so terse outline bullets carry enough context to be found
+    pub contextual_chunks: bool,
+    /// Stages that clean up block text and add its context before it's
+    /// chunked and embedded
+    pub preprocessing: PreprocessPipeline,
+    /// Maximum number of children summarized when `contextual_chunks` is on
+    pub context_child_limit: usize,
+    /// Maximum words per chunk (used when `max_tokens_per_chunk` is `None`)
     pub max_words_per_chunk: usize,
-    /// Overlap words between chunks
+    /// Overlap words between chunks (used when `max_tokens_per_chunk` is `None`)
     pub overlap_words: usize,
+    /// Maximum model tokens per chunk, measured with the model's tokenizer
+    /// and including the special tokens it adds to each input.
+    /// `None` falls back to approximate word-count sizing.
+    pub max_tokens_per_chunk: Option<usize>,
+    /// Overlap tokens between chunks when sizing by tokens
+    pub overlap_tokens: usize,
     /// Batch size for embedding generation
     pub batch_size: usize,
+    /// Lambda for maximal-marginal-relevance re-ranking of search results
+    /// (1.0 = pure relevance, 0.0 = pure diversity). `None` disables MMR.
+    pub mmr_lambda: Option<f32>,
+    /// Minimum normalized similarity (0.0-1.0) a result needs to be returned.
+    /// `None` returns every result Qdrant yields.
+    pub score_threshold: Option<f32>,
+    /// Also store one page-level embedding (title + leading blocks) per page
+    /// in a separate `<collection>_pages` collection
+    pub page_embeddings: bool,
+    /// Number of leading root blocks included in a page-level embedding
+    pub page_embedding_blocks: usize,
+    /// Index sparse BM25 term vectors next to dense embeddings and let Qdrant
+    /// fuse both rankings server-side (RRF). Requires a collection created in
+    /// hybrid mode. Score thresholds then apply to the fused score, which
+    /// is 1.0 for the top result of both rankings.
+    pub hybrid_search: bool,
+    /// How hybrid collections split text into BM25 terms; changing it needs
+    /// a reindex, since stored sparse vectors keep the old terms
+    pub keyword_tokenizer: KeywordTokenizer,
+    /// Embed archived web pages (see `WebArchiver`) in a separate
+    /// `<collection>_archives` collection, so they're searchable semantically
+    pub archive_embeddings: bool,
+    /// Leave the blocks a journal template repeats on every journal page
+    /// (see [`JournalTemplates`]) out of the index
+    pub skip_journal_templates: bool,
+    /// Leave the pages and blocks the policy hides out of the index; a page
+    /// embedded before it was hidden has its embeddings deleted
+    pub visibility: Option<VisibilityPolicy>,
 }
 
 impl Default for EmbeddingServiceConfig {
     fn default() -> Self {
         EmbeddingServiceConfig {
             model: EmbeddingModel::default(),
-            qdrant_url: "http://localhost:6334".to_string(),
+            fastembed: FastEmbedOptions::default(),
+            qdrant: QdrantConnectionConfig::default(),
             collection_name: "logseq_blocks".to_string(),
+            graph_id: None,
+            chunking: ChunkingStrategy::default(),
+            contextual_chunks: false,
+            preprocessing: PreprocessPipeline::standard(),
+            context_child_limit: 5,
             max_words_per_chunk: 150, // ~512 tokens with margin
             overlap_words: 50,
+            // all-MiniLM-L6-v2 was trained on sequences of at most 256 tokens
+            max_tokens_per_chunk: Some(256),
+            overlap_tokens: 32,
             batch_size: 32,
+            mmr_lambda: None,
+            score_threshold: None,
+            page_embeddings: true,
+            page_embedding_blocks: 5,
+            hybrid_search: false,
+            keyword_tokenizer: KeywordTokenizer::default(),
+            archive_embeddings: false,
+            skip_journal_templates: false,
+            visibility: None,
         }
     }
 }
 
+impl EmbeddingServiceConfig {
+    /// Set the graph this service indexes
+    pub fn for_graph(mut self, graph_id: GraphId) -> Self {
+        self.graph_id = Some(graph_id);
+        self
+    }
+
+    /// Collection name actually used in Qdrant, namespaced by graph if one is set
+    pub fn effective_collection_name(&self) -> String {
+        match &self.graph_id {
+            Some(graph_id) => graph_id.collection_name(&self.collection_name),
+            None => self.collection_name.clone(),
+        }
+    }
+
+    /// Collection name used for page-level embeddings
+    pub fn page_collection_name(&self) -> String {
+        format!("{}_pages", self.effective_collection_name())
+    }
+
+    /// Collection name used for archived web pages
+    pub fn archive_collection_name(&self) -> String {
+        format!("{}_archives", self.effective_collection_name())
+    }
+}
+
 /// Service that orchestrates embedding generation and storage
 pub struct EmbeddingService {
     config: EmbeddingServiceConfig,
     embedding_service: Arc<FastEmbedService>,
     vector_store: Arc<QdrantVectorStore>,
+    page_store: Option<Arc<QdrantVectorStore>>,
+    archive_store: Option<Arc<QdrantVectorStore>>,
     text_preprocessor: Arc<TextPreprocessor>,
+    /// Loaded with the model on first use when chunks are sized in model tokens
+    tokenizer: OnceCell<Arc<ModelTokenizer>>,
+    /// Local index of embedded chunks, used to skip unchanged chunks on re-embed
+    chunk_repository: Option<Arc<Mutex<dyn ChunkRepository + Send>>>,
+    /// Where to record when each page was last embedded
+    page_timestamps: Option<Arc<Mutex<dyn PageTimestampRepository + Send>>>,
+    /// Stats accumulated over every page embedded by this service
+    totals: Mutex<EmbeddingStats>,
+    /// Checked between pages by [`embed_pages`](Self::embed_pages)
+    cancellation: CancellationToken,
+    /// When set, [`embed_pages`](Self::embed_pages) reports its progress here
+    progress: Option<ProgressBus>,
+    /// Bumped whenever embeddings are written or deleted; see
+    /// [`bump_on_write`](Self::bump_on_write)
+    generations: Mutex<Vec<IndexGeneration>>,
 }
 
 impl EmbeddingService {
     /// Create a new embedding service
-    pub async fn new(config: EmbeddingServiceConfig) -> Result<Self> {
+    pub async fn new(config: EmbeddingServiceConfig) -> EmbeddingResult<Self> {
         info!("Initializing EmbeddingService with config: {:?}", config);
 
-        let embedding_service = FastEmbedService::new(config.model)
+        let embedding_service = FastEmbedService::with_options(config.model, config.fastembed.clone())
             .await
-            .context("Failed to initialize FastEmbed service")?;
+            .context("Failed to initialize FastEmbed service")
+            .map_err(EmbeddingError::Model)?;
 
-        let vector_store = QdrantVectorStore::new(
-            &config.qdrant_url,
-            &config.collection_name,
+        let vector_store = QdrantVectorStore::with_config(
+            &config.qdrant,
+            config.effective_collection_name(),
             config.model.dimension_count(),
+            config.hybrid_search,
         )
         .await
-        .context("Failed to initialize Qdrant vector store")?;
+        .context("Failed to initialize Qdrant vector store")
+        .map_err(EmbeddingError::VectorStore)?
+        .with_keyword_tokenizer(config.keyword_tokenizer.clone());
 
+        let page_store = if config.page_embeddings {
+            let store = QdrantVectorStore::with_config(
+                &config.qdrant,
+                config.page_collection_name(),
+                config.model.dimension_count(),
+                false,
+            )
+            .await
+            .context("Failed to initialize Qdrant page store")
+            .map_err(EmbeddingError::VectorStore)?;
+            Some(Arc::new(store))
+        } else {
+            None
+        };
+
+        let archive_store = if config.archive_embeddings {
+            let store = QdrantVectorStore::with_config(
+                &config.qdrant,
+                config.archive_collection_name(),
+                config.model.dimension_count(),
+                false,
+            )
+            .await
+            .context("Failed to initialize Qdrant archive store")
+            .map_err(EmbeddingError::VectorStore)?;
+            Some(Arc::new(store))
+        } else {
+            None
+        };
+
+        let text_preprocessor = TextPreprocessor::with_pipeline(config.preprocessing.clone());
         Ok(EmbeddingService {
             config,
             embedding_service: Arc::new(embedding_service),
             vector_store: Arc::new(vector_store),
-            text_preprocessor: Arc::new(TextPreprocessor::new()),
+            page_store,
+            archive_store,
+            text_preprocessor: Arc::new(text_preprocessor),
+            tokenizer: OnceCell::new(),
+            chunk_repository: None,
+            page_timestamps: None,
+            totals: Mutex::new(EmbeddingStats::default()),
+            generations: Mutex::new(Vec::new()),
+            cancellation: CancellationToken::new(),
+            progress: None,
         })
     }
 
+    /// Record every embedded chunk in a local index
+    ///
+    /// With an index, re-embedding a page only embeds chunks whose content or
+    /// model changed, and removes chunks that no longer exist.
+    pub fn with_chunk_repository(
+        mut self,
+        repository: impl ChunkRepository + Send + 'static,
+    ) -> Self {
+        self.chunk_repository = Some(Arc::new(Mutex::new(repository)));
+        self
+    }
+
+    /// Record when each page is embedded, so pages saved since then can be
+    /// told apart (see [`TimestampedPageRepository`](super::TimestampedPageRepository))
+    pub fn with_page_timestamps(mut self, repository: impl PageTimestampRepository + Send + 'static) -> Self {
+        self.page_timestamps = Some(Arc::new(Mutex::new(repository)));
+        self
+    }
+
+    /// Stop [`embed_pages`](Self::embed_pages) early when `token` is cancelled
+    ///
+    /// The token is checked between pages: a page that is being embedded is
+    /// finished, and the stats returned cover the pages embedded until then,
+    /// flagged [`cancelled`](EmbeddingStats::cancelled).
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Publish the progress of [`embed_pages`](Self::embed_pages) runs on `bus`, counting pages
+    pub fn with_progress(mut self, bus: ProgressBus) -> Self {
+        self.progress = Some(bus);
+        self
+    }
+
+    /// Bump `generation` whenever this service writes or deletes embeddings,
+    /// so searches cached before aren't served again
+    ///
+    /// Takes `&self`, as a shared service only learns of a
+    /// [`SearchCache`](super::SearchCache) once it is already in use.
+    pub fn bump_on_write(&self, generation: IndexGeneration) {
+        self.generations.lock().unwrap_or_else(PoisonError::into_inner).push(generation);
+    }
+
     /// Create with default configuration
-    pub async fn new_default() -> Result<Self> {
+    pub async fn new_default() -> EmbeddingResult<Self> {
         Self::new(EmbeddingServiceConfig::default()).await
     }
 
+    /// Load the embedding model now instead of on first use
+    pub async fn warmup(&self) -> EmbeddingResult<()> {
+        self.embedding_service.warmup().await.map_err(EmbeddingError::Model)?;
+        self.ensure_tokenizer().await
+    }
+
+    /// Whether the embedding model has been loaded
+    pub fn is_model_loaded(&self) -> bool {
+        self.embedding_service.is_loaded()
+    }
+
+    /// The model chunks are embedded with
+    pub fn model(&self) -> EmbeddingModel {
+        self.config.model
+    }
+
+    /// Report embedding model download/load progress to `callback`
+    pub fn with_model_load_callback(self, callback: ModelLoadCallback) -> Self {
+        self.embedding_service.set_load_callback(callback);
+        self
+    }
+
+    /// Graph this service indexes, if it is namespaced per graph
+    pub fn graph_id(&self) -> Option<&GraphId> {
+        self.config.graph_id.as_ref()
+    }
+
     /// Embed a single page and store in vector database
+    ///
+    /// The repository's journals tell which blocks are a journal template's
+    /// when [`skip_journal_templates`](EmbeddingServiceConfig::skip_journal_templates) is on.
     pub async fn embed_page<R: PageRepository>(
         &self,
         page: &Page,
-        _repository: &R,
-    ) -> Result<EmbeddingStats> {
+        repository: &R,
+    ) -> EmbeddingResult<EmbeddingStats> {
+        let templates = self.journal_templates(repository)?;
+        self.embed_page_without(page, templates.as_ref()).await
+    }
+
+    /// Embed a page, leaving out the blocks of `templates`
+    #[instrument(skip_all, fields(page_id = %page.id()))]
+    pub(crate) async fn embed_page_without(
+        &self,
+        page: &Page,
+        templates: Option<&JournalTemplates>,
+    ) -> EmbeddingResult<EmbeddingStats> {
+        let visible;
+        let page = match &self.config.visibility {
+            Some(policy) => match policy.visible_page(page) {
+                Some(page) => {
+                    visible = page;
+                    &visible
+                }
+                None => {
+                    debug!("Not embedding hidden page {}", page.title());
+                    self.delete_page_embeddings(page.id()).await?;
+                    return Ok(EmbeddingStats::default());
+                }
+            },
+            None => page,
+        };
         info!("Embedding page: {} ({})", page.title(), page.id());
+        self.ensure_tokenizer().await?;
 
+        let started = Instant::now();
         let mut stats = EmbeddingStats::default();
         let page_title = page.title();
+
+        let all_chunk_data = self.prepare_chunks(page, templates);
+        stats.blocks_processed = all_chunk_data
+            .iter()
+            .filter(|c| c.chunk_index == 0)
+            .count();
+
+        stats.chunks_created = all_chunk_data.len();
+
+        let stored = self.with_chunk_index(|index| index.find_by_page(page.id()))?;
+        let all_chunk_data = match stored {
+            Some(stored) => {
+                let plan =
+                    plan_chunk_updates(all_chunk_data, &stored, &self.config.model.to_string());
+                stats.chunks_skipped = plan.unchanged;
+
+                if !plan.removed.is_empty() {
+                    debug!("Removing {} chunks no longer in page", plan.removed.len());
+                    self.vector_store
+                        .delete_points(plan.removed.clone())
+                        .await
+                        .context("Failed to delete removed chunks")
+                        .map_err(EmbeddingError::VectorStore)?;
+                    self.with_chunk_index(|index| index.delete(&plan.removed))?;
+                }
+
+                plan.to_embed
+            }
+            None => all_chunk_data,
+        };
+
+        // Generate embeddings in batches
+        let mut chunk_batch = Vec::new();
+        for chunk_metadata in all_chunk_data {
+            chunk_batch.push(chunk_metadata);
+
+            if chunk_batch.len() >= self.config.batch_size {
+                self.process_chunk_batch(&mut chunk_batch, &mut stats).await?;
+            }
+        }
+
+        // Process remaining chunks
+        if !chunk_batch.is_empty() {
+            self.process_chunk_batch(&mut chunk_batch, &mut stats).await?;
+        }
+
+        if let Some(ref page_store) = self.page_store {
+            self.embed_page_summary(page, page_store).await?;
+            stats.pages_embedded += 1;
+        }
+
+        if let Some(ref timestamps) = self.page_timestamps {
+            timestamps
+                .lock()
+                .map_err(|_| DomainError::from(RepositoryError::LockPoisoned("Page timestamps")))?
+                .mark_embedded(page.id(), chrono::Utc::now())?;
+        }
+
+        stats.total_time = started.elapsed();
+        self.record(|totals| totals.merge(&stats));
+
+        info!(
+            "Completed embedding page '{}': {} blocks, {} chunks, {} stored, {} unchanged in {:?} ({:.1} chunks/s)",
+            page_title,
+            stats.blocks_processed,
+            stats.chunks_created,
+            stats.chunks_stored,
+            stats.chunks_skipped,
+            stats.total_time,
+            stats.chunks_per_second()
+        );
+
+        self.written();
+        Ok(stats)
+    }
+
+    /// The journal template blocks of the repository's journals, if they're skipped
+    pub(crate) fn journal_templates<R: PageRepository>(&self, repository: &R) -> EmbeddingResult<Option<JournalTemplates>> {
+        if !self.config.skip_journal_templates {
+            return Ok(None);
+        }
+        let templates = JournalTemplates::detect(&repository.find_all()?);
+        debug!("Skipping {} journal template blocks", templates.len());
+        Ok(Some(templates))
+    }
+
+    /// Build chunk metadata (preprocessed, chunked text) for every non-empty
+    /// block of a page that isn't one of `templates`
+    fn prepare_chunks(&self, page: &Page, templates: Option<&JournalTemplates>) -> Vec<ChunkMetadata> {
+        let page_title = page.title();
         let page_id = page.id();
 
-        // Process each block in the page
         let mut all_chunk_data = Vec::new();
 
         for block in page.all_blocks() {
             let block_id = block.id();
             let content = block.content().as_str();
 
-            if content.trim().is_empty() {
+            if content.trim().is_empty() || templates.is_some_and(|templates| templates.contains(content)) {
                 continue;
             }
 
@@ -109,19 +494,36 @@ impl EmbeddingService {
                 .map(|b| b.content().as_str().to_string())
                 .collect::<Vec<_>>();
 
+            let content_with_context = if self.config.contextual_chunks {
+                let parent = block
+                    .parent_id()
+                    .and_then(|id| page.get_block(id))
+                    .map(|p| p.content().as_str());
+                let children: Vec<&str> = block
+                    .child_ids()
+                    .iter()
+                    .filter_map(|id| page.get_block(id))
+                    .map(|c| c.content().as_str())
+                    .collect();
+                self.text_preprocessor.with_outline_context(
+                    content,
+                    parent,
+                    &children,
+                    self.config.context_child_limit,
+                )
+            } else {
+                content.to_string()
+            };
+
             // Preprocess the content
             let preprocessed = self.text_preprocessor.preprocess(
-                content,
+                &content_with_context,
                 page_title,
                 &hierarchy_path,
             );
 
             // Chunk the text if needed
-            let chunks = self.text_preprocessor.chunk_text(
-                &preprocessed,
-                self.config.max_words_per_chunk,
-                self.config.overlap_words,
-            );
+            let chunks = self.chunk(&preprocessed, self.config.chunking, true);
 
             let total_chunks = chunks.len();
 
@@ -144,40 +546,97 @@ impl EmbeddingService {
                 all_chunk_data.push(chunk_metadata);
             }
 
-            stats.blocks_processed += 1;
         }
 
-        stats.chunks_created = all_chunk_data.len();
-
-        // Generate embeddings in batches
-        let mut chunk_batch = Vec::new();
-        for chunk_metadata in all_chunk_data {
-            chunk_batch.push(chunk_metadata);
+        all_chunk_data
+    }
 
-            if chunk_batch.len() >= self.config.batch_size {
-                self.process_chunk_batch(&mut chunk_batch, &mut stats).await?;
-            }
+    /// Load the tokenizer if chunks are sized in tokens, so `chunk` can use it
+    async fn ensure_tokenizer(&self) -> EmbeddingResult<()> {
+        if self.config.max_tokens_per_chunk.is_some() {
+            self.tokenizer
+                .get_or_try_init(|| async {
+                    self.embedding_service
+                        .tokenizer()
+                        .await
+                        .map(Arc::new)
+                        .map_err(EmbeddingError::Model)
+                })
+                .await?;
         }
+        Ok(())
+    }
 
-        // Process remaining chunks
-        if !chunk_batch.is_empty() {
-            self.process_chunk_batch(&mut chunk_batch, &mut stats).await?;
+    /// Split text into chunks, sized in tokens if a tokenizer is configured
+    fn chunk(&self, text: &str, strategy: ChunkingStrategy, overlap: bool) -> Vec<String> {
+        match (self.tokenizer.get(), self.config.max_tokens_per_chunk) {
+            (Some(tokenizer), Some(max_tokens)) => self.text_preprocessor.chunk_by_tokens(
+                text,
+                strategy,
+                tokenizer.as_ref(),
+                max_tokens,
+                if overlap { self.config.overlap_tokens } else { 0 },
+            ),
+            _ => self.text_preprocessor.chunk(
+                text,
+                strategy,
+                self.config.max_words_per_chunk,
+                if overlap { self.config.overlap_words } else { 0 },
+            ),
         }
+    }
 
-        info!(
-            "Completed embedding page '{}': {} blocks, {} chunks, {} stored",
-            page_title, stats.blocks_processed, stats.chunks_created, stats.chunks_stored
+    /// Generate and store the page-level embedding (title + leading root blocks)
+    async fn embed_page_summary(&self, page: &Page, page_store: &QdrantVectorStore) -> EmbeddingResult<()> {
+        let leading_blocks: Vec<&str> = page
+            .root_blocks()
+            .into_iter()
+            .map(|b| b.content().as_str())
+            .filter(|c| !c.trim().is_empty())
+            .take(self.config.page_embedding_blocks)
+            .collect();
+
+        let preprocessed = self.text_preprocessor.preprocess(
+            &leading_blocks.join(". "),
+            page.title(),
+            &[],
         );
 
-        Ok(stats)
+        // A page summary is a single vector, so keep only the first chunk's worth of text
+        let summary_text = self
+            .chunk(&preprocessed, ChunkingStrategy::Words, false)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        let embedding = self
+            .embedding_service
+            .embed_text(&summary_text)
+            .await
+            .context("Failed to generate page embedding")
+            .map_err(EmbeddingError::Model)?;
+
+        let metadata = PageEmbeddingMetadata {
+            page_id: page.id().as_str().to_string(),
+            page_title: page.title().to_string(),
+            preprocessed_content: summary_text,
+            block_count: page.all_blocks().count(),
+        };
+
+        page_store
+            .insert_page(&metadata, &embedding)
+            .await
+            .context("Failed to store page embedding")
+            .map_err(EmbeddingError::VectorStore)
     }
 
     /// Process a batch of chunks: generate embeddings and store
+    #[instrument(skip_all, fields(chunks = chunk_batch.len()))]
     async fn process_chunk_batch(
         &self,
         chunk_batch: &mut Vec<ChunkMetadata>,
         stats: &mut EmbeddingStats,
-    ) -> Result<()> {
+    ) -> EmbeddingResult<()> {
         if chunk_batch.is_empty() {
             return Ok(());
         }
@@ -191,133 +650,904 @@ impl EmbeddingService {
             .collect();
 
         // Generate embeddings
+        let embed_started = Instant::now();
         let embeddings = self
             .embedding_service
             .embed_batch(texts)
             .await
-            .context("Failed to generate embeddings")?;
+            .context("Failed to generate embeddings")
+            .map_err(EmbeddingError::Model)?;
+        let embed_time = embed_started.elapsed();
+
+        let records: Vec<ChunkRecord> = chunk_batch
+            .iter()
+            .map(|c| self.chunk_record(c))
+            .collect();
 
         // Pair chunks with embeddings
         let chunk_embedding_pairs: Vec<(ChunkMetadata, _)> = chunk_batch
             .drain(..)
-            .zip(embeddings.into_iter())
+            .zip(embeddings)
             .collect();
 
         // Store in vector database
+        let upsert_started = Instant::now();
         self.vector_store
             .insert_chunks_batch(chunk_embedding_pairs)
             .await
-            .context("Failed to store chunks in vector database")?;
+            .context("Failed to store chunks in vector database")
+            .map_err(EmbeddingError::VectorStore)?;
+        let upsert_time = upsert_started.elapsed();
 
-        stats.chunks_stored += chunk_batch.len();
+        debug!(
+            "Embedded batch in {:?}, stored in {:?}",
+            embed_time, upsert_time
+        );
+
+        stats.batches += 1;
+        stats.embedding_time += embed_time;
+        stats.upsert_time += upsert_time;
+        stats.chunks_stored += records.len();
+
+        self.with_chunk_index(|index| index.upsert(&records))?;
 
         Ok(())
     }
 
+    fn chunk_record(&self, chunk: &ChunkMetadata) -> ChunkRecord {
+        ChunkRecord {
+            chunk_id: chunk.chunk_id.clone(),
+            block_id: chunk.block_id.clone(),
+            page_id: chunk.page_id.clone(),
+            content_hash: chunk.content_hash(),
+            model: self.config.model.to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Stats accumulated over every page embedded since the service was created
+    pub fn cumulative_stats(&self) -> EmbeddingStats {
+        self.totals
+            .lock()
+            .map(|totals| totals.clone())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, update: impl FnOnce(&mut EmbeddingStats)) {
+        if let Ok(mut totals) = self.totals.lock() {
+            update(&mut totals);
+        }
+    }
+
+    /// Bump the generations following this service's writes
+    fn written(&self) {
+        for generation in self.generations.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            generation.bump();
+        }
+    }
+
+    /// Run `f` against the chunk index, if one is configured
+    fn with_chunk_index<T>(
+        &self,
+        f: impl FnOnce(&mut (dyn ChunkRepository + Send)) -> DomainResult<T>,
+    ) -> EmbeddingResult<Option<T>> {
+        let Some(ref index) = self.chunk_repository else {
+            return Ok(None);
+        };
+        let mut index = index
+            .lock()
+            .map_err(|_| DomainError::from(RepositoryError::LockPoisoned("Chunk index")))?;
+        Ok(Some(f(&mut *index)?))
+    }
+
+    /// Chunks recorded as embedded for a page, from the local chunk index
+    ///
+    /// Returns `None` if no chunk index is configured.
+    pub fn embedded_chunks(&self, page_id: &PageId) -> EmbeddingResult<Option<Vec<ChunkRecord>>> {
+        self.with_chunk_index(|index| index.find_by_page(page_id))
+    }
+
     /// Embed multiple pages in batch
+    #[instrument(skip_all, fields(pages = pages.len()))]
     pub async fn embed_pages<R: PageRepository>(
         &self,
         pages: Vec<&Page>,
         repository: &R,
-    ) -> Result<EmbeddingStats> {
+    ) -> EmbeddingResult<EmbeddingStats> {
         let page_count = pages.len();
         info!("Embedding {} pages", page_count);
 
+        let started = Instant::now();
         let mut total_stats = EmbeddingStats::default();
+        let progress = ProgressOperation::start(self.progress.as_ref(), OperationKind::Embedding, Some(page_count));
 
+        let templates = self.journal_templates(repository)?;
+        let mut done = 0;
         for page in pages {
-            match self.embed_page(page, repository).await {
-                Ok(stats) => {
-                    total_stats.blocks_processed += stats.blocks_processed;
-                    total_stats.chunks_created += stats.chunks_created;
-                    total_stats.chunks_stored += stats.chunks_stored;
-                }
+            if self.cancellation.is_cancelled() {
+                info!("Embedding cancelled after {} of {} pages", done, page_count);
+                total_stats.cancelled = true;
+                break;
+            }
+            match self.embed_page_without(page, templates.as_ref()).await {
+                Ok(stats) => total_stats.merge(&stats),
                 Err(e) => {
                     warn!("Failed to embed page '{}': {}", page.title(), e);
                     total_stats.errors += 1;
+                    self.record(|totals| totals.errors += 1);
                 }
             }
+            done += 1;
+            progress.advance(done);
         }
+        total_stats.total_time = started.elapsed();
+        progress.finish(done, total_stats.cancelled, total_stats.errors > 0);
 
         info!(
-            "Completed embedding {} pages: {} total chunks stored, {} errors",
+            "Completed embedding {} pages: {} total chunks stored, {} errors in {:?} ({:.1} chunks/s)",
             page_count,
             total_stats.chunks_stored,
-            total_stats.errors
+            total_stats.errors,
+            total_stats.total_time,
+            total_stats.chunks_per_second()
         );
 
         Ok(total_stats)
     }
 
     /// Search for similar content
-    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<crate::infrastructure::embeddings::SearchResult>> {
+    ///
+    /// Scores are normalized to 0.0-1.0 via `SimilarityScore`, and results below the
+    /// configured `score_threshold` are dropped.
+    pub async fn search(&self, query: &str, limit: usize) -> EmbeddingResult<Vec<crate::infrastructure::embeddings::SearchResult>> {
+        self.search_with_threshold(query, limit, None).await
+    }
+
+    /// Search for similar content with an explicit minimum score
+    ///
+    /// `score_threshold` overrides the configured threshold when set.
+    pub async fn search_with_threshold(
+        &self,
+        query: &str,
+        limit: usize,
+        score_threshold: Option<f32>,
+    ) -> EmbeddingResult<Vec<crate::infrastructure::embeddings::SearchResult>> {
         debug!("Searching for: '{}' (limit: {})", query, limit);
 
+        let threshold = score_threshold
+            .or(self.config.score_threshold)
+            .map(SimilarityScore::new)
+            .transpose()?;
+
         // Generate query embedding
         let query_embedding = self
             .embedding_service
             .embed_text(query)
             .await
-            .context("Failed to generate query embedding")?;
+            .context("Failed to generate query embedding")
+            .map_err(EmbeddingError::Model)?;
+
+        // Over-fetch so MMR has alternatives to pick from
+        let (fetch_limit, with_vectors) = match self.config.mmr_lambda {
+            Some(_) => (limit * MMR_CANDIDATE_MULTIPLIER, true),
+            None => (limit, false),
+        };
 
         // Search vector database
-        let results = self
-            .vector_store
-            .search(&query_embedding, limit as u64)
-            .await
-            .context("Vector search failed")?;
+        let candidates = if self.config.hybrid_search {
+            self.vector_store
+                .hybrid_search(&query_embedding, query, fetch_limit as u64, with_vectors)
+                .await
+        } else if with_vectors {
+            self.vector_store
+                .search_with_vectors(&query_embedding, fetch_limit as u64)
+                .await
+        } else {
+            self.vector_store
+                .search(&query_embedding, fetch_limit as u64)
+                .await
+        }
+        .context("Vector search failed")
+        .map_err(EmbeddingError::VectorStore)?;
+
+        let candidates =
+            Self::apply_score_threshold(candidates, threshold, self.config.hybrid_search);
+        let results = match self.config.mmr_lambda {
+            Some(lambda) => mmr_rerank(candidates, lambda, limit),
+            None => candidates,
+        };
 
         debug!("Found {} results", results.len());
 
         Ok(results)
     }
 
+    /// Search page-level embeddings ("find the page about X")
+    ///
+    /// Fails if page-level embeddings are disabled in the configuration.
+    pub async fn search_pages(
+        &self,
+        query: &str,
+        limit: usize,
+        score_threshold: Option<f32>,
+    ) -> EmbeddingResult<Vec<PageSearchResult>> {
+        debug!("Searching pages for: '{}' (limit: {})", query, limit);
+
+        let page_store = self
+            .page_store
+            .as_ref()
+            .ok_or(EmbeddingError::Disabled("Page-level"))?;
+
+        let threshold = score_threshold
+            .or(self.config.score_threshold)
+            .map(SimilarityScore::new)
+            .transpose()?;
+
+        let query_embedding = self
+            .embedding_service
+            .embed_text(query)
+            .await
+            .context("Failed to generate query embedding")
+            .map_err(EmbeddingError::Model)?;
+
+        let results = page_store
+            .search_pages(&query_embedding, limit as u64)
+            .await
+            .context("Page vector search failed")
+            .map_err(EmbeddingError::VectorStore)?
+            .into_iter()
+            .filter_map(|mut result| {
+                result.score = Self::normalize_score(result.score, threshold, false)?;
+                Some(result)
+            })
+            .collect::<Vec<_>>();
+
+        debug!("Found {} page results", results.len());
+
+        Ok(results)
+    }
+
+    /// Whether searches fuse dense and sparse (BM25) rankings
+    pub fn is_hybrid(&self) -> bool {
+        self.config.hybrid_search
+    }
+
+    /// Whether page-level embeddings are stored and searchable
+    pub fn has_page_embeddings(&self) -> bool {
+        self.page_store.is_some()
+    }
+
+    /// Every stored page-level embedding, by page, e.g. to compare pages
+    /// with each other
+    ///
+    /// Fails if page-level embeddings are disabled in the configuration.
+    pub async fn page_embeddings(&self) -> EmbeddingResult<HashMap<PageId, EmbeddingVector>> {
+        let page_store = self
+            .page_store
+            .as_ref()
+            .ok_or(EmbeddingError::Disabled("Page-level"))?;
+
+        let vectors = page_store
+            .list_page_vectors()
+            .await
+            .context("Failed to list page embeddings")
+            .map_err(EmbeddingError::VectorStore)?;
+        Ok(vectors
+            .into_iter()
+            .filter_map(|(page_id, vector)| Some((PageId::new(page_id).ok()?, vector)))
+            .collect())
+    }
+
+    /// Embed the readable text of an archived web page, replacing the
+    /// chunks embedded for it before
+    ///
+    /// Chunks are keyed by URL in place of page and block IDs. Fails if
+    /// archive embeddings are disabled in the configuration.
+    pub async fn embed_archive(&self, url: &str, title: Option<&str>, text: &str) -> EmbeddingResult<usize> {
+        let archive_store = self
+            .archive_store
+            .as_ref()
+            .ok_or(EmbeddingError::Disabled("Archive"))?;
+        self.ensure_tokenizer().await?;
+
+        let page_id = PageId::new(url)?;
+        archive_store
+            .delete_page_chunks(&page_id)
+            .await
+            .context("Failed to delete old archive chunks")
+            .map_err(EmbeddingError::VectorStore)?;
+
+        let title = title.unwrap_or(url);
+        let preprocessed = self.text_preprocessor.preprocess(text, title, &[]);
+        let chunks = self.chunk(&preprocessed, self.config.chunking, true);
+        let total_chunks = chunks.len();
+        let batch_size = self.config.batch_size.max(1);
+
+        for (batch_index, batch) in chunks.chunks(batch_size).enumerate() {
+            let embeddings = self
+                .embedding_service
+                .embed_batch(batch.iter().map(String::as_str).collect())
+                .await
+                .context("Failed to generate archive embeddings")
+                .map_err(EmbeddingError::Model)?;
+            let pairs = batch
+                .iter()
+                .enumerate()
+                .map(|(offset, chunk_text)| {
+                    let chunk_index = batch_index * batch_size + offset;
+                    ChunkMetadata {
+                        chunk_id: format!("{}-chunk-{}", url, chunk_index),
+                        block_id: url.to_string(),
+                        page_id: url.to_string(),
+                        page_title: title.to_string(),
+                        chunk_index,
+                        total_chunks,
+                        original_content: chunk_text.clone(),
+                        preprocessed_content: chunk_text.clone(),
+                        hierarchy_path: Vec::new(),
+                    }
+                })
+                .zip(embeddings)
+                .collect();
+            archive_store
+                .insert_chunks_batch(pairs)
+                .await
+                .context("Failed to store archive chunks")
+                .map_err(EmbeddingError::VectorStore)?;
+        }
+
+        debug!("Embedded archive of {} in {} chunks", url, total_chunks);
+        self.written();
+        Ok(total_chunks)
+    }
+
+    /// Search the chunks of archived web pages
+    ///
+    /// Each result's `page_id` is the archived URL. Fails if archive
+    /// embeddings are disabled in the configuration.
+    pub async fn search_archives(
+        &self,
+        query: &str,
+        limit: usize,
+        score_threshold: Option<f32>,
+    ) -> EmbeddingResult<Vec<crate::infrastructure::embeddings::SearchResult>> {
+        debug!("Searching archives for: '{}' (limit: {})", query, limit);
+
+        let archive_store = self
+            .archive_store
+            .as_ref()
+            .ok_or(EmbeddingError::Disabled("Archive"))?;
+
+        let threshold = score_threshold
+            .or(self.config.score_threshold)
+            .map(SimilarityScore::new)
+            .transpose()?;
+
+        let query_embedding = self
+            .embedding_service
+            .embed_text(query)
+            .await
+            .context("Failed to generate query embedding")
+            .map_err(EmbeddingError::Model)?;
+
+        let results = archive_store
+            .search(&query_embedding, limit as u64)
+            .await
+            .context("Archive vector search failed")
+            .map_err(EmbeddingError::VectorStore)?;
+        Ok(Self::apply_score_threshold(results, threshold, false))
+    }
+
+    /// Whether archived web pages are embedded and searchable
+    pub fn has_archive_embeddings(&self) -> bool {
+        self.archive_store.is_some()
+    }
+
+    /// Normalize a raw score, returning `None` if it falls below the threshold
+    ///
+    /// Cosine scores are mapped from [-1, 1] to [0, 1]; fused (RRF) scores
+    /// from hybrid search are already rescaled to [0, 1] and are only clamped.
+    fn normalize_score(raw: f32, threshold: Option<SimilarityScore>, fused: bool) -> Option<f32> {
+        let score = if fused {
+            SimilarityScore::new(raw.clamp(0.0, 1.0)).ok()?
+        } else {
+            SimilarityScore::from_cosine_similarity(raw).ok()?
+        };
+        if threshold.is_some_and(|t| score < t) {
+            return None;
+        }
+        Some(score.value())
+    }
+
+    /// Normalize raw scores and drop results below the threshold
+    fn apply_score_threshold(
+        results: Vec<crate::infrastructure::embeddings::SearchResult>,
+        threshold: Option<SimilarityScore>,
+        fused: bool,
+    ) -> Vec<crate::infrastructure::embeddings::SearchResult> {
+        results
+            .into_iter()
+            .filter_map(|mut result| {
+                result.score = Self::normalize_score(result.score, threshold, fused)?;
+                Some(result)
+            })
+            .collect()
+    }
+
+    /// Reconcile the vector store with the repository
+    ///
+    /// Scrolls every stored chunk and deletes those whose block no longer
+    /// produces them (orphaned) or whose content hash differs from what the
+    /// current page content would embed (stale). Page-level embeddings of
+    /// pages that no longer exist, or that the visibility policy hides, are
+    /// removed too.
+    pub async fn collect_garbage<R: PageRepository>(
+        &self,
+        repository: &R,
+    ) -> EmbeddingResult<GarbageCollectionReport> {
+        info!("Collecting stale embeddings");
+        self.ensure_tokenizer().await?;
+
+        let pages = repository.find_all()?;
+        let templates = self
+            .config
+            .skip_journal_templates
+            .then(|| JournalTemplates::detect(&pages));
+
+        let pages: Vec<Page> = match &self.config.visibility {
+            Some(policy) => pages.iter().filter_map(|page| policy.visible_page(page)).collect(),
+            None => pages,
+        };
+        let expected: HashMap<String, String> = pages
+            .iter()
+            .flat_map(|page| self.prepare_chunks(page, templates.as_ref()))
+            .map(|chunk| {
+                let hash = chunk.content_hash();
+                (chunk.chunk_id, hash)
+            })
+            .collect();
+
+        let stored = self
+            .vector_store
+            .list_chunk_hashes()
+            .await
+            .context("Failed to list stored chunks")
+            .map_err(EmbeddingError::VectorStore)?;
+
+        let mut report = GarbageCollectionReport {
+            chunks_scanned: stored.len(),
+            ..Default::default()
+        };
+
+        let (orphaned, stale) = find_stale_chunks(&expected, stored);
+        report.orphaned_chunks_removed = orphaned.len();
+        report.stale_chunks_removed = stale.len();
+
+        let removed: Vec<String> = orphaned.into_iter().chain(stale).collect();
+        self.vector_store
+            .delete_points(removed.clone())
+            .await
+            .context("Failed to delete stale chunks")
+            .map_err(EmbeddingError::VectorStore)?;
+        self.with_chunk_index(|index| index.delete(&removed))?;
+
+        if let Some(ref page_store) = self.page_store {
+            let existing: HashSet<&str> = pages.iter().map(|p| p.id().as_str()).collect();
+            let orphaned_pages: Vec<String> = page_store
+                .list_page_ids()
+                .await
+                .context("Failed to list stored pages")
+                .map_err(EmbeddingError::VectorStore)?
+                .into_iter()
+                .filter(|id| !existing.contains(id.as_str()))
+                .collect();
+
+            report.orphaned_pages_removed = orphaned_pages.len();
+            page_store
+                .delete_points(orphaned_pages)
+                .await
+                .context("Failed to delete orphaned page embeddings")
+                .map_err(EmbeddingError::VectorStore)?;
+        }
+
+        info!(
+            "Garbage collection removed {} orphaned and {} stale chunks, {} pages ({} chunks scanned)",
+            report.orphaned_chunks_removed,
+            report.stale_chunks_removed,
+            report.orphaned_pages_removed,
+            report.chunks_scanned
+        );
+
+        self.written();
+        Ok(report)
+    }
+
+    /// Embed `pages` from scratch into new collections and the new chunk
+    /// index `shadow_index`, then swap them in for the ones searched
+    ///
+    /// Searches keep using the current index while the new one is built.
+    /// The swap points the collection names at the new collections (see
+    /// [`QdrantVectorStore::swap_into`]) and replaces the chunk index's
+    /// records with `shadow_index`'s at once. Pages changed while the new
+    /// index was built, by the embedding worker or another process, are then
+    /// replayed into it (see [`replay_since`](Self::replay_since)). A rebuild
+    /// that is cancelled or fails to embed a page discards the new
+    /// collections instead, leaving the index as it was. Archive embeddings
+    /// aren't rebuilt.
+    pub async fn rebuild<R: PageRepository>(
+        &self,
+        pages: Vec<&Page>,
+        repository: &R,
+        shadow_index: impl ChunkRepository + Send + 'static,
+    ) -> EmbeddingResult<RebuildReport> {
+        let started = chrono::Utc::now();
+        let recorded = self.recorded_pages()?;
+        let vector_store = self
+            .vector_store
+            .shadow()
+            .await
+            .context("Failed to create shadow collection")
+            .map_err(EmbeddingError::VectorStore)?;
+        let page_store = match &self.page_store {
+            Some(page_store) => Some(Arc::new(
+                page_store
+                    .shadow()
+                    .await
+                    .context("Failed to create shadow page collection")
+                    .map_err(EmbeddingError::VectorStore)?,
+            )),
+            None => None,
+        };
+        info!("Rebuilding the index in collection '{}'", vector_store.collection_name());
+
+        let shadow = EmbeddingService {
+            config: self.config.clone(),
+            embedding_service: self.embedding_service.clone(),
+            vector_store: Arc::new(vector_store),
+            page_store,
+            archive_store: None,
+            text_preprocessor: self.text_preprocessor.clone(),
+            tokenizer: self.tokenizer.clone(),
+            chunk_repository: Some(Arc::new(Mutex::new(shadow_index))),
+            // Recorded once the new index is swapped in
+            page_timestamps: None,
+            totals: Mutex::new(EmbeddingStats::default()),
+            generations: Mutex::new(Vec::new()),
+            cancellation: self.cancellation.clone(),
+            progress: self.progress.clone(),
+        };
+        let page_ids: Vec<PageId> = pages.iter().map(|page| page.id().clone()).collect();
+        let embedding = shadow.embed_pages(pages, repository).await;
+        let swappable = embedding.as_ref().is_ok_and(|stats| !stats.cancelled && stats.errors == 0);
+
+        // New collections, each with the one it replaces
+        let mut swaps = vec![(&shadow.vector_store, &self.vector_store)];
+        if let (Some(new), Some(live)) = (&shadow.page_store, &self.page_store) {
+            swaps.push((new, live));
+        }
+        if !swappable {
+            for (new, _) in swaps {
+                warn!("Rebuild incomplete, discarding collection '{}'", new.collection_name());
+                if let Err(e) = new.delete_collection().await {
+                    warn!("Failed to delete shadow collection '{}': {}", new.collection_name(), e);
+                }
+            }
+            return Ok(RebuildReport {
+                embedding: embedding?,
+                swapped: false,
+                replayed: 0,
+            });
+        }
+
+        for (new, live) in swaps {
+            new.swap_into(live.collection_name())
+                .await
+                .map_err(EmbeddingError::VectorStore)?;
+        }
+        let chunks = shadow.with_chunk_index(|index| index.find_all())?.unwrap_or_default();
+        self.with_chunk_index(|index| index.replace_all(&chunks))?;
+        self.written();
+        let replayed = self.replay_since(started, &recorded, &page_ids, repository).await?;
+
+        let embedding = embedding?;
+        self.record(|totals| totals.merge(&embedding));
+        Ok(RebuildReport {
+            embedding,
+            swapped: true,
+            replayed,
+        })
+    }
+
+    /// Bring an index swapped in by [`rebuild`](Self::rebuild), which holds
+    /// `page_ids` as they were at `started`, up to date with the pages
+    /// changed since, and record the others as embedded then
+    ///
+    /// Pages indexed or embedded since are embedded again from `repository`;
+    /// pages among `recorded` whose timestamps were forgotten since, because
+    /// they were deleted, lose their embeddings. Changes are told by the page
+    /// timestamps, so without them none are replayed. Returns how many pages
+    /// were replayed; one that fails is logged and stays out of date.
+    async fn replay_since<R: PageRepository>(
+        &self,
+        started: chrono::DateTime<chrono::Utc>,
+        recorded: &[PageTimestamps],
+        page_ids: &[PageId],
+        repository: &R,
+    ) -> EmbeddingResult<usize> {
+        let Some(ref timestamps) = self.page_timestamps else {
+            return Ok(0);
+        };
+        let current = self.recorded_pages()?;
+        let since = |at: Option<chrono::DateTime<chrono::Utc>>| at.is_some_and(|at| at > started);
+        let remaining: HashSet<&PageId> = current.iter().map(|page| &page.page_id).collect();
+        let changed: HashSet<&PageId> = current
+            .iter()
+            .filter(|page| since(page.indexed_at) || since(page.embedded_at))
+            .map(|page| &page.page_id)
+            .chain(recorded.iter().map(|page| &page.page_id).filter(|id| !remaining.contains(id)))
+            .collect();
+
+        {
+            let mut timestamps = timestamps
+                .lock()
+                .map_err(|_| DomainError::from(RepositoryError::LockPoisoned("Page timestamps")))?;
+            for page_id in page_ids.iter().filter(|id| !changed.contains(id)) {
+                timestamps.mark_embedded(page_id, started)?;
+            }
+        }
+
+        for page_id in &changed {
+            let replay = match repository.find_by_id(page_id)? {
+                Some(page) => self.embed_page(&page, repository).await.map(|_| ()),
+                None => self.delete_page_embeddings(page_id).await,
+            };
+            if let Err(e) = replay {
+                warn!("Failed to replay page {} into the rebuilt index: {}", page_id, e);
+            }
+        }
+        if !changed.is_empty() {
+            info!("Replayed {} pages changed during the rebuild", changed.len());
+        }
+        Ok(changed.len())
+    }
+
+    /// Timestamps of every page, or none without a timestamp store
+    fn recorded_pages(&self) -> EmbeddingResult<Vec<PageTimestamps>> {
+        match &self.page_timestamps {
+            Some(timestamps) => Ok(timestamps
+                .lock()
+                .map_err(|_| DomainError::from(RepositoryError::LockPoisoned("Page timestamps")))?
+                .find_all()?),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Delete embeddings for a specific page
-    pub async fn delete_page_embeddings(&self, page_id: &PageId) -> Result<()> {
+    pub async fn delete_page_embeddings(&self, page_id: &PageId) -> EmbeddingResult<()> {
         info!("Deleting embeddings for page: {}", page_id);
 
         self.vector_store
             .delete_page_chunks(page_id)
             .await
-            .context("Failed to delete page embeddings")?;
+            .context("Failed to delete page embeddings")
+            .map_err(EmbeddingError::VectorStore)?;
+        self.with_chunk_index(|index| index.delete_by_page(page_id))?;
 
+        if let Some(ref page_store) = self.page_store {
+            page_store
+                .delete_page(page_id)
+                .await
+                .context("Failed to delete page-level embedding")
+                .map_err(EmbeddingError::VectorStore)?;
+        }
+
+        self.written();
         Ok(())
     }
 
     /// Delete embeddings for a specific block
-    pub async fn delete_block_embeddings(&self, block_id: &BlockId) -> Result<()> {
+    pub async fn delete_block_embeddings(&self, block_id: &BlockId) -> EmbeddingResult<()> {
         info!("Deleting embeddings for block: {}", block_id);
 
         self.vector_store
             .delete_block_chunks(block_id)
             .await
-            .context("Failed to delete block embeddings")?;
+            .context("Failed to delete block embeddings")
+            .map_err(EmbeddingError::VectorStore)?;
+        self.with_chunk_index(|index| index.delete_by_block(block_id))?;
 
+        self.written();
         Ok(())
     }
 
+    /// Check that Qdrant is reachable, returning its version
+    pub async fn health_check(&self) -> EmbeddingResult<String> {
+        self.vector_store.health_check().await.map_err(EmbeddingError::VectorStore)
+    }
+
+    /// Check that the chunk (and page) collections still match the configured
+    /// model and search mode, returning any differences found
+    pub async fn validate_collections(&self) -> EmbeddingResult<Vec<String>> {
+        let mut mismatches = self
+            .vector_store
+            .validate_collection()
+            .await
+            .map_err(EmbeddingError::VectorStore)?;
+        if let Some(ref page_store) = self.page_store {
+            mismatches.extend(
+                page_store
+                    .validate_collection()
+                    .await
+                    .map_err(EmbeddingError::VectorStore)?
+                    .into_iter()
+                    .map(|m| format!("page collection: {}", m)),
+            );
+        }
+        Ok(mismatches)
+    }
+
     /// Get statistics about the vector store
-    pub async fn get_stats(&self) -> Result<crate::infrastructure::embeddings::CollectionInfo> {
+    pub async fn get_stats(&self) -> EmbeddingResult<crate::infrastructure::embeddings::CollectionInfo> {
         self.vector_store
             .get_collection_info()
             .await
             .context("Failed to get vector store stats")
+            .map_err(EmbeddingError::VectorStore)
+    }
+}
+
+/// Split stored chunks into orphaned (no longer produced by any block) and
+/// stale (content changed, or stored without a hash) chunk IDs
+fn find_stale_chunks(
+    expected: &HashMap<String, String>,
+    stored: Vec<(String, Option<String>)>,
+) -> (Vec<String>, Vec<String>) {
+    let mut orphaned = Vec::new();
+    let mut stale = Vec::new();
+
+    for (chunk_id, hash) in stored {
+        match expected.get(&chunk_id) {
+            None => orphaned.push(chunk_id),
+            Some(expected_hash) if hash.as_ref() != Some(expected_hash) => stale.push(chunk_id),
+            Some(_) => {}
+        }
+    }
+
+    (orphaned, stale)
+}
+
+/// How a page's freshly prepared chunks compare to the chunk index
+struct ChunkPlan {
+    /// New chunks, or chunks whose content or model changed
+    to_embed: Vec<ChunkMetadata>,
+    /// Chunks already embedded with the same content and model
+    unchanged: usize,
+    /// Indexed chunk IDs the page no longer produces
+    removed: Vec<String>,
+}
+
+fn plan_chunk_updates(chunks: Vec<ChunkMetadata>, stored: &[ChunkRecord], model: &str) -> ChunkPlan {
+    let stored_by_id: HashMap<&str, &ChunkRecord> =
+        stored.iter().map(|r| (r.chunk_id.as_str(), r)).collect();
+    let current: HashSet<&str> = chunks.iter().map(|c| c.chunk_id.as_str()).collect();
+
+    let removed = stored
+        .iter()
+        .filter(|r| !current.contains(r.chunk_id.as_str()))
+        .map(|r| r.chunk_id.clone())
+        .collect();
+
+    let mut unchanged = 0;
+    let mut to_embed = Vec::new();
+    for chunk in chunks {
+        let up_to_date = stored_by_id
+            .get(chunk.chunk_id.as_str())
+            .is_some_and(|r| r.model == model && r.content_hash == chunk.content_hash());
+        if up_to_date {
+            unchanged += 1;
+        } else {
+            to_embed.push(chunk);
+        }
+    }
+
+    ChunkPlan {
+        to_embed,
+        unchanged,
+        removed,
     }
 }
 
+/// Outcome of a vector store garbage collection run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageCollectionReport {
+    pub chunks_scanned: usize,
+    pub orphaned_chunks_removed: usize,
+    pub stale_chunks_removed: usize,
+    pub orphaned_pages_removed: usize,
+}
+
+/// Outcome of an [`EmbeddingService::rebuild`]
+#[derive(Debug, Default, Clone)]
+pub struct RebuildReport {
+    pub embedding: EmbeddingStats,
+    /// The new index replaced the searched one, which it doesn't when the
+    /// rebuild is cancelled or a page fails to embed
+    pub swapped: bool,
+    /// Pages changed while the new index was built, embedded again or
+    /// deleted from it after the swap
+    pub replayed: usize,
+}
+
 /// Statistics from embedding operations
 #[derive(Debug, Default, Clone)]
 pub struct EmbeddingStats {
     pub blocks_processed: usize,
     pub chunks_created: usize,
     pub chunks_stored: usize,
+    /// Chunks left alone because the chunk index shows them unchanged
+    pub chunks_skipped: usize,
+    pub pages_embedded: usize,
     pub errors: usize,
+    /// Number of embed-and-store batches run
+    pub batches: usize,
+    /// Time spent generating embeddings
+    pub embedding_time: Duration,
+    /// Time spent writing vectors to Qdrant
+    pub upsert_time: Duration,
+    /// Wall-clock time of the whole operation
+    pub total_time: Duration,
+    /// The run stopped early (see [`EmbeddingService::with_cancellation`])
+    pub cancelled: bool,
+}
+
+impl EmbeddingStats {
+    /// Add another run's counts and timings to these
+    pub fn merge(&mut self, other: &EmbeddingStats) {
+        self.blocks_processed += other.blocks_processed;
+        self.chunks_created += other.chunks_created;
+        self.chunks_stored += other.chunks_stored;
+        self.chunks_skipped += other.chunks_skipped;
+        self.pages_embedded += other.pages_embedded;
+        self.errors += other.errors;
+        self.batches += other.batches;
+        self.embedding_time += other.embedding_time;
+        self.upsert_time += other.upsert_time;
+        self.total_time += other.total_time;
+        self.cancelled |= other.cancelled;
+    }
+
+    /// Chunks stored per second of wall-clock time
+    pub fn chunks_per_second(&self) -> f64 {
+        let seconds = self.total_time.as_secs_f64();
+        if seconds > 0.0 {
+            self.chunks_stored as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// Average time to embed one batch, in milliseconds
+    pub fn embedding_ms_per_batch(&self) -> f64 {
+        average_ms(self.embedding_time, self.batches)
+    }
+
+    /// Average time to store one batch, in milliseconds
+    pub fn upsert_ms_per_batch(&self) -> f64 {
+        average_ms(self.upsert_time, self.batches)
+    }
+}
+
+fn average_ms(total: Duration, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total.as_secs_f64() * 1000.0 / count as f64
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::value_objects::{BlockContent, BlockId, PageId};
 
     #[tokio::test]
     #[ignore] // Requires running Qdrant instance
@@ -346,4 +1576,146 @@ mod tests {
         assert!(results.is_ok());
         assert_eq!(results.unwrap().len(), 0);
     }
+
+    #[test]
+    fn test_effective_collection_name() {
+        let config = EmbeddingServiceConfig::default();
+        assert_eq!(config.effective_collection_name(), "logseq_blocks");
+
+        let config = config.for_graph(GraphId::new("work").unwrap());
+        assert_eq!(config.effective_collection_name(), "logseq_blocks__work");
+        assert_eq!(config.page_collection_name(), "logseq_blocks__work_pages");
+    }
+
+    fn vector_result(chunk_id: &str, score: f32) -> crate::infrastructure::embeddings::SearchResult {
+        crate::infrastructure::embeddings::SearchResult {
+            chunk_id: chunk_id.to_string(),
+            block_id: "block".to_string(),
+            page_id: "page".to_string(),
+            page_title: "Page".to_string(),
+            original_content: String::new(),
+            preprocessed_content: String::new(),
+            hierarchy_path: vec![],
+            score,
+            vector: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_score_threshold() {
+        let results = vec![
+            vector_result("strong", 0.8),
+            vector_result("weak", -0.2),
+        ];
+        let threshold = SimilarityScore::new(0.5).ok();
+
+        let filtered = EmbeddingService::apply_score_threshold(results, threshold, false);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].chunk_id, "strong");
+        // Cosine 0.8 normalizes to 0.9
+        assert!((filtered[0].score - 0.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_apply_score_threshold_none_keeps_all() {
+        let results = vec![vector_result("a", 0.8), vector_result("b", -1.0)];
+        let filtered = EmbeddingService::apply_score_threshold(results, None, false);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[1].score, 0.0);
+    }
+
+    #[test]
+    fn test_apply_score_threshold_fused_scores_are_not_rescaled() {
+        let results = vec![vector_result("top", 0.5), vector_result("tail", 0.1)];
+        let threshold = SimilarityScore::new(0.3).ok();
+
+        let filtered = EmbeddingService::apply_score_threshold(results, threshold, true);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].score, 0.5);
+    }
+
+    #[test]
+    fn test_find_stale_chunks() {
+        let expected: HashMap<String, String> = [
+            ("kept".to_string(), "h1".to_string()),
+            ("changed".to_string(), "h2".to_string()),
+            ("legacy".to_string(), "h3".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let stored = vec![
+            ("kept".to_string(), Some("h1".to_string())),
+            ("changed".to_string(), Some("old".to_string())),
+            ("legacy".to_string(), None),
+            ("deleted-block".to_string(), Some("h4".to_string())),
+        ];
+
+        let (orphaned, stale) = find_stale_chunks(&expected, stored);
+
+        assert_eq!(orphaned, vec!["deleted-block"]);
+        assert_eq!(stale, vec!["changed", "legacy"]);
+    }
+
+    #[test]
+    fn test_embedding_stats_rates() {
+        let mut stats = EmbeddingStats {
+            chunks_stored: 50,
+            batches: 2,
+            embedding_time: Duration::from_millis(300),
+            upsert_time: Duration::from_millis(100),
+            total_time: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(stats.chunks_per_second(), 100.0);
+        assert_eq!(stats.embedding_ms_per_batch(), 150.0);
+        assert_eq!(stats.upsert_ms_per_batch(), 50.0);
+
+        stats.merge(&stats.clone());
+        assert_eq!(stats.batches, 4);
+        assert_eq!(stats.chunks_per_second(), 100.0);
+
+        assert_eq!(EmbeddingStats::default().chunks_per_second(), 0.0);
+    }
+
+    #[test]
+    fn test_plan_chunk_updates() {
+        let chunk = |id: &str, text: &str| ChunkMetadata {
+            chunk_id: id.to_string(),
+            block_id: id.to_string(),
+            page_id: "page".to_string(),
+            page_title: "Page".to_string(),
+            chunk_index: 0,
+            total_chunks: 1,
+            original_content: text.to_string(),
+            preprocessed_content: text.to_string(),
+            hierarchy_path: vec![],
+        };
+        let record = |c: &ChunkMetadata, model: &str| ChunkRecord {
+            chunk_id: c.chunk_id.clone(),
+            block_id: c.block_id.clone(),
+            page_id: c.page_id.clone(),
+            content_hash: c.content_hash(),
+            model: model.to_string(),
+            created_at: chrono::Utc::now(),
+        };
+
+        let kept = chunk("kept", "same text");
+        let edited = chunk("edited", "new text");
+        let stored = vec![
+            record(&kept, "model-a"),
+            record(&chunk("edited", "old text"), "model-a"),
+            record(&chunk("other-model", "text"), "model-b"),
+            record(&chunk("gone", "text"), "model-a"),
+        ];
+        let chunks = vec![kept, edited, chunk("other-model", "text"), chunk("new", "text")];
+
+        let plan = plan_chunk_updates(chunks, &stored, "model-a");
+
+        assert_eq!(plan.unchanged, 1);
+        let to_embed: Vec<&str> = plan.to_embed.iter().map(|c| c.chunk_id.as_str()).collect();
+        assert_eq!(to_embed, vec!["edited", "other-model", "new"]);
+        assert_eq!(plan.removed, vec!["gone"]);
+    }
 }
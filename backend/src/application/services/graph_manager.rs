@@ -0,0 +1,287 @@
+/// Manager that syncs several Logseq graphs side by side
+use crate::application::repositories::PageRepository;
+use crate::application::services::{SyncError, SyncEvent, SyncService};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio::task::JoinHandle;
+
+/// Events buffered per subscriber before slow receivers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Error, Debug)]
+pub enum GraphError {
+    #[error("Graph already registered: {0}")]
+    AlreadyRegistered(String),
+
+    #[error("Graph not found: {0}")]
+    NotFound(String),
+
+    #[error("Sync error: {0}")]
+    Sync(#[from] SyncError),
+}
+
+pub type GraphResult<T> = Result<T, GraphError>;
+
+/// A sync event tagged with the graph it came from
+#[derive(Debug, Clone)]
+pub struct GraphEvent {
+    pub graph_id: String,
+    pub event: SyncEvent,
+}
+
+/// A registered graph and the tasks driving it
+struct GraphHandle<R: PageRepository> {
+    service: Arc<SyncService<R>>,
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+/// Owns one [`SyncService`] per registered graph directory
+///
+/// Each graph gets an initial sync followed by a file watcher running in its
+/// own task. Events from all graphs are republished on a single channel,
+/// tagged with the graph ID they were registered under.
+pub struct GraphManager<R: PageRepository> {
+    graphs: Mutex<HashMap<String, GraphHandle<R>>>,
+    events: broadcast::Sender<GraphEvent>,
+}
+
+impl<R: PageRepository + Send + Sync + 'static> GraphManager<R> {
+    /// Create a manager with no graphs
+    pub fn new() -> Self {
+        GraphManager {
+            graphs: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Subscribe to events from every graph, including graphs added later
+    pub fn subscribe(&self) -> broadcast::Receiver<GraphEvent> {
+        self.events.subscribe()
+    }
+
+    /// Register a graph and start syncing it in the background
+    pub async fn add_graph(
+        &self,
+        graph_id: impl Into<String>,
+        service: SyncService<R>,
+    ) -> GraphResult<()> {
+        let graph_id = graph_id.into();
+        let mut graphs = self.graphs.lock().await;
+        if graphs.contains_key(&graph_id) {
+            return Err(GraphError::AlreadyRegistered(graph_id));
+        }
+
+        let service = Arc::new(service);
+        // Subscribe before the sync task starts so no event is missed
+        self.spawn_forwarder(graph_id.clone(), service.subscribe());
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let task = {
+            let service = Arc::clone(&service);
+            let graph_id = graph_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = service.sync_once(None).await {
+                    tracing::error!("Initial sync of graph {} failed: {}", graph_id, e);
+                }
+                if let Err(e) = service.watch_until(shutdown_rx, None).await {
+                    tracing::error!("Watcher for graph {} stopped: {}", graph_id, e);
+                }
+            })
+        };
+
+        tracing::info!("Registered graph {}", graph_id);
+        graphs.insert(
+            graph_id,
+            GraphHandle {
+                service,
+                shutdown_tx,
+                task,
+            },
+        );
+        Ok(())
+    }
+
+    /// Stop syncing a graph and wait for its tasks to finish
+    pub async fn remove_graph(&self, graph_id: &str) -> GraphResult<()> {
+        let handle = self
+            .graphs
+            .lock()
+            .await
+            .remove(graph_id)
+            .ok_or_else(|| GraphError::NotFound(graph_id.to_string()))?;
+
+        Self::stop(handle).await;
+        tracing::info!("Removed graph {}", graph_id);
+        Ok(())
+    }
+
+    /// Stop every graph
+    pub async fn shutdown(&self) {
+        let handles: Vec<_> = self.graphs.lock().await.drain().map(|(_, h)| h).collect();
+        for handle in handles {
+            Self::stop(handle).await;
+        }
+    }
+
+    /// IDs of the registered graphs, sorted
+    pub async fn graph_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.graphs.lock().await.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// The sync service for a graph
+    pub async fn service(&self, graph_id: &str) -> Option<Arc<SyncService<R>>> {
+        self.graphs
+            .lock()
+            .await
+            .get(graph_id)
+            .map(|handle| Arc::clone(&handle.service))
+    }
+
+    /// Republish a graph's events, tagged with its ID, until the service is dropped
+    fn spawn_forwarder(&self, graph_id: String, mut receiver: broadcast::Receiver<SyncEvent>) {
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let _ = events.send(GraphEvent {
+                            graph_id: graph_id.clone(),
+                            event,
+                        });
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Dropped {} events from graph {}", skipped, graph_id);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Signal the watcher to stop and wait for it
+    ///
+    /// The event forwarder exits on its own once the last reference to the
+    /// service is dropped and the remaining events have been delivered.
+    async fn stop(handle: GraphHandle<R>) {
+        let _ = handle.shutdown_tx.send(true);
+        let _ = handle.task.await;
+    }
+}
+
+impl<R: PageRepository + Send + Sync + 'static> Default for GraphManager<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::Page;
+    use crate::domain::base::{DomainResult, Entity};
+    use crate::domain::value_objects::{LogseqDirectoryPath, PageId};
+    use std::path::Path;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct MockRepository {
+        pages: HashMap<PageId, Page>,
+    }
+
+    impl PageRepository for MockRepository {
+        fn save(&mut self, page: Page) -> DomainResult<()> {
+            self.pages.insert(page.id().clone(), page);
+            Ok(())
+        }
+
+        fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+            Ok(self.pages.get(id).cloned())
+        }
+
+        fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+            Ok(self.pages.values().find(|p| p.title() == title).cloned())
+        }
+
+        fn find_all(&self) -> DomainResult<Vec<Page>> {
+            Ok(self.pages.values().cloned().collect())
+        }
+
+        fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+            Ok(self.pages.remove(id).is_some())
+        }
+    }
+
+    fn graph_with_page(name: &str) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let pages_dir = temp_dir.path().join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(temp_dir.path().join("journals")).unwrap();
+        std::fs::write(pages_dir.join(format!("{}.md", name)), "- A block").unwrap();
+        temp_dir
+    }
+
+    fn service_for(path: &Path) -> SyncService<MockRepository> {
+        let dir_path = LogseqDirectoryPath::new(path).unwrap();
+        SyncService::new(MockRepository::default(), dir_path, None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_events_are_tagged_with_graph_id() {
+        let work = graph_with_page("work");
+        let personal = graph_with_page("personal");
+
+        let manager = GraphManager::new();
+        let mut events = manager.subscribe();
+        manager.add_graph("work", service_for(work.path())).await.unwrap();
+        manager.add_graph("personal", service_for(personal.path())).await.unwrap();
+
+        let mut created = Vec::new();
+        while created.len() < 2 {
+            let GraphEvent { graph_id, event } =
+                tokio::time::timeout(Duration::from_secs(5), events.recv())
+                    .await
+                    .expect("timed out waiting for sync events")
+                    .unwrap();
+            if let SyncEvent::FileCreated { file_path } = event {
+                assert!(file_path.ends_with(format!("{}.md", graph_id)));
+                created.push(graph_id);
+            }
+        }
+        created.sort();
+        assert_eq!(created, vec!["personal", "work"]);
+
+        manager.shutdown().await;
+        assert!(manager.graph_ids().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_graphs() {
+        let graph = graph_with_page("notes");
+        let other = graph_with_page("other");
+
+        let manager = GraphManager::new();
+        manager.add_graph("notes", service_for(graph.path())).await.unwrap();
+        assert!(matches!(
+            manager.add_graph("notes", service_for(other.path())).await,
+            Err(GraphError::AlreadyRegistered(_))
+        ));
+        manager.add_graph("other", service_for(other.path())).await.unwrap();
+        assert_eq!(manager.graph_ids().await, vec!["notes", "other"]);
+        assert!(manager.service("notes").await.is_some());
+
+        manager.remove_graph("notes").await.unwrap();
+        assert_eq!(manager.graph_ids().await, vec!["other"]);
+        assert!(matches!(
+            manager.remove_graph("notes").await,
+            Err(GraphError::NotFound(_))
+        ));
+
+        manager.shutdown().await;
+    }
+}
@@ -0,0 +1,156 @@
+/// Registry of the Logseq graphs one instance serves, and where each keeps its data
+use crate::application::services::{GraphError, GraphResult};
+use crate::domain::value_objects::GraphId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where a graph lives and where its derived data goes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphSettings {
+    pub id: GraphId,
+    /// The graph's directory
+    pub directory: PathBuf,
+    /// SQLite file for the graph's checkpoints and chunk index; `None` keeps
+    /// neither between runs
+    pub database_path: Option<PathBuf>,
+    /// Vector collection name, before it's namespaced with the graph ID; `None`
+    /// uses the embedding service's default
+    pub collection: Option<String>,
+}
+
+impl GraphSettings {
+    pub fn new(id: GraphId, directory: impl Into<PathBuf>) -> Self {
+        GraphSettings {
+            id,
+            directory: directory.into(),
+            database_path: None,
+            collection: None,
+        }
+    }
+
+    pub fn with_database_path(mut self, database_path: impl Into<PathBuf>) -> Self {
+        self.database_path = Some(database_path.into());
+        self
+    }
+
+    pub fn with_collection(mut self, collection: impl Into<String>) -> Self {
+        self.collection = Some(collection.into());
+        self
+    }
+}
+
+/// The graphs an instance serves, by ID
+///
+/// Only settings live here: the page store, embedding service and sync
+/// service of a graph are built from its [`GraphSettings`] (see
+/// [`Config::for_graph`](crate::config::Config::for_graph)), and
+/// [`GraphManager`](super::GraphManager) runs the syncs. The first graph
+/// added is the default one until another is chosen.
+#[derive(Debug, Clone, Default)]
+pub struct GraphRegistry {
+    graphs: BTreeMap<GraphId, GraphSettings>,
+    default_graph: Option<GraphId>,
+}
+
+impl GraphRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a graph; IDs and directories must be unique
+    pub fn add(&mut self, graph: GraphSettings) -> GraphResult<()> {
+        let taken = self.graphs.contains_key(&graph.id)
+            || self.graphs.values().any(|other| other.directory == graph.directory);
+        if taken {
+            return Err(GraphError::AlreadyRegistered(graph.id.to_string()));
+        }
+
+        self.default_graph.get_or_insert_with(|| graph.id.clone());
+        self.graphs.insert(graph.id.clone(), graph);
+        Ok(())
+    }
+
+    /// Unregister a graph; removing the default makes the first remaining graph the default
+    pub fn remove(&mut self, id: &GraphId) -> GraphResult<GraphSettings> {
+        let graph = self
+            .graphs
+            .remove(id)
+            .ok_or_else(|| GraphError::NotFound(id.to_string()))?;
+        if self.default_graph.as_ref() == Some(id) {
+            self.default_graph = self.graphs.keys().next().cloned();
+        }
+        Ok(graph)
+    }
+
+    pub fn get(&self, id: &GraphId) -> Option<&GraphSettings> {
+        self.graphs.get(id)
+    }
+
+    /// The graph whose ID is `name`, or whose directory is `name`
+    pub fn find(&self, name: &str) -> Option<&GraphSettings> {
+        GraphId::new(name)
+            .ok()
+            .and_then(|id| self.graphs.get(&id))
+            .or_else(|| self.graphs.values().find(|graph| graph.directory == Path::new(name)))
+    }
+
+    /// Every graph, sorted by ID
+    pub fn list(&self) -> impl Iterator<Item = &GraphSettings> {
+        self.graphs.values()
+    }
+
+    pub fn default_graph(&self) -> Option<&GraphSettings> {
+        self.default_graph.as_ref().and_then(|id| self.graphs.get(id))
+    }
+
+    pub fn set_default(&mut self, id: &GraphId) -> GraphResult<()> {
+        if !self.graphs.contains_key(id) {
+            return Err(GraphError::NotFound(id.to_string()));
+        }
+        self.default_graph = Some(id.clone());
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.graphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(id: &str) -> GraphSettings {
+        GraphSettings::new(GraphId::new(id).unwrap(), format!("/graphs/{}", id))
+    }
+
+    #[test]
+    fn test_add_find_and_remove_graphs() {
+        let mut registry = GraphRegistry::new();
+        registry.add(graph("work")).unwrap();
+        registry.add(graph("personal").with_collection("notes")).unwrap();
+        assert!(matches!(registry.add(graph("work")), Err(GraphError::AlreadyRegistered(_))));
+        let same_directory = GraphSettings::new(GraphId::new("job").unwrap(), "/graphs/work");
+        assert!(registry.add(same_directory).is_err());
+
+        let ids: Vec<&str> = registry.list().map(|graph| graph.id.as_str()).collect();
+        assert_eq!(ids, vec!["personal", "work"]);
+        assert_eq!(registry.find("personal").unwrap().collection.as_deref(), Some("notes"));
+        assert_eq!(registry.find("/graphs/work").unwrap().id.as_str(), "work");
+        assert!(registry.find("/graphs/other").is_none());
+
+        assert_eq!(registry.default_graph().unwrap().id.as_str(), "work");
+        registry.remove(&GraphId::new("work").unwrap()).unwrap();
+        assert_eq!(registry.default_graph().unwrap().id.as_str(), "personal");
+        assert!(matches!(
+            registry.set_default(&GraphId::new("work").unwrap()),
+            Err(GraphError::NotFound(_))
+        ));
+        assert_eq!(registry.len(), 1);
+    }
+}
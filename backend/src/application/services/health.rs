@@ -0,0 +1,325 @@
+/// Health checks of the services a deployment depends on
+use crate::application::repositories::PageRepository;
+use crate::application::services::{EmbeddingService, GraphManager};
+use crate::infrastructure::persistence::missing_tables;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Tables the configured services create in `[database] path`: import
+/// checkpoints and the local chunk index
+const DATABASE_TABLES: [&str; 2] = ["import_checkpoints", "chunks"];
+
+/// A checked part of the deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthComponent {
+    /// The SQLite database, and the tables in it
+    Database,
+    /// Qdrant, and the collections of the configured model
+    VectorStore,
+    EmbeddingModel,
+    /// The file watchers of synced graphs
+    Watcher,
+}
+
+impl HealthComponent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthComponent::Database => "database",
+            HealthComponent::VectorStore => "vector_store",
+            HealthComponent::EmbeddingModel => "embedding_model",
+            HealthComponent::Watcher => "watcher",
+        }
+    }
+}
+
+/// How a component is doing, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Not configured, so not checked
+    Disabled,
+    Healthy,
+    /// Working, but not yet ready, like a model that loads on first use
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStatus::Disabled => "disabled",
+            HealthStatus::Healthy => "healthy",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentHealth {
+    pub component: HealthComponent,
+    pub status: HealthStatus,
+    /// What was found, such as the Qdrant version or the error
+    pub detail: String,
+}
+
+impl ComponentHealth {
+    fn new(component: HealthComponent, status: HealthStatus, detail: impl Into<String>) -> Self {
+        ComponentHealth {
+            component,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Every component's health, in [`HealthComponent`] order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    fn new(components: Vec<ComponentHealth>) -> Self {
+        let status = components
+            .iter()
+            .map(|component| component.status)
+            .max()
+            .filter(|status| *status != HealthStatus::Disabled)
+            .unwrap_or(HealthStatus::Healthy);
+        HealthReport { status, components }
+    }
+
+    /// Whether every component is working, if perhaps not ready yet
+    pub fn is_healthy(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+}
+
+/// Service checking the database, vector store, embedding model and file
+/// watchers it's given; components it isn't given are reported disabled
+///
+/// Checks only read: the database is opened read-only and the model is
+/// only loaded with [`with_model_loading`](Self::with_model_loading).
+pub struct HealthService<R: PageRepository> {
+    database: Option<PathBuf>,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    /// Why the embedding service couldn't be started
+    embedding_error: Option<String>,
+    load_model: bool,
+    graphs: Option<Arc<GraphManager<R>>>,
+}
+
+impl<R: PageRepository + Send + Sync + 'static> HealthService<R> {
+    pub fn new() -> Self {
+        HealthService {
+            database: None,
+            embedding_service: None,
+            embedding_error: None,
+            load_model: false,
+            graphs: None,
+        }
+    }
+
+    /// Check the SQLite database at `path` exists and has its tables
+    pub fn with_database(mut self, path: impl Into<PathBuf>) -> Self {
+        self.database = Some(path.into());
+        self
+    }
+
+    /// Check Qdrant and the embedding model through `embedding_service`
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Report the vector store and the model unhealthy, because the
+    /// embedding service failed to start with `error`
+    pub fn with_embedding_error(mut self, error: impl ToString) -> Self {
+        self.embedding_error = Some(error.to_string());
+        self
+    }
+
+    /// Load the embedding model if it isn't loaded, rather than reporting it
+    /// degraded until first use
+    pub fn with_model_loading(mut self) -> Self {
+        self.load_model = true;
+        self
+    }
+
+    /// Check the file watcher of each of the manager's graphs is running
+    pub fn with_graph_manager(mut self, graphs: Arc<GraphManager<R>>) -> Self {
+        self.graphs = Some(graphs);
+        self
+    }
+
+    /// Check every component
+    pub async fn check(&self) -> HealthReport {
+        HealthReport::new(vec![
+            self.check_database(),
+            self.check_vector_store().await,
+            self.check_model().await,
+            self.check_watchers().await,
+        ])
+    }
+
+    fn check_database(&self) -> ComponentHealth {
+        let component = HealthComponent::Database;
+        let Some(path) = &self.database else {
+            return ComponentHealth::new(component, HealthStatus::Disabled, "No database configured");
+        };
+        match missing_tables(path, &DATABASE_TABLES) {
+            Ok(missing) if missing.is_empty() => {
+                ComponentHealth::new(component, HealthStatus::Healthy, path.display().to_string())
+            }
+            Ok(missing) => ComponentHealth::new(
+                component,
+                HealthStatus::Unhealthy,
+                format!("{} lacks tables: {}", path.display(), missing.join(", ")),
+            ),
+            Err(e) => ComponentHealth::new(
+                component,
+                HealthStatus::Unhealthy,
+                format!("Cannot open {}: {}", path.display(), e),
+            ),
+        }
+    }
+
+    async fn check_vector_store(&self) -> ComponentHealth {
+        let component = HealthComponent::VectorStore;
+        let Some(service) = &self.embedding_service else {
+            return self.embeddings_unavailable(component);
+        };
+        let version = match service.health_check().await {
+            Ok(version) => version,
+            Err(e) => return ComponentHealth::new(component, HealthStatus::Unhealthy, e.to_string()),
+        };
+        match service.validate_collections().await {
+            Ok(mismatches) if mismatches.is_empty() => {
+                ComponentHealth::new(component, HealthStatus::Healthy, format!("Qdrant {}", version))
+            }
+            Ok(mismatches) => ComponentHealth::new(
+                component,
+                HealthStatus::Unhealthy,
+                format!("Collections don't match the model: {}", mismatches.join("; ")),
+            ),
+            Err(e) => ComponentHealth::new(component, HealthStatus::Unhealthy, e.to_string()),
+        }
+    }
+
+    async fn check_model(&self) -> ComponentHealth {
+        let component = HealthComponent::EmbeddingModel;
+        let Some(service) = &self.embedding_service else {
+            return self.embeddings_unavailable(component);
+        };
+        let model = service.model().model_name();
+        if !service.is_model_loaded() && self.load_model {
+            if let Err(e) = service.warmup().await {
+                return ComponentHealth::new(component, HealthStatus::Unhealthy, format!("{}: {}", model, e));
+            }
+        }
+        if service.is_model_loaded() {
+            ComponentHealth::new(component, HealthStatus::Healthy, format!("{} loaded", model))
+        } else {
+            ComponentHealth::new(component, HealthStatus::Degraded, format!("{} loads on first use", model))
+        }
+    }
+
+    async fn check_watchers(&self) -> ComponentHealth {
+        let component = HealthComponent::Watcher;
+        let Some(graphs) = &self.graphs else {
+            return ComponentHealth::new(component, HealthStatus::Disabled, "No graphs are watched");
+        };
+
+        let (mut watching, mut starting, mut stopped) = (Vec::new(), Vec::new(), Vec::new());
+        for graph_id in graphs.graph_ids().await {
+            let Some(service) = graphs.service(&graph_id).await else {
+                continue;
+            };
+            let status = service.status();
+            match (status.watching, status.last_sync) {
+                (true, _) => watching.push(graph_id),
+                // The watcher starts once the initial sync is done
+                (false, None) => starting.push(graph_id),
+                (false, Some(_)) => stopped.push(graph_id),
+            }
+        }
+
+        if !stopped.is_empty() {
+            ComponentHealth::new(component, HealthStatus::Unhealthy, format!("Stopped: {}", stopped.join(", ")))
+        } else if !starting.is_empty() {
+            ComponentHealth::new(
+                component,
+                HealthStatus::Degraded,
+                format!("Initial sync running: {}", starting.join(", ")),
+            )
+        } else if watching.is_empty() {
+            ComponentHealth::new(component, HealthStatus::Disabled, "No graphs are watched")
+        } else {
+            ComponentHealth::new(component, HealthStatus::Healthy, format!("Watching: {}", watching.join(", ")))
+        }
+    }
+
+    fn embeddings_unavailable(&self, component: HealthComponent) -> ComponentHealth {
+        match &self.embedding_error {
+            Some(error) => ComponentHealth::new(component, HealthStatus::Unhealthy, error.clone()),
+            None => ComponentHealth::new(component, HealthStatus::Disabled, "Semantic search is not enabled"),
+        }
+    }
+}
+
+impl<R: PageRepository + Send + Sync + 'static> Default for HealthService<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::{
+        InMemoryPageRepository, SqliteChunkRepository, SqliteImportCheckpointRepository,
+    };
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_database_needs_the_tables_of_the_configured_services() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logjam.db");
+        let health = HealthService::<InMemoryPageRepository>::new().with_database(&path);
+
+        let report = health.check().await;
+        assert_eq!(report.components[0].status, HealthStatus::Unhealthy);
+        assert!(!report.is_healthy());
+        assert!(!path.exists(), "checks must not create the database");
+
+        SqliteImportCheckpointRepository::open(&path).unwrap();
+        let report = health.check().await;
+        assert!(report.components[0].detail.ends_with("lacks tables: chunks"));
+
+        SqliteChunkRepository::open(&path).unwrap();
+        let report = health.check().await;
+        assert_eq!(report.status, HealthStatus::Healthy);
+        let statuses: Vec<HealthStatus> = report.components.iter().map(|component| component.status).collect();
+        assert_eq!(
+            statuses,
+            [HealthStatus::Healthy, HealthStatus::Disabled, HealthStatus::Disabled, HealthStatus::Disabled]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_embedding_errors_make_the_report_unhealthy() {
+        let report = HealthService::<InMemoryPageRepository>::new()
+            .with_embedding_error("Qdrant is unreachable")
+            .check()
+            .await;
+
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert_eq!(report.components[1].component, HealthComponent::VectorStore);
+        assert_eq!(report.components[1].detail, "Qdrant is unreachable");
+        assert_eq!(report.components[2].status, HealthStatus::Unhealthy);
+    }
+}
@@ -1,13 +1,29 @@
 /// Import service for importing Logseq directories
-use crate::application::repositories::PageRepository;
-use crate::domain::value_objects::{ImportProgress, LogseqDirectoryPath};
-use crate::infrastructure::file_system::discover_logseq_files;
-use crate::infrastructure::parsers::LogseqMarkdownParser;
-use std::path::PathBuf;
-use std::sync::Arc;
+use crate::application::dto::EmbeddingJobKind;
+use crate::application::repositories::{
+    EmbeddingJobRepository, ImportCheckpointRepository, PageRepository,
+};
+use crate::application::services::duplicate_titles::{
+    merge_blocks, numbered_title, DuplicateTitleAction, DuplicateTitlePolicy, DuplicateTitleResolution,
+};
+use crate::application::services::import_validation::{
+    GraphValidator, ValidationReport, DEFAULT_MAX_BLOCK_LENGTH,
+};
+use crate::application::services::progress::{OperationKind, ProgressBus, ProgressOperation};
+use crate::domain::base::Entity;
+use crate::domain::aggregates::Page;
+use crate::domain::value_objects::{ImportProgress, LogseqDirectoryPath, PageId};
+use crate::infrastructure::file_system::{detect_layout, discover_graph_files, GraphArchive, IgnoreRules};
+use crate::infrastructure::logseq_api::{LogseqApiClient, LogseqApiError};
+use crate::infrastructure::parsers::{LogseqExportParser, LogseqMarkdownParser};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError};
 use std::time::Instant;
 use thiserror::Error;
-use tokio::sync::{mpsc, Semaphore};
+use tokio::sync::{mpsc, Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Error, Debug)]
 pub enum ImportError {
@@ -20,11 +36,20 @@ pub enum ImportError {
     #[error("Parse error: {0}")]
     Parse(#[from] crate::infrastructure::parsers::ParseError),
 
+    #[error(transparent)]
+    LogseqApi(#[from] LogseqApiError),
+
     #[error("Repository error: {0}")]
     Repository(#[from] crate::domain::base::DomainError),
 
     #[error("Domain error: {0}")]
     Domain(String),
+
+    #[error("No checkpoint store configured; use with_checkpoints")]
+    CheckpointsUnavailable,
+
+    #[error("Page title \"{title}\" is already used by {}", kept_by.display())]
+    DuplicateTitle { title: String, kept_by: PathBuf },
 }
 
 pub type ImportResult<T> = Result<T, ImportError>;
@@ -32,19 +57,137 @@ pub type ImportResult<T> = Result<T, ImportError>;
 /// Callback type for progress events
 pub type ProgressCallback = Arc<dyn Fn(ImportProgressEvent) + Send + Sync>;
 
+/// Default number of pages saved per repository call
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Default budget, in bytes of file content, for files parsed but not yet saved
+const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
 /// Progress event for the import process
 #[derive(Debug, Clone)]
 pub enum ImportProgressEvent {
     Started { total_files: usize },
     FileProcessed { file_path: PathBuf, progress: ImportProgress },
+    /// Pages of a saved batch were queued for embedding
+    QueuedForEmbedding { pages_queued: usize, total_queued: usize },
     Completed { pages_imported: usize, duration_ms: u64 },
     Failed { error: String, files_processed: usize },
+    /// The import was cancelled (see [`ImportService::with_cancellation`]);
+    /// pages saved before then stay saved
+    Cancelled { pages_imported: usize, files_processed: usize },
+}
+
+/// What reading a file during import produced
+enum FileImport {
+    Parsed { page: Page, content_hash: String },
+    /// Imported by an earlier run and unchanged since
+    AlreadyImported,
+    /// Merged into another file's page (see [`DuplicateTitlePolicy::Merge`])
+    Merged { content_hash: String },
+}
+
+/// A file read by a parse worker, with its share of the memory budget
+struct ParsedFile {
+    file_path: PathBuf,
+    result: ImportResult<FileImport>,
+    cost: usize,
+    permit: OwnedSemaphorePermit,
+}
+
+/// Parsed pages waiting to be saved together
+#[derive(Default)]
+struct ImportBatch {
+    /// Path and content hash of each page's file
+    files: Vec<(PathBuf, String)>,
+    pages: Vec<Page>,
+    bytes: usize,
+    /// Released once the batch is saved, letting workers parse more files
+    permits: Vec<OwnedSemaphorePermit>,
+}
+
+/// Files of one import whose pages would share a title
+struct DuplicateGroup {
+    title: String,
+    /// In path order; the first keeps the title
+    files: Vec<PathBuf>,
+    /// Under the rename policy, the title each later file's page gets
+    renamed: Vec<String>,
+    /// Under the merge policy, the files read so far, waiting for the rest
+    arrived: Vec<(PathBuf, FileImport)>,
+    /// Files not yet received from the workers
+    remaining: usize,
+}
+
+/// The duplicate titles of an import, worked out from file names before parsing
+#[derive(Default)]
+struct DuplicateGroups {
+    groups: Vec<DuplicateGroup>,
+    by_file: HashMap<PathBuf, usize>,
+}
+
+impl DuplicateGroups {
+    fn new<'a>(files: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let mut by_title: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for file in files {
+            if let Some(stem) = file.file_stem().and_then(|stem| stem.to_str()) {
+                let title = LogseqMarkdownParser::title_from_file_stem(stem);
+                by_title.entry(title).or_default().push(file.clone());
+            }
+        }
+
+        let mut taken: HashSet<String> = by_title.keys().cloned().collect();
+        let mut duplicates = DuplicateGroups::default();
+        for (title, mut files) in by_title.into_iter().filter(|(_, files)| files.len() > 1) {
+            files.sort();
+            let renamed = files[1..]
+                .iter()
+                .map(|_| {
+                    let renamed = numbered_title(&title, |candidate| taken.contains(candidate));
+                    taken.insert(renamed.clone());
+                    renamed
+                })
+                .collect();
+
+            let index = duplicates.groups.len();
+            duplicates.by_file.extend(files.iter().map(|file| (file.clone(), index)));
+            duplicates.groups.push(DuplicateGroup {
+                title,
+                remaining: files.len(),
+                files,
+                renamed,
+                arrived: Vec::new(),
+            });
+        }
+        duplicates
+    }
 }
 
 /// Service for importing Logseq directories
+///
+/// Imports run as a bounded pipeline: a fixed pool of workers parses files
+/// from the discovered list, and parsed pages are saved in batches. Workers
+/// take a share of the memory limit for each file they read and only get it
+/// back once the file's page has been saved, so the pages held in memory stay
+/// within the limit however large the graph is.
 pub struct ImportService<R: PageRepository> {
     repository: R,
     max_concurrent_files: usize,
+    /// Where completed files are recorded, if imports should be resumable
+    ///
+    /// Only used through `&mut self`; the mutex keeps the service `Sync`, so
+    /// imports can run in spawned tasks
+    checkpoints: Option<std::sync::Mutex<Box<dyn ImportCheckpointRepository + Send>>>,
+    batch_size: usize,
+    memory_limit: usize,
+    /// When set, every saved page is queued to be embedded
+    embedding_jobs: Option<Arc<Mutex<dyn EmbeddingJobRepository + Send>>>,
+    /// Blocks longer than this are reported by [`validate_directory`](Self::validate_directory)
+    max_block_length: usize,
+    duplicate_title_policy: DuplicateTitlePolicy,
+    /// Checked between files; once cancelled, imports stop after saving what they parsed
+    cancellation: CancellationToken,
+    /// When set, each import also reports its progress here, counting files
+    progress: Option<ProgressBus>,
 }
 
 impl<R: PageRepository> ImportService<R> {
@@ -52,91 +195,562 @@ impl<R: PageRepository> ImportService<R> {
         ImportService {
             repository,
             max_concurrent_files: 4, // Default bounded concurrency
+            checkpoints: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            embedding_jobs: None,
+            max_block_length: DEFAULT_MAX_BLOCK_LENGTH,
+            duplicate_title_policy: DuplicateTitlePolicy::default(),
+            cancellation: CancellationToken::new(),
+            progress: None,
         }
     }
 
     pub fn with_concurrency(mut self, max_concurrent: usize) -> Self {
-        self.max_concurrent_files = max_concurrent;
+        self.max_concurrent_files = max_concurrent.max(1);
+        self
+    }
+
+    /// Set how many pages are saved per repository call
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Limit the file content, in bytes, parsed but not yet saved
+    ///
+    /// Parsed pages take several times their file size in memory, so this
+    /// bounds rather than equals the memory used.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes.max(2);
+        self
+    }
+
+    /// Record imported files so an interrupted import can be resumed
+    pub fn with_checkpoints(
+        mut self,
+        checkpoints: impl ImportCheckpointRepository + Send + 'static,
+    ) -> Self {
+        self.checkpoints = Some(std::sync::Mutex::new(Box::new(checkpoints)));
+        self
+    }
+
+    /// Queue every page the import saves to be embedded
+    ///
+    /// Pages are queued batch by batch as they are saved, so a worker on the
+    /// queue (see [`EmbeddingQueueService::jobs`](crate::application::services::EmbeddingQueueService::jobs))
+    /// can embed them while the import is still running, without walking the
+    /// graph again afterwards. The queue's worker must read pages from the
+    /// same store this service saves to.
+    pub fn with_embedding_queue(
+        mut self,
+        jobs: Arc<Mutex<dyn EmbeddingJobRepository + Send>>,
+    ) -> Self {
+        self.embedding_jobs = Some(jobs);
+        self
+    }
+
+    /// Set the block length, in characters, above which validation reports a block
+    pub fn with_max_block_length(mut self, chars: usize) -> Self {
+        self.max_block_length = chars;
+        self
+    }
+
+    /// Set what happens when several files of an import produce the same title
+    ///
+    /// The file that comes first in path order keeps the title; what was done
+    /// with the others is listed in [`ImportSummary::duplicate_titles`].
+    /// Titles are compared among the files of one import only.
+    pub fn with_duplicate_title_policy(mut self, policy: DuplicateTitlePolicy) -> Self {
+        self.duplicate_title_policy = policy;
         self
     }
 
+    /// Stop imports early when `token` is cancelled
+    ///
+    /// Imports check the token between files. Pages already parsed are saved
+    /// (and checkpointed) before the import returns, and the summary is
+    /// flagged [`cancelled`](ImportSummary::cancelled); a cancelled directory
+    /// import can be continued with [`resume_import`](Self::resume_import).
+    /// A cancelled token stays cancelled, so later imports on this service
+    /// stop straight away.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Publish the progress of every import on `bus`, alongside the progress callback
+    pub fn with_progress(mut self, bus: ProgressBus) -> Self {
+        self.progress = Some(bus);
+        self
+    }
+
+    /// Parse every file of a Logseq directory and report structural problems,
+    /// without saving anything
+    ///
+    /// A dry run of [`import_directory`](Self::import_directory): reports
+    /// duplicate titles, empty pages, malformed references, oversized blocks
+    /// and files that fail to parse, so a graph can be cleaned up before it is
+    /// indexed. Checkpoints and the embedding queue are left untouched.
+    pub async fn validate_directory(
+        &self,
+        directory_path: LogseqDirectoryPath,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ValidationReport> {
+        let start_time = Instant::now();
+
+        let ignore_rules = IgnoreRules::for_graph(directory_path.as_path());
+        let files = discover_graph_files(&directory_path, &ignore_rules).await?;
+
+        if let Some(ref callback) = progress_callback {
+            callback(ImportProgressEvent::Started { total_files: files.len() });
+        }
+
+        let mut progress = ImportProgress::new(files.len());
+        let mut validator = GraphValidator::new(self.max_block_length);
+
+        for file_path in files {
+            let parsed = match tokio::fs::read(&file_path).await {
+                Ok(bytes) => LogseqMarkdownParser::parse_file_content(&file_path, &LogseqMarkdownParser::decode(&bytes))
+                    .map_err(ImportError::from),
+                Err(e) => Err(e.into()),
+            };
+            match parsed {
+                Ok(page) => validator.check_page(file_path.clone(), &page),
+                Err(e) => validator.unreadable(file_path.clone(), e.to_string()),
+            }
+
+            progress.increment();
+            if let Some(ref callback) = progress_callback {
+                callback(ImportProgressEvent::FileProcessed {
+                    file_path,
+                    progress: progress.clone(),
+                });
+            }
+        }
+
+        Ok(validator.finish(start_time.elapsed().as_millis() as u64))
+    }
+
+    /// Import a graph directory, zip backup or graph export, whichever `path` is
+    ///
+    /// Directories are imported with [`import_directory`](Self::import_directory)
+    /// using the layout in their config, `.zip` files with
+    /// [`import_archive`](Self::import_archive) and `.json`/`.edn` files with
+    /// [`import_export`](Self::import_export).
+    pub async fn import_path(
+        &mut self,
+        path: &Path,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        if path.is_dir() {
+            let directory = LogseqDirectoryPath::with_layout(path, detect_layout(path))?;
+            return self.import_directory(directory, progress_callback).await;
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("zip") => self.import_archive(path, progress_callback).await,
+            Some("json" | "edn") => self.import_export(path, progress_callback).await,
+            _ => Err(ImportError::InvalidDirectory(format!(
+                "{} is not a graph directory, .zip backup or .json/.edn export",
+                path.display()
+            ))),
+        }
+    }
+
     /// Import a Logseq directory with progress tracking
+    ///
+    /// Any checkpoints left by an earlier import of the directory are discarded.
     pub async fn import_directory(
         &mut self,
         directory_path: LogseqDirectoryPath,
         progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        if let Some(checkpoints) = self.checkpoints() {
+            checkpoints.clear(directory_path.as_path())?;
+        }
+        self.run_import(directory_path, HashMap::new(), progress_callback).await
+    }
+
+    /// Re-import a Logseq directory, parsing and saving only changed files
+    ///
+    /// Files whose contents match the hash recorded by an earlier import are
+    /// skipped; new and changed files are imported as usual, and checkpoints
+    /// of files that no longer exist are dropped.
+    pub async fn import_directory_incremental(
+        &mut self,
+        directory_path: LogseqDirectoryPath,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let imported = self
+            .checkpoints()
+            .ok_or(ImportError::CheckpointsUnavailable)?
+            .imported_files(directory_path.as_path())?;
+        self.run_import(directory_path, imported, progress_callback).await
+    }
+
+    /// Continue an interrupted import of a Logseq directory
+    ///
+    /// Works like [`import_directory_incremental`](Self::import_directory_incremental):
+    /// files the interrupted run already saved are skipped.
+    pub async fn resume_import(
+        &mut self,
+        directory_path: LogseqDirectoryPath,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        self.import_directory_incremental(directory_path, progress_callback).await
+    }
+
+    /// Import a Logseq graph export (JSON, or EDN for `.edn` files)
+    ///
+    /// Exports carry Logseq's own block UUIDs and structured properties, so
+    /// this keeps block references intact where markdown parsing can't.
+    /// Progress is reported per page, all under the export's path.
+    pub async fn import_export(
+        &mut self,
+        export_path: &Path,
+        progress_callback: Option<ProgressCallback>,
     ) -> ImportResult<ImportSummary> {
         let start_time = Instant::now();
+        let pages = LogseqExportParser::parse_file(export_path).await?;
+        self.import_pages(export_path, pages, start_time, progress_callback).await
+    }
 
-        // Discover all markdown files
-        let files = discover_logseq_files(directory_path.as_path()).await?;
-        let total_files = files.len();
+    /// Import the graph open in a running Logseq, through its HTTP API
+    ///
+    /// Like exports, pages keep Logseq's UUIDs, and unsaved edits are
+    /// included. Progress is reported per page, all under the API's URL.
+    pub async fn import_from_logseq_api(
+        &mut self,
+        client: &LogseqApiClient,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let start_time = Instant::now();
+        let pages = client.fetch_all_pages().await?;
+        self.import_pages(Path::new(client.url()), pages, start_time, progress_callback).await
+    }
+
+    /// Save pages read from `source`, an export or the Logseq API
+    async fn import_pages(
+        &mut self,
+        source: &Path,
+        pages: Vec<Page>,
+        start_time: Instant,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let total_files = pages.len();
 
-        // Emit started event
         if let Some(ref callback) = progress_callback {
             callback(ImportProgressEvent::Started { total_files });
         }
+        let operation = ProgressOperation::start(self.progress.as_ref(), OperationKind::Import, Some(total_files));
 
-        // Track progress
         let mut progress = ImportProgress::new(total_files);
         let mut errors = Vec::new();
         let mut pages_imported = 0;
+        let mut pages_queued = 0;
+        let mut cancelled = false;
 
-        // Use bounded concurrency with a semaphore
-        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_files));
-        let (tx, mut rx) = mpsc::channel(100);
+        for page in pages {
+            if self.cancellation.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            let title = page.title().to_string();
+            let page_id = page.id().clone();
+            if let Err(e) = self.repository.save(page) {
+                tracing::error!("Failed to save page {} from {}: {}", title, source.display(), e);
+                errors.push((source.to_path_buf(), format!("{}: {}", title, e)));
+            } else {
+                pages_imported += 1;
+                self.queue_for_embedding(&[page_id], &mut pages_queued, progress_callback.as_ref())
+                    .await?;
+            }
 
-        // Spawn tasks for each file
-        for file_path in files {
-            let semaphore = Arc::clone(&semaphore);
+            progress.increment();
+            operation.advance(progress.files_processed());
+            if let Some(ref callback) = progress_callback {
+                callback(ImportProgressEvent::FileProcessed {
+                    file_path: source.to_path_buf(),
+                    progress: progress.clone(),
+                });
+            }
+        }
+
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+        operation.finish(progress.files_processed(), cancelled, !errors.is_empty());
+
+        if let Some(ref callback) = progress_callback {
+            if cancelled {
+                callback(ImportProgressEvent::Cancelled {
+                    pages_imported,
+                    files_processed: progress.files_processed(),
+                });
+            } else if errors.is_empty() {
+                callback(ImportProgressEvent::Completed {
+                    pages_imported,
+                    duration_ms,
+                });
+            } else {
+                callback(ImportProgressEvent::Failed {
+                    error: format!("{} pages failed to import", errors.len()),
+                    files_processed: progress.files_processed(),
+                });
+            }
+        }
+
+        Ok(ImportSummary {
+            total_files,
+            pages_imported,
+            files_skipped: 0,
+            pages_queued,
+            duplicate_titles: Vec::new(),
+            errors,
+            duration_ms,
+            cancelled,
+        })
+    }
+
+    async fn run_import(
+        &mut self,
+        directory_path: LogseqDirectoryPath,
+        imported: HashMap<PathBuf, String>,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let start_time = Instant::now();
+
+        // Discover all markdown files
+        let ignore_rules = IgnoreRules::for_graph(directory_path.as_path());
+        let files = discover_graph_files(&directory_path, &ignore_rules).await?;
+        let total_files = files.len();
+
+        let present: HashSet<&PathBuf> = files.iter().collect();
+        let removed: Vec<PathBuf> = imported
+            .keys()
+            .filter(|path| !present.contains(path))
+            .cloned()
+            .collect();
+        if let (Some(checkpoints), false) = (self.checkpoints(), removed.is_empty()) {
+            checkpoints.forget(directory_path.as_path(), &removed)?;
+        }
+        let imported = Arc::new(imported);
+        let duplicates = DuplicateGroups::new(&files);
+
+        // Emit started event
+        if let Some(ref callback) = progress_callback {
+            callback(ImportProgressEvent::Started { total_files });
+        }
+        let operation = ProgressOperation::start(self.progress.as_ref(), OperationKind::Import, Some(total_files));
+
+        let budget = Arc::new(Semaphore::new(self.memory_limit));
+        let max_cost = self.max_file_cost();
+        let queue = Arc::new(std::sync::Mutex::new(files.into_iter()));
+        let (tx, rx) = mpsc::channel(self.max_concurrent_files * 2);
+
+        for _ in 0..self.max_concurrent_files {
+            let budget = Arc::clone(&budget);
+            let queue = Arc::clone(&queue);
+            let imported = Arc::clone(&imported);
+            let cancellation = self.cancellation.clone();
             let tx = tx.clone();
 
             tokio::spawn(async move {
-                let _permit = semaphore.acquire().await.unwrap();
-                let result = LogseqMarkdownParser::parse_file(&file_path).await;
-                tx.send((file_path, result)).await.ok();
+                while !cancellation.is_cancelled() {
+                    let Some(file_path) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let size = tokio::fs::metadata(&file_path).await.map(|m| m.len()).unwrap_or(0);
+                    let cost = (size as usize).clamp(1, max_cost);
+                    let Ok(permit) = Arc::clone(&budget).acquire_many_owned(cost as u32).await else {
+                        break;
+                    };
+
+                    let result = read_file(&file_path, &imported).await;
+                    let parsed = ParsedFile { file_path, result, cost, permit };
+                    if tx.send(parsed).await.is_err() {
+                        break;
+                    }
+                }
             });
         }
 
-        // Drop the original sender so the channel closes when all tasks complete
+        // Drop the original sender so the channel closes when all workers finish
         drop(tx);
 
+        self.save_parsed(directory_path.as_path(), rx, duplicates, operation, start_time, progress_callback)
+            .await
+    }
+
+    /// Import a Logseq graph from a zip archive, without extracting it
+    ///
+    /// The graph may sit at the archive's root or inside a folder, as in
+    /// Logseq's own backups. Pages and journals are decompressed one at a
+    /// time and go through the same batching and memory limit as
+    /// [`import_directory`](Self::import_directory); their paths (in progress
+    /// events, errors and checkpoints) are the archive's path joined with the
+    /// file's path inside the graph.
+    pub async fn import_archive(
+        &mut self,
+        archive_path: &Path,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let start_time = Instant::now();
+
+        let archive = {
+            let archive_path = archive_path.to_path_buf();
+            tokio::task::spawn_blocking(move || GraphArchive::open(&archive_path))
+                .await
+                .map_err(std::io::Error::other)??
+        };
+        let total_files = archive.len();
+        let duplicates = DuplicateGroups::new(&archive.paths().collect::<Vec<_>>());
+
+        if let Some(checkpoints) = self.checkpoints() {
+            checkpoints.clear(archive_path)?;
+        }
+        if let Some(ref callback) = progress_callback {
+            callback(ImportProgressEvent::Started { total_files });
+        }
+        let operation = ProgressOperation::start(self.progress.as_ref(), OperationKind::Import, Some(total_files));
+
+        let budget = Arc::new(Semaphore::new(self.memory_limit));
+        let max_cost = self.max_file_cost();
+        let (tx, rx) = mpsc::channel(self.max_concurrent_files * 2);
+        let runtime = tokio::runtime::Handle::current();
+
+        // Decompression is blocking and the archive can only be read in order,
+        // so a single blocking task reads and parses the entries
+        tokio::task::spawn_blocking(move || {
+            let imported = HashMap::new();
+            for entry in archive {
+                let size = entry.bytes.as_ref().map_or(0, Vec::len);
+                let cost = size.clamp(1, max_cost);
+                let Ok(permit) = runtime.block_on(Arc::clone(&budget).acquire_many_owned(cost as u32)) else {
+                    break;
+                };
+
+                let result = entry
+                    .bytes
+                    .map_err(ImportError::from)
+                    .and_then(|bytes| parse_file(&entry.path, &bytes, &imported));
+                let parsed = ParsedFile { file_path: entry.path, result, cost, permit };
+                if tx.blocking_send(parsed).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.save_parsed(archive_path, rx, duplicates, operation, start_time, progress_callback)
+            .await
+    }
+
+    /// Largest share of the memory budget one file takes
+    ///
+    /// A file never takes more than half the budget, so a batch that is
+    /// flushed at half the budget always leaves room for the next file.
+    fn max_file_cost(&self) -> usize {
+        (self.memory_limit / 2).min(u32::MAX as usize)
+    }
+
+    /// Save parsed files in batches as they arrive, until the channel closes
+    /// or the import is cancelled
+    async fn save_parsed(
+        &mut self,
+        graph_root: &Path,
+        mut rx: mpsc::Receiver<ParsedFile>,
+        mut duplicates: DuplicateGroups,
+        operation: ProgressOperation,
+        start_time: Instant,
+        progress_callback: Option<ProgressCallback>,
+    ) -> ImportResult<ImportSummary> {
+        let total_files = operation.total().unwrap_or(0);
+        // Track progress
+        let mut progress = ImportProgress::new(total_files);
+        let mut errors = Vec::new();
+        let mut pages_imported = 0;
+        let mut files_skipped = 0;
+        let mut pages_queued = 0;
+        let mut duplicate_titles = Vec::new();
+        let max_cost = self.max_file_cost();
+        let mut cancelled = false;
+
         // Collect results
-        while let Some((file_path, result)) = rx.recv().await {
-            match result {
-                Ok(page) => {
-                    // Save page to repository
-                    if let Err(e) = self.repository.save(page.clone()) {
-                        tracing::error!("Failed to save page from {}: {}", file_path.display(), e);
-                        errors.push((file_path.clone(), e.to_string()));
-                    } else {
-                        pages_imported += 1;
-                    }
+        let mut batch = ImportBatch::default();
+        while let Some(parsed) = rx.recv().await {
+            let file_path = parsed.file_path;
+            let resolved = match duplicates.by_file.get(&file_path) {
+                Some(&index) => {
+                    let group = &mut duplicates.groups[index];
+                    self.resolve_duplicate(group, file_path.clone(), parsed.result, &mut duplicate_titles)
+                        .await
                 }
-                Err(e) => {
-                    tracing::error!("Failed to parse {}: {}", file_path.display(), e);
-                    errors.push((file_path.clone(), e.to_string()));
+                None => vec![(file_path.clone(), parsed.result)],
+            };
+
+            // Files waiting for the rest of their group give their share of
+            // the budget back with this batch
+            batch.bytes += parsed.cost;
+            batch.permits.push(parsed.permit);
+            for (path, result) in resolved {
+                match result {
+                    Ok(FileImport::Parsed { page, content_hash }) => {
+                        batch.files.push((path, content_hash));
+                        batch.pages.push(page);
+                    }
+                    Ok(FileImport::Merged { content_hash }) => batch.files.push((path, content_hash)),
+                    Ok(FileImport::AlreadyImported) => files_skipped += 1,
+                    Err(e) => {
+                        tracing::error!("Failed to import {}: {}", path.display(), e);
+                        errors.push((path, e.to_string()));
+                    }
                 }
             }
 
             // Update progress
             progress.increment();
             progress.set_current_file(None);
+            operation.advance(progress.files_processed());
 
             // Emit progress event
             if let Some(ref callback) = progress_callback {
                 callback(ImportProgressEvent::FileProcessed {
-                    file_path: file_path.clone(),
+                    file_path,
                     progress: progress.clone(),
                 });
             }
+
+            if batch.pages.len() >= self.batch_size || batch.bytes >= max_cost {
+                let batch = std::mem::take(&mut batch);
+                pages_imported += self
+                    .save_batch(graph_root, batch, &mut errors, &mut pages_queued, progress_callback.as_ref())
+                    .await?;
+            }
+
+            // Dropping the receiver on the way out stops the readers too
+            if self.cancellation.is_cancelled() {
+                cancelled = true;
+                break;
+            }
         }
 
+        pages_imported += self
+            .save_batch(graph_root, batch, &mut errors, &mut pages_queued, progress_callback.as_ref())
+            .await?;
         let duration_ms = start_time.elapsed().as_millis() as u64;
+        operation.finish(progress.files_processed(), cancelled, !errors.is_empty());
 
         // Emit completion or failure event
         if let Some(ref callback) = progress_callback {
-            if errors.is_empty() {
+            if cancelled {
+                callback(ImportProgressEvent::Cancelled {
+                    pages_imported,
+                    files_processed: progress.files_processed(),
+                });
+            } else if errors.is_empty() {
                 callback(ImportProgressEvent::Completed {
                     pages_imported,
                     duration_ms,
@@ -152,10 +766,246 @@ impl<R: PageRepository> ImportService<R> {
         Ok(ImportSummary {
             total_files,
             pages_imported,
+            files_skipped,
+            pages_queued,
+            duplicate_titles,
             errors,
             duration_ms,
+            cancelled,
         })
     }
+
+    /// Apply the duplicate title policy to a file that shares its title with others
+    ///
+    /// Returns the files that are ready to be saved or reported; under the
+    /// merge policy that is nothing until the whole group has been read.
+    async fn resolve_duplicate(
+        &self,
+        group: &mut DuplicateGroup,
+        file_path: PathBuf,
+        mut result: ImportResult<FileImport>,
+        resolutions: &mut Vec<DuplicateTitleResolution>,
+    ) -> Vec<(PathBuf, ImportResult<FileImport>)> {
+        let rank = group.files.iter().position(|file| *file == file_path).unwrap_or(0);
+        let kept_by = group.files[0].clone();
+
+        match self.duplicate_title_policy {
+            DuplicateTitlePolicy::Rename => {
+                if let (Ok(FileImport::Parsed { page, .. }), Some(title)) =
+                    (&mut result, rank.checked_sub(1).map(|i| &group.renamed[i]))
+                {
+                    page.set_title(title.clone());
+                    let action = DuplicateTitleAction::Renamed { title: title.clone() };
+                    resolutions.push(resolution(group, &file_path, kept_by, action));
+                }
+                vec![(file_path, result)]
+            }
+            DuplicateTitlePolicy::Error => {
+                if rank > 0 && matches!(result, Ok(FileImport::Parsed { .. })) {
+                    resolutions.push(resolution(group, &file_path, kept_by.clone(), DuplicateTitleAction::Rejected));
+                    result = Err(ImportError::DuplicateTitle {
+                        title: group.title.clone(),
+                        kept_by,
+                    });
+                }
+                vec![(file_path, result)]
+            }
+            DuplicateTitlePolicy::Merge => {
+                group.remaining -= 1;
+                let mut ready = Vec::new();
+                match result {
+                    Ok(file) => group.arrived.push((file_path, file)),
+                    Err(e) => ready.push((file_path, Err(e))),
+                }
+                if group.remaining == 0 {
+                    ready.extend(self.merge_group(group, resolutions).await);
+                }
+                ready
+            }
+        }
+    }
+
+    /// Merge a fully read group into the page of its first readable file
+    ///
+    /// Unless every file is unchanged since an earlier import, unchanged files
+    /// are read again so the merged page has all of the group's blocks.
+    async fn merge_group(
+        &self,
+        group: &mut DuplicateGroup,
+        resolutions: &mut Vec<DuplicateTitleResolution>,
+    ) -> Vec<(PathBuf, ImportResult<FileImport>)> {
+        let mut arrived = std::mem::take(&mut group.arrived);
+        if arrived.iter().all(|(_, file)| matches!(file, FileImport::AlreadyImported)) {
+            return arrived.into_iter().map(|(path, file)| (path, Ok(file))).collect();
+        }
+        arrived.sort_by_key(|(path, _)| group.files.iter().position(|file| file == path));
+
+        let mut ready = Vec::new();
+        let mut merged: Option<(PathBuf, Page, String)> = None;
+        for (path, file) in arrived {
+            let file = match file {
+                FileImport::AlreadyImported => match read_file(&path, &HashMap::new()).await {
+                    Ok(file) => file,
+                    Err(e) => {
+                        ready.push((path, Err(e)));
+                        continue;
+                    }
+                },
+                file => file,
+            };
+            let FileImport::Parsed { page, content_hash } = file else {
+                continue;
+            };
+
+            match merged.as_mut() {
+                None => merged = Some((path, page, content_hash)),
+                Some((kept_by, target, _)) => match merge_blocks(target, &page) {
+                    Ok(()) => {
+                        let kept_by = kept_by.clone();
+                        resolutions.push(resolution(group, &path, kept_by, DuplicateTitleAction::Merged));
+                        ready.push((path, Ok(FileImport::Merged { content_hash })));
+                    }
+                    Err(e) => ready.push((path, Err(e.into()))),
+                },
+            }
+        }
+
+        if let Some((path, page, content_hash)) = merged {
+            ready.insert(0, (path, Ok(FileImport::Parsed { page, content_hash })));
+        }
+        ready
+    }
+
+    /// Save a batch of pages and checkpoint their files
+    ///
+    /// If the save fails, every file in the batch is reported as failed and
+    /// left without a checkpoint, so a resumed import retries all of them.
+    /// Returns the number of pages saved.
+    #[tracing::instrument(skip_all, fields(pages = batch.pages.len()))]
+    async fn save_batch(
+        &mut self,
+        graph_root: &Path,
+        batch: ImportBatch,
+        errors: &mut Vec<(PathBuf, String)>,
+        pages_queued: &mut usize,
+        progress_callback: Option<&ProgressCallback>,
+    ) -> ImportResult<usize> {
+        let ImportBatch { mut files, pages, .. } = batch;
+        if pages.is_empty() {
+            return Ok(0);
+        }
+
+        let page_ids: Vec<PageId> = pages.iter().map(|page| page.id().clone()).collect();
+        match self.repository.save_all(pages) {
+            Ok(()) => {
+                // Queue before checkpointing: a resumed import must not skip
+                // pages that were saved but never queued
+                self.queue_for_embedding(&page_ids, pages_queued, progress_callback)
+                    .await?;
+                self.checkpoint(graph_root, &mut files)?;
+                Ok(page_ids.len())
+            }
+            Err(e) => {
+                tracing::error!("Failed to save a batch of {} pages: {}", files.len(), e);
+                errors.extend(files.into_iter().map(|(path, _)| (path, e.to_string())));
+                Ok(0)
+            }
+        }
+    }
+
+    /// Queue saved pages to be embedded, if an embedding queue is set
+    async fn queue_for_embedding(
+        &self,
+        page_ids: &[PageId],
+        pages_queued: &mut usize,
+        progress_callback: Option<&ProgressCallback>,
+    ) -> ImportResult<()> {
+        let Some(jobs) = self.embedding_jobs.as_ref() else {
+            return Ok(());
+        };
+        if page_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut jobs = jobs.lock().await;
+        for page_id in page_ids {
+            jobs.enqueue(EmbeddingJobKind::Embed, page_id)?;
+        }
+        drop(jobs);
+
+        *pages_queued += page_ids.len();
+        if let Some(callback) = progress_callback {
+            callback(ImportProgressEvent::QueuedForEmbedding {
+                pages_queued: page_ids.len(),
+                total_queued: *pages_queued,
+            });
+        }
+        Ok(())
+    }
+
+    fn checkpoints(&mut self) -> Option<&mut (dyn ImportCheckpointRepository + Send + 'static)> {
+        let checkpoints = self.checkpoints.as_mut()?;
+        Some(checkpoints.get_mut().unwrap_or_else(PoisonError::into_inner).as_mut())
+    }
+
+    /// Record completed files, if checkpoints are enabled
+    fn checkpoint(
+        &mut self,
+        graph_root: &Path,
+        completed: &mut Vec<(PathBuf, String)>,
+    ) -> ImportResult<()> {
+        if let Some(checkpoints) = self.checkpoints() {
+            if !completed.is_empty() {
+                checkpoints.mark_imported(graph_root, completed)?;
+            }
+        }
+        completed.clear();
+        Ok(())
+    }
+}
+
+fn resolution(
+    group: &DuplicateGroup,
+    file_path: &Path,
+    kept_by: PathBuf,
+    action: DuplicateTitleAction,
+) -> DuplicateTitleResolution {
+    tracing::info!(
+        "{} has the same title as {} ({:?})",
+        file_path.display(),
+        kept_by.display(),
+        action
+    );
+    DuplicateTitleResolution {
+        file_path: file_path.to_path_buf(),
+        title: group.title.clone(),
+        kept_by,
+        action,
+    }
+}
+
+/// Read and parse a file, unless an earlier import already covered its contents
+async fn read_file(
+    file_path: &Path,
+    imported: &HashMap<PathBuf, String>,
+) -> ImportResult<FileImport> {
+    let bytes = tokio::fs::read(file_path).await?;
+    parse_file(file_path, &bytes, imported)
+}
+
+/// Parse a file's contents, unless an earlier import already covered them
+fn parse_file(
+    file_path: &Path,
+    bytes: &[u8],
+    imported: &HashMap<PathBuf, String>,
+) -> ImportResult<FileImport> {
+    let content_hash = format!("{:x}", Sha256::digest(bytes));
+    if imported.get(file_path) == Some(&content_hash) {
+        return Ok(FileImport::AlreadyImported);
+    }
+
+    let page = LogseqMarkdownParser::parse_file_content(file_path, &LogseqMarkdownParser::decode(bytes))?;
+    Ok(FileImport::Parsed { page, content_hash })
 }
 
 /// Summary of an import operation
@@ -163,8 +1013,17 @@ impl<R: PageRepository> ImportService<R> {
 pub struct ImportSummary {
     pub total_files: usize,
     pub pages_imported: usize,
+    /// Files left alone because an earlier import already imported their current contents
+    pub files_skipped: usize,
+    /// Saved pages queued for embedding (see [`ImportService::with_embedding_queue`])
+    pub pages_queued: usize,
+    /// Files whose page title another file already had, and what was done with them
+    pub duplicate_titles: Vec<DuplicateTitleResolution>,
     pub errors: Vec<(PathBuf, String)>,
     pub duration_ms: u64,
+    /// The import stopped early (see [`ImportService::with_cancellation`]);
+    /// the counts cover what was done until then
+    pub cancelled: bool,
 }
 
 impl ImportSummary {
@@ -172,7 +1031,12 @@ impl ImportSummary {
         if self.total_files == 0 {
             return 100.0;
         }
-        (self.pages_imported as f64 / self.total_files as f64) * 100.0
+        let files_merged = self
+            .duplicate_titles
+            .iter()
+            .filter(|duplicate| duplicate.action == DuplicateTitleAction::Merged)
+            .count();
+        ((self.pages_imported + self.files_skipped + files_merged) as f64 / self.total_files as f64) * 100.0
     }
 
     pub fn has_errors(&self) -> bool {
@@ -183,20 +1047,26 @@ impl ImportSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::services::import_validation::ValidationIssueKind;
+    use crate::application::services::progress::ProgressPhase;
     use crate::domain::aggregates::Page;
     use crate::domain::base::{DomainResult, Entity};
     use crate::domain::value_objects::PageId;
-    use std::collections::HashMap;
+    use crate::infrastructure::persistence::{
+        SqliteEmbeddingJobRepository, SqliteImportCheckpointRepository,
+    };
 
     // Mock repository for testing
     struct MockPageRepository {
         pages: HashMap<String, Page>,
+        batches_saved: usize,
     }
 
     impl MockPageRepository {
         fn new() -> Self {
             MockPageRepository {
                 pages: HashMap::new(),
+                batches_saved: 0,
             }
         }
     }
@@ -207,6 +1077,11 @@ mod tests {
             Ok(())
         }
 
+        fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+            self.batches_saved += 1;
+            pages.into_iter().try_for_each(|page| self.save(page))
+        }
+
         fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
             Ok(self.pages.get(id.as_str()).cloned())
         }
@@ -224,16 +1099,355 @@ mod tests {
         }
     }
 
+    fn graph_with_pages(pages: &[(&str, &str)]) -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pages_dir = temp_dir.path().join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(temp_dir.path().join("journals")).unwrap();
+        for (name, content) in pages {
+            std::fs::write(pages_dir.join(format!("{}.md", name)), content).unwrap();
+        }
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_files_already_imported() {
+        let graph = graph_with_pages(&[("a", "- A"), ("b", "- B"), ("c", "- C")]);
+        let directory = || LogseqDirectoryPath::new(graph.path()).unwrap();
+
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_checkpoints(SqliteImportCheckpointRepository::open_in_memory().unwrap());
+        let summary = service.import_directory(directory(), None).await.unwrap();
+        assert_eq!(summary.pages_imported, 3);
+
+        std::fs::write(graph.path().join("pages/b.md"), "- B, edited").unwrap();
+        let summary = service.resume_import(directory(), None).await.unwrap();
+        assert_eq!(summary.pages_imported, 1);
+        assert_eq!(summary.files_skipped, 2);
+        assert_eq!(summary.success_rate(), 100.0);
+
+        // A fresh import starts over
+        let summary = service.import_directory(directory(), None).await.unwrap();
+        assert_eq!(summary.pages_imported, 3);
+        assert_eq!(summary.files_skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_import_saves_what_it_parsed_and_can_resume() {
+        let graph = graph_with_pages(&[("a", "- A"), ("b", "- B"), ("c", "- C")]);
+        let directory = || LogseqDirectoryPath::new(graph.path()).unwrap();
+
+        let token = CancellationToken::new();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let callback: ProgressCallback = {
+            let (token, events) = (token.clone(), Arc::clone(&events));
+            Arc::new(move |event| {
+                match event {
+                    ImportProgressEvent::FileProcessed { .. } => token.cancel(),
+                    ImportProgressEvent::Cancelled { pages_imported, .. } => {
+                        events.lock().unwrap().push(pages_imported)
+                    }
+                    _ => {}
+                }
+            })
+        };
+
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_concurrency(1)
+            .with_batch_size(1)
+            .with_checkpoints(SqliteImportCheckpointRepository::open_in_memory().unwrap())
+            .with_cancellation(token);
+        let summary = service.import_directory(directory(), Some(callback)).await.unwrap();
+        assert!(summary.cancelled);
+        assert_eq!(summary.pages_imported, 1);
+        assert_eq!(*events.lock().unwrap(), vec![1]);
+
+        let mut service = service.with_cancellation(CancellationToken::new());
+        let summary = service.resume_import(directory(), None).await.unwrap();
+        assert!(!summary.cancelled);
+        assert_eq!(summary.pages_imported, 2);
+        assert_eq!(summary.files_skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_imports_report_progress_on_the_bus() {
+        let graph = graph_with_pages(&[("a", "- A"), ("b", "- B"), ("c", "- C")]);
+        let bus = ProgressBus::new();
+        let mut events = bus.subscribe();
+
+        let mut service = ImportService::new(MockPageRepository::new()).with_progress(bus);
+        service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+
+        let events: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        let counts: Vec<usize> = events.iter().map(|event| event.current).collect();
+        assert_eq!(counts, vec![0, 1, 2, 3, 3]);
+        assert_eq!(events[0].phase, ProgressPhase::Started);
+        assert_eq!(events[4].phase, ProgressPhase::Completed);
+        assert!(events.iter().all(|event| event.kind == OperationKind::Import && event.total == Some(3)));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_import_only_saves_changed_files() {
+        let graph = graph_with_pages(&[("a", "- A"), ("b", "- B")]);
+        let directory = || LogseqDirectoryPath::new(graph.path()).unwrap();
+
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_checkpoints(SqliteImportCheckpointRepository::open_in_memory().unwrap());
+        service.import_directory(directory(), None).await.unwrap();
+
+        let summary = service.import_directory_incremental(directory(), None).await.unwrap();
+        assert_eq!(summary.pages_imported, 0);
+        assert_eq!(summary.files_skipped, 2);
+
+        std::fs::remove_file(graph.path().join("pages/a.md")).unwrap();
+        std::fs::write(graph.path().join("pages/c.md"), "- C").unwrap();
+        let summary = service.import_directory_incremental(directory(), None).await.unwrap();
+        assert_eq!(summary.pages_imported, 1);
+        assert_eq!(summary.files_skipped, 1);
+
+        let checkpoints = service.checkpoints().unwrap();
+        let imported = checkpoints.imported_files(graph.path()).unwrap();
+        assert!(!imported.contains_key(&graph.path().join("pages/a.md")));
+        assert_eq!(imported.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_export_keeps_block_ids() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let export_path = temp_dir.path().join("graph.json");
+        std::fs::write(
+            &export_path,
+            r#"{"version": 1, "blocks": [
+                {"id": "6530a0f2-0000-4c1e-9b7e-000000000001", "page-name": "a",
+                 "children": [{"id": "6530a0f2-0000-4c1e-9b7e-000000000002", "content": "A"}]},
+                {"id": "6530a0f2-0000-4c1e-9b7e-000000000003", "page-name": "b", "children": []}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut service = ImportService::new(MockPageRepository::new());
+        let summary = service.import_export(&export_path, None).await.unwrap();
+        assert_eq!(summary.total_files, 2);
+        assert_eq!(summary.pages_imported, 2);
+
+        let page = service
+            .repository
+            .find_by_id(&PageId::new("6530a0f2-0000-4c1e-9b7e-000000000001").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(page.root_blocks()[0].id().as_str(), "6530a0f2-0000-4c1e-9b7e-000000000002");
+    }
+
+    #[tokio::test]
+    async fn test_validate_directory_reports_without_saving() {
+        let temp_dir = graph_with_pages(&[("a", "- Links to [[b"), ("A2", "- Fine"), ("empty", "")]);
+        std::fs::write(temp_dir.path().join("journals/a.md"), "- Same title as the page").unwrap();
+
+        let service = ImportService::new(MockPageRepository::new());
+        let report = service
+            .validate_directory(LogseqDirectoryPath::new(temp_dir.path()).unwrap(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(report.files_checked, 4);
+        assert!(service.repository.find_all().unwrap().is_empty());
+
+        let page_a = temp_dir.path().join("pages/a.md");
+        let kinds: Vec<_> = report.issues_for(&page_a).map(|issue| &issue.kind).collect();
+        assert!(matches!(kinds[..], [
+            ValidationIssueKind::MalformedReference { .. },
+            ValidationIssueKind::DuplicateTitle { .. },
+        ]));
+        assert!(report
+            .issues_for(&temp_dir.path().join("pages/empty.md"))
+            .any(|issue| issue.kind == ValidationIssueKind::EmptyPage));
+        assert_eq!(report.issues_for(&temp_dir.path().join("pages/A2.md")).count(), 0);
+    }
+
+    /// A graph whose `pages/Rust.md` and `journals/Rust.md` share a title
+    fn graph_with_duplicate_titles() -> tempfile::TempDir {
+        let temp_dir = graph_with_pages(&[("Rust", "- From pages"), ("Rust (2)", "- Taken")]);
+        std::fs::write(temp_dir.path().join("journals/Rust.md"), "- From journals").unwrap();
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_are_renamed_in_path_order() {
+        let graph = graph_with_duplicate_titles();
+        let mut service = ImportService::new(MockPageRepository::new()).with_concurrency(3);
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages_imported, 3);
+        assert_eq!(summary.duplicate_titles.len(), 1);
+        let duplicate = &summary.duplicate_titles[0];
+        assert_eq!(duplicate.file_path, graph.path().join("pages/Rust.md"));
+        assert_eq!(duplicate.kept_by, graph.path().join("journals/Rust.md"));
+        assert_eq!(duplicate.action, DuplicateTitleAction::Renamed { title: "Rust (3)".to_string() });
+
+        let renamed = service.repository.find_by_title("Rust (3)").unwrap().unwrap();
+        assert_eq!(renamed.root_blocks()[0].content().as_str(), "From pages");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_are_merged() {
+        let graph = graph_with_duplicate_titles();
+        let checkpoints = SqliteImportCheckpointRepository::open_in_memory().unwrap();
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_checkpoints(checkpoints)
+            .with_duplicate_title_policy(DuplicateTitlePolicy::Merge);
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages_imported, 2);
+        assert_eq!(summary.success_rate(), 100.0);
+        assert_eq!(summary.duplicate_titles[0].action, DuplicateTitleAction::Merged);
+
+        let merged = service.repository.find_by_title("Rust").unwrap().unwrap();
+        let contents: Vec<&str> = merged.root_blocks().iter().map(|block| block.content().as_str()).collect();
+        assert_eq!(contents, vec!["From journals", "From pages"]);
+
+        // Both files were checkpointed, so an unchanged re-import skips them
+        let summary = service
+            .import_directory_incremental(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(summary.files_skipped, 3);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_can_be_rejected() {
+        let graph = graph_with_duplicate_titles();
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_duplicate_title_policy(DuplicateTitlePolicy::Error);
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages_imported, 2);
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.errors[0].0, graph.path().join("pages/Rust.md"));
+        assert_eq!(summary.duplicate_titles[0].action, DuplicateTitleAction::Rejected);
+    }
+
+    #[tokio::test]
+    async fn test_import_archive_reads_zipped_graph() {
+        use std::io::Write;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("backup.zip");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        for (name, content) in [
+            ("graph/pages/a.md", "- A"),
+            ("graph/pages/b.md", "- B [[a]]"),
+            ("graph/journals/2024_01_01.md", "- Journal"),
+            ("graph/logseq/.recycle/old.md", "- Trashed"),
+        ] {
+            writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut service = ImportService::new(MockPageRepository::new()).with_batch_size(2);
+        let summary = service.import_archive(&archive_path, None).await.unwrap();
+
+        assert_eq!(summary.total_files, 3);
+        assert_eq!(summary.pages_imported, 3);
+        assert!(!summary.has_errors());
+        assert!(service.repository.find_by_title("b").unwrap().is_some());
+        assert!(service.repository.find_by_title("old").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_import_saves_in_batches_within_memory_limit() {
+        let names: Vec<String> = (0..10).map(|i| format!("page{}", i)).collect();
+        let pages: Vec<(&str, &str)> =
+            names.iter().map(|name| (name.as_str(), "- Twenty bytes long..")).collect();
+        let graph = graph_with_pages(&pages);
+
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_concurrency(3)
+            .with_batch_size(4);
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(summary.pages_imported, 10);
+        assert_eq!(service.repository.batches_saved, 3);
+
+        // A budget smaller than one file still makes progress, one page at a time
+        let mut service = ImportService::new(MockPageRepository::new()).with_memory_limit(16);
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(summary.pages_imported, 10);
+        assert_eq!(service.repository.batches_saved, 10);
+    }
+
+    #[tokio::test]
+    async fn test_saved_pages_are_queued_for_embedding() {
+        let graph = graph_with_pages(&[("a", "- A"), ("b", "- B"), ("c", "- C")]);
+        let jobs = Arc::new(Mutex::new(
+            SqliteEmbeddingJobRepository::open_in_memory().unwrap(),
+        ));
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let callback: ProgressCallback = {
+            let events = Arc::clone(&events);
+            Arc::new(move |event| {
+                if let ImportProgressEvent::QueuedForEmbedding { total_queued, .. } = event {
+                    events.lock().unwrap().push(total_queued);
+                }
+            })
+        };
+
+        let mut service = ImportService::new(MockPageRepository::new())
+            .with_batch_size(2)
+            .with_embedding_queue(jobs.clone());
+        let summary = service
+            .import_directory(LogseqDirectoryPath::new(graph.path()).unwrap(), Some(callback))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages_queued, 3);
+        assert_eq!(*events.lock().unwrap(), vec![2, 3]);
+        assert_eq!(jobs.lock().await.stats().unwrap().pending, 3);
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_checkpoints() {
+        let graph = graph_with_pages(&[("a", "- A")]);
+        let mut service = ImportService::new(MockPageRepository::new());
+
+        let result = service
+            .resume_import(LogseqDirectoryPath::new(graph.path()).unwrap(), None)
+            .await;
+        assert!(matches!(result, Err(ImportError::CheckpointsUnavailable)));
+    }
+
     #[test]
     fn test_import_summary() {
         let summary = ImportSummary {
             total_files: 10,
             pages_imported: 8,
+            files_skipped: 0,
+            pages_queued: 0,
+            duplicate_titles: Vec::new(),
             errors: vec![
                 (PathBuf::from("file1.md"), "error 1".to_string()),
                 (PathBuf::from("file2.md"), "error 2".to_string()),
             ],
             duration_ms: 1000,
+            cancelled: false,
         };
 
         assert_eq!(summary.success_rate(), 80.0);
@@ -0,0 +1,287 @@
+/// Structural checks run over a graph before it is imported
+use crate::domain::aggregates::Page;
+use crate::domain::value_objects::BlockId;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Blocks longer than this, in characters, are reported as oversized by default
+pub const DEFAULT_MAX_BLOCK_LENGTH: usize = 10_000;
+
+/// Longest excerpt of a malformed reference kept in a report
+const EXCERPT_LENGTH: usize = 40;
+
+/// A structural problem found in one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssueKind {
+    /// The file couldn't be read or parsed
+    Unreadable { error: String },
+    /// Another file has the same page title (titles are compared case-insensitively)
+    DuplicateTitle { title: String, other_files: Vec<PathBuf> },
+    /// The page has no blocks with any content
+    EmptyPage,
+    /// A `[[page]]` or `((block))` reference that isn't closed, is empty or
+    /// doesn't point at a block id
+    MalformedReference { block_id: BlockId, excerpt: String },
+    /// A block longer than the configured limit
+    OversizedBlock { block_id: BlockId, length: usize, limit: usize },
+}
+
+impl fmt::Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssueKind::Unreadable { error } => write!(f, "could not be parsed: {}", error),
+            ValidationIssueKind::DuplicateTitle { title, other_files } => write!(
+                f,
+                "title \"{}\" is also used by {} other file(s)",
+                title,
+                other_files.len()
+            ),
+            ValidationIssueKind::EmptyPage => write!(f, "page has no content"),
+            ValidationIssueKind::MalformedReference { block_id, excerpt } => {
+                write!(f, "malformed reference \"{}\" in block {}", excerpt, block_id.as_str())
+            }
+            ValidationIssueKind::OversizedBlock { block_id, length, limit } => write!(
+                f,
+                "block {} is {} characters long (limit {})",
+                block_id.as_str(),
+                length,
+                limit
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub file_path: PathBuf,
+    pub kind: ValidationIssueKind,
+}
+
+/// Result of a dry-run import
+#[derive(Debug)]
+pub struct ValidationReport {
+    pub files_checked: usize,
+    pub issues: Vec<ValidationIssue>,
+    pub duration_ms: u64,
+}
+
+impl ValidationReport {
+    /// Whether the graph can be imported without any reported problems
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Issues for a single file
+    pub fn issues_for(&self, file_path: &Path) -> impl Iterator<Item = &ValidationIssue> {
+        let file_path = file_path.to_path_buf();
+        self.issues.iter().filter(move |issue| issue.file_path == file_path)
+    }
+}
+
+/// Collects issues page by page; duplicate titles are found once all pages are in
+pub(crate) struct GraphValidator {
+    max_block_length: usize,
+    issues: Vec<ValidationIssue>,
+    /// Lowercased title to the title as written and the files using it
+    titles: HashMap<String, (String, Vec<PathBuf>)>,
+    files_checked: usize,
+}
+
+impl GraphValidator {
+    pub(crate) fn new(max_block_length: usize) -> Self {
+        GraphValidator {
+            max_block_length,
+            issues: Vec::new(),
+            titles: HashMap::new(),
+            files_checked: 0,
+        }
+    }
+
+    pub(crate) fn unreadable(&mut self, file_path: PathBuf, error: String) {
+        self.files_checked += 1;
+        self.issues.push(ValidationIssue {
+            file_path,
+            kind: ValidationIssueKind::Unreadable { error },
+        });
+    }
+
+    /// Check one page; the page itself is dropped afterwards
+    pub(crate) fn check_page(&mut self, file_path: PathBuf, page: &Page) {
+        self.files_checked += 1;
+
+        let mut report = |kind| {
+            self.issues.push(ValidationIssue {
+                file_path: file_path.clone(),
+                kind,
+            })
+        };
+
+        if page.all_blocks().all(|block| block.content().as_str().trim().is_empty()) {
+            report(ValidationIssueKind::EmptyPage);
+        }
+
+        let mut blocks: Vec<_> = page.all_blocks().collect();
+        blocks.sort_by(|a, b| a.id().as_str().cmp(b.id().as_str()));
+        for block in blocks {
+            let content = block.content().as_str();
+            let length = content.chars().count();
+            if length > self.max_block_length {
+                report(ValidationIssueKind::OversizedBlock {
+                    block_id: block.id().clone(),
+                    length,
+                    limit: self.max_block_length,
+                });
+            }
+            for excerpt in malformed_references(content) {
+                report(ValidationIssueKind::MalformedReference {
+                    block_id: block.id().clone(),
+                    excerpt,
+                });
+            }
+        }
+
+        self.titles
+            .entry(page.title().to_lowercase())
+            .or_insert_with(|| (page.title().to_string(), Vec::new()))
+            .1
+            .push(file_path);
+    }
+
+    pub(crate) fn finish(mut self, duration_ms: u64) -> ValidationReport {
+        let mut duplicates: Vec<_> = self
+            .titles
+            .into_values()
+            .filter(|(_, files)| files.len() > 1)
+            .collect();
+        duplicates.sort();
+
+        for (title, files) in duplicates {
+            for file_path in &files {
+                let other_files = files.iter().filter(|other| *other != file_path).cloned().collect();
+                self.issues.push(ValidationIssue {
+                    file_path: file_path.clone(),
+                    kind: ValidationIssueKind::DuplicateTitle {
+                        title: title.clone(),
+                        other_files,
+                    },
+                });
+            }
+        }
+
+        ValidationReport {
+            files_checked: self.files_checked,
+            issues: self.issues,
+            duration_ms,
+        }
+    }
+}
+
+/// Excerpts of the malformed references in a block's text
+///
+/// Page references may nest (`[[a [[b]]]]`); text in inline code is skipped.
+fn malformed_references(content: &str) -> Vec<String> {
+    let mut malformed = Vec::new();
+    let mut open_pages: Vec<usize> = Vec::new();
+    let mut position = 0;
+
+    while position < content.len() {
+        let rest = &content[position..];
+        if let Some(code) = rest.strip_prefix('`') {
+            position += code.find('`').map_or(rest.len(), |end| end + 2);
+        } else if rest.starts_with("[[") {
+            open_pages.push(position);
+            position += 2;
+        } else if rest.starts_with("]]") {
+            match open_pages.pop() {
+                Some(start) if content[start + 2..position].trim().is_empty() => {
+                    malformed.push(excerpt(&content[start..position + 2]));
+                }
+                Some(_) => {}
+                None => malformed.push(excerpt(&content[..position + 2])),
+            }
+            position += 2;
+        } else if let Some(reference) = rest.strip_prefix("((") {
+            // Only text made of hex digits and dashes is taken for a block
+            // reference; other double parentheses are left alone
+            let inner = reference.find("))").map(|end| &reference[..end]);
+            match inner {
+                Some(inner) if looks_like_block_id(inner) => {
+                    if uuid::Uuid::parse_str(inner).is_err() {
+                        malformed.push(excerpt(&rest[..inner.len() + 4]));
+                    }
+                    position += inner.len() + 4;
+                }
+                _ => position += 2,
+            }
+        } else {
+            position += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    malformed.extend(open_pages.into_iter().map(|start| excerpt(&content[start..])));
+    malformed
+}
+
+fn looks_like_block_id(text: &str) -> bool {
+    text.contains('-') && text.chars().all(|ch| ch.is_ascii_hexdigit() || ch == '-')
+}
+
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(EXCERPT_LENGTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    fn page(title: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(title).unwrap(), title.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_malformed_references() {
+        assert!(malformed_references("See [[rust]] and [[a [[nested]] page]]").is_empty());
+        assert!(malformed_references("Code `[[not a ref` stays quiet").is_empty());
+        assert!(malformed_references("((6530a0f2-0000-4c1e-9b7e-00000000000a)) ok").is_empty());
+        assert!(malformed_references("f((x)) and ((not a ref))").is_empty());
+
+        assert_eq!(malformed_references("Open [[rust"), vec!["[[rust"]);
+        assert_eq!(malformed_references("Empty [[ ]] ref"), vec!["[[ ]]"]);
+        assert_eq!(malformed_references("Stray]] close"), vec!["Stray]]"]);
+        assert_eq!(malformed_references("Bad ((6530a0f2-0000-4c1e))"), vec!["((6530a0f2-0000-4c1e))"]);
+    }
+
+    #[test]
+    fn test_reports_each_kind_of_issue() {
+        let mut validator = GraphValidator::new(20);
+        validator.check_page(PathBuf::from("pages/rust.md"), &page("Rust", "- See [[memory"));
+        validator.check_page(PathBuf::from("pages/RUST.md"), &page("RUST", "- A block that is far too long"));
+        validator.check_page(PathBuf::from("pages/empty.md"), &page("empty", ""));
+        validator.unreadable(PathBuf::from("pages/broken.md"), "invalid UTF-8".to_string());
+
+        let report = validator.finish(0);
+        assert_eq!(report.files_checked, 4);
+        assert!(!report.is_clean());
+
+        let kinds = |path: &str| -> Vec<ValidationIssueKind> {
+            report.issues_for(Path::new(path)).map(|issue| issue.kind.clone()).collect()
+        };
+        assert!(matches!(kinds("pages/rust.md")[..], [
+            ValidationIssueKind::MalformedReference { .. },
+            ValidationIssueKind::DuplicateTitle { .. },
+        ]));
+        assert!(matches!(kinds("pages/RUST.md")[..], [
+            ValidationIssueKind::OversizedBlock { length: 28, limit: 20, .. },
+            ValidationIssueKind::DuplicateTitle { .. },
+        ]));
+        assert_eq!(kinds("pages/empty.md"), vec![ValidationIssueKind::EmptyPage]);
+        assert!(matches!(kinds("pages/broken.md")[..], [ValidationIssueKind::Unreadable { .. }]));
+    }
+}
@@ -0,0 +1,105 @@
+/// Detection of the blocks a journal template repeats on every journal page
+use crate::application::use_cases::page_names::journal_date;
+use crate::domain::aggregates::Page;
+use std::collections::{HashMap, HashSet};
+
+/// Journal pages a block must be on to count as a template's, however few
+/// journals there are
+const MIN_JOURNALS: usize = 3;
+/// Share of journal pages a block must be on to count as a template's
+const MIN_SHARE: f32 = 0.2;
+
+/// Blocks repeated across journal pages, like the "Morning routine" and
+/// "Gratitude" headings a journal template adds to every day
+///
+/// A block is a template's when the same content, ignoring case and
+/// whitespace, is on at least three journal pages and a fifth of them.
+/// Only the repeated blocks themselves are; what's written under them
+/// differs from day to day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JournalTemplates {
+    /// Normalized contents of the template blocks
+    blocks: HashSet<String>,
+}
+
+impl JournalTemplates {
+    /// The template blocks of the journal pages among `pages`
+    pub fn detect(pages: &[Page]) -> Self {
+        let journals: Vec<&Page> = pages.iter().filter(|page| journal_date(page).is_some()).collect();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for journal in &journals {
+            let blocks: HashSet<String> = journal
+                .all_blocks()
+                .map(|block| normalize(block.content().as_str()))
+                .filter(|content| !content.is_empty())
+                .collect();
+            for content in blocks {
+                *counts.entry(content).or_default() += 1;
+            }
+        }
+
+        let needed = MIN_JOURNALS.max((journals.len() as f32 * MIN_SHARE).ceil() as usize);
+        JournalTemplates {
+            blocks: counts
+                .into_iter()
+                .filter(|(_, count)| *count >= needed)
+                .map(|(content, _)| content)
+                .collect(),
+        }
+    }
+
+    /// Whether a block with `content` is a template's
+    pub fn contains(&self, content: &str) -> bool {
+        !self.blocks.is_empty() && self.blocks.contains(&normalize(content))
+    }
+
+    /// The template blocks, lowercased, in alphabetical order
+    pub fn blocks(&self) -> Vec<&str> {
+        let mut blocks: Vec<&str> = self.blocks.iter().map(String::as_str).collect();
+        blocks.sort_unstable();
+        blocks
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+fn normalize(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    fn page(title: &str, content: &str) -> Page {
+        let id = PageId::new(title.replace(' ', "-")).unwrap();
+        LogseqMarkdownParser::parse_content(content, id, title.into()).unwrap()
+    }
+
+    #[test]
+    fn test_detects_blocks_repeated_across_journals() {
+        let mut pages = vec![page("Morning routine", "- Morning routine\n- Gratitude")];
+        for (day, note) in [(1, "Coffee"), (2, "Tea"), (3, "Coffee"), (4, "Walk"), (5, "Read")] {
+            let content = format!("- Morning routine\n\t- {}\n- gratitude \n\t- Family", note);
+            pages.push(page(&format!("2024_03_0{}", day), &content));
+        }
+        pages.push(page("2024_03_06", "- Off day"));
+
+        let templates = JournalTemplates::detect(&pages);
+        // "Coffee" is on two journals only; "Family" is on every one
+        assert_eq!(templates.blocks(), ["family", "gratitude", "morning routine"]);
+        assert!(templates.contains("Morning  Routine"));
+        assert!(!templates.contains("Coffee"));
+
+        // Too few journals to tell a template from a coincidence
+        assert!(JournalTemplates::detect(&pages[..3]).is_empty());
+    }
+}
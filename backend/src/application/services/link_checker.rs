@@ -0,0 +1,412 @@
+/// Dead link checker that requests stored URLs and records what they answer
+use crate::application::dto::PageConnection;
+use crate::application::repositories::{PageRepository, RepositoryError, UrlCheckRepository};
+use crate::domain::{aggregates::Page, base::Entity, DomainError, DomainResult};
+use chrono::{DateTime, Utc};
+use reqwest::{header::LOCATION, redirect, Method, StatusCode};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+/// How long one request may take before the URL counts as unreachable
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Requests in flight at once unless `with_concurrency` says otherwise
+const DEFAULT_CONCURRENCY: usize = 8;
+/// Time between requests to the same host unless `with_host_interval` says otherwise
+const DEFAULT_HOST_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a check is reused unless `with_max_age` says otherwise
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Redirects followed before giving up on a URL
+const MAX_REDIRECTS: usize = 10;
+
+/// The latest result of requesting a URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlCheck {
+    pub url: String,
+    /// Status of the last answer, after following redirects; none if nothing answered
+    pub status: Option<u16>,
+    /// Where the URL redirected to, if it did
+    pub redirected_to: Option<String>,
+    /// Why no answer came, or why redirects were given up on
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl UrlCheck {
+    /// Whether the URL is dead: it didn't answer, or answered with an error
+    ///
+    /// `429 Too Many Requests` says nothing about the page, so it isn't broken.
+    pub fn is_broken(&self) -> bool {
+        match self.status {
+            Some(status) => self.error.is_some() || (status >= 400 && status != 429),
+            None => true,
+        }
+    }
+}
+
+/// A broken URL and the pages that link to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenLink {
+    pub check: UrlCheck,
+    pub pages: Vec<PageConnection>,
+}
+
+/// What a run of the checker did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkCheckSummary {
+    /// URLs requested
+    pub checked: usize,
+    /// URLs skipped because they were checked recently
+    pub cached: usize,
+    /// URLs found broken, requested or cached
+    pub broken: usize,
+}
+
+/// Requests URLs to find the dead ones, recording every result
+///
+/// Each URL is requested with `HEAD`, falling back to `GET` for servers
+/// that don't allow `HEAD`, and redirects are followed by hand so where a
+/// URL ends up is recorded. Requests are rate-limited: at most
+/// `concurrency` run at once and requests to the same host are spaced
+/// `host_interval` apart. URLs checked less than `max_age` ago aren't
+/// requested again.
+pub struct LinkChecker {
+    checks: Mutex<Box<dyn UrlCheckRepository + Send>>,
+    client: reqwest::Client,
+    concurrency: usize,
+    host_interval: Duration,
+    max_age: Duration,
+}
+
+impl LinkChecker {
+    pub fn new(checks: impl UrlCheckRepository + Send + 'static) -> Self {
+        LinkChecker {
+            checks: Mutex::new(Box::new(checks)),
+            client: client(DEFAULT_REQUEST_TIMEOUT),
+            concurrency: DEFAULT_CONCURRENCY,
+            host_interval: DEFAULT_HOST_INTERVAL,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the time between requests to the same host
+    pub fn with_host_interval(mut self, host_interval: Duration) -> Self {
+        self.host_interval = host_interval;
+        self
+    }
+
+    /// Set how long a check is reused before the URL is requested again
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The latest check of a URL, if it was checked
+    pub fn check_of(&self, url: &str) -> DomainResult<Option<UrlCheck>> {
+        self.checks()?.get(url)
+    }
+
+    /// Check every URL of every page
+    pub async fn check_pages<R: PageRepository>(&self, repository: &R) -> DomainResult<LinkCheckSummary> {
+        self.check_urls(page_urls(&repository.find_all()?)).await
+    }
+
+    /// Check URLs, requesting those not checked within `max_age`
+    pub async fn check_urls(&self, urls: impl IntoIterator<Item = String>) -> DomainResult<LinkCheckSummary> {
+        let urls: BTreeSet<String> = urls.into_iter().collect();
+        let mut summary = LinkCheckSummary::default();
+        let fresh_since = Utc::now() - chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let hosts = Arc::new(HostSchedule::new(self.host_interval));
+        let mut requests = JoinSet::new();
+        for url in urls {
+            match self.check_of(&url)? {
+                Some(check) if check.checked_at >= fresh_since => {
+                    summary.cached += 1;
+                    summary.broken += usize::from(check.is_broken());
+                    continue;
+                }
+                _ => {}
+            }
+            let (client, semaphore, hosts) = (self.client.clone(), Arc::clone(&semaphore), Arc::clone(&hosts));
+            requests.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("the semaphore is never closed");
+                check_url(&client, &hosts, url).await
+            });
+        }
+
+        while let Some(result) = requests.join_next().await {
+            let check = result.map_err(|e| DomainError::InvalidOperation(format!("URL check task failed: {}", e)))?;
+            if check.is_broken() {
+                tracing::debug!("Broken link {}: {:?} {:?}", check.url, check.status, check.error);
+            }
+            summary.checked += 1;
+            summary.broken += usize::from(check.is_broken());
+            self.checks()?.save(&check)?;
+        }
+        Ok(summary)
+    }
+
+    /// Every URL of the pages whose latest check found it broken, with the
+    /// pages linking to it, by URL
+    ///
+    /// URLs never checked aren't reported; run [`check_pages`](Self::check_pages) first.
+    pub fn broken_links<R: PageRepository>(&self, repository: &R) -> DomainResult<Vec<BrokenLink>> {
+        let pages = repository.find_all()?;
+        let mut broken: Vec<BrokenLink> = Vec::new();
+        for url in page_urls(&pages) {
+            let Some(check) = self.check_of(&url)?.filter(UrlCheck::is_broken) else {
+                continue;
+            };
+            let pages = pages
+                .iter()
+                .filter_map(|page| {
+                    let blocks_with_url: Vec<_> = page
+                        .all_blocks()
+                        .filter(|block| block.urls().iter().any(|block_url| block_url.as_str() == url))
+                        .map(|block| block.id().clone())
+                        .collect();
+                    (!blocks_with_url.is_empty()).then(|| PageConnection {
+                        page_id: page.id().clone(),
+                        page_title: page.title().to_string(),
+                        blocks_with_url,
+                    })
+                })
+                .collect();
+            broken.push(BrokenLink { check, pages });
+        }
+        Ok(broken)
+    }
+
+    fn checks(&self) -> DomainResult<std::sync::MutexGuard<'_, Box<dyn UrlCheckRepository + Send>>> {
+        self.checks
+            .lock()
+            .map_err(|_| RepositoryError::LockPoisoned("URL check store").into())
+    }
+}
+
+fn client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(redirect::Policy::none())
+        .build()
+        .unwrap_or_default()
+}
+
+/// The URLs of every block of the pages, in order
+pub(crate) fn page_urls(pages: &[Page]) -> BTreeSet<String> {
+    pages
+        .iter()
+        .flat_map(|page| page.all_urls().into_iter().map(|url| url.as_str().to_string()))
+        .collect()
+}
+
+/// When each host may next be requested
+pub(crate) struct HostSchedule {
+    interval: Duration,
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostSchedule {
+    pub(crate) fn new(interval: Duration) -> Self {
+        HostSchedule {
+            interval,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for the host's turn, booking the turn after it
+    pub(crate) async fn wait(&self, host: &str) {
+        let turn = {
+            let mut next = self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            let turn = next.get(host).copied().filter(|turn| *turn > now).unwrap_or(now);
+            next.insert(host.to_string(), turn + self.interval);
+            turn
+        };
+        tokio::time::sleep_until(turn).await;
+    }
+}
+
+/// Request a URL, following redirects
+async fn check_url(client: &reqwest::Client, hosts: &HostSchedule, url: String) -> UrlCheck {
+    let mut check = UrlCheck {
+        url: url.clone(),
+        status: None,
+        redirected_to: None,
+        error: None,
+        checked_at: Utc::now(),
+    };
+    let mut current = match reqwest::Url::parse(&url) {
+        Ok(current) => current,
+        Err(e) => {
+            check.error = Some(format!("Invalid URL: {}", e));
+            return check;
+        }
+    };
+
+    for _ in 0..=MAX_REDIRECTS {
+        hosts.wait(current.host_str().unwrap_or_default()).await;
+        let location = match request(client, &current).await {
+            Ok((status, location)) => {
+                check.status = Some(status.as_u16());
+                match location.filter(|_| status.is_redirection()) {
+                    Some(location) => location,
+                    None => return check,
+                }
+            }
+            Err(e) => {
+                check.status = None;
+                check.error = Some(e.to_string());
+                return check;
+            }
+        };
+        match current.join(&location) {
+            Ok(next) => {
+                check.redirected_to = Some(next.to_string());
+                current = next;
+            }
+            Err(e) => {
+                check.error = Some(format!("Invalid redirect to '{}': {}", location, e));
+                return check;
+            }
+        }
+    }
+    check.error = Some(format!("More than {} redirects", MAX_REDIRECTS));
+    check
+}
+
+/// `HEAD` a URL, or `GET` it if the server doesn't allow `HEAD`: its status and `Location`
+async fn request(client: &reqwest::Client, url: &reqwest::Url) -> reqwest::Result<(StatusCode, Option<String>)> {
+    let mut response = client.request(Method::HEAD, url.clone()).send().await?;
+    if matches!(response.status(), StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED) {
+        response = client.get(url.clone()).send().await?;
+    }
+    let location = response
+        .headers()
+        .get(LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    Ok((response.status(), location))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::{InMemoryPageRepository, SqliteUrlCheckRepository};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `/ok`, `/moved` (to `/ok`), `/gone` and `/head-refused` (405
+    /// to `HEAD`), counting the requests received
+    async fn server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let line = request.lines().next().unwrap_or_default().to_string();
+                    let response = match line.split(' ').take(2).collect::<Vec<_>>()[..] {
+                        [_, "/ok"] => "200 OK\r\n",
+                        [_, "/moved"] => "301 Moved Permanently\r\nLocation: /ok\r\n",
+                        ["HEAD", "/head-refused"] => "405 Method Not Allowed\r\n",
+                        ["GET", "/head-refused"] => "200 OK\r\n",
+                        _ => "404 Not Found\r\n",
+                    };
+                    let response = format!("HTTP/1.1 {}Content-Length: 0\r\nConnection: close\r\n\r\n", response);
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        (base, requests)
+    }
+
+    fn repository(base: &str) -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("a", "A", format!("- Docs {base}/ok\n- Old {base}/moved\n- Dead {base}/gone")),
+            ("b", "B", format!("- Also dead {base}/gone\n- Fussy {base}/head-refused")),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(&content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    #[tokio::test]
+    async fn test_checks_urls_and_reports_broken_links() {
+        let (base, _) = server().await;
+        let repo = repository(&base);
+        let checker =
+            LinkChecker::new(SqliteUrlCheckRepository::open_in_memory().unwrap()).with_host_interval(Duration::ZERO);
+
+        let summary = checker.check_pages(&repo).await.unwrap();
+        assert_eq!(
+            summary,
+            LinkCheckSummary {
+                checked: 4,
+                cached: 0,
+                broken: 1
+            }
+        );
+
+        let moved = checker.check_of(&format!("{base}/moved")).unwrap().unwrap();
+        assert_eq!(moved.status, Some(200));
+        assert_eq!(moved.redirected_to, Some(format!("{base}/ok")));
+        assert!(!checker.check_of(&format!("{base}/head-refused")).unwrap().unwrap().is_broken());
+
+        let broken = checker.broken_links(&repo).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].check.url, format!("{base}/gone"));
+        assert_eq!(broken[0].check.status, Some(404));
+        let mut titles: Vec<&str> = broken[0].pages.iter().map(|page| page.page_title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["A", "B"]);
+    }
+
+    #[tokio::test]
+    async fn test_reuses_recent_checks_and_records_unreachable_urls() {
+        let (base, requests) = server().await;
+        let checker =
+            LinkChecker::new(SqliteUrlCheckRepository::open_in_memory().unwrap()).with_host_interval(Duration::ZERO);
+
+        checker.check_urls([format!("{base}/gone")]).await.unwrap();
+        let summary = checker.check_urls([format!("{base}/gone")]).await.unwrap();
+        assert_eq!((summary.checked, summary.cached, summary.broken), (0, 1, 1));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let checker = checker.with_max_age(Duration::ZERO);
+        assert_eq!(checker.check_urls([format!("{base}/gone")]).await.unwrap().checked, 1);
+
+        // Nothing listens on port 9 of localhost
+        let summary = checker.check_urls(["http://127.0.0.1:9/".to_string()]).await.unwrap();
+        assert_eq!(summary.broken, 1);
+        let unreachable = checker.check_of("http://127.0.0.1:9/").unwrap().unwrap();
+        assert_eq!(unreachable.status, None);
+        assert!(unreachable.error.is_some());
+    }
+}
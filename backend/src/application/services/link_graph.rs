@@ -0,0 +1,628 @@
+/// Graph of the links between pages, typed by how they link
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::PageId;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// How one page links to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeType {
+    /// A `[[page]]` link
+    PageRef,
+    /// A `#tag`
+    Tag,
+    /// A `((block-uuid))` reference to a block of the other page
+    BlockRef,
+    /// Both pages contain the same URL; recorded in both directions
+    UrlShared,
+}
+
+impl EdgeType {
+    pub const ALL: [EdgeType; 4] = [EdgeType::PageRef, EdgeType::Tag, EdgeType::BlockRef, EdgeType::UrlShared];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeType::PageRef => "page_ref",
+            EdgeType::Tag => "tag",
+            EdgeType::BlockRef => "block_ref",
+            EdgeType::UrlShared => "url_shared",
+        }
+    }
+}
+
+impl FromStr for EdgeType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        EdgeType::ALL
+            .into_iter()
+            .find(|edge_type| edge_type.as_str() == name)
+            .ok_or_else(|| format!("Unknown edge type '{}'", name))
+    }
+}
+
+/// A page of the graph
+///
+/// Pages that are linked to but have no file of their own, as Logseq
+/// allows, have no id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkNode {
+    pub title: String,
+    pub page_id: Option<PageId>,
+}
+
+/// Links of one type from one page to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkEdge {
+    pub source: String,
+    pub target: String,
+    pub edge_type: EdgeType,
+    /// Links of this type: references for page refs, tags and block refs,
+    /// distinct URLs for shared URLs
+    pub count: usize,
+}
+
+/// Which edges a neighbor query follows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Pages the page links to
+    Outgoing,
+    /// Pages that link to the page
+    Incoming,
+    #[default]
+    Both,
+}
+
+/// Pages to find around a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeighborQuery {
+    /// Links followed from the page; 1 finds only direct neighbors
+    pub max_depth: usize,
+    pub direction: Direction,
+    /// Edge types followed; every type when empty
+    pub edge_types: Vec<EdgeType>,
+}
+
+impl Default for NeighborQuery {
+    fn default() -> Self {
+        NeighborQuery {
+            max_depth: 1,
+            direction: Direction::default(),
+            edge_types: Vec::new(),
+        }
+    }
+}
+
+impl NeighborQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_edge_types(mut self, edge_types: impl IntoIterator<Item = EdgeType>) -> Self {
+        self.edge_types = edge_types.into_iter().collect();
+        self
+    }
+
+    fn follows(&self, edge_type: EdgeType) -> bool {
+        self.edge_types.is_empty() || self.edge_types.contains(&edge_type)
+    }
+}
+
+/// A page found by a neighbor query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbor {
+    pub node: LinkNode,
+    /// Links between it and the queried page
+    pub depth: usize,
+    /// Links followed to reach it from pages one step closer
+    pub weight: usize,
+}
+
+/// Every link between the pages of a graph
+///
+/// Pages are identified by title, compared case-insensitively as Logseq
+/// does, since references name pages rather than their ids. Links from a
+/// page to itself are left out. The graph is a snapshot: build it again
+/// when pages change.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkGraph {
+    /// Nodes by lowercased title
+    nodes: BTreeMap<String, LinkNode>,
+    /// Link counts by source, then by target and type
+    outgoing: HashMap<String, BTreeMap<(String, EdgeType), usize>>,
+    /// Link counts by target, then by source and type
+    incoming: HashMap<String, BTreeMap<(String, EdgeType), usize>>,
+}
+
+impl LinkGraph {
+    /// Build the graph of a set of pages
+    pub fn build<'a>(pages: impl IntoIterator<Item = &'a Page>) -> Self {
+        let pages: Vec<&Page> = pages.into_iter().collect();
+        let mut graph = LinkGraph::default();
+        for page in &pages {
+            graph.add_node(page.title(), Some(page.id().clone()));
+        }
+
+        let block_pages = block_uuids(&pages);
+        let mut url_pages: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+        for page in &pages {
+            let source = key(page.title());
+            for block in page.all_blocks() {
+                for reference in block.page_references() {
+                    let edge_type = if reference.is_tag() { EdgeType::Tag } else { EdgeType::PageRef };
+                    let target = graph.add_node(reference.title(), None);
+                    graph.add_edge(&source, &target, edge_type, 1);
+                }
+                for uuid in block_references(block.content().as_str()) {
+                    if let Some(target) = block_pages.get(uuid.as_str()) {
+                        graph.add_edge(&source, target, EdgeType::BlockRef, 1);
+                    }
+                }
+                for url in block.urls() {
+                    url_pages.entry(url.as_str()).or_default().insert(source.clone());
+                }
+            }
+        }
+
+        for sharing in url_pages.values().filter(|sharing| sharing.len() > 1) {
+            for a in sharing {
+                for b in sharing.iter().filter(|b| *b != a) {
+                    graph.add_edge(a, b, EdgeType::UrlShared, 1);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Rebuild a graph from its nodes and edges, e.g. when loading it from storage
+    pub fn from_parts(nodes: impl IntoIterator<Item = LinkNode>, edges: impl IntoIterator<Item = LinkEdge>) -> Self {
+        let mut graph = LinkGraph::default();
+        for node in nodes {
+            graph.nodes.insert(key(&node.title), node);
+        }
+        for edge in edges {
+            let source = graph.add_node(&edge.source, None);
+            let target = graph.add_node(&edge.target, None);
+            graph.add_edge(&source, &target, edge.edge_type, edge.count);
+        }
+        graph
+    }
+
+    fn add_node(&mut self, title: &str, page_id: Option<PageId>) -> String {
+        let key = key(title);
+        let node = self.nodes.entry(key.clone()).or_insert_with(|| LinkNode {
+            title: title.to_string(),
+            page_id: None,
+        });
+        // A page's own title wins over however references spell it
+        if page_id.is_some() {
+            node.title = title.to_string();
+            node.page_id = page_id;
+        }
+        key
+    }
+
+    fn add_edge(&mut self, source: &str, target: &str, edge_type: EdgeType, count: usize) {
+        if source == target || count == 0 {
+            return;
+        }
+        *self
+            .outgoing
+            .entry(source.to_string())
+            .or_default()
+            .entry((target.to_string(), edge_type))
+            .or_default() += count;
+        *self
+            .incoming
+            .entry(target.to_string())
+            .or_default()
+            .entry((source.to_string(), edge_type))
+            .or_default() += count;
+    }
+
+    pub fn node(&self, title: &str) -> Option<&LinkNode> {
+        self.nodes.get(&key(title))
+    }
+
+    /// Every page, by title
+    pub fn nodes(&self) -> impl Iterator<Item = &LinkNode> {
+        self.nodes.values()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.outgoing.values().map(BTreeMap::len).sum()
+    }
+
+    /// Every edge, by source, then target and type
+    pub fn edges(&self) -> Vec<LinkEdge> {
+        self.nodes.keys().flat_map(|source| self.edges_from_key(source)).collect()
+    }
+
+    /// Edges from a page to the pages it links to
+    pub fn outgoing(&self, title: &str) -> Vec<LinkEdge> {
+        self.edges_from_key(&key(title))
+    }
+
+    /// Edges to a page from the pages that link to it
+    pub fn incoming(&self, title: &str) -> Vec<LinkEdge> {
+        let target = key(title);
+        self.incoming
+            .get(&target)
+            .into_iter()
+            .flatten()
+            .map(|((source, edge_type), count)| self.edge(source, &target, *edge_type, *count))
+            .collect()
+    }
+
+    /// Edges between two pages, in either direction
+    pub fn edges_between(&self, a: &str, b: &str) -> Vec<LinkEdge> {
+        let (a, b) = (key(a), key(b));
+        let mut edges = Vec::new();
+        for (source, target) in [(&a, &b), (&b, &a)] {
+            let Some(links) = self.outgoing.get(source) else {
+                continue;
+            };
+            for edge_type in EdgeType::ALL {
+                if let Some(count) = links.get(&(target.clone(), edge_type)) {
+                    edges.push(self.edge(source, target, edge_type, *count));
+                }
+            }
+        }
+        edges
+    }
+
+    fn edges_from_key(&self, source: &str) -> Vec<LinkEdge> {
+        self.outgoing
+            .get(source)
+            .into_iter()
+            .flatten()
+            .map(|((target, edge_type), count)| self.edge(source, target, *edge_type, *count))
+            .collect()
+    }
+
+    fn edge(&self, source: &str, target: &str, edge_type: EdgeType, count: usize) -> LinkEdge {
+        LinkEdge {
+            source: self.nodes[source].title.clone(),
+            target: self.nodes[target].title.clone(),
+            edge_type,
+            count,
+        }
+    }
+
+    /// Pages within `query.max_depth` links of a page, nearest first
+    ///
+    /// Pages at the same depth come by weight, heaviest first, then by title.
+    /// The page itself isn't included; an unknown page has no neighbors.
+    pub fn neighbors(&self, title: &str, query: &NeighborQuery) -> Vec<Neighbor> {
+        let start = key(title);
+        if !self.nodes.contains_key(&start) {
+            return Vec::new();
+        }
+
+        let mut visited: HashSet<String> = HashSet::from([start.clone()]);
+        let mut frontier: VecDeque<String> = VecDeque::from([start]);
+        let mut results = Vec::new();
+        for depth in 1..=query.max_depth {
+            let mut weights: BTreeMap<String, usize> = BTreeMap::new();
+            for current in frontier.drain(..) {
+                for (neighbor, count) in self.adjacent(&current, query) {
+                    if !visited.contains(neighbor) {
+                        *weights.entry(neighbor.clone()).or_default() += count;
+                    }
+                }
+            }
+            if weights.is_empty() {
+                break;
+            }
+
+            let mut layer: Vec<Neighbor> = weights
+                .iter()
+                .map(|(key, weight)| Neighbor {
+                    node: self.nodes[key].clone(),
+                    depth,
+                    weight: *weight,
+                })
+                .collect();
+            layer.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.node.title.cmp(&b.node.title)));
+            results.extend(layer);
+
+            for key in weights.into_keys() {
+                visited.insert(key.clone());
+                frontier.push_back(key);
+            }
+        }
+        results
+    }
+
+    /// The shortest paths from one page to another, at most `limit` of them
+    ///
+    /// Paths list their pages from `from` to `to` and follow the edges
+    /// `query` allows, for at most `query.max_depth` links. A page is a path
+    /// of its own; unknown or unconnected pages have none.
+    pub fn shortest_paths(&self, from: &str, to: &str, query: &NeighborQuery, limit: usize) -> Vec<Vec<LinkNode>> {
+        let (start, goal) = (key(from), key(to));
+        if !self.nodes.contains_key(&start) || !self.nodes.contains_key(&goal) || limit == 0 {
+            return Vec::new();
+        }
+
+        // Every page's predecessors on a shortest path from `start`
+        let mut parents: HashMap<String, BTreeSet<String>> = HashMap::from([(start.clone(), BTreeSet::new())]);
+        let mut frontier = vec![start.clone()];
+        let mut depth = 0;
+        while !parents.contains_key(&goal) && !frontier.is_empty() && depth < query.max_depth {
+            depth += 1;
+            let mut layer: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+            for current in &frontier {
+                for (neighbor, _) in self.adjacent(current, query) {
+                    if !parents.contains_key(neighbor) {
+                        layer.entry(neighbor.clone()).or_default().insert(current.clone());
+                    }
+                }
+            }
+            frontier = layer.keys().cloned().collect();
+            parents.extend(layer);
+        }
+        if !parents.contains_key(&goal) {
+            return Vec::new();
+        }
+
+        // Walk back from the goal, taking predecessors in title order
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![goal]];
+        while let Some(path) = stack.pop() {
+            let last = path.last().expect("paths are never empty");
+            if *last == start {
+                paths.push(path.iter().rev().map(|key| self.nodes[key].clone()).collect());
+                if paths.len() == limit {
+                    break;
+                }
+                continue;
+            }
+            for parent in parents[last].iter().rev() {
+                let mut longer = path.clone();
+                longer.push(parent.clone());
+                stack.push(longer);
+            }
+        }
+        paths
+    }
+
+    /// Group pages into communities: sets of pages more linked to each other than to the rest
+    ///
+    /// Uses label propagation: every page starts in a community of its own
+    /// and, for at most `max_iterations` rounds, joins the community it has
+    /// the most links to, links counted in either direction. Pages are
+    /// visited in title order and ties go to the page's current community,
+    /// then by title, so the result is the same every run. Communities come
+    /// largest first; pages without links are communities of their own.
+    pub fn communities(&self, max_iterations: usize) -> Vec<Vec<LinkNode>> {
+        let query = NeighborQuery::new();
+        let mut weights: BTreeMap<&String, BTreeMap<&String, usize>> = BTreeMap::new();
+        for key in self.nodes.keys() {
+            let adjacent = weights.entry(key).or_default();
+            for (neighbor, count) in self.adjacent(key, &query) {
+                *adjacent.entry(neighbor).or_default() += count;
+            }
+        }
+
+        let mut labels: HashMap<&String, &String> = self.nodes.keys().map(|key| (key, key)).collect();
+        for _ in 0..max_iterations {
+            let mut changed = false;
+            for (key, adjacent) in &weights {
+                let mut tally: BTreeMap<&String, usize> = BTreeMap::new();
+                for (neighbor, count) in adjacent {
+                    *tally.entry(labels[neighbor]).or_default() += count;
+                }
+                let Some(best) = tally.values().max().copied() else {
+                    continue;
+                };
+                let current = labels[key];
+                if tally.get(current) == Some(&best) {
+                    continue;
+                }
+                let label = tally
+                    .iter()
+                    .find(|(_, count)| **count == best)
+                    .map(|(label, _)| *label)
+                    .expect("the best count belongs to a label");
+                labels.insert(key, label);
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut communities: BTreeMap<&String, Vec<LinkNode>> = BTreeMap::new();
+        for (key, node) in &self.nodes {
+            communities.entry(labels[key]).or_default().push(node.clone());
+        }
+        let mut communities: Vec<Vec<LinkNode>> = communities.into_values().collect();
+        communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| key(&a[0].title).cmp(&key(&b[0].title))));
+        communities
+    }
+
+    /// Pages one followed link away, with the links' counts
+    fn adjacent<'a>(&'a self, key: &str, query: &'a NeighborQuery) -> impl Iterator<Item = (&'a String, usize)> + 'a {
+        let outgoing = matches!(query.direction, Direction::Outgoing | Direction::Both)
+            .then(|| self.outgoing.get(key))
+            .flatten();
+        let incoming = matches!(query.direction, Direction::Incoming | Direction::Both)
+            .then(|| self.incoming.get(key))
+            .flatten();
+        // Shared URLs are recorded both ways; following both would count them twice
+        let incoming = incoming
+            .into_iter()
+            .flatten()
+            .filter(|((_, edge_type), _)| query.direction != Direction::Both || *edge_type != EdgeType::UrlShared);
+        outgoing
+            .into_iter()
+            .flatten()
+            .chain(incoming)
+            .filter(|((_, edge_type), _)| query.follows(*edge_type))
+            .map(|((neighbor, _), count)| (neighbor, *count))
+    }
+}
+
+fn key(title: &str) -> String {
+    title.to_lowercase()
+}
+
+/// The page of every block uuid: block ids, `id` properties and `id:: <uuid>`
+/// lines, which the markdown parser keeps as blocks of their own
+pub(crate) fn block_uuids(pages: &[&Page]) -> HashMap<String, String> {
+    let mut uuids = HashMap::new();
+    for page in pages {
+        let page_key = key(page.title());
+        for block in page.all_blocks() {
+            let content = block.content().as_str().trim();
+            let uuid_line = content.strip_prefix("id::").map(str::trim);
+            let ids = [Some(block.id().as_str()), block.properties().get("id").map(String::as_str), uuid_line];
+            for id in ids.into_iter().flatten() {
+                uuids.insert(id.to_lowercase(), page_key.clone());
+            }
+        }
+    }
+    uuids
+}
+
+/// The uuids of the `((block-uuid))` references in a block's text
+pub(crate) fn block_references(content: &str) -> Vec<String> {
+    static BLOCK_REF: OnceLock<Regex> = OnceLock::new();
+    let pattern = BLOCK_REF.get_or_init(|| Regex::new(r"\(\(([0-9A-Za-z-]+)\)\)").expect("valid regex"));
+    pattern
+        .captures_iter(content)
+        .map(|captures| captures[1].to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    const UUID: &str = "6530a0f2-0000-4c1e-9b7e-00000000000a";
+
+    fn pages() -> Vec<Page> {
+        [
+            ("rust", "Rust", format!("- Ownership\n\t- id:: {}\n- see https://doc.rust-lang.org", UUID)),
+            ("notes", "Notes", format!("- Learning [[Rust]] and [[rust]] #lang\n- (({}))", UUID)),
+            ("lang", "Lang", "- Reading https://doc.rust-lang.org".to_string()),
+            ("go", "Go", "- Goroutines, unlike [[Notes]]".to_string()),
+        ]
+        .into_iter()
+        .map(|(id, title, content)| {
+            LogseqMarkdownParser::parse_content(&content, PageId::new(id).unwrap(), title.to_string()).unwrap()
+        })
+        .collect()
+    }
+
+    fn summary(edges: Vec<LinkEdge>) -> Vec<(String, String, EdgeType, usize)> {
+        edges
+            .into_iter()
+            .map(|edge| (edge.source, edge.target, edge.edge_type, edge.count))
+            .collect()
+    }
+
+    #[test]
+    fn test_builds_typed_edges_with_counts() {
+        let pages = pages();
+        let graph = LinkGraph::build(&pages);
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.node("RUST").unwrap().page_id, Some(PageId::new("rust").unwrap()));
+        assert_eq!(
+            summary(graph.outgoing("Notes")),
+            vec![
+                ("Notes".to_string(), "Lang".to_string(), EdgeType::Tag, 1),
+                ("Notes".to_string(), "Rust".to_string(), EdgeType::PageRef, 2),
+                ("Notes".to_string(), "Rust".to_string(), EdgeType::BlockRef, 1),
+            ]
+        );
+        assert_eq!(
+            summary(graph.incoming("Lang")),
+            vec![
+                ("Notes".to_string(), "Lang".to_string(), EdgeType::Tag, 1),
+                ("Rust".to_string(), "Lang".to_string(), EdgeType::UrlShared, 1),
+            ]
+        );
+        assert_eq!(graph.edge_count(), 6);
+        assert_eq!(LinkGraph::from_parts(graph.nodes().cloned(), graph.edges()), graph);
+    }
+
+    #[test]
+    fn test_neighbors_respect_depth_direction_and_types() {
+        let pages = pages();
+        let graph = LinkGraph::build(&pages);
+        let titles = |neighbors: Vec<Neighbor>| {
+            neighbors
+                .into_iter()
+                .map(|neighbor| (neighbor.node.title, neighbor.depth, neighbor.weight))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            titles(graph.neighbors("go", &NeighborQuery::new().with_max_depth(2))),
+            vec![("Notes".to_string(), 1, 1), ("Rust".to_string(), 2, 3), ("Lang".to_string(), 2, 1)]
+        );
+        let outgoing = NeighborQuery::new().with_direction(Direction::Outgoing).with_max_depth(5);
+        assert_eq!(
+            titles(graph.neighbors("Notes", &outgoing)),
+            vec![("Rust".to_string(), 1, 3), ("Lang".to_string(), 1, 1)]
+        );
+        let urls = NeighborQuery::new().with_edge_types([EdgeType::UrlShared]).with_max_depth(3);
+        assert_eq!(titles(graph.neighbors("Rust", &urls)), vec![("Lang".to_string(), 1, 1)]);
+        assert!(graph.neighbors("Python", &NeighborQuery::new()).is_empty());
+    }
+
+    #[test]
+    fn test_communities_group_densely_linked_pages() {
+        let pages: Vec<Page> = [
+            ("rust", "Rust", "- [[Ownership]] and [[Borrowing]]"),
+            ("ownership", "Ownership", "- [[Borrowing]] rules of [[Rust]]"),
+            ("go", "Go", "- [[Goroutines]] and [[Channels]]\n- unlike [[Rust]]"),
+            ("goroutines", "Goroutines", "- [[Channels]] in [[Go]]"),
+            ("python", "Python", "- Dynamic"),
+        ]
+        .into_iter()
+        .map(|(id, title, content)| {
+            LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap()
+        })
+        .collect();
+        let graph = LinkGraph::build(&pages);
+        let titles: Vec<Vec<String>> = graph
+            .communities(20)
+            .into_iter()
+            .map(|community| community.into_iter().map(|node| node.title).collect())
+            .collect();
+
+        assert_eq!(
+            titles,
+            vec![
+                vec!["Borrowing".to_string(), "Ownership".to_string(), "Rust".to_string()],
+                vec!["Channels".to_string(), "Go".to_string(), "Goroutines".to_string()],
+                vec!["Python".to_string()],
+            ]
+        );
+        assert_eq!(graph.communities(0).len(), 7);
+    }
+}
@@ -1,7 +1,54 @@
+pub mod duplicate_titles;
+pub mod embedding_queue_service;
 pub mod embedding_service;
+pub mod graph_manager;
+pub mod graph_registry;
+pub mod health;
 pub mod import_service;
+pub mod import_validation;
+pub mod journal_templates;
+pub mod link_checker;
+pub mod link_graph;
+pub mod page_cache;
+pub mod page_history;
+pub mod page_timestamps;
+pub mod progress;
+pub mod search_cache;
 pub mod sync_service;
+pub mod url_metadata;
+pub mod visibility;
+pub mod web_archiver;
+pub mod webhook_dispatcher;
 
-pub use embedding_service::{EmbeddingService, EmbeddingServiceConfig, EmbeddingStats};
+pub use duplicate_titles::{DuplicateTitleAction, DuplicateTitlePolicy, DuplicateTitleResolution};
+pub use embedding_queue_service::{
+    EmbeddingMetrics, EmbeddingQueueConfig, EmbeddingQueueError, EmbeddingQueueResult,
+    EmbeddingQueueService, EmbeddingWorkerHandle,
+};
+pub use embedding_service::{
+    EmbeddingError, EmbeddingResult, EmbeddingService, EmbeddingServiceConfig, EmbeddingStats,
+    GarbageCollectionReport, RebuildReport,
+};
+pub use graph_manager::{GraphError, GraphEvent, GraphManager, GraphResult};
+pub use graph_registry::{GraphRegistry, GraphSettings};
+pub use health::{ComponentHealth, HealthComponent, HealthReport, HealthService, HealthStatus};
 pub use import_service::{ImportError, ImportProgressEvent, ImportResult, ImportService, ImportSummary, ProgressCallback};
-pub use sync_service::{SyncCallback, SyncError, SyncEvent, SyncResult, SyncService};
+pub use import_validation::{ValidationIssue, ValidationIssueKind, ValidationReport, DEFAULT_MAX_BLOCK_LENGTH};
+pub use journal_templates::JournalTemplates;
+pub use link_checker::{BrokenLink, LinkCheckSummary, LinkChecker, UrlCheck};
+pub use link_graph::{Direction, EdgeType, LinkEdge, LinkGraph, LinkNode, Neighbor, NeighborQuery};
+pub use page_cache::{CachedPageRepository, PageCache, PageCacheStats, DEFAULT_PAGE_CACHE_CAPACITY};
+pub use page_history::VersionedPageRepository;
+pub use page_timestamps::TimestampedPageRepository;
+pub use progress::{OperationKind, ProgressBus, ProgressEvent, ProgressPhase};
+pub use search_cache::{IndexGeneration, SearchCache, SearchCacheStats, DEFAULT_SEARCH_CACHE_CAPACITY};
+pub use sync_service::{
+    ConflictResolution, SyncCallback, SyncConflict, SyncError, SyncErrorRecord, SyncEvent,
+    SyncResult, SyncService, SyncStatus, SyncSummary,
+};
+pub use url_metadata::{UrlMetadata, UrlMetadataService, UrlMetadataSummary};
+pub use visibility::{VisibilityPolicy, VisiblePages};
+pub use web_archiver::{WebArchive, WebArchiveSummary, WebArchiver};
+pub use webhook_dispatcher::{
+    WebhookDelivery, WebhookDispatcher, WebhookEndpoint, WebhookError, WebhookEvent, WebhookEventType,
+};
@@ -0,0 +1,331 @@
+/// LRU cache of pages by ID in front of a page repository
+use crate::application::dto::PageSummary;
+use crate::application::repositories::PageRepository;
+use crate::domain::aggregates::Page;
+use crate::domain::base::{DomainEvent, Entity};
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Pages kept unless a cache is created with another capacity
+pub const DEFAULT_PAGE_CACHE_CAPACITY: usize = 1000;
+
+/// Domain events whose aggregate is a page that has changed
+pub(crate) const PAGE_EVENTS: [&str; 6] = [
+    "PageCreated",
+    "PageUpdated",
+    "PageDeleted",
+    "BlockAdded",
+    "BlockUpdated",
+    "BlockRemoved",
+];
+
+/// Hit and miss counts of a [`PageCache`], since it was created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Pages dropped to make room for others
+    pub evictions: u64,
+    /// Pages dropped because they were written or changed
+    pub invalidations: u64,
+    /// Pages cached now
+    pub entries: usize,
+}
+
+impl PageCacheStats {
+    /// Share of lookups answered from the cache, from 0.0 to 1.0
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    pages: HashMap<PageId, (Arc<Page>, u64)>,
+    /// Pages by when they were last used, least recent first
+    recency: BTreeMap<u64, PageId>,
+    clock: u64,
+    stats: PageCacheStats,
+}
+
+impl CacheState {
+    fn touch(&mut self, id: &PageId) -> Option<Arc<Page>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (page, used) = self.pages.get_mut(id)?;
+        self.recency.remove(used);
+        *used = clock;
+        self.recency.insert(clock, id.clone());
+        Some(Arc::clone(page))
+    }
+
+    fn remove(&mut self, id: &PageId) -> bool {
+        match self.pages.remove(id) {
+            Some((_, used)) => {
+                self.recency.remove(&used);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Least-recently-used cache of pages, keyed by page ID
+///
+/// Pages are shared as `Arc<Page>`, so readers that look a page up by ID
+/// and only look at it don't clone it. The cache is `Sync` and meant to be
+/// shared: the [`CachedPageRepository`] in front of the store invalidates
+/// pages it writes. Nothing else invalidates pages on its own; whoever
+/// learns of changes elsewhere (domain events, another process writing the
+/// store) must pass them to [`handle_event`](Self::handle_event) or
+/// [`invalidate`](Self::invalidate).
+pub struct PageCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl PageCache {
+    /// A cache holding at most `capacity` pages (at least one)
+    pub fn new(capacity: usize) -> Self {
+        PageCache {
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// The cached page with this ID, counting a hit or a miss
+    pub fn get(&self, id: &PageId) -> Option<Arc<Page>> {
+        let mut state = self.state();
+        let page = state.touch(id);
+        match page {
+            Some(_) => state.stats.hits += 1,
+            None => state.stats.misses += 1,
+        }
+        page
+    }
+
+    /// Cache a page, evicting the least recently used page when full
+    pub fn insert(&self, page: Page) -> Arc<Page> {
+        let page = Arc::new(page);
+        let id = page.id().clone();
+        let mut state = self.state();
+
+        state.remove(&id);
+        while state.pages.len() >= self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.pages.remove(&oldest);
+            state.stats.evictions += 1;
+        }
+
+        state.clock += 1;
+        let clock = state.clock;
+        state.recency.insert(clock, id.clone());
+        state.pages.insert(id, (Arc::clone(&page), clock));
+        page
+    }
+
+    /// Drop a page, so the next lookup reads it from the store again
+    pub fn invalidate(&self, id: &PageId) {
+        let mut state = self.state();
+        if state.remove(id) {
+            state.stats.invalidations += 1;
+        }
+    }
+
+    /// Drop the page a page or block event is about; other events are ignored
+    pub fn handle_event<E: DomainEvent>(&self, event: &E) {
+        if !PAGE_EVENTS.contains(&event.event_type()) {
+            return;
+        }
+        if let Ok(id) = PageId::new(event.aggregate_id()) {
+            self.invalidate(&id);
+        }
+    }
+
+    /// Drop every page
+    pub fn clear(&self) {
+        let mut state = self.state();
+        let dropped = state.pages.len() as u64;
+        state.pages.clear();
+        state.recency.clear();
+        state.stats.invalidations += dropped;
+    }
+
+    pub fn stats(&self) -> PageCacheStats {
+        let state = self.state();
+        PageCacheStats {
+            entries: state.pages.len(),
+            ..state.stats.clone()
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for PageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGE_CACHE_CAPACITY)
+    }
+}
+
+/// Page repository that answers lookups by ID from a [`PageCache`]
+///
+/// Only `find_by_id` and [`get`](Self::get) are served from the cache, so it
+/// helps callers that look the same pages up by ID again and again; queries
+/// that scan every page gain nothing. Saves and deletes go straight to the
+/// wrapped repository and invalidate the pages they touch. Lookups by title
+/// fill the cache but can't be answered from it, and `find_all`,
+/// `find_summaries` and `find_by_file_path` bypass it.
+pub struct CachedPageRepository<R: PageRepository> {
+    inner: R,
+    cache: Arc<PageCache>,
+}
+
+impl<R: PageRepository> CachedPageRepository<R> {
+    pub fn new(inner: R, cache: Arc<PageCache>) -> Self {
+        CachedPageRepository { inner, cache }
+    }
+
+    /// The cache, for invalidating pages changed elsewhere and reading its stats
+    pub fn cache(&self) -> &Arc<PageCache> {
+        &self.cache
+    }
+
+    /// Find a page by ID without cloning it when it is cached
+    pub fn get(&self, id: &PageId) -> DomainResult<Option<Arc<Page>>> {
+        if let Some(page) = self.cache.get(id) {
+            return Ok(Some(page));
+        }
+        Ok(self.inner.find_by_id(id)?.map(|page| self.cache.insert(page)))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: PageRepository> PageRepository for CachedPageRepository<R> {
+    fn save(&mut self, page: Page) -> DomainResult<()> {
+        let id = page.id().clone();
+        let saved = self.inner.save(page);
+        self.cache.invalidate(&id);
+        saved
+    }
+
+    fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+        let ids: Vec<PageId> = pages.iter().map(|page| page.id().clone()).collect();
+        let saved = self.inner.save_all(pages);
+        ids.iter().for_each(|id| self.cache.invalidate(id));
+        saved
+    }
+
+    fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+        Ok(self.get(id)?.map(|page| page.as_ref().clone()))
+    }
+
+    fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+        let page = self.inner.find_by_title(title)?;
+        if let Some(page) = &page {
+            self.cache.insert(page.clone());
+        }
+        Ok(page)
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<Page>> {
+        self.inner.find_all()
+    }
+
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        self.inner.find_summaries()
+    }
+
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        self.inner.find_by_file_path(file_path)
+    }
+
+    fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+        let deleted = self.inner.delete(id);
+        self.cache.invalidate(id);
+        deleted
+    }
+
+    fn delete_all(&mut self, ids: &[PageId]) -> DomainResult<usize> {
+        let deleted = self.inner.delete_all(ids);
+        ids.iter().for_each(|id| self.cache.invalidate(id));
+        deleted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::{BlockAdded, ImportStarted};
+    use crate::domain::value_objects::BlockId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn page(id: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), id.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_least_recently_used_pages_are_evicted() {
+        let cache = PageCache::new(2);
+        cache.insert(page("a", "- A"));
+        cache.insert(page("b", "- B"));
+        assert!(cache.get(&PageId::new("a").unwrap()).is_some());
+
+        cache.insert(page("c", "- C"));
+        assert!(cache.get(&PageId::new("b").unwrap()).is_none());
+        assert!(cache.get(&PageId::new("a").unwrap()).is_some());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions, stats.entries), (2, 1, 1, 2));
+        assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_writes_and_page_events_invalidate_cached_pages() {
+        let store = InMemoryPageRepository::new();
+        let mut repository = CachedPageRepository::new(store.clone(), Arc::new(PageCache::new(10)));
+        repository.save(page("a", "- Old")).unwrap();
+
+        let id = PageId::new("a").unwrap();
+        let first = repository.get(&id).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &repository.get(&id).unwrap().unwrap()));
+
+        repository.save(page("a", "- New")).unwrap();
+        let text = |page: Page| page.root_blocks()[0].content().as_str().to_string();
+        assert_eq!(text(repository.find_by_id(&id).unwrap().unwrap()), "New");
+
+        // A change made behind the cache's back shows up once its event is handled
+        let mut behind = store.clone();
+        behind.save(page("a", "- Newer")).unwrap();
+        repository.cache().handle_event(&ImportStarted {
+            directory_path: "a".into(),
+            total_files: 1,
+        });
+        assert_eq!(text(repository.find_by_id(&id).unwrap().unwrap()), "New");
+        repository.cache().handle_event(&BlockAdded {
+            page_id: id.clone(),
+            block_id: BlockId::new("b1").unwrap(),
+            parent_block_id: None,
+        });
+        assert_eq!(text(repository.find_by_id(&id).unwrap().unwrap()), "Newer");
+
+        assert!(repository.delete(&id).unwrap());
+        assert!(repository.find_by_id(&id).unwrap().is_none());
+        assert_eq!(repository.cache().stats().invalidations, 3);
+    }
+}
@@ -0,0 +1,79 @@
+/// Page repository that records a version of every page it saves
+use crate::application::dto::{PageSnapshot, PageSummary};
+use crate::application::repositories::{PageHistoryRepository, PageRepository};
+use crate::domain::aggregates::Page;
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+use std::path::Path;
+
+/// Page repository that snapshots pages into a [`PageHistoryRepository`]
+///
+/// Every save goes to the wrapped repository first and is then recorded;
+/// saving a page unchanged records nothing new. Deleting a page keeps its
+/// history, so deleted content can still be recovered.
+pub struct VersionedPageRepository<R: PageRepository, H: PageHistoryRepository> {
+    inner: R,
+    history: H,
+}
+
+impl<R: PageRepository, H: PageHistoryRepository> VersionedPageRepository<R, H> {
+    pub fn new(inner: R, history: H) -> Self {
+        VersionedPageRepository { inner, history }
+    }
+
+    /// The recorded versions, for [`GetPageHistory`](crate::application::use_cases::GetPageHistory)
+    /// and [`GetPageAtVersion`](crate::application::use_cases::GetPageAtVersion)
+    pub fn history(&self) -> &H {
+        &self.history
+    }
+
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.history)
+    }
+}
+
+impl<R: PageRepository, H: PageHistoryRepository> PageRepository for VersionedPageRepository<R, H> {
+    fn save(&mut self, page: Page) -> DomainResult<()> {
+        let snapshot = PageSnapshot::from(&page);
+        self.inner.save(page)?;
+        self.history.record(&snapshot)?;
+        Ok(())
+    }
+
+    fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+        let snapshots: Vec<PageSnapshot> = pages.iter().map(PageSnapshot::from).collect();
+        self.inner.save_all(pages)?;
+        for snapshot in &snapshots {
+            self.history.record(snapshot)?;
+        }
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+        self.inner.find_by_title(title)
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<Page>> {
+        self.inner.find_all()
+    }
+
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        self.inner.find_summaries()
+    }
+
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        self.inner.find_by_file_path(file_path)
+    }
+
+    fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+        self.inner.delete(id)
+    }
+
+    fn delete_all(&mut self, ids: &[PageId]) -> DomainResult<usize> {
+        self.inner.delete_all(ids)
+    }
+}
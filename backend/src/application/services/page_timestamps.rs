@@ -0,0 +1,143 @@
+/// Page repository that records when each page's content last changed
+use crate::application::dto::{PageSnapshot, PageSummary};
+use crate::application::repositories::{PageRepository, PageTimestampRepository};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Page repository that marks pages indexed in a [`PageTimestampRepository`]
+///
+/// Every save goes to the wrapped repository first and is then marked, by
+/// the page's [`PageSnapshot`] hash so that saving a page unchanged leaves
+/// its time alone. Summaries carry the recorded times, including when
+/// [`EmbeddingService`](super::EmbeddingService) last embedded the page
+/// when both share a database. Deleting pages forgets their times.
+pub struct TimestampedPageRepository<R: PageRepository, T: PageTimestampRepository> {
+    inner: R,
+    timestamps: T,
+}
+
+impl<R: PageRepository, T: PageTimestampRepository> TimestampedPageRepository<R, T> {
+    pub fn new(inner: R, timestamps: T) -> Self {
+        TimestampedPageRepository { inner, timestamps }
+    }
+
+    /// The recorded times, such as the pages whose embeddings are out of date
+    pub fn timestamps(&self) -> &T {
+        &self.timestamps
+    }
+
+    pub fn into_parts(self) -> (R, T) {
+        (self.inner, self.timestamps)
+    }
+}
+
+/// A page's ID and content hash, taken before the page is handed over
+fn fingerprint(page: &Page) -> (PageId, String) {
+    (page.id().clone(), PageSnapshot::from(page).content_hash)
+}
+
+impl<R: PageRepository, T: PageTimestampRepository> PageRepository for TimestampedPageRepository<R, T> {
+    fn save(&mut self, page: Page) -> DomainResult<()> {
+        let (id, content_hash) = fingerprint(&page);
+        self.inner.save(page)?;
+        self.timestamps.mark_indexed(&id, &content_hash, Utc::now())?;
+        Ok(())
+    }
+
+    fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+        let fingerprints: Vec<(PageId, String)> = pages.iter().map(fingerprint).collect();
+        self.inner.save_all(pages)?;
+        let now = Utc::now();
+        for (id, content_hash) in &fingerprints {
+            self.timestamps.mark_indexed(id, content_hash, now)?;
+        }
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+        self.inner.find_by_title(title)
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<Page>> {
+        self.inner.find_all()
+    }
+
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        let mut timestamps: HashMap<PageId, _> = self
+            .timestamps
+            .find_all()?
+            .into_iter()
+            .map(|timestamps| (timestamps.page_id.clone(), timestamps))
+            .collect();
+        Ok(self
+            .inner
+            .find_summaries()?
+            .into_iter()
+            .map(|summary| match timestamps.remove(&summary.page_id) {
+                Some(timestamps) => summary.with_timestamps(&timestamps),
+                None => summary,
+            })
+            .collect())
+    }
+
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        self.inner.find_by_file_path(file_path)
+    }
+
+    fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+        let deleted = self.inner.delete(id)?;
+        self.timestamps.forget(std::slice::from_ref(id))?;
+        Ok(deleted)
+    }
+
+    fn delete_all(&mut self, ids: &[PageId]) -> DomainResult<usize> {
+        let deleted = self.inner.delete_all(ids)?;
+        self.timestamps.forget(ids)?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::{InMemoryPageRepository, SqlitePageTimestampRepository};
+
+    fn page(content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new("rust").unwrap(), "Rust".into()).unwrap()
+    }
+
+    #[test]
+    fn test_saves_mark_changed_pages_out_of_date() {
+        let timestamps = SqlitePageTimestampRepository::open_in_memory().unwrap();
+        let mut repo = TimestampedPageRepository::new(InMemoryPageRepository::new(), timestamps);
+        let id = PageId::new("rust").unwrap();
+
+        repo.save(page("- Ownership")).unwrap();
+        let summary = &repo.find_summaries().unwrap()[0];
+        assert!(summary.indexed_at.is_some());
+        assert_eq!(summary.embedded_at, None);
+        assert_eq!(repo.timestamps().find_out_of_date().unwrap().len(), 1);
+
+        let (inner, mut timestamps) = repo.into_parts();
+        timestamps.mark_embedded(&id, Utc::now()).unwrap();
+        let mut repo = TimestampedPageRepository::new(inner, timestamps);
+        // Reloading the same content keeps the embeddings current
+        repo.save(page("- Ownership")).unwrap();
+        assert!(repo.timestamps().find_out_of_date().unwrap().is_empty());
+        repo.save(page("- Ownership\n- Borrowing")).unwrap();
+        assert_eq!(repo.timestamps().find_out_of_date().unwrap()[0].page_id, id);
+
+        repo.delete(&id).unwrap();
+        assert_eq!(repo.timestamps().find(&id).unwrap(), None);
+    }
+}
@@ -0,0 +1,187 @@
+/// Progress of long-running operations, reported the same way by every service
+use serde::Serialize;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before slow receivers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// What kind of work an operation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    Import,
+    Sync,
+    Embedding,
+}
+
+/// Where an operation is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressPhase {
+    Started,
+    Running,
+    Completed,
+    /// Stopped early on request; `current` says how far it got
+    Cancelled,
+    /// Finished, but with items that failed
+    Failed,
+}
+
+impl ProgressPhase {
+    /// Whether this is the operation's last event
+    pub fn is_final(&self) -> bool {
+        matches!(self, ProgressPhase::Completed | ProgressPhase::Cancelled | ProgressPhase::Failed)
+    }
+}
+
+/// One step of an operation's progress
+///
+/// Items are what the operation counts: files for imports and syncs, pages
+/// for embedding runs. `total` is `None` until the operation knows it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    /// The same for every event of one run
+    pub operation_id: Uuid,
+    pub kind: OperationKind,
+    pub phase: ProgressPhase,
+    pub current: usize,
+    pub total: Option<usize>,
+    /// Items done per second since the operation started
+    pub items_per_second: f64,
+}
+
+impl ProgressEvent {
+    /// Share of the items done, from 0.0 to 100.0, if the total is known
+    pub fn percentage(&self) -> Option<f64> {
+        match self.total? {
+            0 => Some(100.0),
+            total => Some(self.current as f64 / total as f64 * 100.0),
+        }
+    }
+}
+
+/// Channel every service publishes its [`ProgressEvent`]s on
+///
+/// Clones share the channel, so one bus handed to the import, sync and
+/// embedding services (each with its `with_progress` builder) lets a single
+/// subscriber follow all of them. Services without a bus report nothing.
+#[derive(Debug, Clone)]
+pub struct ProgressBus {
+    events: broadcast::Sender<ProgressEvent>,
+}
+
+impl ProgressBus {
+    pub fn new() -> Self {
+        ProgressBus {
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Subscribe to the progress of every operation started after this call
+    ///
+    /// A receiver that falls more than the channel capacity behind gets
+    /// `RecvError::Lagged` and skips ahead rather than slowing the operations.
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: ProgressEvent) {
+        // Sending only fails when nobody is subscribed, which is fine
+        let _ = self.events.send(event);
+    }
+}
+
+impl Default for ProgressBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One run of an operation, publishing its progress on a bus
+///
+/// Started with an optional bus so services can report unconditionally;
+/// without one, every call is a no-op.
+pub(crate) struct ProgressOperation {
+    bus: Option<ProgressBus>,
+    id: Uuid,
+    kind: OperationKind,
+    total: Option<usize>,
+    started: Instant,
+}
+
+impl ProgressOperation {
+    /// Start an operation and publish its `Started` event
+    pub(crate) fn start(bus: Option<&ProgressBus>, kind: OperationKind, total: Option<usize>) -> Self {
+        let operation = ProgressOperation {
+            bus: bus.cloned(),
+            id: Uuid::new_v4(),
+            kind,
+            total,
+            started: Instant::now(),
+        };
+        operation.publish(ProgressPhase::Started, 0);
+        operation
+    }
+
+    /// Number of items the operation covers, if known
+    pub(crate) fn total(&self) -> Option<usize> {
+        self.total
+    }
+
+    /// Report that `current` items are done
+    pub(crate) fn advance(&self, current: usize) {
+        self.publish(ProgressPhase::Running, current);
+    }
+
+    /// Report the operation's end: completed, cancelled, or failed
+    pub(crate) fn finish(self, current: usize, cancelled: bool, failed: bool) {
+        let phase = if cancelled {
+            ProgressPhase::Cancelled
+        } else if failed {
+            ProgressPhase::Failed
+        } else {
+            ProgressPhase::Completed
+        };
+        self.publish(phase, current);
+    }
+
+    fn publish(&self, phase: ProgressPhase, current: usize) {
+        let Some(bus) = &self.bus else {
+            return;
+        };
+        let seconds = self.started.elapsed().as_secs_f64();
+        bus.publish(ProgressEvent {
+            operation_id: self.id,
+            kind: self.kind,
+            phase,
+            current,
+            total: self.total,
+            items_per_second: if seconds > 0.0 { current as f64 / seconds } else { 0.0 },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations_publish_their_progress() {
+        let bus = ProgressBus::new();
+        let mut events = bus.subscribe();
+
+        let operation = ProgressOperation::start(Some(&bus), OperationKind::Import, Some(4));
+        operation.advance(2);
+        operation.finish(3, true, false);
+        ProgressOperation::start(None, OperationKind::Sync, None).finish(0, false, false);
+
+        let events: Vec<ProgressEvent> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        let phases: Vec<ProgressPhase> = events.iter().map(|event| event.phase).collect();
+        assert_eq!(phases, vec![ProgressPhase::Started, ProgressPhase::Running, ProgressPhase::Cancelled]);
+        assert!(events.iter().all(|event| event.operation_id == events[0].operation_id));
+        assert_eq!(events[1].percentage(), Some(50.0));
+        assert!(events[2].phase.is_final());
+    }
+}
@@ -0,0 +1,370 @@
+/// Cache of recent search results, invalidated by an index generation counter
+use crate::application::dto::{ResultType, SearchRequest, SearchResult, SearchType};
+use crate::domain::base::DomainEvent;
+use crate::domain::value_objects::{GraphId, PageId};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::page_cache::PAGE_EVENTS;
+use super::{GraphEvent, SyncEvent};
+
+/// Searches kept unless a cache is created with another capacity
+pub const DEFAULT_SEARCH_CACHE_CAPACITY: usize = 256;
+
+/// Counter of writes to what searches read
+///
+/// Clones share the counter. Everything that writes pages (or their
+/// embeddings) bumps it, and cached results from an earlier generation are
+/// never served.
+#[derive(Debug, Clone, Default)]
+pub struct IndexGeneration(Arc<AtomicU64>);
+
+impl IndexGeneration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn current(&self) -> u64 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Record a write, returning the new generation
+    pub fn bump(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::AcqRel) + 1
+    }
+}
+
+/// A search request reduced to what decides its results
+///
+/// Keyword search ignores case, so its queries are lowercased; semantic
+/// queries are kept as typed since the embedding model sees them that way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SearchKey {
+    query: String,
+    search_type: SearchType,
+    result_type: ResultType,
+    page_filters: Option<Vec<PageId>>,
+    score_threshold: Option<u32>,
+    graph_id: Option<GraphId>,
+    same_block_refs: bool,
+    explain: bool,
+    journal_dates: Option<RangeInclusive<NaiveDate>>,
+    parse_dates: bool,
+}
+
+impl From<&SearchRequest> for SearchKey {
+    fn from(request: &SearchRequest) -> Self {
+        let query = match request.search_type {
+            SearchType::Traditional => request.query.to_lowercase(),
+            SearchType::Semantic => request.query.clone(),
+        };
+        SearchKey {
+            query,
+            search_type: request.search_type.clone(),
+            result_type: request.result_type.clone(),
+            page_filters: request.page_filters.clone(),
+            score_threshold: request.score_threshold.map(f32::to_bits),
+            graph_id: request.graph_id.clone(),
+            same_block_refs: request.same_block_refs,
+            explain: request.explain,
+            journal_dates: request.journal_dates.clone(),
+            parse_dates: request.parse_dates,
+        }
+    }
+}
+
+/// Hit and miss counts of a [`SearchCache`], since it was created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Searches dropped to make room for others
+    pub evictions: u64,
+    /// Searches dropped because the index changed since they ran
+    pub stale: u64,
+    /// Searches cached now
+    pub entries: usize,
+}
+
+impl SearchCacheStats {
+    /// Share of lookups answered from the cache, from 0.0 to 1.0
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+struct CachedSearch {
+    results: Arc<Vec<SearchResult>>,
+    generation: u64,
+    used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    searches: HashMap<SearchKey, CachedSearch>,
+    /// Searches by when they were last used, least recent first
+    recency: BTreeMap<u64, SearchKey>,
+    clock: u64,
+    stats: SearchCacheStats,
+}
+
+impl CacheState {
+    fn remove(&mut self, key: &SearchKey) {
+        if let Some(search) = self.searches.remove(key) {
+            self.recency.remove(&search.used);
+        }
+    }
+}
+
+/// Least-recently-used cache of search results, keyed by normalized request
+///
+/// Each entry remembers the [`IndexGeneration`] it was computed at, and a
+/// lookup after the generation moved on is a miss. Bump the generation with
+/// [`generation`](Self::generation) on writes, or let
+/// [`handle_event`](Self::handle_event) do it for page and block events and
+/// [`follow_graph_events`](Self::follow_graph_events) for synced files.
+pub struct SearchCache {
+    capacity: usize,
+    generation: IndexGeneration,
+    state: Mutex<CacheState>,
+}
+
+impl SearchCache {
+    /// A cache holding at most `capacity` searches (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self::with_generation(capacity, IndexGeneration::new())
+    }
+
+    /// A cache following a generation counter shared with the writers
+    pub fn with_generation(capacity: usize, generation: IndexGeneration) -> Self {
+        SearchCache {
+            capacity: capacity.max(1),
+            generation,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub fn generation(&self) -> &IndexGeneration {
+        &self.generation
+    }
+
+    /// The results of an identical search at the current generation, counting
+    /// a hit or a miss
+    pub fn get(&self, request: &SearchRequest) -> Option<Arc<Vec<SearchResult>>> {
+        let key = SearchKey::from(request);
+        let generation = self.generation.current();
+        let mut state = self.state();
+
+        let fresh = match state.searches.get(&key) {
+            Some(search) => search.generation == generation,
+            None => {
+                state.stats.misses += 1;
+                return None;
+            }
+        };
+        if !fresh {
+            state.remove(&key);
+            state.stats.stale += 1;
+            state.stats.misses += 1;
+            return None;
+        }
+
+        state.clock += 1;
+        let clock = state.clock;
+        let search = state.searches.get_mut(&key)?;
+        let (results, used) = (Arc::clone(&search.results), search.used);
+        search.used = clock;
+        state.recency.remove(&used);
+        state.recency.insert(clock, key);
+        state.stats.hits += 1;
+        Some(results)
+    }
+
+    /// Cache the results of a search that started at `generation`
+    ///
+    /// Taking the generation from before the search ran means results that
+    /// raced a write are stale as soon as they are stored.
+    pub fn insert(&self, request: &SearchRequest, generation: u64, results: Vec<SearchResult>) -> Arc<Vec<SearchResult>> {
+        let results = Arc::new(results);
+        if generation != self.generation.current() {
+            return results;
+        }
+
+        let key = SearchKey::from(request);
+        let mut state = self.state();
+        state.remove(&key);
+        while state.searches.len() >= self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.searches.remove(&oldest);
+            state.stats.evictions += 1;
+        }
+
+        state.clock += 1;
+        let used = state.clock;
+        state.recency.insert(used, key.clone());
+        state.searches.insert(
+            key,
+            CachedSearch {
+                results: Arc::clone(&results),
+                generation,
+                used,
+            },
+        );
+        results
+    }
+
+    /// Bump the generation for a page or block event; other events are ignored
+    pub fn handle_event<E: DomainEvent>(&self, event: &E) {
+        if PAGE_EVENTS.contains(&event.event_type()) {
+            self.generation.bump();
+        }
+    }
+
+    /// Bump the generation for graphs' sync events that change pages, until
+    /// the channel closes
+    pub fn follow_graph_events(&self, mut events: broadcast::Receiver<GraphEvent>) -> JoinHandle<()> {
+        let generation = self.generation.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if changes_pages(&event.event) {
+                            generation.bump();
+                        }
+                    }
+                    // The missed events may have changed pages
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        generation.bump();
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    pub fn stats(&self) -> SearchCacheStats {
+        let state = self.state();
+        SearchCacheStats {
+            entries: state.searches.len(),
+            ..state.stats.clone()
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEARCH_CACHE_CAPACITY)
+    }
+}
+
+/// Whether a sync event saved or deleted pages in the repository
+fn changes_pages(event: &SyncEvent) -> bool {
+    matches!(
+        event,
+        SyncEvent::FileCreated { .. }
+            | SyncEvent::FileUpdated { .. }
+            | SyncEvent::FileDeleted { .. }
+            | SyncEvent::FileRenamed { .. }
+            | SyncEvent::DuplicateTitle(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::{PageResult, SearchItem};
+    use crate::domain::events::PageDeleted;
+    use crate::domain::value_objects::SimilarityScore;
+
+    fn results(title: &str) -> Vec<SearchResult> {
+        vec![SearchResult {
+            item: SearchItem::Page(PageResult {
+                page_id: PageId::new(title).unwrap(),
+                title: title.to_string(),
+                block_count: 0,
+                urls: Vec::new(),
+                page_references: Vec::new(),
+            }),
+            score: SimilarityScore::new(1.0).unwrap(),
+            explanation: None,
+        }]
+    }
+
+    #[test]
+    fn test_identical_searches_are_served_until_the_generation_moves() {
+        let cache = SearchCache::new(2);
+        let generation = cache.generation().current();
+        cache.insert(&SearchRequest::new("Rust"), generation, results("rust"));
+
+        // Keyword search ignores case; semantic search doesn't
+        assert_eq!(*cache.get(&SearchRequest::new("rust")).unwrap(), results("rust"));
+        let semantic = SearchRequest::new("rust").with_search_type(SearchType::Semantic);
+        assert!(cache.get(&semantic).is_none());
+        assert!(cache.get(&SearchRequest::new("rust").with_result_type(ResultType::PagesOnly)).is_none());
+
+        cache.handle_event(&PageDeleted {
+            page_id: PageId::new("rust").unwrap(),
+        });
+        assert!(cache.get(&SearchRequest::new("rust")).is_none());
+
+        // Results computed before a write aren't stored
+        cache.insert(&SearchRequest::new("rust"), generation, results("rust"));
+        assert!(cache.get(&SearchRequest::new("rust")).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.stale, stats.entries), (1, 4, 1, 0));
+    }
+
+    #[test]
+    fn test_least_recently_used_searches_are_evicted() {
+        let cache = SearchCache::new(2);
+        for query in ["a", "b"] {
+            cache.insert(&SearchRequest::new(query), 0, results(query));
+        }
+        assert!(cache.get(&SearchRequest::new("a")).is_some());
+
+        cache.insert(&SearchRequest::new("c"), 0, results("c"));
+        assert!(cache.get(&SearchRequest::new("b")).is_none());
+        assert!(cache.get(&SearchRequest::new("a")).is_some());
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_events_that_change_pages_bump_the_generation() {
+        let cache = SearchCache::default();
+        let (events, receiver) = broadcast::channel(8);
+        let following = cache.follow_graph_events(receiver);
+        let send = |event| {
+            events
+                .send(GraphEvent {
+                    graph_id: "notes".to_string(),
+                    event,
+                })
+                .unwrap();
+        };
+
+        send(SyncEvent::SyncStarted);
+        send(SyncEvent::FileUpdated { file_path: "pages/Rust.md".into() });
+        send(SyncEvent::Progress { processed: 1, total: 2 });
+        send(SyncEvent::FileDeleted { file_path: "pages/Go.md".into() });
+        drop(events);
+        following.await.unwrap();
+
+        assert_eq!(cache.generation().current(), 2);
+    }
+}
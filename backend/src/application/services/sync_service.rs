@@ -1,15 +1,27 @@
 /// Sync service for keeping Logseq directory in sync with changes
 use crate::application::repositories::PageRepository;
+use crate::application::services::duplicate_titles::{
+    merge_blocks, numbered_title, DuplicateTitleAction, DuplicateTitlePolicy, DuplicateTitleResolution,
+};
+use crate::application::services::progress::{OperationKind, ProgressBus, ProgressOperation};
+use crate::application::services::EmbeddingService;
+use crate::domain::aggregates::Page;
+use crate::domain::entities::Block;
 use crate::domain::base::Entity;
-use crate::domain::value_objects::LogseqDirectoryPath;
-use crate::infrastructure::file_system::{discover_logseq_files, FileEvent, FileEventKind, LogseqFileWatcher};
-use crate::infrastructure::parsers::LogseqMarkdownParser;
+use crate::domain::value_objects::{LogseqDirectoryPath, PageId};
+use crate::infrastructure::file_system::{
+    coalesce_events, discover_graph_files, FileEvent, IgnoreRules, LogseqFileWatcher,
+    StabilityCheck,
+};
+use crate::infrastructure::parsers::{LogseqMarkdownParser, LogseqMarkdownWriter};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use thiserror::Error;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Error, Debug)]
 pub enum SyncError {
@@ -24,10 +36,31 @@ pub enum SyncError {
 
     #[error("Watcher error: {0}")]
     Watcher(#[from] crate::infrastructure::file_system::WatcherError),
+
+    #[error("Embedding error: {0}")]
+    Embedding(#[from] super::EmbeddingError),
+
+    #[error("Page title \"{title}\" is already used by {}", kept_by.display())]
+    DuplicateTitle { title: String, kept_by: PathBuf },
 }
 
 pub type SyncResult<T> = Result<T, SyncError>;
 
+/// How often [`SyncService::watch_until`] checks the watcher for new events
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default time between full reconciles while watching
+const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Events buffered per subscriber before slow receivers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Default number of watcher operations whose pages are saved together
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Errors kept in [`SyncStatus::recent_errors`]
+const RECENT_ERROR_LIMIT: usize = 20;
+
 /// Callback type for sync events
 pub type SyncCallback = Arc<dyn Fn(SyncEvent) + Send + Sync>;
 
@@ -38,8 +71,44 @@ pub enum SyncEvent {
     FileCreated { file_path: PathBuf },
     FileUpdated { file_path: PathBuf },
     FileDeleted { file_path: PathBuf },
-    SyncCompleted { files_created: usize, files_updated: usize, files_deleted: usize },
+    /// A file was renamed or moved; its page keeps its ID under the new title
+    FileRenamed { from: PathBuf, to: PathBuf },
+    SyncCompleted {
+        files_created: usize,
+        files_updated: usize,
+        files_deleted: usize,
+        files_renamed: usize,
+    },
     Error { file_path: PathBuf, error: String },
+    /// A page edited in the repository was written back to its file
+    FileWritten { file_path: PathBuf },
+    /// The file and its page in the repository both changed since the last
+    /// sync; neither side was overwritten
+    Conflict(Box<SyncConflict>),
+    /// Progress through a watcher burst larger than one batch
+    Progress { processed: usize, total: usize },
+    /// A file's page title was already taken by another file's page; see
+    /// [`SyncService::with_duplicate_title_policy`]
+    DuplicateTitle(DuplicateTitleResolution),
+}
+
+/// Both versions of a page that changed on disk and in the repository
+#[derive(Debug, Clone)]
+pub struct SyncConflict {
+    pub file_path: PathBuf,
+    /// The page as currently stored in the repository
+    pub repository_page: Page,
+    /// The page as parsed from the changed file
+    pub file_page: Page,
+}
+
+/// Which side of a [`SyncConflict`] wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Overwrite the repository page with the file's contents
+    KeepFile,
+    /// Keep the repository page and stop reporting the file's change
+    KeepRepository,
 }
 
 /// Summary of a one-time sync operation
@@ -48,8 +117,73 @@ pub struct SyncSummary {
     pub files_created: usize,
     pub files_updated: usize,
     pub files_deleted: usize,
+    pub files_renamed: usize,
     pub files_unchanged: usize,
+    /// Files left unsynced because their page was also edited in the repository
+    pub conflicts: usize,
     pub errors: Vec<(PathBuf, String)>,
+    /// The sync stopped early (see [`SyncService::with_cancellation`]);
+    /// files after the last one synced, and deletions, are left for the next sync
+    pub cancelled: bool,
+}
+
+/// Health of a sync service, for showing a sync indicator
+///
+/// Counts cover everything since the service was created, whether it came
+/// from [`SyncService::sync_once`] or the watcher.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncStatus {
+    /// Whether the file watcher loop is running
+    pub watching: bool,
+    /// When the last sync pass or watcher batch finished
+    pub last_sync: Option<SystemTime>,
+    /// Operations from the current watcher batch that haven't been applied yet
+    pub pending_operations: usize,
+    pub files_created: usize,
+    pub files_updated: usize,
+    pub files_deleted: usize,
+    pub files_renamed: usize,
+    pub conflicts: usize,
+    pub errors: usize,
+    /// The most recent errors, oldest first
+    pub recent_errors: Vec<SyncErrorRecord>,
+}
+
+/// A file that failed to sync
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncErrorRecord {
+    pub file_path: PathBuf,
+    pub error: String,
+    pub occurred_at: SystemTime,
+}
+
+impl SyncStatus {
+    /// Update the counters for an emitted event
+    fn record(&mut self, event: &SyncEvent) {
+        match event {
+            SyncEvent::FileCreated { .. } => self.files_created += 1,
+            SyncEvent::FileUpdated { .. } => self.files_updated += 1,
+            SyncEvent::FileDeleted { .. } => self.files_deleted += 1,
+            SyncEvent::FileRenamed { .. } => self.files_renamed += 1,
+            SyncEvent::Conflict(_) => self.conflicts += 1,
+            SyncEvent::SyncCompleted { .. } => self.last_sync = Some(SystemTime::now()),
+            SyncEvent::Error { file_path, error } => {
+                self.errors += 1;
+                if self.recent_errors.len() == RECENT_ERROR_LIMIT {
+                    self.recent_errors.remove(0);
+                }
+                self.recent_errors.push(SyncErrorRecord {
+                    file_path: file_path.clone(),
+                    error: error.clone(),
+                    occurred_at: SystemTime::now(),
+                });
+            }
+            SyncEvent::SyncStarted
+            | SyncEvent::FileWritten { .. }
+            | SyncEvent::Progress { .. }
+            | SyncEvent::DuplicateTitle(_) => {}
+        }
+    }
 }
 
 /// Operation to perform during sync
@@ -58,13 +192,137 @@ enum SyncOperation {
     Create(PathBuf),
     Update(PathBuf),
     Delete(PathBuf),
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+impl SyncOperation {
+    /// The file the operation leaves behind (the new path of a rename)
+    fn path(&self) -> &Path {
+        match self {
+            SyncOperation::Create(path)
+            | SyncOperation::Update(path)
+            | SyncOperation::Delete(path)
+            | SyncOperation::Rename { to: path, .. } => path,
+        }
+    }
+}
+
+/// What a sync operation ended up doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncOutcome {
+    Created,
+    Updated,
+    Deleted,
+    Renamed,
+    /// The file was touched but its content didn't change
+    Unchanged,
+    /// The page was edited in the repository too; nothing was saved
+    Conflict,
+}
+
+/// A parsed file whose page is ready to be saved
+#[derive(Debug)]
+struct PendingSave {
+    path: PathBuf,
+    page: Page,
+    metadata: FileMetadata,
+    /// Set when the page's title was already taken by another file's page
+    duplicate: Option<DuplicateTitleResolution>,
+}
+
+/// Result of reading a changed file before saving it
+#[derive(Debug)]
+enum PreparedSave {
+    Ready(PendingSave),
+    Conflict(SyncConflict),
+}
+
+/// What applying an operation left to do
+#[derive(Debug)]
+enum Applied {
+    Done(SyncOutcome),
+    /// The page still has to be saved; the outcome applies once it is
+    Save(Box<PendingSave>, SyncOutcome),
 }
 
 /// Metadata about a synced file
 #[derive(Debug, Clone)]
 struct FileMetadata {
-    title: String,
+    page_id: PageId,
     last_modified: SystemTime,
+    /// SHA-256 of the file contents, used to recognize the file after a rename
+    content_hash: String,
+    /// Fingerprint of the page as saved, used to notice edits made in the repository
+    page_hash: String,
+}
+
+/// Synced files and their metadata
+///
+/// Entries are looked up by [`path_key`], so on Windows `C:\Graph\pages\A.md`
+/// and `c:/graph/pages/a.md` are the same file. Each entry keeps the path as
+/// it was first seen, which is what iteration returns.
+#[derive(Debug, Default)]
+struct SyncRegistry {
+    entries: HashMap<PathBuf, (PathBuf, FileMetadata)>,
+}
+
+impl SyncRegistry {
+    fn get(&self, path: &Path) -> Option<&FileMetadata> {
+        self.entries.get(&path_key(path)).map(|(_, metadata)| metadata)
+    }
+
+    fn get_mut(&mut self, path: &Path) -> Option<&mut FileMetadata> {
+        self.entries.get_mut(&path_key(path)).map(|(_, metadata)| metadata)
+    }
+
+    fn contains_key(&self, path: &Path) -> bool {
+        self.entries.contains_key(&path_key(path))
+    }
+
+    fn insert(&mut self, path: PathBuf, metadata: FileMetadata) {
+        self.entries.insert(path_key(&path), (path, metadata));
+    }
+
+    fn remove(&mut self, path: &Path) -> Option<FileMetadata> {
+        self.entries.remove(&path_key(path)).map(|(_, metadata)| metadata)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&PathBuf, &FileMetadata)> {
+        self.entries.values().map(|(path, metadata)| (path, metadata))
+    }
+
+    /// Registered paths whose files were synced into the given page
+    fn paths_for_page(&self, page_id: &PageId) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .iter()
+            .filter(|(_, metadata)| metadata.page_id == *page_id)
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Record a newly saved fingerprint for every file synced into a page
+    ///
+    /// Files merged into one page share it; without this, saving one file's
+    /// changes would look like an edit in the repository to the others.
+    fn set_page_hash(&mut self, page_id: &PageId, page_hash: &str) {
+        for (_, metadata) in self.entries.values_mut() {
+            if metadata.page_id == *page_id {
+                metadata.page_hash = page_hash.to_string();
+            }
+        }
+    }
+
+    /// Registered paths that aren't among `present`
+    fn missing_from(&self, present: &[PathBuf]) -> Vec<PathBuf> {
+        let present: HashSet<PathBuf> = present.iter().map(|path| path_key(path)).collect();
+        self.entries
+            .iter()
+            .filter(|(key, _)| !present.contains(*key))
+            .map(|(_, (path, _))| path.clone())
+            .collect()
+    }
 }
 
 /// Service for syncing Logseq directory changes
@@ -74,7 +332,26 @@ pub struct SyncService<R: PageRepository> {
     watcher: LogseqFileWatcher,
     debounce_duration: Duration,
     /// Tracks files that have been synced with their metadata
-    sync_registry: Arc<Mutex<HashMap<PathBuf, FileMetadata>>>,
+    sync_registry: Arc<Mutex<SyncRegistry>>,
+    /// When set, embeddings of deleted pages are removed too
+    embedding_service: Option<Arc<EmbeddingService>>,
+    /// Every sync event is published here; callbacks are invoked alongside
+    events: broadcast::Sender<SyncEvent>,
+    /// Paths that are never synced
+    ignore_rules: IgnoreRules,
+    /// How often the watcher loop runs a full sync to catch missed events
+    reconcile_interval: Option<Duration>,
+    /// Guards watcher-triggered parses against files that are still being written
+    read_stability: StabilityCheck,
+    /// Counters and flags reported by [`status`](Self::status)
+    status: std::sync::Mutex<SyncStatus>,
+    /// Watcher operations whose pages are saved in one repository call
+    batch_size: usize,
+    duplicate_title_policy: DuplicateTitlePolicy,
+    /// Checked between files and batches; once cancelled, syncing and watching stop
+    cancellation: CancellationToken,
+    /// When set, every sync and watcher burst also reports its progress here
+    progress: Option<ProgressBus>,
 }
 
 impl<R: PageRepository + Send + 'static> SyncService<R> {
@@ -87,16 +364,123 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
         let debounce = debounce_duration.unwrap_or(Duration::from_millis(500));
 
         let watcher = LogseqFileWatcher::new(directory_path.as_path(), debounce)?;
+        let ignore_rules = IgnoreRules::for_graph(directory_path.as_path());
 
         Ok(SyncService {
             repository: Arc::new(Mutex::new(repository)),
             directory_path,
             watcher,
             debounce_duration: debounce,
-            sync_registry: Arc::new(Mutex::new(HashMap::new())),
+            sync_registry: Arc::new(Mutex::new(SyncRegistry::default())),
+            embedding_service: None,
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            ignore_rules,
+            reconcile_interval: Some(DEFAULT_RECONCILE_INTERVAL),
+            read_stability: StabilityCheck::default(),
+            status: std::sync::Mutex::new(SyncStatus::default()),
+            batch_size: DEFAULT_BATCH_SIZE,
+            duplicate_title_policy: DuplicateTitlePolicy::default(),
+            cancellation: CancellationToken::new(),
+            progress: None,
         })
     }
 
+    /// Remove a page's embeddings whenever its file is deleted
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Replace the ignore rules loaded from the graph directory
+    pub fn with_ignore_rules(mut self, ignore_rules: IgnoreRules) -> Self {
+        self.ignore_rules = ignore_rules;
+        self
+    }
+
+    /// Set how often watching falls back to a full sync (`None` disables it)
+    ///
+    /// File watchers can miss events on network drives or across sleep/wake;
+    /// a periodic reconcile makes the repository converge regardless.
+    pub fn with_reconcile_interval(mut self, interval: Option<Duration>) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// Set how files reported by the watcher are read before parsing
+    pub fn with_read_stability(mut self, read_stability: StabilityCheck) -> Self {
+        self.read_stability = read_stability;
+        self
+    }
+
+    /// Set how many watcher operations are processed and saved together
+    ///
+    /// Bursts larger than one batch also report [`SyncEvent::Progress`].
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set what happens when a file's page would take another file's title
+    ///
+    /// A title is taken when the repository's page with that title was synced
+    /// from a different file. Renames and merges are reported with
+    /// [`SyncEvent::DuplicateTitle`]; a rejected file is reported as a
+    /// [`SyncError::DuplicateTitle`] error. Merged pages are rebuilt from all
+    /// of their files whenever one of them changes or is deleted, and are not
+    /// written back by [`write_back`](Self::write_back).
+    pub fn with_duplicate_title_policy(mut self, policy: DuplicateTitlePolicy) -> Self {
+        self.duplicate_title_policy = policy;
+        self
+    }
+
+    /// Stop syncing and watching when `token` is cancelled
+    ///
+    /// [`sync_once`](Self::sync_once) checks the token between files and
+    /// returns a summary flagged [`cancelled`](SyncSummary::cancelled); the
+    /// watcher checks it between batches of operations and stops watching.
+    /// Whatever was left unsynced is picked up by the next full sync.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Publish the progress of every sync on `bus`, counting files
+    ///
+    /// Full syncs count the graph's files; watcher bursts count the files
+    /// that changed.
+    pub fn with_progress(mut self, bus: ProgressBus) -> Self {
+        self.progress = Some(bus);
+        self
+    }
+
+    /// Subscribe to sync events
+    ///
+    /// Each receiver sees every event published after it subscribed. A receiver
+    /// that falls more than the channel capacity behind gets `RecvError::Lagged`
+    /// and skips ahead rather than blocking the sync.
+    pub fn subscribe(&self) -> broadcast::Receiver<SyncEvent> {
+        self.events.subscribe()
+    }
+
+    /// Current health of the service
+    pub fn status(&self) -> SyncStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut SyncStatus)) {
+        update(&mut self.status.lock().unwrap());
+    }
+
+    /// Publish an event to subscribers and the optional callback
+    fn emit(&self, event: SyncEvent, callback: Option<&SyncCallback>) {
+        self.update_status(|status| status.record(&event));
+        if let Some(cb) = callback {
+            cb(event.clone());
+        }
+        // Sending only fails when nobody is subscribed, which is fine
+        let _ = self.events.send(event);
+    }
+
     /// Perform a one-time sync of the directory
     ///
     /// This method:
@@ -107,60 +491,95 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
     pub async fn sync_once(&self, callback: Option<SyncCallback>) -> SyncResult<SyncSummary> {
         tracing::info!("Starting one-time sync for {:?}", self.directory_path);
 
-        if let Some(ref cb) = callback {
-            cb(SyncEvent::SyncStarted);
-        }
+        self.emit(SyncEvent::SyncStarted, callback.as_ref());
 
         let mut summary = SyncSummary {
             files_created: 0,
             files_updated: 0,
             files_deleted: 0,
+            files_renamed: 0,
             files_unchanged: 0,
+            conflicts: 0,
             errors: Vec::new(),
+            cancelled: false,
         };
 
         // Discover all current files in the directory
-        let current_files = discover_logseq_files(self.directory_path.as_path()).await?;
-        let current_files_set: HashSet<PathBuf> = current_files.iter().cloned().collect();
+        let current_files = discover_graph_files(&self.directory_path, &self.ignore_rules).await?;
+
+        // Files that disappeared while an identical new file appeared were renamed
+        let (missing, new): (Vec<PathBuf>, Vec<PathBuf>) = {
+            let registry = self.sync_registry.lock().await;
+            let missing = registry.missing_from(&current_files);
+            let new = current_files
+                .iter()
+                .filter(|path| !registry.contains_key(path))
+                .cloned()
+                .collect();
+            (missing, new)
+        };
+        let progress = ProgressOperation::start(self.progress.as_ref(), OperationKind::Sync, Some(current_files.len()));
+        let mut renamed_targets = HashSet::new();
+        for (from, to) in self.detect_renames(&missing, &new).await {
+            if self.rename_page(&from, &to).await? {
+                summary.files_renamed += 1;
+                self.emit(SyncEvent::FileRenamed { from, to: to.clone() }, callback.as_ref());
+                renamed_targets.insert(to);
+            }
+        }
 
         // Process each discovered file
-        for file_path in current_files {
-            match self.sync_file(&file_path, &mut summary, callback.as_ref()).await {
-                Ok(_) => {}
-                Err(e) => {
+        let mut files_done = 0;
+        for file_path in &current_files {
+            if self.cancellation.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+
+            if !renamed_targets.contains(file_path) {
+                if let Err(e) = self.sync_file(file_path, &mut summary, callback.as_ref()).await {
                     let error_msg = e.to_string();
                     tracing::error!("Failed to sync {}: {}", file_path.display(), error_msg);
                     summary.errors.push((file_path.clone(), error_msg.clone()));
 
-                    if let Some(ref cb) = callback {
-                        cb(SyncEvent::Error {
-                            file_path,
+                    self.emit(
+                        SyncEvent::Error {
+                            file_path: file_path.clone(),
                             error: error_msg,
-                        });
-                    }
+                        },
+                        callback.as_ref(),
+                    );
                 }
             }
+            files_done += 1;
+            progress.advance(files_done);
         }
 
         // Handle deletions: files in registry but not in current_files
-        let deleted_count = self.handle_deletions(&current_files_set, callback.as_ref()).await?;
-        summary.files_deleted = deleted_count;
+        if !summary.cancelled {
+            summary.files_deleted = self.handle_deletions(&current_files, callback.as_ref()).await?;
+        }
+        progress.finish(files_done, summary.cancelled, !summary.errors.is_empty());
 
         // Emit completion event
-        if let Some(ref cb) = callback {
-            cb(SyncEvent::SyncCompleted {
+        self.emit(
+            SyncEvent::SyncCompleted {
                 files_created: summary.files_created,
                 files_updated: summary.files_updated,
                 files_deleted: summary.files_deleted,
-            });
-        }
+                files_renamed: summary.files_renamed,
+            },
+            callback.as_ref(),
+        );
 
         tracing::info!(
-            "One-time sync completed: {} created, {} updated, {} deleted, {} unchanged, {} errors",
+            "One-time sync completed: {} created, {} updated, {} deleted, {} renamed, {} unchanged, {} conflicts, {} errors",
             summary.files_created,
             summary.files_updated,
             summary.files_deleted,
+            summary.files_renamed,
             summary.files_unchanged,
+            summary.conflicts,
             summary.errors.len()
         );
 
@@ -168,6 +587,7 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
     }
 
     /// Sync a single file, determining if it's new, updated, or unchanged
+    #[tracing::instrument(skip_all, fields(path = %file_path.display()))]
     async fn sync_file(
         &self,
         file_path: &PathBuf,
@@ -179,25 +599,27 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
         let modified = file_meta.modified()?;
 
         // Extract title from filename
-        let title = file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid filename: {}", file_path.display())
-            ))?
-            .to_string();
+        let title = title_from_path(file_path)?;
 
         // Check sync registry to determine if file needs syncing
         let mut registry = self.sync_registry.lock().await;
-        let needs_sync = if let Some(metadata) = registry.get(file_path) {
-            // File was previously synced, check if it changed
-            modified > metadata.last_modified
-        } else {
+        let previous = match registry.get(file_path) {
+            // File was previously synced and hasn't been touched since
+            Some(metadata) if modified <= metadata.last_modified => {
+                summary.files_unchanged += 1;
+                return Ok(());
+            }
+            Some(metadata) => Some(metadata.clone()),
             // New file
-            true
+            None => None,
         };
 
+        // A newer mtime alone (git checkout, Syncthing, restored backups) isn't a change
+        let content_hash = hash_file(file_path).await?;
+        let needs_sync = previous
+            .as_ref()
+            .is_none_or(|metadata| metadata.content_hash != content_hash);
+
         if needs_sync {
             // Check if page already exists in repository (for determining create vs update)
             let repo = self.repository.lock().await;
@@ -205,32 +627,50 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
             drop(repo); // Release lock before parsing
 
             // Parse the file
-            let page = LogseqMarkdownParser::parse_file(file_path).await?;
+            let bytes = tokio::fs::read(file_path).await?;
+            let page = self.parse_synced(&registry, file_path, &bytes).await?;
+            let (page, duplicate) = self.resolve_duplicate_title(&registry, file_path, page).await?;
+            let page_id = page.id().clone();
+            let page_hash = page_fingerprint(&page);
+
+            let edited = self.edited_in_repository(previous.as_ref(), &page).await?;
+            if let Some(repository_page) = edited {
+                summary.conflicts += 1;
+                self.emit_conflict(file_path, repository_page, page, callback);
+                return Ok(());
+            }
 
             // Save to repository
             let mut repo = self.repository.lock().await;
-            repo.save(page)?;
+            tracing::info_span!("save", page_id = %page_id).in_scope(|| repo.save(page))?;
             drop(repo); // Release lock
 
             // Update registry
+            registry.set_page_hash(&page_id, &page_hash);
             registry.insert(file_path.clone(), FileMetadata {
-                title: title.clone(),
+                page_id,
                 last_modified: modified,
+                content_hash,
+                page_hash,
             });
+            drop(registry);
+
+            if let Some(duplicate) = duplicate {
+                self.emit(SyncEvent::DuplicateTitle(duplicate), callback);
+            }
 
             // Update summary and emit event
             if existing_page.is_some() {
                 summary.files_updated += 1;
-                if let Some(cb) = callback {
-                    cb(SyncEvent::FileUpdated { file_path: file_path.clone() });
-                }
+                self.emit(SyncEvent::FileUpdated { file_path: file_path.clone() }, callback);
             } else {
                 summary.files_created += 1;
-                if let Some(cb) = callback {
-                    cb(SyncEvent::FileCreated { file_path: file_path.clone() });
-                }
+                self.emit(SyncEvent::FileCreated { file_path: file_path.clone() }, callback);
             }
         } else {
+            if let Some(metadata) = registry.get_mut(file_path) {
+                metadata.last_modified = modified;
+            }
             summary.files_unchanged += 1;
         }
 
@@ -240,66 +680,222 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
     /// Handle deleted files by removing them from repository and registry
     async fn handle_deletions(
         &self,
-        current_files: &HashSet<PathBuf>,
+        current_files: &[PathBuf],
         callback: Option<&SyncCallback>,
     ) -> SyncResult<usize> {
         let mut deleted_count = 0;
-        let mut registry = self.sync_registry.lock().await;
 
         // Find files in registry that are no longer in the directory
-        let to_delete: Vec<PathBuf> = registry
-            .keys()
-            .filter(|path| !current_files.contains(*path))
-            .cloned()
-            .collect();
+        let to_delete = self.sync_registry.lock().await.missing_from(current_files);
 
         for file_path in to_delete {
-            if let Some(metadata) = registry.remove(&file_path) {
-                // Try to delete from repository using the title
-                let mut repo = self.repository.lock().await;
-                if let Ok(Some(page)) = repo.find_by_title(&metadata.title) {
-                    let page_id = page.id().clone();
-                    if repo.delete(&page_id).is_ok() {
-                        deleted_count += 1;
-
-                        if let Some(cb) = callback {
-                            cb(SyncEvent::FileDeleted { file_path: file_path.clone() });
-                        }
-
-                        tracing::info!("Deleted page '{}' (file: {})", metadata.title, file_path.display());
-                    }
-                }
-                drop(repo); // Release lock
+            if self.delete_page_for_file(&file_path).await?.is_some() {
+                deleted_count += 1;
+
+                self.emit(SyncEvent::FileDeleted { file_path: file_path.clone() }, callback);
             }
         }
 
         Ok(deleted_count)
     }
 
+    /// Remove the page parsed from `file_path`, its blocks and its embeddings
+    ///
+    /// The page is found through the sync registry, falling back to the file
+    /// path recorded on pages in the repository. Returns the ID of the deleted
+    /// page, or `None` if no page came from that file.
+    async fn delete_page_for_file(&self, file_path: &Path) -> SyncResult<Option<PageId>> {
+        let registered = self.sync_registry.lock().await.remove(file_path);
+
+        let mut repo = self.repository.lock().await;
+        let page_id = match registered {
+            Some(metadata) => Some(metadata.page_id),
+            None => repo.find_by_file_path(file_path)?.map(|page| page.id().clone()),
+        };
+        let Some(page_id) = page_id else {
+            return Ok(None);
+        };
+
+        // A page merged from several files lives on with the files that remain
+        let remaining = self.sync_registry.lock().await.paths_for_page(&page_id);
+        if let Some(title) = remaining.first().map(|file| title_from_path(file)).transpose()? {
+            drop(repo);
+            let page = merged_page(page_id.clone(), &title, &remaining, None).await?;
+            let page_hash = page_fingerprint(&page);
+            self.repository.lock().await.save(page)?;
+            self.sync_registry.lock().await.set_page_hash(&page_id, &page_hash);
+
+            tracing::info!("Removed {} from merged page {}", file_path.display(), page_id);
+            return Ok(None);
+        }
+
+        let deleted = repo.delete(&page_id)?;
+        drop(repo); // Release lock before touching the vector store
+
+        if let Some(ref embedding_service) = self.embedding_service {
+            embedding_service.delete_page_embeddings(&page_id).await?;
+        }
+
+        if deleted {
+            tracing::info!("Deleted page {} (file: {})", page_id, file_path.display());
+        }
+        Ok(deleted.then_some(page_id))
+    }
+
+    /// Pair vanished files with new files of identical content
+    ///
+    /// `missing` must be files known to the sync registry; unreadable new files
+    /// are skipped (they are synced as creations instead).
+    async fn detect_renames(&self, missing: &[PathBuf], new: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+        if missing.is_empty() || new.is_empty() {
+            return Vec::new();
+        }
+
+        let deleted: Vec<(PathBuf, String)> = {
+            let registry = self.sync_registry.lock().await;
+            missing
+                .iter()
+                .filter_map(|path| {
+                    registry
+                        .get(path)
+                        .map(|metadata| (path.clone(), metadata.content_hash.clone()))
+                })
+                .collect()
+        };
+
+        let mut created = Vec::new();
+        for path in new {
+            if let Ok(hash) = hash_file(path).await {
+                created.push((path.clone(), hash));
+            }
+        }
+
+        match_renames(&deleted, &created)
+    }
+
+    /// Move the page synced from `from` over to the file `to`
+    ///
+    /// The page keeps its ID (so links and embeddings stay attached) and takes
+    /// the new file's name as its title. Returns `false` if `from` has no page.
+    async fn rename_page(&self, from: &Path, to: &Path) -> SyncResult<bool> {
+        let Some(metadata) = self.sync_registry.lock().await.get(from).cloned() else {
+            return Ok(false);
+        };
+        let title = title_from_path(to)?;
+
+        let mut repo = self.repository.lock().await;
+        let Some(mut page) = repo.find_by_id(&metadata.page_id)? else {
+            return Ok(false);
+        };
+        page.set_title(title);
+        page.set_file_path(to.to_path_buf());
+        repo.save(page)?;
+        drop(repo);
+
+        tracing::info!(
+            "Renamed page {} ({} -> {})",
+            metadata.page_id,
+            from.display(),
+            to.display()
+        );
+
+        let last_modified = tokio::fs::metadata(to).await?.modified()?;
+        let mut registry = self.sync_registry.lock().await;
+        registry.remove(from);
+        registry.insert(
+            to.to_path_buf(),
+            FileMetadata {
+                last_modified,
+                ..metadata
+            },
+        );
+        Ok(true)
+    }
+
     /// Start watching for file changes and sync them
-    /// This runs indefinitely until cancelled
+    /// This runs until the service is cancelled (see [`with_cancellation`](Self::with_cancellation))
     pub async fn start_watching(
         &self,
         callback: Option<SyncCallback>,
+    ) -> SyncResult<()> {
+        self.emit(SyncEvent::SyncStarted, callback.as_ref());
+
+        // Never signalled, and kept alive for as long as the watch runs
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        self.watch_until(shutdown_rx, callback).await
+    }
+
+    /// Watch for file changes until `shutdown` becomes true or the service is cancelled
+    ///
+    /// Polls the watcher instead of blocking on it, so many services can watch
+    /// side by side on one runtime. Every reconcile interval (see
+    /// [`with_reconcile_interval`](Self::with_reconcile_interval)) a full
+    /// [`sync_once`](Self::sync_once) picks up anything the watcher missed.
+    pub async fn watch_until(
+        &self,
+        mut shutdown: watch::Receiver<bool>,
+        callback: Option<SyncCallback>,
     ) -> SyncResult<()> {
         tracing::info!("Starting file watcher for {:?}", self.directory_path);
 
-        if let Some(ref cb) = callback {
-            cb(SyncEvent::SyncStarted);
-        }
+        self.update_status(|status| status.watching = true);
+        let result = self.watch_loop(&mut shutdown, callback).await;
+        self.update_status(|status| {
+            status.watching = false;
+            status.pending_operations = 0;
+        });
 
-        loop {
-            // Wait for file events (blocking)
-            if let Some(events) = self.watcher.recv() {
+        tracing::info!("Stopped file watcher for {:?}", self.directory_path);
+        result
+    }
+
+    async fn watch_loop(
+        &self,
+        shutdown: &mut watch::Receiver<bool>,
+        callback: Option<SyncCallback>,
+    ) -> SyncResult<()> {
+        let mut next_reconcile = self.reconcile_interval.map(|interval| Instant::now() + interval);
+
+        while !*shutdown.borrow() && !self.cancellation.is_cancelled() {
+            if next_reconcile.is_some_and(|at| Instant::now() >= at) {
+                self.reconcile(callback.clone()).await;
+                next_reconcile = self.reconcile_interval.map(|interval| Instant::now() + interval);
+            }
+
+            if let Some(events) = self.watcher.try_recv() {
                 self.process_events(events, callback.clone()).await?;
+                continue;
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+                _ = self.cancellation.cancelled() => {}
+                // A dropped sender can never signal again; treat it as shutdown
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
             }
+        }
+
+        Ok(())
+    }
 
-            // Small delay to prevent busy waiting
-            tokio::time::sleep(Duration::from_millis(10)).await;
+    /// Run a full sync from the watcher loop; failures are logged, not fatal
+    async fn reconcile(&self, callback: Option<SyncCallback>) {
+        tracing::debug!("Reconciling {:?}", self.directory_path);
+        match self.sync_once(callback).await {
+            Ok(summary) => tracing::debug!("Reconcile finished: {:?}", summary),
+            Err(e) => tracing::warn!("Reconcile of {:?} failed: {}", self.directory_path, e),
         }
     }
 
     /// Process a batch of file events
+    ///
+    /// Operations are applied [`batch_size`](Self::with_batch_size) at a time,
+    /// and the pages each chunk produces are saved with a single
+    /// [`PageRepository::save_all`] call.
     async fn process_events(
         &self,
         events: Vec<FileEvent>,
@@ -307,96 +903,776 @@ impl<R: PageRepository + Send + 'static> SyncService<R> {
     ) -> SyncResult<()> {
         let mut stats = SyncStats::default();
 
-        for event in events {
-            let operation = match event.kind {
-                FileEventKind::Created => SyncOperation::Create(event.path.clone()),
-                FileEventKind::Modified => SyncOperation::Update(event.path.clone()),
-                FileEventKind::Deleted => SyncOperation::Delete(event.path.clone()),
-            };
+        let operations = self.plan_operations(events).await;
+        let total = operations.len();
+        self.update_status(|status| status.pending_operations = total);
+        let progress = ProgressOperation::start(self.progress.as_ref(), OperationKind::Sync, Some(total));
 
-            match self.process_operation(operation, callback.as_ref()).await {
-                Ok(op_type) => {
-                    match op_type {
-                        FileEventKind::Created => stats.files_created += 1,
-                        FileEventKind::Modified => stats.files_updated += 1,
-                        FileEventKind::Deleted => stats.files_deleted += 1,
+        let mut operations = operations.into_iter();
+        let mut processed = 0;
+        let (mut cancelled, mut failed) = (false, false);
+        loop {
+            let chunk: Vec<SyncOperation> = operations.by_ref().take(self.batch_size).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            if self.cancellation.is_cancelled() {
+                tracing::info!("Sync cancelled with {} of {} operations left", total - processed, total);
+                cancelled = true;
+                break;
+            }
+            let chunk_len = chunk.len();
+
+            let mut saves = Vec::new();
+            for operation in chunk {
+                let file_path = operation.path().to_path_buf();
+                match self.apply_operation(operation, callback.as_ref()).await {
+                    Ok(Applied::Done(outcome)) => stats.record(outcome),
+                    Ok(Applied::Save(save, outcome)) => saves.push((*save, outcome)),
+                    Err(e) => {
+                        failed = true;
+                        self.report_error(file_path, &e, callback.as_ref());
                     }
                 }
+            }
+
+            let paths: Vec<PathBuf> = saves.iter().map(|(save, _)| save.path.clone()).collect();
+            match self.commit_saves(saves, callback.as_ref()).await {
+                Ok(outcomes) => outcomes.into_iter().for_each(|outcome| stats.record(outcome)),
                 Err(e) => {
-                    tracing::error!("Failed to sync {}: {}", event.path.display(), e);
-                    if let Some(ref cb) = callback {
-                        cb(SyncEvent::Error {
-                            file_path: event.path.clone(),
-                            error: e.to_string(),
-                        });
+                    failed = true;
+                    for path in paths {
+                        self.report_error(path, &e, callback.as_ref());
                     }
                 }
             }
+
+            processed += chunk_len;
+            progress.advance(processed);
+            self.update_status(|status| status.pending_operations -= chunk_len);
+            if total > self.batch_size {
+                self.emit(SyncEvent::Progress { processed, total }, callback.as_ref());
+            }
         }
+        progress.finish(processed, cancelled, failed);
 
         // Emit completion event
-        if let Some(ref cb) = callback {
-            cb(SyncEvent::SyncCompleted {
+        self.emit(
+            SyncEvent::SyncCompleted {
                 files_created: stats.files_created,
                 files_updated: stats.files_updated,
                 files_deleted: stats.files_deleted,
-            });
+                files_renamed: stats.files_renamed,
+            },
+            callback.as_ref(),
+        );
+
+        Ok(())
+    }
+
+    fn report_error(&self, file_path: PathBuf, error: &SyncError, callback: Option<&SyncCallback>) {
+        tracing::error!("Failed to sync {}: {}", file_path.display(), error);
+        self.emit(
+            SyncEvent::Error {
+                file_path,
+                error: error.to_string(),
+            },
+            callback,
+        );
+    }
+
+    /// Turn a batch of watcher events into sync operations
+    ///
+    /// Events are first coalesced to one per path. The debounced watcher only
+    /// reports that a path changed, so the operation is derived from whether
+    /// the file still exists and whether it was synced before, not from the
+    /// event kind. Deletions and creations of identical content within the
+    /// batch become renames.
+    async fn plan_operations(&self, events: Vec<FileEvent>) -> Vec<SyncOperation> {
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        let mut deleted = Vec::new();
+
+        let events = events
+            .into_iter()
+            .filter(|event| {
+                self.directory_path.contains_content(&event.path)
+                    && !self.ignore_rules.is_ignored(&event.path)
+            })
+            .collect();
+
+        for event in coalesce_events(events) {
+            let exists = tokio::fs::try_exists(&event.path).await.unwrap_or(false);
+            let known = self.sync_registry.lock().await.contains_key(&event.path);
+            match (exists, known) {
+                (false, _) => deleted.push(event.path),
+                (true, false) => created.push(event.path),
+                (true, true) => updated.push(event.path),
+            }
+        }
+
+        let renames = self.detect_renames(&deleted, &created).await;
+        let renamed_from: HashSet<&PathBuf> = renames.iter().map(|(from, _)| from).collect();
+        let renamed_to: HashSet<&PathBuf> = renames.iter().map(|(_, to)| to).collect();
+
+        let mut operations: Vec<SyncOperation> = deleted
+            .iter()
+            .filter(|path| !renamed_from.contains(path))
+            .cloned()
+            .map(SyncOperation::Delete)
+            .collect();
+        operations.extend(
+            created
+                .iter()
+                .filter(|path| !renamed_to.contains(path))
+                .cloned()
+                .map(SyncOperation::Create),
+        );
+        operations.extend(updated.into_iter().map(SyncOperation::Update));
+        operations.extend(
+            renames
+                .into_iter()
+                .map(|(from, to)| SyncOperation::Rename { from, to }),
+        );
+        operations
+    }
+
+    /// Whether a synced file still has the content it was synced with
+    ///
+    /// If so, its recorded modification time is refreshed.
+    async fn content_unchanged(&self, path: &Path) -> SyncResult<bool> {
+        let Some(previous_hash) = self
+            .sync_registry
+            .lock()
+            .await
+            .get(path)
+            .map(|metadata| metadata.content_hash.clone())
+        else {
+            return Ok(false);
+        };
+
+        if hash_file(path).await? != previous_hash {
+            return Ok(false);
+        }
+
+        let last_modified = tokio::fs::metadata(path).await?.modified()?;
+        if let Some(metadata) = self.sync_registry.lock().await.get_mut(path) {
+            metadata.last_modified = last_modified;
+        }
+        Ok(true)
+    }
+
+    /// Remove the page synced from a deleted file and report the deletion
+    async fn delete_file(
+        &self,
+        path: &Path,
+        callback: Option<&SyncCallback>,
+    ) -> SyncResult<SyncOutcome> {
+        tracing::info!("File deleted: {}", path.display());
+
+        if self.delete_page_for_file(path).await?.is_none() {
+            tracing::debug!("No page was synced from {}", path.display());
+        }
+
+        self.emit(SyncEvent::FileDeleted { file_path: path.to_path_buf() }, callback);
+
+        Ok(SyncOutcome::Deleted)
+    }
+
+    /// Parse a file, save its page and record it in the sync registry
+    ///
+    /// With `check_conflicts`, nothing is saved if the page was also edited in
+    /// the repository since the last sync; the conflict is returned instead.
+    async fn save_file(
+        &self,
+        path: &Path,
+        check_conflicts: bool,
+    ) -> SyncResult<Option<SyncConflict>> {
+        match self.prepare_save(path, check_conflicts).await? {
+            PreparedSave::Ready(save) => {
+                self.store_pages(vec![save]).await?;
+                Ok(None)
+            }
+            PreparedSave::Conflict(conflict) => Ok(Some(conflict)),
+        }
+    }
+
+    /// Parse a file into the page to save, checking for conflicting edits
+    ///
+    /// The file is read once its contents have settled (see
+    /// [`with_read_stability`](Self::with_read_stability)), so a write still in
+    /// progress isn't indexed half-finished.
+    async fn prepare_save(&self, path: &Path, check_conflicts: bool) -> SyncResult<PreparedSave> {
+        // Parse the file
+        let bytes = self.read_stability.read(path).await?;
+        let (page, duplicate) = {
+            let registry = self.sync_registry.lock().await;
+            let page = self.parse_synced(&registry, path, &bytes).await?;
+            self.resolve_duplicate_title(&registry, path, page).await?
+        };
+        let metadata = FileMetadata {
+            page_id: page.id().clone(),
+            last_modified: tokio::fs::metadata(path).await?.modified()?,
+            content_hash: format!("{:x}", Sha256::digest(&bytes)),
+            page_hash: page_fingerprint(&page),
+        };
+
+        if check_conflicts {
+            let previous = self.sync_registry.lock().await.get(path).cloned();
+            let edited = self.edited_in_repository(previous.as_ref(), &page).await?;
+            if let Some(repository_page) = edited {
+                return Ok(PreparedSave::Conflict(SyncConflict {
+                    file_path: path.to_path_buf(),
+                    repository_page,
+                    file_page: page,
+                }));
+            }
+        }
+
+        Ok(PreparedSave::Ready(PendingSave {
+            path: path.to_path_buf(),
+            page,
+            metadata,
+            duplicate,
+        }))
+    }
+
+    /// Parse a file's contents into the page it was synced into before, if any
+    ///
+    /// Keeping the page's ID makes an edited file update its page instead of
+    /// saving a new one beside it. Files synced before a restart aren't in
+    /// the registry, so the repository is asked which page came from `path`.
+    async fn parse_synced(&self, registry: &SyncRegistry, path: &Path, bytes: &[u8]) -> SyncResult<Page> {
+        let content = LogseqMarkdownParser::decode(bytes);
+        let page_id = match registry.get(path) {
+            Some(metadata) => Some(metadata.page_id.clone()),
+            None => self.repository.lock().await.find_by_file_path(path)?.map(|page| page.id().clone()),
+        };
+        Ok(match page_id {
+            Some(page_id) => LogseqMarkdownParser::parse_file_content_with_id(path, &content, page_id)?,
+            None => LogseqMarkdownParser::parse_file_content(path, &content)?,
+        })
+    }
+
+    /// Apply the duplicate title policy to a page parsed from `path`
+    ///
+    /// Returns the page to save, and what was done if its title was taken.
+    async fn resolve_duplicate_title(
+        &self,
+        registry: &SyncRegistry,
+        path: &Path,
+        mut page: Page,
+    ) -> SyncResult<(Page, Option<DuplicateTitleResolution>)> {
+        let title = page.title().to_string();
+        let holder = self.repository.lock().await.find_by_title(&title)?;
+        let kept_by = holder
+            .as_ref()
+            .and_then(|holder| holder.file_path())
+            .filter(|file| path_key(file) != path_key(path))
+            .map(Path::to_path_buf);
+
+        let resolution = |kept_by: PathBuf, action| DuplicateTitleResolution {
+            file_path: path.to_path_buf(),
+            title: title.clone(),
+            kept_by,
+            action,
+        };
+
+        match self.duplicate_title_policy {
+            DuplicateTitlePolicy::Rename => {
+                let Some(kept_by) = kept_by else {
+                    return Ok((page, None));
+                };
+                let repo = self.repository.lock().await;
+                // A numbered title this file's page already has is free for it
+                let renamed = numbered_title(&title, |candidate| {
+                    repo.find_by_title(candidate).ok().flatten().is_some_and(|existing| {
+                        existing.file_path().map(path_key) != Some(path_key(path))
+                    })
+                });
+                drop(repo);
+
+                tracing::info!(
+                    "{} has the same title as {}; saving it as {}",
+                    path.display(),
+                    kept_by.display(),
+                    renamed
+                );
+                page.set_title(renamed.clone());
+                let action = DuplicateTitleAction::Renamed { title: renamed };
+                Ok((page, Some(resolution(kept_by, action))))
+            }
+            DuplicateTitlePolicy::Error => match kept_by {
+                Some(kept_by) => Err(SyncError::DuplicateTitle { title, kept_by }),
+                None => Ok((page, None)),
+            },
+            DuplicateTitlePolicy::Merge => {
+                // Every synced file with this title, the title's holder first
+                let mut files: Vec<PathBuf> = registry
+                    .iter()
+                    .map(|(file, _)| file)
+                    .filter(|file| path_key(file) != path_key(path))
+                    .filter(|file| title_from_path(file).is_ok_and(|file_title| file_title == title))
+                    .cloned()
+                    .chain(kept_by.clone())
+                    .collect();
+                if files.is_empty() {
+                    return Ok((page, None));
+                }
+                files.push(path.to_path_buf());
+                files.sort();
+                files.dedup_by(|a, b| path_key(a) == path_key(b));
+                let holder_file = holder.as_ref().and_then(|holder| holder.file_path()).map(path_key);
+                if let Some(position) = files.iter().position(|file| Some(path_key(file)) == holder_file) {
+                    let holder_file = files.remove(position);
+                    files.insert(0, holder_file);
+                }
+
+                let page_id = holder.map_or_else(|| page.id().clone(), |holder| holder.id().clone());
+                let first = files[0].clone();
+                let merged = merged_page(page_id, &title, &files, Some((path, page))).await?;
+
+                // Changes to the file that holds the title need no reporting
+                if path_key(&first) == path_key(path) {
+                    return Ok((merged, None));
+                }
+                tracing::info!(
+                    "{} has the same title as {}; merging it into that page",
+                    path.display(),
+                    first.display()
+                );
+                Ok((merged, Some(resolution(first, DuplicateTitleAction::Merged))))
+            }
+        }
+    }
+
+    /// Save parsed pages in one repository call and record their files
+    async fn store_pages(&self, saves: Vec<PendingSave>) -> SyncResult<()> {
+        let mut registered = Vec::with_capacity(saves.len());
+        let mut pages = Vec::with_capacity(saves.len());
+        for save in saves {
+            registered.push((save.path, save.metadata));
+            pages.push(save.page);
+        }
+
+        self.repository.lock().await.save_all(pages)?;
+
+        // Remember which page each file produced, so a later deletion can remove it
+        let mut registry = self.sync_registry.lock().await;
+        for (path, metadata) in registered {
+            registry.set_page_hash(&metadata.page_id, &metadata.page_hash);
+            registry.insert(path, metadata);
+        }
+        Ok(())
+    }
+
+    /// Store pending saves and report each file as created or updated
+    async fn commit_saves(
+        &self,
+        saves: Vec<(PendingSave, SyncOutcome)>,
+        callback: Option<&SyncCallback>,
+    ) -> SyncResult<Vec<SyncOutcome>> {
+        if saves.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (mut saves, outcomes): (Vec<PendingSave>, Vec<SyncOutcome>) = saves.into_iter().unzip();
+        let paths: Vec<PathBuf> = saves.iter().map(|save| save.path.clone()).collect();
+        let duplicates: Vec<DuplicateTitleResolution> =
+            saves.iter_mut().filter_map(|save| save.duplicate.take()).collect();
+        self.store_pages(saves).await?;
+
+        for duplicate in duplicates {
+            self.emit(SyncEvent::DuplicateTitle(duplicate), callback);
+        }
+        for (file_path, outcome) in paths.into_iter().zip(&outcomes) {
+            self.emit(
+                if *outcome == SyncOutcome::Created {
+                    SyncEvent::FileCreated { file_path }
+                } else {
+                    SyncEvent::FileUpdated { file_path }
+                },
+                callback,
+            );
+        }
+        Ok(outcomes)
+    }
+
+    /// The repository's page, if it was edited since the file was last synced
+    ///
+    /// An edit that already matches the file's new contents is not a conflict.
+    async fn edited_in_repository(
+        &self,
+        previous: Option<&FileMetadata>,
+        parsed: &Page,
+    ) -> SyncResult<Option<Page>> {
+        let Some(previous) = previous else {
+            return Ok(None);
+        };
+        let Some(current) = self.repository.lock().await.find_by_id(&previous.page_id)? else {
+            return Ok(None);
+        };
+
+        let current_hash = page_fingerprint(&current);
+        if current_hash == previous.page_hash || current_hash == page_fingerprint(parsed) {
+            return Ok(None);
+        }
+        Ok(Some(current))
+    }
+
+    fn emit_conflict(
+        &self,
+        file_path: &Path,
+        repository_page: Page,
+        file_page: Page,
+        callback: Option<&SyncCallback>,
+    ) {
+        tracing::warn!(
+            "Conflict: {} and its page were both changed since the last sync",
+            file_path.display()
+        );
+        self.emit(
+            SyncEvent::Conflict(Box::new(SyncConflict {
+                file_path: file_path.to_path_buf(),
+                repository_page,
+                file_page,
+            })),
+            callback,
+        );
+    }
+
+    /// Write pages edited in the repository back to their markdown files
+    ///
+    /// Only pages synced from a file are considered, and only if they changed
+    /// since that sync. If the file changed on disk as well, a
+    /// [`SyncEvent::Conflict`] is emitted and neither side is touched.
+    ///
+    /// The hash of each written file is recorded in the sync registry before
+    /// the write, so the watcher events it triggers are recognized as unchanged
+    /// content and the page isn't re-imported. Returns the number of files written.
+    pub async fn write_back(&self, callback: Option<SyncCallback>) -> SyncResult<usize> {
+        let synced: Vec<(PathBuf, FileMetadata)> = self
+            .sync_registry
+            .lock()
+            .await
+            .iter()
+            .map(|(path, metadata)| (path.clone(), metadata.clone()))
+            .collect();
+
+        let mut files_per_page: HashMap<&PageId, usize> = HashMap::new();
+        for (_, metadata) in &synced {
+            *files_per_page.entry(&metadata.page_id).or_default() += 1;
         }
+        let merged: HashSet<PageId> = files_per_page
+            .into_iter()
+            .filter(|(_, files)| *files > 1)
+            .map(|(page_id, _)| page_id.clone())
+            .collect();
 
+        let mut written = 0;
+        for (file_path, metadata) in synced {
+            if merged.contains(&metadata.page_id) {
+                // There is no telling which of its files an edit belongs in
+                tracing::debug!("Not writing back {}: its page is merged from several files", file_path.display());
+                continue;
+            }
+            let Some(page) = self.repository.lock().await.find_by_id(&metadata.page_id)? else {
+                continue;
+            };
+            if page_fingerprint(&page) == metadata.page_hash {
+                continue;
+            }
+            if self.write_page_file(&file_path, metadata, page, callback.as_ref()).await? {
+                written += 1;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Write one edited page to its file unless the file changed too
+    async fn write_page_file(
+        &self,
+        file_path: &Path,
+        metadata: FileMetadata,
+        page: Page,
+        callback: Option<&SyncCallback>,
+    ) -> SyncResult<bool> {
+        match hash_file(file_path).await {
+            Ok(hash) if hash == metadata.content_hash => {}
+            Ok(_) => {
+                let file_page = LogseqMarkdownParser::parse_file(file_path).await?;
+                self.emit_conflict(file_path, page, file_page, callback);
+                return Ok(false);
+            }
+            // Deleted on disk; writing would resurrect it before the deletion is synced
+            Err(SyncError::FileSystem(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!("Not writing back {}: file was deleted", file_path.display());
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        }
+
+        // Register the new content first so the watcher treats the write as an echo
+        let content = LogseqMarkdownWriter::render(&page);
+        let pending = FileMetadata {
+            content_hash: format!("{:x}", Sha256::digest(content.as_bytes())),
+            page_hash: page_fingerprint(&page),
+            ..metadata.clone()
+        };
+        self.sync_registry
+            .lock()
+            .await
+            .insert(file_path.to_path_buf(), pending.clone());
+
+        if let Err(e) = LogseqMarkdownWriter::write_file(&page, file_path).await {
+            self.sync_registry
+                .lock()
+                .await
+                .insert(file_path.to_path_buf(), metadata);
+            return Err(e.into());
+        }
+
+        let last_modified = tokio::fs::metadata(file_path).await?.modified()?;
+        self.sync_registry.lock().await.insert(
+            file_path.to_path_buf(),
+            FileMetadata {
+                last_modified,
+                ..pending
+            },
+        );
+
+        tracing::info!("Wrote page {} back to {}", page.id(), file_path.display());
+        self.emit(
+            SyncEvent::FileWritten {
+                file_path: file_path.to_path_buf(),
+            },
+            callback,
+        );
+        Ok(true)
+    }
+
+    /// Settle a conflict reported by [`SyncEvent::Conflict`]
+    pub async fn resolve_conflict(
+        &self,
+        file_path: &Path,
+        resolution: ConflictResolution,
+    ) -> SyncResult<()> {
+        match resolution {
+            ConflictResolution::KeepFile => {
+                self.save_file(file_path, false).await?;
+            }
+            ConflictResolution::KeepRepository => {
+                let Some(metadata) = self.sync_registry.lock().await.get(file_path).cloned() else {
+                    return Ok(());
+                };
+                let repository_page = self.repository.lock().await.find_by_id(&metadata.page_id)?;
+                let page_hash = repository_page
+                    .as_ref()
+                    .map_or(metadata.page_hash.clone(), page_fingerprint);
+
+                // Treat the file's current contents as seen, so only later
+                // edits to it are synced again
+                let updated = FileMetadata {
+                    last_modified: tokio::fs::metadata(file_path).await?.modified()?,
+                    content_hash: hash_file(file_path).await?,
+                    page_hash,
+                    ..metadata
+                };
+                self.sync_registry.lock().await.insert(file_path.to_path_buf(), updated);
+            }
+        }
         Ok(())
     }
 
-    /// Process a single sync operation
-    async fn process_operation(
+    /// Apply a single sync operation, leaving any page save to the caller
+    async fn apply_operation(
         &self,
         operation: SyncOperation,
         callback: Option<&SyncCallback>,
-    ) -> SyncResult<FileEventKind> {
+    ) -> SyncResult<Applied> {
         match &operation {
-            SyncOperation::Create(path) | SyncOperation::Update(path) => {
-                // Parse the file
-                let page = LogseqMarkdownParser::parse_file(path).await?;
+            SyncOperation::Create(path) | SyncOperation::Update(path)
+                if !tokio::fs::try_exists(path).await.unwrap_or(false) =>
+            {
+                // Removed again after the batch was planned (e.g. an editor's temp file)
+                if self.sync_registry.lock().await.contains_key(path) {
+                    self.delete_file(path, callback).await.map(Applied::Done)
+                } else {
+                    tracing::debug!("Skipping {}: file disappeared", path.display());
+                    Ok(Applied::Done(SyncOutcome::Unchanged))
+                }
+            }
 
-                // Save to repository
-                let mut repo = self.repository.lock().await;
-                repo.save(page)?;
+            SyncOperation::Update(path) if self.content_unchanged(path).await? => {
+                tracing::debug!("Skipping {}: content unchanged", path.display());
+                Ok(Applied::Done(SyncOutcome::Unchanged))
+            }
 
-                // Emit event and determine result based on operation type
-                let is_create = matches!(operation, SyncOperation::Create(_));
+            SyncOperation::Create(path) | SyncOperation::Update(path) => {
+                let outcome = if matches!(operation, SyncOperation::Create(_)) {
+                    SyncOutcome::Created
+                } else {
+                    SyncOutcome::Updated
+                };
+                self.prepare(path, true, outcome, callback).await
+            }
 
-                if let Some(cb) = callback {
-                    if is_create {
-                        cb(SyncEvent::FileCreated { file_path: path.clone() });
-                    } else {
-                        cb(SyncEvent::FileUpdated { file_path: path.clone() });
-                    }
+            SyncOperation::Delete(path) => self.delete_file(path, callback).await.map(Applied::Done),
+
+            SyncOperation::Rename { from, to } => {
+                if !self.rename_page(from, to).await? {
+                    // The old page is gone; treat the new file as a fresh page. It
+                    // was never synced, so there is nothing to conflict with.
+                    return self.prepare(to, false, SyncOutcome::Created, callback).await;
                 }
 
-                Ok(if is_create {
-                    FileEventKind::Created
-                } else {
-                    FileEventKind::Modified
-                })
+                self.emit(
+                    SyncEvent::FileRenamed {
+                        from: from.clone(),
+                        to: to.clone(),
+                    },
+                    callback,
+                );
+
+                Ok(Applied::Done(SyncOutcome::Renamed))
+            }
+        }
+    }
+
+    /// Prepare a file's page for saving, reporting a conflict instead if there is one
+    async fn prepare(
+        &self,
+        path: &Path,
+        check_conflicts: bool,
+        outcome: SyncOutcome,
+        callback: Option<&SyncCallback>,
+    ) -> SyncResult<Applied> {
+        match self.prepare_save(path, check_conflicts).await? {
+            PreparedSave::Ready(save) => Ok(Applied::Save(Box::new(save), outcome)),
+            PreparedSave::Conflict(conflict) => {
+                self.emit(SyncEvent::Conflict(Box::new(conflict)), callback);
+                Ok(Applied::Done(SyncOutcome::Conflict))
+            }
+        }
+    }
+}
+
+/// One page holding the blocks of several files, in order, under `title`
+///
+/// The first file provides the page's file path and, where files disagree,
+/// its properties. `parsed` is a file that has already been parsed; files
+/// that can no longer be read are left out.
+async fn merged_page(
+    page_id: PageId,
+    title: &str,
+    files: &[PathBuf],
+    mut parsed: Option<(&Path, Page)>,
+) -> SyncResult<Page> {
+    let mut merged = Page::new(page_id, title.to_string());
+    if let Some(first) = files.first() {
+        merged.set_file_path(first.clone());
+    }
+
+    for file in files {
+        let part = match parsed.take_if(|(path, _)| path_key(path) == path_key(file)) {
+            Some((_, page)) => page,
+            None => match LogseqMarkdownParser::parse_file(file).await {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::warn!("Leaving {} out of its merged page: {}", file.display(), e);
+                    continue;
+                }
+            },
+        };
+        for (key, value) in part.properties() {
+            if !merged.properties().contains_key(key) {
+                merged.set_property(key.clone(), value.clone());
             }
+        }
+        merge_blocks(&mut merged, &part)?;
+    }
+    Ok(merged)
+}
+
+/// Page title for a markdown file: its name without the extension
+fn title_from_path(file_path: &Path) -> SyncResult<String> {
+    file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(LogseqMarkdownParser::title_from_file_stem)
+        .ok_or_else(|| {
+            SyncError::FileSystem(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid filename: {}", file_path.display()),
+            ))
+        })
+}
 
-            SyncOperation::Delete(path) => {
-                // For deletion, we'd need to maintain a mapping from file paths to page IDs
-                // For now, we'll just log it
-                tracing::info!("File deleted: {}", path.display());
+/// SHA-256 of a file's contents
+async fn hash_file(file_path: &Path) -> SyncResult<String> {
+    let content = tokio::fs::read(file_path).await?;
+    Ok(format!("{:x}", Sha256::digest(&content)))
+}
 
-                // In a full implementation, you'd:
-                // 1. Look up the page ID from the file path (requires a file->page mapping)
-                // 2. Delete from repository
-                // For now, we just emit the event
+/// Key identifying a file in the sync registry
+///
+/// Windows paths are case-insensitive and accept either separator, so they are
+/// folded to one spelling; elsewhere the path is used as is.
+fn path_key(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(fold_windows_path(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
 
-                if let Some(cb) = callback {
-                    cb(SyncEvent::FileDeleted { file_path: path.clone() });
-                }
+fn fold_windows_path(path: &str) -> String {
+    path.trim_start_matches(r"\\?\").replace('/', "\\").to_lowercase()
+}
 
-                Ok(FileEventKind::Deleted)
+/// SHA-256 over a page's blocks in document order
+///
+/// Block IDs are regenerated on every parse and the title follows the file
+/// name, so only block content and nesting take part.
+fn page_fingerprint(page: &Page) -> String {
+    fn visit(page: &Page, block: &Block, hasher: &mut Sha256) {
+        hasher.update(block.indent_level().value().to_le_bytes());
+        hasher.update(block.content().as_str().as_bytes());
+        hasher.update([0]);
+        for child_id in block.child_ids() {
+            if let Some(child) = page.get_block(child_id) {
+                visit(page, child, hasher);
             }
         }
     }
+
+    let mut hasher = Sha256::new();
+    for block in page.root_blocks() {
+        visit(page, block, &mut hasher);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Pair deleted files with created files of the same content hash
+///
+/// Each file takes part in at most one rename; when several files share
+/// content, they are paired in order.
+fn match_renames(
+    deleted: &[(PathBuf, String)],
+    created: &[(PathBuf, String)],
+) -> Vec<(PathBuf, PathBuf)> {
+    let mut available: Vec<&(PathBuf, String)> = created.iter().collect();
+    let mut renames = Vec::new();
+
+    for (from, hash) in deleted {
+        if let Some(index) = available.iter().position(|(_, h)| h == hash) {
+            let (to, _) = available.remove(index);
+            renames.push((from.clone(), to.clone()));
+        }
+    }
+
+    renames
 }
 
 #[derive(Default)]
@@ -404,6 +1680,19 @@ struct SyncStats {
     files_created: usize,
     files_updated: usize,
     files_deleted: usize,
+    files_renamed: usize,
+}
+
+impl SyncStats {
+    fn record(&mut self, outcome: SyncOutcome) {
+        match outcome {
+            SyncOutcome::Created => self.files_created += 1,
+            SyncOutcome::Updated => self.files_updated += 1,
+            SyncOutcome::Deleted => self.files_deleted += 1,
+            SyncOutcome::Renamed => self.files_renamed += 1,
+            SyncOutcome::Unchanged | SyncOutcome::Conflict => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -412,11 +1701,29 @@ mod tests {
     use crate::application::repositories::PageRepository;
     use crate::domain::aggregates::Page;
     use crate::domain::base::DomainResult;
-    use crate::domain::value_objects::PageId;
+    use crate::domain::value_objects::{BlockContent, PageId};
+    use crate::infrastructure::file_system::FileEventKind;
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
     use tempfile::TempDir;
 
+    impl<R: PageRepository + Send + 'static> SyncService<R> {
+        /// Apply one operation and save its page right away
+        async fn process_operation(
+            &self,
+            operation: SyncOperation,
+            callback: Option<&SyncCallback>,
+        ) -> SyncResult<SyncOutcome> {
+            match self.apply_operation(operation, callback).await? {
+                Applied::Done(outcome) => Ok(outcome),
+                Applied::Save(save, outcome) => {
+                    self.commit_saves(vec![(*save, outcome)], callback).await?;
+                    Ok(outcome)
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_sync_stats() {
         let stats = SyncStats::default();
@@ -443,6 +1750,8 @@ mod tests {
         fn save(&mut self, page: Page) -> DomainResult<()> {
             let title = page.title().to_string();
             let mut pages = self.pages.lock().unwrap();
+            // Saving an existing page (e.g. after a rename) replaces it
+            pages.retain(|_, existing| existing.id() != page.id());
             pages.insert(title, page);
             Ok(())
         }
@@ -502,6 +1811,35 @@ mod tests {
         assert_eq!(summary.errors.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_cancelled_sync_stops_between_files_and_stops_watching() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+        std::fs::write(pages_dir.join("page1.md"), "- First page").unwrap();
+        std::fs::write(pages_dir.join("page2.md"), "- Second page").unwrap();
+
+        let token = CancellationToken::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(MockRepository::new(), dir_path, None)
+            .unwrap()
+            .with_cancellation(token.clone());
+        let callback: SyncCallback = Arc::new(move |event| {
+            if let SyncEvent::FileCreated { .. } = event {
+                token.cancel();
+            }
+        });
+
+        let summary = service.sync_once(Some(callback)).await.unwrap();
+        assert!(summary.cancelled);
+        assert_eq!(summary.files_created, 1);
+
+        let watched = tokio::time::timeout(Duration::from_secs(5), service.start_watching(None)).await;
+        assert!(matches!(watched, Ok(Ok(()))), "watching ignored the cancellation");
+    }
+
     #[tokio::test]
     async fn test_sync_once_updated_files() {
         // Create a temporary Logseq directory
@@ -571,6 +1909,81 @@ mod tests {
         assert_eq!(summary2.files_unchanged, 1);
     }
 
+    #[tokio::test]
+    async fn test_sync_once_skips_touched_but_identical_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let file_path = pages_dir.join("page1.md");
+        std::fs::write(&file_path, "- Same content").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+        let page_id = repo.find_by_title("page1").unwrap().unwrap().id().clone();
+
+        // Rewrite identical content with a newer modification time
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::write(&file_path, "- Same content").unwrap();
+
+        let summary = service.sync_once(None).await.unwrap();
+        assert_eq!(summary.files_updated, 0);
+        assert_eq!(summary.files_unchanged, 1);
+        assert_eq!(repo.find_by_title("page1").unwrap().unwrap().id(), &page_id);
+
+        let outcome = service
+            .process_operation(SyncOperation::Update(file_path), None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, SyncOutcome::Unchanged);
+    }
+
+    #[tokio::test]
+    async fn test_edited_files_keep_their_page_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        std::fs::create_dir(logseq_dir.join("pages")).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+        let file_path = logseq_dir.join("pages").join("page1.md");
+        std::fs::write(&file_path, "- First").unwrap();
+
+        // Keyed by ID, so a page saved under a new ID would sit beside the old one
+        let repo = crate::infrastructure::persistence::InMemoryPageRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path.clone(), None).unwrap();
+        service.sync_once(None).await.unwrap();
+        let page_id = repo.find_by_title("page1").unwrap().unwrap().id().clone();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::write(&file_path, "- Edited").unwrap();
+        assert_eq!(service.sync_once(None).await.unwrap().files_updated, 1);
+        std::fs::write(&file_path, "- Edited again").unwrap();
+        service
+            .process_operation(SyncOperation::Update(file_path.clone()), None)
+            .await
+            .unwrap();
+        assert_eq!(repo.len(), 1);
+        assert_eq!(repo.find_by_title("page1").unwrap().unwrap().id(), &page_id);
+
+        // A new service doesn't know the file, but the repository does
+        let restarted = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        std::fs::write(&file_path, "- After a restart").unwrap();
+        restarted.sync_once(None).await.unwrap();
+        assert_eq!(repo.len(), 1);
+        assert_eq!(repo.find_by_title("page1").unwrap().unwrap().id(), &page_id);
+
+        std::fs::remove_file(&file_path).unwrap();
+        restarted
+            .process_operation(SyncOperation::Delete(file_path), None)
+            .await
+            .unwrap();
+        assert!(repo.is_empty());
+    }
+
     #[tokio::test]
     async fn test_sync_once_deleted_files() {
         // Create a temporary Logseq directory
@@ -608,6 +2021,452 @@ mod tests {
         assert_eq!(summary2.files_unchanged, 1);
     }
 
+    #[tokio::test]
+    async fn test_watcher_delete_removes_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let watched = pages_dir.join("watched.md");
+        std::fs::write(&watched, "- Created while watching").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+
+        service
+            .process_operation(SyncOperation::Create(watched.clone()), None)
+            .await
+            .unwrap();
+        assert!(repo.find_by_title("watched").unwrap().is_some());
+
+        // A page the registry doesn't know about is found by its recorded file path
+        let unregistered = pages_dir.join("unregistered.md");
+        std::fs::write(&unregistered, "- Imported elsewhere").unwrap();
+        let page = LogseqMarkdownParser::parse_file(&unregistered).await.unwrap();
+        repo.clone().save(page).unwrap();
+
+        std::fs::remove_file(&watched).unwrap();
+        std::fs::remove_file(&unregistered).unwrap();
+        for path in [watched, unregistered] {
+            let kind = service
+                .process_operation(SyncOperation::Delete(path), None)
+                .await
+                .unwrap();
+            assert_eq!(kind, SyncOutcome::Deleted);
+        }
+
+        assert!(repo.find_all().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watcher_events_for_ignored_paths_are_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        let backup_dir = logseq_dir.join("logseq").join("bak").join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+        std::fs::create_dir_all(&backup_dir).unwrap();
+
+        let page = pages_dir.join("page.md");
+        let lock_file = pages_dir.join(".#page.md");
+        let backup = backup_dir.join("page.md");
+        for path in [&page, &lock_file, &backup] {
+            std::fs::write(path, "- Content").unwrap();
+        }
+
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(MockRepository::new(), dir_path, None).unwrap();
+
+        let events = [&page, &lock_file, &backup]
+            .into_iter()
+            .map(|path| FileEvent {
+                path: path.clone(),
+                kind: FileEventKind::Modified,
+            })
+            .collect();
+        let operations = service.plan_operations(events).await;
+
+        assert_eq!(operations.len(), 1);
+        assert!(matches!(&operations[0], SyncOperation::Create(path) if *path == page));
+    }
+
+    #[tokio::test]
+    async fn test_files_that_vanish_before_processing() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let synced = pages_dir.join("synced.md");
+        std::fs::write(&synced, "- Synced").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+
+        // A temp file that is gone by the time it is processed is skipped quietly
+        let transient = service
+            .process_operation(SyncOperation::Create(pages_dir.join("transient.md")), None)
+            .await
+            .unwrap();
+        assert_eq!(transient, SyncOutcome::Unchanged);
+
+        // A synced file that vanished is treated as deleted instead of failing to parse
+        std::fs::remove_file(&synced).unwrap();
+        let outcome = service
+            .process_operation(SyncOperation::Update(synced), None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, SyncOutcome::Deleted);
+        assert!(repo.find_all().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_are_reported_as_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let file_path = pages_dir.join("shared.md");
+        std::fs::write(&file_path, "- Original").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+
+        // Edit the page in the repository...
+        let mut page = repo.find_by_title("shared").unwrap().unwrap();
+        let block_id = page.root_blocks()[0].id().clone();
+        page.get_block_mut(&block_id)
+            .unwrap()
+            .update_content(BlockContent::new("Edited in app"));
+        repo.clone().save(page).unwrap();
+
+        // ...and on disk
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        std::fs::write(&file_path, "- Edited on disk").unwrap();
+
+        let mut events = service.subscribe();
+        let summary = service.sync_once(None).await.unwrap();
+        assert_eq!(summary.conflicts, 1);
+        assert_eq!(summary.files_updated, 0);
+
+        let conflict = std::iter::from_fn(|| events.try_recv().ok())
+            .find_map(|event| match event {
+                SyncEvent::Conflict(conflict) => Some(conflict),
+                _ => None,
+            })
+            .expect("conflict event");
+        assert_eq!(conflict.file_path, file_path);
+        assert_eq!(conflict.repository_page.root_blocks()[0].content().as_str(), "Edited in app");
+        assert_eq!(conflict.file_page.root_blocks()[0].content().as_str(), "Edited on disk");
+
+        // Nothing was overwritten
+        let stored = repo.find_by_title("shared").unwrap().unwrap();
+        assert_eq!(stored.root_blocks()[0].content().as_str(), "Edited in app");
+
+        // Keeping the file applies it and clears the conflict
+        service
+            .resolve_conflict(&file_path, ConflictResolution::KeepFile)
+            .await
+            .unwrap();
+        let stored = repo.find_by_title("shared").unwrap().unwrap();
+        assert_eq!(stored.root_blocks()[0].content().as_str(), "Edited on disk");
+        assert_eq!(service.sync_once(None).await.unwrap().conflicts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_back_updates_file_without_reimporting() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let file_path = pages_dir.join("notes.md");
+        std::fs::write(&file_path, "- Original\n  - Child").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+
+        // Nothing edited yet, nothing to write
+        assert_eq!(service.write_back(None).await.unwrap(), 0);
+
+        let mut page = repo.find_by_title("notes").unwrap().unwrap();
+        let page_id = page.id().clone();
+        let block_id = page.root_blocks()[0].id().clone();
+        page.get_block_mut(&block_id)
+            .unwrap()
+            .update_content(BlockContent::new("Edited in app"));
+        repo.clone().save(page).unwrap();
+
+        assert_eq!(service.write_back(None).await.unwrap(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "- Edited in app\n\t- Child\n"
+        );
+
+        // The watcher event caused by our own write is suppressed
+        let outcome = service
+            .process_operation(SyncOperation::Update(file_path.clone()), None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, SyncOutcome::Unchanged);
+
+        let summary = service.sync_once(None).await.unwrap();
+        assert_eq!(summary.files_updated, 0);
+        assert_eq!(summary.conflicts, 0);
+        assert_eq!(repo.find_by_title("notes").unwrap().unwrap().id(), &page_id);
+    }
+
+    #[tokio::test]
+    async fn test_watching_reconciles_periodically() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        std::fs::create_dir(logseq_dir.join("pages")).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = Arc::new(
+            SyncService::new(MockRepository::new(), dir_path, None)
+                .unwrap()
+                .with_reconcile_interval(Some(Duration::from_millis(50))),
+        );
+        let mut events = service.subscribe();
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let watcher = tokio::spawn({
+            let service = Arc::clone(&service);
+            async move { service.watch_until(shutdown_rx, None).await }
+        });
+
+        // Only a full sync emits SyncStarted from inside the watch loop
+        let started = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SyncEvent::SyncStarted = events.recv().await.unwrap() {
+                    break;
+                }
+            }
+        })
+        .await;
+        assert!(started.is_ok(), "no reconcile ran");
+
+        shutdown_tx.send(true).unwrap();
+        watcher.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_status_tracks_syncs_and_watching() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        std::fs::create_dir(logseq_dir.join("pages")).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+        std::fs::write(logseq_dir.join("pages/one.md"), "- One").unwrap();
+        std::fs::write(logseq_dir.join("pages/two.md"), "- Two").unwrap();
+
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = Arc::new(SyncService::new(MockRepository::new(), dir_path, None).unwrap());
+        assert_eq!(service.status(), SyncStatus::default());
+
+        service.sync_once(None).await.unwrap();
+        let status = service.status();
+        assert_eq!(status.files_created, 2);
+        assert!(status.last_sync.is_some());
+        assert!(!status.watching);
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let watcher = tokio::spawn({
+            let service = Arc::clone(&service);
+            async move { service.watch_until(shutdown_rx, None).await }
+        });
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !service.status().watching {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("watcher never started");
+
+        shutdown_tx.send(true).unwrap();
+        watcher.await.unwrap().unwrap();
+        assert!(!service.status().watching);
+    }
+
+    #[test]
+    fn test_status_keeps_only_recent_errors() {
+        let mut status = SyncStatus::default();
+        for i in 0..RECENT_ERROR_LIMIT + 5 {
+            status.record(&SyncEvent::Error {
+                file_path: PathBuf::from(format!("pages/{}.md", i)),
+                error: "parse failed".to_string(),
+            });
+        }
+
+        assert_eq!(status.errors, RECENT_ERROR_LIMIT + 5);
+        assert_eq!(status.recent_errors.len(), RECENT_ERROR_LIMIT);
+        assert_eq!(status.recent_errors[0].file_path, PathBuf::from("pages/5.md"));
+    }
+
+    #[test]
+    fn test_windows_paths_fold_to_one_key() {
+        assert_eq!(
+            fold_windows_path(r"C:\Graph\pages\Note.md"),
+            fold_windows_path("c:/graph/Pages/note.md")
+        );
+        assert_eq!(
+            fold_windows_path(r"\\?\C:\Graph\pages\note.md"),
+            r"c:\graph\pages\note.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_decodes_escaped_file_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+        std::fs::write(pages_dir.join("projects%2Flogjam.md"), "- Namespaced").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+
+        assert!(repo.find_by_title("projects/logjam").unwrap().is_some());
+        assert_eq!(service.sync_once(None).await.unwrap().files_unchanged, 1);
+    }
+
+    #[test]
+    fn test_match_renames() {
+        let path = |name: &str| PathBuf::from(format!("pages/{}.md", name));
+        let deleted = vec![
+            (path("old"), "h1".to_string()),
+            (path("gone"), "h2".to_string()),
+        ];
+        let created = vec![
+            (path("fresh"), "h3".to_string()),
+            (path("new"), "h1".to_string()),
+        ];
+
+        assert_eq!(match_renames(&deleted, &created), vec![(path("old"), path("new"))]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_once_detects_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let old_path = pages_dir.join("foo.md");
+        std::fs::write(&old_path, "- Content that moves").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+        let original_id = repo.find_by_title("foo").unwrap().unwrap().id().clone();
+
+        let new_path = pages_dir.join("bar.md");
+        std::fs::rename(&old_path, &new_path).unwrap();
+
+        let summary = service.sync_once(None).await.unwrap();
+        assert_eq!(summary.files_renamed, 1);
+        assert_eq!(summary.files_created, 0);
+        assert_eq!(summary.files_deleted, 0);
+
+        let renamed = repo.find_by_title("bar").unwrap().unwrap();
+        assert_eq!(renamed.id(), &original_id);
+        assert_eq!(renamed.file_path(), Some(new_path.as_path()));
+        assert!(repo.find_by_title("foo").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_watcher_batch_detects_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let old_path = pages_dir.join("foo.md");
+        std::fs::write(&old_path, "- Content that moves").unwrap();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None).unwrap();
+        service.sync_once(None).await.unwrap();
+
+        let new_path = pages_dir.join("bar.md");
+        std::fs::rename(&old_path, &new_path).unwrap();
+
+        // The debounced watcher reports both paths as modified
+        let events = vec![
+            FileEvent { path: old_path.clone(), kind: FileEventKind::Modified },
+            FileEvent { path: new_path.clone(), kind: FileEventKind::Modified },
+        ];
+        let operations = service.plan_operations(events).await;
+        assert_eq!(operations.len(), 1);
+        assert!(matches!(
+            &operations[0],
+            SyncOperation::Rename { from, to } if from == &old_path && to == &new_path
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_large_bursts_are_processed_in_batches() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+        let pages_dir = logseq_dir.join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        let events: Vec<FileEvent> = (0..5)
+            .map(|i| {
+                let path = pages_dir.join(format!("page{}.md", i));
+                std::fs::write(&path, format!("- Block {}", i)).unwrap();
+                FileEvent { path, kind: FileEventKind::Created }
+            })
+            .collect();
+
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(logseq_dir).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None)
+            .unwrap()
+            .with_read_stability(StabilityCheck::none())
+            .with_batch_size(2);
+        let mut receiver = service.subscribe();
+
+        service.process_events(events, None).await.unwrap();
+
+        let mut progress = Vec::new();
+        let mut created = 0;
+        while let Ok(event) = receiver.try_recv() {
+            match event {
+                SyncEvent::Progress { processed, total } => progress.push((processed, total)),
+                SyncEvent::FileCreated { .. } => created += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(progress, vec![(2, 5), (4, 5), (5, 5)]);
+        assert_eq!(created, 5);
+        assert_eq!(repo.find_all().unwrap().len(), 5);
+        assert_eq!(service.status().pending_operations, 0);
+    }
+
     #[tokio::test]
     async fn test_sync_once_mixed_operations() {
         // Create a temporary Logseq directory
@@ -651,6 +2510,126 @@ mod tests {
         assert_eq!(summary2.files_unchanged, 0);
     }
 
+    /// A graph with `pages/Rust.md` and `journals/Rust.md`, which share a title
+    fn graph_with_duplicate_titles() -> (TempDir, PathBuf, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let page = temp_dir.path().join("pages/Rust.md");
+        let journal = temp_dir.path().join("journals/Rust.md");
+        std::fs::create_dir(temp_dir.path().join("pages")).unwrap();
+        std::fs::create_dir(temp_dir.path().join("journals")).unwrap();
+        std::fs::write(&page, "- From pages").unwrap();
+        std::fs::write(&journal, "- From journals").unwrap();
+        (temp_dir, page, journal)
+    }
+
+    fn sorted_titles(repo: &MockRepository) -> Vec<String> {
+        let mut titles: Vec<String> = repo
+            .find_all()
+            .unwrap()
+            .iter()
+            .map(|page| page.title().to_string())
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_are_renamed() {
+        let (temp_dir, page, journal) = graph_with_duplicate_titles();
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(temp_dir.path()).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None)
+            .unwrap()
+            .with_read_stability(StabilityCheck::none());
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let callback: SyncCallback = {
+            let events = events.clone();
+            Arc::new(move |event| events.lock().unwrap().push(event))
+        };
+        service.sync_once(Some(callback)).await.unwrap();
+        assert_eq!(sorted_titles(&repo), vec!["Rust", "Rust (2)"]);
+        assert_eq!(
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|event| matches!(event, SyncEvent::DuplicateTitle(_)))
+                .count(),
+            1
+        );
+
+        // Later changes to either file keep both pages under the same titles
+        for path in [&page, &journal] {
+            std::fs::write(path, "- Edited").unwrap();
+            service
+                .process_operation(SyncOperation::Update(path.clone()), None)
+                .await
+                .unwrap();
+        }
+        assert_eq!(sorted_titles(&repo), vec!["Rust", "Rust (2)"]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_are_merged() {
+        let (temp_dir, page, journal) = graph_with_duplicate_titles();
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(temp_dir.path()).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None)
+            .unwrap()
+            .with_read_stability(StabilityCheck::none())
+            .with_duplicate_title_policy(DuplicateTitlePolicy::Merge);
+
+        service.sync_once(None).await.unwrap();
+        let merged = repo.find_by_title("Rust").unwrap().unwrap();
+        let page_id = merged.id().clone();
+        assert_eq!(merged.all_blocks().count(), 2);
+
+        // Editing one file rebuilds the page instead of appending to it again
+        std::fs::write(&journal, "- Edited journal").unwrap();
+        service
+            .process_operation(SyncOperation::Update(journal.clone()), None)
+            .await
+            .unwrap();
+        std::fs::write(&page, "- Edited page").unwrap();
+        service
+            .process_operation(SyncOperation::Update(page.clone()), None)
+            .await
+            .unwrap();
+        let merged = repo.find_by_title("Rust").unwrap().unwrap();
+        assert_eq!(merged.id(), &page_id);
+        let mut contents: Vec<&str> = merged.all_blocks().map(|block| block.content().as_str()).collect();
+        contents.sort();
+        assert_eq!(contents, vec!["Edited journal", "Edited page"]);
+
+        // Deleting one file leaves the page with the other's blocks
+        std::fs::remove_file(&journal).unwrap();
+        service
+            .process_operation(SyncOperation::Delete(journal), None)
+            .await
+            .unwrap();
+        let remaining = repo.find_by_title("Rust").unwrap().unwrap();
+        assert_eq!(remaining.id(), &page_id);
+        assert_eq!(remaining.all_blocks().count(), 1);
+        assert_eq!(service.write_back(None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_titles_can_be_rejected() {
+        let (temp_dir, _, _) = graph_with_duplicate_titles();
+        let repo = MockRepository::new();
+        let dir_path = LogseqDirectoryPath::new(temp_dir.path()).unwrap();
+        let service = SyncService::new(repo.clone(), dir_path, None)
+            .unwrap()
+            .with_duplicate_title_policy(DuplicateTitlePolicy::Error);
+
+        let summary = service.sync_once(None).await.unwrap();
+        assert_eq!(summary.files_created, 1);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].1.contains("already used"));
+        assert_eq!(sorted_titles(&repo), vec!["Rust"]);
+    }
+
     #[tokio::test]
     async fn test_sync_once_with_journals() {
         // Create a temporary Logseq directory
@@ -721,4 +2700,33 @@ mod tests {
         // Check for SyncStarted
         assert!(matches!(evts[0], SyncEvent::SyncStarted));
     }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_sync_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let pages_dir = temp_dir.path().join("pages");
+        std::fs::create_dir(&pages_dir).unwrap();
+        std::fs::create_dir(temp_dir.path().join("journals")).unwrap();
+        std::fs::write(pages_dir.join("page1.md"), "- First block").unwrap();
+
+        let dir_path = LogseqDirectoryPath::new(temp_dir.path()).unwrap();
+        let service = SyncService::new(MockRepository::new(), dir_path, None).unwrap();
+
+        let mut first = service.subscribe();
+        let mut second = service.subscribe();
+        service.sync_once(None).await.unwrap();
+
+        for receiver in [&mut first, &mut second] {
+            assert!(matches!(receiver.try_recv().unwrap(), SyncEvent::SyncStarted));
+            assert!(matches!(
+                receiver.try_recv().unwrap(),
+                SyncEvent::FileCreated { ref file_path } if file_path.ends_with("page1.md")
+            ));
+            assert!(matches!(
+                receiver.try_recv().unwrap(),
+                SyncEvent::SyncCompleted { files_created: 1, .. }
+            ));
+            assert!(receiver.try_recv().is_err());
+        }
+    }
 }
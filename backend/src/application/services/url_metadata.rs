@@ -0,0 +1,364 @@
+/// URL metadata service that fetches the titles and descriptions of stored URLs
+use super::link_checker::{page_urls, HostSchedule};
+use crate::application::repositories::{PageRepository, RepositoryError, UrlMetadataRepository};
+use crate::domain::{DomainError, DomainResult};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How long fetching one page may take
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Fetches in flight at once unless `with_concurrency` says otherwise
+const DEFAULT_CONCURRENCY: usize = 8;
+/// Time between fetches from the same host unless `with_host_interval` says otherwise
+const DEFAULT_HOST_INTERVAL: Duration = Duration::from_secs(1);
+/// How long metadata is kept before it's fetched again, unless `with_max_age` says otherwise
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Bytes of a page read looking for its metadata; it's all in the `<head>`
+const MAX_HTML_BYTES: usize = 512 * 1024;
+
+/// What a URL's page says about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlMetadata {
+    pub url: String,
+    /// The Open Graph title, else the Twitter card's, else the `<title>`
+    pub title: Option<String>,
+    /// The Open Graph description, else the Twitter card's, else the `description` meta tag
+    pub description: Option<String>,
+    /// The page's icon, else the site's `/favicon.ico`
+    pub favicon: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl UrlMetadata {
+    fn empty(url: String) -> Self {
+        UrlMetadata {
+            url,
+            title: None,
+            description: None,
+            favicon: None,
+            fetched_at: Utc::now(),
+        }
+    }
+}
+
+/// What a run of the fetcher did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlMetadataSummary {
+    /// URLs fetched, whether or not they had metadata
+    pub fetched: usize,
+    /// URLs skipped because their metadata is recent
+    pub cached: usize,
+    /// URLs that couldn't be fetched
+    pub failed: usize,
+}
+
+/// Fetches and stores the titles, descriptions and icons of URLs
+///
+/// Fetching is rate-limited like [`LinkChecker`](super::LinkChecker)'s
+/// requests. A URL that can't be fetched, or isn't HTML, is stored without
+/// metadata, so it isn't fetched again until its metadata is `max_age` old.
+pub struct UrlMetadataService {
+    metadata: Mutex<Box<dyn UrlMetadataRepository + Send>>,
+    client: reqwest::Client,
+    concurrency: usize,
+    host_interval: Duration,
+    max_age: Duration,
+}
+
+impl UrlMetadataService {
+    pub fn new(metadata: impl UrlMetadataRepository + Send + 'static) -> Self {
+        UrlMetadataService {
+            metadata: Mutex::new(Box::new(metadata)),
+            client: client(DEFAULT_REQUEST_TIMEOUT),
+            concurrency: DEFAULT_CONCURRENCY,
+            host_interval: DEFAULT_HOST_INTERVAL,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the time between fetches from the same host
+    pub fn with_host_interval(mut self, host_interval: Duration) -> Self {
+        self.host_interval = host_interval;
+        self
+    }
+
+    /// Set how long metadata is kept before the URL is fetched again
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The stored metadata of a URL, if it was fetched
+    pub fn metadata_of(&self, url: &str) -> DomainResult<Option<UrlMetadata>> {
+        self.store()?.get(url)
+    }
+
+    /// Fetch the metadata of every URL of every page
+    pub async fn fetch_pages<R: PageRepository>(&self, repository: &R) -> DomainResult<UrlMetadataSummary> {
+        self.fetch_urls(page_urls(&repository.find_all()?)).await
+    }
+
+    /// Fetch the metadata of URLs whose stored metadata is missing or older than `max_age`
+    pub async fn fetch_urls(&self, urls: impl IntoIterator<Item = String>) -> DomainResult<UrlMetadataSummary> {
+        let urls: BTreeSet<String> = urls.into_iter().collect();
+        let mut summary = UrlMetadataSummary::default();
+        let fresh_since = Utc::now() - chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let hosts = Arc::new(HostSchedule::new(self.host_interval));
+        let mut fetches = JoinSet::new();
+        for url in urls {
+            if self.metadata_of(&url)?.is_some_and(|metadata| metadata.fetched_at >= fresh_since) {
+                summary.cached += 1;
+                continue;
+            }
+            let (client, semaphore, hosts) = (self.client.clone(), Arc::clone(&semaphore), Arc::clone(&hosts));
+            fetches.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("the semaphore is never closed");
+                let host = reqwest::Url::parse(&url)
+                    .ok()
+                    .and_then(|parsed| parsed.host_str().map(str::to_string))
+                    .unwrap_or_default();
+                hosts.wait(&host).await;
+                match fetch(&client, &url).await {
+                    Ok(metadata) => (metadata, true),
+                    Err(e) => {
+                        tracing::warn!("Cannot fetch metadata of {}: {}", url, e);
+                        (UrlMetadata::empty(url), false)
+                    }
+                }
+            });
+        }
+
+        while let Some(result) = fetches.join_next().await {
+            let (metadata, fetched) =
+                result.map_err(|e| DomainError::InvalidOperation(format!("URL metadata task failed: {}", e)))?;
+            if fetched {
+                summary.fetched += 1;
+            } else {
+                summary.failed += 1;
+            }
+            self.store()?.save(&metadata)?;
+        }
+        Ok(summary)
+    }
+
+    fn store(&self) -> DomainResult<std::sync::MutexGuard<'_, Box<dyn UrlMetadataRepository + Send>>> {
+        self.metadata
+            .lock()
+            .map_err(|_| RepositoryError::LockPoisoned("URL metadata store").into())
+    }
+}
+
+fn client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_default()
+}
+
+/// GET a URL and read the metadata of its page, following redirects
+async fn fetch(client: &reqwest::Client, url: &str) -> reqwest::Result<UrlMetadata> {
+    let mut response = client
+        .get(url)
+        .header(ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await?
+        .error_for_status()?;
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("html"));
+    let final_url = response.url().clone();
+    if !is_html {
+        return Ok(UrlMetadata::empty(url.to_string()));
+    }
+
+    let mut html = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        html.extend_from_slice(&chunk);
+        if html.len() >= MAX_HTML_BYTES {
+            break;
+        }
+    }
+    Ok(parse_metadata(url, &final_url, &String::from_utf8_lossy(&html)))
+}
+
+/// Read a page's metadata from its HTML; `base` is where the page was
+/// fetched from, after redirects, for resolving the icon's link
+pub(crate) fn parse_metadata(url: &str, base: &reqwest::Url, html: &str) -> UrlMetadata {
+    static TITLE: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let title_tag = TITLE.get_or_init(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").expect("valid regex"));
+    let tag = TAG.get_or_init(|| Regex::new(r"(?is)<(meta|link)\s[^>]*>").expect("valid regex"));
+    let attribute = ATTRIBUTE.get_or_init(|| {
+        Regex::new(r#"(?s)([a-zA-Z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).expect("valid regex")
+    });
+
+    let mut meta: Vec<(String, String)> = Vec::new();
+    let mut icon = None;
+    for found in tag.captures_iter(html) {
+        let attributes: Vec<(String, &str)> = attribute
+            .captures_iter(&found[0])
+            .map(|a| {
+                let value = a.get(2).or(a.get(3)).or(a.get(4)).map_or("", |value| value.as_str());
+                (a[1].to_lowercase(), value)
+            })
+            .collect();
+        let get = |name: &str| attributes.iter().find(|(key, _)| key == name).map(|(_, value)| *value);
+
+        if found[1].eq_ignore_ascii_case("meta") {
+            if let (Some(key), Some(content)) = (get("property").or(get("name")), get("content")) {
+                meta.push((key.to_lowercase(), content.to_string()));
+            }
+        } else if icon.is_none()
+            && get("rel").is_some_and(|rel| rel.split_whitespace().any(|rel| rel.eq_ignore_ascii_case("icon")))
+        {
+            icon = get("href").and_then(|href| base.join(href.trim()).ok());
+        }
+    }
+
+    let meta_value = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| meta.iter().find(|(name, _)| name == key).map(|(_, value)| text(value)))
+            .filter(|value| !value.is_empty())
+    };
+    let title = meta_value(&["og:title", "twitter:title"]).or_else(|| {
+        title_tag
+            .captures(html)
+            .map(|title| text(&title[1]))
+            .filter(|title| !title.is_empty())
+    });
+    UrlMetadata {
+        url: url.to_string(),
+        title,
+        description: meta_value(&["og:description", "twitter:description", "description"]),
+        favicon: icon.or_else(|| base.join("/favicon.ico").ok()).map(|icon| icon.to_string()),
+        fetched_at: Utc::now(),
+    }
+}
+
+/// HTML text as plain text: entities decoded and whitespace collapsed
+pub(crate) fn text(html: &str) -> String {
+    static ENTITY: OnceLock<Regex> = OnceLock::new();
+    let entity = ENTITY.get_or_init(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").expect("valid regex"));
+    let decoded = entity.replace_all(html, |entity: &regex::Captures| {
+        let name = &entity[1];
+        let decoded = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => name
+                .strip_prefix("#x")
+                .or_else(|| name.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16).ok())
+                .unwrap_or_else(|| name.strip_prefix('#').and_then(|decimal| decimal.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        decoded.map_or_else(|| entity[0].to_string(), String::from)
+    });
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::SqliteUrlMetadataRepository;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parses_open_graph_and_html_metadata() {
+        let base = reqwest::Url::parse("https://example.com/posts/rust").unwrap();
+        let html = r#"<html><head>
+            <title>Rust &amp; memory | Blog</title>
+            <meta property="og:title" content="Rust &amp; memory">
+            <meta name='description' content='Ownership,
+                borrowing &#8212; and lifetimes'>
+            <link rel="shortcut icon" href="/static/icon.png">
+        </head></html>"#;
+        let metadata = parse_metadata("https://example.com/rust", &base, html);
+        assert_eq!(metadata.url, "https://example.com/rust");
+        assert_eq!(metadata.title.as_deref(), Some("Rust & memory"));
+        assert_eq!(metadata.description.as_deref(), Some("Ownership, borrowing \u{2014} and lifetimes"));
+        assert_eq!(metadata.favicon.as_deref(), Some("https://example.com/static/icon.png"));
+
+        let plain = parse_metadata("https://example.com/", &base, "<TITLE>\n  Home\n</TITLE>");
+        assert_eq!(plain.title.as_deref(), Some("Home"));
+        assert_eq!(plain.description, None);
+        assert_eq!(plain.favicon.as_deref(), Some("https://example.com/favicon.ico"));
+    }
+
+    #[tokio::test]
+    async fn test_fetches_and_stores_metadata() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let (status, content_type, body) = match request.split(' ').nth(1) {
+                        Some("/article") => ("200 OK", "text/html; charset=utf-8", "<title>An article</title>"),
+                        Some("/data.json") => ("200 OK", "application/json", "{}"),
+                        _ => ("404 Not Found", "text/html", "<title>Not found</title>"),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let service = UrlMetadataService::new(SqliteUrlMetadataRepository::open_in_memory().unwrap())
+            .with_host_interval(Duration::ZERO);
+        let urls = ["/article", "/data.json", "/missing"].map(|path| format!("{}{}", base, path));
+        let summary = service.fetch_urls(urls.clone()).await.unwrap();
+        assert_eq!(
+            summary,
+            UrlMetadataSummary {
+                fetched: 2,
+                cached: 0,
+                failed: 1
+            }
+        );
+
+        let article = service.metadata_of(&urls[0]).unwrap().unwrap();
+        assert_eq!(article.title.as_deref(), Some("An article"));
+        assert_eq!(article.favicon, Some(format!("{}/favicon.ico", base)));
+        assert_eq!(service.metadata_of(&urls[1]).unwrap().unwrap().title, None);
+        assert_eq!(service.metadata_of(&urls[2]).unwrap().unwrap().title, None);
+
+        let again = service.fetch_urls(urls).await.unwrap();
+        assert_eq!((again.fetched, again.cached), (0, 3));
+    }
+}
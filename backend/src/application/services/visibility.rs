@@ -0,0 +1,243 @@
+/// What pages and blocks may be shown outside the graph
+use crate::application::dto::PageSummary;
+use crate::application::repositories::PageRepository;
+use crate::application::services::SyncEvent;
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::{BlockId, PageId, Visibility};
+use crate::domain::{DomainError, DomainResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Which pages and blocks exports, the API and, if configured, embeddings
+/// leave out
+///
+/// Pages and blocks marked private (`private:: true` or `public:: false`,
+/// see [`Visibility`]) are always left out; a private block takes its
+/// children with it. With `public_only`, so is every page not marked
+/// `public:: true`, as when Logseq publishes a graph whose pages aren't all
+/// public.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VisibilityPolicy {
+    pub public_only: bool,
+}
+
+impl VisibilityPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show only pages marked `public:: true`
+    pub fn with_public_only(mut self, public_only: bool) -> Self {
+        self.public_only = public_only;
+        self
+    }
+
+    pub fn is_page_visible(&self, page: &Page) -> bool {
+        match page.visibility() {
+            Visibility::Private => false,
+            Visibility::Unmarked => !self.public_only,
+            Visibility::Public => true,
+        }
+    }
+
+    /// The page without its private blocks, or `None` if the page is hidden
+    pub fn visible_page(&self, page: &Page) -> Option<Page> {
+        if !self.is_page_visible(page) {
+            return None;
+        }
+        let private: Vec<BlockId> = page
+            .all_blocks()
+            .filter(|block| page.block_visibility(block.id()) == Visibility::Private)
+            .map(|block| block.id().clone())
+            .collect();
+        let mut visible = page.clone();
+        for id in private {
+            // Already gone if an ancestor was private too
+            if visible.get_block(&id).is_some() {
+                visible.remove_block(&id).ok()?;
+            }
+        }
+        Some(visible)
+    }
+}
+
+/// Read-only view of a repository through a [`VisibilityPolicy`]
+///
+/// Hidden pages aren't found and visible ones come without their private
+/// blocks, so use cases run over the view see only what may be shown.
+/// Without a policy, the view shows every page as it is. Saving or deleting
+/// through the view fails.
+pub struct VisiblePages<'a, R: PageRepository> {
+    repository: &'a R,
+    policy: Option<VisibilityPolicy>,
+}
+
+impl<'a, R: PageRepository> VisiblePages<'a, R> {
+    pub fn new(repository: &'a R, policy: Option<VisibilityPolicy>) -> Self {
+        VisiblePages { repository, policy }
+    }
+
+    fn visible(&self, page: Option<Page>) -> Option<Page> {
+        match &self.policy {
+            Some(policy) => page.and_then(|page| policy.visible_page(&page)),
+            None => page,
+        }
+    }
+
+    /// Whether a sync event names only files whose pages the view shows
+    ///
+    /// A deleted file's page is gone, so whether it was hidden can't be told;
+    /// with a policy, deletions aren't shown.
+    pub fn shows_sync_event(&self, event: &SyncEvent) -> bool {
+        if self.policy.is_none() {
+            return true;
+        }
+        let files: Vec<&Path> = match event {
+            SyncEvent::SyncStarted | SyncEvent::SyncCompleted { .. } | SyncEvent::Progress { .. } => return true,
+            SyncEvent::FileDeleted { .. } => return false,
+            SyncEvent::FileCreated { file_path }
+            | SyncEvent::FileUpdated { file_path }
+            | SyncEvent::FileWritten { file_path }
+            | SyncEvent::Error { file_path, .. } => vec![file_path],
+            // The old path has no page any more; the one the page moved to decides
+            SyncEvent::FileRenamed { to, .. } => vec![to],
+            SyncEvent::Conflict(conflict) => vec![&conflict.file_path],
+            SyncEvent::DuplicateTitle(resolution) => vec![&resolution.file_path, &resolution.kept_by],
+        };
+        files
+            .into_iter()
+            .all(|file| self.find_by_file_path(file).ok().flatten().is_some())
+    }
+
+    fn read_only() -> DomainError {
+        DomainError::InvalidOperation("Pages are read-only through a visibility filter".to_string())
+    }
+}
+
+impl<R: PageRepository> PageRepository for VisiblePages<'_, R> {
+    fn save(&mut self, _page: Page) -> DomainResult<()> {
+        Err(Self::read_only())
+    }
+
+    fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+        Ok(self.visible(self.repository.find_by_id(id)?))
+    }
+
+    fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+        Ok(self.visible(self.repository.find_by_title(title)?))
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<Page>> {
+        let pages = self.repository.find_all()?;
+        Ok(match &self.policy {
+            Some(policy) => pages.iter().filter_map(|page| policy.visible_page(page)).collect(),
+            None => pages,
+        })
+    }
+
+    /// The wrapped repository's summaries of the visible pages, counting
+    /// only their visible blocks
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        if self.policy.is_none() {
+            return self.repository.find_summaries();
+        }
+        let mut visible: HashMap<PageId, Page> = self
+            .find_all()?
+            .into_iter()
+            .map(|page| (page.id().clone(), page))
+            .collect();
+        Ok(self
+            .repository
+            .find_summaries()?
+            .into_iter()
+            .filter_map(|summary| {
+                let page = visible.remove(&summary.page_id)?;
+                Some(PageSummary {
+                    indexed_at: summary.indexed_at,
+                    embedded_at: summary.embedded_at,
+                    ..PageSummary::from(&page)
+                })
+            })
+            .collect())
+    }
+
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        Ok(self.visible(self.repository.find_by_file_path(file_path)?))
+    }
+
+    fn delete(&mut self, _id: &PageId) -> DomainResult<bool> {
+        Err(Self::read_only())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- public:: true\n- Ownership\n- Salary\n  private:: true\n\t- 100k\n- Borrowing"),
+            ("go", "Go", "- Goroutines"),
+            ("diary", "Diary", "- private:: true\n- Dear diary"),
+            ("draft", "Draft", "- public:: false\n- Unfinished"),
+        ] {
+            let mut page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.into()).unwrap();
+            page.set_file_path(format!("pages/{}.md", title).into());
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    fn titles(repository: &impl PageRepository) -> Vec<String> {
+        let mut titles: Vec<String> = repository
+            .find_summaries()
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_policy_hides_private_pages_and_blocks() {
+        let repo = repository();
+        let visible = VisiblePages::new(&repo, Some(VisibilityPolicy::new()));
+
+        assert_eq!(titles(&visible), ["Go", "Rust"]);
+        assert!(visible.find_by_title("Diary").unwrap().is_none());
+        let rust = visible.find_by_id(&PageId::new("rust").unwrap()).unwrap().unwrap();
+        let contents: Vec<&str> = rust.all_blocks().map(|block| block.content().as_str()).collect();
+        assert_eq!(contents.len(), 3);
+        assert!(!contents.iter().any(|content| content.contains("Salary") || content.contains("100k")));
+        let summary = visible.find_summaries().unwrap().into_iter().find(|summary| summary.title == "Rust");
+        assert_eq!(summary.unwrap().block_count, 3);
+
+        let public = VisiblePages::new(&repo, Some(VisibilityPolicy::new().with_public_only(true)));
+        assert_eq!(titles(&public), ["Rust"]);
+        let unfiltered = VisiblePages::new(&repo, None);
+        assert_eq!(titles(&unfiltered), ["Diary", "Draft", "Go", "Rust"]);
+    }
+
+    #[test]
+    fn test_policy_hides_sync_events_about_hidden_pages() {
+        let repo = repository();
+        let visible = VisiblePages::new(&repo, Some(VisibilityPolicy::new()));
+        let updated = |path: &str| SyncEvent::FileUpdated { file_path: path.into() };
+
+        assert!(visible.shows_sync_event(&updated("pages/Rust.md")));
+        assert!(!visible.shows_sync_event(&updated("pages/Diary.md")));
+        assert!(!visible.shows_sync_event(&SyncEvent::FileRenamed {
+            from: "pages/Notes.md".into(),
+            to: "pages/Diary.md".into(),
+        }));
+        assert!(!visible.shows_sync_event(&SyncEvent::FileDeleted { file_path: "pages/Go.md".into() }));
+        assert!(visible.shows_sync_event(&SyncEvent::SyncStarted));
+        assert!(VisiblePages::new(&repo, None).shows_sync_event(&updated("pages/Diary.md")));
+    }
+}
@@ -0,0 +1,511 @@
+/// Web archiver that keeps readable snapshots of linked pages and searches them
+use super::link_checker::{page_urls, HostSchedule};
+use super::url_metadata::{parse_metadata, text};
+use crate::application::dto::{
+    ArchiveResult, MatchKind, MatchedField, ScoreExplanation, SearchItem, SearchRequest, SearchResult, SearchType,
+};
+use crate::application::repositories::{PageRepository, RepositoryError, WebArchiveRepository};
+use crate::application::services::EmbeddingService;
+use crate::application::use_cases::search::SearchError;
+use crate::domain::value_objects::Url;
+use crate::domain::{DomainError, DomainResult};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How long fetching one page may take
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Fetches in flight at once unless `with_concurrency` says otherwise
+const DEFAULT_CONCURRENCY: usize = 4;
+/// Time between fetches from the same host unless `with_host_interval` says otherwise
+const DEFAULT_HOST_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a snapshot is kept before the page is archived again, unless `with_max_age` says otherwise
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Bytes of a page read for its snapshot; the rest is dropped
+const MAX_HTML_BYTES: usize = 4 * 1024 * 1024;
+/// Archive chunks fetched per semantic search
+const SEMANTIC_SEARCH_LIMIT: usize = 20;
+/// Characters of archived text shown on each side of a keyword match
+const EXCERPT_RADIUS: usize = 120;
+
+/// A readable snapshot of a web page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebArchive {
+    pub url: String,
+    pub title: Option<String>,
+    /// The page's main text, one paragraph per line
+    pub text: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// What a run of the archiver did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebArchiveSummary {
+    /// URLs fetched and archived
+    pub archived: usize,
+    /// URLs skipped because their snapshot is recent
+    pub cached: usize,
+    /// URLs fetched that weren't HTML or had no readable text
+    pub skipped: usize,
+    /// URLs that couldn't be fetched; they're tried again on the next run
+    pub failed: usize,
+    /// Snapshots embedded for semantic search
+    pub embedded: usize,
+}
+
+/// Saves readable snapshots of linked web pages, and searches them
+///
+/// Each page's main text is extracted the way reader views do it: scripts,
+/// navigation, headers, footers and sidebars are dropped, and the
+/// `<article>` (else `<main>`, else `<body>`) is kept as paragraphs.
+/// Fetching is rate-limited like [`LinkChecker`](super::LinkChecker)'s
+/// requests. With an [`EmbeddingService`] whose configuration enables
+/// `archive_embeddings`, snapshots are also embedded and searched
+/// semantically.
+pub struct WebArchiver {
+    archives: Mutex<Box<dyn WebArchiveRepository + Send>>,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    client: reqwest::Client,
+    concurrency: usize,
+    host_interval: Duration,
+    max_age: Duration,
+}
+
+impl WebArchiver {
+    pub fn new(archives: impl WebArchiveRepository + Send + 'static) -> Self {
+        WebArchiver {
+            archives: Mutex::new(Box::new(archives)),
+            embedding_service: None,
+            client: client(DEFAULT_REQUEST_TIMEOUT),
+            concurrency: DEFAULT_CONCURRENCY,
+            host_interval: DEFAULT_HOST_INTERVAL,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// Embed snapshots as they're archived, and search them semantically
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the time between fetches from the same host
+    pub fn with_host_interval(mut self, host_interval: Duration) -> Self {
+        self.host_interval = host_interval;
+        self
+    }
+
+    /// Set how long a snapshot is kept before the page is archived again
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The snapshot of a URL, if it was archived
+    pub fn archive_of(&self, url: &str) -> DomainResult<Option<WebArchive>> {
+        self.store()?.get(url)
+    }
+
+    /// Archive every URL of every page
+    pub async fn archive_pages<R: PageRepository>(&self, repository: &R) -> DomainResult<WebArchiveSummary> {
+        self.archive_urls(page_urls(&repository.find_all()?)).await
+    }
+
+    /// Archive URLs that have no snapshot, or one older than `max_age`
+    pub async fn archive_urls(&self, urls: impl IntoIterator<Item = String>) -> DomainResult<WebArchiveSummary> {
+        let urls: BTreeSet<String> = urls.into_iter().collect();
+        let mut summary = WebArchiveSummary::default();
+        let fresh_since = Utc::now() - chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let hosts = Arc::new(HostSchedule::new(self.host_interval));
+        let mut fetches = JoinSet::new();
+        for url in urls {
+            if self.archive_of(&url)?.is_some_and(|archive| archive.archived_at >= fresh_since) {
+                summary.cached += 1;
+                continue;
+            }
+            let (client, semaphore, hosts) = (self.client.clone(), Arc::clone(&semaphore), Arc::clone(&hosts));
+            fetches.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("the semaphore is never closed");
+                let host = reqwest::Url::parse(&url)
+                    .ok()
+                    .and_then(|parsed| parsed.host_str().map(str::to_string))
+                    .unwrap_or_default();
+                hosts.wait(&host).await;
+                let fetched = fetch(&client, &url).await;
+                (url, fetched)
+            });
+        }
+
+        while let Some(result) = fetches.join_next().await {
+            let (url, fetched) =
+                result.map_err(|e| DomainError::InvalidOperation(format!("Web archive task failed: {}", e)))?;
+            let archive = match fetched {
+                Ok(Some(archive)) => archive,
+                Ok(None) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Cannot archive {}: {}", url, e);
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+            self.store()?.save(&archive)?;
+            summary.archived += 1;
+
+            if let Some(embedding_service) = self.embedding_service.as_ref().filter(|s| s.has_archive_embeddings()) {
+                match embedding_service
+                    .embed_archive(&archive.url, archive.title.as_deref(), &archive.text)
+                    .await
+                {
+                    Ok(_) => summary.embedded += 1,
+                    Err(e) => tracing::warn!("Cannot embed archive of {}: {}", archive.url, e),
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Search the snapshots for a query, by keyword or semantically like the
+    /// request asks, best match first
+    ///
+    /// Semantic search falls back to keywords without archive embeddings.
+    pub async fn search(&self, request: &SearchRequest) -> Result<Vec<SearchResult>, SearchError> {
+        let embedding_service = self
+            .embedding_service
+            .as_ref()
+            .filter(|s| s.has_archive_embeddings());
+        let mut results = match (&request.search_type, embedding_service) {
+            (SearchType::Semantic, Some(embedding_service)) => {
+                self.semantic_search(request, embedding_service).await?
+            }
+            _ => self.keyword_search(&request.query, request.explain)?,
+        };
+        results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
+        Ok(results)
+    }
+
+    /// Snapshots whose title or text contains the query, ignoring case
+    fn keyword_search(&self, query: &str, explain: bool) -> DomainResult<Vec<SearchResult>> {
+        let query = query.to_lowercase();
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut results = Vec::new();
+        for archive in self.store()?.all()? {
+            let title_matches = archive
+                .title
+                .as_ref()
+                .is_some_and(|title| title.to_lowercase().contains(&query));
+            let excerpt = excerpt_around(&archive.text, &query);
+            // Below a block matching the same words, so notes come first
+            let explanation = match (title_matches, &excerpt) {
+                (true, _) => ScoreExplanation::new(MatchedField::ArchiveTitle, MatchKind::Contains, 0.6),
+                (false, Some(_)) => ScoreExplanation::new(MatchedField::ArchiveText, MatchKind::Contains, 0.5),
+                (false, None) => continue,
+            };
+            let excerpt = excerpt.unwrap_or_else(|| leading_text(&archive.text));
+            results.push(archive_result(archive, excerpt, explanation, explain)?);
+        }
+        Ok(results)
+    }
+
+    /// Snapshots whose embedded chunks are similar to the query, scored by
+    /// their best chunk
+    async fn semantic_search(
+        &self,
+        request: &SearchRequest,
+        embedding_service: &EmbeddingService,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let chunks = embedding_service
+            .search_archives(&request.query, SEMANTIC_SEARCH_LIMIT, request.score_threshold)
+            .await?;
+
+        let mut best: HashMap<String, (String, f32)> = HashMap::new();
+        for chunk in chunks {
+            let entry = best.entry(chunk.page_id).or_insert((String::new(), f32::MIN));
+            if chunk.score > entry.1 {
+                *entry = (chunk.original_content, chunk.score);
+            }
+        }
+
+        let mut results = Vec::new();
+        for (url, (excerpt, score)) in best {
+            // Skip chunks of snapshots that were since removed
+            if let Some(archive) = self.archive_of(&url)? {
+                let explanation = ScoreExplanation::new(MatchedField::ArchiveText, MatchKind::Semantic, score);
+                results.push(archive_result(archive, excerpt, explanation, request.explain)?);
+            }
+        }
+        Ok(results)
+    }
+
+    fn store(&self) -> DomainResult<std::sync::MutexGuard<'_, Box<dyn WebArchiveRepository + Send>>> {
+        self.archives
+            .lock()
+            .map_err(|_| RepositoryError::LockPoisoned("Web archive store").into())
+    }
+}
+
+/// An archive matched as `explanation` says, which is kept if `explain`
+fn archive_result(
+    archive: WebArchive,
+    excerpt: String,
+    explanation: ScoreExplanation,
+    explain: bool,
+) -> DomainResult<SearchResult> {
+    Ok(SearchResult {
+        item: SearchItem::Archive(ArchiveResult {
+            url: Url::new(archive.url)?,
+            title: archive.title,
+            excerpt,
+            archived_at: archive.archived_at,
+        }),
+        score: explanation.score(),
+        explanation: explain.then_some(explanation),
+    })
+}
+
+fn client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_default()
+}
+
+/// GET a URL and take a snapshot of its page, following redirects; none if
+/// it isn't HTML or has no readable text
+async fn fetch(client: &reqwest::Client, url: &str) -> reqwest::Result<Option<WebArchive>> {
+    let mut response = client
+        .get(url)
+        .header(ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await?
+        .error_for_status()?;
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("html"));
+    if !is_html {
+        return Ok(None);
+    }
+    let final_url = response.url().clone();
+
+    let mut html = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        html.extend_from_slice(&chunk);
+        if html.len() >= MAX_HTML_BYTES {
+            break;
+        }
+    }
+    let html = String::from_utf8_lossy(&html);
+    let text = readable_text(&html);
+    if text.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(WebArchive {
+        url: url.to_string(),
+        title: parse_metadata(url, &final_url, &html).title,
+        text,
+        archived_at: Utc::now(),
+    }))
+}
+
+/// The main text of an HTML page, one paragraph per line
+fn readable_text(html: &str) -> String {
+    static NOISE: OnceLock<Vec<Regex>> = OnceLock::new();
+    static CONTAINERS: OnceLock<Vec<Regex>> = OnceLock::new();
+    static BREAK: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let noise = NOISE.get_or_init(|| {
+        let comment = Regex::new(r"(?s)<!--.*?-->").expect("valid regex");
+        let elements = [
+            "head", "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+            "form",
+        ]
+        .map(|name| Regex::new(&format!(r"(?is)<{0}\b[^>]*>.*?</{0}\s*>", name)).expect("valid regex"));
+        std::iter::once(comment).chain(elements).collect()
+    });
+    let containers = CONTAINERS.get_or_init(|| {
+        ["article", "main", "body"]
+            .map(|name| Regex::new(&format!(r"(?is)<{0}\b[^>]*>(.*)</{0}\s*>", name)).expect("valid regex"))
+            .into()
+    });
+    let line_break = BREAK.get_or_init(|| {
+        Regex::new(r"(?i)</?(p|div|section|li|ul|ol|h[1-6]|br|hr|tr|table|blockquote|pre|dd|dt|figcaption)\b[^>]*>")
+            .expect("valid regex")
+    });
+    let tag = TAG.get_or_init(|| Regex::new(r"(?s)<[^>]*>").expect("valid regex"));
+
+    let mut html = html.to_string();
+    for element in noise {
+        html = element.replace_all(&html, " ").into_owned();
+    }
+    let content = containers
+        .iter()
+        .find_map(|container| container.captures(&html).map(|found| found[1].to_string()))
+        .unwrap_or(html);
+    let content = line_break.replace_all(&content, "\n");
+    let content = tag.replace_all(&content, "");
+    content
+        .lines()
+        .map(text)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The text around the first match of a lowercase query, if it matches
+fn excerpt_around(text: &str, query: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    let found = lower.find(query)?;
+    // Lowercasing can change byte lengths; map the match back by characters
+    let start_char = lower[..found].chars().count();
+    let match_chars = query.chars().count();
+    let chars: Vec<char> = text.chars().collect();
+    let from = start_char.saturating_sub(EXCERPT_RADIUS);
+    let to = (start_char + match_chars + EXCERPT_RADIUS).min(chars.len());
+    let mut excerpt: String = chars[from..to].iter().collect::<String>().replace('\n', " ");
+    if from > 0 {
+        excerpt.insert(0, '…');
+    }
+    if to < chars.len() {
+        excerpt.push('…');
+    }
+    Some(excerpt)
+}
+
+/// The start of a snapshot's text, for matches on its title
+fn leading_text(text: &str) -> String {
+    let mut excerpt: String = text.chars().take(2 * EXCERPT_RADIUS).collect::<String>().replace('\n', " ");
+    if text.chars().count() > 2 * EXCERPT_RADIUS {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::SqliteWebArchiveRepository;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_extracts_the_readable_text_of_a_page() {
+        let html = r#"<html><head><title>Ignored</title><style>p { color: red }</style></head>
+            <body>
+              <header><nav><a href="/">Home</a> <a href="/blog">Blog</a></nav></header>
+              <article>
+                <h1>Ownership in Rust</h1>
+                <p>Every value has an <em>owner</em>.</p>
+                <!-- an ad -->
+                <script>track();</script>
+                <p>Values are dropped &amp; freed<br>when their owner goes out of scope.</p>
+              </article>
+              <aside>Related posts</aside>
+              <footer>&copy; 2024</footer>
+            </body></html>"#;
+        assert_eq!(
+            readable_text(html),
+            "Ownership in Rust\nEvery value has an owner.\nValues are dropped & freed\nwhen their owner goes out of scope."
+        );
+        assert_eq!(readable_text("<p>Just a fragment</p>"), "Just a fragment");
+        assert_eq!(readable_text("<html><body><script>x()</script></body></html>"), "");
+    }
+
+    #[test]
+    fn test_excerpts_around_the_match() {
+        let text = format!("{}Ownership rules{}", "a".repeat(200), "b".repeat(200));
+        let excerpt = excerpt_around(&text, "ownership").unwrap();
+        assert!(excerpt.starts_with('…') && excerpt.ends_with('…'));
+        assert!(excerpt.contains("Ownership rules"));
+        assert_eq!(excerpt.chars().count(), 2 + 2 * EXCERPT_RADIUS + "ownership".len());
+        assert_eq!(excerpt_around("short text", "short").as_deref(), Some("short text"));
+        assert_eq!(excerpt_around("short text", "missing"), None);
+    }
+
+    #[tokio::test]
+    async fn test_archives_pages_and_searches_them_by_keyword() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let (status, content_type, body) = match request.split(' ').nth(1) {
+                        Some("/article") => (
+                            "200 OK",
+                            "text/html",
+                            "<title>Borrowing</title><article><p>References borrow values.</p></article>",
+                        ),
+                        Some("/data.json") => ("200 OK", "application/json", "{}"),
+                        _ => ("404 Not Found", "text/html", "<p>Not found</p>"),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let archiver = WebArchiver::new(SqliteWebArchiveRepository::open_in_memory().unwrap())
+            .with_host_interval(Duration::ZERO);
+        let urls = ["/article", "/data.json", "/missing"].map(|path| format!("{}{}", base, path));
+        let summary = archiver.archive_urls(urls.clone()).await.unwrap();
+        assert_eq!(
+            summary,
+            WebArchiveSummary {
+                archived: 1,
+                cached: 0,
+                skipped: 1,
+                failed: 1,
+                embedded: 0
+            }
+        );
+
+        let archive = archiver.archive_of(&urls[0]).unwrap().unwrap();
+        assert_eq!(archive.title.as_deref(), Some("Borrowing"));
+        assert_eq!(archive.text, "References borrow values.");
+        assert_eq!(archiver.archive_of(&urls[1]).unwrap(), None);
+
+        let results = archiver.search(&SearchRequest::new("BORROW VALUES")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        let SearchItem::Archive(found) = &results[0].item else {
+            panic!("expected an archive result");
+        };
+        assert_eq!(found.url.as_str(), urls[0]);
+        assert_eq!(found.excerpt, "References borrow values.");
+        assert!(archiver.search(&SearchRequest::new("lifetimes")).await.unwrap().is_empty());
+
+        let again = archiver.archive_urls(urls).await.unwrap();
+        assert_eq!((again.archived, again.cached, again.failed), (0, 1, 1));
+    }
+}
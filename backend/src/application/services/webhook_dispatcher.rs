@@ -0,0 +1,564 @@
+/// Webhook dispatcher that POSTs page and sync events to configured URLs
+use crate::application::services::{GraphEvent, SyncEvent};
+use crate::domain::events::{PageCreated, PageDeleted, PageUpdated};
+use crate::infrastructure::embeddings::RetryPolicy;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio::task::{JoinHandle, JoinSet};
+
+/// Header carrying the event type, e.g. `PageUpdated`
+pub const EVENT_HEADER: &str = "X-Logjam-Event";
+/// Header carrying a unique ID per delivery, the same across its retries
+pub const DELIVERY_HEADER: &str = "X-Logjam-Delivery";
+/// Header carrying `sha256=<hex HMAC of the body>`, for endpoints with a secret
+pub const SIGNATURE_HEADER: &str = "X-Logjam-Signature-256";
+
+/// How long one delivery attempt may take before it counts as failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Endpoint answered {status}")]
+    Status {
+        status: u16,
+        /// The endpoint's `Retry-After`, in seconds
+        retry_after: Option<u64>,
+    },
+}
+
+impl WebhookError {
+    /// Minimum delay before retrying, or `None` when retrying can't help
+    ///
+    /// Network failures, timeouts, `429` and `5xx` answers are retried;
+    /// other answers mean the endpoint rejected the event.
+    fn retry_delay(&self) -> Option<Duration> {
+        match self {
+            WebhookError::Request(_) => Some(Duration::ZERO),
+            WebhookError::Status { status, retry_after } if *status == 429 || *status >= 500 => {
+                Some(Duration::from_secs(retry_after.unwrap_or(0)))
+            }
+            WebhookError::Status { .. } => None,
+        }
+    }
+}
+
+/// Events webhooks can subscribe to, named as the domain events are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    PageCreated,
+    PageUpdated,
+    PageDeleted,
+    SyncCompleted,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 4] = [
+        WebhookEventType::PageCreated,
+        WebhookEventType::PageUpdated,
+        WebhookEventType::PageDeleted,
+        WebhookEventType::SyncCompleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::PageCreated => "PageCreated",
+            WebhookEventType::PageUpdated => "PageUpdated",
+            WebhookEventType::PageDeleted => "PageDeleted",
+            WebhookEventType::SyncCompleted => "SyncCompleted",
+        }
+    }
+}
+
+impl FromStr for WebhookEventType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        WebhookEventType::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == name)
+            .ok_or_else(|| format!("Unknown webhook event '{}'", name))
+    }
+}
+
+/// The JSON body POSTed for an event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookEvent {
+    pub event: WebhookEventType,
+    /// The synced graph the event came from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph_id: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    /// Event-specific fields: the page's ID and title, its file, or a
+    /// sync's counts
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event: WebhookEventType, data: Value) -> Self {
+        WebhookEvent {
+            event,
+            graph_id: None,
+            occurred_at: Utc::now(),
+            data,
+        }
+    }
+
+    pub fn with_graph_id(mut self, graph_id: impl Into<String>) -> Self {
+        self.graph_id = Some(graph_id.into());
+        self
+    }
+
+    /// The event for a graph's sync event, if webhooks report it
+    ///
+    /// Sync only knows the files it changed, so page events carry the file
+    /// path; a rename is an update of the page under its new file.
+    pub fn from_graph_event(event: &GraphEvent) -> Option<Self> {
+        let (event_type, data) = match &event.event {
+            SyncEvent::FileCreated { file_path } => (WebhookEventType::PageCreated, json!({ "file_path": file_path })),
+            SyncEvent::FileUpdated { file_path } => (WebhookEventType::PageUpdated, json!({ "file_path": file_path })),
+            SyncEvent::FileDeleted { file_path } => (WebhookEventType::PageDeleted, json!({ "file_path": file_path })),
+            SyncEvent::FileRenamed { from, to } => (
+                WebhookEventType::PageUpdated,
+                json!({ "file_path": to, "renamed_from": from }),
+            ),
+            SyncEvent::SyncCompleted {
+                files_created,
+                files_updated,
+                files_deleted,
+                files_renamed,
+            } => (
+                WebhookEventType::SyncCompleted,
+                json!({
+                    "files_created": files_created,
+                    "files_updated": files_updated,
+                    "files_deleted": files_deleted,
+                    "files_renamed": files_renamed,
+                }),
+            ),
+            _ => return None,
+        };
+        Some(WebhookEvent::new(event_type, data).with_graph_id(event.graph_id.clone()))
+    }
+}
+
+impl From<&PageCreated> for WebhookEvent {
+    fn from(event: &PageCreated) -> Self {
+        WebhookEvent::new(
+            WebhookEventType::PageCreated,
+            json!({ "page_id": event.page_id.as_str(), "title": event.title }),
+        )
+    }
+}
+
+impl From<&PageUpdated> for WebhookEvent {
+    fn from(event: &PageUpdated) -> Self {
+        WebhookEvent::new(
+            WebhookEventType::PageUpdated,
+            json!({ "page_id": event.page_id.as_str(), "title": event.title }),
+        )
+    }
+}
+
+impl From<&PageDeleted> for WebhookEvent {
+    fn from(event: &PageDeleted) -> Self {
+        WebhookEvent::new(WebhookEventType::PageDeleted, json!({ "page_id": event.page_id.as_str() }))
+    }
+}
+
+/// A URL to POST events to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Key the body is signed with, in [`SIGNATURE_HEADER`]
+    pub secret: Option<String>,
+    /// Events the endpoint receives; every event when empty
+    pub events: HashSet<WebhookEventType>,
+}
+
+impl WebhookEndpoint {
+    pub fn new(url: impl Into<String>) -> Self {
+        WebhookEndpoint {
+            url: url.into(),
+            secret: None,
+            events: HashSet::new(),
+        }
+    }
+
+    /// Sign deliveries with a shared secret
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Only deliver these events
+    pub fn with_events(mut self, events: impl IntoIterator<Item = WebhookEventType>) -> Self {
+        self.events = events.into_iter().collect();
+        self
+    }
+
+    pub fn accepts(&self, event_type: WebhookEventType) -> bool {
+        self.events.is_empty() || self.events.contains(&event_type)
+    }
+}
+
+/// The outcome of delivering an event to one endpoint
+#[derive(Debug)]
+pub struct WebhookDelivery {
+    pub url: String,
+    /// Why the last attempt failed, if every attempt did
+    pub error: Option<String>,
+}
+
+/// POSTs events to every endpoint that subscribed to them
+///
+/// Failed deliveries are retried with the dispatcher's [`RetryPolicy`];
+/// each endpoint is delivered to independently, so one slow or failing
+/// endpoint doesn't hold up the others.
+pub struct WebhookDispatcher {
+    endpoints: Vec<WebhookEndpoint>,
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+}
+
+impl WebhookDispatcher {
+    pub fn new(endpoints: Vec<WebhookEndpoint>) -> Self {
+        WebhookDispatcher {
+            endpoints,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn endpoints(&self) -> &[WebhookEndpoint] {
+        &self.endpoints
+    }
+
+    /// Deliver an event to its subscribers, waiting until each succeeds or
+    /// runs out of retries
+    pub async fn deliver(&self, event: &WebhookEvent) -> Vec<WebhookDelivery> {
+        let body: Arc<[u8]> = match serde_json::to_vec(event) {
+            Ok(body) => body.into(),
+            Err(e) => {
+                tracing::error!("Cannot serialize webhook event {}: {}", event.event.as_str(), e);
+                return Vec::new();
+            }
+        };
+        let delivery_id: Arc<str> = uuid::Uuid::new_v4().to_string().into();
+
+        let mut deliveries = JoinSet::new();
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            if !endpoint.accepts(event.event) {
+                continue;
+            }
+            let request = WebhookRequest {
+                client: self.client.clone(),
+                endpoint: endpoint.clone(),
+                event_type: event.event,
+                delivery_id: delivery_id.clone(),
+                body: body.clone(),
+            };
+            let retry_policy = self.retry_policy.clone();
+            deliveries.spawn(async move {
+                let operation = format!("Webhook {} to {}", request.event_type.as_str(), request.endpoint.url);
+                let result = retry_policy
+                    .run(&operation, WebhookError::retry_delay, || request.send())
+                    .await;
+                let delivery = WebhookDelivery {
+                    url: request.endpoint.url,
+                    error: result.err().map(|e| format!("{:#}", e)),
+                };
+                (index, delivery)
+            });
+        }
+
+        let mut results: Vec<(usize, WebhookDelivery)> = Vec::new();
+        while let Some(result) = deliveries.join_next().await {
+            match result {
+                Ok(delivery) => results.push(delivery),
+                Err(e) => tracing::error!("Webhook delivery task failed: {}", e),
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, delivery)| delivery).collect()
+    }
+
+    /// Deliver an event in the background, logging deliveries that fail
+    pub fn dispatch(self: &Arc<Self>, event: WebhookEvent) -> JoinHandle<()> {
+        let dispatcher = Arc::clone(self);
+        tokio::spawn(async move {
+            for delivery in dispatcher.deliver(&event).await {
+                if let Some(error) = delivery.error {
+                    tracing::error!("{}", error);
+                }
+            }
+        })
+    }
+
+    /// Dispatch the webhook events of graphs' sync events until the
+    /// channel closes
+    pub fn forward_graph_events(self: &Arc<Self>, mut events: broadcast::Receiver<GraphEvent>) -> JoinHandle<()> {
+        let dispatcher = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = WebhookEvent::from_graph_event(&event) {
+                            dispatcher.dispatch(event);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Webhooks missed {} sync events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// One endpoint's delivery of an event, retried as a whole
+struct WebhookRequest {
+    client: reqwest::Client,
+    endpoint: WebhookEndpoint,
+    event_type: WebhookEventType,
+    delivery_id: Arc<str>,
+    body: Arc<[u8]>,
+}
+
+impl WebhookRequest {
+    async fn send(&self) -> Result<(), WebhookError> {
+        let mut request = self
+            .client
+            .post(&self.endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, self.event_type.as_str())
+            .header(DELIVERY_HEADER, &*self.delivery_id)
+            .body(self.body.to_vec());
+        if let Some(secret) = &self.endpoint.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret.as_bytes(), &self.body));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        Err(WebhookError::Status {
+            status: status.as_u16(),
+            retry_after,
+        })
+    }
+}
+
+/// The [`SIGNATURE_HEADER`] value for a body: `sha256=` and the hex
+/// HMAC-SHA256 of the body, keyed with the endpoint's secret
+///
+/// Endpoints verify a delivery by computing the same over the raw body.
+pub fn signature(secret: &[u8], body: &[u8]) -> String {
+    let mac: String = hmac_sha256(secret, body).iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", mac)
+}
+
+/// HMAC (RFC 2104) over SHA-256
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A request an endpoint received: its headers (lowercased names) and body
+    struct Received {
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl Received {
+        fn header(&self, name: &str) -> Option<&str> {
+            let name = name.to_lowercase();
+            self.headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// An HTTP endpoint answering with `statuses` in turn (then 200),
+    /// recording what it receives
+    async fn endpoint(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let (head, body) = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length: usize = head
+                            .lines()
+                            .filter_map(|line| line.split_once(':'))
+                            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                            .map(|(_, value)| value.trim().parse().unwrap())
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break (head.to_string(), body.to_string());
+                        }
+                    }
+                };
+                let headers = head
+                    .lines()
+                    .skip(1)
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+                    .collect();
+                log.lock().unwrap().push(Received { headers, body });
+
+                let status = statuses.next().unwrap_or(200);
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, received)
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_max_attempts(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+    }
+
+    #[test]
+    fn test_signature_is_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            signature(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Keys longer than a block are hashed first (test case 6)
+        assert_eq!(
+            signature(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First"),
+            "sha256=60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivers_signed_events_to_subscribed_endpoints_with_retries() {
+        let (flaky_url, flaky) = endpoint(vec![503]).await;
+        let (sync_url, sync_only) = endpoint(vec![]).await;
+        let dispatcher = WebhookDispatcher::new(vec![
+            WebhookEndpoint::new(&flaky_url).with_secret("s3cret"),
+            WebhookEndpoint::new(&sync_url).with_events([WebhookEventType::SyncCompleted]),
+        ])
+        .with_retry_policy(fast_retries());
+
+        let event = WebhookEvent::from(&PageCreated {
+            page_id: crate::domain::value_objects::PageId::new("rust").unwrap(),
+            title: "Rust".to_string(),
+        });
+        let deliveries = dispatcher.deliver(&event).await;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].url, flaky_url);
+        assert!(deliveries[0].error.is_none(), "{:?}", deliveries[0].error);
+        assert!(sync_only.lock().unwrap().is_empty());
+
+        let received = flaky.lock().unwrap();
+        assert_eq!(received.len(), 2, "the 503 is retried");
+        let request = &received[1];
+        assert_eq!(request.header(EVENT_HEADER), Some("PageCreated"));
+        assert_eq!(request.header(DELIVERY_HEADER), received[0].header(DELIVERY_HEADER));
+        let expected = signature(b"s3cret", request.body.as_bytes());
+        assert_eq!(request.header(SIGNATURE_HEADER), Some(expected.as_str()));
+
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["event"], "PageCreated");
+        assert_eq!(body["data"], json!({ "page_id": "rust", "title": "Rust" }));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_deliveries_are_not_retried() {
+        let (url, received) = endpoint(vec![400]).await;
+        let dispatcher = WebhookDispatcher::new(vec![WebhookEndpoint::new(&url)]).with_retry_policy(fast_retries());
+
+        let event = WebhookEvent::new(WebhookEventType::PageDeleted, json!({ "page_id": "rust" }));
+        let deliveries = dispatcher.deliver(&event).await;
+        assert!(deliveries[0].error.as_deref().unwrap().contains("400"));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_graph_events_map_to_webhook_events() {
+        let graph_event = |event| GraphEvent {
+            graph_id: "notes".to_string(),
+            event,
+        };
+
+        let event = WebhookEvent::from_graph_event(&graph_event(SyncEvent::FileUpdated {
+            file_path: PathBuf::from("/notes/journals/2024_01_01.md"),
+        }))
+        .unwrap();
+        assert_eq!(event.event, WebhookEventType::PageUpdated);
+        assert_eq!(event.graph_id.as_deref(), Some("notes"));
+        assert_eq!(event.data["file_path"], "/notes/journals/2024_01_01.md");
+
+        let event = WebhookEvent::from_graph_event(&graph_event(SyncEvent::SyncCompleted {
+            files_created: 1,
+            files_updated: 2,
+            files_deleted: 0,
+            files_renamed: 0,
+        }))
+        .unwrap();
+        assert_eq!(event.event, WebhookEventType::SyncCompleted);
+        assert_eq!(event.data["files_updated"], 2);
+
+        assert!(WebhookEvent::from_graph_event(&graph_event(SyncEvent::SyncStarted)).is_none());
+        assert_eq!("SyncCompleted".parse(), Ok(WebhookEventType::SyncCompleted));
+        assert!("BlockAdded".parse::<WebhookEventType>().is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::application::{
+    dto::{ActivityStats, DailyActivity, Streak, WeeklyTopics},
+    repositories::PageRepository,
+};
+use crate::application::use_cases::page_names::journal_date;
+use crate::domain::{entities::Block, DomainResult};
+use chrono::{DateTime, Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+/// Pages listed per week unless `with_pages_per_week` says otherwise
+const DEFAULT_PAGES_PER_WEEK: usize = 5;
+
+/// Block properties holding when a block was written
+const TIMESTAMP_PROPERTIES: [&str; 2] = ["created-at", "created_at"];
+
+/// Use case for summarizing when and about what a graph was written
+///
+/// A block is dated by its `created-at` property when it has one (a Unix
+/// timestamp in seconds or milliseconds, or an RFC 3339 or `YYYY-MM-DD`
+/// date), and otherwise by its page's date if the page is a journal, e.g.
+/// `2024_01_15` or `Jan 15th, 2024`. Blocks with neither aren't counted.
+pub struct GetActivityStats<'a, R: PageRepository> {
+    repository: &'a R,
+    pages_per_week: usize,
+}
+
+impl<'a, R: PageRepository> GetActivityStats<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            pages_per_week: DEFAULT_PAGES_PER_WEEK,
+        }
+    }
+
+    /// Set how many of each week's most referenced pages are listed
+    pub fn with_pages_per_week(mut self, pages_per_week: usize) -> Self {
+        self.pages_per_week = pages_per_week;
+        self
+    }
+
+    /// Get the activity of the days in `range`, both ends included
+    pub fn execute(&self, range: RangeInclusive<NaiveDate>) -> DomainResult<ActivityStats> {
+        let pages = self.repository.find_all()?;
+        let titles: HashMap<String, &str> = pages.iter().map(|page| (page.title().to_lowercase(), page.title())).collect();
+
+        let mut blocks_per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        // Reference counts per week, by lowercased title and then spelling
+        let mut references: BTreeMap<NaiveDate, HashMap<String, BTreeMap<String, usize>>> = BTreeMap::new();
+        for page in &pages {
+            let page_date = journal_date(page);
+            for block in page.all_blocks() {
+                let Some(date) = block_date(block).or(page_date).filter(|date| range.contains(date)) else {
+                    continue;
+                };
+                *blocks_per_day.entry(date).or_default() += 1;
+                let week = references.entry(week_start(date)).or_default();
+                for reference in block.page_references() {
+                    let spellings = week.entry(reference.title().to_lowercase()).or_default();
+                    *spellings.entry(reference.title().to_string()).or_default() += 1;
+                }
+            }
+        }
+
+        let days: Vec<DailyActivity> = blocks_per_day
+            .iter()
+            .map(|(date, blocks)| DailyActivity {
+                date: *date,
+                blocks: *blocks,
+            })
+            .collect();
+        let streaks = streaks(&days);
+        let end = *range.end();
+        let current_streak = streaks
+            .iter()
+            .find(|streak| streak.end == end || streak.end + Duration::days(1) == end)
+            .cloned();
+        let longest_streak = streaks.into_iter().fold(None, |longest: Option<Streak>, streak| match longest {
+            Some(longest) if longest.days >= streak.days => Some(longest),
+            _ => Some(streak),
+        });
+
+        let mut weeks: BTreeMap<NaiveDate, WeeklyTopics> = BTreeMap::new();
+        for day in &days {
+            let week_start = week_start(day.date);
+            weeks
+                .entry(week_start)
+                .or_insert_with(|| WeeklyTopics {
+                    week_start,
+                    blocks: 0,
+                    pages: Vec::new(),
+                })
+                .blocks += day.blocks;
+        }
+        for (week_start, counts) in references {
+            let mut pages: Vec<(String, usize)> = counts
+                .into_iter()
+                .map(|(key, spellings)| {
+                    let count = spellings.values().sum();
+                    // A page's own title, else the spelling that sorts first
+                    let title = match titles.get(&key) {
+                        Some(title) => title.to_string(),
+                        None => spellings.into_keys().next().unwrap_or(key),
+                    };
+                    (title, count)
+                })
+                .collect();
+            pages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            pages.truncate(self.pages_per_week);
+            if let Some(week) = weeks.get_mut(&week_start) {
+                week.pages = pages;
+            }
+        }
+
+        Ok(ActivityStats {
+            start: *range.start(),
+            end,
+            total_blocks: days.iter().map(|day| day.blocks).sum(),
+            days,
+            longest_streak,
+            current_streak,
+            weeks: weeks.into_values().collect(),
+        })
+    }
+}
+
+/// Runs of consecutive days, in date order
+fn streaks(days: &[DailyActivity]) -> Vec<Streak> {
+    let mut streaks: Vec<Streak> = Vec::new();
+    for day in days {
+        match streaks.last_mut() {
+            Some(streak) if streak.end + Duration::days(1) == day.date => {
+                streak.end = day.date;
+                streak.days += 1;
+            }
+            _ => streaks.push(Streak {
+                start: day.date,
+                end: day.date,
+                days: 1,
+            }),
+        }
+    }
+    streaks
+}
+
+/// Monday of the week of `date`
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// When a block was written, from its timestamp property
+pub(crate) fn block_date(block: &Block) -> Option<NaiveDate> {
+    let value = TIMESTAMP_PROPERTIES
+        .iter()
+        .find_map(|key| block.properties().get(*key))?
+        .trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        // Logseq writes milliseconds; anything this small must be seconds
+        let millis = if timestamp.abs() < 100_000_000_000 { timestamp * 1000 } else { timestamp };
+        return DateTime::from_timestamp_millis(millis).map(|time| time.date_naive());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("d1", "2024_01_01", "- Started [[Rust]]\n- Read about #memory"),
+            ("d2", "Jan 2nd, 2024", "- More [[rust]]"),
+            ("d3", "2024-01-03", "- Rested"),
+            ("d5", "2024_01_05", "- Tried [[Go]]"),
+            ("d8", "2024_01_08", "- [[Go]] channels\n- [[Go]] again #memory"),
+            ("rust", "Rust", "- A language"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+
+        // A non-journal page dated through its blocks' timestamps
+        let mut page = LogseqMarkdownParser::parse_content("- Drafted", PageId::new("ideas").unwrap(), "Ideas".to_string()).unwrap();
+        let block_id = page.all_blocks().next().unwrap().id().clone();
+        // 2024-01-09T12:00:00Z
+        page.get_block_mut(&block_id).unwrap().set_property("created-at", "1704801600000");
+        repo.save(page).unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_counts_days_streaks_and_weekly_topics() {
+        let repo = repository();
+        let stats = GetActivityStats::new(&repo)
+            .execute(date("2024-01-01")..=date("2024-01-10"))
+            .unwrap();
+
+        assert_eq!(stats.total_blocks, 8);
+        let days: Vec<(NaiveDate, usize)> = stats.days.iter().map(|day| (day.date, day.blocks)).collect();
+        assert_eq!(
+            days,
+            vec![
+                (date("2024-01-01"), 2),
+                (date("2024-01-02"), 1),
+                (date("2024-01-03"), 1),
+                (date("2024-01-05"), 1),
+                (date("2024-01-08"), 2),
+                (date("2024-01-09"), 1),
+            ]
+        );
+        let longest = stats.longest_streak.unwrap();
+        assert_eq!((longest.start, longest.days), (date("2024-01-01"), 3));
+        let current = stats.current_streak.unwrap();
+        assert_eq!((current.start, current.end), (date("2024-01-08"), date("2024-01-09")));
+
+        assert_eq!(stats.weeks.len(), 2);
+        assert_eq!(stats.weeks[0].week_start, date("2024-01-01"));
+        assert_eq!(stats.weeks[0].blocks, 5);
+        assert_eq!(
+            stats.weeks[0].pages,
+            vec![("Rust".to_string(), 2), ("Go".to_string(), 1), ("memory".to_string(), 1)]
+        );
+        assert_eq!(stats.weeks[1].pages[0], ("Go".to_string(), 2));
+
+        let january_2nd = GetActivityStats::new(&repo)
+            .execute(date("2024-01-02")..=date("2024-01-02"))
+            .unwrap();
+        assert_eq!(january_2nd.total_blocks, 1);
+        assert!(GetActivityStats::new(&repo)
+            .execute(date("2023-01-01")..=date("2023-12-31"))
+            .unwrap()
+            .current_streak
+            .is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::application::{
+    dto::{AliasAction, AliasSuggestion},
+    repositories::PageRepository,
+};
+use crate::application::use_cases::page_names::{journal_date, normalize_tag};
+use crate::domain::{
+    aggregates::Page,
+    base::Entity,
+    value_objects::{EmbeddingVector, PageId},
+    DomainResult,
+};
+use std::collections::HashMap;
+
+/// Similarity two pages' embeddings need unless `with_min_similarity` says otherwise
+const DEFAULT_MIN_SIMILARITY: f32 = 0.95;
+/// Confidence added when one title abbreviates the other
+const ABBREVIATION_BONUS: f32 = 0.05;
+
+/// Use case for finding pages that are likely the same thing under two
+/// titles, from their page-level embeddings
+///
+/// Every pair of pages whose embeddings are at least the minimum
+/// similarity apart is a suggestion. When one title abbreviates the other
+/// (`ML`, `Machine Learning`) the short one is suggested as an alias of the
+/// long one, with more confidence; otherwise the smaller page is suggested
+/// for merging into the larger. Journal pages, which often share a
+/// template, and pairs already aliased to each other are left out.
+///
+/// Pairs are compared one by one, which is fine for the thousands of
+/// pages of a personal graph.
+pub struct SuggestAliases<'a, R: PageRepository> {
+    repository: &'a R,
+    embeddings: HashMap<PageId, EmbeddingVector>,
+    min_similarity: f32,
+}
+
+impl<'a, R: PageRepository> SuggestAliases<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            embeddings: HashMap::new(),
+            min_similarity: DEFAULT_MIN_SIMILARITY,
+        }
+    }
+
+    /// Compare pages by these embeddings, e.g. `EmbeddingService::page_embeddings`
+    pub fn with_page_embeddings(mut self, embeddings: HashMap<PageId, EmbeddingVector>) -> Self {
+        self.embeddings = embeddings;
+        self
+    }
+
+    /// Set how similar two pages' embeddings must be to be suggested
+    pub fn with_min_similarity(mut self, min_similarity: f32) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+
+    /// Get the suggestions, most confident first
+    pub fn execute(&self) -> DomainResult<Vec<AliasSuggestion>> {
+        let mut pages: Vec<(Page, &EmbeddingVector)> = self
+            .repository
+            .find_all()?
+            .into_iter()
+            .filter(|page| journal_date(page).is_none())
+            .filter_map(|page| {
+                let embedding = self.embeddings.get(page.id())?;
+                Some((page, embedding))
+            })
+            .collect();
+        pages.sort_by_key(|(page, _)| page.title().to_lowercase());
+
+        let mut suggestions = Vec::new();
+        for (i, (a, a_embedding)) in pages.iter().enumerate() {
+            for (b, b_embedding) in &pages[i + 1..] {
+                if a_embedding.dimension_count() != b_embedding.dimension_count() || aliased(a, b) {
+                    continue;
+                }
+                let similarity = a_embedding.cosine_similarity(b_embedding)?;
+                if similarity >= self.min_similarity {
+                    suggestions.push(suggestion(a, b, similarity));
+                }
+            }
+        }
+
+        suggestions.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+        });
+        Ok(suggestions)
+    }
+}
+
+/// What to do with two similar pages
+fn suggestion(a: &Page, b: &Page, similarity: f32) -> AliasSuggestion {
+    let (page, other, action, confidence) = if abbreviates(a.title(), b.title()) {
+        (b, a, AliasAction::Alias, similarity + ABBREVIATION_BONUS)
+    } else if abbreviates(b.title(), a.title()) {
+        (a, b, AliasAction::Alias, similarity + ABBREVIATION_BONUS)
+    } else if b.all_blocks().count() > a.all_blocks().count() {
+        (b, a, AliasAction::Merge, similarity)
+    } else {
+        (a, b, AliasAction::Merge, similarity)
+    };
+    AliasSuggestion {
+        page_id: page.id().clone(),
+        title: page.title().to_string(),
+        other_id: other.id().clone(),
+        other_title: other.title().to_string(),
+        action,
+        similarity,
+        confidence: confidence.clamp(0.0, 1.0),
+    }
+}
+
+/// Whether either page lists the other's title in its `alias::` property
+fn aliased(a: &Page, b: &Page) -> bool {
+    let lists = |page: &Page, title: &str| {
+        let title = title.to_lowercase();
+        page.properties()
+            .get("alias")
+            .is_some_and(|aliases| aliases.split(',').any(|alias| normalize_tag(alias) == title))
+    };
+    lists(a, b.title()) || lists(b, a.title())
+}
+
+/// Whether `short` is made of the initials of `long`'s words, like `ML` of
+/// `Machine Learning`
+fn abbreviates(short: &str, long: &str) -> bool {
+    let letters: String = short.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+    let words: Vec<&str> = long.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    let initials: String = words
+        .iter()
+        .filter_map(|word| word.chars().next())
+        .flat_map(char::to_lowercase)
+        .collect();
+    words.len() > 1 && letters == initials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    #[test]
+    fn test_abbreviations_are_initials() {
+        assert!(abbreviates("ML", "Machine Learning"));
+        assert!(abbreviates("nlp", "natural-language processing"));
+        assert!(!abbreviates("ML", "Machine"));
+        assert!(!abbreviates("MLX", "Machine Learning"));
+    }
+
+    #[test]
+    fn test_suggests_aliases_for_abbreviations_and_merges_otherwise() {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content, alias) in [
+            ("ml", "ML", "- models", None),
+            ("machine-learning", "Machine Learning", "- models\n- training", None),
+            ("rust", "Rust", "- ownership", Some("rustlang")),
+            ("rustlang", "rustlang", "- ownership", None),
+            ("notes", "Rust notes", "- ownership\n- borrowing", None),
+            ("python", "Python", "- dynamic", None),
+            ("journal-1", "2024_01_01", "- TODO", None),
+            ("journal-2", "2024_01_02", "- TODO", None),
+        ] {
+            let mut page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.into()).unwrap();
+            if let Some(alias) = alias {
+                page.set_property("alias", alias);
+            }
+            repo.save(page).unwrap();
+        }
+        let vector = |values: &[f32]| EmbeddingVector::new(values.to_vec()).unwrap();
+        let embeddings = HashMap::from([
+            (PageId::new("ml").unwrap(), vector(&[1.0, 0.1, 0.0])),
+            (PageId::new("machine-learning").unwrap(), vector(&[1.0, 0.12, 0.0])),
+            (PageId::new("rust").unwrap(), vector(&[0.0, 1.0, 0.0])),
+            (PageId::new("rustlang").unwrap(), vector(&[0.0, 1.0, 0.01])),
+            (PageId::new("notes").unwrap(), vector(&[0.0, 1.0, 0.2])),
+            (PageId::new("python").unwrap(), vector(&[0.0, 0.0, 1.0])),
+            (PageId::new("journal-1").unwrap(), vector(&[0.5, 0.5, 0.5])),
+            (PageId::new("journal-2").unwrap(), vector(&[0.5, 0.5, 0.5])),
+        ]);
+
+        let suggestions = SuggestAliases::new(&repo).with_page_embeddings(embeddings).execute().unwrap();
+        let pairs: Vec<(&str, &str, AliasAction)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.title.as_str(), suggestion.other_title.as_str(), suggestion.action))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Machine Learning", "ML", AliasAction::Alias),
+                ("Rust notes", "rustlang", AliasAction::Merge),
+                ("Rust notes", "Rust", AliasAction::Merge),
+            ]
+        );
+        assert_eq!(suggestions[0].confidence, 1.0);
+        assert!(suggestions[1].confidence < suggestions[0].confidence);
+    }
+}
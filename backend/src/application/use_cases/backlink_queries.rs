@@ -0,0 +1,126 @@
+use crate::application::{dto::Backlink, repositories::PageRepository};
+use crate::domain::{base::Entity, value_objects::PageId, DomainError, DomainResult};
+
+/// Use case for finding the blocks that reference a page
+///
+/// A block references a page through a `[[page]]` link or a `#tag`; titles are
+/// compared case-insensitively, as Logseq does. References from the page's
+/// own blocks are left out.
+pub struct GetBacklinks<'a, R: PageRepository> {
+    repository: &'a R,
+}
+
+impl<'a, R: PageRepository> GetBacklinks<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self { repository }
+    }
+
+    /// Get every block on other pages that references the page
+    pub fn execute(&self, page_id: &PageId) -> DomainResult<Vec<Backlink>> {
+        let page = self
+            .repository
+            .find_by_id(page_id)?
+            .ok_or_else(|| DomainError::NotFound(format!("Page with id {:?} not found", page_id)))?;
+        let title = page.title().to_lowercase();
+
+        let mut pages = self.repository.find_all()?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+
+        let mut results = Vec::new();
+        for other in pages.iter().filter(|other| other.id() != page_id) {
+            for block in other.all_blocks() {
+                let references_page = block
+                    .page_references()
+                    .iter()
+                    .any(|reference| reference.title().to_lowercase() == title);
+                if !references_page {
+                    continue;
+                }
+
+                results.push(Backlink {
+                    page_id: other.id().clone(),
+                    page_title: other.title().to_string(),
+                    block_id: block.id().clone(),
+                    block_content: block.content().as_str().to_string(),
+                    hierarchy_path: other
+                        .get_hierarchy_path(block.id())
+                        .iter()
+                        .map(|b| b.content().as_str().to_string())
+                        .collect(),
+                    block_path: other.block_path(block.id()),
+                });
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::Page;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use std::collections::HashMap;
+
+    struct InMemoryPageRepository {
+        pages: HashMap<PageId, Page>,
+    }
+
+    impl PageRepository for InMemoryPageRepository {
+        fn save(&mut self, page: Page) -> DomainResult<()> {
+            self.pages.insert(page.id().clone(), page);
+            Ok(())
+        }
+
+        fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+            Ok(self.pages.get(id).cloned())
+        }
+
+        fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+            Ok(self.pages.values().find(|p| p.title() == title).cloned())
+        }
+
+        fn find_all(&self) -> DomainResult<Vec<Page>> {
+            Ok(self.pages.values().cloned().collect())
+        }
+
+        fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+            Ok(self.pages.remove(id).is_some())
+        }
+    }
+
+    fn page(id: &str, title: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_get_backlinks() {
+        let mut repo = InMemoryPageRepository {
+            pages: HashMap::new(),
+        };
+        repo.save(page("rust", "Rust", "- See [[Rust]] itself")).unwrap();
+        repo.save(page("notes", "Notes", "- Languages\n\t- Learning [[rust]]\n- Unrelated"))
+            .unwrap();
+        repo.save(page("todo", "Todo", "- Read the book #Rust")).unwrap();
+
+        let backlinks = GetBacklinks::new(&repo)
+            .execute(&PageId::new("rust").unwrap())
+            .unwrap();
+
+        assert_eq!(backlinks.len(), 2);
+        assert_eq!(backlinks[0].page_title, "Notes");
+        assert_eq!(backlinks[0].hierarchy_path, vec!["Languages", "Learning [[rust]]"]);
+        assert_eq!(backlinks[1].page_title, "Todo");
+        assert!(backlinks[1].block_content.contains("#Rust"));
+    }
+
+    #[test]
+    fn test_get_backlinks_for_missing_page() {
+        let repo = InMemoryPageRepository {
+            pages: HashMap::new(),
+        };
+        let result = GetBacklinks::new(&repo).execute(&PageId::new("missing").unwrap());
+        assert!(matches!(result, Err(DomainError::NotFound(_))));
+    }
+}
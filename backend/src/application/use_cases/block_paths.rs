@@ -0,0 +1,86 @@
+use crate::application::{dto::BlockAddress, repositories::PageRepository};
+use crate::domain::{
+    aggregates::Page,
+    base::Entity,
+    value_objects::{BlockId, BlockPath},
+    DomainResult,
+};
+
+/// Use case for translating between block IDs and [`BlockPath`]s, so tools
+/// can address blocks as `Page > parent > block` instead of by UUID
+pub struct ResolveBlockPath<'a, R: PageRepository> {
+    repository: &'a R,
+}
+
+impl<'a, R: PageRepository> ResolveBlockPath<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self { repository }
+    }
+
+    /// The block a path addresses, if its page and block exist
+    pub fn resolve(&self, path: &BlockPath) -> DomainResult<Option<BlockAddress>> {
+        let page = match self.repository.find_by_title(path.page())? {
+            Some(page) => Some(page),
+            // Titles are matched ignoring case, as Logseq does
+            None => {
+                let title = path.page().to_lowercase();
+                self.repository
+                    .find_all()?
+                    .into_iter()
+                    .find(|page| page.title().to_lowercase() == title)
+            }
+        };
+        Ok(page.and_then(|page| {
+            let block_id = page.resolve_block_path(path)?.clone();
+            address(&page, &block_id)
+        }))
+    }
+
+    /// The path of a block, looked up by its ID in every page
+    pub fn path_of(&self, block_id: &BlockId) -> DomainResult<Option<BlockAddress>> {
+        Ok(self
+            .repository
+            .find_all()?
+            .iter()
+            .find(|page| page.get_block(block_id).is_some())
+            .and_then(|page| address(page, block_id)))
+    }
+}
+
+fn address(page: &Page, block_id: &BlockId) -> Option<BlockAddress> {
+    Some(BlockAddress {
+        page_id: page.id().clone(),
+        block_id: block_id.clone(),
+        block_path: page.block_path(block_id)?,
+        content: page.get_block(block_id)?.content().as_str().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    #[test]
+    fn test_resolves_paths_and_ids() {
+        let mut repo = InMemoryPageRepository::new();
+        let content = "- Ownership\n\t- Moves\n\t- Moves\n- Lifetimes";
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("rust").unwrap(), "Rust".into()).unwrap();
+        repo.save(page).unwrap();
+
+        let resolve = ResolveBlockPath::new(&repo);
+        let path = BlockPath::parse("rust > Ownership > Moves[2]").unwrap();
+        let address = resolve.resolve(&path).unwrap().unwrap();
+        assert_eq!(address.page_id.as_str(), "rust");
+        assert_eq!(address.block_path.to_string(), "Rust > Ownership > Moves[2]");
+        assert_eq!(address.content, "Moves");
+
+        let back = resolve.path_of(&address.block_id).unwrap().unwrap();
+        assert_eq!(back, address);
+
+        assert_eq!(resolve.resolve(&BlockPath::parse("Rust > Borrowing").unwrap()).unwrap(), None);
+        assert_eq!(resolve.path_of(&BlockId::new("missing").unwrap()).unwrap(), None);
+    }
+}
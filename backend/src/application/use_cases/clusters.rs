@@ -0,0 +1,260 @@
+use crate::application::{
+    dto::PageCluster,
+    repositories::PageRepository,
+    services::{LinkGraph, LinkNode, NeighborQuery},
+};
+use crate::domain::{
+    value_objects::{EmbeddingVector, PageId},
+    DomainError, DomainResult,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Label propagation rounds unless `with_max_iterations` says otherwise
+const DEFAULT_MAX_ITERATIONS: usize = 20;
+/// Smallest cluster returned unless `with_min_size` says otherwise
+const DEFAULT_MIN_SIZE: usize = 2;
+/// Representatives per cluster unless `with_representatives` says otherwise
+const DEFAULT_REPRESENTATIVES: usize = 3;
+/// Similarity an unlinked page needs to a cluster's centroid to join it
+const DEFAULT_MIN_SIMILARITY: f32 = 0.5;
+
+/// Use case for finding the topics a graph's pages cluster into
+///
+/// Clusters are the link graph's communities (see
+/// [`LinkGraph::communities`]); a cluster's representatives are the pages
+/// with the most links inside it. Given page embeddings, clustering is
+/// refined with each cluster's centroid, the mean of its pages' embeddings:
+/// pages without links join the cluster they're most similar to, and
+/// representatives are the pages closest to the centroid.
+pub struct GetClusters<'a, R: PageRepository> {
+    repository: &'a R,
+    max_iterations: usize,
+    min_size: usize,
+    representatives: usize,
+    embeddings: HashMap<PageId, EmbeddingVector>,
+    min_similarity: f32,
+}
+
+impl<'a, R: PageRepository> GetClusters<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            min_size: DEFAULT_MIN_SIZE,
+            representatives: DEFAULT_REPRESENTATIVES,
+            embeddings: HashMap::new(),
+            min_similarity: DEFAULT_MIN_SIMILARITY,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Leave out clusters with fewer pages than this
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size.max(1);
+        self
+    }
+
+    /// Set how many representative pages each cluster lists
+    pub fn with_representatives(mut self, representatives: usize) -> Self {
+        self.representatives = representatives;
+        self
+    }
+
+    /// Refine clusters with these page embeddings
+    pub fn with_page_embeddings(mut self, embeddings: HashMap<PageId, EmbeddingVector>) -> Self {
+        self.embeddings = embeddings;
+        self
+    }
+
+    /// Set how similar an unlinked page must be to a cluster to join it
+    pub fn with_min_similarity(mut self, min_similarity: f32) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+
+    /// Get the clusters of the graph, largest first
+    pub fn execute(&self) -> DomainResult<Vec<PageCluster>> {
+        let pages = self.repository.find_all()?;
+        let graph = LinkGraph::build(&pages);
+        let mut communities = graph.communities(self.max_iterations);
+        if !self.embeddings.is_empty() {
+            communities = self.refine(communities)?;
+        }
+
+        let mut clusters = Vec::new();
+        for mut community in communities.into_iter().filter(|community| community.len() >= self.min_size) {
+            community.sort_by_key(|node| node.title.to_lowercase());
+            let representatives = self.representatives(&graph, &community)?;
+            clusters.push(PageCluster {
+                id: clusters.len(),
+                pages: community,
+                representatives,
+            });
+        }
+        Ok(clusters)
+    }
+
+    /// Move unlinked pages into the linked cluster whose centroid they're most similar to
+    fn refine(&self, communities: Vec<Vec<LinkNode>>) -> DomainResult<Vec<Vec<LinkNode>>> {
+        let (mut linked, unlinked): (Vec<Vec<LinkNode>>, Vec<Vec<LinkNode>>) =
+            communities.into_iter().partition(|community| community.len() > 1);
+        let centroids = linked
+            .iter()
+            .map(|community| self.centroid(community))
+            .collect::<DomainResult<Vec<_>>>()?;
+
+        let mut remaining = Vec::new();
+        for community in unlinked {
+            let mut best: Option<(usize, f32)> = None;
+            if let Some(embedding) = self.embedding(&community[0]) {
+                for (i, centroid) in centroids.iter().enumerate() {
+                    let Some(centroid) = centroid else {
+                        continue;
+                    };
+                    let similarity = embedding.cosine_similarity(centroid)?;
+                    if similarity >= self.min_similarity && best.is_none_or(|(_, best)| similarity > best) {
+                        best = Some((i, similarity));
+                    }
+                }
+            }
+            match best {
+                Some((i, _)) => linked[i].extend(community),
+                None => remaining.push(community),
+            }
+        }
+
+        linked.extend(remaining);
+        linked.sort_by_key(|community| std::cmp::Reverse(community.len()));
+        Ok(linked)
+    }
+
+    /// The pages closest to the cluster's centroid, else those with the most links inside it
+    fn representatives(&self, graph: &LinkGraph, community: &[LinkNode]) -> DomainResult<Vec<LinkNode>> {
+        let members: HashSet<String> = community.iter().map(|node| node.title.to_lowercase()).collect();
+        let centroid = self.centroid(community)?;
+
+        let mut ranked = Vec::new();
+        for node in community {
+            let similarity = match (&centroid, self.embedding(node)) {
+                (Some(centroid), Some(embedding)) => Some(embedding.cosine_similarity(centroid)?),
+                _ => None,
+            };
+            let links: usize = graph
+                .neighbors(&node.title, &NeighborQuery::new())
+                .iter()
+                .filter(|neighbor| members.contains(&neighbor.node.title.to_lowercase()))
+                .map(|neighbor| neighbor.weight)
+                .sum();
+            ranked.push((node, similarity, links));
+        }
+        // Pages without embeddings come after those with; `community` is in title order
+        ranked.sort_by(|a, b| match (a.1, b.1) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }
+        .then_with(|| b.2.cmp(&a.2)));
+
+        Ok(ranked
+            .into_iter()
+            .take(self.representatives)
+            .map(|(node, _, _)| node.clone())
+            .collect())
+    }
+
+    /// Mean of the embeddings of a cluster's pages; none if none have one
+    fn centroid(&self, community: &[LinkNode]) -> DomainResult<Option<EmbeddingVector>> {
+        let embeddings: Vec<&EmbeddingVector> = community.iter().filter_map(|node| self.embedding(node)).collect();
+        let Some(first) = embeddings.first() else {
+            return Ok(None);
+        };
+
+        let mut sum = vec![0.0; first.dimension_count()];
+        for embedding in &embeddings {
+            if embedding.dimension_count() != sum.len() {
+                return Err(DomainError::InvalidOperation(
+                    "Cannot average embeddings of different dimensions".to_string(),
+                ));
+            }
+            for (total, value) in sum.iter_mut().zip(embedding.dimensions()) {
+                *total += value;
+            }
+        }
+        let count = embeddings.len() as f32;
+        EmbeddingVector::new(sum.into_iter().map(|total| total / count).collect()).map(Some)
+    }
+
+    fn embedding(&self, node: &LinkNode) -> Option<&EmbeddingVector> {
+        node.page_id.as_ref().and_then(|id| self.embeddings.get(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- [[Ownership]] and [[Borrowing]]"),
+            ("ownership", "Ownership", "- [[Borrowing]] rules of [[Rust]]"),
+            ("go", "Go", "- [[Goroutines]] and [[Channels]]\n- unlike [[Rust]]"),
+            ("goroutines", "Goroutines", "- [[Channels]] in [[Go]]"),
+            ("lifetimes", "Lifetimes", "- How long references live"),
+            ("python", "Python", "- Dynamic"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    fn titles(nodes: &[LinkNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_clusters_linked_pages_with_representatives() {
+        let repo = repository();
+        let clusters = GetClusters::new(&repo).with_representatives(2).execute().unwrap();
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].id, 0);
+        assert_eq!(titles(&clusters[0].pages), vec!["Borrowing", "Ownership", "Rust"]);
+        assert_eq!(titles(&clusters[0].representatives), vec!["Ownership", "Rust"]);
+        assert_eq!(titles(&clusters[1].pages), vec!["Channels", "Go", "Goroutines"]);
+        assert_eq!(titles(&clusters[1].representatives), vec!["Go", "Goroutines"]);
+
+        let all = GetClusters::new(&repo).with_min_size(1).execute().unwrap();
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn test_embeddings_place_unlinked_pages_and_pick_representatives() {
+        let repo = repository();
+        let vector = |values: &[f32]| EmbeddingVector::new(values.to_vec()).unwrap();
+        let embeddings = HashMap::from([
+            (PageId::new("rust").unwrap(), vector(&[1.0, 0.0, 0.0])),
+            (PageId::new("ownership").unwrap(), vector(&[0.8, 0.2, 0.0])),
+            (PageId::new("go").unwrap(), vector(&[0.0, 1.0, 0.0])),
+            (PageId::new("lifetimes").unwrap(), vector(&[0.9, 0.1, 0.0])),
+            (PageId::new("python").unwrap(), vector(&[0.0, 0.0, 1.0])),
+        ]);
+        let clusters = GetClusters::new(&repo)
+            .with_page_embeddings(embeddings)
+            .with_representatives(1)
+            .execute()
+            .unwrap();
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(titles(&clusters[0].pages), vec!["Borrowing", "Lifetimes", "Ownership", "Rust"]);
+        assert_eq!(titles(&clusters[0].representatives), vec!["Lifetimes"]);
+        assert_eq!(titles(&clusters[1].pages), vec!["Channels", "Go", "Goroutines"]);
+        assert_eq!(titles(&clusters[1].representatives), vec!["Go"]);
+    }
+}
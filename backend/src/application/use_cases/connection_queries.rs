@@ -0,0 +1,205 @@
+use crate::application::{
+    dto::{Backlink, ConnectionPath, ConnectionStep},
+    repositories::PageRepository,
+    services::{
+        link_graph::{block_references, block_uuids},
+        EdgeType, LinkGraph, LinkNode, NeighborQuery,
+    },
+};
+use crate::domain::{aggregates::Page, base::Entity, entities::Block, value_objects::PageId, DomainError, DomainResult};
+use std::collections::{HashMap, HashSet};
+
+/// Links followed between two pages unless `with_max_depth` says otherwise
+const DEFAULT_MAX_DEPTH: usize = 6;
+/// Paths returned unless `with_max_paths` says otherwise
+const DEFAULT_MAX_PATHS: usize = 5;
+
+/// Use case for explaining how two pages are related
+///
+/// Finds the shortest chains of links between two pages, following links in
+/// either direction: a page both link to or tag relates them as much as a
+/// direct link does. Each step of a path comes with the blocks that make
+/// the link, so the answer can be read without opening the pages.
+pub struct FindConnection<'a, R: PageRepository> {
+    repository: &'a R,
+    query: NeighborQuery,
+    max_paths: usize,
+}
+
+impl<'a, R: PageRepository> FindConnection<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            query: NeighborQuery::new().with_max_depth(DEFAULT_MAX_DEPTH),
+            max_paths: DEFAULT_MAX_PATHS,
+        }
+    }
+
+    /// Set the most links a path may have
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.query.max_depth = max_depth;
+        self
+    }
+
+    /// Set how many of the shortest paths are returned
+    pub fn with_max_paths(mut self, max_paths: usize) -> Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    /// Only follow links of these types
+    pub fn with_edge_types(mut self, edge_types: impl IntoIterator<Item = EdgeType>) -> Self {
+        self.query = self.query.with_edge_types(edge_types);
+        self
+    }
+
+    /// Get the shortest paths from one page to another; none if they aren't connected
+    pub fn execute(&self, page_a: &PageId, page_b: &PageId) -> DomainResult<Vec<ConnectionPath>> {
+        let pages = self.repository.find_all()?;
+        let find = |id: &PageId| {
+            pages
+                .iter()
+                .find(|page| page.id() == id)
+                .ok_or_else(|| DomainError::NotFound(format!("Page with id {:?} not found", id)))
+        };
+        let (a, b) = (find(page_a)?, find(page_b)?);
+
+        let graph = LinkGraph::build(&pages);
+        let by_title: HashMap<String, &Page> = pages.iter().map(|page| (page.title().to_lowercase(), page)).collect();
+        let paths = graph.shortest_paths(a.title(), b.title(), &self.query, self.max_paths);
+
+        Ok(paths
+            .into_iter()
+            .map(|nodes| ConnectionPath {
+                steps: nodes
+                    .windows(2)
+                    .map(|pair| self.step(&graph, &by_title, &pair[0], &pair[1]))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    fn step(&self, graph: &LinkGraph, by_title: &HashMap<String, &Page>, from: &LinkNode, to: &LinkNode) -> ConnectionStep {
+        let mut edge_types: Vec<EdgeType> = graph
+            .edges_between(&from.title, &to.title)
+            .into_iter()
+            .map(|edge| edge.edge_type)
+            .filter(|edge_type| self.query.edge_types.is_empty() || self.query.edge_types.contains(edge_type))
+            .collect();
+        edge_types.sort();
+        edge_types.dedup();
+
+        let page = |node: &LinkNode| by_title.get(&node.title.to_lowercase()).copied();
+        let mut blocks = Vec::new();
+        for (source, target) in [(page(from), to), (page(to), from)] {
+            if let Some(source) = source {
+                blocks.extend(linking_blocks(source, target, page(target), &edge_types));
+            }
+        }
+        ConnectionStep {
+            from: from.clone(),
+            to: to.clone(),
+            edge_types,
+            blocks,
+        }
+    }
+}
+
+/// Blocks of `page` that link it to `target` in one of the `edge_types` ways
+fn linking_blocks(page: &Page, target: &LinkNode, target_page: Option<&Page>, edge_types: &[EdgeType]) -> Vec<Backlink> {
+    let title = target.title.to_lowercase();
+    let target_uuids: HashSet<String> = target_page
+        .map(|target_page| block_uuids(&[target_page]).into_keys().collect())
+        .unwrap_or_default();
+    let target_urls: HashSet<&str> = target_page
+        .map(|target_page| target_page.all_urls().into_iter().map(|url| url.as_str()).collect())
+        .unwrap_or_default();
+
+    let links = |block: &Block| {
+        edge_types.iter().any(|edge_type| match edge_type {
+            EdgeType::PageRef | EdgeType::Tag => block.page_references().iter().any(|reference| {
+                reference.is_tag() == (*edge_type == EdgeType::Tag) && reference.title().to_lowercase() == title
+            }),
+            EdgeType::BlockRef => block_references(block.content().as_str())
+                .iter()
+                .any(|uuid| target_uuids.contains(uuid)),
+            EdgeType::UrlShared => block.urls().iter().any(|url| target_urls.contains(url.as_str())),
+        })
+    };
+
+    page.all_blocks()
+        .filter(|block| links(block))
+        .map(|block| Backlink {
+            page_id: page.id().clone(),
+            page_title: page.title().to_string(),
+            block_id: block.id().clone(),
+            block_content: block.content().as_str().to_string(),
+            hierarchy_path: page
+                .get_hierarchy_path(block.id())
+                .iter()
+                .map(|b| b.content().as_str().to_string())
+                .collect(),
+            block_path: page.block_path(block.id()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- Ownership\n\t- Borrowing #memory"),
+            ("gc", "Garbage collection", "- Tracing frees #memory for you"),
+            ("go", "Go", "- Collected by [[Garbage collection]]\n- Compare https://go.dev/doc"),
+            ("notes", "Notes", "- Read https://go.dev/doc"),
+            ("python", "Python", "- Dynamic"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    fn id(id: &str) -> PageId {
+        PageId::new(id).unwrap()
+    }
+
+    fn titles(path: &ConnectionPath) -> Vec<&str> {
+        path.pages().into_iter().map(|node| node.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_finds_shortest_paths_with_linking_blocks() {
+        let repo = repository();
+        let paths = FindConnection::new(&repo).execute(&id("rust"), &id("notes")).unwrap();
+
+        assert_eq!(paths.len(), 1);
+        let path = &paths[0];
+        assert_eq!(titles(path), vec!["Rust", "memory", "Garbage collection", "Go", "Notes"]);
+        assert_eq!(path.steps[0].edge_types, vec![EdgeType::Tag]);
+        assert_eq!(path.steps[0].blocks[0].block_content, "Borrowing #memory");
+        assert_eq!(path.steps[0].blocks[0].hierarchy_path, vec!["Ownership", "Borrowing #memory"]);
+        assert_eq!(path.steps[2].blocks[0].page_title, "Go");
+        assert_eq!(path.steps[3].edge_types, vec![EdgeType::UrlShared]);
+        assert_eq!(path.steps[3].blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_limits_and_unconnected_pages() {
+        let repo = repository();
+        let use_case = FindConnection::new(&repo);
+        assert!(use_case.execute(&id("rust"), &id("python")).unwrap().is_empty());
+        assert!(use_case.execute(&id("rust"), &id("rust")).unwrap()[0].is_empty());
+        assert!(matches!(use_case.execute(&id("rust"), &id("java")), Err(DomainError::NotFound(_))));
+
+        let short = FindConnection::new(&repo).with_max_depth(3);
+        assert!(short.execute(&id("rust"), &id("notes")).unwrap().is_empty());
+        let no_urls = FindConnection::new(&repo).with_edge_types([EdgeType::PageRef, EdgeType::Tag]);
+        assert!(no_urls.execute(&id("rust"), &id("notes")).unwrap().is_empty());
+    }
+}
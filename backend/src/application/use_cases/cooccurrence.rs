@@ -0,0 +1,243 @@
+use crate::application::{
+    dto::{Cooccurrence, ReferenceSuggestion},
+    repositories::PageRepository,
+};
+use crate::domain::DomainResult;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Where two references must both appear to count as appearing together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CooccurrenceScope {
+    /// The same block
+    #[default]
+    Block,
+    /// Any blocks of the same page
+    Page,
+}
+
+/// Use case for finding which tags and page references are used together
+///
+/// Tags and `[[links]]` both name pages, so they're counted as one kind of
+/// reference, case-insensitively. Pairs are listed most frequent first;
+/// [`suggest`](Self::suggest) turns them into references to add next to the
+/// ones already written.
+pub struct GetCooccurrences<'a, R: PageRepository> {
+    repository: &'a R,
+    scope: CooccurrenceScope,
+    min_count: usize,
+    limit: Option<usize>,
+}
+
+/// How often references appear, alone and in pairs
+struct Counts {
+    /// Display title of each reference, by lowercased title: its most used spelling
+    titles: HashMap<String, String>,
+    /// Blocks or pages each reference appears in
+    occurrences: HashMap<String, usize>,
+    /// Blocks or pages each pair appears in, keyed in sorted order
+    pairs: BTreeMap<(String, String), usize>,
+}
+
+impl Counts {
+    fn weight(&self, pair: &(String, String), count: usize) -> f64 {
+        let either = self.occurrences[&pair.0] + self.occurrences[&pair.1] - count;
+        count as f64 / either as f64
+    }
+}
+
+impl<'a, R: PageRepository> GetCooccurrences<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            scope: CooccurrenceScope::default(),
+            min_count: 1,
+            limit: None,
+        }
+    }
+
+    pub fn with_scope(mut self, scope: CooccurrenceScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Leave out pairs that appear together fewer times than this
+    pub fn with_min_count(mut self, min_count: usize) -> Self {
+        self.min_count = min_count.max(1);
+        self
+    }
+
+    /// Return at most this many pairs (or suggestions)
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Get every pair of references that appear together, most frequent first
+    ///
+    /// Ties are broken by weight, then by title.
+    pub fn execute(&self) -> DomainResult<Vec<Cooccurrence>> {
+        let counts = self.count()?;
+        let mut results: Vec<Cooccurrence> = counts
+            .pairs
+            .iter()
+            .filter(|(_, count)| **count >= self.min_count)
+            .map(|(pair, count)| Cooccurrence {
+                first: counts.titles[&pair.0].clone(),
+                second: counts.titles[&pair.1].clone(),
+                count: *count,
+                weight: counts.weight(pair, *count),
+            })
+            .collect();
+        results.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| b.weight.total_cmp(&a.weight)));
+        results.truncate(self.limit.unwrap_or(usize::MAX));
+        Ok(results)
+    }
+
+    /// Suggest references to add to a block (or page) that has `references`
+    ///
+    /// Suggestions are references that appear together with any of them,
+    /// best first by the sum of their weights with each.
+    pub fn suggest(&self, references: &[&str]) -> DomainResult<Vec<ReferenceSuggestion>> {
+        let counts = self.count()?;
+        let present: BTreeSet<String> = references.iter().map(|title| title.to_lowercase()).collect();
+
+        let mut suggestions: BTreeMap<&String, ReferenceSuggestion> = BTreeMap::new();
+        for (pair, count) in counts.pairs.iter().filter(|(_, count)| **count >= self.min_count) {
+            let candidate = match (present.contains(&pair.0), present.contains(&pair.1)) {
+                (true, false) => &pair.1,
+                (false, true) => &pair.0,
+                _ => continue,
+            };
+            let suggestion = suggestions.entry(candidate).or_insert_with(|| ReferenceSuggestion {
+                title: counts.titles[candidate].clone(),
+                count: 0,
+                score: 0.0,
+            });
+            suggestion.count += count;
+            suggestion.score += counts.weight(pair, *count);
+        }
+
+        let mut results: Vec<ReferenceSuggestion> = suggestions.into_values().collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.count.cmp(&a.count)));
+        results.truncate(self.limit.unwrap_or(usize::MAX));
+        Ok(results)
+    }
+
+    fn count(&self) -> DomainResult<Counts> {
+        let mut pages = self.repository.find_all()?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+
+        let mut spellings: HashMap<String, BTreeMap<&str, usize>> = HashMap::new();
+        let mut counts = Counts {
+            titles: HashMap::new(),
+            occurrences: HashMap::new(),
+            pairs: BTreeMap::new(),
+        };
+        let mut record = |references: BTreeSet<String>| {
+            for reference in &references {
+                *counts.occurrences.entry(reference.clone()).or_default() += 1;
+            }
+            let references: Vec<&String> = references.iter().collect();
+            for (i, first) in references.iter().enumerate() {
+                for second in &references[i + 1..] {
+                    *counts.pairs.entry(((*first).clone(), (*second).clone())).or_default() += 1;
+                }
+            }
+        };
+
+        for page in &pages {
+            let mut page_references = BTreeSet::new();
+            for block in page.all_blocks() {
+                let mut block_references = BTreeSet::new();
+                for reference in block.page_references() {
+                    let key = reference.title().to_lowercase();
+                    *spellings.entry(key.clone()).or_default().entry(reference.title()).or_default() += 1;
+                    block_references.insert(key);
+                }
+                match self.scope {
+                    CooccurrenceScope::Block => record(block_references),
+                    CooccurrenceScope::Page => page_references.extend(block_references),
+                }
+            }
+            if self.scope == CooccurrenceScope::Page {
+                record(page_references);
+            }
+        }
+
+        for (key, spellings) in spellings {
+            // Ties go to the spelling that sorts first
+            let (title, _) = spellings
+                .into_iter()
+                .rev()
+                .max_by_key(|(_, count)| *count)
+                .expect("every reference has a spelling");
+            counts.titles.insert(key, title.to_string());
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("a", "A", "- Borrow checker #rust #memory\n- Lifetimes #Rust [[Memory]]\n- Goroutines #go"),
+            ("b", "B", "- Arenas #rust #memory #alloc\n- Channels #go"),
+            ("c", "C", "- Escape analysis #go\n- #memory"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    fn pairs(results: Vec<Cooccurrence>) -> Vec<(String, String, usize)> {
+        results.into_iter().map(|c| (c.first, c.second, c.count)).collect()
+    }
+
+    #[test]
+    fn test_counts_pairs_per_block_and_per_page() {
+        let repo = repository();
+
+        let by_block = GetCooccurrences::new(&repo).execute().unwrap();
+        assert_eq!(by_block[0].first, "memory");
+        assert_eq!((by_block[0].second.as_str(), by_block[0].count), ("rust", 3));
+        // memory appears in 4 blocks and rust in 3, together in 3
+        assert!((by_block[0].weight - 0.75).abs() < 1e-9);
+        assert!(by_block.iter().all(|pair| pair.first != "go" && pair.second != "go"));
+
+        let by_page = GetCooccurrences::new(&repo)
+            .with_scope(CooccurrenceScope::Page)
+            .with_min_count(2)
+            .execute()
+            .unwrap();
+        assert_eq!(
+            pairs(by_page),
+            vec![
+                ("go".to_string(), "memory".to_string(), 3),
+                ("go".to_string(), "rust".to_string(), 2),
+                ("memory".to_string(), "rust".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggests_references_used_alongside() {
+        let repo = repository();
+        let suggestions = GetCooccurrences::new(&repo).suggest(&["Rust"]).unwrap();
+        let titles: Vec<&str> = suggestions.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["memory", "alloc"]);
+        assert_eq!(suggestions[0].count, 3);
+
+        let suggestions = GetCooccurrences::new(&repo).with_limit(1).suggest(&["rust", "memory"]).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].title, "alloc");
+        assert!(GetCooccurrences::new(&repo).suggest(&["python"]).unwrap().is_empty());
+    }
+}
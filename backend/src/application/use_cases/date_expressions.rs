@@ -0,0 +1,242 @@
+/// Dates written into search queries, as journal date ranges
+use chrono::{Datelike, Days, Month, Months, NaiveDate, Weekday};
+use std::ops::RangeInclusive;
+
+/// Words that may introduce a date expression, and are dropped with it
+const PREPOSITIONS: [&str; 6] = ["from", "in", "during", "on", "over", "within"];
+
+/// A date expression found in a search query, and the rest of the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatedQuery {
+    pub range: RangeInclusive<NaiveDate>,
+    /// The query without the expression, its words separated by single spaces
+    pub remainder: String,
+}
+
+/// Find the first date expression in `query`, resolving relative dates
+/// against `today`
+///
+/// Understands:
+/// - `today`, `yesterday` and ISO dates like `2024-03-05`
+/// - `this`/`last` `week`/`month`/`year`: calendar periods, weeks starting
+///   on Monday
+/// - `past week`/`month`/`year` and `last`/`past N days`/`weeks`/`months`:
+///   the period up to and including today
+/// - `N days`/`weeks`/`months ago`: that day, or the calendar week or month
+///   it falls in
+/// - `last monday`, and after a preposition `monday`, `march` or `march 2024`
+///
+/// A `from`, `in`, `during`, `on`, `over` or `within` before the expression
+/// goes with it, as does a `the` after that; after `since`, the range runs
+/// until today.
+pub fn extract_date_range(query: &str, today: NaiveDate) -> Option<DatedQuery> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let normalized: Vec<String> = words
+        .iter()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .collect();
+
+    for start in 0..normalized.len() {
+        let (since, mut at) = match normalized[start].as_str() {
+            "since" => (true, start + 1),
+            word if PREPOSITIONS.contains(&word) => (false, start + 1),
+            _ => (false, start),
+        };
+        if at > start && normalized.get(at).is_some_and(|word| word == "the") {
+            at += 1;
+        }
+        let Some((range, length)) = parse_expression(&normalized[at..], today, at > start) else {
+            continue;
+        };
+        let range = if since { *range.start()..=today } else { range };
+        let remainder = words[..start].iter().chain(&words[at + length..]).copied().collect::<Vec<_>>().join(" ");
+        return Some(DatedQuery { range, remainder });
+    }
+    None
+}
+
+/// The range an expression at the start of `words` stands for, and how many
+/// words it takes
+fn parse_expression(
+    words: &[String],
+    today: NaiveDate,
+    after_preposition: bool,
+) -> Option<(RangeInclusive<NaiveDate>, usize)> {
+    let first = words.first()?.as_str();
+    let second = words.get(1).map(String::as_str);
+    let third = words.get(2).map(String::as_str);
+
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((date..=date, 1));
+    }
+    match first {
+        "today" => return Some((today..=today, 1)),
+        "yesterday" => {
+            let day = today.pred_opt()?;
+            return Some((day..=day, 1));
+        }
+        _ => {}
+    }
+
+    if matches!(first, "last" | "past") {
+        if let (Some(count), Some(unit)) = (second.and_then(number), third.and_then(Unit::parse)) {
+            return Some((unit.up_to(today, count)?, 3));
+        }
+        if let Some(unit) = second.and_then(Unit::parse) {
+            // "last week" is the calendar week before this one, "past week" the seven days up to today
+            let range = if first == "last" { unit.period(unit.back(today, 1)?)? } else { unit.up_to(today, 1)? };
+            return Some((range, 2));
+        }
+        if let Some(weekday) = second.and_then(weekday).filter(|_| first == "last") {
+            let day = previous(today, weekday)?;
+            return Some((day..=day, 2));
+        }
+    }
+    if first == "this" {
+        if let Some(unit) = second.and_then(Unit::parse) {
+            return Some((unit.period(today)?, 2));
+        }
+    }
+    if let (Some(count), Some(unit), Some("ago")) = (number(first), second.and_then(Unit::parse), third) {
+        return Some((unit.period(unit.back(today, count)?)?, 3));
+    }
+
+    // Weekday and month names are common words, so only after a preposition
+    if after_preposition {
+        if let Some(weekday) = weekday(first) {
+            let day = previous(today, weekday)?;
+            return Some((day..=day, 1));
+        }
+        if let Ok(month) = first.parse::<Month>() {
+            let month = month.number_from_month();
+            if let Some(year) = second.and_then(|word| word.parse::<i32>().ok()).filter(|year| (1000..=9999).contains(year)) {
+                return Some((Unit::Month.period(NaiveDate::from_ymd_opt(year, month, 1)?)?, 2));
+            }
+            // The latest such month: this year's, unless it hasn't begun
+            let year = if month <= today.month() { today.year() } else { today.year() - 1 };
+            return Some((Unit::Month.period(NaiveDate::from_ymd_opt(year, month, 1)?)?, 1));
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Unit {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "day" | "days" => Some(Unit::Day),
+            "week" | "weeks" => Some(Unit::Week),
+            "month" | "months" => Some(Unit::Month),
+            "year" | "years" => Some(Unit::Year),
+            _ => None,
+        }
+    }
+
+    /// The day `count` units before `day`
+    fn back(self, day: NaiveDate, count: u32) -> Option<NaiveDate> {
+        match self {
+            Unit::Day => day.checked_sub_days(Days::new(count.into())),
+            Unit::Week => day.checked_sub_days(Days::new(7 * u64::from(count))),
+            Unit::Month => day.checked_sub_months(Months::new(count)),
+            Unit::Year => day.checked_sub_months(Months::new(12 * count)),
+        }
+    }
+
+    /// The `count` units ending with `today`
+    fn up_to(self, today: NaiveDate, count: u32) -> Option<RangeInclusive<NaiveDate>> {
+        Some(self.back(today, count)?.succ_opt()?..=today)
+    }
+
+    /// The calendar day, week, month or year `day` falls in
+    fn period(self, day: NaiveDate) -> Option<RangeInclusive<NaiveDate>> {
+        let start = match self {
+            Unit::Day => day,
+            Unit::Week => day.checked_sub_days(Days::new(day.weekday().num_days_from_monday().into()))?,
+            Unit::Month => day.with_day(1)?,
+            Unit::Year => day.with_ordinal(1)?,
+        };
+        let end = match self {
+            Unit::Day => day,
+            Unit::Week => start.checked_add_days(Days::new(6))?,
+            Unit::Month => start.checked_add_months(Months::new(1))?.pred_opt()?,
+            Unit::Year => start.checked_add_months(Months::new(12))?.pred_opt()?,
+        };
+        Some(start..=end)
+    }
+}
+
+/// A count written as digits or as a small number word
+fn number(word: &str) -> Option<u32> {
+    const WORDS: [&str; 13] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    ];
+    match word {
+        "a" | "an" => Some(1),
+        _ => word
+            .parse()
+            .ok()
+            .or_else(|| WORDS.iter().position(|number| *number == word).map(|n| n as u32)),
+    }
+}
+
+/// A weekday's full or short English name
+fn weekday(word: &str) -> Option<Weekday> {
+    word.parse().ok()
+}
+
+/// The latest `weekday` before `today`
+fn previous(today: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let days_since = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    let days_since = if days_since == 0 { 7 } else { days_since };
+    today.checked_sub_days(Days::new(days_since.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn extract(query: &str) -> Option<(String, String, String)> {
+        // A Wednesday
+        let dated = extract_date_range(query, date("2024-03-13"))?;
+        Some((dated.range.start().to_string(), dated.range.end().to_string(), dated.remainder))
+    }
+
+    #[test]
+    fn test_relative_expressions_become_ranges_and_leave_the_keywords() {
+        let expected = |start: &str, end: &str, remainder: &str| {
+            Some((start.to_string(), end.to_string(), remainder.to_string()))
+        };
+        assert_eq!(extract("meeting notes from last week"), expected("2024-03-04", "2024-03-10", "meeting notes"));
+        assert_eq!(extract("standup this week"), expected("2024-03-11", "2024-03-17", "standup"));
+        assert_eq!(extract("yesterday"), expected("2024-03-12", "2024-03-12", ""));
+        assert_eq!(extract("ideas over the past 3 days"), expected("2024-03-11", "2024-03-13", "ideas"));
+        assert_eq!(extract("last month budget"), expected("2024-02-01", "2024-02-29", "budget"));
+        assert_eq!(extract("past week"), expected("2024-03-07", "2024-03-13", ""));
+        assert_eq!(extract("two weeks ago retro"), expected("2024-02-26", "2024-03-03", "retro"));
+        assert_eq!(extract("call on monday"), expected("2024-03-11", "2024-03-11", "call"));
+        assert_eq!(extract("last wednesday"), expected("2024-03-06", "2024-03-06", ""));
+        assert_eq!(extract("trips in december"), expected("2023-12-01", "2023-12-31", "trips"));
+        assert_eq!(extract("trips in March 2022"), expected("2022-03-01", "2022-03-31", "trips"));
+        assert_eq!(extract("books since 2024-01-15"), expected("2024-01-15", "2024-03-13", "books"));
+        assert_eq!(extract("release on 2023-06-01."), expected("2023-06-01", "2023-06-01", "release"));
+    }
+
+    #[test]
+    fn test_ordinary_words_are_not_dates() {
+        assert_eq!(extract("may the force"), None);
+        assert_eq!(extract("monday standup"), None);
+        assert_eq!(extract("the last chapter"), None);
+        assert_eq!(extract("this book"), None);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::application::{dto::Flashcard, repositories::PageRepository};
+use crate::domain::{aggregates::Page, base::Entity, entities::Block, DomainResult};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Tag marking a block as a flashcard
+const CARD_TAG: &str = "card";
+
+/// Use case for collecting the graph's flashcards
+///
+/// A block referencing `card` (as `#card` or `[[card]]`) is a card; its
+/// children are the answer. Property blocks under it, such as the review
+/// state Logseq keeps in `card-next-schedule::`, are left out.
+pub struct GetFlashcards<'a, R: PageRepository> {
+    repository: &'a R,
+}
+
+impl<'a, R: PageRepository> GetFlashcards<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self { repository }
+    }
+
+    /// Every card, pages in title order and each page's cards in outline order
+    pub fn execute(&self) -> DomainResult<Vec<Flashcard>> {
+        let mut pages = self.repository.find_all()?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+
+        let mut cards = Vec::new();
+        for page in &pages {
+            for block in page.root_blocks() {
+                collect_cards(page, block, &mut cards);
+            }
+        }
+        Ok(cards)
+    }
+}
+
+/// Render cards as a tab-separated file for Anki's "Import File"
+///
+/// The header lines tell Anki the columns (front, back, context, tags) and
+/// that fields are HTML. Logseq's `{{cloze text}}` becomes Anki's
+/// `{{c1::text}}`, numbered within each card, so such cards import into a
+/// Cloze note type. Each card is tagged with its page, namespaces (`a/b`)
+/// becoming Anki's nested tags (`a::b`).
+pub fn anki_tsv(cards: &[Flashcard]) -> String {
+    let mut tsv = String::from("#separator:tab\n#html:true\n#columns:Front\tBack\tContext\tTags\n#tags column:4\n");
+    for card in cards {
+        let back: Vec<String> = card
+            .back
+            .iter()
+            .map(|line| {
+                let text = line.trim_start_matches(' ');
+                format!("{}{}", "&nbsp;".repeat(line.len() - text.len()), field(text))
+            })
+            .collect();
+        let context: Vec<&str> =
+            std::iter::once(card.page_title.as_str()).chain(card.hierarchy_path.iter().map(String::as_str)).collect();
+        let tag: String = card
+            .page_title
+            .split('/')
+            .map(|part| part.split_whitespace().collect::<Vec<_>>().join("_"))
+            .collect::<Vec<_>>()
+            .join("::");
+
+        tsv.push_str(&cloze(&field(&card.front)));
+        tsv.push('\t');
+        tsv.push_str(&cloze(&back.join("<br>")));
+        tsv.push('\t');
+        tsv.push_str(&field(&context.join(" > ")));
+        tsv.push('\t');
+        tsv.push_str(&tag.replace('\t', " "));
+        tsv.push('\n');
+    }
+    tsv
+}
+
+fn collect_cards(page: &Page, block: &Block, cards: &mut Vec<Flashcard>) {
+    let is_card = block
+        .page_references()
+        .iter()
+        .any(|reference| reference.title().eq_ignore_ascii_case(CARD_TAG));
+    if is_card {
+        let mut back = Vec::new();
+        for child_id in block.child_ids() {
+            if let Some(child) = page.get_block(child_id) {
+                answer_lines(page, child, 0, &mut back);
+            }
+        }
+        let mut ancestors = page.get_ancestors(block.id());
+        ancestors.reverse();
+        cards.push(Flashcard {
+            page_id: page.id().clone(),
+            page_title: page.title().to_string(),
+            block_id: block.id().clone(),
+            front: without_card_tag(block.content().as_str()),
+            back,
+            hierarchy_path: ancestors.iter().map(|b| b.content().as_str().to_string()).collect(),
+        });
+    }
+
+    // Cards may nest, each answered by its own children
+    for child_id in block.child_ids() {
+        if let Some(child) = page.get_block(child_id) {
+            collect_cards(page, child, cards);
+        }
+    }
+}
+
+fn answer_lines(page: &Page, block: &Block, depth: usize, lines: &mut Vec<String>) {
+    let content = block.content().as_str();
+    if is_property(content) {
+        return;
+    }
+    lines.push(format!("{}{}", "  ".repeat(depth), content));
+    for child_id in block.child_ids() {
+        if let Some(child) = page.get_block(child_id) {
+            answer_lines(page, child, depth + 1, lines);
+        }
+    }
+}
+
+/// Whether a block is a `key:: value` property line
+fn is_property(content: &str) -> bool {
+    content.split_once("::").is_some_and(|(key, _)| {
+        !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+fn without_card_tag(content: &str) -> String {
+    static CARD: OnceLock<Regex> = OnceLock::new();
+    let card = CARD.get_or_init(|| Regex::new(r"(?i)#?\[\[card\]\]|(^|\s)#card\b").expect("valid regex"));
+    let front = card.replace_all(content, "$1");
+    front.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Logseq cloze deletions as Anki's, numbered in order
+fn cloze(text: &str) -> String {
+    static CLOZE: OnceLock<Regex> = OnceLock::new();
+    let pattern = CLOZE.get_or_init(|| Regex::new(r"\{\{cloze\s+(.*?)\}\}").expect("valid regex"));
+    let mut number = 0;
+    pattern
+        .replace_all(text, |captures: &regex::Captures| {
+            number += 1;
+            format!("{{{{c{}::{}}}}}", number, captures[1].trim())
+        })
+        .into_owned()
+}
+
+/// Text as an HTML field on one line of the file
+fn field(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\t', " ")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    #[test]
+    fn test_cards_export_with_answers_context_and_cloze() {
+        let content = "- Ownership\n\
+                       \t- What does a move do? #card\n\
+                       \t\tcard-next-schedule:: 2024-03-01T00:00:00.000Z\n\
+                       \t\t- Transfers ownership\n\
+                       \t\t\t- The old binding <can't> be used\n\
+                       - {{cloze Borrowing}} lends a value [[card]]\n\
+                       - Not a card";
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("rust").unwrap(), "Lang/Rust Notes".into())
+            .unwrap();
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(page).unwrap();
+
+        let cards = GetFlashcards::new(&repo).execute().unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].front, "What does a move do?");
+        assert_eq!(cards[0].back, vec!["Transfers ownership", "  The old binding <can't> be used"]);
+        assert_eq!(cards[0].hierarchy_path, vec!["Ownership"]);
+        assert_eq!(cards[1].front, "{{cloze Borrowing}} lends a value");
+
+        let tsv = anki_tsv(&cards);
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines[2], "#columns:Front\tBack\tContext\tTags");
+        assert_eq!(
+            lines[4],
+            "What does a move do?\tTransfers ownership<br>&nbsp;&nbsp;The old binding &lt;can't&gt; be used\t\
+             Lang/Rust Notes &gt; Ownership\tLang::Rust_Notes"
+        );
+        assert_eq!(lines[5], "{{c1::Borrowing}} lends a value\t\tLang/Rust Notes\tLang::Rust_Notes");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::application::{dto::UrlWithContext, repositories::PageRepository};
+use crate::application::{dto::UrlWithContext, repositories::PageRepository, services::UrlMetadataService};
 use crate::domain::{value_objects::PageId, DomainResult};
 
 /// Use case for getting all links associated with a page
@@ -7,11 +7,30 @@ use crate::domain::{value_objects::PageId, DomainResult};
 /// hierarchical context (path to the block, related page references).
 pub struct GetLinksForPage<'a, R: PageRepository> {
     repository: &'a R,
+    url_metadata: Option<&'a UrlMetadataService>,
+    same_block_refs: bool,
 }
 
 impl<'a, R: PageRepository> GetLinksForPage<'a, R> {
     pub fn new(repository: &'a R) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            url_metadata: None,
+            same_block_refs: false,
+        }
+    }
+
+    /// Include the URLs' stored titles and descriptions
+    pub fn with_url_metadata(mut self, url_metadata: &'a UrlMetadataService) -> Self {
+        self.url_metadata = Some(url_metadata);
+        self
+    }
+
+    /// Include the page references of the block holding each URL, which
+    /// the hierarchical context leaves out
+    pub fn with_same_block_refs(mut self) -> Self {
+        self.same_block_refs = true;
+        self
     }
 
     /// Get all URLs in the page with their context
@@ -52,6 +71,15 @@ impl<'a, R: PageRepository> GetLinksForPage<'a, R> {
                     block_content: block.content().as_str().to_string(),
                     hierarchy_path,
                     related_page_refs,
+                    same_block_page_refs: if self.same_block_refs {
+                        block.page_references().to_vec()
+                    } else {
+                        Vec::new()
+                    },
+                    metadata: match self.url_metadata {
+                        Some(url_metadata) => url_metadata.metadata_of(url.as_str())?,
+                        None => None,
+                    },
                 });
             }
         }
@@ -160,6 +188,40 @@ mod tests {
         assert_eq!(links.len(), 2);
     }
 
+    #[test]
+    fn test_get_links_for_page_with_url_metadata() {
+        use crate::application::repositories::UrlMetadataRepository;
+        use crate::application::services::UrlMetadata;
+        use crate::infrastructure::persistence::SqliteUrlMetadataRepository;
+
+        let mut repo = InMemoryPageRepository::new();
+        let page_id = PageId::new("page-1").unwrap();
+        let mut page = Page::new(page_id.clone(), "Page 1".to_string());
+        let mut block = Block::new_root(BlockId::new("block-1").unwrap(), BlockContent::new("Read later"));
+        block.add_url(Url::new("https://example.com").unwrap());
+        page.add_block(block).unwrap();
+        repo.save(page).unwrap();
+
+        let mut store = SqliteUrlMetadataRepository::open_in_memory().unwrap();
+        let metadata = UrlMetadata {
+            url: "https://example.com".to_string(),
+            title: Some("Example Domain".to_string()),
+            description: None,
+            favicon: None,
+            fetched_at: chrono::Utc::now(),
+        };
+        store.save(&metadata).unwrap();
+        let url_metadata = UrlMetadataService::new(store);
+
+        let links = GetLinksForPage::new(&repo).execute(&page_id).unwrap();
+        assert_eq!(links[0].metadata, None);
+        let links = GetLinksForPage::new(&repo)
+            .with_url_metadata(&url_metadata)
+            .execute(&page_id)
+            .unwrap();
+        assert_eq!(links[0].metadata, Some(metadata));
+    }
+
     #[test]
     fn test_get_links_for_page_with_hierarchy() {
         let mut repo = InMemoryPageRepository::new();
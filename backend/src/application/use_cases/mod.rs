@@ -1,9 +1,48 @@
+pub mod activity_stats;
+pub mod alias_suggestions;
+pub mod backlink_queries;
+pub mod block_paths;
+pub mod connection_queries;
+pub mod clusters;
+pub mod cooccurrence;
+pub mod date_expressions;
+pub mod flashcards;
 pub mod indexing;
 pub mod link_queries;
+pub mod page_deletion;
+pub mod page_diff;
+pub mod page_history;
+pub mod page_names;
+pub mod page_outline;
+pub mod rag_context;
 pub mod search;
+pub mod search_export;
+pub mod site_export;
+pub mod tag_rename;
+pub mod timeline;
+pub mod top_referenced;
 pub mod url_queries;
 
+pub use activity_stats::GetActivityStats;
+pub use alias_suggestions::SuggestAliases;
+pub use backlink_queries::GetBacklinks;
+pub use block_paths::ResolveBlockPath;
+pub use connection_queries::FindConnection;
+pub use clusters::GetClusters;
+pub use cooccurrence::{CooccurrenceScope, GetCooccurrences};
+pub use date_expressions::{extract_date_range, DatedQuery};
+pub use flashcards::{anki_tsv, GetFlashcards};
 pub use indexing::{BatchIndexPages, IndexPage};
 pub use link_queries::GetLinksForPage;
-pub use search::SearchPagesAndBlocks;
+pub use page_deletion::{DeletePages, DeletePagesError, DeletedPages};
+pub use page_diff::{diff_pages, DiffPageWithFile, DiffPages};
+pub use page_history::{GetPageAtVersion, GetPageHistory};
+pub use page_outline::GetPageOutline;
+pub use rag_context::GetRagContext;
+pub use search::{RankingConfig, SearchError, SearchPagesAndBlocks, SearchResultStream};
+pub use search_export::{ExportFormat, ExportSearchResults};
+pub use site_export::{ExportError, ExportSite};
+pub use tag_rename::{RenameTag, RenameTagError, RenamedTag};
+pub use timeline::GetMentionTimeline;
+pub use top_referenced::GetTopReferencedPages;
 pub use url_queries::GetPagesForUrl;
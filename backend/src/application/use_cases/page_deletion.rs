@@ -0,0 +1,224 @@
+use crate::application::{
+    dto::{PageFilter, PageSummary},
+    repositories::PageRepository,
+    services::{EmbeddingService, VisibilityPolicy},
+};
+use crate::application::use_cases::page_names::normalize_tag;
+use crate::domain::{aggregates::Page, events::PageDeleted, value_objects::PageId, DomainError};
+use regex::Regex;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DeletePagesError {
+    #[error("A filter is needed; an empty one would delete every page")]
+    EmptyFilter,
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+}
+
+/// Pages deleted by [`DeletePages`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeletedPages {
+    /// The pages deleted, in title order
+    pub pages: Vec<PageSummary>,
+    /// Pages whose embeddings couldn't be deleted; the next garbage
+    /// collection removes them
+    pub embedding_errors: usize,
+}
+
+impl DeletedPages {
+    /// The event of each deletion, for caches and webhooks
+    pub fn events(&self) -> Vec<PageDeleted> {
+        self.pages
+            .iter()
+            .map(|page| PageDeleted {
+                page_id: page.page_id.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Use case for deleting every page a [`PageFilter`] matches, such as junk
+/// left by an import
+///
+/// [`dry_run`](Self::dry_run) lists the pages without touching them. The
+/// deletion itself is one [`PageRepository::delete_all`] call, followed by
+/// the pages' embeddings when an embedding service is given.
+pub struct DeletePages<'a, R: PageRepository> {
+    repository: &'a mut R,
+    filter: PageFilter,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    visibility: Option<VisibilityPolicy>,
+}
+
+impl<'a, R: PageRepository> DeletePages<'a, R> {
+    pub fn new(repository: &'a mut R, filter: PageFilter) -> Self {
+        Self {
+            repository,
+            filter,
+            embedding_service: None,
+            visibility: None,
+        }
+    }
+
+    /// Delete the pages' chunk and page embeddings too
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Match only pages the policy shows, as when deleting through the API
+    pub fn with_visibility(mut self, visibility: VisibilityPolicy) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// The pages that would be deleted, in title order
+    pub fn dry_run(&self) -> Result<Vec<PageSummary>, DeletePagesError> {
+        let matcher = Matcher::new(&self.filter)?;
+        let mut pages: Vec<PageSummary> = self
+            .repository
+            .find_all()?
+            .iter()
+            .filter(|page| matcher.matches(page))
+            .filter(|page| self.visibility.is_none_or(|policy| policy.is_page_visible(page)))
+            .map(PageSummary::from)
+            .collect();
+        pages.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(pages)
+    }
+
+    /// Delete the matching pages
+    pub async fn execute(&mut self) -> Result<DeletedPages, DeletePagesError> {
+        let pages = self.dry_run()?;
+        let ids: Vec<PageId> = pages.iter().map(|page| page.page_id.clone()).collect();
+        self.repository.delete_all(&ids)?;
+        tracing::info!("Deleted {} pages", ids.len());
+
+        let mut embedding_errors = 0;
+        if let Some(embedding_service) = &self.embedding_service {
+            for id in &ids {
+                if let Err(e) = embedding_service.delete_page_embeddings(id).await {
+                    tracing::warn!("Failed to delete the embeddings of page {}: {}", id, e);
+                    embedding_errors += 1;
+                }
+            }
+        }
+        Ok(DeletedPages { pages, embedding_errors })
+    }
+}
+
+/// A [`PageFilter`] ready to test pages against
+struct Matcher {
+    /// Title prefix of the namespace's pages, lowercased
+    namespace: Option<String>,
+    tag: Option<String>,
+    title: Option<Regex>,
+}
+
+impl Matcher {
+    fn new(filter: &PageFilter) -> Result<Self, DeletePagesError> {
+        if filter.is_empty() {
+            return Err(DeletePagesError::EmptyFilter);
+        }
+        let title = filter.title_pattern.as_deref().map(|pattern| {
+            let mut regex = String::from("(?i)^");
+            for c in pattern.chars() {
+                match c {
+                    '*' => regex.push_str(".*"),
+                    '?' => regex.push('.'),
+                    c => regex.push_str(&regex::escape(&c.to_string())),
+                }
+            }
+            regex.push('$');
+            Regex::new(&regex).expect("escaped pattern is a valid regex")
+        });
+        Ok(Matcher {
+            namespace: filter
+                .namespace
+                .as_deref()
+                .map(|namespace| format!("{}/", namespace.trim_end_matches('/').to_lowercase())),
+            tag: filter.tag.as_deref().map(normalize_tag),
+            title,
+        })
+    }
+
+    fn matches(&self, page: &Page) -> bool {
+        let in_namespace = self
+            .namespace
+            .as_ref()
+            .is_none_or(|prefix| page.title().to_lowercase().starts_with(prefix.as_str()));
+        let tagged = self.tag.as_ref().is_none_or(|tag| {
+            page.properties()
+                .get("tags")
+                .is_some_and(|tags| tags.split(',').any(|listed| normalize_tag(listed) == *tag))
+        });
+        let titled = self.title.as_ref().is_none_or(|title| title.is_match(page.title()));
+        in_namespace && tagged && titled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn repository() -> InMemoryPageRepository {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, tags) in [
+            ("a", "imports/2023/Notes", None),
+            ("b", "imports/Scratch", Some("[[Junk]], drafts")),
+            ("c", "Imported ideas", Some("#junk")),
+            ("d", "Rust", Some("language")),
+        ] {
+            let mut page = LogseqMarkdownParser::parse_content("- text", PageId::new(id).unwrap(), title.into()).unwrap();
+            if let Some(tags) = tags {
+                page.set_property("tags", tags);
+            }
+            repo.save(page).unwrap();
+        }
+        repo
+    }
+
+    fn titles(pages: &[PageSummary]) -> Vec<&str> {
+        pages.iter().map(|page| page.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filters_match_namespace_tag_and_title_pattern() {
+        let mut repo = repository();
+        let dry_run = |repo: &mut InMemoryPageRepository, filter| DeletePages::new(repo, filter).dry_run().unwrap();
+
+        let pages = dry_run(&mut repo, PageFilter::new().with_namespace("Imports"));
+        assert_eq!(titles(&pages), ["imports/2023/Notes", "imports/Scratch"]);
+        let pages = dry_run(&mut repo, PageFilter::new().with_tag("junk"));
+        assert_eq!(titles(&pages), ["Imported ideas", "imports/Scratch"]);
+        let pages = dry_run(&mut repo, PageFilter::new().with_title_pattern("import?d *"));
+        assert_eq!(titles(&pages), ["Imported ideas"]);
+        let pages = dry_run(&mut repo, PageFilter::new().with_namespace("imports").with_tag("junk"));
+        assert_eq!(titles(&pages), ["imports/Scratch"]);
+
+        let empty = DeletePages::new(&mut repo, PageFilter::new()).dry_run();
+        assert!(matches!(empty, Err(DeletePagesError::EmptyFilter)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_deletes_what_the_dry_run_lists() {
+        let mut repo = repository();
+        let mut delete = DeletePages::new(&mut repo, PageFilter::new().with_title_pattern("*import*"));
+        let preview = delete.dry_run().unwrap();
+        assert_eq!(preview.len(), 3);
+
+        let deleted = delete.execute().await.unwrap();
+        assert_eq!(deleted.pages, preview);
+        assert_eq!(deleted.embedding_errors, 0);
+        assert_eq!(deleted.events()[0].page_id.as_str(), "c");
+
+        let remaining = repo.find_all().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].title(), "Rust");
+    }
+}
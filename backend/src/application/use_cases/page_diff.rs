@@ -0,0 +1,319 @@
+use crate::application::{
+    dto::{BlockChange, DiffBlock, PageDiff},
+    repositories::{PageHistoryRepository, PageRepository},
+};
+use crate::domain::{
+    aggregates::Page,
+    base::RepositoryError,
+    entities::Block,
+    value_objects::PageId,
+    DomainError, DomainResult,
+};
+use crate::infrastructure::parsers::LogseqMarkdownParser;
+use std::collections::HashSet;
+
+/// Least word overlap (Dice coefficient) for an edited block to count as
+/// modified rather than removed and added
+const MODIFIED_SIMILARITY: f64 = 0.5;
+
+/// Use case for comparing two recorded versions of a page
+pub struct DiffPages<'a, H: PageHistoryRepository> {
+    history: &'a H,
+}
+
+impl<'a, H: PageHistoryRepository> DiffPages<'a, H> {
+    pub fn new(history: &'a H) -> Self {
+        Self { history }
+    }
+
+    /// What changed from `version_a` to `version_b`
+    pub fn execute(&self, page_id: &PageId, version_a: u32, version_b: u32) -> DomainResult<PageDiff> {
+        let page_at = |version: u32| {
+            self.history
+                .version(page_id, version)?
+                .ok_or_else(|| DomainError::NotFound(format!("Version {} of page {} not found", version, page_id)))?
+                .snapshot
+                .to_page()
+        };
+        Ok(diff_pages(&page_at(version_a)?, &page_at(version_b)?))
+    }
+}
+
+/// Use case for comparing a stored page with its file on disk
+///
+/// Shows what syncing the file would change, e.g. for resolving a conflict
+/// between an edit made through the API and one made in Logseq.
+pub struct DiffPageWithFile<'a, R: PageRepository> {
+    repository: &'a R,
+}
+
+impl<'a, R: PageRepository> DiffPageWithFile<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self { repository }
+    }
+
+    /// What the file changes compared to the stored page
+    pub async fn execute(&self, page_id: &PageId) -> DomainResult<PageDiff> {
+        let page = self
+            .repository
+            .find_by_id(page_id)?
+            .ok_or_else(|| DomainError::NotFound(format!("Page with id {:?} not found", page_id)))?;
+        let file_path = page
+            .file_path()
+            .ok_or_else(|| DomainError::InvalidOperation(format!("Page {} has no file", page_id)))?;
+
+        let file = LogseqMarkdownParser::parse_file(file_path).await.map_err(|e| RepositoryError::Storage {
+            backend: "file system",
+            source: Box::new(e),
+        })?;
+        Ok(diff_pages(&page, &file))
+    }
+}
+
+/// A block with the contents of its ancestors, in document order
+struct Entry<'p> {
+    block: &'p Block,
+    ancestors: Vec<String>,
+}
+
+impl Entry<'_> {
+    fn content(&self) -> &str {
+        self.block.content().as_str()
+    }
+
+    fn to_diff_block(&self) -> DiffBlock {
+        let mut hierarchy_path = self.ancestors.clone();
+        hierarchy_path.push(self.content().to_string());
+        DiffBlock {
+            block_id: self.block.id().clone(),
+            content: self.content().to_string(),
+            hierarchy_path,
+        }
+    }
+}
+
+fn entries(page: &Page) -> Vec<Entry<'_>> {
+    fn visit<'p>(page: &'p Page, block: &'p Block, ancestors: &mut Vec<String>, entries: &mut Vec<Entry<'p>>) {
+        entries.push(Entry {
+            block,
+            ancestors: ancestors.clone(),
+        });
+        ancestors.push(block.content().as_str().to_string());
+        for child_id in block.child_ids() {
+            if let Some(child) = page.get_block(child_id) {
+                visit(page, child, ancestors, entries);
+            }
+        }
+        ancestors.pop();
+    }
+
+    let mut entries = Vec::new();
+    for block in page.root_blocks() {
+        visit(page, block, &mut Vec::new(), &mut entries);
+    }
+    entries
+}
+
+/// Block-level differences from `before` to `after`
+///
+/// Block IDs change every time a page is parsed, so blocks are matched by
+/// content: the longest common sequence of contents stays in place, the same
+/// content found elsewhere has moved, and an edited block is paired with the
+/// most similar block between the same unchanged neighbours.
+pub fn diff_pages(before: &Page, after: &Page) -> PageDiff {
+    let old = entries(before);
+    let new = entries(after);
+    let anchors = common_sequence(&old, &new);
+
+    // Where each block of the older page went in the newer one
+    let mut old_to_new: Vec<Option<usize>> = vec![None; old.len()];
+    let mut new_matched = vec![false; new.len()];
+    // Changes with their position in the newer page, removed blocks sorting first
+    let mut changes: Vec<((usize, bool, usize), BlockChange)> = Vec::new();
+    let mut unchanged = 0;
+
+    for &(i, j) in &anchors {
+        old_to_new[i] = Some(j);
+        new_matched[j] = true;
+        if old[i].ancestors == new[j].ancestors {
+            unchanged += 1;
+        } else {
+            let change = BlockChange::Moved {
+                before: old[i].to_diff_block(),
+                after: new[j].to_diff_block(),
+            };
+            changes.push(((j, true, 0), change));
+        }
+    }
+
+    for j in 0..new.len() {
+        if new_matched[j] {
+            continue;
+        }
+        let same = (0..old.len()).find(|&i| old_to_new[i].is_none() && old[i].content() == new[j].content());
+        if let Some(i) = same {
+            old_to_new[i] = Some(j);
+            new_matched[j] = true;
+            let change = BlockChange::Moved {
+                before: old[i].to_diff_block(),
+                after: new[j].to_diff_block(),
+            };
+            changes.push(((j, true, 0), change));
+        }
+    }
+
+    for j in 0..new.len() {
+        if new_matched[j] {
+            continue;
+        }
+        // Only blocks between the same unchanged neighbours are candidates
+        let gap = anchors.partition_point(|&(_, anchor)| anchor < j);
+        let from = if gap == 0 { 0 } else { anchors[gap - 1].0 + 1 };
+        let to = anchors.get(gap).map_or(old.len(), |&(anchor, _)| anchor);
+        let best = (from..to)
+            .filter(|&i| old_to_new[i].is_none())
+            .map(|i| (i, similarity(old[i].content(), new[j].content())))
+            .filter(|&(_, score)| score >= MODIFIED_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, _)) = best {
+            old_to_new[i] = Some(j);
+            new_matched[j] = true;
+            let change = BlockChange::Modified {
+                before: old[i].to_diff_block(),
+                after: new[j].to_diff_block(),
+            };
+            changes.push(((j, true, 0), change));
+        }
+    }
+
+    for (j, entry) in new.iter().enumerate().filter(|(j, _)| !new_matched[*j]) {
+        changes.push(((j, true, 0), BlockChange::Added { block: entry.to_diff_block() }));
+    }
+    for (i, entry) in old.iter().enumerate().filter(|(i, _)| old_to_new[*i].is_none()) {
+        // Where the block would be in the newer page: after the block that preceded it
+        let position = old_to_new[..i].iter().rev().find_map(|j| *j).map_or(0, |j| j + 1);
+        changes.push(((position, false, i), BlockChange::Removed { block: entry.to_diff_block() }));
+    }
+
+    changes.sort_by_key(|(key, _)| *key);
+    PageDiff {
+        changes: changes.into_iter().map(|(_, change)| change).collect(),
+        unchanged,
+    }
+}
+
+/// Index pairs of the longest common subsequence of block contents
+///
+/// Equal leading and trailing blocks are matched first, so the quadratic
+/// table only covers the part of the page that changed.
+fn common_sequence(old: &[Entry], new: &[Entry]) -> Vec<(usize, usize)> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a.content() == b.content())
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.content() == b.content())
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j]: common subsequence length of old_middle[i..] and new_middle[j..]
+    let (n, m) = (old_middle.len(), new_middle.len());
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old_middle[i].content() == new_middle[j].content() {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_middle[i].content() == new_middle[j].content() {
+            pairs.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    pairs
+}
+
+/// Dice coefficient of the two texts' lowercased words
+fn similarity(a: &str, b: &str) -> f64 {
+    let words = |text: &str| -> HashSet<String> { text.split_whitespace().map(str::to_lowercase).collect() };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::PageSnapshot;
+    use crate::infrastructure::persistence::SqlitePageHistoryRepository;
+
+    fn page(content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new("rust").unwrap(), "Rust".to_string()).unwrap()
+    }
+
+    fn describe(change: &BlockChange) -> String {
+        match change {
+            BlockChange::Added { block } => format!("+ {}", block.content),
+            BlockChange::Removed { block } => format!("- {}", block.content),
+            BlockChange::Modified { before, after } => format!("~ {} -> {}", before.content, after.content),
+            BlockChange::Moved { before, after } => {
+                format!("> {} under {:?} -> {:?}", after.content, before.hierarchy_path, after.hierarchy_path)
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_finds_added_removed_modified_and_moved_blocks() {
+        let before = page("- Ownership\n\t- Borrowing rules\n- Traits\n- Macros\n- Unsafe");
+        let after = page("- Ownership\n- Traits\n\t- Borrowing rules\n- Declarative macros\n- Async");
+
+        let diff = diff_pages(&before, &after);
+        let changes: Vec<String> = diff.changes.iter().map(describe).collect();
+        assert_eq!(
+            changes,
+            vec![
+                r#"> Borrowing rules under ["Ownership", "Borrowing rules"] -> ["Traits", "Borrowing rules"]"#,
+                "~ Macros -> Declarative macros",
+                "- Unsafe",
+                "+ Async",
+            ]
+        );
+        assert_eq!(diff.unchanged, 2);
+        assert!(diff_pages(&before, &page("- Ownership\n\t- Borrowing rules\n- Traits\n- Macros\n- Unsafe")).is_empty());
+    }
+
+    #[test]
+    fn test_diff_between_recorded_versions() {
+        let mut history = SqlitePageHistoryRepository::open_in_memory().unwrap();
+        history.record(&PageSnapshot::from(&page("- Ownership"))).unwrap();
+        history.record(&PageSnapshot::from(&page("- Ownership\n- Lifetimes"))).unwrap();
+
+        let page_id = PageId::new("rust").unwrap();
+        let diff = DiffPages::new(&history).execute(&page_id, 1, 2).unwrap();
+        assert_eq!(diff.changes.iter().map(describe).collect::<Vec<_>>(), vec!["+ Lifetimes"]);
+        assert!(matches!(
+            DiffPages::new(&history).execute(&page_id, 1, 3),
+            Err(DomainError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::application::{dto::PageVersion, repositories::PageHistoryRepository};
+use crate::domain::{aggregates::Page, value_objects::PageId, DomainError, DomainResult};
+
+/// Use case for listing the recorded versions of a page
+///
+/// Versions are recorded by [`VersionedPageRepository`](crate::application::services::VersionedPageRepository)
+/// as pages are saved; a page deleted since still has its history.
+pub struct GetPageHistory<'a, H: PageHistoryRepository> {
+    history: &'a H,
+}
+
+impl<'a, H: PageHistoryRepository> GetPageHistory<'a, H> {
+    pub fn new(history: &'a H) -> Self {
+        Self { history }
+    }
+
+    /// Every version of the page, newest first
+    pub fn execute(&self, page_id: &PageId) -> DomainResult<Vec<PageVersion>> {
+        let mut versions = self.history.versions(page_id)?;
+        versions.reverse();
+        Ok(versions)
+    }
+}
+
+/// Use case for recovering a page as it was at one of its versions
+pub struct GetPageAtVersion<'a, H: PageHistoryRepository> {
+    history: &'a H,
+}
+
+impl<'a, H: PageHistoryRepository> GetPageAtVersion<'a, H> {
+    pub fn new(history: &'a H) -> Self {
+        Self { history }
+    }
+
+    /// The page at that version, with new block IDs; saving it restores it
+    pub fn execute(&self, page_id: &PageId, version: u32) -> DomainResult<Page> {
+        self.history
+            .version(page_id, version)?
+            .ok_or_else(|| DomainError::NotFound(format!("Version {} of page {} not found", version, page_id)))?
+            .snapshot
+            .to_page()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::repositories::PageRepository;
+    use crate::application::services::VersionedPageRepository;
+    use crate::domain::base::Entity;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::{InMemoryPageRepository, SqlitePageHistoryRepository};
+
+    fn page(content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new("rust").unwrap(), "Rust".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_overwritten_content_can_be_recovered() {
+        let history = SqlitePageHistoryRepository::open_in_memory().unwrap();
+        let mut repository = VersionedPageRepository::new(InMemoryPageRepository::new(), history);
+        let page_id = PageId::new("rust").unwrap();
+
+        let mut first = page("- Ownership\n\t- Borrowing");
+        first.set_property("tags", "language");
+        repository.save(first).unwrap();
+        repository.save(page("- Lifetimes")).unwrap();
+        repository.save(page("- Lifetimes")).unwrap();
+        repository.delete(&page_id).unwrap();
+
+        let versions = GetPageHistory::new(repository.history()).execute(&page_id).unwrap();
+        let numbers: Vec<u32> = versions.iter().map(|version| version.version).collect();
+        assert_eq!(numbers, vec![2, 1]);
+
+        let recovered = GetPageAtVersion::new(repository.history()).execute(&page_id, 1).unwrap();
+        assert_eq!(recovered.id(), &page_id);
+        assert_eq!(recovered.all_blocks().count(), 2);
+        assert_eq!(recovered.properties().get("tags").map(String::as_str), Some("language"));
+
+        let missing = GetPageAtVersion::new(repository.history()).execute(&page_id, 3);
+        assert!(matches!(missing, Err(DomainError::NotFound(_))));
+    }
+}
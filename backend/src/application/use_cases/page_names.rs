@@ -0,0 +1,52 @@
+/// Reading tags and journal dates out of pages' names, shared by the use cases
+use crate::domain::aggregates::Page;
+use chrono::NaiveDate;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A tag as written in `tags::` or `alias::` (`#tag`, `[[tag]]` or plain),
+/// lowercased
+pub(crate) fn normalize_tag(tag: &str) -> String {
+    let tag = tag.trim().trim_start_matches('#');
+    let tag = tag.strip_prefix("[[").and_then(|tag| tag.strip_suffix("]]")).unwrap_or(tag);
+    tag.trim().to_lowercase()
+}
+
+/// The date of a journal page, from its title or file name
+pub(crate) fn journal_date(page: &Page) -> Option<NaiveDate> {
+    let stem = page
+        .file_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned());
+    std::iter::once(page.title().to_string())
+        .chain(stem)
+        .find_map(|name| parse_journal_title(&name))
+}
+
+/// Parse the date formats Logseq names journals with
+fn parse_journal_title(title: &str) -> Option<NaiveDate> {
+    static ORDINAL: OnceLock<Regex> = OnceLock::new();
+    let ordinal = ORDINAL.get_or_init(|| Regex::new(r"(\d)(st|nd|rd|th)\b").expect("valid regex"));
+    let title = ordinal.replace_all(title.trim(), "$1");
+
+    ["%Y_%m_%d", "%Y-%m-%d", "%Y/%m/%d", "%Y%m%d", "%b %d, %Y", "%B %d, %Y", "%d %b %Y", "%d %B %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&title, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parses_journal_titles() {
+        for title in ["2024_01_15", "2024-01-15", "Jan 15th, 2024", "January 15, 2024", "15 Jan 2024"] {
+            assert_eq!(parse_journal_title(title), Some(date("2024-01-15")), "{}", title);
+        }
+        assert_eq!(parse_journal_title("Rust"), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::application::{
+    dto::{OutlineBlock, PageOutline},
+    repositories::PageRepository,
+};
+use crate::domain::{
+    aggregates::Page,
+    base::Entity,
+    value_objects::{BlockId, PageId},
+    DomainError, DomainResult,
+};
+
+/// Use case for getting a page's block tree as an outline to render
+///
+/// Blocks carry only their ID, content and children, none of the URLs,
+/// references or related data of other queries. With a depth limit, blocks
+/// below it are left out and counted on their parent instead.
+pub struct GetPageOutline<'a, R: PageRepository> {
+    repository: &'a R,
+    max_depth: Option<usize>,
+}
+
+impl<'a, R: PageRepository> GetPageOutline<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            max_depth: None,
+        }
+    }
+
+    /// Include `depth` levels of blocks: 1 for only the root blocks
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    pub fn execute(&self, page_id: &PageId) -> DomainResult<PageOutline> {
+        let page = self
+            .repository
+            .find_by_id(page_id)?
+            .ok_or_else(|| DomainError::NotFound(format!("Page with id {:?} not found", page_id)))?;
+
+        let root_ids: Vec<BlockId> = page.root_blocks().iter().map(|block| block.id().clone()).collect();
+        let blocks = match self.max_depth {
+            Some(0) => Vec::new(),
+            _ => self.outline(&page, &root_ids, 1),
+        };
+        Ok(PageOutline {
+            page_id: page.id().clone(),
+            title: page.title().to_string(),
+            blocks,
+        })
+    }
+
+    fn outline(&self, page: &Page, ids: &[BlockId], depth: usize) -> Vec<OutlineBlock> {
+        let expand = self.max_depth.is_none_or(|max_depth| depth < max_depth);
+        ids.iter()
+            .filter_map(|id| page.get_block(id))
+            .map(|block| {
+                let (children, hidden_children) = if expand {
+                    (self.outline(page, block.child_ids(), depth + 1), 0)
+                } else {
+                    (Vec::new(), block.child_ids().len())
+                };
+                OutlineBlock {
+                    block_id: block.id().clone(),
+                    content: block.content().as_str().to_string(),
+                    children,
+                    hidden_children,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    #[test]
+    fn test_outline_keeps_order_and_limits_depth() {
+        let mut repo = InMemoryPageRepository::new();
+        let content = "- Ownership\n\t- Moves\n\t\t- Copy types\n\t- Borrowing\n- Lifetimes";
+        let page_id = PageId::new("rust").unwrap();
+        repo.save(LogseqMarkdownParser::parse_content(content, page_id.clone(), "Rust".into()).unwrap())
+            .unwrap();
+
+        let outline = GetPageOutline::new(&repo).execute(&page_id).unwrap();
+        assert_eq!(outline.title, "Rust");
+        let contents: Vec<&str> = outline.blocks.iter().map(|block| block.content.as_str()).collect();
+        assert_eq!(contents, ["Ownership", "Lifetimes"]);
+        assert_eq!(outline.blocks[0].children[0].children[0].content, "Copy types");
+        assert_eq!(outline.blocks[0].children[1].content, "Borrowing");
+
+        let shallow = GetPageOutline::new(&repo).with_max_depth(2).execute(&page_id).unwrap();
+        let moves = &shallow.blocks[0].children[0];
+        assert!(moves.children.is_empty());
+        assert_eq!(moves.hidden_children, 1);
+        assert_eq!(shallow.blocks[0].hidden_children, 0);
+
+        let missing = GetPageOutline::new(&repo).execute(&PageId::new("go").unwrap());
+        assert!(matches!(missing, Err(DomainError::NotFound(_))));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::application::{
+    dto::{ContextPassage, ResultType, SearchItem, SearchRequest, SearchType},
+    repositories::PageRepository,
+    services::EmbeddingService,
+    use_cases::{SearchError, SearchPagesAndBlocks},
+};
+use crate::domain::{aggregates::Page, base::Entity, value_objects::BlockId};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Use case for gathering graph context to answer a question with
+///
+/// Finds the blocks that best match a query (semantically when an embedding
+/// service is configured) and returns each with its ancestors as breadcrumbs
+/// and its descendants as an outline, so the passage reads on its own. A
+/// block already included under an earlier passage isn't repeated.
+pub struct GetRagContext<'a, R: PageRepository> {
+    repository: &'a R,
+    embedding_service: Option<Arc<EmbeddingService>>,
+}
+
+impl<'a, R: PageRepository> GetRagContext<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            embedding_service: None,
+        }
+    }
+
+    /// Create with semantic search support
+    pub fn with_embedding_service(
+        repository: &'a R,
+        embedding_service: Arc<EmbeddingService>,
+    ) -> Self {
+        Self {
+            repository,
+            embedding_service: Some(embedding_service),
+        }
+    }
+
+    /// Get up to `limit` passages for a query, best match first
+    pub async fn execute(&self, query: &str, limit: usize) -> Result<Vec<ContextPassage>, SearchError> {
+        let request = SearchRequest::new(query).with_result_type(ResultType::BlocksOnly);
+        let results = match &self.embedding_service {
+            Some(embedding_service) => {
+                SearchPagesAndBlocks::with_embedding_service(self.repository, embedding_service.clone())
+                    .execute(request.with_search_type(SearchType::Semantic))
+                    .await?
+            }
+            None => SearchPagesAndBlocks::new(self.repository).execute(request).await?,
+        };
+
+        let mut pages: HashMap<String, Option<Page>> = HashMap::new();
+        let mut covered: HashSet<BlockId> = HashSet::new();
+        let mut passages = Vec::new();
+
+        for result in results {
+            if passages.len() >= limit {
+                break;
+            }
+            let SearchItem::Block(block_result) = result.item else {
+                continue;
+            };
+            if covered.contains(&block_result.block_id) {
+                continue;
+            }
+
+            let page_key = block_result.page_id.as_str().to_string();
+            if !pages.contains_key(&page_key) {
+                let page = self.repository.find_by_id(&block_result.page_id)?;
+                pages.insert(page_key.clone(), page);
+            }
+            // Pages removed since they were embedded have nothing to show
+            let Some(page) = pages[&page_key].as_ref() else {
+                continue;
+            };
+            let Some(block) = page.get_block(&block_result.block_id) else {
+                continue;
+            };
+
+            let mut breadcrumbs: Vec<String> = page
+                .get_ancestors(block.id())
+                .iter()
+                .map(|ancestor| ancestor.content().as_str().to_string())
+                .collect();
+            breadcrumbs.reverse();
+
+            let mut content = format!("- {}\n", block.content().as_str());
+            let base_level = block.indent_level().value();
+            for descendant in page.get_descendants(block.id()) {
+                let depth = descendant.indent_level().value().saturating_sub(base_level);
+                content.push_str(&"\t".repeat(depth));
+                content.push_str(&format!("- {}\n", descendant.content().as_str()));
+                covered.insert(descendant.id().clone());
+            }
+            covered.insert(block.id().clone());
+
+            passages.push(ContextPassage {
+                page_id: page.id().clone(),
+                page_title: page.title().to_string(),
+                block_id: block.id().clone(),
+                breadcrumbs,
+                content,
+                score: result.score,
+            });
+        }
+
+        Ok(passages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn page(id: &str, title: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_passages_carry_breadcrumbs_and_children() -> Result<(), SearchError> {
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(page(
+            "rust",
+            "Rust",
+            "- Memory\n\t- Ownership moves values\n\t\t- Borrowing lends ownership\n- Unrelated",
+        ))?;
+
+        let passages = GetRagContext::new(&repo).execute("ownership", 5).await?;
+
+        // The nested match sits inside the first passage, so it isn't repeated
+        assert_eq!(passages.len(), 1);
+        let passage = &passages[0];
+        assert_eq!(passage.page_title, "Rust");
+        assert_eq!(passage.breadcrumbs, vec!["Memory"]);
+        assert_eq!(passage.content, "- Ownership moves values\n\t- Borrowing lends ownership\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_limit_caps_passages() -> Result<(), SearchError> {
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(page("a", "A", "- rust one\n- rust two\n- rust three"))?;
+
+        let passages = GetRagContext::new(&repo).execute("rust", 2).await?;
+        assert_eq!(passages.len(), 2);
+        Ok(())
+    }
+}
@@ -1,13 +1,148 @@
 use crate::application::{
     dto::{
-        BlockResult, PageResult, ResultType, SearchItem, SearchRequest, SearchResult,
-        SearchType, UrlResult,
+        pages::last_updated, AdjustmentKind, BlockResult, MatchKind, MatchedField, PageResult, ResultType,
+        ScoreAdjustment, ScoreExplanation, SearchItem, SearchRequest, SearchResult, SearchType, UrlResult,
     },
     repositories::PageRepository,
-    services::EmbeddingService,
+    services::{EmbeddingError, EmbeddingService, JournalTemplates, SearchCache, UrlMetadataService, WebArchiver},
+    use_cases::{date_expressions::extract_date_range, page_names::journal_date},
 };
-use crate::domain::{aggregates::Page, base::Entity, value_objects::PageId, DomainResult};
+use crate::domain::{
+    aggregates::Page,
+    base::Entity,
+    value_objects::{BlockId, GraphId, PageId, SimilarityScore},
+    DomainError, DomainResult,
+};
+use crate::infrastructure::text::KeywordTokenizer;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::pin::Pin;
 use std::sync::Arc;
+use thiserror::Error;
+use tokio_stream::Stream;
+
+#[derive(Error, Debug)]
+pub enum SearchError {
+    /// Semantic search was asked of a graph the embedding service doesn't index
+    #[error("No semantic index configured for graph '{0}'")]
+    GraphNotIndexed(GraphId),
+
+    #[error("Semantic search failed: {0}")]
+    Embedding(#[from] EmbeddingError),
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+}
+
+/// How keyword matches are scored and ordered
+///
+/// A title or block scores by where the query is found in it (`exact`,
+/// `prefix`, `contains`, else `all_terms` when it holds every word of the
+/// query), and page titles are weighted by `title_weight`. The boosts then
+/// weigh every result by its page: `recency_boost` is the share of a score
+/// that depends on how recently the page was updated (its latest
+/// `updated-at` property), `page_centrality_boost` the share that depends on
+/// how many blocks of other pages reference it. Both are off by default.
+/// Blocks a journal template repeats on every day (see [`JournalTemplates`])
+/// are weighted by `journal_template_weight`, which leaves them out at 0.0.
+///
+/// Scores are capped at 1.0, so a title weight above 1.0 only lifts title
+/// matches that score below it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RankingConfig {
+    /// Multiplier of page title matches, relative to block matches
+    pub title_weight: f32,
+    /// Score of text that is the query
+    pub exact: f32,
+    /// Score of text starting with the query
+    pub prefix: f32,
+    /// Score of text containing the query
+    pub contains: f32,
+    /// Score of text holding every word of a multi-word query, but not the
+    /// query as typed
+    pub all_terms: f32,
+    /// Share (0.0-1.0) of a score that depends on the page's recency
+    pub recency_boost: f32,
+    /// Days after which a page counts half as recent
+    pub recency_half_life_days: f32,
+    /// Share (0.0-1.0) of a score that depends on the page's references
+    pub page_centrality_boost: f32,
+    /// Multiplier (0.0-1.0) of blocks repeated by a journal template
+    pub journal_template_weight: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        RankingConfig {
+            title_weight: 1.0,
+            exact: 1.0,
+            prefix: 0.9,
+            contains: 0.7,
+            all_terms: 0.6,
+            recency_boost: 0.0,
+            recency_half_life_days: 30.0,
+            page_centrality_boost: 0.0,
+            journal_template_weight: 1.0,
+        }
+    }
+}
+
+impl RankingConfig {
+    /// Check that scores and boosts are within 0.0-1.0 and the title weight
+    /// and half-life are positive
+    pub fn validate(&self) -> DomainResult<()> {
+        let shares = [
+            ("exact", self.exact),
+            ("prefix", self.prefix),
+            ("contains", self.contains),
+            ("all_terms", self.all_terms),
+            ("recency_boost", self.recency_boost),
+            ("page_centrality_boost", self.page_centrality_boost),
+            ("journal_template_weight", self.journal_template_weight),
+        ];
+        for (name, value) in shares {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(DomainError::InvalidValue(format!(
+                    "Ranking {} must be between 0.0 and 1.0, got {}",
+                    name, value
+                )));
+            }
+        }
+        for (name, value) in [("title_weight", self.title_weight), ("recency_half_life_days", self.recency_half_life_days)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(DomainError::InvalidValue(format!("Ranking {} must be positive, got {}", name, value)));
+            }
+        }
+        Ok(())
+    }
+
+    fn has_page_boosts(&self) -> bool {
+        self.recency_boost > 0.0 || self.page_centrality_boost > 0.0
+    }
+
+    /// A page boost taking `share` of a score, for a page whose recency or
+    /// centrality is `signal` (0.0-1.0)
+    fn boost(kind: AdjustmentKind, share: f32, signal: f32) -> ScoreAdjustment {
+        ScoreAdjustment {
+            kind,
+            signal: Some(signal),
+            factor: 1.0 - share * (1.0 - signal),
+        }
+    }
+
+    /// 1.0 for a page updated now, halving every half-life
+    fn recency(&self, updated_at: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+        let days = (now - updated_at).num_seconds().max(0) as f32 / 86_400.0;
+        0.5f32.powf(days / self.recency_half_life_days)
+    }
+}
+
+/// Search results yielded as they are found, see
+/// [`SearchPagesAndBlocks::execute_stream`]
+pub type SearchResultStream<'a> = Pin<Box<dyn Stream<Item = Result<SearchResult, SearchError>> + Send + 'a>>;
 
 /// Use case for searching pages and blocks
 ///
@@ -16,6 +151,12 @@ use std::sync::Arc;
 pub struct SearchPagesAndBlocks<'a, R: PageRepository> {
     repository: &'a R,
     embedding_service: Option<Arc<EmbeddingService>>,
+    url_metadata: Option<&'a UrlMetadataService>,
+    web_archiver: Option<&'a WebArchiver>,
+    cache: Option<&'a SearchCache>,
+    tokenizer: KeywordTokenizer,
+    ranking: RankingConfig,
+    today: Option<NaiveDate>,
 }
 
 impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
@@ -23,6 +164,12 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
         Self {
             repository,
             embedding_service: None,
+            url_metadata: None,
+            web_archiver: None,
+            cache: None,
+            tokenizer: KeywordTokenizer::new(),
+            ranking: RankingConfig::default(),
+            today: None,
         }
     }
 
@@ -34,32 +181,203 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
         Self {
             repository,
             embedding_service: Some(embedding_service),
+            url_metadata: None,
+            web_archiver: None,
+            cache: None,
+            tokenizer: KeywordTokenizer::new(),
+            ranking: RankingConfig::default(),
+            today: None,
         }
     }
 
+    /// Include the stored titles and descriptions of URLs found
+    pub fn with_url_metadata(mut self, url_metadata: &'a UrlMetadataService) -> Self {
+        self.url_metadata = Some(url_metadata);
+        self
+    }
+
+    /// Also search archived web pages when all result types are requested
+    pub fn with_web_archiver(mut self, web_archiver: &'a WebArchiver) -> Self {
+        self.web_archiver = Some(web_archiver);
+        self
+    }
+
+    /// Answer repeated searches from a cache until the index changes
+    ///
+    /// URL metadata and archived pages found later don't show up in cached
+    /// results until the cache's generation moves on.
+    pub fn with_cache(mut self, cache: &'a SearchCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Split keyword queries, and the titles and blocks they match, with
+    /// another tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: KeywordTokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Score and order keyword matches with other weights
+    pub fn with_ranking(mut self, ranking: RankingConfig) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Resolve date expressions like "last week" against `today` rather
+    /// than the local date
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
     /// Execute a search query and return matching results
-    pub async fn execute(&self, request: SearchRequest) -> DomainResult<Vec<SearchResult>> {
-        // Get all pages (or filtered pages if specified)
-        let pages = if let Some(ref page_filters) = request.page_filters {
-            self.get_filtered_pages(page_filters)?
-        } else {
-            self.repository.find_all()?
+    #[tracing::instrument(skip_all, fields(query = %request.query, search_type = ?request.search_type))]
+    pub async fn execute(&self, request: SearchRequest) -> Result<Vec<SearchResult>, SearchError> {
+        let request = self.resolve_dates(request);
+        let excerpts = request
+            .excerpt_length
+            .map(|length| (KeywordQuery::new(&request.query, self.tokenizer.clone(), self.ranking), length));
+        let mut results = match self.cache {
+            None => self.search(request).await?,
+            Some(cache) => match cache.get(&request) {
+                Some(results) => results.as_ref().clone(),
+                None => {
+                    let generation = cache.generation().current();
+                    let results = self.search(request.clone()).await?;
+                    cache.insert(&request, generation, results.clone());
+                    results
+                }
+            },
         };
 
+        // Cached results keep whole blocks, whatever excerpt length they're read with
+        if let Some((query, length)) = &excerpts {
+            for result in &mut results {
+                query.window(result, *length);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Execute a search query, yielding at most `limit` results as they are found
+    ///
+    /// Keyword searches match one page at a time as the stream is polled, so
+    /// the first results arrive before the scan finishes and dropping the
+    /// stream (or reaching `limit`) stops it. Pages are matched in title
+    /// order and each page's results come best first; only [`execute`]
+    /// ranks across pages. Semantic searches, and searches including
+    /// archived web pages, are ranked as a whole and then streamed.
+    ///
+    /// The cache isn't consulted for keyword searches, whose order it
+    /// doesn't keep. Searches for journal dates alone are answered newest
+    /// first, like [`execute`] answers them.
+    ///
+    /// [`execute`]: Self::execute
+    #[tracing::instrument(skip_all, fields(query = %request.query, search_type = ?request.search_type))]
+    pub async fn execute_stream(
+        &self,
+        request: SearchRequest,
+        limit: Option<usize>,
+    ) -> Result<SearchResultStream<'a>, SearchError> {
+        let request = self.resolve_dates(request);
+        let limit = limit.unwrap_or(usize::MAX);
+        let ranked = matches!(request.search_type, SearchType::Semantic) && self.embedding_service.is_some();
+        let archived = self.web_archiver.is_some() && request.result_type == ResultType::All && !filters_pages(&request);
+        if ranked || archived || dates_only(&request) {
+            let results = self.execute(request).await?;
+            return Ok(Box::pin(tokio_stream::iter(results.into_iter().take(limit).map(Ok))));
+        }
+
+        let mut pages = self.pages_to_search(&request)?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+
+        let threshold = request.score_threshold.map(SimilarityScore::new).transpose()?;
+        let query = KeywordQuery::new(&request.query, self.tokenizer.clone(), self.ranking);
+        let boosts = self.page_boosts(&pages, filters_pages(&request))?;
+        let templates = self.template_weight(&pages, filters_pages(&request))?;
+        let excerpts = request
+            .excerpt_length
+            .map(|length| (KeywordQuery::new(&request.query, self.tokenizer.clone(), self.ranking), length));
+        let url_metadata = self.url_metadata;
+        let results = pages
+            .into_iter()
+            .flat_map(move |page| {
+                let mut results = Self::search_in_page(&page, &request, &query, boosts.of(&page), templates.as_ref());
+                results.retain(|result| threshold.is_none_or(|threshold| result.score >= threshold));
+                results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
+                results
+            })
+            .take(limit)
+            .map(move |mut result| {
+                if let (Some(url_metadata), SearchItem::Url(url)) = (url_metadata, &mut result.item) {
+                    url.metadata = url_metadata.metadata_of(url.url.as_str())?;
+                }
+                if let Some((query, length)) = &excerpts {
+                    query.window(&mut result, *length);
+                }
+                Ok(result)
+            });
+        Ok(Box::pin(tokio_stream::iter(results)))
+    }
+
+    async fn search(&self, request: SearchRequest) -> Result<Vec<SearchResult>, SearchError> {
+        let threshold = request.score_threshold.map(SimilarityScore::new).transpose()?;
+
+        // Get all pages (or those the filters and journal dates leave)
+        let pages = self.pages_to_search(&request)?;
+
         // Perform search based on search type
-        let results = match request.search_type {
-            SearchType::Traditional => self.traditional_search(&pages, &request),
-            SearchType::Semantic => {
-                if let Some(ref embedding_service) = self.embedding_service {
-                    self.semantic_search(&pages, &request, embedding_service)
-                        .await?
-                } else {
-                    // Fall back to traditional search if no embedding service
-                    self.traditional_search(&pages, &request)
+        let mut results = match (&request.search_type, &self.embedding_service) {
+            _ if dates_only(&request) => Self::journal_results(&pages, &request),
+            (SearchType::Semantic, Some(embedding_service)) => {
+                let mut results = self.semantic_search(&pages, &request, embedding_service).await?;
+                // The index covers every page, so journal dates narrow its results here
+                if request.journal_dates.is_some() {
+                    let journals: HashSet<&PageId> = pages.iter().map(|page| page.id()).collect();
+                    results.retain(|result| match &result.item {
+                        SearchItem::Page(page) => journals.contains(&page.page_id),
+                        SearchItem::Block(block) => journals.contains(&block.page_id),
+                        SearchItem::Url(url) => journals.contains(&url.page_id),
+                        SearchItem::Archive(_) => false,
+                    });
+                }
+                if let Some(templates) = self.template_weight(&pages, filters_pages(&request))? {
+                    templates.apply(&mut results);
+                    results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
                 }
+                results
+            }
+            // Fall back to traditional search if no embedding service
+            _ => {
+                let query = KeywordQuery::new(&request.query, self.tokenizer.clone(), self.ranking);
+                let boosts = self.page_boosts(&pages, filters_pages(&request))?;
+                let templates = self.template_weight(&pages, filters_pages(&request))?;
+                Self::traditional_search(&pages, &request, &query, &boosts, templates.as_ref())
             }
         };
 
+        if let Some(url_metadata) = self.url_metadata {
+            for result in &mut results {
+                if let SearchItem::Url(url) = &mut result.item {
+                    url.metadata = url_metadata.metadata_of(url.url.as_str())?;
+                }
+            }
+        }
+
+        // Archived pages aren't pages of the graph, so page filters exclude them
+        if let Some(web_archiver) = self.web_archiver {
+            if request.result_type == ResultType::All && !filters_pages(&request) {
+                results.extend(web_archiver.search(&request).await?);
+                results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
+            }
+        }
+
+        // Every kind of result is scored from 0.0 to 1.0, so one threshold fits all
+        if let Some(threshold) = threshold {
+            results.retain(|result| result.score >= threshold);
+        }
+
         Ok(results)
     }
 
@@ -69,16 +387,22 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
         _pages: &[Page],
         request: &SearchRequest,
         embedding_service: &EmbeddingService,
-    ) -> DomainResult<Vec<SearchResult>> {
-        use crate::domain::base::DomainError;
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        if let Some(ref graph_id) = request.graph_id {
+            if embedding_service.graph_id() != Some(graph_id) {
+                return Err(SearchError::GraphNotIndexed(graph_id.clone()));
+            }
+        }
 
         // Perform vector search
         let vector_results = embedding_service
-            .search(&request.query, 50)
-            .await
-            .map_err(|e| DomainError::InvalidOperation(format!("Semantic search failed: {}", e)))?;
+            .search_with_threshold(&request.query, 50, request.score_threshold)
+            .await?;
 
         let mut results = Vec::new();
+        // Whether each block's page still has it, looked up once per page
+        let mut page_blocks: HashMap<PageId, HashSet<BlockId>> = HashMap::new();
+        let semantic_kind = if embedding_service.is_hybrid() { MatchKind::Hybrid } else { MatchKind::Semantic };
 
         // Convert vector search results to SearchResults
         for vr in vector_results {
@@ -93,6 +417,19 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
                 let block_id = crate::domain::value_objects::BlockId::new(&vr.block_id)
                     .map_err(|e| DomainError::InvalidValue(format!("Invalid block ID: {}", e)))?;
 
+                // Skip blocks removed since they were indexed, or hidden by
+                // a visibility filter over the repository
+                if !page_blocks.contains_key(&page_id) {
+                    let blocks = match self.repository.find_by_id(&page_id)? {
+                        Some(page) => page.all_blocks().map(|block| block.id().clone()).collect(),
+                        None => HashSet::new(),
+                    };
+                    page_blocks.insert(page_id.clone(), blocks);
+                }
+                if !page_blocks[&page_id].contains(&block_id) {
+                    continue;
+                }
+
                 // Fetch the actual page for related data
                 let related_pages = Vec::new();
                 let related_urls = Vec::new();
@@ -107,17 +444,109 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
                         page_id,
                         page_title: vr.page_title,
                         hierarchy_path: vr.hierarchy_path,
+                        block_path: None,
                         related_pages,
                         related_urls,
+                        truncated: false,
                     }),
-                    score: vr.score as f64,
+                    score: SimilarityScore::saturating(vr.score),
+                    explanation: request
+                        .explain
+                        .then(|| ScoreExplanation::new(MatchedField::Content, semantic_kind, vr.score)),
                 });
             }
         }
 
+        // Page-level embeddings answer "find the page about X" queries
+        if matches!(request.result_type, ResultType::PagesOnly | ResultType::All)
+            && embedding_service.has_page_embeddings()
+        {
+            let page_results = embedding_service
+                .search_pages(&request.query, 20, request.score_threshold)
+                .await?;
+
+            for pr in page_results {
+                let page_id = PageId::new(&pr.page_id)
+                    .map_err(|e| DomainError::InvalidValue(format!("Invalid page ID: {}", e)))?;
+
+                // Skip pages that were removed since they were indexed
+                if let Some(page) = self.repository.find_by_id(&page_id)? {
+                    results.push(SearchResult {
+                        item: SearchItem::Page(Self::page_result(&page)),
+                        score: SimilarityScore::saturating(pr.score),
+                        explanation: request
+                            .explain
+                            .then(|| ScoreExplanation::new(MatchedField::Page, semantic_kind, pr.score)),
+                    });
+                }
+            }
+
+            results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
+        }
+
         Ok(results)
     }
 
+    fn page_result(page: &Page) -> PageResult {
+        PageResult {
+            page_id: page.id().clone(),
+            title: page.title().to_string(),
+            block_count: page.all_blocks().count(),
+            urls: page.all_urls().into_iter().cloned().collect(),
+            page_references: page.all_page_references().into_iter().cloned().collect(),
+        }
+    }
+
+    /// Move a date expression out of the query into `journal_dates`, if the
+    /// request asks for that and the query has one
+    fn resolve_dates(&self, mut request: SearchRequest) -> SearchRequest {
+        if !request.parse_dates {
+            return request;
+        }
+        request.parse_dates = false;
+        let today = self.today.unwrap_or_else(|| Local::now().date_naive());
+        if let Some(dated) = extract_date_range(&request.query, today) {
+            request.query = dated.remainder;
+            request.journal_dates = Some(dated.range);
+        }
+        request
+    }
+
+    /// The pages a request searches: all of them, or those its page
+    /// filters and journal dates leave
+    fn pages_to_search(&self, request: &SearchRequest) -> DomainResult<Vec<Page>> {
+        let mut pages = if let Some(ref page_filters) = request.page_filters {
+            self.get_filtered_pages(page_filters)?
+        } else {
+            self.repository.find_all()?
+        };
+        if let Some(ref dates) = request.journal_dates {
+            pages.retain(|page| journal_date(page).is_some_and(|date| dates.contains(&date)));
+        }
+        Ok(pages)
+    }
+
+    /// The journal pages themselves, newest first, for a search with dates
+    /// and no words
+    fn journal_results(pages: &[Page], request: &SearchRequest) -> Vec<SearchResult> {
+        if !matches!(request.result_type, ResultType::PagesOnly | ResultType::All) {
+            return Vec::new();
+        }
+        let mut journals: Vec<(NaiveDate, &Page)> =
+            pages.iter().filter_map(|page| Some((journal_date(page)?, page))).collect();
+        journals.sort_by_key(|(date, _)| std::cmp::Reverse(*date));
+        journals
+            .into_iter()
+            .map(|(_, page)| SearchResult {
+                item: SearchItem::Page(Self::page_result(page)),
+                score: SimilarityScore::saturating(1.0),
+                explanation: request
+                    .explain
+                    .then(|| ScoreExplanation::new(MatchedField::JournalDate, MatchKind::Exact, 1.0)),
+            })
+            .collect()
+    }
+
     fn get_filtered_pages(&self, page_ids: &[PageId]) -> DomainResult<Vec<Page>> {
         let mut pages = Vec::new();
         for page_id in page_ids {
@@ -128,82 +557,158 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
         Ok(pages)
     }
 
-    fn traditional_search(&self, pages: &[Page], request: &SearchRequest) -> Vec<SearchResult> {
-        let query_lower = request.query.to_lowercase();
+    /// Match the query against every page, on all cores
+    ///
+    /// Pages are matched independently, so they are spread over rayon's
+    /// thread pool; collecting keeps page order, which the stable sort
+    /// then keeps for results with equal scores.
+    fn traditional_search(
+        pages: &[Page],
+        request: &SearchRequest,
+        query: &KeywordQuery,
+        boosts: &PageBoosts,
+        templates: Option<&TemplateWeight>,
+    ) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = pages
+            .par_iter()
+            .flat_map_iter(|page| Self::search_in_page(page, request, query, boosts.of(page), templates))
+            .collect();
+
+        // Sort by score (highest first)
+        results.sort_by(|a, b| b.score.value().total_cmp(&a.score.value()));
+
+        results
+    }
+
+    /// Matches in one page, their scores adjusted by the page's `boosts` and
+    /// the weight of journal `templates`
+    fn search_in_page(
+        page: &Page,
+        request: &SearchRequest,
+        query: &KeywordQuery,
+        boosts: &[ScoreAdjustment],
+        templates: Option<&TemplateWeight>,
+    ) -> Vec<SearchResult> {
         let mut results = Vec::new();
+        let result_type = &request.result_type;
 
-        for page in pages {
-            // Search pages
-            if matches!(
-                request.result_type,
-                ResultType::PagesOnly | ResultType::All
-            ) {
-                if let Some(result) = self.search_page(page, &query_lower) {
-                    results.push(result);
-                }
-            }
+        // Search pages
+        if matches!(result_type, ResultType::PagesOnly | ResultType::All) {
+            results.extend(Self::search_page(page, query));
+        }
 
-            // Search blocks
-            if matches!(
-                request.result_type,
-                ResultType::BlocksOnly | ResultType::All
-            ) {
-                results.extend(self.search_blocks(page, &query_lower));
-            }
+        // Search blocks
+        if matches!(result_type, ResultType::BlocksOnly | ResultType::All) {
+            results.extend(Self::search_blocks(page, query));
+        }
 
-            // Search URLs
-            if matches!(request.result_type, ResultType::UrlsOnly | ResultType::All) {
-                results.extend(self.search_urls(page, &query_lower));
-            }
+        // Search URLs
+        if matches!(result_type, ResultType::UrlsOnly | ResultType::All) {
+            results.extend(Self::search_urls(page, &query.text, request.same_block_refs));
         }
 
-        // Sort by score (highest first)
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        if let Some(templates) = templates {
+            templates.apply(&mut results);
+        }
 
+        // Keyword results are scored from their explanation, kept if asked for
+        for result in &mut results {
+            if let Some(explanation) = &mut result.explanation {
+                explanation.adjustments.extend_from_slice(boosts);
+                result.score = explanation.score();
+            }
+            if !request.explain {
+                result.explanation = None;
+            }
+        }
         results
     }
 
-    fn search_page(&self, page: &Page, query: &str) -> Option<SearchResult> {
-        let title_lower = page.title().to_lowercase();
-        if title_lower.contains(query) {
-            // Calculate score based on match quality
-            let score = if title_lower == query {
-                1.0 // Exact match
-            } else if title_lower.starts_with(query) {
-                0.9 // Prefix match
-            } else {
-                0.7 // Contains match
-            };
+    /// A keyword match, scored by its explanation
+    fn keyword_result(item: SearchItem, explanation: ScoreExplanation) -> SearchResult {
+        SearchResult {
+            item,
+            score: explanation.score(),
+            explanation: Some(explanation),
+        }
+    }
 
-            Some(SearchResult {
-                item: SearchItem::Page(PageResult {
-                    page_id: page.id().clone(),
-                    title: page.title().to_string(),
-                    block_count: page.all_blocks().count(),
-                    urls: page.all_urls().into_iter().cloned().collect(),
-                    page_references: page.all_page_references().into_iter().cloned().collect(),
-                }),
-                score,
+    /// Each page's boosts from the ranking; pages are only looked up again
+    /// when `filtered` leaves some out, since centrality counts references
+    /// from the whole graph
+    fn page_boosts(&self, pages: &[Page], filtered: bool) -> DomainResult<PageBoosts> {
+        let ranking = &self.ranking;
+        if !ranking.has_page_boosts() {
+            return Ok(PageBoosts::default());
+        }
+
+        let backlinks = match (ranking.page_centrality_boost > 0.0, filtered) {
+            (false, _) => HashMap::new(),
+            (true, false) => backlink_counts(pages),
+            (true, true) => backlink_counts(&self.repository.find_all()?),
+        };
+        let most_backlinks = backlinks.values().copied().max().unwrap_or(0);
+        let now = Utc::now();
+
+        let boosts = pages
+            .iter()
+            .map(|page| {
+                let mut boosts = Vec::new();
+                if ranking.recency_boost > 0.0 {
+                    let recency = last_updated(page).map_or(0.0, |updated_at| ranking.recency(updated_at, now));
+                    boosts.push(RankingConfig::boost(AdjustmentKind::Recency, ranking.recency_boost, recency));
+                }
+                if ranking.page_centrality_boost > 0.0 {
+                    let count = backlinks.get(&page.title().to_lowercase()).copied().unwrap_or(0);
+                    let centrality = if most_backlinks == 0 {
+                        0.0
+                    } else {
+                        (count as f32).ln_1p() / (most_backlinks as f32).ln_1p()
+                    };
+                    let share = ranking.page_centrality_boost;
+                    boosts.push(RankingConfig::boost(AdjustmentKind::PageCentrality, share, centrality));
+                }
+                (page.id().clone(), boosts)
             })
+            .collect();
+        Ok(PageBoosts(boosts))
+    }
+
+    /// The graph's journal templates and their weight, unless the ranking
+    /// leaves them as they are; like centrality, templates are detected
+    /// across every journal even when `filtered` leaves some out
+    fn template_weight(&self, pages: &[Page], filtered: bool) -> DomainResult<Option<TemplateWeight>> {
+        let weight = self.ranking.journal_template_weight;
+        if weight >= 1.0 {
+            return Ok(None);
+        }
+        let templates = if filtered {
+            JournalTemplates::detect(&self.repository.find_all()?)
         } else {
-            None
+            JournalTemplates::detect(pages)
+        };
+        Ok((!templates.is_empty()).then_some(TemplateWeight { templates, weight }))
+    }
+
+    fn search_page(page: &Page, query: &KeywordQuery) -> Option<SearchResult> {
+        let (kind, score) = query.score(page.title())?;
+        let mut explanation = ScoreExplanation::new(MatchedField::Title, kind, score);
+        let title_weight = query.ranking.title_weight;
+        if title_weight != 1.0 {
+            explanation.adjustments.push(ScoreAdjustment {
+                kind: AdjustmentKind::TitleWeight,
+                signal: None,
+                factor: title_weight,
+            });
         }
+        Some(Self::keyword_result(SearchItem::Page(Self::page_result(page)), explanation))
     }
 
-    fn search_blocks(&self, page: &Page, query: &str) -> Vec<SearchResult> {
+    fn search_blocks(page: &Page, query: &KeywordQuery) -> Vec<SearchResult> {
         let mut results = Vec::new();
 
         for block in page.all_blocks() {
-            let content_lower = block.content().as_str().to_lowercase();
-            if content_lower.contains(query) {
-                let score = if content_lower == query {
-                    1.0
-                } else if content_lower.starts_with(query) {
-                    0.9
-                } else {
-                    0.7
-                };
-
+            if let Some((kind, score)) = query.score(block.content().as_str()) {
                 // Get hierarchy path for context
                 let hierarchy_path = page
                     .get_hierarchy_path(block.id())
@@ -225,25 +730,26 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
                     related_urls.extend(descendant.urls().iter().cloned());
                 }
 
-                results.push(SearchResult {
-                    item: SearchItem::Block(BlockResult {
-                        block_id: block.id().clone(),
-                        content: block.content().as_str().to_string(),
-                        page_id: page.id().clone(),
-                        page_title: page.title().to_string(),
-                        hierarchy_path,
-                        related_pages,
-                        related_urls,
-                    }),
-                    score,
+                let item = SearchItem::Block(BlockResult {
+                    block_id: block.id().clone(),
+                    content: block.content().as_str().to_string(),
+                    page_id: page.id().clone(),
+                    page_title: page.title().to_string(),
+                    hierarchy_path,
+                    block_path: page.block_path(block.id()),
+                    related_pages,
+                    related_urls,
+                    truncated: false,
                 });
+                let explanation = ScoreExplanation::new(MatchedField::Content, kind, score);
+                results.push(Self::keyword_result(item, explanation));
             }
         }
 
         results
     }
 
-    fn search_urls(&self, page: &Page, query: &str) -> Vec<SearchResult> {
+    fn search_urls(page: &Page, query: &str, same_block_refs: bool) -> Vec<SearchResult> {
         let mut results = Vec::new();
 
         // Get all URLs with their context
@@ -252,10 +758,10 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
         for (url, ancestor_refs, descendant_refs) in urls_with_context {
             let url_str = url.as_str().to_lowercase();
             if url_str.contains(query) {
-                let score = if url_str == query {
-                    1.0
+                let explanation = if url_str == query {
+                    ScoreExplanation::new(MatchedField::Url, MatchKind::Exact, 1.0)
                 } else {
-                    0.8
+                    ScoreExplanation::new(MatchedField::Url, MatchKind::Contains, 0.8)
                 };
 
                 // Find the block containing this URL
@@ -263,8 +769,7 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
                     .all_blocks()
                     .find(|b| b.urls().iter().any(|u| u == url))
                 {
-                    results.push(SearchResult {
-                        item: SearchItem::Url(UrlResult {
+                    let item = SearchItem::Url(UrlResult {
                             url: url.clone(),
                             containing_block_id: block.id().clone(),
                             containing_block_content: block.content().as_str().to_string(),
@@ -272,9 +777,14 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
                             page_title: page.title().to_string(),
                             ancestor_page_refs: ancestor_refs.into_iter().cloned().collect(),
                             descendant_page_refs: descendant_refs.into_iter().cloned().collect(),
-                        }),
-                        score,
-                    });
+                            same_block_page_refs: if same_block_refs {
+                                block.page_references().to_vec()
+                            } else {
+                                Vec::new()
+                            },
+                            metadata: None,
+                        });
+                    results.push(Self::keyword_result(item, explanation));
                 }
             }
         }
@@ -283,13 +793,165 @@ impl<'a, R: PageRepository> SearchPagesAndBlocks<'a, R> {
     }
 }
 
+/// Recency and centrality boosts of pages' results, by page; pages left
+/// out aren't boosted
+#[derive(Default)]
+struct PageBoosts(HashMap<PageId, Vec<ScoreAdjustment>>);
+
+impl PageBoosts {
+    fn of(&self, page: &Page) -> &[ScoreAdjustment] {
+        self.0.get(page.id()).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// What block results repeated by a journal template are multiplied by
+struct TemplateWeight {
+    templates: JournalTemplates,
+    /// 0.0 or less leaves the blocks out
+    weight: f32,
+}
+
+impl TemplateWeight {
+    fn apply(&self, results: &mut Vec<SearchResult>) {
+        let is_template = |result: &SearchResult| match &result.item {
+            SearchItem::Block(block) => self.templates.contains(&block.content),
+            _ => false,
+        };
+        if self.weight <= 0.0 {
+            results.retain(|result| !is_template(result));
+            return;
+        }
+        for result in results.iter_mut().filter(|result| is_template(result)) {
+            match &mut result.explanation {
+                Some(explanation) => {
+                    explanation.adjustments.push(ScoreAdjustment {
+                        kind: AdjustmentKind::JournalTemplate,
+                        signal: None,
+                        factor: self.weight,
+                    });
+                    result.score = explanation.score();
+                }
+                None => result.score = SimilarityScore::saturating(result.score.value() * self.weight),
+            }
+        }
+    }
+}
+
+/// Whether a request searches only some of the graph's pages
+fn filters_pages(request: &SearchRequest) -> bool {
+    request.page_filters.is_some() || request.journal_dates.is_some()
+}
+
+/// Whether a request asks for journal dates and no words
+fn dates_only(request: &SearchRequest) -> bool {
+    request.journal_dates.is_some() && request.query.trim().is_empty()
+}
+
+/// Blocks referencing each page from other pages, by lowercased title; a
+/// block counts once however often it references a page
+fn backlink_counts(pages: &[Page]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for page in pages {
+        let own_title = page.title().to_lowercase();
+        for block in page.all_blocks() {
+            let titles: BTreeSet<String> = block
+                .page_references()
+                .iter()
+                .map(|reference| reference.title().to_lowercase())
+                .filter(|title| *title != own_title)
+                .collect();
+            for title in titles {
+                *counts.entry(title).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// A keyword query, lowercased and split into terms
+struct KeywordQuery {
+    text: String,
+    terms: Vec<String>,
+    tokenizer: KeywordTokenizer,
+    ranking: RankingConfig,
+}
+
+impl KeywordQuery {
+    fn new(query: &str, tokenizer: KeywordTokenizer, ranking: RankingConfig) -> Self {
+        KeywordQuery {
+            text: query.to_lowercase(),
+            terms: tokenizer.tokenize(query),
+            tokenizer,
+            ranking,
+        }
+    }
+
+    /// How well `text` matches, and the ranking's score for that: the query
+    /// as typed scores by where it is found, and otherwise every term of
+    /// the query has to be there
+    ///
+    /// Unless the tokenizer stems words or drops stop words, a query of one
+    /// term is found as typed wherever its term is, so only longer queries
+    /// are split (which is what finds CJK words in another order, or with
+    /// particles between them).
+    fn score(&self, text: &str) -> Option<(MatchKind, f32)> {
+        let text_lower = text.to_lowercase();
+        let split = self.terms.len() > 1 || (self.tokenizer.normalizes_words() && !self.terms.is_empty());
+        if text_lower.contains(&self.text) {
+            Some(if text_lower == self.text {
+                (MatchKind::Exact, self.ranking.exact)
+            } else if text_lower.starts_with(&self.text) {
+                (MatchKind::Prefix, self.ranking.prefix)
+            } else {
+                (MatchKind::Contains, self.ranking.contains)
+            })
+        } else if split && self.tokenizer.contains_all(&text_lower, &self.terms) {
+            Some((MatchKind::AllTerms, self.ranking.all_terms))
+        } else {
+            None
+        }
+    }
+
+    /// Cut a block result's content down to `length` characters centred on
+    /// where the query, or else its first term, is found; blocks it's
+    /// found in neither (semantic matches) are cut from the start
+    fn window(&self, result: &mut SearchResult, length: usize) {
+        let SearchItem::Block(block) = &mut result.item else {
+            return;
+        };
+        let chars: Vec<char> = block.content.chars().collect();
+        if chars.len() <= length {
+            return;
+        }
+        let lower = block.content.to_lowercase();
+        let (start, matched) = std::iter::once(&self.text)
+            .chain(&self.terms)
+            .filter(|needle| !needle.is_empty())
+            .find_map(|needle| lower.find(needle.as_str()).map(|found| (found, needle.chars().count())))
+            // Lowercasing can change byte lengths; map the match back by characters
+            .map_or((0, 0), |(found, matched)| (lower[..found].chars().count(), matched));
+
+        let from = (start + matched / 2).saturating_sub(length / 2).min(chars.len() - length);
+        let to = from + length;
+        let mut excerpt: String = chars[from..to].iter().collect();
+        if from > 0 {
+            excerpt.insert(0, '…');
+        }
+        if to < chars.len() {
+            excerpt.push('…');
+        }
+        block.content = excerpt;
+        block.truncated = true;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::{
         base::Entity,
         entities::Block,
-        value_objects::{BlockContent, BlockId, Url},
+        value_objects::{BlockContent, BlockId, PageReference, Url},
     };
     use std::collections::HashMap;
 
@@ -419,6 +1081,30 @@ mod tests {
         assert!(results.len() >= 2);
     }
 
+    #[tokio::test]
+    async fn test_score_threshold_applies_to_keyword_results() {
+        use tokio_stream::StreamExt;
+
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(create_test_page()).unwrap();
+        let use_case = SearchPagesAndBlocks::new(&repo);
+
+        // "Test Page" is a prefix match (0.9), the blocks only contain "test" (0.7)
+        let results = use_case.execute(SearchRequest::new("test").with_score_threshold(0.8)).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(&results[0].item, SearchItem::Page(page) if page.title == "Test Page"));
+        assert!(results.iter().all(|result| result.score.value() >= 0.8));
+
+        let streamed = use_case
+            .execute_stream(SearchRequest::new("test").with_score_threshold(0.8), None)
+            .await
+            .unwrap();
+        assert_eq!(streamed.collect::<Vec<_>>().await.len(), 1);
+
+        let invalid = use_case.execute(SearchRequest::new("test").with_score_threshold(1.5)).await;
+        assert!(matches!(invalid, Err(SearchError::Domain(DomainError::InvalidValue(_)))));
+    }
+
     #[tokio::test]
     async fn test_search_urls() {
         let mut repo = InMemoryPageRepository::new();
@@ -430,15 +1116,431 @@ mod tests {
             BlockContent::new("Check out this link"),
         );
         block.add_url(Url::new("https://example.com").unwrap());
+        block.add_page_reference(PageReference::from_brackets("reading").unwrap());
         page.add_block(block).unwrap();
 
         repo.save(page).unwrap();
 
         let use_case = SearchPagesAndBlocks::new(&repo);
         let request = SearchRequest::new("example.com").with_result_type(ResultType::UrlsOnly);
-        let results = use_case.execute(request).await.unwrap();
+        let results = use_case.execute(request.clone()).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(matches!(&results[0].item, SearchItem::Url(url) if url.same_block_page_refs.is_empty()));
+
+        let results = use_case.execute(request.with_same_block_refs()).await.unwrap();
+        let SearchItem::Url(url) = &results[0].item else {
+            panic!("Expected a URL result");
+        };
+        assert_eq!(url.same_block_page_refs, vec![PageReference::from_brackets("reading").unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_word_queries_find_every_term_in_any_order() {
+        let mut repo = InMemoryPageRepository::new();
+        let mut page = Page::new(PageId::new("notes").unwrap(), "メモ".to_string());
+        for (id, content) in [("ja", "Rustの所有権について"), ("en", "Ownership rules in Rust")] {
+            page.add_block(Block::new_root(BlockId::new(id).unwrap(), BlockContent::new(content))).unwrap();
+        }
+        repo.save(page).unwrap();
+
+        let use_case = SearchPagesAndBlocks::new(&repo);
+        for (query, expected) in [("所有権 rust", "Rustの所有権について"), ("rust ownership", "Ownership rules in Rust")] {
+            let request = SearchRequest::new(query).with_result_type(ResultType::BlocksOnly);
+            let results = use_case.execute(request).await.unwrap();
+            assert_eq!(results.len(), 1, "{}", query);
+            assert!(matches!(&results[0].item, SearchItem::Block(block) if block.content == expected));
+            assert_eq!(results[0].score.value(), RankingConfig::default().all_terms);
+        }
+    }
 
+    #[tokio::test]
+    async fn test_stemmed_queries_find_other_forms_of_a_word() {
+        use crate::infrastructure::text::Language;
+
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(create_test_page()).unwrap();
+        let mut page = Page::new(PageId::new("vectors").unwrap(), "Vectors".to_string());
+        let block = Block::new_root(BlockId::new("vectors-1").unwrap(), BlockContent::new("One embedding per block"));
+        page.add_block(block).unwrap();
+        repo.save(page).unwrap();
+
+        let request = || SearchRequest::new("embeddings").with_result_type(ResultType::BlocksOnly);
+        assert!(SearchPagesAndBlocks::new(&repo).execute(request()).await.unwrap().is_empty());
+
+        let tokenizer = KeywordTokenizer::new()
+            .with_stemming(Language::English)
+            .with_stop_words(Language::English);
+        let use_case = SearchPagesAndBlocks::new(&repo).with_tokenizer(tokenizer);
+        let results = use_case.execute(request()).await.unwrap();
         assert_eq!(results.len(), 1);
-        assert!(matches!(results[0].item, SearchItem::Url(_)));
+        assert_eq!(results[0].score.value(), RankingConfig::default().all_terms);
+        // Stop words alone match nothing
+        assert!(use_case.execute(SearchRequest::new("the of")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ranking_weights_and_page_boosts_order_results() {
+        let mut repo = InMemoryPageRepository::new();
+        let mut fresh = Page::new(PageId::new("fresh").unwrap(), "Fresh".to_string());
+        fresh.set_property("updated-at", Utc::now().to_rfc3339());
+        let mut stale = Page::new(PageId::new("stale").unwrap(), "Stale".to_string());
+        stale.set_property("updated-at", "2020-01-01");
+        for page in [&mut fresh, &mut stale] {
+            let block_id = BlockId::new(format!("{}-1", page.id().as_str())).unwrap();
+            page.add_block(Block::new_root(block_id, BlockContent::new("Rust notes"))).unwrap();
+        }
+        // Only the stale page is referenced from elsewhere
+        let mut index = Page::new(PageId::new("index").unwrap(), "Index".to_string());
+        let mut block = Block::new_root(BlockId::new("index-1").unwrap(), BlockContent::new("See [[Stale]]"));
+        block.add_page_reference(PageReference::from_brackets("Stale").unwrap());
+        index.add_block(block).unwrap();
+        for page in [fresh, stale, index] {
+            repo.save(page).unwrap();
+        }
+
+        let ranked = |ranking: RankingConfig, request: SearchRequest| {
+            let repo = &repo;
+            async move {
+                let results = SearchPagesAndBlocks::new(repo).with_ranking(ranking).execute(request).await.unwrap();
+                results
+                    .into_iter()
+                    .map(|result| match result.item {
+                        SearchItem::Page(page) => (page.title, result.score.value()),
+                        SearchItem::Block(block) => (block.page_title, result.score.value()),
+                        _ => panic!("Expected page and block results"),
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let blocks = || SearchRequest::new("rust").with_result_type(ResultType::BlocksOnly);
+
+        let recent_first = RankingConfig {
+            recency_boost: 0.5,
+            ..RankingConfig::default()
+        };
+        let results = ranked(recent_first, blocks()).await;
+        assert_eq!(results[0].0, "Fresh");
+        assert!((results[0].1 - 0.9).abs() < 0.01);
+        assert!((results[1].1 - 0.45).abs() < 0.01);
+
+        let referenced_first = RankingConfig {
+            page_centrality_boost: 0.5,
+            ..RankingConfig::default()
+        };
+        let results = ranked(referenced_first, blocks()).await;
+        assert_eq!(results, vec![("Stale".to_string(), 0.9), ("Fresh".to_string(), 0.45)]);
+
+        let weighted = RankingConfig {
+            prefix: 0.8,
+            title_weight: 0.5,
+            ..RankingConfig::default()
+        };
+        assert_eq!(ranked(weighted, blocks()).await[0].1, 0.8);
+        let titles = SearchRequest::new("fresh").with_result_type(ResultType::PagesOnly);
+        assert_eq!(ranked(weighted, titles).await, vec![("Fresh".to_string(), 0.5)]);
+
+        let invalid = RankingConfig {
+            contains: 1.5,
+            ..RankingConfig::default()
+        };
+        assert!(invalid.validate().is_err());
+        assert!(RankingConfig::default().validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_journal_template_blocks_are_demoted_or_left_out() {
+        let mut repo = InMemoryPageRepository::new();
+        for day in 1..=4 {
+            let mut journal = Page::new(PageId::new(format!("day-{}", day)).unwrap(), format!("2024_03_0{}", day));
+            for (i, content) in ["Rust review", &format!("Rust note {}", day)].into_iter().enumerate() {
+                let block_id = BlockId::new(format!("day-{}-{}", day, i)).unwrap();
+                journal.add_block(Block::new_root(block_id, BlockContent::new(content))).unwrap();
+            }
+            repo.save(journal).unwrap();
+        }
+        let request = || SearchRequest::new("rust").with_result_type(ResultType::BlocksOnly).with_explain();
+        let search = |weight: f32| {
+            let repo = &repo;
+            async move {
+                let ranking = RankingConfig {
+                    journal_template_weight: weight,
+                    ..RankingConfig::default()
+                };
+                SearchPagesAndBlocks::new(repo).with_ranking(ranking).execute(request()).await
+            }
+        };
+        let contents = |results: &[SearchResult]| {
+            results
+                .iter()
+                .map(|result| match &result.item {
+                    SearchItem::Block(block) => block.content.clone(),
+                    _ => panic!("Expected block results"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let demoted = search(0.5).await.unwrap();
+        assert_eq!(demoted.len(), 8);
+        assert!(contents(&demoted[4..]).iter().all(|content| content == "Rust review"));
+        let adjustment = &demoted[7].explanation.as_ref().unwrap().adjustments[0];
+        assert_eq!(adjustment.kind, AdjustmentKind::JournalTemplate);
+
+        let left_out = search(0.0).await.unwrap();
+        assert_eq!(left_out.len(), 4);
+        assert!(contents(&left_out).iter().all(|content| content.starts_with("Rust note")));
+        assert_eq!(search(1.0).await.unwrap().len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_explained_results_break_down_their_score() {
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(create_test_page()).unwrap();
+        let ranking = RankingConfig {
+            title_weight: 0.5,
+            recency_boost: 0.2,
+            ..RankingConfig::default()
+        };
+        let use_case = SearchPagesAndBlocks::new(&repo).with_ranking(ranking);
+
+        let results = use_case.execute(SearchRequest::new("test page")).await.unwrap();
+        assert!(results.iter().all(|result| result.explanation.is_none()));
+
+        let results = use_case.execute(SearchRequest::new("test page").with_explain()).await.unwrap();
+        let title = results
+            .iter()
+            .find(|result| matches!(result.item, SearchItem::Page(_)))
+            .unwrap();
+        let explanation = title.explanation.as_ref().unwrap();
+        assert_eq!(explanation.field, MatchedField::Title);
+        assert_eq!(explanation.kind, MatchKind::Exact);
+        assert_eq!(explanation.base_score, 1.0);
+        // The page has no `updated-at`, so it counts as not recent at all
+        assert_eq!(
+            explanation.adjustments,
+            vec![
+                ScoreAdjustment {
+                    kind: AdjustmentKind::TitleWeight,
+                    signal: None,
+                    factor: 0.5,
+                },
+                ScoreAdjustment {
+                    kind: AdjustmentKind::Recency,
+                    signal: Some(0.0),
+                    factor: 0.8,
+                },
+            ]
+        );
+        assert_eq!(title.score, explanation.score());
+        assert_eq!(title.score.value(), 0.4);
+    }
+
+    #[tokio::test]
+    async fn test_search_includes_archived_web_pages() {
+        use crate::application::repositories::WebArchiveRepository;
+        use crate::application::services::WebArchive;
+        use crate::infrastructure::persistence::SqliteWebArchiveRepository;
+
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(create_test_page()).unwrap();
+        let mut archives = SqliteWebArchiveRepository::open_in_memory().unwrap();
+        archives
+            .save(&WebArchive {
+                url: "https://example.com/testing".to_string(),
+                title: Some("Testing in Rust".to_string()),
+                text: "Unit tests live next to the code.".to_string(),
+                archived_at: chrono::Utc::now(),
+            })
+            .unwrap();
+        let web_archiver = WebArchiver::new(archives);
+
+        let use_case = SearchPagesAndBlocks::new(&repo).with_web_archiver(&web_archiver);
+        let results = use_case.execute(SearchRequest::new("test")).await.unwrap();
+        assert!(matches!(results.last().unwrap().item, SearchItem::Archive(_)));
+
+        let request = SearchRequest::new("test").with_result_type(ResultType::BlocksOnly);
+        let results = use_case.execute(request).await.unwrap();
+        assert!(results.iter().all(|result| matches!(result.item, SearchItem::Block(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cached_searches_are_repeated_until_the_index_changes() {
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(create_test_page()).unwrap();
+        let cache = SearchCache::default();
+        async fn search(repo: &InMemoryPageRepository, cache: &SearchCache) -> usize {
+            let request = SearchRequest::new("Test").with_result_type(ResultType::PagesOnly);
+            let use_case = SearchPagesAndBlocks::new(repo).with_cache(cache);
+            use_case.execute(request).await.unwrap().len()
+        }
+        assert_eq!(search(&repo, &cache).await, 1);
+
+        repo.save(Page::new(PageId::new("other-test").unwrap(), "Other Test".to_string())).unwrap();
+        assert_eq!(search(&repo, &cache).await, 1);
+        cache.generation().bump();
+        assert_eq!(search(&repo, &cache).await, 2);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_date_expressions_search_journals_in_range() {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("monday", "2024_03_04", "Meeting notes with the design team"),
+            ("friday", "2024_03_08", "Meeting notes on the budget"),
+            ("today", "2024_03_13", "Meeting notes about hiring"),
+            ("project", "Project", "Meeting notes for the project"),
+        ] {
+            let mut page = Page::new(PageId::new(id).unwrap(), title.to_string());
+            let block = Block::new_root(BlockId::new(format!("{}-block", id)).unwrap(), BlockContent::new(content));
+            page.add_block(block).unwrap();
+            repo.save(page).unwrap();
+        }
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let use_case = SearchPagesAndBlocks::new(&repo).with_today(today);
+        let page_ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results
+                .into_iter()
+                .map(|result| match result.item {
+                    SearchItem::Page(page) => page.page_id.as_str().to_string(),
+                    SearchItem::Block(block) => block.page_id.as_str().to_string(),
+                    other => panic!("unexpected result {:?}", other),
+                })
+                .collect()
+        };
+
+        let request = SearchRequest::new("meeting notes from last week")
+            .with_result_type(ResultType::BlocksOnly)
+            .with_date_expressions();
+        let mut found = page_ids(use_case.execute(request).await.unwrap());
+        found.sort();
+        assert_eq!(found, vec!["friday", "monday"]);
+
+        // Without the flag, the expression is searched for as words
+        let request = SearchRequest::new("meeting notes from last week").with_result_type(ResultType::BlocksOnly);
+        assert!(use_case.execute(request).await.unwrap().is_empty());
+
+        // Dates alone list the journals, newest first
+        let request = SearchRequest::new("this week").with_date_expressions();
+        assert_eq!(page_ids(use_case.execute(request).await.unwrap()), vec!["today"]);
+        let request = SearchRequest::new("since 2024-03-01").with_date_expressions();
+        assert_eq!(page_ids(use_case.execute(request).await.unwrap()), vec!["today", "friday", "monday"]);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_search_matches_pages_in_title_order_up_to_the_limit() {
+        use tokio_stream::StreamExt;
+
+        let mut repo = InMemoryPageRepository::new();
+        for title in ["Rust testing", "Async tests", "Test Page"] {
+            let id = title.to_lowercase().replace(' ', "-");
+            let mut page = Page::new(PageId::new(&id).unwrap(), title.to_string());
+            let block = Block::new_root(BlockId::new(format!("{}-block", id)).unwrap(), BlockContent::new("test"));
+            page.add_block(block).unwrap();
+            repo.save(page).unwrap();
+        }
+        let use_case = SearchPagesAndBlocks::new(&repo);
+
+        let stream = use_case.execute_stream(SearchRequest::new("test"), Some(3)).await.unwrap();
+        let results: Vec<SearchResult> = stream.map(Result::unwrap).collect().await;
+        let found: Vec<(&str, f32)> = results
+            .iter()
+            .map(|result| match &result.item {
+                SearchItem::Page(page) => (page.title.as_str(), result.score.value()),
+                SearchItem::Block(block) => (block.page_title.as_str(), result.score.value()),
+                _ => panic!("Expected page and block results"),
+            })
+            .collect();
+        assert_eq!(found, vec![("Async tests", 1.0), ("Async tests", 0.7), ("Rust testing", 1.0)]);
+
+        // Without a limit the stream finds what a ranked search finds
+        let stream = use_case.execute_stream(SearchRequest::new("test"), None).await.unwrap();
+        let streamed: Vec<SearchResult> = stream.map(Result::unwrap).collect().await;
+        let mut ranked = use_case.execute(SearchRequest::new("test")).await.unwrap();
+        assert_eq!(streamed.len(), 6);
+        for result in &streamed {
+            let position = ranked.iter().position(|other| other == result).unwrap();
+            ranked.remove(position);
+        }
+        assert!(ranked.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_long_blocks_are_cut_to_an_excerpt_around_the_match() {
+        use tokio_stream::StreamExt;
+
+        let mut repo = InMemoryPageRepository::new();
+        let mut page = Page::new(PageId::new("article").unwrap(), "Article".to_string());
+        let article = format!("{} The borrow checker rejects this. {}", "Intro. ".repeat(50), "Outro. ".repeat(50));
+        for (id, content) in [("long", article.as_str()), ("short", "Borrow checking")] {
+            let block = Block::new_root(BlockId::new(id).unwrap(), BlockContent::new(content));
+            page.add_block(block).unwrap();
+        }
+        repo.save(page).unwrap();
+        let use_case = SearchPagesAndBlocks::new(&repo);
+        let blocks = |results: Vec<SearchResult>| -> Vec<BlockResult> {
+            results
+                .into_iter()
+                .filter_map(|result| match result.item {
+                    SearchItem::Block(block) => Some(block),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let request = SearchRequest::new("borrow").with_result_type(ResultType::BlocksOnly);
+        let whole = blocks(use_case.execute(request.clone().with_excerpt_length(40)).await.unwrap());
+        let long = whole.iter().find(|block| block.block_id.as_str() == "long").unwrap();
+        assert!(long.truncated);
+        assert!(long.content.starts_with('…') && long.content.ends_with('…'));
+        assert!(long.content.contains("The borrow checker"));
+        assert_eq!(long.content.chars().count(), 42);
+        let short = whole.iter().find(|block| block.block_id.as_str() == "short").unwrap();
+        assert_eq!((short.content.as_str(), short.truncated), ("Borrow checking", false));
+
+        // Streamed results are cut the same way, and no length keeps blocks whole
+        let stream = use_case.execute_stream(request.clone().with_excerpt_length(40), None).await.unwrap();
+        let streamed = blocks(stream.map(Result::unwrap).collect().await);
+        assert!(streamed.iter().any(|block| block.content == long.content));
+        let full = blocks(use_case.execute(request).await.unwrap());
+        assert!(full.iter().all(|block| !block.truncated));
+        assert!(full.iter().any(|block| block.content.trim() == article.trim()));
+    }
+
+    #[test]
+    fn test_traditional_search_ranks_matches_from_every_page() {
+        let pages: Vec<Page> = (0..500)
+            .map(|i| {
+                let mut page = Page::new(PageId::new(format!("note-{}", i)).unwrap(), format!("Note {}", i));
+                let block = Block::new_root(BlockId::new(format!("block-{}", i)).unwrap(), BlockContent::new("A note"));
+                page.add_block(block).unwrap();
+                page
+            })
+            .collect();
+
+        let request = SearchRequest::new("note");
+        let query = KeywordQuery::new(&request.query, KeywordTokenizer::new(), RankingConfig::default());
+        let results = SearchPagesAndBlocks::<InMemoryPageRepository>::traditional_search(
+            &pages,
+            &request,
+            &query,
+            &PageBoosts::default(),
+            None,
+        );
+        assert_eq!(results.len(), 1000);
+        assert!(results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+        // Equal scores keep page order
+        let titles: Vec<&str> = results
+            .iter()
+            .filter_map(|result| match &result.item {
+                SearchItem::Page(page) => Some(page.title.as_str()),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<String> = (0..500).map(|i| format!("Note {}", i)).collect();
+        assert_eq!(titles, expected);
     }
 }
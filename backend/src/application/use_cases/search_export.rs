@@ -0,0 +1,135 @@
+use super::{site_export::ExportError, SearchPagesAndBlocks};
+use crate::application::{
+    dto::{SearchRequest, SearchResultRow},
+    repositories::PageRepository,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File format search results are exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One row per result, list columns joined on one line
+    Csv,
+    /// An array of [`SearchResultRow`] objects
+    Json,
+}
+
+impl ExportFormat {
+    /// The format a file's `.csv` or `.json` extension names
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Use case for saving search results to a file, for analysis in
+/// spreadsheets or scripts
+///
+/// The search runs as the given [`SearchPagesAndBlocks`] is configured, and
+/// every kind of result is written as a [`SearchResultRow`], best first.
+pub struct ExportSearchResults<'s, 'a, R: PageRepository> {
+    search: &'s SearchPagesAndBlocks<'a, R>,
+}
+
+impl<'s, 'a, R: PageRepository> ExportSearchResults<'s, 'a, R> {
+    pub fn new(search: &'s SearchPagesAndBlocks<'a, R>) -> Self {
+        Self { search }
+    }
+
+    /// Run the search and write its results to `path`, returning how many
+    /// were written
+    pub async fn execute(&self, request: SearchRequest, format: ExportFormat, path: &Path) -> Result<usize, ExportError> {
+        let results = self.search.execute(request).await?;
+        let rows: Vec<SearchResultRow> = results.iter().map(SearchResultRow::from).collect();
+
+        let contents = match format {
+            ExportFormat::Csv => csv(&rows),
+            ExportFormat::Json => serde_json::to_string_pretty(&rows)?,
+        };
+        std::fs::write(path, contents)?;
+        Ok(rows.len())
+    }
+}
+
+const CSV_COLUMNS: [&str; 8] = ["kind", "page_id", "page_title", "block_id", "content", "score", "hierarchy_path", "urls"];
+
+/// Rows as RFC 4180 CSV; hierarchy paths are joined with ` > ` and URLs
+/// with spaces
+fn csv(rows: &[SearchResultRow]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let urls: Vec<&str> = row.urls.iter().map(|url| url.as_str()).collect();
+        let fields = [
+            row.kind.as_str().to_string(),
+            row.page_id.as_ref().map(|id| id.as_str().to_string()).unwrap_or_default(),
+            row.page_title.clone().unwrap_or_default(),
+            row.block_id.as_ref().map(|id| id.as_str().to_string()).unwrap_or_default(),
+            row.content.clone(),
+            row.score.to_string(),
+            row.hierarchy_path.join(" > "),
+            urls.join(" "),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::ResultType;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_exports_results_as_csv_and_json() {
+        let content = "- Languages\n\t- Rust, \"fast\" and safe https://rust-lang.org";
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("notes").unwrap(), "Notes".into()).unwrap();
+        let mut repo = InMemoryPageRepository::new();
+        repo.save(page).unwrap();
+        let search = SearchPagesAndBlocks::new(&repo);
+        let export = ExportSearchResults::new(&search);
+        let dir = TempDir::new().unwrap();
+        let request = || SearchRequest::new("rust").with_result_type(ResultType::BlocksOnly);
+
+        let path = dir.path().join("results.csv");
+        let format = ExportFormat::from_path(&path).unwrap();
+        assert_eq!(export.execute(request(), format, &path).await.unwrap(), 1);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "kind,page_id,page_title,block_id,content,score,hierarchy_path,urls");
+        assert!(lines[1].starts_with("block,notes,Notes,"));
+        assert!(lines[1].ends_with(
+            ",\"Rust, \"\"fast\"\" and safe https://rust-lang.org\",0.9,\
+             \"Languages > Rust, \"\"fast\"\" and safe https://rust-lang.org\","
+        ));
+
+        let path = dir.path().join("results.JSON");
+        let format = ExportFormat::from_path(&path).unwrap();
+        export.execute(request(), format, &path).await.unwrap();
+        let rows: Vec<SearchResultRow> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].page_title.as_deref(), Some("Notes"));
+
+        assert_eq!(ExportFormat::from_path(Path::new("results.txt")), None);
+    }
+}
@@ -0,0 +1,130 @@
+use super::SearchError;
+use crate::application::repositories::PageRepository;
+use crate::domain::{aggregates::Page, entities::Block, DomainError};
+use crate::infrastructure::parsers::{html_writer::INDEX_FILE, HtmlWriter};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Cannot write the export: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Cannot serialize the export: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Search(#[from] SearchError),
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+}
+
+/// Use case for publishing a graph as a static, read-only HTML site
+///
+/// Every page is rendered to its own file, with a section listing the blocks
+/// of other pages that reference it (as [`GetBacklinks`] finds them), and
+/// `index.html` links them all in title order.
+///
+/// [`GetBacklinks`]: super::GetBacklinks
+pub struct ExportSite<'a, R: PageRepository> {
+    repository: &'a R,
+    title: String,
+}
+
+impl<'a, R: PageRepository> ExportSite<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            title: "All pages".to_string(),
+        }
+    }
+
+    /// Head the index with `title`, such as the graph's name
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Write the site into `output_dir`, creating it if needed, and return
+    /// how many pages were written
+    ///
+    /// Files already in the directory are left alone unless a page's file
+    /// replaces them.
+    pub fn execute(&self, output_dir: &Path) -> Result<usize, ExportError> {
+        let mut pages = self.repository.find_all()?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+        let writer = HtmlWriter::new(&pages);
+        let backlinks = backlinks(&pages);
+
+        std::fs::create_dir_all(output_dir)?;
+        for page in &pages {
+            let file = writer.file_name(page.title()).expect("every page is named");
+            let references = backlinks.get(&page.title().to_lowercase()).map_or(&[][..], Vec::as_slice);
+            std::fs::write(output_dir.join(file), writer.render_page(page, references))?;
+        }
+
+        let index: Vec<&Page> = pages.iter().collect();
+        std::fs::write(output_dir.join(INDEX_FILE), writer.render_index(&self.title, &index))?;
+        Ok(pages.len())
+    }
+}
+
+/// Blocks referencing each page from other pages, by lowercased title, in
+/// page then outline order; a block is listed once however often it
+/// references a page
+fn backlinks(pages: &[Page]) -> HashMap<String, Vec<(&Page, &Block)>> {
+    let mut backlinks: HashMap<String, Vec<(&Page, &Block)>> = HashMap::new();
+    for page in pages {
+        let own_title = page.title().to_lowercase();
+        for block in page.all_blocks() {
+            let titles: BTreeSet<String> = block
+                .page_references()
+                .iter()
+                .map(|reference| reference.title().to_lowercase())
+                .filter(|title| *title != own_title)
+                .collect();
+            for title in titles {
+                backlinks.entry(title).or_default().push((page, block));
+            }
+        }
+    }
+    backlinks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_writes_every_page_with_backlinks_and_an_index() {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- Learning [[Book]] and [[book]]\n- About #Rust itself"),
+            ("book", "Book", "- The Rust programming language"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.into()).unwrap();
+            repo.save(page).unwrap();
+        }
+        let output = TempDir::new().unwrap();
+        let site = output.path().join("site");
+
+        let written = ExportSite::new(&repo).with_title("Notes").execute(&site).unwrap();
+        assert_eq!(written, 2);
+
+        let index = std::fs::read_to_string(site.join("index.html")).unwrap();
+        assert!(index.contains("<h1>Notes</h1>"));
+        assert!(index.find("book.html").unwrap() < index.find("rust.html").unwrap());
+
+        let book = std::fs::read_to_string(site.join("book.html")).unwrap();
+        assert_eq!(book.matches("<h2>1 Linked References</h2>").count(), 1);
+        assert!(book.contains("<li>Learning <a class=\"page-ref\" href=\"book.html\">Book</a>"));
+        let rust = std::fs::read_to_string(site.join("rust.html")).unwrap();
+        assert!(!rust.contains("Linked References"));
+    }
+}
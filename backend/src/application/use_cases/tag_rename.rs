@@ -0,0 +1,312 @@
+use crate::application::{dto::PageSummary, repositories::PageRepository, services::EmbeddingService};
+use crate::application::use_cases::page_names::normalize_tag;
+use crate::domain::{
+    aggregates::Page,
+    events::PageUpdated,
+    value_objects::{BlockContent, BlockId},
+    DomainError,
+};
+use crate::infrastructure::parsers::{LogseqMarkdownParser, LogseqMarkdownWriter};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RenameTagError {
+    #[error("A tag name cannot be empty")]
+    EmptyName,
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+}
+
+/// Pages changed by [`RenameTag`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenamedTag {
+    /// The pages rewritten, in title order
+    pub pages: Vec<PageSummary>,
+    /// Blocks whose content was rewritten
+    pub blocks: usize,
+    /// Markdown files rewritten, with [`RenameTag::with_file_write_back`]
+    pub files_written: usize,
+    /// Pages whose file couldn't be written; the repository has the change
+    pub write_errors: usize,
+    /// Pages that couldn't be re-embedded; their search results keep the old
+    /// name until they are
+    pub embedding_errors: usize,
+}
+
+impl RenamedTag {
+    /// The event of each rewritten page, for caches and webhooks
+    pub fn events(&self) -> Vec<PageUpdated> {
+        self.pages
+            .iter()
+            .map(|page| PageUpdated {
+                page_id: page.page_id.clone(),
+                title: Some(page.title.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Use case for renaming a tag across the graph
+///
+/// Every `#old`, `#[[old]]` and `[[old]]` in block content becomes the new
+/// name, matched case-insensitively like Logseq matches page names, and so
+/// does `old` in the pages' `tags` property. A new name that isn't a single
+/// word is written `#[[new name]]`. The blocks' page references are
+/// re-extracted from the rewritten content, so link queries and the link
+/// graph follow the new name. The tag's own page keeps its title.
+///
+/// Files are written directly with [`with_file_write_back`](Self::with_file_write_back);
+/// for a graph kept in sync by a `SyncService`, call its `write_back` instead,
+/// which checks the files weren't edited meanwhile.
+pub struct RenameTag<'a, R: PageRepository> {
+    repository: &'a mut R,
+    old: String,
+    new: String,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    write_back: bool,
+}
+
+impl<'a, R: PageRepository> RenameTag<'a, R> {
+    /// Rename `old` to `new`; either may be written `#tag` or `[[tag]]`
+    pub fn new(repository: &'a mut R, old: &str, new: &str) -> Self {
+        Self {
+            repository,
+            old: tag_name(old),
+            new: tag_name(new),
+            embedding_service: None,
+            write_back: false,
+        }
+    }
+
+    /// Re-embed the rewritten pages; the chunk index limits this to the
+    /// blocks that changed
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Write the rewritten pages parsed from a file back to it
+    pub fn with_file_write_back(mut self) -> Self {
+        self.write_back = true;
+        self
+    }
+
+    /// The pages that would be rewritten, in title order
+    pub fn dry_run(&self) -> Result<Vec<PageSummary>, RenameTagError> {
+        let mut pages: Vec<PageSummary> = self.rewritten()?.0.iter().map(PageSummary::from).collect();
+        pages.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(pages)
+    }
+
+    /// Rewrite and save the pages referencing the tag
+    pub async fn execute(&mut self) -> Result<RenamedTag, RenameTagError> {
+        let (pages, blocks) = self.rewritten()?;
+        self.repository.save_all(pages.clone())?;
+        tracing::info!("Renamed tag {} to {} in {} pages", self.old, self.new, pages.len());
+
+        let mut renamed = RenamedTag {
+            blocks,
+            ..Default::default()
+        };
+        for page in &pages {
+            if self.write_back {
+                if let Some(path) = page.file_path() {
+                    match LogseqMarkdownWriter::write_file(page, path).await {
+                        Ok(()) => renamed.files_written += 1,
+                        Err(e) => {
+                            tracing::warn!("Failed to write {}: {}", path.display(), e);
+                            renamed.write_errors += 1;
+                        }
+                    }
+                }
+            }
+            if let Some(embedding_service) = &self.embedding_service {
+                match embedding_service.embed_page(page, &*self.repository).await {
+                    Ok(stats) if stats.errors == 0 => {}
+                    Ok(stats) => {
+                        tracing::warn!("Failed to re-embed {} blocks of page {}", stats.errors, page.title());
+                        renamed.embedding_errors += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to re-embed page {}: {}", page.title(), e);
+                        renamed.embedding_errors += 1;
+                    }
+                }
+            }
+        }
+
+        renamed.pages = pages.iter().map(PageSummary::from).collect();
+        renamed.pages.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(renamed)
+    }
+
+    /// The pages referencing the tag, rewritten, and the number of blocks rewritten
+    fn rewritten(&self) -> Result<(Vec<Page>, usize), RenameTagError> {
+        if self.old.is_empty() || self.new.is_empty() {
+            return Err(RenameTagError::EmptyName);
+        }
+        let old = self.old.to_lowercase();
+        let mut pages = Vec::new();
+        let mut blocks = 0;
+        for mut page in self.repository.find_all()? {
+            let mut changed = false;
+            let rewrites: Vec<(BlockId, String)> = page
+                .all_blocks()
+                .filter_map(|block| {
+                    rename_references(block.content().as_str(), &old, &self.new).map(|content| (block.id().clone(), content))
+                })
+                .collect();
+            for (id, content) in rewrites {
+                if let Some(block) = page.get_block_mut(&id) {
+                    block.set_page_references(LogseqMarkdownParser::extract_page_references(&content));
+                    block.update_content(BlockContent::new(content));
+                    blocks += 1;
+                    changed = true;
+                }
+            }
+
+            if let Some(tags) = page.properties().get("tags") {
+                if tags.split(',').any(|tag| normalize_tag(tag) == old) {
+                    let tags: Vec<String> = tags
+                        .split(',')
+                        .map(|tag| {
+                            if normalize_tag(tag) == old {
+                                rename_references(tag.trim(), &old, &self.new).unwrap_or_else(|| self.new.clone())
+                            } else {
+                                tag.trim().to_string()
+                            }
+                        })
+                        .collect();
+                    page.set_property("tags", tags.join(", "));
+                    changed = true;
+                }
+            }
+
+            if changed {
+                pages.push(page);
+            }
+        }
+        Ok((pages, blocks))
+    }
+}
+
+/// A tag's name without the `#` or `[[...]]` it may be written with
+fn tag_name(tag: &str) -> String {
+    let tag = tag.trim().trim_start_matches('#');
+    let tag = tag.strip_prefix("[[").and_then(|tag| tag.strip_suffix("]]")).unwrap_or(tag);
+    tag.trim().to_string()
+}
+
+/// `content` with its references to `old` (lowercased) pointing at `new`,
+/// or `None` if it has none
+///
+/// Tags end where [`LogseqMarkdownParser`] ends them, at whitespace or ASCII
+/// punctuation, so what's renamed is what the parser reads as a reference to
+/// `old`: `#old-notes` included, `#oldest` not.
+fn rename_references(content: &str, old: &str, new: &str) -> Option<String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut renamed = String::with_capacity(content.len());
+    let mut changed = false;
+    let mut position = 0;
+
+    while position < chars.len() {
+        if chars[position] == '[' && chars.get(position + 1) == Some(&'[') {
+            let closing = (position + 2..chars.len().saturating_sub(1)).find(|&i| chars[i] == ']' && chars[i + 1] == ']');
+            if let Some(closing) = closing {
+                let title: String = chars[position + 2..closing].iter().collect();
+                if title.to_lowercase() == old {
+                    renamed.push_str(&format!("[[{}]]", new));
+                    changed = true;
+                } else {
+                    renamed.extend(&chars[position..closing + 2]);
+                }
+                position = closing + 2;
+                continue;
+            }
+        } else if chars[position] == '#' && (position == 0 || chars[position - 1].is_whitespace()) {
+            let end = (position + 1..chars.len())
+                .find(|&i| chars[i].is_whitespace() || chars[i].is_ascii_punctuation())
+                .unwrap_or(chars.len());
+            let tag: String = chars[position + 1..end].iter().collect();
+            if !tag.is_empty() && tag.to_lowercase() == old {
+                if new.chars().any(|c| c.is_whitespace() || c.is_ascii_punctuation()) {
+                    renamed.push_str(&format!("#[[{}]]", new));
+                } else {
+                    renamed.push_str(&format!("#{}", new));
+                }
+                changed = true;
+                position = end;
+                continue;
+            }
+        }
+        renamed.push(chars[position]);
+        position += 1;
+    }
+
+    changed.then_some(renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use tempfile::TempDir;
+
+    fn page(id: &str, title: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.into()).unwrap()
+    }
+
+    fn references(page: &Page) -> Vec<String> {
+        let mut titles: Vec<String> = page
+            .all_blocks()
+            .flat_map(|block| block.page_references().iter().map(|reference| reference.title().to_string()))
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_rewrites_tags_and_links_but_not_longer_names() {
+        let rename = |content| rename_references(content, "rust", "rust-lang");
+        assert_eq!(rename("#Rust and [[rust]] and #[[RUST]]").unwrap(), "#[[rust-lang]] and [[rust-lang]] and #[[rust-lang]]");
+        assert_eq!(rename("#rust, then more").unwrap(), "#[[rust-lang]], then more");
+        assert_eq!(rename("#rustacean [[rust notes]] a#rust"), None);
+        assert_eq!(rename_references("see #todo", "todo", "task").unwrap(), "see #task");
+    }
+
+    #[tokio::test]
+    async fn test_execute_rewrites_blocks_references_and_files() {
+        let dir = TempDir::new().unwrap();
+        let mut repo = InMemoryPageRepository::new();
+        let mut notes = page("a", "Notes", "- Ownership #rust\n\t- See [[Rust]] and [[memory]]\n- Unrelated");
+        notes.set_file_path(dir.path().join("Notes.md"));
+        repo.save(notes).unwrap();
+        let mut tagged = page("b", "Tagged", "- text");
+        tagged.set_property("tags", "[[Rust]], drafts");
+        repo.save(tagged).unwrap();
+        repo.save(page("c", "Rust", "- #rustacean")).unwrap();
+
+        let mut rename = RenameTag::new(&mut repo, "#rust", "Rust language").with_file_write_back();
+        let preview = rename.dry_run().unwrap();
+        let renamed = rename.execute().await.unwrap();
+        assert_eq!(renamed.pages, preview);
+        assert_eq!(renamed.pages.iter().map(|page| page.title.as_str()).collect::<Vec<_>>(), ["Notes", "Tagged"]);
+        assert_eq!((renamed.blocks, renamed.files_written, renamed.write_errors), (2, 1, 0));
+        assert_eq!(renamed.events()[0].page_id.as_str(), "a");
+
+        let notes = repo.find_by_id(&PageId::new("a").unwrap()).unwrap().unwrap();
+        assert_eq!(references(&notes), ["Rust language", "Rust language", "memory"]);
+        let written = std::fs::read_to_string(dir.path().join("Notes.md")).unwrap();
+        assert!(written.contains("Ownership #[[Rust language]]\n\t- See [[Rust language]] and [[memory]]"));
+        let tagged = repo.find_by_id(&PageId::new("b").unwrap()).unwrap().unwrap();
+        assert_eq!(tagged.properties()["tags"], "[[Rust language]], drafts");
+        let untouched = repo.find_by_id(&PageId::new("c").unwrap()).unwrap().unwrap();
+        assert_eq!(references(&untouched), ["rustacean"]);
+
+        let empty = RenameTag::new(&mut repo, "rust", "#").dry_run();
+        assert!(matches!(empty, Err(RenameTagError::EmptyName)));
+    }
+}
@@ -0,0 +1,111 @@
+use super::page_names::journal_date;
+use crate::application::{
+    dto::{Backlink, TimelineMention},
+    repositories::PageRepository,
+};
+use crate::domain::{base::Entity, DomainResult};
+
+/// Use case for the history of a topic: every journal block that
+/// references a page, oldest first
+///
+/// References are `[[page]]` links and `#tags`, with titles compared
+/// case-insensitively like [`GetBacklinks`](super::GetBacklinks) does. The
+/// page itself needn't exist. Blocks of the same journal keep their order
+/// in it.
+pub struct GetMentionTimeline<'a, R: PageRepository> {
+    repository: &'a R,
+}
+
+impl<'a, R: PageRepository> GetMentionTimeline<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self { repository }
+    }
+
+    /// Get every journal block referencing the page titled `page_title`, by journal date
+    pub fn execute(&self, page_title: &str) -> DomainResult<Vec<TimelineMention>> {
+        let title = page_title.to_lowercase();
+        let mut journals: Vec<_> = self
+            .repository
+            .find_all()?
+            .into_iter()
+            .filter_map(|page| journal_date(&page).map(|date| (date, page)))
+            .collect();
+        // Two pages can name the same day (`2024_01_15` and `Jan 15th, 2024`)
+        journals.sort_by(|(a_date, a), (b_date, b)| a_date.cmp(b_date).then_with(|| a.title().cmp(b.title())));
+
+        let mut timeline = Vec::new();
+        for (date, journal) in &journals {
+            // Outline order: each block, then its descendants
+            let blocks = journal
+                .root_blocks()
+                .into_iter()
+                .flat_map(|root| std::iter::once(root).chain(journal.get_descendants(root.id())));
+            for block in blocks {
+                let mentions_page = block
+                    .page_references()
+                    .iter()
+                    .any(|reference| reference.title().to_lowercase() == title);
+                if !mentions_page {
+                    continue;
+                }
+                timeline.push(TimelineMention {
+                    date: *date,
+                    mention: Backlink {
+                        page_id: journal.id().clone(),
+                        page_title: journal.title().to_string(),
+                        block_id: block.id().clone(),
+                        block_content: block.content().as_str().to_string(),
+                        hierarchy_path: journal
+                            .get_hierarchy_path(block.id())
+                            .iter()
+                            .map(|b| b.content().as_str().to_string())
+                            .collect(),
+                        block_path: journal.block_path(block.id()),
+                    },
+                });
+            }
+        }
+        Ok(timeline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_orders_journal_mentions_by_date() {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("d3", "2024_03_01", "- Finished the [[Rust]] book\n- Unrelated"),
+            ("d1", "Jan 15th, 2024", "- Started learning [[rust]]\n  - Chapter one\n  - Tried #Rust macros"),
+            ("d2", "2024_02_01", "- Went hiking"),
+            ("rust", "Rust", "- See [[Rust]]"),
+            ("notes", "Reading notes", "- [[Rust]] is memory safe"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+
+        let timeline = GetMentionTimeline::new(&repo).execute("RUST").unwrap();
+        let entries: Vec<(String, &str)> = timeline
+            .iter()
+            .map(|entry| (entry.date.to_string(), entry.mention.block_content.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("2024-01-15".to_string(), "Started learning [[rust]]"),
+                ("2024-01-15".to_string(), "Tried #Rust macros"),
+                ("2024-03-01".to_string(), "Finished the [[Rust]] book"),
+            ]
+        );
+        assert_eq!(timeline[1].mention.hierarchy_path.len(), 2);
+        assert_eq!(timeline[0].date, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert!(GetMentionTimeline::new(&repo).execute("Go").unwrap().is_empty());
+    }
+}
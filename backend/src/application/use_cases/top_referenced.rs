@@ -0,0 +1,164 @@
+use super::activity_stats::block_date;
+use super::page_names::journal_date;
+use crate::application::{
+    dto::{ReferencedPage, TopReferencedPages},
+    repositories::PageRepository,
+};
+use crate::domain::{base::Entity, value_objects::PageId, DomainResult};
+use chrono::NaiveDate;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+
+/// Pages listed per ranking unless `with_limit` says otherwise
+const DEFAULT_LIMIT: usize = 10;
+
+/// Use case for ranking pages by the references to them, overall and
+/// within a recent window ("what am I thinking about lately")
+///
+/// A reference counts once per block, and only from blocks of other pages.
+/// Blocks are dated like [`GetActivityStats`](super::GetActivityStats)
+/// dates them: by their `created-at` property, else by their journal's
+/// date. Undated blocks count toward the overall ranking only.
+pub struct GetTopReferencedPages<'a, R: PageRepository> {
+    repository: &'a R,
+    limit: usize,
+}
+
+impl<'a, R: PageRepository> GetTopReferencedPages<'a, R> {
+    pub fn new(repository: &'a R) -> Self {
+        Self {
+            repository,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Set how many pages each ranking lists
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Rank pages by references overall and within `window`, both ends included
+    pub fn execute(&self, window: RangeInclusive<NaiveDate>) -> DomainResult<TopReferencedPages> {
+        let pages = self.repository.find_all()?;
+        let ids: HashMap<String, (&str, &PageId)> = pages
+            .iter()
+            .map(|page| (page.title().to_lowercase(), (page.title(), page.id())))
+            .collect();
+
+        // Counts by lowercased title: spellings seen, all references, recent references
+        let mut counts: HashMap<String, (BTreeSet<String>, usize, usize)> = HashMap::new();
+        for page in &pages {
+            let own_title = page.title().to_lowercase();
+            let page_date = journal_date(page);
+            for block in page.all_blocks() {
+                let recent = block_date(block).or(page_date).is_some_and(|date| window.contains(&date));
+                let mut seen = BTreeSet::new();
+                for reference in block.page_references() {
+                    let key = reference.title().to_lowercase();
+                    if key == own_title || !seen.insert(key.clone()) {
+                        continue;
+                    }
+                    let (spellings, references, recent_references) = counts.entry(key).or_default();
+                    spellings.insert(reference.title().to_string());
+                    *references += 1;
+                    *recent_references += usize::from(recent);
+                }
+            }
+        }
+
+        let referenced: Vec<ReferencedPage> = counts
+            .into_iter()
+            .map(|(key, (spellings, references, recent_references))| {
+                // A page's own title, else the spelling that sorts first
+                let (title, page_id) = match ids.get(&key) {
+                    Some((title, id)) => (title.to_string(), Some((*id).clone())),
+                    None => (spellings.into_iter().next().unwrap_or(key), None),
+                };
+                ReferencedPage {
+                    title,
+                    page_id,
+                    references,
+                    recent_references,
+                }
+            })
+            .collect();
+
+        let mut overall = referenced.clone();
+        overall.sort_by(|a, b| b.references.cmp(&a.references).then_with(|| a.title.cmp(&b.title)));
+        overall.truncate(self.limit);
+
+        let mut trending: Vec<ReferencedPage> = referenced
+            .into_iter()
+            .filter(|page| page.recent_references > 0)
+            .collect();
+        // Recent share compared without division: a/b > c/d iff a*d > c*b
+        trending.sort_by(|a, b| {
+            b.recent_references
+                .cmp(&a.recent_references)
+                .then_with(|| (b.recent_references * a.references).cmp(&(a.recent_references * b.references)))
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        trending.truncate(self.limit);
+
+        Ok(TopReferencedPages {
+            window_start: *window.start(),
+            window_end: *window.end(),
+            overall,
+            trending,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn titles(pages: &[ReferencedPage]) -> Vec<&str> {
+        pages.iter().map(|page| page.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ranks_pages_overall_and_within_the_window() {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("d1", "2024_01_01", "- [[Rust]] and [[rust]] again\n- [[Rust]] borrowing\n- [[Go]]"),
+            ("d2", "2024_01_02", "- [[Rust]] lifetimes"),
+            ("d9", "2024_01_09", "- [[Go]] channels #memory\n- #memory"),
+            ("d10", "2024_01_10", "- [[Rust]] macros"),
+            ("rust", "Rust", "- See [[Rust]] and [[Go]]"),
+            ("go", "Go", "- A language"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+
+        let top = GetTopReferencedPages::new(&repo)
+            .execute(date("2024-01-08")..=date("2024-01-14"))
+            .unwrap();
+        assert_eq!(titles(&top.overall), vec!["Rust", "Go", "memory"]);
+        // Once per block, and never from the page itself
+        assert_eq!(top.overall[0].references, 4);
+        assert_eq!(top.overall[0].page_id, Some(PageId::new("rust").unwrap()));
+        assert_eq!(top.overall[1].references, 3);
+        assert_eq!(top.overall[2].page_id, None);
+
+        // Go and Rust are tied lately, but more of Go's references are recent
+        assert_eq!(titles(&top.trending), vec!["memory", "Go", "Rust"]);
+        assert_eq!(top.trending[0].recent_references, 2);
+        assert_eq!(top.trending[2].recent_references, 1);
+
+        let limited = GetTopReferencedPages::new(&repo)
+            .with_limit(1)
+            .execute(date("2023-01-01")..=date("2023-12-31"))
+            .unwrap();
+        assert_eq!(titles(&limited.overall), vec!["Rust"]);
+        assert!(limited.trending.is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+/// Command-line argument parsing for the `logjam` binary
+use backend::application::dto::ResultType;
+use backend::application::use_cases::ExportFormat;
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: logjam [OPTIONS] <COMMAND>
+
+Commands:
+  import <PATH>     Import a graph directory, .zip backup or .json/.edn export, or
+                    the graph open in Logseq from its API's URL (http://127.0.0.1:12315)
+      --dry-run       Only check the graph for problems (directories only)
+      --embed         Embed the imported pages for semantic search
+  sync              Sync the graph once
+      --watch         Keep syncing file changes until interrupted
+  search <QUERY>    Search pages, blocks and URLs
+      --semantic      Search by meaning instead of keywords
+      --limit <N>     Show at most N results (default 10)
+      --results <R>   all, pages, blocks or urls (default all)
+      --output <FILE> Write every result to a .csv or .json file instead
+  stats             Count the graph's pages, blocks and links, and out-of-date embeddings
+  export <DIR>      Write the graph as a static HTML site, one file per page, leaving
+                    out private pages and blocks and what [visibility] hides
+      --cards         Write the #card blocks to an Anki-importable TSV file instead
+  reindex           Re-embed every page of the graph
+      --stale         Only pages changed since they were embedded, or the jobs of
+                      [database] embedding_outbox (needs [database] path)
+      --rebuild       Embed every page into a new index, searched once complete
+  mcp               Serve the graph to MCP clients over stdin/stdout
+      --semantic      Search by meaning (needs Qdrant)
+  graphs            List the configured graphs
+  doctor            Check the database, Qdrant and the embedding model
+
+Options:
+  --config <FILE>     Configuration file (default: $LOGJAM_CONFIG, else ./logjam.toml if present)
+  --graph <DIR|ID>    Graph directory or configured graph ID (default: the configured graph, else the current directory)
+  --qdrant-url <URL>  Qdrant server for embeddings (default: the configured one, else http://localhost:6334)
+  --json              Print JSON instead of text
+  -h, --help          Print this help
+";
+
+/// Results shown by `search` unless `--limit` says otherwise
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    #[error("missing command")]
+    MissingCommand,
+
+    #[error("unknown command '{0}'")]
+    UnknownCommand(String),
+
+    #[error("unknown option '{0}'")]
+    UnknownOption(String),
+
+    #[error("'{0}' needs a value")]
+    MissingValue(String),
+
+    #[error("invalid value '{value}' for '{option}'")]
+    InvalidValue { option: String, value: String },
+
+    #[error("missing {0}")]
+    MissingArgument(&'static str),
+
+    #[error("unexpected argument '{0}'")]
+    UnexpectedArgument(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    /// Overrides the configured graph
+    pub graph: Option<PathBuf>,
+    pub qdrant_url: Option<String>,
+    pub json: bool,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Import { path: PathBuf, dry_run: bool, embed: bool },
+    Sync { watch: bool },
+    Search {
+        query: String,
+        semantic: bool,
+        limit: usize,
+        results: ResultType,
+        /// File to export the results to, and its format
+        output: Option<(PathBuf, ExportFormat)>,
+    },
+    Stats,
+    Export { output: PathBuf, cards: bool },
+    Reindex { stale: bool, rebuild: bool },
+    Mcp { semantic: bool },
+    Graphs,
+    Doctor,
+    Help,
+}
+
+/// Parse the arguments after the program name
+///
+/// Options may come before or after the command; `--name=value` and
+/// `--name value` are both accepted, and `--` ends option parsing.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, ArgsError> {
+    let mut args = args.into_iter();
+    let mut positionals = Vec::new();
+    let mut config = None;
+    let mut graph = None;
+    let mut qdrant_url = None;
+    let mut json = false;
+    let mut help = false;
+    let mut flags = Flags::default();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positionals.extend(args.by_ref());
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            positionals.push(arg);
+            continue;
+        }
+
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let takes_value = matches!(name.as_str(), "--config" | "--graph" | "--qdrant-url" | "--limit" | "--results" | "--output");
+        if inline_value.is_some() && !takes_value {
+            return Err(ArgsError::UnexpectedArgument(arg));
+        }
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| ArgsError::MissingValue(name.clone()))
+        };
+
+        match name.as_str() {
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--graph" => graph = Some(PathBuf::from(value()?)),
+            "--qdrant-url" => qdrant_url = Some(value()?),
+            "--limit" => flags.limit = Some(parse_value(&name, &value()?, |v| v.parse().ok())?),
+            "--results" => flags.results = Some(parse_value(&name, &value()?, result_type)?),
+            "--output" => flags.output = Some(parse_value(&name, &value()?, output_file)?),
+            "--json" => json = true,
+            "--dry-run" => flags.dry_run = true,
+            "--embed" => flags.embed = true,
+            "--watch" => flags.watch = true,
+            "--semantic" => flags.semantic = true,
+            "--cards" => flags.cards = true,
+            "--stale" => flags.stale = true,
+            "--rebuild" => flags.rebuild = true,
+            "-h" | "--help" => help = true,
+            _ => return Err(ArgsError::UnknownOption(arg)),
+        }
+    }
+
+    let mut positionals = positionals.into_iter();
+    let command = match (help, positionals.next()) {
+        (true, _) => Command::Help,
+        (false, None) => return Err(ArgsError::MissingCommand),
+        (false, Some(name)) => flags.command(&name, &mut positionals)?,
+    };
+    if let Some(extra) = positionals.next() {
+        return Err(ArgsError::UnexpectedArgument(extra));
+    }
+
+    Ok(Cli {
+        config,
+        graph,
+        qdrant_url,
+        json,
+        command,
+    })
+}
+
+/// Command-specific options, checked once the command is known
+#[derive(Default)]
+struct Flags {
+    dry_run: bool,
+    embed: bool,
+    watch: bool,
+    semantic: bool,
+    cards: bool,
+    stale: bool,
+    rebuild: bool,
+    limit: Option<usize>,
+    results: Option<ResultType>,
+    output: Option<(PathBuf, ExportFormat)>,
+}
+
+impl Flags {
+    fn command(self, name: &str, positionals: &mut impl Iterator<Item = String>) -> Result<Command, ArgsError> {
+        let command = match name {
+            "import" => Command::Import {
+                path: positionals.next().map(PathBuf::from).ok_or(ArgsError::MissingArgument("path to import"))?,
+                dry_run: self.dry_run,
+                embed: self.embed,
+            },
+            "sync" => Command::Sync { watch: self.watch },
+            "search" => Command::Search {
+                query: positionals.next().ok_or(ArgsError::MissingArgument("search query"))?,
+                semantic: self.semantic,
+                limit: self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+                results: self.results.clone().unwrap_or(ResultType::All),
+                output: self.output.clone(),
+            },
+            "stats" => Command::Stats,
+            "export" => Command::Export {
+                output: positionals.next().map(PathBuf::from).ok_or(ArgsError::MissingArgument("output path"))?,
+                cards: self.cards,
+            },
+            "reindex" => Command::Reindex {
+                stale: self.stale,
+                rebuild: self.rebuild,
+            },
+            "mcp" => Command::Mcp { semantic: self.semantic },
+            "graphs" => Command::Graphs,
+            "doctor" => Command::Doctor,
+            "help" => Command::Help,
+            _ => return Err(ArgsError::UnknownCommand(name.to_string())),
+        };
+
+        // Reject options that mean nothing for the command rather than ignore them
+        let misplaced = [
+            ("--dry-run", self.dry_run && name != "import"),
+            ("--embed", self.embed && name != "import"),
+            ("--watch", self.watch && name != "sync"),
+            ("--semantic", self.semantic && !matches!(name, "search" | "mcp")),
+            ("--cards", self.cards && name != "export"),
+            ("--stale", self.stale && name != "reindex"),
+            // A rebuild embeds every page
+            ("--rebuild", self.rebuild && (name != "reindex" || self.stale)),
+            // An export has every result
+            ("--limit", self.limit.is_some() && (name != "search" || self.output.is_some())),
+            ("--results", self.results.is_some() && name != "search"),
+            ("--output", self.output.is_some() && name != "search"),
+        ];
+        match misplaced.iter().find(|(_, misplaced)| *misplaced) {
+            Some((option, _)) => Err(ArgsError::UnexpectedArgument(option.to_string())),
+            None => Ok(command),
+        }
+    }
+}
+
+fn parse_value<T>(option: &str, value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<T, ArgsError> {
+    parse(value).ok_or_else(|| ArgsError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn output_file(value: &str) -> Option<(PathBuf, ExportFormat)> {
+    let path = PathBuf::from(value);
+    let format = ExportFormat::from_path(&path)?;
+    Some((path, format))
+}
+
+fn result_type(value: &str) -> Option<ResultType> {
+    match value {
+        "all" => Some(ResultType::All),
+        "pages" => Some(ResultType::PagesOnly),
+        "blocks" => Some(ResultType::BlocksOnly),
+        "urls" => Some(ResultType::UrlsOnly),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &str) -> Result<Cli, ArgsError> {
+        parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parses_commands_and_options() {
+        let cli = parse_args("--json search rust --semantic --limit=5 --graph ~/notes").unwrap();
+        assert!(cli.json);
+        assert_eq!(cli.graph, Some(PathBuf::from("~/notes")));
+        assert_eq!(
+            cli.command,
+            Command::Search {
+                query: "rust".to_string(),
+                semantic: true,
+                limit: 5,
+                results: ResultType::All,
+                output: None,
+            }
+        );
+        assert_eq!(
+            parse_args("search rust --output hits.csv").unwrap().command,
+            Command::Search {
+                query: "rust".to_string(),
+                semantic: false,
+                limit: DEFAULT_SEARCH_LIMIT,
+                results: ResultType::All,
+                output: Some((PathBuf::from("hits.csv"), ExportFormat::Csv)),
+            }
+        );
+
+        let cli = parse_args("import backup.zip --embed --config=logjam.toml").unwrap();
+        assert_eq!(cli.graph, None);
+        assert_eq!(cli.config, Some(PathBuf::from("logjam.toml")));
+        assert_eq!(
+            cli.command,
+            Command::Import {
+                path: PathBuf::from("backup.zip"),
+                dry_run: false,
+                embed: true,
+            }
+        );
+        assert_eq!(parse_args("sync --watch").unwrap().command, Command::Sync { watch: true });
+        assert_eq!(parse_args("stats --help").unwrap().command, Command::Help);
+        assert_eq!(parse_args("mcp --semantic").unwrap().command, Command::Mcp { semantic: true });
+        assert_eq!(parse_args("graphs --graph work").unwrap().graph, Some(PathBuf::from("work")));
+        assert_eq!(parse_args("doctor --json").unwrap().command, Command::Doctor);
+        assert_eq!(
+            parse_args("reindex --stale").unwrap().command,
+            Command::Reindex { stale: true, rebuild: false }
+        );
+        assert_eq!(
+            parse_args("reindex --rebuild").unwrap().command,
+            Command::Reindex { stale: false, rebuild: true }
+        );
+        assert_eq!(
+            parse_args("export site").unwrap().command,
+            Command::Export { output: PathBuf::from("site"), cards: false }
+        );
+        assert_eq!(
+            parse_args("export --cards cards.txt").unwrap().command,
+            Command::Export { output: PathBuf::from("cards.txt"), cards: true }
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_arguments() {
+        assert_eq!(parse_args(""), Err(ArgsError::MissingCommand));
+        assert_eq!(parse_args("index"), Err(ArgsError::UnknownCommand("index".to_string())));
+        assert_eq!(parse_args("search"), Err(ArgsError::MissingArgument("search query")));
+        assert_eq!(parse_args("export"), Err(ArgsError::MissingArgument("output path")));
+        assert_eq!(parse_args("search rust --limit"), Err(ArgsError::MissingValue("--limit".to_string())));
+        assert!(matches!(parse_args("search rust --results=tags"), Err(ArgsError::InvalidValue { .. })));
+        assert!(matches!(parse_args("search rust --output hits.txt"), Err(ArgsError::InvalidValue { .. })));
+        assert_eq!(
+            parse_args("search rust --output hits.json --limit 3"),
+            Err(ArgsError::UnexpectedArgument("--limit".to_string()))
+        );
+        assert_eq!(parse_args("stats --watch"), Err(ArgsError::UnexpectedArgument("--watch".to_string())));
+        assert_eq!(parse_args("mcp --limit 3"), Err(ArgsError::UnexpectedArgument("--limit".to_string())));
+        assert_eq!(
+            parse_args("reindex --stale --rebuild"),
+            Err(ArgsError::UnexpectedArgument("--rebuild".to_string()))
+        );
+        assert_eq!(parse_args("stats extra"), Err(ArgsError::UnexpectedArgument("extra".to_string())));
+        assert_eq!(parse_args("stats --verbose"), Err(ArgsError::UnknownOption("--verbose".to_string())));
+    }
+}
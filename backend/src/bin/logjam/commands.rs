@@ -0,0 +1,857 @@
+/// What each `logjam` command does, on top of the application services
+///
+/// There's no persistent page store yet, so every command loads the graph into
+/// memory first; only embeddings, which live in Qdrant, outlast a run.
+use crate::args::{Cli, Command};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use backend::application::dto::{ResultType, SearchItem, SearchRequest, SearchResult, SearchType};
+use backend::application::repositories::{PageRepository, PageTimestampRepository};
+use backend::application::services::{
+    DuplicateTitleAction, EmbeddingQueueConfig, EmbeddingQueueService, EmbeddingService, EmbeddingStats,
+    GarbageCollectionReport, HealthService, ImportSummary,
+    SyncCallback, SyncEvent, SyncSummary, TimestampedPageRepository, ValidationReport, VisiblePages,
+};
+use backend::application::use_cases::{
+    anki_tsv, ExportFormat, ExportSearchResults, ExportSite, GetFlashcards, SearchPagesAndBlocks,
+};
+use backend::config::Config;
+use backend::domain::base::Entity;
+use backend::domain::value_objects::{GraphId, LogseqDirectoryPath, PageId, PageReference};
+use backend::infrastructure::file_system::detect_layout;
+use backend::infrastructure::persistence::{
+    InMemoryPageRepository, SqliteChunkRepository, SqliteEmbeddingJobRepository, SqlitePageTimestampRepository,
+};
+use backend::mcp::McpServer;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Longest block excerpt shown in text search results, in characters
+const EXCERPT_LENGTH: usize = 80;
+
+pub async fn run(cli: Cli, config: &Config) -> Result<()> {
+    match &cli.command {
+        Command::Import { path, dry_run: true, .. } => validate(&cli, config, path).await,
+        Command::Import { path, embed, .. } => import(&cli, config, path, *embed).await,
+        Command::Sync { watch } => sync(&cli, config, *watch).await,
+        Command::Search { query, semantic, limit, results, output } => {
+            search(&cli, config, query, *semantic, *limit, results.clone(), output.as_ref()).await
+        }
+        Command::Stats => stats(&cli, config).await,
+        Command::Export { output, cards: false } => export(&cli, config, output).await,
+        Command::Export { output, cards: true } => export_cards(&cli, config, output).await,
+        Command::Reindex { rebuild: true, .. } => rebuild(&cli, config).await,
+        Command::Reindex { stale, .. } => reindex(&cli, config, *stale).await,
+        Command::Mcp { semantic } => mcp(config, *semantic).await,
+        Command::Graphs => graphs(&cli, config),
+        Command::Doctor => doctor(&cli, config).await,
+        Command::Help => Ok(()),
+    }
+}
+
+/// The configuration, with the options given on the command line taking precedence
+///
+/// `--graph` names one of the configured graphs or any graph directory.
+pub fn config(cli: &Cli) -> Result<Config> {
+    let mut config = Config::load(cli.config.as_deref()).context("Cannot load the configuration")?;
+    if let Some(graph) = &cli.graph {
+        let registry = config.graph_registry()?;
+        match registry.find(&graph.to_string_lossy()) {
+            Some(settings) => config = config.for_graph(settings),
+            None => {
+                config.graph.path = graph.clone();
+                config.graph.id = None;
+            }
+        }
+    }
+    if let Some(url) = &cli.qdrant_url {
+        config.qdrant.url = url.clone();
+    }
+    Ok(config)
+}
+
+async fn validate(cli: &Cli, config: &Config, path: &Path) -> Result<()> {
+    let directory = graph_directory(path)?;
+    let report = config
+        .import_service(InMemoryPageRepository::new())?
+        .validate_directory(directory, None)
+        .await?;
+
+    print(cli, validation_json(&report), || {
+        let mut text = format!("Checked {} files in {} ms\n", report.files_checked, report.duration_ms);
+        for issue in &report.issues {
+            let _ = writeln!(text, "  {}: {}", issue.file_path.display(), issue.kind);
+        }
+        text
+    });
+
+    if !report.is_clean() {
+        bail!("found {} problem(s)", report.issues.len());
+    }
+    Ok(())
+}
+
+/// Import a graph from disk, or from a running Logseq when `path` is its
+/// API's URL
+async fn import(cli: &Cli, config: &Config, path: &Path, embed: bool) -> Result<()> {
+    let cancellation = cancel_on_ctrl_c();
+    let repository = InMemoryPageRepository::new();
+    let mut service = config
+        .import_service(repository.clone())?
+        .with_cancellation(cancellation.clone());
+    let client = path
+        .to_str()
+        .filter(|path| path.starts_with("http://") || path.starts_with("https://"))
+        .map(|url| config.logseq_api_client(Some(url)));
+    let summary = match &client {
+        Some(client) => service.import_from_logseq_api(client, None).await?,
+        None => service.import_path(path, None).await?,
+    };
+
+    let embedding = if embed && !summary.cancelled {
+        let name = match &client {
+            Some(client) => client.graph_name().await?.unwrap_or_default(),
+            None => std::fs::canonicalize(path)?
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let service = embedding_service(config, GraphId::from_name(&name)?, cancellation).await?;
+        let pages = repository.find_all()?;
+        Some(service.embed_pages(pages.iter().collect(), &repository).await?)
+    } else {
+        None
+    };
+
+    let value = json!({
+        "import": import_json(&summary),
+        "embedding": embedding.as_ref().map(embedding_json),
+    });
+    print(cli, value, || {
+        let mut text = import_text(&summary);
+        if let Some(stats) = &embedding {
+            text.push_str(&embedding_text(stats));
+        }
+        text
+    });
+    Ok(())
+}
+
+async fn sync(cli: &Cli, config: &Config, watch: bool) -> Result<()> {
+    let directory = graph_directory(&config.graph.path)?;
+    let service = config
+        .sync_service(InMemoryPageRepository::new(), directory.clone())?
+        .with_cancellation(cancel_on_ctrl_c());
+
+    let summary = service.sync_once(None).await?;
+    print(cli, sync_json(&summary), || sync_text(&summary));
+    if !watch || summary.cancelled {
+        return Ok(());
+    }
+
+    eprintln!("Watching {} for changes (Ctrl-C to stop)", directory.as_path().display());
+    let json = cli.json;
+    let callback: SyncCallback = Arc::new(move |event| print_event(json, &event));
+    service.start_watching(Some(callback)).await?;
+    Ok(())
+}
+
+/// Serve the graph to an MCP client over stdin and stdout, keeping it in
+/// sync with the files until the client disconnects
+async fn mcp(config: &Config, semantic: bool) -> Result<()> {
+    let directory = graph_directory(&config.graph.path)?;
+    let repository = InMemoryPageRepository::new();
+    let stop = CancellationToken::new();
+    let service = config
+        .sync_service(repository.clone(), directory.clone())?
+        .with_cancellation(stop.clone());
+    let summary = service.sync_once(None).await?;
+    // stdout carries the protocol, so progress goes to stderr
+    eprint!("{}", sync_text(&summary));
+
+    let mut server = McpServer::new(repository).with_visibility(config.visibility_policy());
+    if semantic {
+        server = server.with_embedding_service(embedding_service(config, config.graph_id(), CancellationToken::new()).await?);
+    }
+
+    let watcher = tokio::spawn(async move { service.start_watching(None).await });
+    let served = server.serve_stdio().await;
+    stop.cancel();
+    watcher.await??;
+    Ok(served?)
+}
+
+async fn search(
+    cli: &Cli,
+    config: &Config,
+    query: &str,
+    semantic: bool,
+    limit: usize,
+    result_type: ResultType,
+    output: Option<&(PathBuf, ExportFormat)>,
+) -> Result<()> {
+    let (_, repository, _) = load_graph(config).await?;
+    // Exported results leave out what the visibility settings hide
+    let repository = VisiblePages::new(&repository, output.is_some().then(|| config.visibility_policy()));
+
+    let search_type = if semantic { SearchType::Semantic } else { SearchType::Traditional };
+    let request = SearchRequest::new(query)
+        .with_search_type(search_type)
+        .with_result_type(result_type);
+    let use_case = if semantic {
+        let service = embedding_service(config, config.graph_id(), CancellationToken::new()).await?;
+        SearchPagesAndBlocks::with_embedding_service(&repository, service)
+    } else {
+        SearchPagesAndBlocks::new(&repository)
+    }
+    .with_tokenizer(config.keyword_tokenizer())
+    .with_ranking(config.search.ranking);
+
+    if let Some((path, format)) = output {
+        let written = ExportSearchResults::new(&use_case).execute(request, *format, path).await?;
+        let value = json!({ "output": path, "results": written });
+        print(cli, value, || format!("Wrote {} results to {}\n", written, path.display()));
+        return Ok(());
+    }
+
+    let mut results = use_case.execute(request).await?;
+    results.truncate(limit);
+
+    let value = Value::Array(results.iter().map(search_result_json).collect());
+    print(cli, value, || {
+        if results.is_empty() {
+            return "No results\n".to_string();
+        }
+        results.iter().map(search_result_text).collect()
+    });
+    Ok(())
+}
+
+async fn stats(cli: &Cli, config: &Config) -> Result<()> {
+    let (directory, repository, summary) = load_graph(config).await?;
+    let pages = repository.find_all()?;
+
+    let journals_dir = directory.journals_dir();
+    let journal_pages = pages
+        .iter()
+        .filter(|page| match (page.file_path(), &journals_dir) {
+            (Some(file_path), Some(journals_dir)) => file_path.starts_with(journals_dir),
+            _ => false,
+        })
+        .count();
+    let blocks: usize = pages.iter().map(|page| page.all_blocks().count()).sum();
+    let urls: HashSet<&str> = pages
+        .iter()
+        .flat_map(|page| page.all_urls())
+        .map(|url| url.as_str())
+        .collect();
+    let referenced: HashSet<String> = pages
+        .iter()
+        .flat_map(|page| page.all_page_references())
+        .map(|reference| reference.title().to_lowercase())
+        .collect();
+
+    // Pages no longer in the graph keep their timestamps until they're deleted
+    let page_ids: HashSet<&PageId> = pages.iter().map(|page| page.id()).collect();
+    let timestamps = match page_timestamps(config)? {
+        Some(timestamps) => Some(
+            timestamps
+                .find_all()?
+                .into_iter()
+                .filter(|timestamps| page_ids.contains(&timestamps.page_id))
+                .collect::<Vec<_>>(),
+        ),
+        None => None,
+    };
+    let out_of_date = timestamps
+        .as_ref()
+        .map(|timestamps| timestamps.iter().filter(|timestamps| timestamps.embeddings_out_of_date()).count());
+    let last_indexed = timestamps.iter().flatten().filter_map(|timestamps| timestamps.indexed_at).max();
+    let last_embedded = timestamps.iter().flatten().filter_map(|timestamps| timestamps.embedded_at).max();
+
+    let value = json!({
+        "graph": directory.as_path(),
+        "pages": pages.len(),
+        "journal_pages": journal_pages,
+        "blocks": blocks,
+        "urls": urls.len(),
+        "referenced_pages": referenced.len(),
+        "files_failed": summary.errors.len(),
+        "load_time_ms": summary.duration_ms,
+        "last_indexed_at": last_indexed,
+        "last_embedded_at": last_embedded,
+        "embeddings_out_of_date": out_of_date,
+    });
+    print(cli, value, || {
+        let time = |time: Option<DateTime<Utc>>| time.map_or_else(|| "never".to_string(), |time| time.to_rfc3339());
+        let mut text = format!(
+            "Graph:            {}\n\
+             Pages:            {} ({} journal)\n\
+             Blocks:           {}\n\
+             Links:            {}\n\
+             Referenced pages: {}\n\
+             Failed files:     {}\n",
+            directory.as_path().display(),
+            pages.len(),
+            journal_pages,
+            blocks,
+            urls.len(),
+            referenced.len(),
+            summary.errors.len(),
+        );
+        if let Some(out_of_date) = out_of_date {
+            let _ = writeln!(text, "Last indexed:     {}\nLast embedded:    {}", time(last_indexed), time(last_embedded));
+            if out_of_date > 0 {
+                let _ = writeln!(
+                    text,
+                    "warning: embeddings of {} pages are out of date; run `logjam reindex --stale`",
+                    out_of_date
+                );
+            }
+        }
+        text
+    });
+    Ok(())
+}
+
+/// Embed every page again, then drop embeddings of blocks and pages that are gone
+///
+/// With `stale`, only pages changed since they were last embedded are, and
+/// garbage is left for a full reindex; with an embedding outbox, its jobs are
+/// run instead. Ctrl-C stops after the page being embedded, without
+/// collecting garbage.
+async fn reindex(cli: &Cli, config: &Config, stale: bool) -> Result<()> {
+    let (_, repository, _) = load_graph(config).await?;
+    if let (true, true, Some(database)) = (stale, config.database.embedding_outbox, &config.database.path) {
+        return run_outbox(cli, config, database, repository).await;
+    }
+    let mut pages = repository.find_all()?;
+    if stale {
+        let Some(timestamps) = page_timestamps(config)? else {
+            bail!("--stale needs [database] path, where the times pages were embedded are kept");
+        };
+        let out_of_date: HashSet<PageId> =
+            timestamps.find_out_of_date()?.into_iter().map(|timestamps| timestamps.page_id).collect();
+        pages.retain(|page| out_of_date.contains(page.id()));
+    }
+    let service = embedding_service(config, config.graph_id(), cancel_on_ctrl_c()).await?;
+
+    let stats = service.embed_pages(pages.iter().collect(), &repository).await?;
+    let garbage = match stats.cancelled || stale {
+        true => None,
+        false => Some(service.collect_garbage(&repository).await?),
+    };
+
+    let value = json!({
+        "embedding": embedding_json(&stats),
+        "garbage_collection": garbage.as_ref().map(garbage_json),
+    });
+    print(cli, value, || {
+        let mut text = embedding_text(&stats);
+        if let Some(garbage) = &garbage {
+            let _ = writeln!(
+                text,
+                "Removed {} stale and {} orphaned chunks, {} orphaned pages",
+                garbage.stale_chunks_removed, garbage.orphaned_chunks_removed, garbage.orphaned_pages_removed,
+            );
+        }
+        text
+    });
+    Ok(())
+}
+
+/// Run the embedding jobs queued in the outbox of `database` (see
+/// `[database] embedding_outbox`), including those of earlier runs
+async fn run_outbox(cli: &Cli, config: &Config, database: &Path, repository: InMemoryPageRepository) -> Result<()> {
+    let jobs = SqliteEmbeddingJobRepository::open(database)
+        .with_context(|| format!("Cannot open the database {}", database.display()))?;
+    let service = embedding_service(config, config.graph_id(), cancel_on_ctrl_c()).await?;
+    let queue = EmbeddingQueueService::new(
+        Arc::new(tokio::sync::Mutex::new(repository)),
+        jobs,
+        service.clone(),
+        EmbeddingQueueConfig::default(),
+    )
+    .await?;
+    let jobs_run = queue.flush().await?;
+    let failed = queue.stats().await?.failed;
+    let stats = service.cumulative_stats();
+
+    let value = json!({
+        "embedding": embedding_json(&stats),
+        "jobs_run": jobs_run,
+        "jobs_failed": failed,
+    });
+    print(cli, value, || {
+        let mut text = embedding_text(&stats);
+        let _ = writeln!(text, "Ran {} queued embedding jobs, {} failed", jobs_run, failed);
+        text
+    });
+    Ok(())
+}
+
+/// Embed every page into a new index while searches keep using the current
+/// one, then swap it in; Ctrl-C discards the new index
+async fn rebuild(cli: &Cli, config: &Config) -> Result<()> {
+    let (_, repository, _) = load_graph(config).await?;
+    let pages = repository.find_all()?;
+    let service = embedding_service(config, config.graph_id(), cancel_on_ctrl_c()).await?;
+
+    // The new chunk index is a database of its own until it's swapped in
+    let shadow_path = config.database.path.as_ref().map(|path| {
+        let mut shadow = path.clone().into_os_string();
+        shadow.push(".rebuild");
+        PathBuf::from(shadow)
+    });
+    let shadow_index = match &shadow_path {
+        Some(path) => {
+            // Left behind by a rebuild that didn't finish
+            let _ = std::fs::remove_file(path);
+            SqliteChunkRepository::open(path)
+        }
+        None => SqliteChunkRepository::open_in_memory(),
+    }
+    .context("Cannot create the new chunk index")?;
+    let report = service.rebuild(pages.iter().collect(), &repository, shadow_index).await;
+    if let Some(path) = &shadow_path {
+        let _ = std::fs::remove_file(path);
+    }
+    let report = report?;
+
+    let value = json!({
+        "embedding": embedding_json(&report.embedding),
+        "swapped": report.swapped,
+        "replayed": report.replayed,
+    });
+    print(cli, value, || {
+        let mut text = embedding_text(&report.embedding);
+        text.push_str(match report.swapped {
+            true => "Swapped in the new index\n",
+            false => "Discarded the new index; searches still use the previous one\n",
+        });
+        if report.replayed > 0 {
+            let _ = writeln!(text, "Replayed {} pages changed during the rebuild", report.replayed);
+        }
+        text
+    });
+    Ok(())
+}
+
+/// Check what the configuration points at, loading the embedding model to
+/// be sure it can be, and fail if anything is unhealthy
+async fn doctor(cli: &Cli, config: &Config) -> Result<()> {
+    let mut health = HealthService::<InMemoryPageRepository>::new().with_model_loading();
+    if let Some(database) = &config.database.path {
+        health = health.with_database(database);
+    }
+    health = match config.embedding_service(Some(config.graph_id())).await {
+        Ok(service) => health.with_embedding_service(Arc::new(service)),
+        Err(e) => health.with_embedding_error(format!("{:#}", e)),
+    };
+    let report = health.check().await;
+
+    print(cli, serde_json::to_value(&report)?, || {
+        let mut text = String::new();
+        for component in &report.components {
+            let _ = writeln!(
+                text,
+                "{:<17}{:<11}{}",
+                component.component.as_str(),
+                component.status.as_str(),
+                component.detail
+            );
+        }
+        text
+    });
+    if !report.is_healthy() {
+        bail!("Some components are unhealthy");
+    }
+    Ok(())
+}
+
+async fn export(cli: &Cli, config: &Config, output: &Path) -> Result<()> {
+    let (directory, repository, _) = load_graph(config).await?;
+    let name = directory
+        .as_path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "All pages".to_string());
+    let repository = VisiblePages::new(&repository, Some(config.visibility_policy()));
+    let pages = ExportSite::new(&repository).with_title(name).execute(output)?;
+
+    let value = json!({ "output": output, "pages": pages });
+    print(cli, value, || format!("Wrote {} pages to {}\n", pages, output.display()));
+    Ok(())
+}
+
+async fn export_cards(cli: &Cli, config: &Config, output: &Path) -> Result<()> {
+    let (_, repository, _) = load_graph(config).await?;
+    let repository = VisiblePages::new(&repository, Some(config.visibility_policy()));
+    let cards = GetFlashcards::new(&repository).execute()?;
+    std::fs::write(output, anki_tsv(&cards)).with_context(|| format!("Cannot write {}", output.display()))?;
+
+    let value = json!({ "output": output, "cards": cards.len() });
+    print(cli, value, || format!("Wrote {} cards to {}\n", cards.len(), output.display()));
+    Ok(())
+}
+
+/// List the configured graphs, the default one first
+fn graphs(cli: &Cli, config: &Config) -> Result<()> {
+    let registry = config.graph_registry()?;
+    let default = registry.default_graph().map(|graph| graph.id.clone());
+    let mut graphs: Vec<_> = registry.list().collect();
+    graphs.sort_by_key(|graph| Some(&graph.id) != default.as_ref());
+
+    let value = json!(graphs
+        .iter()
+        .map(|graph| json!({
+            "id": graph.id.as_str(),
+            "path": graph.directory,
+            "database": graph.database_path,
+            "collection": graph.collection,
+            "default": Some(&graph.id) == default.as_ref(),
+        }))
+        .collect::<Vec<_>>());
+    print(cli, value, || {
+        let mut text = String::new();
+        for graph in &graphs {
+            let marker = if Some(&graph.id) == default.as_ref() { " (default)" } else { "" };
+            let _ = writeln!(text, "{}{}  {}", graph.id, marker, graph.directory.display());
+        }
+        text
+    });
+    Ok(())
+}
+
+/// A graph directory, with the layout its config describes
+fn graph_directory(path: &Path) -> Result<LogseqDirectoryPath> {
+    // Canonical so the graph's name (and with it, its embeddings) doesn't depend on how it's written
+    let root = std::fs::canonicalize(path)
+        .with_context(|| format!("Cannot open graph {}", path.display()))?;
+    Ok(LogseqDirectoryPath::with_layout(&root, detect_layout(&root))?)
+}
+
+/// Read the configured graph directory into memory, marking the pages that
+/// changed since the last run indexed when there's a database
+async fn load_graph(config: &Config) -> Result<(LogseqDirectoryPath, InMemoryPageRepository, ImportSummary)> {
+    let directory = graph_directory(&config.graph.path)?;
+    let repository = InMemoryPageRepository::new();
+    let summary = match page_timestamps(config)? {
+        Some(timestamps) => {
+            config
+                .import_service(TimestampedPageRepository::new(repository.clone(), timestamps))?
+                .import_directory(directory.clone(), None)
+                .await?
+        }
+        None => {
+            config
+                .import_service(repository.clone())?
+                .import_directory(directory.clone(), None)
+                .await?
+        }
+    };
+    Ok((directory, repository, summary))
+}
+
+/// When pages were last indexed and embedded, kept in `[database] path`
+/// with the outbox of their embedding jobs if it's enabled
+fn page_timestamps(config: &Config) -> Result<Option<SqlitePageTimestampRepository>> {
+    let Some(path) = &config.database.path else {
+        return Ok(None);
+    };
+    let mut timestamps = SqlitePageTimestampRepository::open(path);
+    if config.database.embedding_outbox {
+        timestamps = timestamps.and_then(SqlitePageTimestampRepository::with_embedding_outbox);
+    }
+    let timestamps = timestamps.with_context(|| format!("Cannot open the database {}", path.display()))?;
+    Ok(Some(timestamps))
+}
+
+async fn embedding_service(
+    config: &Config,
+    graph_id: GraphId,
+    cancellation: CancellationToken,
+) -> Result<Arc<EmbeddingService>> {
+    let service = config
+        .embedding_service(Some(graph_id))
+        .await
+        .context("Could not start the embedding service (is Qdrant running?)")?;
+    Ok(Arc::new(service.with_cancellation(cancellation)))
+}
+
+/// A token that is cancelled on the first Ctrl-C, so long operations can
+/// stop cleanly and report what they did
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    tokio::spawn({
+        let token = token.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                token.cancel();
+            }
+        }
+    });
+    token
+}
+
+/// Print `value` as JSON with `--json`, otherwise the text `text` builds
+fn print(cli: &Cli, value: Value, text: impl FnOnce() -> String) {
+    if cli.json {
+        println!("{:#}", value);
+    } else {
+        print!("{}", text());
+    }
+}
+
+/// Print a watcher event as it happens; with `--json`, one object per line
+fn print_event(json: bool, event: &SyncEvent) {
+    let (kind, file_path, detail) = match event {
+        SyncEvent::FileCreated { file_path } => ("created", file_path, None),
+        SyncEvent::FileUpdated { file_path } => ("updated", file_path, None),
+        SyncEvent::FileDeleted { file_path } => ("deleted", file_path, None),
+        SyncEvent::FileWritten { file_path } => ("written", file_path, None),
+        SyncEvent::FileRenamed { from, to } => ("renamed", to, Some(format!("from {}", from.display()))),
+        SyncEvent::Error { file_path, error } => ("error", file_path, Some(error.clone())),
+        SyncEvent::Conflict(conflict) => (
+            "conflict",
+            &conflict.file_path,
+            Some("changed on disk and in the repository".to_string()),
+        ),
+        SyncEvent::DuplicateTitle(resolution) => (
+            "duplicate_title",
+            &resolution.file_path,
+            Some(format!("\"{}\" is taken by {}", resolution.title, resolution.kept_by.display())),
+        ),
+        SyncEvent::SyncStarted | SyncEvent::SyncCompleted { .. } | SyncEvent::Progress { .. } => return,
+    };
+
+    if json {
+        println!("{}", json!({ "event": kind, "file_path": file_path, "detail": detail }));
+    } else {
+        match detail {
+            Some(detail) => println!("{:<16}{} ({})", kind, file_path.display(), detail),
+            None => println!("{:<16}{}", kind, file_path.display()),
+        }
+    }
+}
+
+fn validation_json(report: &ValidationReport) -> Value {
+    json!({
+        "files_checked": report.files_checked,
+        "issues": report
+            .issues
+            .iter()
+            .map(|issue| json!({ "file_path": issue.file_path, "issue": issue.kind.to_string() }))
+            .collect::<Vec<_>>(),
+        "duration_ms": report.duration_ms,
+    })
+}
+
+fn import_json(summary: &ImportSummary) -> Value {
+    json!({
+        "total_files": summary.total_files,
+        "pages_imported": summary.pages_imported,
+        "files_skipped": summary.files_skipped,
+        "success_rate": summary.success_rate(),
+        "duplicate_titles": summary
+            .duplicate_titles
+            .iter()
+            .map(|duplicate| json!({
+                "file_path": duplicate.file_path,
+                "title": duplicate.title,
+                "kept_by": duplicate.kept_by,
+                "action": duplicate_action(&duplicate.action),
+            }))
+            .collect::<Vec<_>>(),
+        "errors": errors_json(&summary.errors),
+        "duration_ms": summary.duration_ms,
+        "cancelled": summary.cancelled,
+    })
+}
+
+fn import_text(summary: &ImportSummary) -> String {
+    let mut text = format!(
+        "Imported {} pages from {} files in {} ms\n",
+        summary.pages_imported, summary.total_files, summary.duration_ms
+    );
+    if summary.cancelled {
+        text.push_str("Cancelled before every file was imported\n");
+    }
+    if !summary.duplicate_titles.is_empty() {
+        text.push_str("Duplicate titles:\n");
+        for duplicate in &summary.duplicate_titles {
+            let _ = writeln!(
+                text,
+                "  {}: \"{}\" is taken by {}; {}",
+                duplicate.file_path.display(),
+                duplicate.title,
+                duplicate.kept_by.display(),
+                duplicate_action(&duplicate.action)
+            );
+        }
+    }
+    text.push_str(&errors_text(&summary.errors));
+    text
+}
+
+fn duplicate_action(action: &DuplicateTitleAction) -> String {
+    match action {
+        DuplicateTitleAction::Renamed { title } => format!("renamed to \"{}\"", title),
+        DuplicateTitleAction::Merged => "merged".to_string(),
+        DuplicateTitleAction::Rejected => "rejected".to_string(),
+    }
+}
+
+fn sync_json(summary: &SyncSummary) -> Value {
+    json!({
+        "files_created": summary.files_created,
+        "files_updated": summary.files_updated,
+        "files_deleted": summary.files_deleted,
+        "files_renamed": summary.files_renamed,
+        "files_unchanged": summary.files_unchanged,
+        "conflicts": summary.conflicts,
+        "errors": errors_json(&summary.errors),
+        "cancelled": summary.cancelled,
+    })
+}
+
+fn sync_text(summary: &SyncSummary) -> String {
+    let mut text = format!(
+        "Synced {} files ({} unchanged)\n",
+        summary.files_created + summary.files_updated + summary.files_deleted + summary.files_renamed,
+        summary.files_unchanged
+    );
+    if summary.cancelled {
+        text.push_str("Cancelled; the rest is synced next time\n");
+    }
+    text.push_str(&errors_text(&summary.errors));
+    text
+}
+
+fn errors_json(errors: &[(std::path::PathBuf, String)]) -> Value {
+    errors
+        .iter()
+        .map(|(file_path, error)| json!({ "file_path": file_path, "error": error }))
+        .collect()
+}
+
+fn errors_text(errors: &[(std::path::PathBuf, String)]) -> String {
+    let mut text = String::new();
+    if !errors.is_empty() {
+        text.push_str("Errors:\n");
+        for (file_path, error) in errors {
+            let _ = writeln!(text, "  {}: {}", file_path.display(), error);
+        }
+    }
+    text
+}
+
+fn embedding_json(stats: &EmbeddingStats) -> Value {
+    json!({
+        "pages_embedded": stats.pages_embedded,
+        "chunks_stored": stats.chunks_stored,
+        "chunks_skipped": stats.chunks_skipped,
+        "errors": stats.errors,
+        "duration_ms": stats.total_time.as_millis() as u64,
+        "cancelled": stats.cancelled,
+    })
+}
+
+fn embedding_text(stats: &EmbeddingStats) -> String {
+    format!(
+        "{} {} pages ({} chunks stored, {} errors) in {:.1} s\n",
+        if stats.cancelled { "Cancelled after embedding" } else { "Embedded" },
+        stats.pages_embedded,
+        stats.chunks_stored,
+        stats.errors,
+        stats.total_time.as_secs_f64()
+    )
+}
+
+fn garbage_json(report: &GarbageCollectionReport) -> Value {
+    json!({
+        "chunks_scanned": report.chunks_scanned,
+        "orphaned_chunks_removed": report.orphaned_chunks_removed,
+        "stale_chunks_removed": report.stale_chunks_removed,
+        "orphaned_pages_removed": report.orphaned_pages_removed,
+    })
+}
+
+fn search_result_json(result: &SearchResult) -> Value {
+    let titles = |references: &[PageReference]| -> Vec<String> {
+        references.iter().map(|reference| reference.title().to_string()).collect()
+    };
+    match &result.item {
+        SearchItem::Page(page) => json!({
+            "type": "page",
+            "score": result.score,
+            "page_id": page.page_id.as_str(),
+            "title": page.title,
+            "block_count": page.block_count,
+        }),
+        SearchItem::Block(block) => json!({
+            "type": "block",
+            "score": result.score,
+            "block_id": block.block_id.as_str(),
+            "content": block.content,
+            "page_id": block.page_id.as_str(),
+            "page_title": block.page_title,
+            "hierarchy_path": block.hierarchy_path,
+            "block_path": block.block_path.as_ref().map(ToString::to_string),
+            "related_pages": titles(&block.related_pages),
+        }),
+        SearchItem::Url(url) => json!({
+            "type": "url",
+            "score": result.score,
+            "url": url.url.as_str(),
+            "block_id": url.containing_block_id.as_str(),
+            "page_id": url.page_id.as_str(),
+            "page_title": url.page_title,
+        }),
+        SearchItem::Archive(archive) => json!({
+            "type": "archive",
+            "score": result.score,
+            "url": archive.url.as_str(),
+            "title": archive.title,
+            "excerpt": archive.excerpt,
+            "archived_at": archive.archived_at.to_rfc3339(),
+        }),
+    }
+}
+
+fn search_result_text(result: &SearchResult) -> String {
+    match &result.item {
+        SearchItem::Page(page) => format!("{:>6.2}  page   {}\n", result.score.value(), page.title),
+        SearchItem::Block(block) => format!(
+            "{:>6.2}  block  {}: {}\n",
+            result.score.value(),
+            block.page_title,
+            excerpt(&block.content)
+        ),
+        SearchItem::Url(url) => format!(
+            "{:>6.2}  url    {} ({})\n",
+            result.score.value(),
+            url.url.as_str(),
+            url.page_title
+        ),
+        SearchItem::Archive(archive) => format!(
+            "{:>6.2}  web    {}: {}\n",
+            result.score.value(),
+            archive.title.as_deref().unwrap_or(archive.url.as_str()),
+            excerpt(&archive.excerpt)
+        ),
+    }
+}
+
+/// The first line of a block, shortened to fit a terminal line
+fn excerpt(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default();
+    match line.char_indices().nth(EXCERPT_LENGTH) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
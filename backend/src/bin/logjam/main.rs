@@ -0,0 +1,43 @@
+/// The `logjam` command-line tool: import, sync, search and embed a Logseq graph
+mod args;
+mod commands;
+mod telemetry;
+
+use args::{Command, USAGE};
+use std::process::ExitCode;
+use telemetry::Telemetry;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = match args::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if cli.command == Command::Help {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    // The configuration says where spans go, so it's loaded before tracing starts
+    let started = commands::config(&cli).and_then(|config| Ok((Telemetry::init(&config.telemetry)?, config)));
+    let (telemetry, config) = match started {
+        Ok(started) => started,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let code = match commands::run(cli, &config).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    };
+    telemetry.shutdown();
+    code
+}
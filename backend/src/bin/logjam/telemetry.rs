@@ -0,0 +1,61 @@
+/// Where `tracing` output goes: logs to stderr, and spans to an OpenTelemetry
+/// collector when one is configured
+use anyhow::Result;
+use backend::config::TelemetryConfig;
+#[cfg(feature = "otel")]
+use backend::infrastructure::telemetry::OtlpTracing;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// The installed subscriber's exporters, flushed by [`Telemetry::shutdown`]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    otlp: Option<OtlpTracing>,
+}
+
+impl Telemetry {
+    /// Install the global subscriber
+    ///
+    /// Logs go to stderr so `--json` output stays parseable; LOGJAM_LOG=info
+    /// shows progress. Spans are exported over OTLP if `[telemetry]` names an
+    /// endpoint and the `otel` feature is enabled.
+    pub fn init(config: &TelemetryConfig) -> Result<Self> {
+        let logs = tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_filter(EnvFilter::try_from_env("LOGJAM_LOG").unwrap_or_else(|_| EnvFilter::new("warn")));
+        let registry = tracing_subscriber::registry().with(logs);
+
+        #[cfg(feature = "otel")]
+        {
+            let otlp = match &config.otlp_endpoint {
+                Some(endpoint) => Some(OtlpTracing::start(endpoint, &config.service_name)?),
+                None => None,
+            };
+            let spans = match &otlp {
+                Some(otlp) => Some(otlp.layer().with_filter(EnvFilter::try_new(&config.filter)?)),
+                None => None,
+            };
+            registry.with(spans).init();
+            Ok(Self { otlp })
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            registry.init();
+            if config.otlp_endpoint.is_some() {
+                tracing::warn!("Spans aren't exported: logjam was built without the `otel` feature");
+            }
+            Ok(Self {})
+        }
+    }
+
+    /// Export the spans still queued
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(otlp) = self.otlp {
+            if let Err(e) = otlp.shutdown() {
+                eprintln!("warning: {}", e);
+            }
+        }
+    }
+}
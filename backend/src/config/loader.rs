@@ -0,0 +1,186 @@
+/// Reading configuration files and `LOGJAM_*` environment overrides
+use serde_json::{Map, Number, Value};
+use std::path::PathBuf;
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Prefix of the environment variables that override configuration values
+pub const ENV_PREFIX: &str = "LOGJAM_";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid TOML: {0}")]
+    Parse(String),
+
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+
+    #[error("Cannot open the database: {0}")]
+    Database(#[from] crate::domain::base::DomainError),
+}
+
+/// A TOML document as JSON, so serde can read it into the configuration
+///
+/// Dates and times become RFC 3339 strings.
+pub fn toml_to_json(text: &str) -> Result<Value, ConfigError> {
+    let document: DocumentMut = text.parse().map_err(|e: toml_edit::TomlError| ConfigError::Parse(e.to_string()))?;
+    table_to_json(document.as_table())
+}
+
+fn table_to_json(table: &Table) -> Result<Value, ConfigError> {
+    let mut object = Map::new();
+    for (key, item) in table.iter() {
+        if let Some(value) = item_to_json(item)? {
+            object.insert(key.to_string(), value);
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+fn item_to_json(item: &Item) -> Result<Option<Value>, ConfigError> {
+    Ok(match item {
+        Item::None => None,
+        Item::Value(value) => Some(value_to_json(value)?),
+        Item::Table(table) => Some(table_to_json(table)?),
+        Item::ArrayOfTables(tables) => Some(Value::Array(tables.iter().map(table_to_json).collect::<Result<_, _>>()?)),
+    })
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Result<Value, ConfigError> {
+    use toml_edit::Value as Toml;
+
+    Ok(match value {
+        Toml::String(s) => Value::String(s.value().clone()),
+        Toml::Integer(i) => Value::from(*i.value()),
+        Toml::Float(f) => Number::from_f64(*f.value())
+            .map(Value::Number)
+            .ok_or_else(|| ConfigError::Invalid(format!("{} is not a usable number", f.value())))?,
+        Toml::Boolean(b) => Value::Bool(*b.value()),
+        Toml::Datetime(d) => Value::String(d.value().to_string()),
+        Toml::Array(array) => Value::Array(array.iter().map(value_to_json).collect::<Result<_, _>>()?),
+        Toml::InlineTable(table) => {
+            let mut object = Map::new();
+            for (key, value) in table.iter() {
+                object.insert(key.to_string(), value_to_json(value)?);
+            }
+            Value::Object(object)
+        }
+    })
+}
+
+/// Copy every value of `overlay` into `base`, merging tables key by key
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Apply `LOGJAM_<SECTION>_<KEY>` variables to the sections of `config`
+///
+/// `LOGJAM_QDRANT_API_KEY` sets `api_key` in `[qdrant]`. Variables naming no
+/// section, such as `LOGJAM_LOG`, are left alone. Values are read as TOML
+/// literals (`8`, `true`, `0.5`), and as plain strings when they aren't one
+/// or the key already holds a string; quote a value (`'"1234"'`) to force a
+/// string.
+pub fn apply_env(config: &mut Value, vars: impl IntoIterator<Item = (String, String)>) -> Result<(), ConfigError> {
+    for (name, raw) in vars {
+        let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let Some((section, key)) = rest.to_lowercase().split_once('_').map(|(s, k)| (s.to_string(), k.to_string()))
+        else {
+            continue;
+        };
+        let Some(Value::Object(section)) = config.get_mut(&section) else {
+            continue;
+        };
+        let value = match section.get(&key) {
+            Some(Value::String(_)) => Value::String(raw),
+            _ => env_value(&raw)?,
+        };
+        section.insert(key, value);
+    }
+    Ok(())
+}
+
+fn env_value(raw: &str) -> Result<Value, ConfigError> {
+    match raw.parse::<toml_edit::Value>() {
+        Ok(value) => value_to_json(&value),
+        Err(_) => Ok(Value::String(raw.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_toml_becomes_json() {
+        let value = toml_to_json(
+            r#"
+            [qdrant]
+            url = "http://qdrant:6334"
+            timeout_secs = 10
+
+            [embeddings]
+            mmr_lambda = 0.5
+            hybrid_search = true
+
+            [[webhooks]]
+            url = "https://example.com/hook"
+            events = ["PageCreated"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "qdrant": { "url": "http://qdrant:6334", "timeout_secs": 10 },
+                "embeddings": { "mmr_lambda": 0.5, "hybrid_search": true },
+                "webhooks": [{ "url": "https://example.com/hook", "events": ["PageCreated"] }],
+            })
+        );
+        assert!(matches!(toml_to_json("[qdrant"), Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_env_overrides_sections() {
+        let mut config = json!({
+            "qdrant": { "url": "http://localhost:6334", "api_key": null },
+            "sync": { "debounce_ms": null },
+        });
+        let vars = [
+            ("LOGJAM_QDRANT_URL", "1234"),
+            ("LOGJAM_QDRANT_API_KEY", "secret"),
+            ("LOGJAM_SYNC_DEBOUNCE_MS", "250"),
+            ("LOGJAM_LOG", "debug"),
+            ("LOGJAM_WEBHOOKS_URL", "https://example.com"),
+            ("HOME", "/root"),
+        ];
+        apply_env(&mut config, vars.map(|(k, v)| (k.to_string(), v.to_string()))).unwrap();
+        assert_eq!(
+            config,
+            json!({
+                "qdrant": { "url": "1234", "api_key": "secret" },
+                "sync": { "debounce_ms": 250 },
+            })
+        );
+    }
+}
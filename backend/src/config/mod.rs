@@ -0,0 +1,947 @@
+/// Settings of every service, read from a TOML file and `LOGJAM_*` environment variables
+///
+/// A [`Config`] is loaded once and then builds the services it configures:
+///
+/// ```toml
+/// [graph]
+/// path = "~/notes"
+///
+/// [[graphs]]
+/// id = "work"
+/// path = "~/work-notes"
+///
+/// [database]
+/// path = "~/.local/share/logjam/logjam.db"
+/// embedding_outbox = true
+///
+/// [qdrant]
+/// url = "http://localhost:6334"
+///
+/// [chunking]
+/// strategy = "sentences"
+/// max_tokens = 256
+///
+/// [preprocessing]
+/// stages = ["expand_page_references", "expand_tags", "add_hierarchy"]
+/// hierarchy_depth = 3
+/// skip_journal_templates = true
+///
+/// [search]
+/// language = "english"
+///
+/// [search.ranking]
+/// title_weight = 1.2
+/// recency_boost = 0.2
+/// journal_template_weight = 0.5
+///
+/// [sync]
+/// debounce_ms = 500
+///
+/// [server]
+/// address = "127.0.0.1:3000"
+/// tokens = [{ token = "s3cret", scopes = ["read"] }]
+///
+/// [[webhooks]]
+/// url = "https://example.com/hooks/logjam"
+/// events = ["PageCreated", "PageUpdated"]
+///
+/// [telemetry]
+/// otlp_endpoint = "http://localhost:4317"
+///
+/// [logseq]
+/// api_token = "s3cret"
+///
+/// [visibility]
+/// public_only = true
+/// embeddings = true
+/// ```
+///
+/// Every key is optional; unset tuning knobs keep the defaults of the service
+/// they configure. Environment variables override the file, e.g.
+/// `LOGJAM_QDRANT_URL` or `LOGJAM_SYNC_DEBOUNCE_MS` (see [`loader::apply_env`]).
+pub mod loader;
+
+pub use loader::ConfigError;
+
+use crate::application::repositories::PageRepository;
+use crate::application::use_cases::RankingConfig;
+use crate::application::services::{
+    DuplicateTitlePolicy, EmbeddingService, EmbeddingServiceConfig, GraphRegistry, GraphSettings, ImportService,
+    SyncError, SyncService, VisibilityPolicy, WebhookDispatcher, WebhookEndpoint, WebhookEventType,
+};
+use crate::domain::value_objects::{EmbeddingModel, GraphId, LogseqDirectoryPath};
+use crate::infrastructure::embeddings::{
+    AddHierarchy, ChunkingStrategy, ExecutionProvider, ExpandPageReferences, ExpandTags, FastEmbedOptions,
+    PreprocessPipeline, QdrantConnectionConfig, StripTaskMarkers,
+};
+use crate::infrastructure::logseq_api::{LogseqApiClient, DEFAULT_LOGSEQ_API_URL};
+use crate::infrastructure::persistence::{
+    SqliteChunkRepository, SqliteImportCheckpointRepository, SqlitePageTimestampRepository,
+};
+use crate::infrastructure::text::{KeywordTokenizer, Language};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File read by [`Config::load`] when no other file is given
+pub const DEFAULT_CONFIG_FILE: &str = "logjam.toml";
+
+/// Environment variable naming the configuration file
+pub const CONFIG_FILE_VAR: &str = "LOGJAM_CONFIG";
+
+/// ID of the `[graph]` graph when it has none and its directory has no usable name
+const FALLBACK_GRAPH_ID: &str = "default";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub graph: GraphConfig,
+    /// More graphs served besides `[graph]`
+    pub graphs: Vec<NamedGraphConfig>,
+    pub database: DatabaseConfig,
+    pub qdrant: QdrantConfig,
+    pub embeddings: EmbeddingsConfig,
+    pub chunking: ChunkingConfig,
+    pub preprocessing: PreprocessingConfig,
+    pub search: SearchConfig,
+    pub import: ImportConfig,
+    pub sync: SyncConfig,
+    pub server: ServerConfig,
+    pub webhooks: Vec<WebhookConfig>,
+    pub telemetry: TelemetryConfig,
+    pub logseq: LogseqConfig,
+    pub visibility: VisibilityConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphConfig {
+    /// Logseq graph directory
+    pub path: PathBuf,
+    /// Namespaces the graph's embeddings; derived from the directory's name when unset
+    pub id: Option<GraphId>,
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        GraphConfig {
+            path: PathBuf::from("."),
+            id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamedGraphConfig {
+    pub id: GraphId,
+    pub path: PathBuf,
+    /// SQLite file of the graph; `<id>.sqlite` next to `[database] path` when unset
+    #[serde(default)]
+    pub database: Option<PathBuf>,
+    /// Vector collection name, before it's namespaced with the ID; `[qdrant] collection` when unset
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// Preprocessing of the graph's blocks; `[preprocessing]` when unset
+    #[serde(default)]
+    pub preprocessing: Option<PreprocessingConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// SQLite file for import checkpoints and the local chunk index; `None`
+    /// keeps neither between runs
+    pub path: Option<PathBuf>,
+    /// Queue each page change's embedding job in the database, in the same
+    /// transaction that records the change, for the embedding worker to run
+    pub embedding_outbox: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QdrantConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    /// Connect over TLS even if the URL says `http://`
+    pub tls: bool,
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    /// Collection name, before it's namespaced per graph
+    pub collection: Option<String>,
+    /// Recreate collections whose vector layout doesn't match the model
+    pub recreate_on_mismatch: bool,
+}
+
+impl Default for QdrantConfig {
+    fn default() -> Self {
+        let connection = QdrantConnectionConfig::default();
+        QdrantConfig {
+            url: connection.url,
+            api_key: None,
+            tls: connection.tls,
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            collection: None,
+            recreate_on_mismatch: connection.recreate_on_mismatch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmbeddingsConfig {
+    /// Model name, with or without its organization
+    pub model: String,
+    /// `cpu`, `cuda` or `directml`
+    pub execution_provider: String,
+    /// GPU used by the `cuda` and `directml` providers
+    pub device_id: i32,
+    pub threads: Option<usize>,
+    /// Where downloaded models are cached
+    pub cache_dir: Option<PathBuf>,
+    pub batch_size: Option<usize>,
+    pub page_embeddings: Option<bool>,
+    /// Also embed archived web pages, for searching them semantically
+    pub archive_embeddings: Option<bool>,
+    pub hybrid_search: Option<bool>,
+    pub mmr_lambda: Option<f32>,
+    pub score_threshold: Option<f32>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        EmbeddingsConfig {
+            model: EmbeddingModel::default().model_name().to_string(),
+            execution_provider: "cpu".to_string(),
+            device_id: 0,
+            threads: None,
+            cache_dir: None,
+            batch_size: None,
+            page_embeddings: None,
+            archive_embeddings: None,
+            hybrid_search: None,
+            mmr_lambda: None,
+            score_threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChunkingConfig {
+    pub strategy: Option<ChunkingStrategy>,
+    /// Maximum model tokens per chunk; 0 sizes chunks in words instead
+    pub max_tokens: Option<usize>,
+    pub overlap_tokens: Option<usize>,
+    pub max_words: Option<usize>,
+    pub overlap_words: Option<usize>,
+    /// Embed blocks with a summary of their parent and children
+    pub contextual: Option<bool>,
+}
+
+/// What blocks go through before they are chunked and embedded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreprocessingConfig {
+    /// Stages run in order, out of `strip_task_markers`,
+    /// `expand_page_references`, `expand_tags` and `add_hierarchy`; all four
+    /// when unset
+    pub stages: Option<Vec<String>>,
+    /// Ancestors `add_hierarchy` includes
+    pub hierarchy_depth: Option<usize>,
+    /// Whether `add_hierarchy` includes the page title
+    pub page_title: Option<bool>,
+    /// Leave out blocks repeated on many journal pages by a template, like
+    /// "Morning routine", so they don't crowd out what's written under them
+    pub skip_journal_templates: Option<bool>,
+}
+
+impl PreprocessingConfig {
+    /// The configured stages; custom stages can only be added in code
+    pub fn pipeline(&self) -> Result<PreprocessPipeline, ConfigError> {
+        let mut add_hierarchy = AddHierarchy::new();
+        if let Some(depth) = self.hierarchy_depth {
+            add_hierarchy = add_hierarchy.with_depth(depth);
+        }
+        if self.page_title == Some(false) {
+            add_hierarchy = add_hierarchy.without_page_title();
+        }
+        let Some(stages) = &self.stages else {
+            return Ok(PreprocessPipeline::empty()
+                .with_stage(StripTaskMarkers::new())
+                .with_stage(ExpandPageReferences::new())
+                .with_stage(ExpandTags::new())
+                .with_stage(add_hierarchy));
+        };
+
+        let mut pipeline = PreprocessPipeline::empty();
+        for stage in stages {
+            pipeline = match stage.as_str() {
+                "strip_task_markers" => pipeline.with_stage(StripTaskMarkers::new()),
+                "expand_page_references" => pipeline.with_stage(ExpandPageReferences::new()),
+                "expand_tags" => pipeline.with_stage(ExpandTags::new()),
+                "add_hierarchy" => pipeline.with_stage(add_hierarchy.clone()),
+                other => return Err(ConfigError::Invalid(format!("Unknown preprocessing stage '{}'", other))),
+            };
+        }
+        Ok(pipeline)
+    }
+}
+
+/// Keyword search, and the sparse vectors of hybrid semantic search
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Language of the notes, e.g. `english`; without one, words are only
+    /// lowercased
+    pub language: Option<Language>,
+    /// Match words by their stem in `language`, so "embedding" finds
+    /// "embeddings" (on by default when a language is set)
+    pub stemming: Option<bool>,
+    /// Leave `language`'s most common words out of queries and the index
+    /// (on by default when a language is set)
+    pub stop_words: Option<bool>,
+    /// Weights of keyword search scoring; unset ones keep their defaults
+    pub ranking: RankingConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportConfig {
+    /// Files parsed at the same time
+    pub concurrency: Option<usize>,
+    pub batch_size: Option<usize>,
+    pub memory_limit_mb: Option<usize>,
+    pub max_block_length: Option<usize>,
+    pub duplicate_titles: Option<DuplicateTitlePolicy>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    pub debounce_ms: Option<u64>,
+    /// Seconds between full syncs while watching; 0 disables them
+    pub reconcile_interval_secs: Option<u64>,
+    pub batch_size: Option<usize>,
+    pub duplicate_titles: Option<DuplicateTitlePolicy>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub address: String,
+    /// Static bearer tokens; with none and no introspection, the API is open
+    pub tokens: Vec<TokenConfig>,
+    /// OAuth token introspection endpoint for tokens not listed in `tokens`
+    pub introspection: Option<IntrospectionConfig>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            address: "127.0.0.1:3000".to_string(),
+            tokens: Vec::new(),
+            introspection: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    pub token: String,
+    /// `read` and/or `write`
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntrospectionConfig {
+    pub endpoint: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Key deliveries are signed with
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Event types sent to the endpoint; all of them when empty
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+/// Exporting spans to an OpenTelemetry collector, with the `otel` feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// OTLP/gRPC endpoint of the collector; spans aren't exported when unset
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` the spans are reported under
+    pub service_name: String,
+    /// Spans exported, as a `tracing` filter like `LOGJAM_LOG`'s; the
+    /// default leaves out dependencies, including the exporter's own spans
+    pub filter: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            otlp_endpoint: None,
+            service_name: "logjam".to_string(),
+            filter: "backend=info".to_string(),
+        }
+    }
+}
+
+/// The HTTP API of a running Logseq app, an import source besides files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogseqConfig {
+    pub api_url: String,
+    /// One of the tokens set up in Logseq's API server settings
+    pub api_token: Option<String>,
+}
+
+impl Default for LogseqConfig {
+    fn default() -> Self {
+        LogseqConfig {
+            api_url: DEFAULT_LOGSEQ_API_URL.to_string(),
+            api_token: None,
+        }
+    }
+}
+
+/// Pages and blocks left out of exports and API responses, besides those
+/// marked private (see [`VisibilityPolicy`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VisibilityConfig {
+    /// Show only pages marked `public:: true`
+    pub public_only: bool,
+    /// Leave the hidden pages and blocks out of embeddings too
+    pub embeddings: bool,
+}
+
+impl Config {
+    /// Load the configuration file, then apply the environment's overrides
+    ///
+    /// The file is `path` if given, otherwise the one `LOGJAM_CONFIG` names,
+    /// otherwise `logjam.toml` in the working directory if there is one.
+    /// Without a file, the defaults and the environment make the configuration.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_FILE_VAR).map(PathBuf::from))
+            .or_else(|| Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()));
+        let text = match path {
+            Some(path) => {
+                Some(std::fs::read_to_string(&path).map_err(|source| ConfigError::Read { path, source })?)
+            }
+            None => None,
+        };
+        Self::from_sources(text.as_deref(), std::env::vars())
+    }
+
+    /// Read a TOML document, without environment overrides
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        Self::from_sources(Some(text), std::iter::empty())
+    }
+
+    /// The defaults, overridden by a TOML document and then by `LOGJAM_*` variables
+    pub fn from_sources(
+        toml: Option<&str>,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut value = serde_json::to_value(Config::default()).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        if let Some(toml) = toml {
+            loader::merge(&mut value, loader::toml_to_json(toml)?);
+        }
+        loader::apply_env(&mut value, env)?;
+
+        let config: Config = serde_json::from_value(value).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the values serde can't, such as model and event names
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.embedding_service_config(None)?;
+        for graph in &self.graphs {
+            if let Some(preprocessing) = &graph.preprocessing {
+                preprocessing.pipeline()?;
+            }
+        }
+        self.webhook_endpoints()?;
+        self.search
+            .ranking
+            .validate()
+            .map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        let mut ids: Vec<&GraphId> = self.graphs.iter().map(|graph| &graph.id).chain(&self.graph.id).collect();
+        ids.sort_by_key(|id| id.as_str());
+        if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(ConfigError::Invalid(format!("Graph '{}' is configured twice", pair[0])));
+        }
+        tracing_subscriber::EnvFilter::try_new(&self.telemetry.filter)
+            .map_err(|e| ConfigError::Invalid(format!("Invalid telemetry filter: {}", e)))?;
+        #[cfg(feature = "server")]
+        self.api_auth()?;
+        Ok(())
+    }
+
+    /// The `[graph]` graph, as the default, and the `[[graphs]]`
+    pub fn graph_registry(&self) -> Result<GraphRegistry, ConfigError> {
+        let mut registry = GraphRegistry::new();
+        let mut main = GraphSettings::new(self.graph_id(), self.graph.path.clone());
+        main.database_path = self.database.path.clone();
+        main.collection = self.qdrant.collection.clone();
+        registry.add(main).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+
+        for graph in &self.graphs {
+            let mut settings = GraphSettings::new(graph.id.clone(), graph.path.clone());
+            settings.database_path = graph.database.clone().or_else(|| {
+                let shared = self.database.path.as_ref()?;
+                Some(shared.with_file_name(graph.id.database_file_name()))
+            });
+            settings.collection = graph.collection.clone().or_else(|| self.qdrant.collection.clone());
+            registry.add(settings).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        }
+        Ok(registry)
+    }
+
+    /// ID of the `[graph]` graph: its `id`, else its directory's name
+    pub fn graph_id(&self) -> GraphId {
+        if let Some(id) = &self.graph.id {
+            return id.clone();
+        }
+        let path = std::fs::canonicalize(&self.graph.path).unwrap_or_else(|_| self.graph.path.clone());
+        path.file_name()
+            .and_then(|name| GraphId::from_name(&name.to_string_lossy()).ok())
+            .unwrap_or_else(|| GraphId::new(FALLBACK_GRAPH_ID).expect("valid graph ID"))
+    }
+
+    /// This configuration with one of the registry's graphs as `[graph]`, so
+    /// the services it builds read and write that graph's data
+    pub fn for_graph(&self, graph: &GraphSettings) -> Config {
+        let mut config = self.clone();
+        config.graph = GraphConfig {
+            path: graph.directory.clone(),
+            id: Some(graph.id.clone()),
+        };
+        config.database.path = graph.database_path.clone();
+        config.qdrant.collection = graph.collection.clone();
+        let named = self.graphs.iter().find(|named| named.id == graph.id);
+        if let Some(preprocessing) = named.and_then(|named| named.preprocessing.clone()) {
+            config.preprocessing = preprocessing;
+        }
+        config
+    }
+
+    /// Settings of the embedding service of a graph (or of the shared collection)
+    pub fn embedding_service_config(&self, graph_id: Option<GraphId>) -> Result<EmbeddingServiceConfig, ConfigError> {
+        let embeddings = &self.embeddings;
+        let chunking = &self.chunking;
+        let mut config = EmbeddingServiceConfig {
+            model: EmbeddingModel::from_name(&embeddings.model)
+                .ok_or_else(|| ConfigError::Invalid(format!("Unknown embedding model '{}'", embeddings.model)))?,
+            fastembed: self.fastembed_options()?,
+            qdrant: self.qdrant_connection(),
+            graph_id,
+            keyword_tokenizer: self.keyword_tokenizer(),
+            preprocessing: self.preprocessing.pipeline()?,
+            ..EmbeddingServiceConfig::default()
+        };
+        if let Some(collection) = &self.qdrant.collection {
+            config.collection_name = collection.clone();
+        }
+        if let Some(strategy) = chunking.strategy {
+            config.chunking = strategy;
+        }
+        if let Some(max_tokens) = chunking.max_tokens {
+            config.max_tokens_per_chunk = Some(max_tokens).filter(|&tokens| tokens > 0);
+        }
+        set(&mut config.overlap_tokens, chunking.overlap_tokens);
+        set(&mut config.max_words_per_chunk, chunking.max_words);
+        set(&mut config.overlap_words, chunking.overlap_words);
+        set(&mut config.contextual_chunks, chunking.contextual);
+        set(&mut config.batch_size, embeddings.batch_size);
+        set(&mut config.page_embeddings, embeddings.page_embeddings);
+        set(&mut config.archive_embeddings, embeddings.archive_embeddings);
+        set(&mut config.hybrid_search, embeddings.hybrid_search);
+        set(&mut config.skip_journal_templates, self.preprocessing.skip_journal_templates);
+        if self.visibility.embeddings {
+            config.visibility = Some(self.visibility_policy());
+        }
+        config.mmr_lambda = embeddings.mmr_lambda.or(config.mmr_lambda);
+        config.score_threshold = embeddings.score_threshold.or(config.score_threshold);
+        Ok(config)
+    }
+
+    /// What exports and the API may show; the API applies it through
+    /// `ApiState::with_visibility`
+    pub fn visibility_policy(&self) -> VisibilityPolicy {
+        VisibilityPolicy::new().with_public_only(self.visibility.public_only)
+    }
+
+    /// How keyword search and hybrid collections split text into terms
+    pub fn keyword_tokenizer(&self) -> KeywordTokenizer {
+        let search = &self.search;
+        let mut tokenizer = KeywordTokenizer::new();
+        if let Some(language) = search.language {
+            if search.stemming.unwrap_or(true) {
+                tokenizer = tokenizer.with_stemming(language);
+            }
+            if search.stop_words.unwrap_or(true) {
+                tokenizer = tokenizer.with_stop_words(language);
+            }
+        }
+        tokenizer
+    }
+
+    fn fastembed_options(&self) -> Result<FastEmbedOptions, ConfigError> {
+        let embeddings = &self.embeddings;
+        let device_id = embeddings.device_id;
+        let execution_provider = match embeddings.execution_provider.to_lowercase().as_str() {
+            "cpu" => ExecutionProvider::Cpu,
+            "cuda" => ExecutionProvider::Cuda { device_id },
+            "directml" => ExecutionProvider::DirectMl { device_id },
+            other => return Err(ConfigError::Invalid(format!("Unknown execution provider '{}'", other))),
+        };
+        Ok(FastEmbedOptions {
+            execution_provider,
+            intra_threads: embeddings.threads,
+            cache_dir: embeddings.cache_dir.clone(),
+        })
+    }
+
+    pub fn qdrant_connection(&self) -> QdrantConnectionConfig {
+        let qdrant = &self.qdrant;
+        let mut connection = QdrantConnectionConfig::new(qdrant.url.clone()).with_tls(qdrant.tls);
+        if let Some(api_key) = &qdrant.api_key {
+            connection = connection.with_api_key(api_key.clone());
+        }
+        if let Some(secs) = qdrant.timeout_secs {
+            connection = connection.with_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = qdrant.connect_timeout_secs {
+            connection = connection.with_connect_timeout(Duration::from_secs(secs));
+        }
+        connection.recreate_on_mismatch = qdrant.recreate_on_mismatch;
+        connection
+    }
+
+    /// Start the embedding service of a graph, keeping its chunk index and
+    /// when it embedded each page in the database when there is one
+    pub async fn embedding_service(&self, graph_id: Option<GraphId>) -> anyhow::Result<EmbeddingService> {
+        let mut service = EmbeddingService::new(self.embedding_service_config(graph_id)?).await?;
+        if let Some(path) = &self.database.path {
+            service = service
+                .with_chunk_repository(SqliteChunkRepository::open(path).map_err(ConfigError::from)?)
+                .with_page_timestamps(SqlitePageTimestampRepository::open(path).map_err(ConfigError::from)?);
+        }
+        Ok(service)
+    }
+
+    /// An import service with the `[import]` settings, recording checkpoints
+    /// in the database when there is one
+    pub fn import_service<R: PageRepository>(&self, repository: R) -> Result<ImportService<R>, ConfigError> {
+        let import = &self.import;
+        let mut service = ImportService::new(repository);
+        if let Some(concurrency) = import.concurrency {
+            service = service.with_concurrency(concurrency);
+        }
+        if let Some(batch_size) = import.batch_size {
+            service = service.with_batch_size(batch_size);
+        }
+        if let Some(megabytes) = import.memory_limit_mb {
+            service = service.with_memory_limit(megabytes.saturating_mul(1024 * 1024));
+        }
+        if let Some(chars) = import.max_block_length {
+            service = service.with_max_block_length(chars);
+        }
+        if let Some(policy) = import.duplicate_titles {
+            service = service.with_duplicate_title_policy(policy);
+        }
+        if let Some(path) = &self.database.path {
+            service = service.with_checkpoints(SqliteImportCheckpointRepository::open(path)?);
+        }
+        Ok(service)
+    }
+
+    /// A client of the Logseq API at `url`, or at `[logseq] api_url`, with
+    /// the configured token
+    pub fn logseq_api_client(&self, url: Option<&str>) -> LogseqApiClient {
+        let client = LogseqApiClient::new(url.unwrap_or(&self.logseq.api_url));
+        match &self.logseq.api_token {
+            Some(token) => client.with_token(token),
+            None => client,
+        }
+    }
+
+    /// A sync service for a graph directory with the `[sync]` settings
+    pub fn sync_service<R: PageRepository + Send + 'static>(
+        &self,
+        repository: R,
+        directory: LogseqDirectoryPath,
+    ) -> Result<SyncService<R>, SyncError> {
+        let sync = &self.sync;
+        let mut service = SyncService::new(repository, directory, sync.debounce_ms.map(Duration::from_millis))?;
+        if let Some(secs) = sync.reconcile_interval_secs {
+            service = service.with_reconcile_interval(Some(Duration::from_secs(secs)).filter(|d| !d.is_zero()));
+        }
+        if let Some(batch_size) = sync.batch_size {
+            service = service.with_batch_size(batch_size);
+        }
+        if let Some(policy) = sync.duplicate_titles {
+            service = service.with_duplicate_title_policy(policy);
+        }
+        Ok(service)
+    }
+
+    pub fn webhook_endpoints(&self) -> Result<Vec<WebhookEndpoint>, ConfigError> {
+        self.webhooks
+            .iter()
+            .map(|webhook| {
+                let events = webhook
+                    .events
+                    .iter()
+                    .map(|name| name.parse::<WebhookEventType>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(ConfigError::Invalid)?;
+                let mut endpoint = WebhookEndpoint::new(webhook.url.clone()).with_events(events);
+                if let Some(secret) = &webhook.secret {
+                    endpoint = endpoint.with_secret(secret.clone());
+                }
+                Ok(endpoint)
+            })
+            .collect()
+    }
+
+    /// A dispatcher for the `[[webhooks]]` endpoints, if there are any
+    pub fn webhook_dispatcher(&self) -> Result<Option<WebhookDispatcher>, ConfigError> {
+        let endpoints = self.webhook_endpoints()?;
+        Ok((!endpoints.is_empty()).then(|| WebhookDispatcher::new(endpoints)))
+    }
+
+    /// Authentication for the API, if `[server]` sets tokens or introspection
+    #[cfg(feature = "server")]
+    pub fn api_auth(&self) -> Result<Option<crate::server::ApiAuth>, ConfigError> {
+        use crate::server::{ApiAuth, Scope, TokenIntrospection};
+
+        let server = &self.server;
+        if server.tokens.is_empty() && server.introspection.is_none() {
+            return Ok(None);
+        }
+        let mut auth = ApiAuth::new();
+        for token in &server.tokens {
+            let scopes = token
+                .scopes
+                .iter()
+                .map(|name| Scope::parse(name).ok_or_else(|| ConfigError::Invalid(format!("Unknown scope '{}'", name))))
+                .collect::<Result<Vec<_>, _>>()?;
+            auth = auth.with_token(token.token.clone(), scopes);
+        }
+        if let Some(introspection) = &server.introspection {
+            auth = auth.with_introspection(TokenIntrospection::new(
+                introspection.endpoint.clone(),
+                introspection.client_id.clone(),
+                introspection.client_secret.clone(),
+            ));
+        }
+        Ok(Some(auth))
+    }
+
+    /// Address the API listens on
+    pub fn server_address(&self) -> Result<std::net::SocketAddr, ConfigError> {
+        self.server
+            .address
+            .parse()
+            .map_err(|_| ConfigError::Invalid(format!("Invalid server address '{}'", self.server.address)))
+    }
+}
+
+/// Overwrite `target` with `value` when the configuration sets one
+fn set<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_defaults_match_the_services() {
+        let config = Config::from_sources(None, std::iter::empty()).unwrap();
+        assert_eq!(config, Config::default());
+
+        let embedding = config.embedding_service_config(None).unwrap();
+        let defaults = EmbeddingServiceConfig::default();
+        assert_eq!(embedding.collection_name, defaults.collection_name);
+        assert_eq!(embedding.max_tokens_per_chunk, defaults.max_tokens_per_chunk);
+        assert_eq!(embedding.qdrant.url, defaults.qdrant.url);
+        assert_eq!(embedding.qdrant.timeout, defaults.qdrant.timeout);
+        assert_eq!(config.server_address().unwrap().port(), 3000);
+        assert!(config.webhook_dispatcher().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_file_then_environment() {
+        let toml = r#"
+            [graph]
+            path = "/notes"
+
+            [qdrant]
+            url = "http://qdrant:6334"
+            timeout_secs = 10
+
+            [embeddings]
+            model = "all-minilm-l6-v2"
+            mmr_lambda = 0.7
+
+            [chunking]
+            strategy = "words"
+            max_tokens = 0
+            max_words = 80
+
+            [search]
+            language = "english"
+            stop_words = false
+
+            [search.ranking]
+            prefix = 0.8
+            recency_boost = 0.25
+
+            [import]
+            duplicate_titles = "merge"
+
+            [[webhooks]]
+            url = "https://example.com/hook"
+            events = ["PageCreated"]
+        "#;
+        let vars = env(&[
+            ("LOGJAM_QDRANT_URL", "https://cloud:6334"),
+            ("LOGJAM_SYNC_DEBOUNCE_MS", "250"),
+            ("LOGJAM_TELEMETRY_OTLP_ENDPOINT", "http://collector:4317"),
+            ("LOGJAM_LOGSEQ_API_TOKEN", "s3cret"),
+            ("LOGJAM_PREPROCESSING_SKIP_JOURNAL_TEMPLATES", "true"),
+            ("LOGJAM_DATABASE_EMBEDDING_OUTBOX", "true"),
+            ("LOGJAM_VISIBILITY_PUBLIC_ONLY", "true"),
+        ]);
+        let config = Config::from_sources(Some(toml), vars).unwrap();
+
+        assert_eq!(config.graph.path, PathBuf::from("/notes"));
+        assert_eq!(config.sync.debounce_ms, Some(250));
+        assert_eq!(config.telemetry.otlp_endpoint.as_deref(), Some("http://collector:4317"));
+        assert_eq!(config.logseq.api_token.as_deref(), Some("s3cret"));
+        assert_eq!(config.logseq.api_url, DEFAULT_LOGSEQ_API_URL);
+        assert_eq!(config.import.duplicate_titles, Some(DuplicateTitlePolicy::Merge));
+        assert_eq!(
+            config.search.ranking,
+            RankingConfig {
+                prefix: 0.8,
+                recency_boost: 0.25,
+                ..RankingConfig::default()
+            }
+        );
+
+        let embedding = config.embedding_service_config(None).unwrap();
+        assert_eq!(embedding.qdrant.url, "https://cloud:6334");
+        assert_eq!(embedding.qdrant.timeout, Duration::from_secs(10));
+        assert_eq!(embedding.chunking, ChunkingStrategy::Words);
+        assert_eq!(embedding.max_tokens_per_chunk, None);
+        assert_eq!(embedding.max_words_per_chunk, 80);
+        assert!(embedding.skip_journal_templates);
+        assert!(config.database.embedding_outbox);
+        assert!(config.visibility_policy().public_only);
+        assert_eq!(embedding.visibility, None);
+        assert_eq!(embedding.mmr_lambda, Some(0.7));
+        assert_eq!(embedding.keyword_tokenizer, KeywordTokenizer::new().with_stemming(Language::English));
+
+        let dispatcher = config.webhook_dispatcher().unwrap().unwrap();
+        assert!(dispatcher.endpoints()[0].accepts(WebhookEventType::PageCreated));
+        assert!(!dispatcher.endpoints()[0].accepts(WebhookEventType::PageDeleted));
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings() {
+        let invalid = [
+            "[qdrant]\nadress = \"http://qdrant:6334\"",
+            "[sync]\ndebounce_ms = \"soon\"",
+            "[embeddings]\nmodel = \"bge-small\"",
+            "[embeddings]\nexecution_provider = \"tpu\"",
+            "[search]\nlanguage = \"klingon\"",
+            "[search.ranking]\nrecency_boost = 1.5",
+            "[preprocessing]\nstages = [\"strip_emoji\"]",
+            "[[webhooks]]\nurl = \"https://example.com\"\nevents = [\"PageMoved\"]",
+            "[telemetry]\nfilter = \"backend=loud\"",
+        ];
+        for toml in invalid {
+            assert!(matches!(Config::from_toml(toml), Err(ConfigError::Invalid(_))), "{}", toml);
+        }
+        let vars = env(&[("LOGJAM_IMPORT_CONCURENCY", "8")]);
+        assert!(Config::from_sources(None, vars).is_err());
+    }
+
+    #[test]
+    fn test_import_service_keeps_checkpoints_in_the_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.database.path = Some(dir.path().join("logjam.db"));
+        config.import.concurrency = Some(2);
+
+        config.import_service(InMemoryPageRepository::new()).unwrap();
+        assert!(dir.path().join("logjam.db").exists());
+    }
+
+    #[test]
+    fn test_graph_registry_gives_each_graph_its_own_data() {
+        let toml = r#"
+            [graph]
+            path = "/notes"
+            id = "notes"
+
+            [database]
+            path = "/data/logjam.db"
+
+            [[graphs]]
+            id = "work"
+            path = "/work"
+
+            [[graphs]]
+            id = "papers"
+            path = "/papers"
+            database = "/papers/papers.db"
+            collection = "research"
+            preprocessing = { stages = ["expand_page_references", "add_hierarchy"], page_title = false }
+        "#;
+        let config = Config::from_toml(toml).unwrap();
+        let registry = config.graph_registry().unwrap();
+        assert_eq!(registry.default_graph().unwrap().id.as_str(), "notes");
+
+        let work = registry.find("work").unwrap();
+        assert_eq!(work.database_path, Some(PathBuf::from("/data/work.sqlite")));
+        let papers = config.for_graph(registry.find("/papers").unwrap());
+        assert_eq!(papers.graph_id().as_str(), "papers");
+        assert_eq!(papers.database.path, Some(PathBuf::from("/papers/papers.db")));
+        let embedding = papers.embedding_service_config(Some(papers.graph_id())).unwrap();
+        assert_eq!(embedding.effective_collection_name(), "research__papers");
+        let stages: Vec<&str> = embedding.preprocessing.stage_names().collect();
+        assert_eq!(stages, vec!["expand_page_references", "add_hierarchy"]);
+        let work = config.for_graph(work).embedding_service_config(None).unwrap();
+        assert_eq!(work.preprocessing.stage_names().count(), 4);
+
+        let twice = format!("{}\n[[graphs]]\nid = \"notes\"\npath = \"/other\"", toml);
+        assert!(matches!(Config::from_toml(&twice), Err(ConfigError::Invalid(_))));
+    }
+}
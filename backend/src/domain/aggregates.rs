@@ -2,8 +2,9 @@
 use super::base::{AggregateRoot, DomainError, DomainResult, Entity};
 use super::entities::Block;
 use super::events::DomainEventEnum;
-use super::value_objects::{BlockId, PageId, PageReference, Url};
-use std::collections::HashMap;
+use super::value_objects::{BlockId, BlockPath, BlockPathSegment, PageId, PageReference, Url, Visibility};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// A Page is an aggregate root that represents a Logseq page (markdown file)
 /// It contains a tree of blocks and manages the relationships between them
@@ -13,6 +14,10 @@ pub struct Page {
     title: String,
     blocks: HashMap<BlockId, Block>,
     root_block_ids: Vec<BlockId>,
+    /// Markdown file the page was parsed from, if any
+    file_path: Option<PathBuf>,
+    /// Logseq page properties, by key
+    properties: BTreeMap<String, String>,
 }
 
 impl Page {
@@ -23,6 +28,8 @@ impl Page {
             title,
             blocks: HashMap::new(),
             root_block_ids: Vec::new(),
+            file_path: None,
+            properties: BTreeMap::new(),
         }
     }
 
@@ -36,6 +43,56 @@ impl Page {
         self.title = title;
     }
 
+    /// Get the markdown file the page was parsed from
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file_path.as_deref()
+    }
+
+    /// Record the markdown file the page was parsed from
+    pub fn set_file_path(&mut self, file_path: PathBuf) {
+        self.file_path = Some(file_path);
+    }
+
+    /// Get the page's properties
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        &self.properties
+    }
+
+    /// Set a page property, replacing any previous value
+    pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.properties.insert(key.into(), value.into());
+    }
+
+    /// What the page's `public::` and `private::` properties mark, set as
+    /// page properties or written in its first block when that holds only
+    /// properties
+    pub fn visibility(&self) -> Visibility {
+        let first_block = self
+            .root_blocks()
+            .first()
+            .filter(|block| block.content().is_properties_only())
+            .map(|block| block.visibility())
+            .unwrap_or_default();
+        Visibility::from_properties(&self.properties).max(first_block)
+    }
+
+    /// What a block's `public::` and `private::` properties mark, written in
+    /// the block or in its first child when that holds only properties, as
+    /// Logseq's indented property lines parse
+    pub fn block_visibility(&self, block_id: &BlockId) -> Visibility {
+        let Some(block) = self.blocks.get(block_id) else {
+            return Visibility::Unmarked;
+        };
+        let first_child = block
+            .child_ids()
+            .first()
+            .and_then(|id| self.blocks.get(id))
+            .filter(|child| child.content().is_properties_only())
+            .map(|child| child.visibility())
+            .unwrap_or_default();
+        block.visibility().max(first_child)
+    }
+
     /// Add a block to the page
     pub fn add_block(&mut self, block: Block) -> DomainResult<()> {
         let block_id = block.id().clone();
@@ -240,6 +297,59 @@ impl Page {
 
         path
     }
+
+    /// The [`BlockPath`] addressing a block of the page
+    pub fn block_path(&self, block_id: &BlockId) -> Option<BlockPath> {
+        self.blocks.get(block_id)?;
+        let segments = self
+            .get_hierarchy_path(block_id)
+            .into_iter()
+            .map(|block| {
+                let label = BlockPathSegment::label_of(block.content().as_str());
+                let occurrence = self
+                    .siblings(block)
+                    .iter()
+                    .take_while(|id| *id != block.id())
+                    .filter_map(|id| self.blocks.get(id))
+                    .filter(|sibling| BlockPathSegment::label_of(sibling.content().as_str()) == label)
+                    .count()
+                    + 1;
+                BlockPathSegment::new(label, occurrence)
+            })
+            .collect();
+        BlockPath::new(self.title.clone(), segments).ok()
+    }
+
+    /// The block a [`BlockPath`] addresses, if it's on this page; titles
+    /// are compared ignoring case, block lines exactly
+    pub fn resolve_block_path(&self, path: &BlockPath) -> Option<&BlockId> {
+        if path.page().to_lowercase() != self.title.to_lowercase() {
+            return None;
+        }
+        let mut candidates: &[BlockId] = &self.root_block_ids;
+        let mut found = None;
+        for segment in path.segments() {
+            let id = candidates
+                .iter()
+                .filter(|id| {
+                    self.blocks.get(*id).is_some_and(|block| {
+                        BlockPathSegment::label_of(block.content().as_str()) == segment.label()
+                    })
+                })
+                .nth(segment.occurrence() - 1)?;
+            candidates = self.blocks.get(id)?.child_ids();
+            found = Some(id);
+        }
+        found
+    }
+
+    /// The block and its siblings, in order
+    fn siblings(&self, block: &Block) -> &[BlockId] {
+        match block.parent_id().and_then(|parent_id| self.blocks.get(parent_id)) {
+            Some(parent) => parent.child_ids(),
+            None => &self.root_block_ids,
+        }
+    }
 }
 
 impl Entity for Page {
@@ -500,4 +610,62 @@ mod tests {
         let root = page.get_block(&root_id).unwrap();
         assert_eq!(root.child_ids().len(), 0);
     }
+
+    #[test]
+    fn test_block_paths_resolve_both_ways() {
+        let mut page = Page::new(PageId::new("page-1").unwrap(), "Rust".to_string());
+        let block = |id: &str, content: &str, parent: Option<&str>| {
+            let id = BlockId::new(id).unwrap();
+            match parent {
+                None => Block::new_root(id, BlockContent::new(content)),
+                Some(parent) => {
+                    Block::new_child(id, BlockContent::new(content), BlockId::new(parent).unwrap(), IndentLevel::new(1))
+                }
+            }
+        };
+        page.add_block(block("a", "Notes", None)).unwrap();
+        page.add_block(block("b", "Notes\nsecond line", None)).unwrap();
+        page.add_block(block("c", "Ownership", Some("b"))).unwrap();
+
+        let c = BlockId::new("c").unwrap();
+        let path = page.block_path(&c).unwrap();
+        assert_eq!(path.to_string(), "Rust > Notes[2] > Ownership");
+        assert_eq!(page.resolve_block_path(&path), Some(&c));
+
+        let first = BlockPath::parse("rust > Notes").unwrap();
+        assert_eq!(page.resolve_block_path(&first).unwrap().as_str(), "a");
+        assert_eq!(page.resolve_block_path(&BlockPath::parse("Rust > Notes > Ownership").unwrap()), None);
+        assert_eq!(page.resolve_block_path(&BlockPath::parse("Go > Notes").unwrap()), None);
+        assert_eq!(page.block_path(&BlockId::new("missing").unwrap()), None);
+    }
+
+    #[test]
+    fn test_visibility_from_properties_and_first_block() {
+        let mut page = Page::new(PageId::new("rust").unwrap(), "Rust".to_string());
+        let first = Block::new_root(BlockId::new("a").unwrap(), BlockContent::new("public:: true\ntags:: language"));
+        let second = Block::new_root(BlockId::new("b").unwrap(), BlockContent::new("Secret\nprivate:: TRUE"));
+        page.add_block(first).unwrap();
+        page.add_block(second).unwrap();
+
+        assert_eq!(page.visibility(), Visibility::Public);
+        assert_eq!(page.get_block(&BlockId::new("b").unwrap()).unwrap().visibility(), Visibility::Private);
+
+        page.set_property("public", "false");
+        assert_eq!(page.visibility(), Visibility::Private);
+
+        // A first block with text of its own is a block, not the page's properties
+        let mut page = Page::new(PageId::new("go").unwrap(), "Go".to_string());
+        page.add_block(Block::new_root(BlockId::new("c").unwrap(), BlockContent::new("Go\npublic:: true"))).unwrap();
+        assert_eq!(page.visibility(), Visibility::Unmarked);
+
+        // Properties parsed into a child block mark its parent
+        let child = Block::new_child(
+            BlockId::new("d").unwrap(),
+            BlockContent::new("private:: true"),
+            BlockId::new("c").unwrap(),
+            IndentLevel::new(1),
+        );
+        page.add_block(child).unwrap();
+        assert_eq!(page.block_visibility(&BlockId::new("c").unwrap()), Visibility::Private);
+    }
 }
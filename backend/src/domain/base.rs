@@ -1,5 +1,6 @@
 /// Base DDD abstractions for the domain layer
 use std::fmt::Debug;
+use thiserror::Error;
 
 /// Trait for value objects - immutable objects defined by their attributes
 /// Value objects are equal if all their attributes are equal
@@ -33,31 +34,44 @@ pub trait DomainEvent: Debug + Clone {
 pub type DomainResult<T> = Result<T, DomainError>;
 
 /// Domain-specific errors
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Error, Debug)]
 pub enum DomainError {
     /// Invalid value provided
+    #[error("Invalid value: {0}")]
     InvalidValue(String),
     /// Entity not found
+    #[error("Not found: {0}")]
     NotFound(String),
     /// Business rule violation
+    #[error("Business rule violation: {0}")]
     BusinessRuleViolation(String),
     /// Invalid operation
+    #[error("Invalid operation: {0}")]
     InvalidOperation(String),
+    /// The store behind a repository failed
+    #[error("Repository error: {0}")]
+    Repository(#[from] RepositoryError),
 }
 
-impl std::fmt::Display for DomainError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            DomainError::InvalidValue(msg) => write!(f, "Invalid value: {}", msg),
-            DomainError::NotFound(msg) => write!(f, "Not found: {}", msg),
-            DomainError::BusinessRuleViolation(msg) => write!(f, "Business rule violation: {}", msg),
-            DomainError::InvalidOperation(msg) => write!(f, "Invalid operation: {}", msg),
-        }
-    }
+/// Failures of the store behind a repository, apart from the domain's own
+/// errors so callers can tell a failing store from a missing or invalid entity
+#[derive(Error, Debug)]
+pub enum RepositoryError {
+    /// The storage backend (SQLite, the file system, ...) failed
+    #[error("{backend} error: {source}")]
+    Storage {
+        backend: &'static str,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A stored value that no longer reads back, e.g. a malformed timestamp
+    #[error("Invalid stored value: {0}")]
+    InvalidData(String),
+    /// A thread panicked while holding the lock of the named store
+    #[error("{0} lock poisoned")]
+    LockPoisoned(&'static str),
 }
 
-impl std::error::Error for DomainError {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +115,19 @@ mod tests {
         let error = DomainError::InvalidValue("test".to_string());
         assert_eq!(error.to_string(), "Invalid value: test");
     }
+
+    #[test]
+    fn test_repository_error_keeps_its_source() {
+        let io = std::io::Error::other("disk full");
+        let error = DomainError::from(RepositoryError::Storage {
+            backend: "SQLite",
+            source: Box::new(io),
+        });
+        assert!(matches!(error, DomainError::Repository(RepositoryError::Storage { .. })));
+        assert_eq!(error.to_string(), "Repository error: SQLite error: disk full");
+
+        let storage = std::error::Error::source(&error).unwrap();
+        let io = std::error::Error::source(storage).unwrap();
+        assert_eq!(io.downcast_ref::<std::io::Error>().unwrap().to_string(), "disk full");
+    }
 }
@@ -1,8 +1,9 @@
 /// Domain entities
 use super::base::Entity;
 use super::value_objects::{
-    BlockContent, BlockId, ChunkId, EmbeddingVector, IndentLevel, PageId, PageReference, Url,
+    BlockContent, BlockId, ChunkId, EmbeddingVector, IndentLevel, PageId, PageReference, Url, Visibility,
 };
+use std::collections::BTreeMap;
 
 /// A Block represents a single bullet point in Logseq
 /// Blocks form a tree structure where each block can have a parent and children
@@ -15,6 +16,8 @@ pub struct Block {
     child_ids: Vec<BlockId>,
     urls: Vec<Url>,
     page_references: Vec<PageReference>,
+    /// Logseq block properties (`key:: value`), by key
+    properties: BTreeMap<String, String>,
 }
 
 impl Block {
@@ -28,6 +31,7 @@ impl Block {
             child_ids: Vec::new(),
             urls: Vec::new(),
             page_references: Vec::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -46,6 +50,7 @@ impl Block {
             child_ids: Vec::new(),
             urls: Vec::new(),
             page_references: Vec::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -120,6 +125,35 @@ impl Block {
         }
     }
 
+    /// Replace the block's page references, e.g. after its content changed
+    pub fn set_page_references(&mut self, references: Vec<PageReference>) {
+        self.page_references.clear();
+        for reference in references {
+            self.add_page_reference(reference);
+        }
+    }
+
+    /// Get the block's properties
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        &self.properties
+    }
+
+    /// Set a property, replacing any previous value
+    pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.properties.insert(key.into(), value.into());
+    }
+
+    /// What the block's `public::` and `private::` properties mark, set as
+    /// properties or written in its content
+    pub fn visibility(&self) -> Visibility {
+        Visibility::from_properties(
+            self.properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.as_str()))
+                .chain(self.content.property_lines()),
+        )
+    }
+
     /// Update the block's content
     pub fn update_content(&mut self, content: BlockContent) {
         self.content = content;
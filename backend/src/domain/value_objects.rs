@@ -1,10 +1,34 @@
 /// Value objects for the domain layer
 use super::base::{DomainError, DomainResult, ValueObject};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::path::{Path, PathBuf};
 
+/// Conversions through which serde reads and writes a validated string value
+/// object as its plain string, running the same checks as `new`
+macro_rules! string_conversions {
+    ($($name:ident),+) => {$(
+        impl TryFrom<String> for $name {
+            type Error = DomainError;
+
+            fn try_from(value: String) -> DomainResult<Self> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.as_str().to_string()
+            }
+        }
+    )+};
+}
+
+string_conversions!(PageId, BlockId, Url, GraphId, ChunkId);
+
 /// Unique identifier for a Page
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct PageId(String);
 
 impl PageId {
@@ -30,7 +54,8 @@ impl fmt::Display for PageId {
 }
 
 /// Unique identifier for a Block
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct BlockId(String);
 
 impl BlockId {
@@ -55,8 +80,190 @@ impl fmt::Display for BlockId {
     }
 }
 
-/// A URL value object
+/// Separator between the page title and the blocks of a [`BlockPath`]
+const BLOCK_PATH_SEPARATOR: &str = " > ";
+
+/// Address of a block by where it sits rather than by its ID: the page's title,
+/// then the first line of each block from the root down, like
+/// `Rust > Ownership > Borrowing`
+///
+/// When siblings start with the same line, the later ones are numbered, so
+/// `Rust > Notes[2]` is the second root block reading "Notes". `>` and `\` in
+/// titles and lines are escaped with a `\`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BlockPath {
+    page: String,
+    segments: Vec<BlockPathSegment>,
+}
+
+/// One block of a [`BlockPath`]: its first line, and which of its siblings
+/// with that line it is
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockPathSegment {
+    label: String,
+    /// 1 for the first sibling with the label
+    occurrence: usize,
+}
+
+impl BlockPathSegment {
+    pub fn new(label: impl Into<String>, occurrence: usize) -> Self {
+        BlockPathSegment {
+            label: label.into(),
+            occurrence: occurrence.max(1),
+        }
+    }
+
+    /// The label a block's content is addressed by: its first non-empty
+    /// line, with runs of whitespace made single spaces
+    pub fn label_of(content: &str) -> String {
+        let line = content.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+        line.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn occurrence(&self) -> usize {
+        self.occurrence
+    }
+}
+
+impl BlockPath {
+    pub fn new(page: impl Into<String>, segments: Vec<BlockPathSegment>) -> DomainResult<Self> {
+        let page = page.into().trim().to_string();
+        if page.is_empty() {
+            return Err(DomainError::InvalidValue("BlockPath needs a page title".to_string()));
+        }
+        if segments.is_empty() {
+            return Err(DomainError::InvalidValue(format!("BlockPath '{}' names no block", page)));
+        }
+        Ok(BlockPath { page, segments })
+    }
+
+    /// Read a path written like `Page > parent > child[2]`
+    pub fn parse(path: &str) -> DomainResult<Self> {
+        let mut parts = split_escaped(path).into_iter();
+        let page = parts.next().map(|part| unescape(&part).0).unwrap_or_default();
+        let segments = parts
+            .map(|part| {
+                let (label, escaped) = unescape(&part);
+                let occurrence = occurrence_suffix(&label, &escaped);
+                let label = match occurrence {
+                    Some((occurrence, at)) => BlockPathSegment::new(label[..at].trim_end(), occurrence),
+                    None => BlockPathSegment::new(label, 1),
+                };
+                if label.label.is_empty() {
+                    return Err(DomainError::InvalidValue(format!("BlockPath '{}' has an empty block", path)));
+                }
+                Ok(label)
+            })
+            .collect::<DomainResult<Vec<_>>>()?;
+        Self::new(page, segments)
+    }
+
+    /// Title of the page holding the block
+    pub fn page(&self) -> &str {
+        &self.page
+    }
+
+    /// The blocks from the page's root down to the addressed block
+    pub fn segments(&self) -> &[BlockPathSegment] {
+        &self.segments
+    }
+}
+
+/// Split on unescaped `>`, trimming each part but keeping escapes
+fn split_escaped(path: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = path.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                let part = parts.last_mut().expect("parts is never empty");
+                part.push('\\');
+                part.extend(chars.next());
+            }
+            '>' => parts.push(String::new()),
+            ch => parts.last_mut().expect("parts is never empty").push(ch),
+        }
+    }
+    parts.iter().map(|part| part.trim().to_string()).collect()
+}
+
+/// Remove escapes, noting the byte offsets of escaped characters
+fn unescape(part: &str) -> (String, Vec<usize>) {
+    let mut text = String::with_capacity(part.len());
+    let mut escaped = Vec::new();
+    let mut chars = part.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(ch) = chars.next() {
+                    escaped.push(text.len());
+                    text.push(ch);
+                }
+            }
+            ch => text.push(ch),
+        }
+    }
+    (text, escaped)
+}
+
+/// A trailing, unescaped `[n]`, as the occurrence and the offset it starts at
+fn occurrence_suffix(label: &str, escaped: &[usize]) -> Option<(usize, usize)> {
+    let digits = label.strip_suffix(']')?;
+    let at = digits.rfind('[')?;
+    if escaped.contains(&at) || escaped.contains(&(label.len() - 1)) {
+        return None;
+    }
+    let occurrence = digits[at + 1..].parse().ok().filter(|n| *n > 0)?;
+    Some((occurrence, at))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('>', "\\>")
+}
+
+impl ValueObject for BlockPath {}
+
+impl fmt::Display for BlockPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", escape(&self.page))?;
+        for segment in &self.segments {
+            let mut label = escape(&segment.label);
+            // A line that itself ends like a number mustn't read as one
+            if occurrence_suffix(&label, &[]).is_some() {
+                let at = label.rfind('[').expect("the suffix starts with [");
+                label.insert(at, '\\');
+            }
+            write!(f, "{}{}", BLOCK_PATH_SEPARATOR, label)?;
+            if segment.occurrence > 1 {
+                write!(f, "[{}]", segment.occurrence)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for BlockPath {
+    type Error = DomainError;
+
+    fn try_from(value: String) -> DomainResult<Self> {
+        BlockPath::parse(&value)
+    }
+}
+
+impl From<BlockPath> for String {
+    fn from(value: BlockPath) -> String {
+        value.to_string()
+    }
+}
+
+/// A URL value object
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Url {
     value: String,
 }
@@ -103,7 +310,8 @@ impl fmt::Display for Url {
 }
 
 /// A reference to another page (e.g., [[page-name]] or #tag)
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "PageReferenceFields")]
 pub struct PageReference {
     /// The title/name of the referenced page
     title: String,
@@ -153,6 +361,25 @@ impl PageReference {
 
 impl ValueObject for PageReference {}
 
+/// A page reference as serde reads it, before its title is checked
+#[derive(Deserialize)]
+struct PageReferenceFields {
+    title: String,
+    is_tag: bool,
+}
+
+impl TryFrom<PageReferenceFields> for PageReference {
+    type Error = DomainError;
+
+    fn try_from(fields: PageReferenceFields) -> DomainResult<Self> {
+        if fields.is_tag {
+            PageReference::from_tag(fields.title)
+        } else {
+            PageReference::from_brackets(fields.title)
+        }
+    }
+}
+
 impl fmt::Display for PageReference {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.is_tag {
@@ -164,7 +391,8 @@ impl fmt::Display for PageReference {
 }
 
 /// The content of a block as plain text
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct BlockContent {
     text: String,
 }
@@ -181,10 +409,67 @@ impl BlockContent {
     pub fn is_empty(&self) -> bool {
         self.text.trim().is_empty()
     }
+
+    /// The `key:: value` property lines written in the text, keys lowercased
+    pub fn property_lines(&self) -> impl Iterator<Item = (String, &str)> {
+        self.text.lines().filter_map(property_line)
+    }
+
+    /// Whether every line of the text is a property, as in the first block
+    /// of a page holding the page's properties
+    pub fn is_properties_only(&self) -> bool {
+        !self.is_empty()
+            && self
+                .text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .all(|line| property_line(line).is_some())
+    }
+}
+
+fn property_line(line: &str) -> Option<(String, &str)> {
+    let (key, value) = line.trim().split_once(":: ")?;
+    let property = !key.is_empty() && key.chars().all(|ch| ch.is_alphanumeric() || ch == '-' || ch == '_');
+    property.then(|| (key.to_lowercase(), value.trim()))
 }
 
 impl ValueObject for BlockContent {}
 
+/// Whether a page or block is marked for showing outside the graph, by
+/// Logseq's `public::` property or `private::`, from least to most restricted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Neither property is set
+    #[default]
+    Unmarked,
+    /// `public:: true`
+    Public,
+    /// `private:: true` or `public:: false`
+    Private,
+}
+
+impl Visibility {
+    /// The visibility properties among `properties` mark; private wins
+    /// over public when both are set
+    pub fn from_properties<K: AsRef<str>, V: AsRef<str>>(properties: impl IntoIterator<Item = (K, V)>) -> Self {
+        let mut visibility = Visibility::Unmarked;
+        for (key, value) in properties {
+            let value = value.as_ref().trim();
+            let marked = match key.as_ref() {
+                "private" if value.eq_ignore_ascii_case("true") => Visibility::Private,
+                "public" if value.eq_ignore_ascii_case("false") => Visibility::Private,
+                "public" if value.eq_ignore_ascii_case("true") => Visibility::Public,
+                _ => continue,
+            };
+            visibility = visibility.max(marked);
+        }
+        visibility
+    }
+}
+
+impl ValueObject for Visibility {}
+
 impl fmt::Display for BlockContent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.text)
@@ -192,7 +477,8 @@ impl fmt::Display for BlockContent {
 }
 
 /// The indentation level of a block (0 = root level, 1 = first indent, etc.)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct IndentLevel(usize);
 
 impl IndentLevel {
@@ -229,14 +515,78 @@ impl fmt::Display for IndentLevel {
     }
 }
 
-/// A validated Logseq directory path that contains pages/ and journals/ subdirectories
+/// Where a graph keeps its pages and journals, relative to the graph root
+///
+/// Logseq lets users rename both directories (`:pages-directory` and
+/// `:journals-directory` in `logseq/config.edn`), and some graphs have no
+/// journals at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphLayout {
+    pages_dir: PathBuf,
+    journals_dir: Option<PathBuf>,
+}
+
+impl GraphLayout {
+    pub fn new(pages_dir: impl Into<PathBuf>, journals_dir: Option<PathBuf>) -> DomainResult<Self> {
+        let pages_dir = Self::validate(pages_dir.into())?;
+        let journals_dir = journals_dir.map(Self::validate).transpose()?;
+        Ok(GraphLayout {
+            pages_dir,
+            journals_dir,
+        })
+    }
+
+    pub fn pages_dir(&self) -> &Path {
+        &self.pages_dir
+    }
+
+    pub fn journals_dir(&self) -> Option<&Path> {
+        self.journals_dir.as_deref()
+    }
+
+    /// Directory names must stay inside the graph root
+    fn validate(dir: PathBuf) -> DomainResult<PathBuf> {
+        let inside_root = !dir.as_os_str().is_empty()
+            && dir
+                .components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)));
+        if !inside_root {
+            return Err(DomainError::InvalidValue(format!(
+                "Graph directory must be a relative path inside the graph: {}",
+                dir.display()
+            )));
+        }
+        Ok(dir)
+    }
+}
+
+impl Default for GraphLayout {
+    fn default() -> Self {
+        GraphLayout {
+            pages_dir: PathBuf::from("pages"),
+            journals_dir: Some(PathBuf::from("journals")),
+        }
+    }
+}
+
+impl ValueObject for GraphLayout {}
+
+/// A validated Logseq directory path whose pages (and journals, if the layout
+/// has them) directories exist
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LogseqDirectoryPath {
     path: PathBuf,
+    layout: GraphLayout,
 }
 
 impl LogseqDirectoryPath {
+    /// Validate a graph using the default `pages/` and `journals/` layout
     pub fn new(path: impl Into<PathBuf>) -> DomainResult<Self> {
+        Self::with_layout(path, GraphLayout::default())
+    }
+
+    /// Validate a graph with custom page and journal directories
+    pub fn with_layout(path: impl Into<PathBuf>, layout: GraphLayout) -> DomainResult<Self> {
         let path = path.into();
 
         // Validate that the path exists and is a directory
@@ -254,37 +604,45 @@ impl LogseqDirectoryPath {
             )));
         }
 
-        // Validate that pages/ and journals/ subdirectories exist
-        let pages_dir = path.join("pages");
-        let journals_dir = path.join("journals");
-
-        if !pages_dir.exists() || !pages_dir.is_dir() {
-            return Err(DomainError::InvalidValue(format!(
-                "Directory does not contain a 'pages' subdirectory: {}",
-                path.display()
-            )));
-        }
-
-        if !journals_dir.exists() || !journals_dir.is_dir() {
-            return Err(DomainError::InvalidValue(format!(
-                "Directory does not contain a 'journals' subdirectory: {}",
-                path.display()
-            )));
+        // Validate that the pages (and journals) subdirectories exist
+        let required = std::iter::once(layout.pages_dir()).chain(layout.journals_dir());
+        for dir in required {
+            if !path.join(dir).is_dir() {
+                return Err(DomainError::InvalidValue(format!(
+                    "Directory does not contain a '{}' subdirectory: {}",
+                    dir.display(),
+                    path.display()
+                )));
+            }
         }
 
-        Ok(LogseqDirectoryPath { path })
+        Ok(LogseqDirectoryPath { path, layout })
     }
 
     pub fn as_path(&self) -> &Path {
         &self.path
     }
 
+    pub fn layout(&self) -> &GraphLayout {
+        &self.layout
+    }
+
     pub fn pages_dir(&self) -> PathBuf {
-        self.path.join("pages")
+        self.path.join(self.layout.pages_dir())
+    }
+
+    pub fn journals_dir(&self) -> Option<PathBuf> {
+        self.layout.journals_dir().map(|dir| self.path.join(dir))
     }
 
-    pub fn journals_dir(&self) -> PathBuf {
-        self.path.join("journals")
+    /// The directories whose markdown files become pages
+    pub fn content_dirs(&self) -> Vec<PathBuf> {
+        std::iter::once(self.pages_dir()).chain(self.journals_dir()).collect()
+    }
+
+    /// Whether a path lies inside one of the content directories
+    pub fn contains_content(&self, path: &Path) -> bool {
+        self.content_dirs().iter().any(|dir| path.starts_with(dir))
     }
 }
 
@@ -296,6 +654,76 @@ impl fmt::Display for LogseqDirectoryPath {
     }
 }
 
+/// Identifier for a Logseq graph, used to namespace per-graph storage
+/// (vector collections, databases) so multiple graphs don't collide
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GraphId(String);
+
+impl GraphId {
+    /// Create a graph ID. Only ASCII letters, digits, `-` and `_` are allowed
+    /// since the ID is embedded in collection and file names.
+    pub fn new(id: impl Into<String>) -> DomainResult<Self> {
+        let id = id.into();
+        if id.is_empty() {
+            return Err(DomainError::InvalidValue("GraphId cannot be empty".to_string()));
+        }
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(DomainError::InvalidValue(format!(
+                "GraphId may only contain ASCII letters, digits, '-' and '_', got '{}'",
+                id
+            )));
+        }
+        Ok(GraphId(id))
+    }
+
+    /// Derive a graph ID from the name of the graph's root directory
+    pub fn from_directory(directory: &LogseqDirectoryPath) -> DomainResult<Self> {
+        let name = directory
+            .as_path()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Self::from_name(&name)
+    }
+
+    /// Derive a graph ID from a name, e.g. of a backup the graph was imported from
+    ///
+    /// Lowercases the name and replaces characters IDs can't hold with `-`.
+    pub fn from_name(name: &str) -> DomainResult<Self> {
+        let slug: String = name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+            .collect();
+
+        Self::new(slug.trim_matches('-'))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Namespaced vector collection name for this graph (e.g. `logseq_blocks__work`)
+    pub fn collection_name(&self, base: &str) -> String {
+        format!("{}__{}", base, self.0)
+    }
+
+    /// Namespaced database file name for this graph (e.g. `work.sqlite`)
+    pub fn database_file_name(&self) -> String {
+        format!("{}.sqlite", self.0)
+    }
+}
+
+impl ValueObject for GraphId {}
+
+impl fmt::Display for GraphId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Tracks the progress of an import operation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImportProgress {
@@ -344,7 +772,8 @@ impl ImportProgress {
 impl ValueObject for ImportProgress {}
 
 /// Unique identifier for a text chunk (may be 1:1 or 1:many with BlockId)
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct ChunkId(String);
 
 impl ChunkId {
@@ -375,7 +804,8 @@ impl fmt::Display for ChunkId {
 }
 
 /// Vector embedding for semantic search (384 dimensions for all-MiniLM-L6-v2)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<f32>", into = "Vec<f32>")]
 pub struct EmbeddingVector {
     dimensions: Vec<f32>,
 }
@@ -432,11 +862,26 @@ impl EmbeddingVector {
 
 impl ValueObject for EmbeddingVector {}
 
+impl TryFrom<Vec<f32>> for EmbeddingVector {
+    type Error = DomainError;
+
+    fn try_from(dimensions: Vec<f32>) -> DomainResult<Self> {
+        EmbeddingVector::new(dimensions)
+    }
+}
+
+impl From<EmbeddingVector> for Vec<f32> {
+    fn from(vector: EmbeddingVector) -> Vec<f32> {
+        vector.dimensions
+    }
+}
+
 // Manual Eq implementation since f32 doesn't implement Eq
 impl Eq for EmbeddingVector {}
 
 /// Normalized similarity score (0.0-1.0) for search results
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f32", into = "f32")]
 pub struct SimilarityScore(f32);
 
 impl SimilarityScore {
@@ -454,6 +899,15 @@ impl SimilarityScore {
         self.0
     }
 
+    /// Create a score from a value that may fall outside 0.0-1.0, moving it
+    /// to the nearest bound (NaN becomes 0.0)
+    pub fn saturating(score: f32) -> Self {
+        if score.is_nan() {
+            return SimilarityScore(0.0);
+        }
+        SimilarityScore(score.clamp(0.0, 1.0))
+    }
+
     /// Create a score from a cosine similarity value (which can be -1.0 to 1.0)
     /// Maps it to 0.0-1.0 range
     pub fn from_cosine_similarity(cosine: f32) -> DomainResult<Self> {
@@ -467,6 +921,20 @@ impl SimilarityScore {
 // It's a scoring value, not a domain value object
 impl Eq for SimilarityScore {}
 
+impl TryFrom<f32> for SimilarityScore {
+    type Error = DomainError;
+
+    fn try_from(score: f32) -> DomainResult<Self> {
+        SimilarityScore::new(score)
+    }
+}
+
+impl From<SimilarityScore> for f32 {
+    fn from(score: SimilarityScore) -> f32 {
+        score.0
+    }
+}
+
 
 impl fmt::Display for SimilarityScore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -474,10 +942,12 @@ impl fmt::Display for SimilarityScore {
     }
 }
 
-/// Supported embedding models
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Supported embedding models, serialized by their model name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum EmbeddingModel {
     /// all-MiniLM-L6-v2 model (384 dimensions)
+    #[default]
     AllMiniLML6V2,
 }
 
@@ -493,16 +963,34 @@ impl EmbeddingModel {
             EmbeddingModel::AllMiniLML6V2 => "sentence-transformers/all-MiniLM-L6-v2",
         }
     }
-}
 
-impl Default for EmbeddingModel {
-    fn default() -> Self {
-        EmbeddingModel::AllMiniLML6V2
+    /// The model with this name, with or without its organization and ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        [EmbeddingModel::AllMiniLML6V2].into_iter().find(|model| {
+            let full_name = model.model_name();
+            let short_name = full_name.rsplit('/').next().unwrap_or(full_name);
+            name.eq_ignore_ascii_case(full_name) || name.eq_ignore_ascii_case(short_name)
+        })
     }
 }
 
 impl ValueObject for EmbeddingModel {}
 
+impl TryFrom<String> for EmbeddingModel {
+    type Error = DomainError;
+
+    fn try_from(name: String) -> DomainResult<Self> {
+        EmbeddingModel::from_name(&name)
+            .ok_or_else(|| DomainError::InvalidValue(format!("Unknown embedding model '{}'", name)))
+    }
+}
+
+impl From<EmbeddingModel> for String {
+    fn from(model: EmbeddingModel) -> String {
+        model.model_name().to_string()
+    }
+}
+
 impl fmt::Display for EmbeddingModel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.model_name())
@@ -522,6 +1010,31 @@ mod tests {
         assert!(empty_id.is_err());
     }
 
+    #[test]
+    fn test_block_path_round_trips_through_text() {
+        let path = BlockPath::new(
+            "Rust",
+            vec![
+                BlockPathSegment::new("Ownership > borrowing", 1),
+                BlockPathSegment::new("Notes", 2),
+                BlockPathSegment::new("Chapter [3]", 1),
+            ],
+        )
+        .unwrap();
+        let text = path.to_string();
+        assert_eq!(text, "Rust > Ownership \\> borrowing > Notes[2] > Chapter \\[3]");
+        assert_eq!(BlockPath::parse(&text).unwrap(), path);
+
+        let parsed = BlockPath::parse("  Rust>Ownership >  Notes [2] ").unwrap();
+        assert_eq!(parsed.page(), "Rust");
+        assert_eq!(parsed.segments()[1], BlockPathSegment::new("Notes", 2));
+
+        assert!(BlockPath::parse("Rust").is_err());
+        assert!(BlockPath::parse(" > Ownership").is_err());
+        assert!(BlockPath::parse("Rust >  > Notes").is_err());
+        assert_eq!(BlockPathSegment::label_of("\n  Ownership   rules\nmore"), "Ownership rules");
+    }
+
     #[test]
     fn test_block_id_creation() {
         let id = BlockId::new("block-123").unwrap();
@@ -608,6 +1121,51 @@ mod tests {
         assert!(invalid_path.is_err());
     }
 
+    #[test]
+    fn test_logseq_directory_path_with_custom_layout() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("notes").join("pages")).unwrap();
+
+        // The default layout needs pages/ and journals/
+        assert!(LogseqDirectoryPath::new(root).is_err());
+
+        let layout = GraphLayout::new("notes/pages", None).unwrap();
+        let directory = LogseqDirectoryPath::with_layout(root, layout).unwrap();
+        assert_eq!(directory.content_dirs(), vec![root.join("notes").join("pages")]);
+        assert!(directory.contains_content(&root.join("notes/pages/sub/page.md")));
+        assert!(!directory.contains_content(&root.join("journals/2025_01_01.md")));
+
+        assert!(GraphLayout::new("../outside", None).is_err());
+        assert!(GraphLayout::new("/absolute", None).is_err());
+        assert!(GraphLayout::new("", None).is_err());
+    }
+
+    #[test]
+    fn test_graph_id_creation() {
+        let id = GraphId::new("work-notes_2").unwrap();
+        assert_eq!(id.as_str(), "work-notes_2");
+        assert_eq!(id.collection_name("logseq_blocks"), "logseq_blocks__work-notes_2");
+        assert_eq!(id.database_file_name(), "work-notes_2.sqlite");
+
+        assert!(GraphId::new("").is_err());
+        assert!(GraphId::new("has space").is_err());
+        assert!(GraphId::new("../escape").is_err());
+    }
+
+    #[test]
+    fn test_graph_id_from_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let graph_dir = temp_dir.path().join("My Graph");
+        std::fs::create_dir_all(graph_dir.join("pages")).unwrap();
+        std::fs::create_dir_all(graph_dir.join("journals")).unwrap();
+
+        let directory = LogseqDirectoryPath::new(&graph_dir).unwrap();
+        let id = GraphId::from_directory(&directory).unwrap();
+        assert_eq!(id.as_str(), "my-graph");
+        assert_eq!(GraphId::from_name("My Graph").unwrap(), id);
+    }
+
     #[test]
     fn test_import_progress() {
         let mut progress = ImportProgress::new(10);
@@ -712,6 +1270,14 @@ mod tests {
         assert!((score3.value() - 0.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_saturating_similarity_score() {
+        assert_eq!(SimilarityScore::saturating(0.25).value(), 0.25);
+        assert_eq!(SimilarityScore::saturating(1.5).value(), 1.0);
+        assert_eq!(SimilarityScore::saturating(-0.5).value(), 0.0);
+        assert_eq!(SimilarityScore::saturating(f32::NAN).value(), 0.0);
+    }
+
     #[test]
     fn test_embedding_model() {
         let model = EmbeddingModel::default();
@@ -719,4 +1285,26 @@ mod tests {
         assert_eq!(model.dimension_count(), 384);
         assert_eq!(model.model_name(), "sentence-transformers/all-MiniLM-L6-v2");
     }
+
+    #[test]
+    fn test_value_objects_serde_round_trip() {
+        let page_id = PageId::new("page-1").unwrap();
+        assert_eq!(serde_json::to_string(&page_id).unwrap(), "\"page-1\"");
+        assert_eq!(serde_json::from_str::<PageId>("\"page-1\"").unwrap(), page_id);
+
+        let tag = PageReference::from_tag("rust").unwrap();
+        let json = serde_json::to_value(&tag).unwrap();
+        assert_eq!(json, serde_json::json!({ "title": "rust", "is_tag": true }));
+        assert_eq!(serde_json::from_value::<PageReference>(json).unwrap(), tag);
+
+        let model: EmbeddingModel = serde_json::from_str("\"all-MiniLM-L6-v2\"").unwrap();
+        assert_eq!(serde_json::to_string(&model).unwrap(), "\"sentence-transformers/all-MiniLM-L6-v2\"");
+        assert_eq!(serde_json::to_string(&IndentLevel::new(2)).unwrap(), "2");
+
+        // Deserializing runs the same validation as the constructors
+        assert!(serde_json::from_str::<BlockId>("\"\"").is_err());
+        assert!(serde_json::from_str::<Url>("\"ftp://example.com\"").is_err());
+        assert!(serde_json::from_str::<PageReference>(r#"{"title":"","is_tag":false}"#).is_err());
+        assert!(serde_json::from_str::<SimilarityScore>("1.5").is_err());
+    }
 }
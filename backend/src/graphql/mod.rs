@@ -0,0 +1,4 @@
+pub mod schema;
+pub mod types;
+
+pub use schema::{build_schema, LogjamSchema, QueryRoot, SearchMode, SearchResults};
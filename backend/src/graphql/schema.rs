@@ -0,0 +1,292 @@
+/// GraphQL query root and schema construction
+use super::types::{
+    find_by_title, paginate, tag_names, ArchiveNode, BlockNode, PageNode, SearchHit, SearchItemNode, TagNode, UrlNode,
+};
+use crate::application::dto::{ResultType, SearchItem, SearchRequest, SearchType};
+use crate::application::repositories::PageRepository;
+use crate::application::services::{EmbeddingService, VisibilityPolicy, VisiblePages};
+use crate::application::use_cases::SearchPagesAndBlocks;
+use crate::domain::value_objects::{PageId, Url};
+use async_graphql::connection::Connection;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Enum, Object, Result, Schema, ID};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub type LogjamSchema<R> = Schema<QueryRoot<R>, EmptyMutation, EmptySubscription>;
+
+/// Build the read-only schema over a repository
+///
+/// Semantic search falls back to keyword search without an embedding
+/// service, as [`SearchPagesAndBlocks`] does. With a visibility policy,
+/// pages and blocks it hides are never resolved.
+pub fn build_schema<R>(
+    repository: Arc<Mutex<R>>,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    visibility: Option<VisibilityPolicy>,
+) -> LogjamSchema<R>
+where
+    R: PageRepository + Send + Sync + 'static,
+{
+    Schema::build(QueryRoot(PhantomData), EmptyMutation, EmptySubscription)
+        .data(SchemaData {
+            repository,
+            embedding_service,
+            visibility,
+        })
+        .finish()
+}
+
+/// What resolvers read from, stored in the schema's data
+pub(crate) struct SchemaData<R> {
+    pub(crate) repository: Arc<Mutex<R>>,
+    pub(crate) embedding_service: Option<Arc<EmbeddingService>>,
+    pub(crate) visibility: Option<VisibilityPolicy>,
+}
+
+impl<R: PageRepository> SchemaData<R> {
+    /// `repository` as the visibility policy lets it be resolved
+    pub(crate) fn visible<'a>(&self, repository: &'a R) -> VisiblePages<'a, R> {
+        VisiblePages::new(repository, self.visibility)
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchMode {
+    Traditional,
+    Semantic,
+}
+
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchResults {
+    All,
+    Pages,
+    Blocks,
+    Urls,
+}
+
+pub struct QueryRoot<R>(PhantomData<fn() -> R>);
+
+#[Object(name = "Query")]
+impl<R: PageRepository + Send + Sync + 'static> QueryRoot<R> {
+    /// A page by id, or by title (case-insensitive)
+    async fn page(&self, ctx: &Context<'_>, id: Option<ID>, title: Option<String>) -> Result<Option<PageNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        let page = match (id, title) {
+            (Some(id), _) => repository.find_by_id(&PageId::new(id.0)?)?,
+            (None, Some(title)) => find_by_title(&repository, &title)?,
+            (None, None) => return Err("Pass a page `id` or `title`".into()),
+        };
+        Ok(page.map(|page| PageNode::new(Arc::new(page))))
+    }
+
+    /// All pages, by title
+    async fn pages(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<usize, PageNode<R>>> {
+        let data = data::<R>(ctx);
+        let mut pages = data.visible(&*data.repository.lock().await).find_all()?;
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+        let pages = pages.into_iter().map(|page| PageNode::new(Arc::new(page))).collect();
+        paginate(pages, after, before, first, last).await
+    }
+
+    /// Search pages, blocks and URLs, best match first
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        #[graphql(default_with = "SearchMode::Traditional")] mode: SearchMode,
+        #[graphql(default_with = "SearchResults::All")] results: SearchResults,
+        #[graphql(default = 20)] limit: usize,
+        threshold: Option<f32>,
+    ) -> Result<Vec<SearchHit<R>>> {
+        let data = data::<R>(ctx);
+        let mut request = SearchRequest::new(query).with_result_type(match results {
+            SearchResults::All => ResultType::All,
+            SearchResults::Pages => ResultType::PagesOnly,
+            SearchResults::Blocks => ResultType::BlocksOnly,
+            SearchResults::Urls => ResultType::UrlsOnly,
+        });
+        if mode == SearchMode::Semantic {
+            request = request.with_search_type(SearchType::Semantic);
+        }
+        if let Some(threshold) = threshold {
+            request = request.with_score_threshold(threshold);
+        }
+
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        let use_case = match &data.embedding_service {
+            Some(embedding_service) => SearchPagesAndBlocks::with_embedding_service(&repository, embedding_service.clone()),
+            None => SearchPagesAndBlocks::new(&repository),
+        };
+        let mut matches = use_case.execute(request).await?;
+        matches.truncate(limit);
+
+        let mut hits = Vec::with_capacity(matches.len());
+        for result in matches {
+            let item = match result.item {
+                SearchItem::Page(page) => repository
+                    .find_by_id(&page.page_id)?
+                    .map(|page| SearchItemNode::Page(PageNode::new(Arc::new(page)))),
+                SearchItem::Block(block) => repository.find_by_id(&block.page_id)?.and_then(|page| {
+                    let page = Arc::new(page);
+                    let node = BlockNode::new(page.clone(), page.get_block(&block.block_id)?);
+                    Some(SearchItemNode::Block(node))
+                }),
+                SearchItem::Url(url) => Some(SearchItemNode::Url(UrlNode::new(url.url))),
+                SearchItem::Archive(archive) => Some(SearchItemNode::Archive(ArchiveNode {
+                    url: archive.url.as_str().to_string(),
+                    title: archive.title,
+                    excerpt: archive.excerpt,
+                    archived_at: archive.archived_at.to_rfc3339(),
+                })),
+            };
+            // A match whose page went away since indexing, or is hidden, is skipped
+            if let Some(item) = item {
+                hits.push(SearchHit { score: f64::from(result.score.value()), item });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Every `#tag` used in the graph, by name
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<TagNode<R>>> {
+        let data = data::<R>(ctx);
+        let pages = data.visible(&*data.repository.lock().await).find_all()?;
+        Ok(tag_names(&pages).into_iter().map(TagNode::new).collect())
+    }
+
+    /// A tag by name (case-insensitive), if any block uses it
+    async fn tag(&self, ctx: &Context<'_>, name: String) -> Result<Option<TagNode<R>>> {
+        let data = data::<R>(ctx);
+        let pages = data.visible(&*data.repository.lock().await).find_all()?;
+        let name = name.to_lowercase();
+        Ok(tag_names(&pages).contains(&name).then(|| TagNode::new(name)))
+    }
+
+    /// A URL, to find the blocks that contain it
+    async fn url(&self, url: String) -> Result<UrlNode<R>> {
+        Ok(UrlNode::new(Url::new(url)?))
+    }
+}
+
+/// The schema's [`SchemaData`]
+pub(crate) fn data<'a, R: PageRepository + Send + Sync + 'static>(ctx: &Context<'a>) -> &'a SchemaData<R> {
+    ctx.data_unchecked::<SchemaData<R>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use serde_json::{json, Value};
+
+    fn schema() -> LogjamSchema<InMemoryPageRepository> {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- Ownership moves values #memory\n\t- Borrowing lends them\n- https://doc.rust-lang.org"),
+            ("notes", "Notes", "- Learning [[Rust]] this week\n- Also [[rust]] again #memory"),
+            ("go", "Go", "- Goroutines"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        build_schema(Arc::new(Mutex::new(repo)), None, None)
+    }
+
+    async fn execute(query: &str) -> Value {
+        let response = schema().execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_page_with_nested_blocks_and_backlinks() {
+        let data = execute(
+            r#"{
+                page(title: "rust") {
+                    title
+                    blocks { content children { content parent { content } } }
+                    urls { domain }
+                    backlinks(first: 1) {
+                        edges { node { content page { title } } }
+                        pageInfo { hasNextPage }
+                    }
+                }
+            }"#,
+        )
+        .await;
+
+        let page = &data["page"];
+        assert_eq!(page["title"], "Rust");
+        assert_eq!(page["blocks"][0]["children"][0]["content"], "Borrowing lends them");
+        assert_eq!(page["blocks"][0]["children"][0]["parent"]["content"], "Ownership moves values #memory");
+        assert_eq!(page["urls"], json!([{ "domain": "doc.rust-lang.org" }]));
+        assert_eq!(page["backlinks"]["edges"][0]["node"]["content"], "Learning [[Rust]] this week");
+        assert_eq!(page["backlinks"]["edges"][0]["node"]["page"]["title"], "Notes");
+        assert_eq!(page["backlinks"]["pageInfo"]["hasNextPage"], true);
+    }
+
+    #[tokio::test]
+    async fn test_search_results_and_tags() {
+        let data = execute(
+            r#"{
+                search(query: "goroutines", results: BLOCKS) {
+                    item { __typename ... on Block { content page { title } } }
+                }
+                tags { name blocks { edges { node { page { title } } } } }
+                notes: page(id: "notes") { references { edges { node { title isTag page { id } } } } }
+            }"#,
+        )
+        .await;
+
+        let item = &data["search"][0]["item"];
+        assert_eq!(item["__typename"], "Block");
+        assert_eq!(item["page"]["title"], "Go");
+
+        let tag = &data["tags"][0];
+        assert_eq!(tag["name"], "memory");
+        let tagged: Vec<&Value> = tag["blocks"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| &edge["node"]["page"]["title"])
+            .collect();
+        assert_eq!(tagged, vec!["Notes", "Rust"]);
+
+        // Links resolve to their page case-insensitively; tags without a page don't
+        let references = &data["notes"]["references"]["edges"];
+        assert_eq!(references[0]["node"]["page"]["id"], "rust");
+        assert_eq!(references[1]["node"]["page"]["id"], "rust");
+        assert_eq!(references[2]["node"], json!({ "title": "memory", "isTag": true, "page": null }));
+    }
+
+    #[tokio::test]
+    async fn test_pages_connection_pages_by_cursor() {
+        let data = execute("{ pages(first: 2) { edges { cursor node { title } } pageInfo { endCursor } } }").await;
+        let titles: Vec<&Value> = data["pages"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| &edge["node"]["title"])
+            .collect();
+        assert_eq!(titles, vec!["Go", "Notes"]);
+
+        let cursor = data["pages"]["pageInfo"]["endCursor"].as_str().unwrap();
+        let data = execute(&format!(r#"{{ pages(after: "{}") {{ edges {{ node {{ title }} }} }} }}"#, cursor)).await;
+        assert_eq!(data["pages"]["edges"], json!([{ "node": { "title": "Rust" } }]));
+
+        let response = schema().execute("{ page { title } }").await;
+        assert_eq!(response.errors.len(), 1);
+    }
+}
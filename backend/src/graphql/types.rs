@@ -0,0 +1,501 @@
+/// GraphQL object types wrapping the domain model
+///
+/// Nested fields resolve lazily against the repository in the schema's data,
+/// so a query only pays for the backlinks and references it asks for.
+use super::schema::data;
+use crate::application::repositories::PageRepository;
+use crate::application::use_cases::{GetBacklinks, GetPagesForUrl};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::entities::Block;
+use crate::domain::value_objects::{BlockId, PageId, PageReference, Url};
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{Context, Object, OutputType, Result, SimpleObject, Union, ID};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A `key:: value` property of a page or block
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct Property {
+    pub key: String,
+    pub value: String,
+}
+
+fn properties(properties: &BTreeMap<String, String>) -> Vec<Property> {
+    properties
+        .iter()
+        .map(|(key, value)| Property {
+            key: key.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+pub struct PageNode<R> {
+    page: Arc<Page>,
+    _repository: PhantomData<fn() -> R>,
+}
+
+impl<R> PageNode<R> {
+    pub(crate) fn new(page: Arc<Page>) -> Self {
+        PageNode {
+            page,
+            _repository: PhantomData,
+        }
+    }
+}
+
+#[Object(name = "Page")]
+impl<R: PageRepository + Send + Sync + 'static> PageNode<R> {
+    async fn id(&self) -> ID {
+        ID(self.page.id().as_str().to_string())
+    }
+
+    async fn title(&self) -> &str {
+        self.page.title()
+    }
+
+    /// Source file, for pages read from a graph directory
+    async fn file_path(&self) -> Option<String> {
+        self.page.file_path().map(|path| path.display().to_string())
+    }
+
+    async fn properties(&self) -> Vec<Property> {
+        properties(self.page.properties())
+    }
+
+    /// Top-level blocks, in page order
+    async fn blocks(&self) -> Vec<BlockNode<R>> {
+        self.page
+            .root_blocks()
+            .into_iter()
+            .map(|block| BlockNode::new(self.page.clone(), block))
+            .collect()
+    }
+
+    async fn block_count(&self) -> usize {
+        self.page.all_blocks().count()
+    }
+
+    /// Distinct URLs in the page's blocks, in page order
+    async fn urls(&self) -> Vec<UrlNode<R>> {
+        let mut seen = HashSet::new();
+        outline(&self.page)
+            .into_iter()
+            .flat_map(|block| block.urls())
+            .filter(|url| seen.insert(*url))
+            .cloned()
+            .map(UrlNode::new)
+            .collect()
+    }
+
+    /// Distinct `#tags` in the page's blocks
+    async fn tags(&self) -> Vec<TagNode<R>> {
+        tag_names(std::iter::once(&*self.page)).into_iter().map(TagNode::new).collect()
+    }
+
+    /// `[[links]]` and `#tags` in the page's blocks, in page order
+    async fn references(
+        &self,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<usize, ReferenceNode<R>>> {
+        let references = outline(&self.page)
+            .into_iter()
+            .flat_map(|block| {
+                block
+                    .page_references()
+                    .iter()
+                    .map(|reference| ReferenceNode::new(reference.clone(), BlockNode::new(self.page.clone(), block)))
+            })
+            .collect();
+        paginate(references, after, before, first, last).await
+    }
+
+    /// Blocks on other pages that link to or tag this page
+    async fn backlinks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<usize, BlockNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        let backlinks = GetBacklinks::new(&repository).execute(self.page.id())?;
+
+        // Grouped by page as the use case orders them, then in outline order
+        let mut referencing: Vec<(PageId, HashSet<BlockId>)> = Vec::new();
+        for backlink in backlinks {
+            match referencing.last_mut() {
+                Some((page_id, block_ids)) if *page_id == backlink.page_id => {
+                    block_ids.insert(backlink.block_id);
+                }
+                _ => referencing.push((backlink.page_id, HashSet::from([backlink.block_id]))),
+            }
+        }
+
+        let mut blocks = Vec::new();
+        for (page_id, block_ids) in referencing {
+            let Some(page) = repository.find_by_id(&page_id)? else {
+                continue;
+            };
+            let page = Arc::new(page);
+            blocks.extend(
+                outline(&page)
+                    .into_iter()
+                    .filter(|block| block_ids.contains(block.id()))
+                    .map(|block| BlockNode::new(page.clone(), block)),
+            );
+        }
+        drop(store);
+        paginate(blocks, after, before, first, last).await
+    }
+}
+
+pub struct BlockNode<R> {
+    page: Arc<Page>,
+    block: Block,
+    _repository: PhantomData<fn() -> R>,
+}
+
+impl<R> BlockNode<R> {
+    pub(crate) fn new(page: Arc<Page>, block: &Block) -> Self {
+        BlockNode {
+            block: block.clone(),
+            page,
+            _repository: PhantomData,
+        }
+    }
+
+    fn related(&self, blocks: Vec<&Block>) -> Vec<BlockNode<R>> {
+        blocks
+            .into_iter()
+            .map(|block| BlockNode::new(self.page.clone(), block))
+            .collect()
+    }
+}
+
+#[Object(name = "Block")]
+impl<R: PageRepository + Send + Sync + 'static> BlockNode<R> {
+    async fn id(&self) -> ID {
+        ID(self.block.id().as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        self.block.content().as_str()
+    }
+
+    /// Nesting depth, 0 for top-level blocks
+    async fn depth(&self) -> usize {
+        self.block.indent_level().value()
+    }
+
+    async fn properties(&self) -> Vec<Property> {
+        properties(self.block.properties())
+    }
+
+    async fn page(&self) -> PageNode<R> {
+        PageNode::new(self.page.clone())
+    }
+
+    async fn parent(&self) -> Option<BlockNode<R>> {
+        let parent = self.page.get_block(self.block.parent_id()?)?;
+        Some(BlockNode::new(self.page.clone(), parent))
+    }
+
+    async fn children(&self) -> Vec<BlockNode<R>> {
+        self.related(
+            self.block
+                .child_ids()
+                .iter()
+                .filter_map(|id| self.page.get_block(id))
+                .collect(),
+        )
+    }
+
+    /// Enclosing blocks, from the top level down
+    async fn ancestors(&self) -> Vec<BlockNode<R>> {
+        let mut ancestors = self.page.get_ancestors(self.block.id());
+        ancestors.reverse();
+        self.related(ancestors)
+    }
+
+    async fn urls(&self) -> Vec<UrlNode<R>> {
+        self.block.urls().iter().cloned().map(UrlNode::new).collect()
+    }
+
+    /// `[[links]]` and `#tags` in the block
+    async fn references(&self) -> Vec<ReferenceNode<R>> {
+        self.block
+            .page_references()
+            .iter()
+            .map(|reference| ReferenceNode::new(reference.clone(), BlockNode::new(self.page.clone(), &self.block)))
+            .collect()
+    }
+}
+
+/// A `[[link]]` or `#tag` in a block
+pub struct ReferenceNode<R> {
+    reference: PageReference,
+    block: BlockNode<R>,
+}
+
+impl<R> ReferenceNode<R> {
+    fn new(reference: PageReference, block: BlockNode<R>) -> Self {
+        ReferenceNode { reference, block }
+    }
+}
+
+#[Object(name = "Reference")]
+impl<R: PageRepository + Send + Sync + 'static> ReferenceNode<R> {
+    /// Title of the referenced page, as written
+    async fn title(&self) -> &str {
+        self.reference.title()
+    }
+
+    async fn is_tag(&self) -> bool {
+        self.reference.is_tag()
+    }
+
+    /// The block containing the reference
+    async fn block(&self) -> BlockNode<R> {
+        BlockNode::new(self.block.page.clone(), &self.block.block)
+    }
+
+    /// The referenced page, if the graph has one
+    async fn page(&self, ctx: &Context<'_>) -> Result<Option<PageNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        Ok(find_by_title(&repository, self.reference.title())?.map(|page| PageNode::new(Arc::new(page))))
+    }
+}
+
+pub struct UrlNode<R> {
+    url: Url,
+    _repository: PhantomData<fn() -> R>,
+}
+
+impl<R> UrlNode<R> {
+    pub(crate) fn new(url: Url) -> Self {
+        UrlNode {
+            url,
+            _repository: PhantomData,
+        }
+    }
+}
+
+#[Object(name = "Url")]
+impl<R: PageRepository + Send + Sync + 'static> UrlNode<R> {
+    async fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    async fn domain(&self) -> Option<String> {
+        self.url.domain()
+    }
+
+    /// Blocks containing the URL, across the graph
+    async fn blocks(&self, ctx: &Context<'_>) -> Result<Vec<BlockNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        let mut blocks = Vec::new();
+        for connection in GetPagesForUrl::new(&repository).execute(&self.url)? {
+            let Some(page) = repository.find_by_id(&connection.page_id)? else {
+                continue;
+            };
+            let page = Arc::new(page);
+            blocks.extend(
+                connection
+                    .blocks_with_url
+                    .iter()
+                    .filter_map(|id| page.get_block(id))
+                    .map(|block| BlockNode::new(page.clone(), block)),
+            );
+        }
+        Ok(blocks)
+    }
+}
+
+/// A readable snapshot of a linked web page
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+#[graphql(name = "Archive")]
+pub struct ArchiveNode {
+    pub url: String,
+    pub title: Option<String>,
+    /// The matching passage of the page's archived text
+    pub excerpt: String,
+    /// When the snapshot was taken, as RFC 3339
+    pub archived_at: String,
+}
+
+/// A `#tag` used somewhere in the graph
+pub struct TagNode<R> {
+    name: String,
+    _repository: PhantomData<fn() -> R>,
+}
+
+impl<R> TagNode<R> {
+    pub(crate) fn new(name: String) -> Self {
+        TagNode {
+            name,
+            _repository: PhantomData,
+        }
+    }
+}
+
+#[Object(name = "Tag")]
+impl<R: PageRepository + Send + Sync + 'static> TagNode<R> {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The tag's page, if the graph has one
+    async fn page(&self, ctx: &Context<'_>) -> Result<Option<PageNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        Ok(find_by_title(&repository, &self.name)?.map(|page| PageNode::new(Arc::new(page))))
+    }
+
+    /// Blocks carrying the tag, by page title then page order
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<usize, BlockNode<R>>> {
+        let data = data::<R>(ctx);
+        let store = data.repository.lock().await;
+        let repository = data.visible(&store);
+        let mut pages = repository.find_all()?;
+        drop(store);
+        pages.sort_by(|a, b| a.title().cmp(b.title()));
+
+        let name = self.name.to_lowercase();
+        let mut blocks = Vec::new();
+        for page in pages.into_iter().map(Arc::new) {
+            blocks.extend(
+                outline(&page)
+                    .into_iter()
+                    .filter(|block| {
+                        block
+                            .page_references()
+                            .iter()
+                            .any(|reference| reference.is_tag() && reference.title().to_lowercase() == name)
+                    })
+                    .map(|block| BlockNode::new(page.clone(), block)),
+            );
+        }
+        paginate(blocks, after, before, first, last).await
+    }
+}
+
+/// A search match, with its relevance score
+pub struct SearchHit<R: PageRepository + Send + Sync + 'static> {
+    pub(crate) score: f64,
+    pub(crate) item: SearchItemNode<R>,
+}
+
+#[Object(name = "SearchResult")]
+impl<R: PageRepository + Send + Sync + 'static> SearchHit<R> {
+    async fn score(&self) -> f64 {
+        self.score
+    }
+
+    async fn item(&self) -> &SearchItemNode<R> {
+        &self.item
+    }
+}
+
+#[derive(Union)]
+#[graphql(name = "SearchItem")]
+pub enum SearchItemNode<R: PageRepository + Send + Sync + 'static> {
+    Page(PageNode<R>),
+    Block(BlockNode<R>),
+    Url(UrlNode<R>),
+    Archive(ArchiveNode),
+}
+
+/// Look a page up by title, exactly first and then ignoring case
+pub(crate) fn find_by_title<R: PageRepository>(
+    repository: &R,
+    title: &str,
+) -> crate::domain::DomainResult<Option<Page>> {
+    if let Some(page) = repository.find_by_title(title)? {
+        return Ok(Some(page));
+    }
+    let title = title.to_lowercase();
+    Ok(repository
+        .find_all()?
+        .into_iter()
+        .find(|page| page.title().to_lowercase() == title))
+}
+
+/// A page's blocks in outline order: each block before its children
+pub(crate) fn outline(page: &Page) -> Vec<&Block> {
+    fn visit<'a>(page: &'a Page, block: &'a Block, blocks: &mut Vec<&'a Block>) {
+        blocks.push(block);
+        for child in block.child_ids().iter().filter_map(|id| page.get_block(id)) {
+            visit(page, child, blocks);
+        }
+    }
+
+    let mut blocks = Vec::new();
+    for block in page.root_blocks() {
+        visit(page, block, &mut blocks);
+    }
+    blocks
+}
+
+/// Distinct tag names in the pages, lowercased as Logseq compares them
+pub(crate) fn tag_names<'a>(pages: impl IntoIterator<Item = &'a Page>) -> BTreeSet<String> {
+    pages
+        .into_iter()
+        .flat_map(|page| page.all_page_references())
+        .filter(|reference| reference.is_tag())
+        .map(|reference| reference.title().to_lowercase())
+        .collect()
+}
+
+/// Page through `items` Relay-style, with their offsets as cursors
+pub(crate) async fn paginate<T: OutputType>(
+    items: Vec<T>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> Result<Connection<usize, T>> {
+    query(after, before, first, last, |after: Option<usize>, before: Option<usize>, first, last| async move {
+        let mut start = after.map(|after| after + 1).unwrap_or(0);
+        let mut end = before.unwrap_or(items.len()).min(items.len());
+        if let Some(first) = first {
+            end = end.min(start.saturating_add(first));
+        }
+        if let Some(last) = last {
+            start = start.max(end.saturating_sub(last));
+        }
+        let start = start.min(end);
+
+        let mut connection = Connection::new(start > 0, end < items.len());
+        connection.edges.extend(
+            items
+                .into_iter()
+                .enumerate()
+                .skip(start)
+                .take(end - start)
+                .map(|(offset, item)| Edge::new(offset, item)),
+        );
+        Ok::<_, async_graphql::Error>(connection)
+    })
+    .await
+}
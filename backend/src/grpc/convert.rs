@@ -0,0 +1,221 @@
+/// Conversions from application types to their protobuf messages
+use super::proto;
+use crate::application::dto::{Backlink, PageSummary, SearchItem, SearchResult};
+use crate::application::services::{
+    DuplicateTitleAction, DuplicateTitleResolution, GraphEvent, ImportProgressEvent, ImportSummary, SyncEvent,
+};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::entities::Block;
+use std::path::Path;
+
+fn path(path: &Path) -> String {
+    path.display().to_string()
+}
+
+impl From<&Page> for proto::Page {
+    fn from(page: &Page) -> Self {
+        proto::Page {
+            id: page.id().as_str().to_string(),
+            title: page.title().to_string(),
+            file_path: page.file_path().map(path),
+            properties: page.properties().clone().into_iter().collect(),
+            blocks: page.root_blocks().into_iter().map(|block| block_message(page, block)).collect(),
+        }
+    }
+}
+
+fn block_message(page: &Page, block: &Block) -> proto::Block {
+    proto::Block {
+        id: block.id().as_str().to_string(),
+        content: block.content().as_str().to_string(),
+        properties: block.properties().clone().into_iter().collect(),
+        urls: block.urls().iter().map(|url| url.as_str().to_string()).collect(),
+        references: block
+            .page_references()
+            .iter()
+            .map(|reference| reference.title().to_string())
+            .collect(),
+        children: block
+            .child_ids()
+            .iter()
+            .filter_map(|id| page.get_block(id))
+            .map(|child| block_message(page, child))
+            .collect(),
+    }
+}
+
+impl From<PageSummary> for proto::PageSummary {
+    fn from(summary: PageSummary) -> Self {
+        proto::PageSummary {
+            id: summary.page_id.as_str().to_string(),
+            title: summary.title,
+            block_count: summary.block_count as u64,
+            updated_at: summary.updated_at.map_or(0, |time| time.timestamp()),
+            preview: summary.preview,
+            indexed_at: summary.indexed_at.map_or(0, |time| time.timestamp()),
+            embedded_at: summary.embedded_at.map_or(0, |time| time.timestamp()),
+        }
+    }
+}
+
+impl From<SearchResult> for proto::SearchResult {
+    fn from(result: SearchResult) -> Self {
+        let item = match result.item {
+            SearchItem::Page(page) => proto::search_result::Item::Page(proto::PageHit {
+                page_id: page.page_id.as_str().to_string(),
+                title: page.title,
+                block_count: page.block_count as u64,
+            }),
+            SearchItem::Block(block) => proto::search_result::Item::Block(proto::BlockHit {
+                block_id: block.block_id.as_str().to_string(),
+                content: block.content,
+                page_id: block.page_id.as_str().to_string(),
+                page_title: block.page_title,
+                hierarchy_path: block.hierarchy_path,
+                block_path: block.block_path.map(|path| path.to_string()).unwrap_or_default(),
+            }),
+            SearchItem::Url(url) => proto::search_result::Item::Url(proto::UrlHit {
+                url: url.url.as_str().to_string(),
+                block_id: url.containing_block_id.as_str().to_string(),
+                block_content: url.containing_block_content,
+                page_id: url.page_id.as_str().to_string(),
+                page_title: url.page_title,
+            }),
+            SearchItem::Archive(archive) => proto::search_result::Item::Archive(proto::ArchiveHit {
+                url: archive.url.as_str().to_string(),
+                title: archive.title.unwrap_or_default(),
+                excerpt: archive.excerpt,
+                archived_at: archive.archived_at.timestamp(),
+            }),
+        };
+        proto::SearchResult {
+            score: f64::from(result.score.value()),
+            item: Some(item),
+        }
+    }
+}
+
+impl From<Backlink> for proto::Backlink {
+    fn from(backlink: Backlink) -> Self {
+        proto::Backlink {
+            page_id: backlink.page_id.as_str().to_string(),
+            page_title: backlink.page_title,
+            block_id: backlink.block_id.as_str().to_string(),
+            block_content: backlink.block_content,
+            hierarchy_path: backlink.hierarchy_path,
+            block_path: backlink.block_path.map(|path| path.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&DuplicateTitleResolution> for proto::DuplicateTitle {
+    fn from(duplicate: &DuplicateTitleResolution) -> Self {
+        let (action, renamed_to) = match &duplicate.action {
+            DuplicateTitleAction::Renamed { title } => ("renamed", Some(title.clone())),
+            DuplicateTitleAction::Merged => ("merged", None),
+            DuplicateTitleAction::Rejected => ("rejected", None),
+        };
+        proto::DuplicateTitle {
+            file_path: path(&duplicate.file_path),
+            title: duplicate.title.clone(),
+            kept_by: path(&duplicate.kept_by),
+            action: action.to_string(),
+            renamed_to,
+        }
+    }
+}
+
+impl From<&ImportSummary> for proto::ImportSummary {
+    fn from(summary: &ImportSummary) -> Self {
+        proto::ImportSummary {
+            total_files: summary.total_files as u64,
+            pages_imported: summary.pages_imported as u64,
+            files_skipped: summary.files_skipped as u64,
+            errors: summary
+                .errors
+                .iter()
+                .map(|(file_path, error)| proto::FileError {
+                    file_path: path(file_path),
+                    error: error.clone(),
+                })
+                .collect(),
+            duration_ms: summary.duration_ms,
+            pages_queued: summary.pages_queued as u64,
+            duplicate_titles: summary.duplicate_titles.iter().map(Into::into).collect(),
+            cancelled: summary.cancelled,
+        }
+    }
+}
+
+/// The message for an import progress event
+///
+/// Completion and cancellation are reported with the import's summary
+/// instead, and embedding queue progress isn't part of the API, so those
+/// events map to nothing.
+pub fn import_event(event: ImportProgressEvent) -> Option<proto::ImportEvent> {
+    use proto::import_event::Event;
+
+    let event = match event {
+        ImportProgressEvent::Started { total_files } => Event::Started(proto::ImportStarted {
+            total_files: total_files as u64,
+        }),
+        ImportProgressEvent::FileProcessed { file_path, progress } => Event::FileImported(proto::FileImported {
+            file_path: path(&file_path),
+            files_processed: progress.files_processed() as u64,
+            total_files: progress.total_files() as u64,
+        }),
+        ImportProgressEvent::Failed { error, files_processed } => Event::Failed(proto::ImportFailed {
+            error,
+            files_processed: files_processed as u64,
+        }),
+        ImportProgressEvent::Completed { .. }
+        | ImportProgressEvent::Cancelled { .. }
+        | ImportProgressEvent::QueuedForEmbedding { .. } => return None,
+    };
+    Some(proto::ImportEvent { event: Some(event) })
+}
+
+impl From<GraphEvent> for proto::SyncEvent {
+    fn from(event: GraphEvent) -> Self {
+        use proto::sync_event::Event;
+
+        let changed = |file_path: &Path| proto::FileChanged { file_path: path(file_path) };
+        let message = match &event.event {
+            SyncEvent::SyncStarted => Event::SyncStarted(proto::SyncStarted {}),
+            SyncEvent::FileCreated { file_path } => Event::FileCreated(changed(file_path)),
+            SyncEvent::FileUpdated { file_path } => Event::FileUpdated(changed(file_path)),
+            SyncEvent::FileDeleted { file_path } => Event::FileDeleted(changed(file_path)),
+            SyncEvent::FileRenamed { from, to } => Event::FileRenamed(proto::FileRenamed {
+                from: path(from),
+                to: path(to),
+            }),
+            SyncEvent::SyncCompleted {
+                files_created,
+                files_updated,
+                files_deleted,
+                files_renamed,
+            } => Event::SyncCompleted(proto::SyncCompleted {
+                files_created: *files_created as u64,
+                files_updated: *files_updated as u64,
+                files_deleted: *files_deleted as u64,
+                files_renamed: *files_renamed as u64,
+            }),
+            SyncEvent::Error { file_path, error } => Event::Error(proto::FileError {
+                file_path: path(file_path),
+                error: error.clone(),
+            }),
+            SyncEvent::FileWritten { file_path } => Event::FileWritten(changed(file_path)),
+            SyncEvent::Conflict(conflict) => Event::Conflict(changed(&conflict.file_path)),
+            SyncEvent::Progress { processed, total } => Event::Progress(proto::SyncProgress {
+                processed: *processed as u64,
+                total: *total as u64,
+            }),
+            SyncEvent::DuplicateTitle(duplicate) => Event::DuplicateTitle(duplicate.into()),
+        };
+        proto::SyncEvent {
+            graph_id: event.graph_id,
+            event: Some(message),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+/// Mapping of application errors to gRPC statuses
+use crate::application::services::{EmbeddingError, ImportError};
+use crate::application::use_cases::SearchError;
+use crate::domain::DomainError;
+use tonic::Status;
+
+pub fn domain_status(error: DomainError) -> Status {
+    let message = error.to_string();
+    match error {
+        DomainError::InvalidValue(_) => Status::invalid_argument(message),
+        DomainError::NotFound(_) => Status::not_found(message),
+        DomainError::BusinessRuleViolation(_) => Status::failed_precondition(message),
+        DomainError::InvalidOperation(_) | DomainError::Repository(_) => Status::internal(message),
+    }
+}
+
+pub fn search_status(error: SearchError) -> Status {
+    let message = error.to_string();
+    match error {
+        SearchError::GraphNotIndexed(_) => Status::not_found(message),
+        SearchError::Domain(e) | SearchError::Embedding(EmbeddingError::Domain(e)) => domain_status(e),
+        SearchError::Embedding(EmbeddingError::VectorStore(_)) => Status::unavailable(message),
+        SearchError::Embedding(_) => Status::internal(message),
+    }
+}
+
+pub fn import_status(error: ImportError) -> Status {
+    let message = error.to_string();
+    match error {
+        ImportError::InvalidDirectory(_) | ImportError::CheckpointsUnavailable => Status::invalid_argument(message),
+        ImportError::FileSystem(e) if e.kind() == std::io::ErrorKind::NotFound => Status::not_found(message),
+        ImportError::Parse(_) => Status::invalid_argument(message),
+        ImportError::LogseqApi(_) => Status::unavailable(message),
+        ImportError::DuplicateTitle { .. } => Status::already_exists(message),
+        ImportError::Repository(e) => domain_status(e),
+        ImportError::FileSystem(_) | ImportError::Domain(_) => Status::internal(message),
+    }
+}
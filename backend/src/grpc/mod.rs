@@ -0,0 +1,11 @@
+pub mod convert;
+pub mod error;
+pub mod service;
+
+/// Messages, client and server generated from `backend/proto/logjam.proto`
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("logjam.v1");
+}
+
+pub use service::{serve, LogjamService};
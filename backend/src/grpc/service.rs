@@ -0,0 +1,377 @@
+/// gRPC service implementation over the use cases
+use super::convert::import_event;
+use super::error::{domain_status, import_status, search_status};
+use super::proto::logjam_server::{Logjam, LogjamServer};
+use super::proto::{
+    self, page_selector, BacklinksResponse, ImportEvent, ImportRequest, ListPagesRequest, ListPagesResponse,
+    PageSelector, SearchResponse, WatchSyncRequest,
+};
+use crate::application::dto::{ResultType, SearchRequest, SearchType};
+use crate::application::repositories::PageRepository;
+use crate::application::services::{
+    EmbeddingService, GraphManager, ImportService, ProgressCallback, VisibilityPolicy, VisiblePages,
+};
+use crate::application::use_cases::{GetBacklinks, SearchPagesAndBlocks};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::{GraphId, LogseqDirectoryPath, PageId};
+use crate::infrastructure::file_system::detect_layout;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+type EventStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// The `logjam.v1.Logjam` service: the page repository, plus the optional
+/// services behind semantic search, imports and sync events
+///
+/// Calls whose service isn't configured fail with `UNAVAILABLE`, except
+/// semantic search, which falls back to keyword search. As with the REST
+/// API, imports write through the import service's own repository.
+pub struct LogjamService<R: PageRepository> {
+    repository: Arc<Mutex<R>>,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    /// One import runs at a time
+    import_service: Option<Arc<Mutex<ImportService<R>>>>,
+    graphs: Option<Arc<GraphManager<R>>>,
+    /// Without it, private pages and blocks are served like the others
+    visibility: Option<VisibilityPolicy>,
+}
+
+impl<R: PageRepository> LogjamService<R> {
+    pub fn new(repository: R) -> Self {
+        LogjamService {
+            repository: Arc::new(Mutex::new(repository)),
+            embedding_service: None,
+            import_service: None,
+            graphs: None,
+            visibility: None,
+        }
+    }
+
+    /// Enable semantic search
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Enable `Import`
+    pub fn with_import_service(mut self, import_service: ImportService<R>) -> Self {
+        self.import_service = Some(Arc::new(Mutex::new(import_service)));
+        self
+    }
+
+    /// Stream the sync events of the manager's graphs from `WatchSync`
+    pub fn with_graph_manager(mut self, graphs: Arc<GraphManager<R>>) -> Self {
+        self.graphs = Some(graphs);
+        self
+    }
+
+    /// Leave pages and blocks the policy hides out of every response, as
+    /// the REST API's `ApiState::with_visibility` does
+    pub fn with_visibility(mut self, visibility: VisibilityPolicy) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// `repository` as the visibility policy lets it be served
+    fn visible<'a>(&self, repository: &'a R) -> VisiblePages<'a, R> {
+        VisiblePages::new(repository, self.visibility)
+    }
+}
+
+/// Serve the gRPC API on an address until the process stops
+pub async fn serve<R>(address: SocketAddr, service: LogjamService<R>) -> Result<(), tonic::transport::Error>
+where
+    R: PageRepository + Send + Sync + 'static,
+{
+    tracing::info!("Serving the gRPC API on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(LogjamServer::new(service))
+        .serve(address)
+        .await
+}
+
+#[tonic::async_trait]
+impl<R: PageRepository + Send + Sync + 'static> Logjam for LogjamService<R> {
+    async fn search(&self, request: Request<proto::SearchRequest>) -> Result<Response<SearchResponse>, Status> {
+        let request = request.into_inner();
+        let search_type = match request.mode() {
+            proto::SearchMode::Traditional => SearchType::Traditional,
+            proto::SearchMode::Semantic => SearchType::Semantic,
+        };
+        let result_type = match request.results() {
+            proto::ResultType::All => ResultType::All,
+            proto::ResultType::Pages => ResultType::PagesOnly,
+            proto::ResultType::Blocks => ResultType::BlocksOnly,
+            proto::ResultType::Urls => ResultType::UrlsOnly,
+        };
+        let mut search = SearchRequest::new(request.query)
+            .with_search_type(search_type)
+            .with_result_type(result_type);
+        if let Some(threshold) = request.threshold {
+            search = search.with_score_threshold(threshold);
+        }
+        if let Some(graph) = request.graph {
+            search = search.with_graph(GraphId::new(graph).map_err(domain_status)?);
+        }
+
+        let store = self.repository.lock().await;
+        let repository = self.visible(&store);
+        let use_case = match &self.embedding_service {
+            Some(embedding_service) => SearchPagesAndBlocks::with_embedding_service(&repository, embedding_service.clone()),
+            None => SearchPagesAndBlocks::new(&repository),
+        };
+        let mut results = use_case.execute(search).await.map_err(search_status)?;
+        if request.limit > 0 {
+            results.truncate(request.limit as usize);
+        }
+
+        Ok(Response::new(SearchResponse {
+            results: results.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn list_pages(&self, _request: Request<ListPagesRequest>) -> Result<Response<ListPagesResponse>, Status> {
+        let mut pages = self
+            .visible(&*self.repository.lock().await)
+            .find_summaries()
+            .map_err(domain_status)?;
+        pages.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(Response::new(ListPagesResponse {
+            pages: pages.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_page(&self, request: Request<PageSelector>) -> Result<Response<proto::Page>, Status> {
+        let store = self.repository.lock().await;
+        let page = find_page(&self.visible(&store), request.into_inner())?;
+        Ok(Response::new((&page).into()))
+    }
+
+    async fn get_backlinks(&self, request: Request<PageSelector>) -> Result<Response<BacklinksResponse>, Status> {
+        let store = self.repository.lock().await;
+        let repository = self.visible(&store);
+        let page = find_page(&repository, request.into_inner())?;
+        let backlinks = GetBacklinks::new(&repository)
+            .execute(page.id())
+            .map_err(domain_status)?;
+        Ok(Response::new(BacklinksResponse {
+            backlinks: backlinks.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    type ImportStream = EventStream<ImportEvent>;
+
+    async fn import(&self, request: Request<ImportRequest>) -> Result<Response<Self::ImportStream>, Status> {
+        let import_service = self
+            .import_service
+            .clone()
+            .ok_or_else(|| Status::unavailable("Imports are not enabled on this server"))?;
+        let request = request.into_inner();
+        let path = PathBuf::from(request.path);
+        if !path.exists() {
+            return Err(Status::not_found(format!("{} does not exist", path.display())));
+        }
+        if request.incremental && !path.is_dir() {
+            return Err(Status::invalid_argument("Incremental imports need a graph directory"));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let progress_tx = tx.clone();
+        let callback: ProgressCallback = Arc::new(move |event| {
+            if let Some(message) = import_event(event) {
+                let _ = progress_tx.send(Ok(message));
+            }
+        });
+
+        // The import runs to completion even if the client stops listening
+        tokio::spawn(async move {
+            let mut import_service = import_service.lock().await;
+            let result = if request.incremental {
+                match LogseqDirectoryPath::with_layout(&path, detect_layout(&path)) {
+                    Ok(directory) => import_service.import_directory_incremental(directory, Some(callback)).await,
+                    Err(e) => Err(e.into()),
+                }
+            } else {
+                import_service.import_path(&path, Some(callback)).await
+            };
+            let message = result.map(|summary| ImportEvent {
+                event: Some(proto::import_event::Event::Completed((&summary).into())),
+            });
+            let _ = tx.send(message.map_err(import_status));
+        });
+
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(rx))))
+    }
+
+    type WatchSyncStream = EventStream<proto::SyncEvent>;
+
+    async fn watch_sync(&self, request: Request<WatchSyncRequest>) -> Result<Response<Self::WatchSyncStream>, Status> {
+        let graphs = self
+            .graphs
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("Sync is not enabled on this server"))?;
+        let graph_id = request.into_inner().graph_id;
+        if !graph_id.is_empty() && graphs.service(&graph_id).await.is_none() {
+            return Err(Status::not_found(format!("Graph {} not found", graph_id)));
+        }
+
+        let repository = self.repository.clone();
+        let visibility = self.visibility;
+        let events = BroadcastStream::new(graphs.subscribe()).then(move |event| {
+            let repository = repository.clone();
+            let graph_id = graph_id.clone();
+            async move {
+                match event {
+                    Ok(event) if graph_id.is_empty() || event.graph_id == graph_id => {
+                        // Events about hidden pages' files are left out
+                        let shown = visibility.is_none()
+                            || VisiblePages::new(&*repository.lock().await, visibility).shows_sync_event(&event.event);
+                        shown.then(|| Ok(event.into()))
+                    }
+                    Ok(_) => None,
+                    // A slow client misses events rather than losing the stream
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                        tracing::warn!("gRPC sync event stream skipped {} events", skipped);
+                        None
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(events.filter_map(|event| event))))
+    }
+}
+
+/// Look a page up by id, or by title, exactly first and then ignoring case
+fn find_page<R: PageRepository>(repository: &R, selector: PageSelector) -> Result<Page, Status> {
+    let page = match selector.page {
+        Some(page_selector::Page::Id(id)) => {
+            let page_id = PageId::new(id).map_err(domain_status)?;
+            repository.find_by_id(&page_id).map_err(domain_status)?
+        }
+        Some(page_selector::Page::Title(title)) => match repository.find_by_title(&title).map_err(domain_status)? {
+            Some(page) => Some(page),
+            None => {
+                let title = title.to_lowercase();
+                repository
+                    .find_all()
+                    .map_err(domain_status)?
+                    .into_iter()
+                    .find(|page| page.title().to_lowercase() == title)
+            }
+        },
+        None => return Err(Status::invalid_argument("Pass a page id or title")),
+    };
+    page.ok_or_else(|| Status::not_found("No such page"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use tempfile::TempDir;
+    use tonic::Code;
+
+    fn service() -> LogjamService<InMemoryPageRepository> {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- Ownership moves values\n\t- Borrowing lends them"),
+            ("notes", "Notes", "- Learning [[Rust]] this week"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        LogjamService::new(repo)
+    }
+
+    fn by_title(title: &str) -> Request<PageSelector> {
+        Request::new(PageSelector {
+            page: Some(page_selector::Page::Title(title.to_string())),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pages_search_and_backlinks() {
+        let service = service();
+
+        let page = service.get_page(by_title("rust")).await.unwrap().into_inner();
+        assert_eq!(page.id, "rust");
+        assert_eq!(page.blocks[0].children[0].content, "Borrowing lends them");
+
+        let backlinks = service.get_backlinks(by_title("Rust")).await.unwrap().into_inner().backlinks;
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].page_title, "Notes");
+
+        let request = proto::SearchRequest {
+            query: "borrowing".to_string(),
+            results: proto::ResultType::Blocks.into(),
+            limit: 1,
+            ..Default::default()
+        };
+        let results = service.search(Request::new(request)).await.unwrap().into_inner().results;
+        assert_eq!(results.len(), 1);
+        match &results[0].item {
+            Some(proto::search_result::Item::Block(block)) => {
+                assert_eq!(block.hierarchy_path, vec!["Ownership moves values", "Borrowing lends them"]);
+            }
+            other => panic!("expected a block, got {:?}", other),
+        }
+
+        let status = service.get_page(by_title("Go")).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_import_streams_progress_then_summary() {
+        let graph = TempDir::new().unwrap();
+        std::fs::create_dir(graph.path().join("pages")).unwrap();
+        std::fs::write(graph.path().join("pages/rust.md"), "- Ownership").unwrap();
+        std::fs::write(graph.path().join("pages/go.md"), "- Goroutines").unwrap();
+
+        let repository = InMemoryPageRepository::new();
+        let service =
+            LogjamService::new(repository.clone()).with_import_service(ImportService::new(repository.clone()));
+        let request = ImportRequest {
+            path: graph.path().display().to_string(),
+            incremental: false,
+        };
+        let events: Vec<ImportEvent> = service
+            .import(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        use proto::import_event::Event;
+        assert!(matches!(events.first().unwrap().event, Some(Event::Started(proto::ImportStarted { total_files: 2 }))));
+        let imported = events.iter().filter(|event| matches!(event.event, Some(Event::FileImported(_))));
+        assert_eq!(imported.count(), 2);
+        match &events.last().unwrap().event {
+            Some(Event::Completed(summary)) => assert_eq!(summary.pages_imported, 2),
+            other => panic!("expected the summary last, got {:?}", other),
+        }
+        assert_eq!(repository.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_services_are_unavailable() {
+        let service = service();
+        let request = ImportRequest {
+            path: "/tmp".to_string(),
+            incremental: false,
+        };
+        assert_eq!(service.import(Request::new(request)).await.err().unwrap().code(), Code::Unavailable);
+        let request = Request::new(WatchSyncRequest::default());
+        assert_eq!(service.watch_sync(request).await.err().unwrap().code(), Code::Unavailable);
+    }
+}
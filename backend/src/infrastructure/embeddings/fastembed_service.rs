@@ -1,40 +1,241 @@
 /// FastEmbed service for local embedding generation
 use anyhow::{Context, Result};
-use fastembed::{EmbeddingModel as FastEmbedModel, InitOptions, TextEmbedding};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::{debug, info};
-
+use fastembed::{
+    EmbeddingModel as FastEmbedModel, ExecutionProviderDispatch, InitOptions, TextEmbedding,
+};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OnceCell};
+use tracing::{debug, info, warn};
+
+use super::tokenizer::ModelTokenizer;
 use crate::domain::value_objects::{EmbeddingModel, EmbeddingVector};
 
+/// ONNX Runtime execution provider used for inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionProvider {
+    #[default]
+    Cpu,
+    /// NVIDIA GPU (requires the `cuda` feature)
+    Cuda { device_id: i32 },
+    /// DirectX 12 GPU on Windows (requires the `directml` feature)
+    DirectMl { device_id: i32 },
+}
+
+impl ExecutionProvider {
+    /// Build the ONNX Runtime dispatch list, failing if support wasn't compiled in
+    ///
+    /// GPU providers fall back to CPU at runtime if no device is available.
+    fn dispatch(self) -> Result<Vec<ExecutionProviderDispatch>> {
+        match self {
+            ExecutionProvider::Cpu => Ok(Vec::new()),
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda { device_id } => Ok(vec![ort::ep::CUDA::default()
+                .with_device_id(device_id)
+                .build()]),
+            #[cfg(not(feature = "cuda"))]
+            ExecutionProvider::Cuda { .. } => {
+                anyhow::bail!("CUDA execution provider requires building with the `cuda` feature")
+            }
+            #[cfg(feature = "directml")]
+            ExecutionProvider::DirectMl { device_id } => Ok(vec![ort::ep::DirectML::default()
+                .with_device_id(device_id)
+                .build()]),
+            #[cfg(not(feature = "directml"))]
+            ExecutionProvider::DirectMl { .. } => anyhow::bail!(
+                "DirectML execution provider requires building with the `directml` feature"
+            ),
+        }
+    }
+}
+
+/// Runtime options for FastEmbed model initialization
+#[derive(Debug, Clone, Default)]
+pub struct FastEmbedOptions {
+    /// Execution provider for inference
+    pub execution_provider: ExecutionProvider,
+    /// Intra-op threads for ONNX Runtime; `None` uses all cores
+    pub intra_threads: Option<usize>,
+    /// Directory where downloaded models are cached; `None` uses fastembed's default
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl FastEmbedOptions {
+    pub fn with_execution_provider(mut self, execution_provider: ExecutionProvider) -> Self {
+        self.execution_provider = execution_provider;
+        self
+    }
+
+    pub fn with_intra_threads(mut self, intra_threads: usize) -> Self {
+        self.intra_threads = Some(intra_threads);
+        self
+    }
+
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+}
+
+/// Progress of loading the embedding model
+///
+/// fastembed doesn't report byte-level download progress, so a download is
+/// announced up front (`Started { download: true }`) and completes with `Ready`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelLoadEvent {
+    /// Loading began; `download` is true if the model isn't cached and will be fetched
+    Started { model: EmbeddingModel, download: bool },
+    /// The model is loaded and ready for inference
+    Ready { model: EmbeddingModel, elapsed: Duration },
+    /// Loading failed; the next use retries
+    Failed { model: EmbeddingModel, error: String },
+}
+
+pub type ModelLoadCallback = Arc<dyn Fn(ModelLoadEvent) + Send + Sync>;
+
 /// Service for generating embeddings using fastembed
+///
+/// The model is downloaded and loaded on first use (or on [`warmup`](Self::warmup)),
+/// so creating the service is cheap.
 pub struct FastEmbedService {
-    model: Arc<Mutex<TextEmbedding>>,
+    model: OnceCell<Mutex<TextEmbedding>>,
     model_type: EmbeddingModel,
+    options: FastEmbedOptions,
+    load_callback: RwLock<Option<ModelLoadCallback>>,
 }
 
 impl FastEmbedService {
     /// Create a new FastEmbed service with the specified model
     pub async fn new(model_type: EmbeddingModel) -> Result<Self> {
-        info!("Initializing FastEmbed service with model: {}", model_type);
-
-        let fastembed_model = match model_type {
-            EmbeddingModel::AllMiniLML6V2 => FastEmbedModel::AllMiniLML6V2,
-        };
+        Self::with_options(model_type, FastEmbedOptions::default()).await
+    }
 
-        let model = TextEmbedding::try_new(
-            InitOptions::new(fastembed_model).with_show_download_progress(true),
-        )
-        .context("Failed to initialize FastEmbed model")?;
+    /// Create a new FastEmbed service with explicit runtime options
+    ///
+    /// Options are validated here, but the model itself isn't loaded until first use.
+    pub async fn with_options(model_type: EmbeddingModel, options: FastEmbedOptions) -> Result<Self> {
+        info!(
+            "Configuring FastEmbed service with model: {} ({:?})",
+            model_type, options
+        );
 
-        info!("FastEmbed model initialized successfully");
+        options.execution_provider.dispatch()?;
 
         Ok(FastEmbedService {
-            model: Arc::new(Mutex::new(model)),
+            model: OnceCell::new(),
             model_type,
+            options,
+            load_callback: RwLock::new(None),
         })
     }
 
+    /// Report model loading progress to `callback`
+    pub fn set_load_callback(&self, callback: ModelLoadCallback) {
+        if let Ok(mut slot) = self.load_callback.write() {
+            *slot = Some(callback);
+        }
+    }
+
+    /// Load the model now and run one inference, so the first real request isn't slow
+    pub async fn warmup(&self) -> Result<()> {
+        self.embed_text("warmup").await.map(|_| ())
+    }
+
+    /// Whether the model has been loaded
+    pub fn is_loaded(&self) -> bool {
+        self.model.initialized()
+    }
+
+    /// The loaded model, loading it on first call
+    async fn model(&self) -> Result<&Mutex<TextEmbedding>> {
+        self.model.get_or_try_init(|| self.load()).await
+    }
+
+    async fn load(&self) -> Result<Mutex<TextEmbedding>> {
+        let download = !self.is_cached();
+        info!(
+            "Loading FastEmbed model {}{}",
+            self.model_type,
+            if download { " (downloading)" } else { "" }
+        );
+        self.emit(ModelLoadEvent::Started {
+            model: self.model_type,
+            download,
+        });
+
+        let started = Instant::now();
+        let init_options = self.init_options()?;
+        // Downloading and session creation block, keep them off the async workers
+        let loaded = tokio::task::spawn_blocking(move || TextEmbedding::try_new(init_options))
+            .await
+            .context("FastEmbed model loading task failed")
+            .and_then(|result| result.context("Failed to initialize FastEmbed model"));
+
+        match loaded {
+            Ok(model) => {
+                let elapsed = started.elapsed();
+                info!("FastEmbed model loaded in {:?}", elapsed);
+                self.emit(ModelLoadEvent::Ready {
+                    model: self.model_type,
+                    elapsed,
+                });
+                Ok(Mutex::new(model))
+            }
+            Err(e) => {
+                warn!("Failed to load FastEmbed model: {:#}", e);
+                self.emit(ModelLoadEvent::Failed {
+                    model: self.model_type,
+                    error: format!("{:#}", e),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    fn fastembed_model(&self) -> FastEmbedModel {
+        match self.model_type {
+            EmbeddingModel::AllMiniLML6V2 => FastEmbedModel::AllMiniLML6V2,
+        }
+    }
+
+    fn init_options(&self) -> Result<InitOptions> {
+        let mut init_options = InitOptions::new(self.fastembed_model())
+            .with_show_download_progress(true)
+            .with_execution_providers(self.options.execution_provider.dispatch()?);
+        if let Some(threads) = self.options.intra_threads {
+            init_options = init_options.with_intra_threads(threads);
+        }
+        if let Some(ref cache_dir) = self.options.cache_dir {
+            init_options = init_options.with_cache_dir(cache_dir.clone());
+        }
+        Ok(init_options)
+    }
+
+    /// Whether the model files are already in the cache directory
+    fn is_cached(&self) -> bool {
+        let cache_dir = self
+            .options
+            .cache_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(fastembed::get_cache_dir()));
+        TextEmbedding::get_model_info(&self.fastembed_model())
+            .map(|info| {
+                cache_dir
+                    .join(format!("models--{}", info.model_code.replace('/', "--")))
+                    .exists()
+            })
+            .unwrap_or(false)
+    }
+
+    fn emit(&self, event: ModelLoadEvent) {
+        if let Ok(callback) = self.load_callback.read() {
+            if let Some(callback) = callback.as_ref() {
+                callback(event);
+            }
+        }
+    }
+
     /// Create a new FastEmbed service with the default model
     pub async fn new_default() -> Result<Self> {
         Self::new(EmbeddingModel::default()).await
@@ -44,7 +245,7 @@ impl FastEmbedService {
     pub async fn embed_text(&self, text: &str) -> Result<EmbeddingVector> {
         debug!("Generating embedding for text (length: {})", text.len());
 
-        let mut model = self.model.lock().await;
+        let mut model = self.model().await?.lock().await;
         let embeddings = model
             .embed(vec![text], None)
             .context("Failed to generate embedding")?;
@@ -67,7 +268,7 @@ impl FastEmbedService {
             return Ok(Vec::new());
         }
 
-        let mut model = self.model.lock().await;
+        let mut model = self.model().await?.lock().await;
         let embeddings = model
             .embed(texts, None)
             .context("Failed to generate batch embeddings")?;
@@ -83,6 +284,12 @@ impl FastEmbedService {
         Ok(result)
     }
 
+    /// Tokenizer of the model, for measuring text in model tokens (loads the model)
+    pub async fn tokenizer(&self) -> Result<ModelTokenizer> {
+        let model = self.model().await?.lock().await;
+        Ok(ModelTokenizer::new(model.tokenizer.clone()))
+    }
+
     /// Get the model type being used
     pub fn model_type(&self) -> EmbeddingModel {
         self.model_type
@@ -98,6 +305,28 @@ impl FastEmbedService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cpu_provider_uses_runtime_default() {
+        assert!(ExecutionProvider::Cpu.dispatch().unwrap().is_empty());
+    }
+
+    #[cfg(not(feature = "cuda"))]
+    #[test]
+    fn test_cuda_provider_requires_feature() {
+        assert!(ExecutionProvider::Cuda { device_id: 0 }.dispatch().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_model_loads_lazily() {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let service = FastEmbedService::new_default().await.unwrap();
+        let sink = Arc::clone(&events);
+        service.set_load_callback(Arc::new(move |event| sink.lock().unwrap().push(event)));
+
+        assert!(!service.is_loaded());
+        assert!(events.lock().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_create_service() {
         let service = FastEmbedService::new_default().await;
@@ -0,0 +1,132 @@
+/// Maximal marginal relevance (MMR) re-ranking for semantic search results
+use super::qdrant_store::SearchResult;
+use crate::domain::value_objects::EmbeddingVector;
+
+/// Re-rank search results with maximal marginal relevance
+///
+/// Greedily picks the candidate maximising
+/// `lambda * relevance - (1 - lambda) * max_similarity_to_already_selected`.
+/// `lambda = 1.0` keeps the original ranking, `lambda = 0.0` maximises diversity.
+///
+/// Similarity between two results is the cosine similarity of their stored vectors
+/// when both are present; otherwise results from the same page count as duplicates.
+pub fn mmr_rerank(candidates: Vec<SearchResult>, lambda: f32, limit: usize) -> Vec<SearchResult> {
+    let lambda = lambda.clamp(0.0, 1.0);
+    let vectors: Vec<Option<EmbeddingVector>> = candidates
+        .iter()
+        .map(|c| c.vector.clone().and_then(|v| EmbeddingVector::new(v).ok()))
+        .collect();
+
+    let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+    let mut selected: Vec<usize> = Vec::with_capacity(limit.min(candidates.len()));
+
+    while selected.len() < limit && !remaining.is_empty() {
+        let mut best_pos = 0;
+        let mut best_score = f32::NEG_INFINITY;
+
+        for (pos, &idx) in remaining.iter().enumerate() {
+            let redundancy = selected
+                .iter()
+                .map(|&s| similarity(&candidates[idx], &vectors[idx], &candidates[s], &vectors[s]))
+                .fold(0.0_f32, f32::max);
+
+            let mmr_score = lambda * candidates[idx].score - (1.0 - lambda) * redundancy;
+            if mmr_score > best_score {
+                best_score = mmr_score;
+                best_pos = pos;
+            }
+        }
+
+        selected.push(remaining.remove(best_pos));
+    }
+
+    let mut slots: Vec<Option<SearchResult>> = candidates.into_iter().map(Some).collect();
+    selected
+        .into_iter()
+        .filter_map(|idx| slots[idx].take())
+        .collect()
+}
+
+fn similarity(
+    a: &SearchResult,
+    a_vec: &Option<EmbeddingVector>,
+    b: &SearchResult,
+    b_vec: &Option<EmbeddingVector>,
+) -> f32 {
+    if let (Some(va), Some(vb)) = (a_vec, b_vec) {
+        if let Ok(cosine) = va.cosine_similarity(vb) {
+            return cosine;
+        }
+    }
+
+    if a.page_id == b.page_id {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(chunk_id: &str, page_id: &str, score: f32, vector: Option<Vec<f32>>) -> SearchResult {
+        SearchResult {
+            chunk_id: chunk_id.to_string(),
+            block_id: format!("{}-block", chunk_id),
+            page_id: page_id.to_string(),
+            page_title: page_id.to_string(),
+            original_content: String::new(),
+            preprocessed_content: String::new(),
+            hierarchy_path: vec![],
+            score,
+            vector,
+        }
+    }
+
+    #[test]
+    fn test_lambda_one_keeps_relevance_order() {
+        let candidates = vec![
+            result("a", "page-1", 0.9, None),
+            result("b", "page-1", 0.8, None),
+            result("c", "page-2", 0.7, None),
+        ];
+
+        let ranked = mmr_rerank(candidates, 1.0, 3);
+        let ids: Vec<&str> = ranked.iter().map(|r| r.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_diversifies_across_pages_without_vectors() {
+        let candidates = vec![
+            result("a", "page-1", 0.9, None),
+            result("b", "page-1", 0.85, None),
+            result("c", "page-2", 0.7, None),
+        ];
+
+        let ranked = mmr_rerank(candidates, 0.5, 2);
+        let ids: Vec<&str> = ranked.iter().map(|r| r.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_uses_vector_similarity_when_available() {
+        let candidates = vec![
+            result("a", "page-1", 0.9, Some(vec![1.0, 0.0])),
+            result("b", "page-2", 0.88, Some(vec![1.0, 0.01])),
+            result("c", "page-3", 0.8, Some(vec![0.0, 1.0])),
+        ];
+
+        let ranked = mmr_rerank(candidates, 0.5, 2);
+        let ids: Vec<&str> = ranked.iter().map(|r| r.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_limit_larger_than_candidates() {
+        let candidates = vec![result("a", "page-1", 0.9, None)];
+        let ranked = mmr_rerank(candidates, 0.5, 10);
+        assert_eq!(ranked.len(), 1);
+    }
+}
@@ -1,8 +1,28 @@
 /// Embeddings infrastructure for semantic search
 mod fastembed_service;
+mod mmr;
+mod preprocess;
 mod qdrant_store;
+mod rate_limit;
+mod retry;
+mod sparse;
 mod text_preprocessor;
+mod tokenizer;
 
-pub use fastembed_service::FastEmbedService;
-pub use qdrant_store::{ChunkMetadata, CollectionInfo, QdrantVectorStore, SearchResult};
-pub use text_preprocessor::TextPreprocessor;
+pub use fastembed_service::{
+    ExecutionProvider, FastEmbedOptions, FastEmbedService, ModelLoadCallback, ModelLoadEvent,
+};
+pub use mmr::mmr_rerank;
+pub use preprocess::{
+    AddHierarchy, ExpandPageReferences, ExpandTags, PreprocessContext, PreprocessPipeline, PreprocessStage,
+    StripTaskMarkers,
+};
+pub use qdrant_store::{
+    ChunkMetadata, CollectionInfo, CollectionSchema, PageEmbeddingMetadata, PageSearchResult,
+    QdrantConnectionConfig, QdrantVectorStore, SearchResult,
+};
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use retry::RetryPolicy;
+pub use sparse::{SparseEncoder, SparseVector};
+pub use text_preprocessor::{ChunkingStrategy, TextPreprocessor, TokenCounter};
+pub use tokenizer::ModelTokenizer;
@@ -0,0 +1,265 @@
+/// Stages that prepare a block's text for embedding, run as a pipeline
+use regex::Regex;
+use std::fmt;
+use std::sync::Arc;
+
+/// Where the text being preprocessed comes from
+#[derive(Debug, Clone, Copy)]
+pub struct PreprocessContext<'a> {
+    pub page_title: &'a str,
+    /// Contents of the block's ancestors, root first
+    pub hierarchy_path: &'a [String],
+}
+
+/// One step of preparing text for embedding
+///
+/// Implement it to tune what the model sees, e.g. to drop a plugin's markup,
+/// and add the stage to a [`PreprocessPipeline`].
+pub trait PreprocessStage: Send + Sync {
+    /// Short name shown when the pipeline is logged
+    fn name(&self) -> &str;
+
+    fn apply(&self, text: String, context: &PreprocessContext) -> String;
+}
+
+/// Drops a leading task marker (`TODO`, `DONE`, `LATER`, `NOW`, `IN-PROGRESS`)
+pub struct StripTaskMarkers {
+    marker: Regex,
+}
+
+impl StripTaskMarkers {
+    pub fn new() -> Self {
+        StripTaskMarkers {
+            marker: Regex::new(r"^(TODO|DONE|LATER|NOW|IN-PROGRESS)\s+").unwrap(),
+        }
+    }
+}
+
+impl Default for StripTaskMarkers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreprocessStage for StripTaskMarkers {
+    fn name(&self) -> &str {
+        "strip_task_markers"
+    }
+
+    fn apply(&self, text: String, _context: &PreprocessContext) -> String {
+        self.marker.replace(&text, "").into_owned()
+    }
+}
+
+/// Replaces `[[page references]]` with the page name
+pub struct ExpandPageReferences {
+    reference: Regex,
+}
+
+impl ExpandPageReferences {
+    pub fn new() -> Self {
+        ExpandPageReferences {
+            reference: Regex::new(r"\[\[([^\]]+)\]\]").unwrap(),
+        }
+    }
+}
+
+impl Default for ExpandPageReferences {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreprocessStage for ExpandPageReferences {
+    fn name(&self) -> &str {
+        "expand_page_references"
+    }
+
+    fn apply(&self, text: String, _context: &PreprocessContext) -> String {
+        self.reference.replace_all(&text, "$1").into_owned()
+    }
+}
+
+/// Replaces `#tags` with the tag name
+pub struct ExpandTags {
+    tag: Regex,
+}
+
+impl ExpandTags {
+    pub fn new() -> Self {
+        ExpandTags {
+            tag: Regex::new(r"#(\w+)").unwrap(),
+        }
+    }
+}
+
+impl Default for ExpandTags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreprocessStage for ExpandTags {
+    fn name(&self) -> &str {
+        "expand_tags"
+    }
+
+    fn apply(&self, text: String, _context: &PreprocessContext) -> String {
+        self.tag.replace_all(&text, "$1").into_owned()
+    }
+}
+
+/// Prefixes the page title and the closest ancestors, as in
+/// `Page: Books. Context: Reading > Fiction. The block`
+#[derive(Debug, Clone)]
+pub struct AddHierarchy {
+    page_title: bool,
+    depth: usize,
+}
+
+impl AddHierarchy {
+    /// Closest ancestors included unless another depth is set
+    pub const DEFAULT_DEPTH: usize = 2;
+
+    pub fn new() -> Self {
+        AddHierarchy {
+            page_title: true,
+            depth: Self::DEFAULT_DEPTH,
+        }
+    }
+
+    /// Include the `depth` closest ancestors; 0 leaves them out
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn without_page_title(mut self) -> Self {
+        self.page_title = false;
+        self
+    }
+}
+
+impl Default for AddHierarchy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreprocessStage for AddHierarchy {
+    fn name(&self) -> &str {
+        "add_hierarchy"
+    }
+
+    fn apply(&self, text: String, context: &PreprocessContext) -> String {
+        let mut context_parts = vec![];
+        if self.page_title && !context.page_title.is_empty() {
+            context_parts.push(format!("Page: {}", context.page_title));
+        }
+
+        let path = context.hierarchy_path;
+        let parents = &path[path.len() - path.len().min(self.depth)..];
+        if !parents.is_empty() {
+            context_parts.push(format!("Context: {}", parents.join(" > ")));
+        }
+
+        if context_parts.is_empty() {
+            text
+        } else {
+            format!("{}. {}", context_parts.join(". "), text.trim())
+        }
+    }
+}
+
+/// Preprocessing stages, run in order; the result is trimmed
+///
+/// The default pipeline strips task markers, expands page references and
+/// tags, then adds the page title and two closest ancestors. Changing the
+/// pipeline of a graph changes what its chunks embed, so it needs a reindex.
+#[derive(Clone)]
+pub struct PreprocessPipeline {
+    stages: Vec<Arc<dyn PreprocessStage>>,
+}
+
+impl PreprocessPipeline {
+    /// A pipeline without stages, which only trims text
+    pub fn empty() -> Self {
+        PreprocessPipeline { stages: Vec::new() }
+    }
+
+    /// The stages `TextPreprocessor` has always run
+    pub fn standard() -> Self {
+        Self::empty()
+            .with_stage(StripTaskMarkers::new())
+            .with_stage(ExpandPageReferences::new())
+            .with_stage(ExpandTags::new())
+            .with_stage(AddHierarchy::new())
+    }
+
+    /// Run `stage` after the stages added so far
+    pub fn with_stage(mut self, stage: impl PreprocessStage + 'static) -> Self {
+        self.stages.push(Arc::new(stage));
+        self
+    }
+
+    pub fn stage_names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|stage| stage.name())
+    }
+
+    pub fn run(&self, text: &str, context: &PreprocessContext) -> String {
+        let text = self
+            .stages
+            .iter()
+            .fold(text.to_string(), |text, stage| stage.apply(text, context));
+        text.trim().to_string()
+    }
+}
+
+impl Default for PreprocessPipeline {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl fmt::Debug for PreprocessPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.stage_names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Uppercase;
+
+    impl PreprocessStage for Uppercase {
+        fn name(&self) -> &str {
+            "uppercase"
+        }
+
+        fn apply(&self, text: String, _context: &PreprocessContext) -> String {
+            text.to_uppercase()
+        }
+    }
+
+    #[test]
+    fn test_stages_run_in_order_with_custom_ones() {
+        let hierarchy = vec!["Reading".to_string(), "Fiction".to_string(), "Novels".to_string()];
+        let context = PreprocessContext {
+            page_title: "Books",
+            hierarchy_path: &hierarchy,
+        };
+
+        let pipeline = PreprocessPipeline::empty()
+            .with_stage(ExpandTags::new())
+            .with_stage(Uppercase)
+            .with_stage(AddHierarchy::new().with_depth(1).without_page_title());
+        assert_eq!(pipeline.stage_names().collect::<Vec<_>>(), vec!["expand_tags", "uppercase", "add_hierarchy"]);
+        assert_eq!(
+            pipeline.run("TODO read #dune ", &context),
+            "Context: Novels. TODO READ DUNE"
+        );
+        assert_eq!(PreprocessPipeline::empty().run(" [[Dune]] ", &context), "[[Dune]]");
+    }
+}
@@ -1,24 +1,200 @@
 /// Qdrant vector store for semantic search
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use qdrant_client::{
     Payload,
-    Qdrant,
+    Qdrant, QdrantError,
     qdrant::{
-        CreateCollectionBuilder, DeletePointsBuilder, Distance, PointStruct,
-        SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+        Condition, CreateAliasBuilder, CreateCollectionBuilder, DeletePointsBuilder, Distance, Filter, Fusion,
+        Modifier, NamedVectors, PayloadIncludeSelector, PointId, PointStruct,
+        PrefetchQueryBuilder, Query, QueryPointsBuilder, RetrievedPoint, ScoredPoint, ScrollPointsBuilder,
+        SearchPointsBuilder, SparseVectorParamsBuilder, SparseVectorsConfigBuilder,
+        UpsertPointsBuilder, Value, VectorParamsBuilder, Vectors, VectorsConfigBuilder, VectorsOutput,
+        with_payload_selector::SelectorOptions as PayloadSelector,
+        vector_output::Vector,
+        vectors_config::Config as VectorsConfigKind,
     },
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tonic::Code;
 use tracing::{debug, info, warn};
 
+use super::retry::RetryPolicy;
+use super::sparse::SparseEncoder;
 use crate::domain::value_objects::{BlockId, ChunkId, EmbeddingVector, PageId};
+use crate::infrastructure::text::KeywordTokenizer;
+
+/// Name of the dense vector in hybrid collections
+const DENSE_VECTOR_NAME: &str = "dense";
+/// Name of the sparse (BM25) vector in hybrid collections
+const SPARSE_VECTOR_NAME: &str = "sparse";
+/// How many candidates each branch of a hybrid query contributes per requested result
+const HYBRID_PREFETCH_MULTIPLIER: u64 = 2;
+/// RRF score Qdrant gives the first result of a branch (rank r scores 1 / (r + 2))
+const RRF_BEST_RANK_SCORE: f32 = 0.5;
+/// Points upserted per request when a collection is copied
+const COPY_BATCH_SIZE: usize = 256;
+
+/// How to reach a Qdrant server
+///
+/// Plain `http://` URLs work for a local instance. Secured instances and Qdrant
+/// Cloud need an API key and TLS; TLS is used for `https://` URLs, or for any
+/// URL when `tls` is set.
+#[derive(Clone)]
+pub struct QdrantConnectionConfig {
+    /// Qdrant gRPC URL (e.g., "http://localhost:6334")
+    pub url: String,
+    /// API key sent with every request
+    pub api_key: Option<String>,
+    /// Connect over TLS even if the URL says `http://`
+    pub tls: bool,
+    /// Timeout for each request
+    pub timeout: Duration,
+    /// Timeout for establishing the connection
+    pub connect_timeout: Duration,
+    /// Check that the server responds before using it
+    pub check_health: bool,
+    /// Retries for upserts, searches, deletes and scrolls that fail transiently
+    pub retry: RetryPolicy,
+    /// Drop and recreate an existing collection whose vector layout doesn't
+    /// match the model (losing its embeddings) instead of failing to connect
+    pub recreate_on_mismatch: bool,
+}
+
+impl QdrantConnectionConfig {
+    /// Connection to the server at `url` with default settings
+    pub fn new(url: impl Into<String>) -> Self {
+        QdrantConnectionConfig {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Connection to a Qdrant Cloud cluster (TLS with API key)
+    pub fn cloud(url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self::new(url).with_api_key(api_key).with_tls(true)
+    }
+
+    /// Authenticate with an API key
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Force TLS regardless of the URL scheme
+    pub fn with_tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Set the per-request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the connection timeout
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Set the retry policy for transient failures
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Recreate collections whose vector layout doesn't match instead of failing
+    pub fn with_recreate_on_mismatch(mut self, recreate: bool) -> Self {
+        self.recreate_on_mismatch = recreate;
+        self
+    }
+
+    /// Enable or disable the health check on connect
+    pub fn with_health_check(mut self, check_health: bool) -> Self {
+        self.check_health = check_health;
+        self
+    }
+
+    /// URL actually connected to, with the scheme upgraded to `https` if `tls` is set
+    pub fn effective_url(&self) -> String {
+        match self.url.strip_prefix("http://") {
+            Some(rest) if self.tls => format!("https://{}", rest),
+            _ => self.url.clone(),
+        }
+    }
+
+    /// Whether requests are sent over TLS
+    pub fn uses_tls(&self) -> bool {
+        self.effective_url().starts_with("https://")
+    }
+}
+
+impl Default for QdrantConnectionConfig {
+    fn default() -> Self {
+        QdrantConnectionConfig {
+            url: "http://localhost:6334".to_string(),
+            api_key: None,
+            tls: false,
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(5),
+            check_health: true,
+            retry: RetryPolicy::default(),
+            recreate_on_mismatch: false,
+        }
+    }
+}
+
+// Hand-written so the API key never ends up in logs
+impl fmt::Debug for QdrantConnectionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QdrantConnectionConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("tls", &self.tls)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("check_health", &self.check_health)
+            .field("retry", &self.retry)
+            .field("recreate_on_mismatch", &self.recreate_on_mismatch)
+            .finish()
+    }
+}
+
+/// Collection to build a replacement of the collection `name` in, started
+/// at `at`; the time keeps consecutive rebuilds apart
+fn shadow_collection_name(name: &str, at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("{}_shadow_{}", name, at.format("%Y%m%d%H%M%S"))
+}
+
+/// Collection created at `at` to serve the collection `name` from, behind
+/// an alias of that name
+fn aliased_collection_name(name: &str, at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("{}_{}", name, at.format("%Y%m%d%H%M%S"))
+}
+
+/// Payload selector returning only `fields`
+fn payload_fields(fields: &[&str]) -> PayloadIncludeSelector {
+    PayloadIncludeSelector {
+        fields: fields.iter().map(|f| f.to_string()).collect(),
+    }
+}
 
 /// Vector store implementation using Qdrant
 pub struct QdrantVectorStore {
     client: Qdrant,
     collection_name: String,
     dimension_count: usize,
+    /// Set for hybrid collections, which store a sparse BM25 vector next to
+    /// the dense one under named vectors
+    sparse_encoder: Option<SparseEncoder>,
+    retry: RetryPolicy,
 }
 
 impl QdrantVectorStore {
@@ -33,25 +209,114 @@ impl QdrantVectorStore {
         collection_name: impl Into<String>,
         dimension_count: usize,
     ) -> Result<Self> {
+        Self::with_config(
+            &QdrantConnectionConfig::new(url),
+            collection_name,
+            dimension_count,
+            false,
+        )
+        .await
+    }
+
+    /// Create a hybrid vector store that indexes sparse BM25 term vectors
+    /// alongside dense embeddings, enabling server-side hybrid queries
+    ///
+    /// The collection must have been created in hybrid mode; dense-only
+    /// collections can't be reused.
+    pub async fn new_hybrid(
+        url: &str,
+        collection_name: impl Into<String>,
+        dimension_count: usize,
+    ) -> Result<Self> {
+        Self::with_config(
+            &QdrantConnectionConfig::new(url),
+            collection_name,
+            dimension_count,
+            true,
+        )
+        .await
+    }
+
+    /// Create a vector store using full connection settings (API key, TLS, timeouts)
+    ///
+    /// `hybrid` selects a hybrid collection, as with [`QdrantVectorStore::new_hybrid`].
+    pub async fn with_config(
+        connection: &QdrantConnectionConfig,
+        collection_name: impl Into<String>,
+        dimension_count: usize,
+        hybrid: bool,
+    ) -> Result<Self> {
+        let sparse_encoder = hybrid.then(SparseEncoder::default);
+        Self::connect(connection, collection_name.into(), dimension_count, sparse_encoder).await
+    }
+
+    async fn connect(
+        connection: &QdrantConnectionConfig,
+        collection_name: String,
+        dimension_count: usize,
+        sparse_encoder: Option<SparseEncoder>,
+    ) -> Result<Self> {
+        let url = connection.effective_url();
         info!("Connecting to Qdrant at {}", url);
 
-        let client = Qdrant::from_url(url)
+        if connection.api_key.is_some() && !connection.uses_tls() {
+            warn!("Sending Qdrant API key without TLS; use an https:// URL for remote servers");
+        }
+
+        let client = Qdrant::from_url(&url)
+            .api_key(connection.api_key.clone())
+            .timeout(connection.timeout)
+            .connect_timeout(connection.connect_timeout)
             .build()
             .context("Failed to connect to Qdrant")?;
 
-        let collection_name = collection_name.into();
         let store = QdrantVectorStore {
             client,
             collection_name: collection_name.clone(),
             dimension_count,
+            sparse_encoder,
+            retry: connection.retry.clone(),
         };
 
-        // Ensure collection exists
+        if connection.check_health {
+            let version = store
+                .health_check()
+                .await
+                .with_context(|| format!("Qdrant at {} is not reachable", url))?;
+            debug!("Qdrant {} is healthy", version);
+        }
+
+        // Ensure collection exists, as an alias so rebuilds can swap it
         if !store.collection_exists().await? {
             info!("Creating collection: {}", collection_name);
-            store.create_collection().await?;
+            store.behind_alias().await?.swap_into(&collection_name).await?;
         } else {
-            info!("Collection '{}' already exists", collection_name);
+            let mismatches = store.validate_collection().await?;
+            let aliased = store.alias_target(&collection_name).await?.is_some();
+            if mismatches.is_empty() {
+                info!("Collection '{}' already exists", collection_name);
+                if !aliased {
+                    store.move_behind_alias().await?;
+                }
+            } else if connection.recreate_on_mismatch {
+                warn!(
+                    "Recreating collection '{}' ({}); stored embeddings are discarded",
+                    collection_name,
+                    mismatches.join("; ")
+                );
+                if !aliased {
+                    store.delete_collection().await?;
+                }
+                store.behind_alias().await?.swap_into(&collection_name).await?;
+            } else {
+                bail!(
+                    "Collection '{}' doesn't match the embedding configuration: {}. \
+                     Use another collection name, or enable recreate_on_mismatch to \
+                     rebuild it and re-embed",
+                    collection_name,
+                    mismatches.join("; ")
+                );
+            }
         }
 
         Ok(store)
@@ -62,14 +327,61 @@ impl QdrantVectorStore {
         Self::new("http://localhost:6334", collection_name, dimension_count).await
     }
 
+    /// Run a Qdrant request under the store's retry policy
+    async fn with_retry<T, F, Fut>(&self, operation: &str, request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, QdrantError>>,
+    {
+        self.retry.run(operation, transient_delay, request).await
+    }
+
+    /// Check that the server responds, returning its version
+    pub async fn health_check(&self) -> Result<String> {
+        let reply = self
+            .client
+            .health_check()
+            .await
+            .context("Qdrant health check failed")?;
+        Ok(reply.version)
+    }
+
+    /// Whether this store indexes sparse vectors for hybrid search
+    pub fn is_hybrid(&self) -> bool {
+        self.sparse_encoder.is_some()
+    }
+
+    /// Split text into sparse vector terms with `tokenizer`; dense-only
+    /// stores ignore it
+    pub fn with_keyword_tokenizer(mut self, tokenizer: KeywordTokenizer) -> Self {
+        self.sparse_encoder = self.sparse_encoder.map(|encoder| encoder.with_tokenizer(tokenizer));
+        self
+    }
+
     /// Create collection with proper vector configuration
     async fn create_collection(&self) -> Result<()> {
+        let dense_params = VectorParamsBuilder::new(self.dimension_count as u64, Distance::Cosine);
+
+        let builder = if self.is_hybrid() {
+            let mut vectors = VectorsConfigBuilder::default();
+            vectors.add_named_vector_params(DENSE_VECTOR_NAME, dense_params);
+
+            // Qdrant applies IDF server-side, completing the BM25 weighting
+            let mut sparse = SparseVectorsConfigBuilder::default();
+            sparse.add_named_vector_params(
+                SPARSE_VECTOR_NAME,
+                SparseVectorParamsBuilder::default().modifier(Modifier::Idf as i32),
+            );
+
+            CreateCollectionBuilder::new(&self.collection_name)
+                .vectors_config(vectors)
+                .sparse_vectors_config(sparse)
+        } else {
+            CreateCollectionBuilder::new(&self.collection_name).vectors_config(dense_params)
+        };
+
         self.client
-            .create_collection(
-                CreateCollectionBuilder::new(&self.collection_name).vectors_config(
-                    VectorParamsBuilder::new(self.dimension_count as u64, Distance::Cosine),
-                ),
-            )
+            .create_collection(builder)
             .await
             .context("Failed to create collection")?;
 
@@ -80,22 +392,188 @@ impl QdrantVectorStore {
         Ok(())
     }
 
-    /// Check if collection exists
+    /// Vector layout this store writes: the model's dimension, cosine distance,
+    /// and the hybrid named vectors if enabled
+    pub fn expected_schema(&self) -> CollectionSchema {
+        CollectionSchema {
+            dimension: self.dimension_count as u64,
+            distance: Distance::Cosine,
+            hybrid: self.is_hybrid(),
+        }
+    }
+
+    /// Vector layout of the collection as stored in Qdrant
+    ///
+    /// Returns `None` if the layout isn't one this store creates (e.g. named
+    /// vectors without a dense vector).
+    pub async fn collection_schema(&self) -> Result<Option<CollectionSchema>> {
+        let response = self
+            .with_retry("Collection info", || {
+                self.client.collection_info(&self.collection_name)
+            })
+            .await
+            .context("Failed to get collection info")?;
+
+        Ok(response
+            .result
+            .as_ref()
+            .and_then(CollectionSchema::from_collection_info))
+    }
+
+    /// Differences between the stored collection layout and the expected one
+    ///
+    /// An empty list means the collection can be used as is.
+    pub async fn validate_collection(&self) -> Result<Vec<String>> {
+        Ok(match self.collection_schema().await? {
+            Some(actual) => actual.mismatches(&self.expected_schema()),
+            None => vec!["vector configuration is not recognized".to_string()],
+        })
+    }
+
+    /// Check if collection exists, as a collection or an alias of one
     async fn collection_exists(&self) -> Result<bool> {
         let collections = self.client.list_collections().await?;
         Ok(collections
             .collections
             .iter()
-            .any(|c| c.name == self.collection_name))
+            .any(|c| c.name == self.collection_name)
+            || self.alias_target(&self.collection_name).await?.is_some())
+    }
+
+    /// Collection the alias `name` points at, if it is an alias
+    async fn alias_target(&self, name: &str) -> Result<Option<String>> {
+        let aliases = self.client.list_aliases().await.context("Failed to list aliases")?;
+        Ok(aliases
+            .aliases
+            .into_iter()
+            .find(|alias| alias.alias_name == name)
+            .map(|alias| alias.collection_name))
+    }
+
+    /// Name of the collection (or alias) this store reads and writes
+    pub fn collection_name(&self) -> &str {
+        &self.collection_name
+    }
+
+    /// An empty store with this one's layout, in a new collection to build a
+    /// replacement index in; see [`swap_into`](Self::swap_into)
+    pub async fn shadow(&self) -> Result<Self> {
+        self.sibling(shadow_collection_name(&self.collection_name, chrono::Utc::now())).await
+    }
+
+    /// An empty store with this one's layout, in a new collection to serve
+    /// this store's name from once swapped into it
+    async fn behind_alias(&self) -> Result<Self> {
+        self.sibling(aliased_collection_name(&self.collection_name, chrono::Utc::now())).await
+    }
+
+    /// An empty store with this one's layout in the new collection `collection_name`
+    async fn sibling(&self, collection_name: String) -> Result<Self> {
+        let sibling = QdrantVectorStore {
+            client: self.client.clone(),
+            collection_name,
+            dimension_count: self.dimension_count,
+            sparse_encoder: self.sparse_encoder.clone(),
+            retry: self.retry.clone(),
+        };
+        sibling.create_collection().await?;
+        Ok(sibling)
+    }
+
+    /// Make `name` an alias of this store's collection, so stores reading
+    /// `name` see this collection from their next request, and delete the
+    /// collection `name` stood for until then
+    ///
+    /// Qdrant repoints an existing alias in one step, so searches never find
+    /// `name` missing. Stores create their collections behind an alias of
+    /// their name, and move older plain collections behind one when they
+    /// connect, so `name` can't be a collection of its own.
+    pub async fn swap_into(&self, name: &str) -> Result<()> {
+        let previous = self.alias_target(name).await?;
+        if previous.is_none() {
+            let collections = self.client.list_collections().await?;
+            if collections.collections.iter().any(|c| c.name == name) {
+                bail!("'{}' is a collection, not an alias; connect a store to it to move it behind one", name);
+            }
+        }
+
+        self.client
+            .create_alias(CreateAliasBuilder::new(&self.collection_name, name))
+            .await
+            .with_context(|| format!("Failed to point '{}' at '{}'", name, self.collection_name))?;
+        info!("Swapped collection '{}' into '{}'", self.collection_name, name);
+
+        if let Some(previous) = previous.filter(|previous| *previous != self.collection_name) {
+            self.client
+                .delete_collection(&previous)
+                .await
+                .with_context(|| format!("Failed to delete replaced collection '{}'", previous))?;
+            info!("Deleted replaced collection: {}", previous);
+        }
+        Ok(())
+    }
+
+    /// Serve this store's plain collection, created before collections were
+    /// kept behind aliases, from a copy behind an alias of the same name
+    ///
+    /// Searches fail between deleting the collection and creating the alias,
+    /// once; later swaps only repoint the alias.
+    async fn move_behind_alias(&self) -> Result<()> {
+        let copy = self.behind_alias().await?;
+        let points: Vec<PointStruct> = self
+            .scroll_points(true, true)
+            .await?
+            .into_iter()
+            .map(|point| self.copied_point(point))
+            .collect::<Result<_>>()?;
+        for batch in points.chunks(COPY_BATCH_SIZE) {
+            let request = UpsertPointsBuilder::new(&copy.collection_name, batch.to_vec()).wait(true);
+            self.with_retry("Upsert", || self.client.upsert_points(request.clone()))
+                .await
+                .context("Failed to copy points")?;
+        }
+        info!(
+            "Copied {} points of collection '{}' into '{}'",
+            points.len(),
+            self.collection_name,
+            copy.collection_name
+        );
+
+        warn!("Replacing collection '{}' with an alias; searches fail until it's created", self.collection_name);
+        self.delete_collection().await?;
+        copy.swap_into(&self.collection_name).await
     }
 
-    /// Delete the collection (useful for testing)
+    /// A stored point as upserted, with the vectors read back from Qdrant
+    fn copied_point(&self, point: RetrievedPoint) -> Result<PointStruct> {
+        let text = point
+            .payload
+            .get("preprocessed_content")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        let embedding = self
+            .dense_vector(point.vectors.as_ref())
+            .context("Stored point has no dense vector")
+            .and_then(|vector| EmbeddingVector::new(vector).context("Invalid stored embedding"))?;
+        Ok(PointStruct {
+            id: point.id,
+            payload: point.payload,
+            vectors: Some(self.point_vectors(&embedding, &text)),
+        })
+    }
+
+    /// Delete the collection, or the collection behind its alias (useful for testing)
     pub async fn delete_collection(&self) -> Result<()> {
+        let collection = self
+            .alias_target(&self.collection_name)
+            .await?
+            .unwrap_or_else(|| self.collection_name.clone());
         self.client
-            .delete_collection(&self.collection_name)
+            .delete_collection(&collection)
             .await
             .context("Failed to delete collection")?;
-        info!("Deleted collection: {}", self.collection_name);
+        info!("Deleted collection: {}", collection);
         Ok(())
     }
 
@@ -116,6 +594,7 @@ impl QdrantVectorStore {
             "total_chunks": chunk.total_chunks,
             "original_content": chunk.original_content,
             "preprocessed_content": chunk.preprocessed_content,
+            "content_hash": chunk.content_hash(),
             "hierarchy_path": chunk.hierarchy_path,
             "created_at": chrono::Utc::now().to_rfc3339(),
         })
@@ -124,14 +603,12 @@ impl QdrantVectorStore {
 
         let point = PointStruct::new(
             chunk.chunk_id.clone(),
-            embedding.dimensions().to_vec(),
+            self.point_vectors(embedding, &chunk.preprocessed_content),
             payload,
         );
 
-        self.client
-            .upsert_points(
-                UpsertPointsBuilder::new(&self.collection_name, vec![point]).wait(true),
-            )
+        let request = UpsertPointsBuilder::new(&self.collection_name, vec![point]).wait(true);
+        self.with_retry("Upsert", || self.client.upsert_points(request.clone()))
             .await
             .context("Failed to insert chunk")?;
 
@@ -161,6 +638,7 @@ impl QdrantVectorStore {
                     "total_chunks": chunk.total_chunks,
                     "original_content": chunk.original_content,
                     "preprocessed_content": chunk.preprocessed_content,
+                    "content_hash": chunk.content_hash(),
                     "hierarchy_path": chunk.hierarchy_path,
                     "created_at": chrono::Utc::now().to_rfc3339(),
                 })
@@ -169,16 +647,14 @@ impl QdrantVectorStore {
 
                 Ok(PointStruct::new(
                     chunk.chunk_id.clone(),
-                    embedding.dimensions().to_vec(),
+                    self.point_vectors(&embedding, &chunk.preprocessed_content),
                     payload,
                 ))
             })
             .collect();
 
-        self.client
-            .upsert_points(
-                UpsertPointsBuilder::new(&self.collection_name, points?).wait(true),
-            )
+        let request = UpsertPointsBuilder::new(&self.collection_name, points?).wait(true);
+        self.with_retry("Upsert", || self.client.upsert_points(request.clone()))
             .await
             .context("Failed to insert batch")?;
 
@@ -191,38 +667,269 @@ impl QdrantVectorStore {
         &self,
         query_embedding: &EmbeddingVector,
         limit: u64,
+    ) -> Result<Vec<SearchResult>> {
+        self.run_search(query_embedding, limit, false).await
+    }
+
+    /// Search for similar chunks, also returning the stored vector of each hit
+    ///
+    /// Used by re-ranking stages (e.g. MMR) that need to compare results with each other.
+    pub async fn search_with_vectors(
+        &self,
+        query_embedding: &EmbeddingVector,
+        limit: u64,
+    ) -> Result<Vec<SearchResult>> {
+        self.run_search(query_embedding, limit, true).await
+    }
+
+    async fn run_search(
+        &self,
+        query_embedding: &EmbeddingVector,
+        limit: u64,
+        with_vectors: bool,
     ) -> Result<Vec<SearchResult>> {
         debug!("Searching with limit: {}", limit);
 
+        let mut request = SearchPointsBuilder::new(
+            &self.collection_name,
+            query_embedding.dimensions().to_vec(),
+            limit,
+        )
+        .with_payload(true)
+        .with_vectors(with_vectors);
+        if self.is_hybrid() {
+            request = request.vector_name(DENSE_VECTOR_NAME);
+        }
+
         let search_result = self
-            .client
-            .search_points(
-                SearchPointsBuilder::new(
-                    &self.collection_name,
-                    query_embedding.dimensions().to_vec(),
-                    limit,
-                )
-                .with_payload(true),
-            )
+            .with_retry("Search", || self.client.search_points(request.clone()))
             .await
             .context("Search failed")?;
 
         let results: Vec<SearchResult> = search_result
+            .result
+            .into_iter()
+            .map(|point| self.to_search_result(point))
+            .collect();
+
+        debug!("Found {} results", results.len());
+        Ok(results)
+    }
+
+    /// Hybrid search: dense and sparse (BM25) candidates fused server-side
+    /// with reciprocal rank fusion
+    ///
+    /// Scores are RRF scores, not cosine similarities, rescaled to 0.0-1.0 as
+    /// a share of the best possible fused score: 1.0 is the first result of
+    /// every branch. Fails on dense-only stores.
+    pub async fn hybrid_search(
+        &self,
+        query_embedding: &EmbeddingVector,
+        query_text: &str,
+        limit: u64,
+        with_vectors: bool,
+    ) -> Result<Vec<SearchResult>> {
+        debug!("Hybrid searching with limit: {}", limit);
+
+        let encoder = self
+            .sparse_encoder
+            .as_ref()
+            .context("Hybrid search requires a hybrid collection")?;
+        let sparse_query = encoder.encode_query(query_text);
+        let prefetch_limit = limit * HYBRID_PREFETCH_MULTIPLIER;
+
+        let mut request = QueryPointsBuilder::new(&self.collection_name)
+            .add_prefetch(
+                PrefetchQueryBuilder::default()
+                    .query(Query::new_nearest(query_embedding.dimensions().to_vec()))
+                    .using(DENSE_VECTOR_NAME)
+                    .limit(prefetch_limit),
+            );
+        // A query with no usable terms would only add an empty branch
+        let branches = if sparse_query.indices.is_empty() { 1.0 } else { 2.0 };
+        if !sparse_query.indices.is_empty() {
+            let terms: Vec<(u32, f32)> = sparse_query
+                .indices
+                .into_iter()
+                .zip(sparse_query.values)
+                .collect();
+            request = request.add_prefetch(
+                PrefetchQueryBuilder::default()
+                    .query(Query::new_nearest(terms.as_slice()))
+                    .using(SPARSE_VECTOR_NAME)
+                    .limit(prefetch_limit),
+            );
+        }
+
+        let request = request
+            .query(Query::new_fusion(Fusion::Rrf))
+            .limit(limit)
+            .with_payload(true)
+            .with_vectors(with_vectors);
+        let response = self
+            .with_retry("Hybrid search", || self.client.query(request.clone()))
+            .await
+            .context("Hybrid search failed")?;
+
+        let results: Vec<SearchResult> = response
+            .result
+            .into_iter()
+            .map(|point| {
+                let mut result = self.to_search_result(point);
+                result.score /= RRF_BEST_RANK_SCORE * branches;
+                result
+            })
+            .collect();
+
+        debug!("Found {} hybrid results", results.len());
+        Ok(results)
+    }
+
+    /// Vectors stored for a point: the dense embedding, plus the sparse
+    /// BM25 vector of `text` for hybrid collections
+    fn point_vectors(&self, embedding: &EmbeddingVector, text: &str) -> Vectors {
+        match &self.sparse_encoder {
+            Some(encoder) => {
+                let sparse = encoder.encode_document(text);
+                NamedVectors::default()
+                    .add_vector(
+                        DENSE_VECTOR_NAME,
+                        qdrant_client::qdrant::Vector::new_dense(embedding.dimensions().to_vec()),
+                    )
+                    .add_vector(
+                        SPARSE_VECTOR_NAME,
+                        qdrant_client::qdrant::Vector::new_sparse(sparse.indices, sparse.values),
+                    )
+                    .into()
+            }
+            None => embedding.dimensions().to_vec().into(),
+        }
+    }
+
+    /// The dense embedding among a point's stored vectors
+    fn dense_vector(&self, vectors: Option<&VectorsOutput>) -> Option<Vec<f32>> {
+        vectors
+            .and_then(|v| {
+                if self.is_hybrid() {
+                    v.get_vector_by_name(DENSE_VECTOR_NAME)
+                } else {
+                    v.get_vector()
+                }
+            })
+            .and_then(|v| match v {
+                Vector::Dense(dense) => Some(dense.data),
+                _ => None,
+            })
+    }
+
+    fn to_search_result(&self, point: ScoredPoint) -> SearchResult {
+        let vector = self.dense_vector(point.vectors.as_ref());
+        let payload = point.payload;
+        SearchResult {
+            chunk_id: payload
+                .get("chunk_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            block_id: payload
+                .get("block_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            page_id: payload
+                .get("page_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            page_title: payload
+                .get("page_title")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            original_content: payload
+                .get("original_content")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            preprocessed_content: payload
+                .get("preprocessed_content")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            hierarchy_path: payload
+                .get("hierarchy_path")
+                .and_then(|v| v.as_list())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            score: point.score,
+            vector,
+        }
+    }
+
+    /// Insert or replace the page-level embedding for a page
+    ///
+    /// Page-level embeddings live in their own collection (see
+    /// `EmbeddingService`), keyed by page ID.
+    pub async fn insert_page(
+        &self,
+        page: &PageEmbeddingMetadata,
+        embedding: &EmbeddingVector,
+    ) -> Result<()> {
+        debug!("Inserting page embedding: {}", page.page_id);
+
+        let payload: Payload = json!({
+            "page_id": page.page_id,
+            "page_title": page.page_title,
+            "preprocessed_content": page.preprocessed_content,
+            "block_count": page.block_count,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+        })
+        .try_into()
+        .context("Failed to serialize payload")?;
+
+        let point = PointStruct::new(
+            page.page_id.clone(),
+            self.point_vectors(embedding, &page.preprocessed_content),
+            payload,
+        );
+
+        let request = UpsertPointsBuilder::new(&self.collection_name, vec![point]).wait(true);
+        self.with_retry("Upsert", || self.client.upsert_points(request.clone()))
+            .await
+            .context("Failed to insert page embedding")?;
+
+        Ok(())
+    }
+
+    /// Search page-level embeddings
+    pub async fn search_pages(
+        &self,
+        query_embedding: &EmbeddingVector,
+        limit: u64,
+    ) -> Result<Vec<PageSearchResult>> {
+        debug!("Searching pages with limit: {}", limit);
+
+        let request = SearchPointsBuilder::new(
+            &self.collection_name,
+            query_embedding.dimensions().to_vec(),
+            limit,
+        )
+        .with_payload(true);
+        let search_result = self
+            .with_retry("Page search", || self.client.search_points(request.clone()))
+            .await
+            .context("Page search failed")?;
+
+        let results: Vec<PageSearchResult> = search_result
             .result
             .into_iter()
             .map(|point| {
                 let payload = point.payload;
-                SearchResult {
-                    chunk_id: payload
-                        .get("chunk_id")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
-                    block_id: payload
-                        .get("block_id")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
+                PageSearchResult {
                     page_id: payload
                         .get("page_id")
                         .and_then(|v| v.as_str())
@@ -233,31 +940,17 @@ impl QdrantVectorStore {
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string())
                         .unwrap_or_default(),
-                    original_content: payload
-                        .get("original_content")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
                     preprocessed_content: payload
                         .get("preprocessed_content")
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string())
                         .unwrap_or_default(),
-                    hierarchy_path: payload
-                        .get("hierarchy_path")
-                        .and_then(|v| v.as_list())
-                        .map(|arr| {
-                            arr.iter()
-                                .filter_map(|v| v.as_str().map(String::from))
-                                .collect()
-                        })
-                        .unwrap_or_default(),
                     score: point.score,
                 }
             })
             .collect();
 
-        debug!("Found {} results", results.len());
+        debug!("Found {} page results", results.len());
         Ok(results)
     }
 
@@ -265,54 +958,174 @@ impl QdrantVectorStore {
     pub async fn delete_chunk(&self, chunk_id: &ChunkId) -> Result<()> {
         debug!("Deleting chunk: {}", chunk_id);
 
-        use qdrant_client::qdrant::PointId;
-
-        self.client
-            .delete_points(
-                DeletePointsBuilder::new(&self.collection_name)
-                    .points(vec![PointId::from(chunk_id.as_str().to_string())])
-                    .wait(true),
-            )
+        let request = DeletePointsBuilder::new(&self.collection_name)
+            .points(vec![PointId::from(chunk_id.as_str().to_string())])
+            .wait(true);
+        self.with_retry("Delete", || self.client.delete_points(request.clone()))
             .await
             .context("Failed to delete chunk")?;
 
         Ok(())
     }
 
-    /// Delete all chunks for a specific block
-    pub async fn delete_block_chunks(&self, block_id: &BlockId) -> Result<()> {
-        debug!("Deleting all chunks for block: {}", block_id);
+    /// Delete the page-level embedding for a page
+    pub async fn delete_page(&self, page_id: &PageId) -> Result<()> {
+        debug!("Deleting page embedding: {}", page_id);
 
-        // Note: Qdrant doesn't support filter-based deletion in the same way
-        // For now, we'll need to search for chunks and delete by ID
-        // In production, consider using Qdrant's scroll API for large deletions
-        warn!(
-            "Block deletion not yet implemented. Block ID: {}",
-            block_id
-        );
+        let request = DeletePointsBuilder::new(&self.collection_name)
+            .points(vec![PointId::from(page_id.as_str().to_string())])
+            .wait(true);
+        self.with_retry("Delete", || self.client.delete_points(request.clone()))
+            .await
+            .context("Failed to delete page embedding")?;
 
         Ok(())
     }
 
+    /// Delete points by their IDs (chunk IDs, or page IDs in a page store)
+    pub async fn delete_points(&self, ids: Vec<String>) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Deleting {} points", ids.len());
+
+        let request = DeletePointsBuilder::new(&self.collection_name)
+            .points(ids.into_iter().map(PointId::from).collect::<Vec<_>>())
+            .wait(true);
+        self.with_retry("Delete", || self.client.delete_points(request.clone()))
+            .await
+            .context("Failed to delete points")?;
+
+        Ok(())
+    }
+
+    /// List every stored chunk ID with its content hash
+    ///
+    /// The hash is `None` for chunks stored before hashes were recorded.
+    pub async fn list_chunk_hashes(&self) -> Result<Vec<(String, Option<String>)>> {
+        let payloads = self.scroll_payloads(&["chunk_id", "content_hash"]).await?;
+
+        Ok(payloads
+            .into_iter()
+            .filter_map(|payload| {
+                let chunk_id = payload.get("chunk_id")?.as_str()?.to_string();
+                let hash = payload
+                    .get("content_hash")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                Some((chunk_id, hash))
+            })
+            .collect())
+    }
+
+    /// List the page IDs of every stored page-level embedding
+    pub async fn list_page_ids(&self) -> Result<Vec<String>> {
+        let payloads = self.scroll_payloads(&["page_id"]).await?;
+
+        Ok(payloads
+            .into_iter()
+            .filter_map(|payload| Some(payload.get("page_id")?.as_str()?.to_string()))
+            .collect())
+    }
+
+    /// List every stored page-level embedding with its page ID
+    pub async fn list_page_vectors(&self) -> Result<Vec<(String, EmbeddingVector)>> {
+        let points = self.scroll_points(payload_fields(&["page_id"]), true).await?;
+
+        points
+            .into_iter()
+            .filter_map(|point| {
+                let page_id = point.payload.get("page_id")?.as_str()?.to_string();
+                let vector = self.dense_vector(point.vectors.as_ref())?;
+                Some(EmbeddingVector::new(vector).map(|vector| (page_id, vector)))
+            })
+            .collect::<std::result::Result<_, _>>()
+            .context("Invalid page embedding")
+    }
+
+    /// Scroll through the whole collection, returning only the given payload fields
+    async fn scroll_payloads(&self, fields: &[&str]) -> Result<Vec<HashMap<String, Value>>> {
+        let points = self.scroll_points(payload_fields(fields), false).await?;
+        Ok(points.into_iter().map(|point| point.payload).collect())
+    }
+
+    /// Scroll through the whole collection, returning the payload `selector`
+    /// picks of each point, and its vectors if `with_vectors`
+    async fn scroll_points(
+        &self,
+        selector: impl Into<PayloadSelector>,
+        with_vectors: bool,
+    ) -> Result<Vec<RetrievedPoint>> {
+        const SCROLL_PAGE_SIZE: u32 = 256;
+
+        let selector = selector.into();
+        let mut points = Vec::new();
+        let mut offset: Option<PointId> = None;
+
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.collection_name)
+                .limit(SCROLL_PAGE_SIZE)
+                .with_payload(selector.clone())
+                .with_vectors(with_vectors);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+
+            let response = self
+                .with_retry("Scroll", || self.client.scroll(request.clone()))
+                .await
+                .context("Failed to scroll collection")?;
+
+            points.extend(response.result);
+
+            match response.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        debug!("Scrolled {} points", points.len());
+        Ok(points)
+    }
+
+    /// Delete all chunks for a specific block
+    pub async fn delete_block_chunks(&self, block_id: &BlockId) -> Result<()> {
+        debug!("Deleting all chunks for block: {}", block_id);
+        self.delete_matching("block_id", block_id.as_str())
+            .await
+            .context("Failed to delete block chunks")
+    }
+
     /// Delete all chunks for a specific page
     pub async fn delete_page_chunks(&self, page_id: &PageId) -> Result<()> {
         debug!("Deleting all chunks for page: {}", page_id);
+        self.delete_matching("page_id", page_id.as_str())
+            .await
+            .context("Failed to delete page chunks")
+    }
 
-        warn!("Page deletion not yet implemented. Page ID: {}", page_id);
-
+    /// Delete every point whose payload `field` equals `value`
+    async fn delete_matching(&self, field: &str, value: &str) -> Result<()> {
+        let request = DeletePointsBuilder::new(&self.collection_name)
+            .points(Filter::must([Condition::matches(field, value.to_string())]))
+            .wait(true);
+        self.with_retry("Delete", || self.client.delete_points(request.clone()))
+            .await?;
         Ok(())
     }
 
     /// Get collection info
     pub async fn get_collection_info(&self) -> Result<CollectionInfo> {
         let collection = self
-            .client
-            .collection_info(&self.collection_name)
+            .with_retry("Collection info", || {
+                self.client.collection_info(&self.collection_name)
+            })
             .await
             .context("Failed to get collection info")?;
 
         let (vectors_count, points_count) = if let Some(result) = collection.result {
-            (result.vectors_count, result.points_count)
+            (result.indexed_vectors_count, result.points_count)
         } else {
             (None, None)
         };
@@ -339,6 +1152,13 @@ pub struct ChunkMetadata {
     pub hierarchy_path: Vec<String>,
 }
 
+impl ChunkMetadata {
+    /// SHA-256 of the embedded (preprocessed) text, used to detect stale vectors
+    pub fn content_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.preprocessed_content.as_bytes()))
+    }
+}
+
 /// Search result from vector database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -350,6 +1170,27 @@ pub struct SearchResult {
     pub preprocessed_content: String,
     pub hierarchy_path: Vec<String>,
     pub score: f32,
+    /// Stored embedding of the hit, only populated by `search_with_vectors`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
+}
+
+/// Metadata for a page-level embedding (title + leading blocks)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageEmbeddingMetadata {
+    pub page_id: String,
+    pub page_title: String,
+    pub preprocessed_content: String,
+    pub block_count: usize,
+}
+
+/// Page-level search result from vector database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSearchResult {
+    pub page_id: String,
+    pub page_title: String,
+    pub preprocessed_content: String,
+    pub score: f32,
 }
 
 /// Collection information
@@ -360,10 +1201,143 @@ pub struct CollectionInfo {
     pub points_count: Option<u64>,
 }
 
+/// Vector layout of a collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionSchema {
+    /// Size of the dense vector
+    pub dimension: u64,
+    /// Distance metric of the dense vector
+    pub distance: Distance,
+    /// Named dense + sparse vectors (hybrid) rather than a single dense vector
+    pub hybrid: bool,
+}
+
+impl CollectionSchema {
+    fn from_collection_info(info: &qdrant_client::qdrant::CollectionInfo) -> Option<Self> {
+        let params = info.config.as_ref()?.params.as_ref()?;
+        let (dense, named) = match params.vectors_config.as_ref()?.config.as_ref()? {
+            VectorsConfigKind::Params(dense) => (dense, false),
+            VectorsConfigKind::ParamsMap(named) => (named.map.get(DENSE_VECTOR_NAME)?, true),
+        };
+        let has_sparse = params
+            .sparse_vectors_config
+            .as_ref()
+            .is_some_and(|sparse| sparse.map.contains_key(SPARSE_VECTOR_NAME));
+
+        Some(CollectionSchema {
+            dimension: dense.size,
+            distance: Distance::try_from(dense.distance).unwrap_or(Distance::UnknownDistance),
+            hybrid: named && has_sparse,
+        })
+    }
+
+    /// Human-readable differences from `expected`; empty if compatible
+    pub fn mismatches(&self, expected: &CollectionSchema) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if self.dimension != expected.dimension {
+            mismatches.push(format!(
+                "vector dimension is {} but the model produces {}",
+                self.dimension, expected.dimension
+            ));
+        }
+        if self.distance != expected.distance {
+            mismatches.push(format!(
+                "distance is {:?} but {:?} is expected",
+                self.distance, expected.distance
+            ));
+        }
+        match (self.hybrid, expected.hybrid) {
+            (false, true) => mismatches
+                .push("collection is dense-only but hybrid search is enabled".to_string()),
+            (true, false) => mismatches
+                .push("collection is hybrid but hybrid search is disabled".to_string()),
+            _ => {}
+        }
+        mismatches
+    }
+}
+
+/// Whether a Qdrant error is worth retrying, and the minimum wait before doing so
+fn transient_delay(error: &QdrantError) -> Option<Duration> {
+    match error {
+        QdrantError::ResourceExhaustedError {
+            retry_after_seconds,
+            ..
+        } => Some(Duration::from_secs(*retry_after_seconds)),
+        QdrantError::ResponseError { status } => matches!(
+            status.code(),
+            Code::Unavailable | Code::DeadlineExceeded | Code::Aborted | Code::ResourceExhausted
+        )
+        .then_some(Duration::ZERO),
+        QdrantError::Io(_) => Some(Duration::ZERO),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_connection_config_tls_upgrade() {
+        let local = QdrantConnectionConfig::default();
+        assert_eq!(local.effective_url(), "http://localhost:6334");
+        assert!(!local.uses_tls());
+
+        let cloud = QdrantConnectionConfig::cloud("http://xyz.cloud.qdrant.io:6334", "secret");
+        assert_eq!(cloud.effective_url(), "https://xyz.cloud.qdrant.io:6334");
+        assert!(cloud.uses_tls());
+
+        assert!(QdrantConnectionConfig::new("https://qdrant.example.com:6334").uses_tls());
+    }
+
+    #[test]
+    fn test_collection_schema_mismatches() {
+        let expected = CollectionSchema {
+            dimension: 384,
+            distance: Distance::Cosine,
+            hybrid: true,
+        };
+        assert!(expected.mismatches(&expected).is_empty());
+
+        let stored = CollectionSchema {
+            dimension: 768,
+            distance: Distance::Dot,
+            hybrid: false,
+        };
+        let mismatches = stored.mismatches(&expected);
+        assert_eq!(mismatches.len(), 3);
+        assert!(mismatches[0].contains("768"));
+        assert!(mismatches[2].contains("dense-only"));
+    }
+
+    #[test]
+    fn test_transient_delay_classification() {
+        let unavailable = QdrantError::ResponseError {
+            status: tonic::Status::unavailable("connection reset"),
+        };
+        assert_eq!(transient_delay(&unavailable), Some(Duration::ZERO));
+
+        let throttled = QdrantError::ResourceExhaustedError {
+            status: tonic::Status::resource_exhausted("rate limited"),
+            retry_after_seconds: 3,
+        };
+        assert_eq!(transient_delay(&throttled), Some(Duration::from_secs(3)));
+
+        let bad_request = QdrantError::ResponseError {
+            status: tonic::Status::invalid_argument("wrong vector size"),
+        };
+        assert_eq!(transient_delay(&bad_request), None);
+    }
+
+    #[test]
+    fn test_connection_config_debug_redacts_api_key() {
+        let config = QdrantConnectionConfig::default().with_api_key("secret-key");
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("secret-key"));
+        assert!(debug.contains("<redacted>"));
+    }
+
     // Note: These tests require a running Qdrant instance
     // Run with: docker run -p 6333:6333 -p 6334:6334 qdrant/qdrant
 
@@ -383,6 +1357,48 @@ mod tests {
         assert_eq!(info.points_count, Some(0));
     }
 
+    #[test]
+    fn test_shadow_collection_names_are_apart_per_rebuild() {
+        let at = chrono::DateTime::parse_from_rfc3339("2024-03-01T08:30:05Z").unwrap().with_timezone(&chrono::Utc);
+        assert_eq!(shadow_collection_name("logseq_blocks", at), "logseq_blocks_shadow_20240301083005");
+        assert_eq!(aliased_collection_name("logseq_blocks", at), "logseq_blocks_20240301083005");
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires running Qdrant instance
+    async fn test_swapped_shadow_replaces_the_collection() {
+        let store = create_test_store().await.unwrap();
+        let chunk = |id: &str| ChunkMetadata {
+            chunk_id: id.to_string(),
+            block_id: id.to_string(),
+            page_id: "page-1".to_string(),
+            page_title: "Test Page".to_string(),
+            chunk_index: 0,
+            total_chunks: 1,
+            original_content: "Rust".to_string(),
+            preprocessed_content: "rust".to_string(),
+            hierarchy_path: vec![],
+        };
+        let embedding = EmbeddingVector::new(vec![0.1; 384]).unwrap();
+        store.insert_chunk(&chunk("old"), &embedding).await.unwrap();
+
+        // The store's name is an alias from the start, repointed by each swap
+        assert!(store.alias_target(store.collection_name()).await.unwrap().is_some());
+        for id in ["first", "second"] {
+            let shadow = store.shadow().await.unwrap();
+            shadow.insert_chunk(&chunk(id), &embedding).await.unwrap();
+            shadow.swap_into(store.collection_name()).await.unwrap();
+
+            let results = store.search(&embedding, 5).await.unwrap();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].chunk_id, id);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+
+        let target = store.alias_target(store.collection_name()).await.unwrap().unwrap();
+        let _ = store.client.delete_collection(target).await;
+    }
+
     #[tokio::test]
     #[ignore] // Requires running Qdrant instance
     async fn test_insert_and_search() {
@@ -452,4 +1468,38 @@ mod tests {
         // Cleanup
         let _ = store.delete_collection().await;
     }
+
+    #[tokio::test]
+    #[ignore] // Requires running Qdrant instance
+    async fn test_hybrid_insert_and_search() {
+        let collection_name = format!("test_hybrid_{}", uuid::Uuid::new_v4());
+        let store = QdrantVectorStore::new_hybrid("http://localhost:6334", collection_name, 384)
+            .await
+            .unwrap();
+
+        let chunk = ChunkMetadata {
+            chunk_id: "hybrid-chunk-1".to_string(),
+            block_id: "hybrid-block-1".to_string(),
+            page_id: "hybrid-page-1".to_string(),
+            page_title: "Test Page".to_string(),
+            chunk_index: 0,
+            total_chunks: 1,
+            original_content: "Borrow checker notes".to_string(),
+            preprocessed_content: "borrow checker notes".to_string(),
+            hierarchy_path: vec![],
+        };
+        let embedding = EmbeddingVector::new(vec![0.1; 384]).unwrap();
+        store.insert_chunk(&chunk, &embedding).await.unwrap();
+
+        let results = store
+            .hybrid_search(&embedding, "borrow checker", 5, false)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, "hybrid-chunk-1");
+
+        // Cleanup
+        let _ = store.delete_collection().await;
+    }
 }
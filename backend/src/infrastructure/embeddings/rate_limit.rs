@@ -0,0 +1,184 @@
+/// Request rate and concurrency limits for remote embedding providers
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// How many requests a provider accepts: a token bucket refilled at
+/// `requests_per_second` holding up to `burst` requests, and a cap on
+/// requests awaiting a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    /// Sustained request rate; 0 or less leaves the rate unlimited
+    pub requests_per_second: f64,
+    /// Requests that may be sent at once after an idle spell (at least 1)
+    pub burst: u32,
+    /// Requests in flight at the same time (at least 1)
+    pub max_in_flight: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            requests_per_second: 0.0,
+            burst: 1,
+            max_in_flight: 4,
+        }
+    }
+}
+
+impl RateLimit {
+    /// Limit that never makes a request wait
+    pub fn unlimited() -> Self {
+        RateLimit {
+            max_in_flight: Semaphore::MAX_PERMITS,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_rate(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.requests_per_second = requests_per_second;
+        self.burst = burst;
+        self
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+}
+
+/// Queue holding requests back until a [`RateLimit`] lets them through
+///
+/// Requests over the limit wait rather than fail: each takes an in-flight
+/// slot, then the next token, in the order they arrived. Share one limiter
+/// (e.g. in an `Arc`) among everything calling the same provider.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    in_flight: Semaphore,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens left as of `updated`; negative when requests have reserved
+    /// tokens not yet refilled
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        let in_flight = Semaphore::new(limit.max_in_flight.clamp(1, Semaphore::MAX_PERMITS));
+        let bucket = Mutex::new(Bucket {
+            tokens: f64::from(limit.burst.max(1)),
+            updated: Instant::now(),
+        });
+        Self { limit, in_flight, bucket }
+    }
+
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// Run `request` once the limit allows it, keeping its in-flight slot
+    /// until it completes
+    pub async fn run<T, Fut>(&self, request: impl FnOnce() -> Fut) -> T
+    where
+        Fut: Future<Output = T>,
+    {
+        let _slot = self.in_flight.acquire().await.expect("the semaphore is never closed");
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tracing::debug!("Embedding request rate limited, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+        request().await
+    }
+
+    /// Take the next token as of `now`, returning how long until it's refilled
+    fn reserve(&self, now: Instant) -> Duration {
+        let rate = self.limit.requests_per_second;
+        if rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let burst = f64::from(self.limit.burst.max(1));
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst) - 1.0;
+        bucket.updated = bucket.updated.max(now);
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_bucket_allows_a_burst_then_spaces_requests() {
+        let limiter = RateLimiter::new(RateLimit::default().with_rate(10.0, 2));
+        let start = Instant::now();
+
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::from_millis(100));
+        // Queued behind the previous request's reservation
+        assert_eq!(limiter.reserve(start), Duration::from_millis(200));
+
+        // Refilled, but never beyond the burst
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert!(limiter.reserve(later) > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_the_limit_wait_instead_of_failing() {
+        let limiter = Arc::new(RateLimiter::new(RateLimit::default().with_rate(100.0, 1).with_max_in_flight(2)));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let started = Instant::now();
+
+        let requests: Vec<_> = (0..6)
+            .map(|i| {
+                let (limiter, in_flight, most_in_flight) = (limiter.clone(), in_flight.clone(), most_in_flight.clone());
+                tokio::spawn(async move {
+                    limiter
+                        .run(|| async {
+                            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            most_in_flight.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            i
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for (i, request) in requests.into_iter().enumerate() {
+            assert_eq!(request.await.unwrap(), i);
+        }
+
+        assert!(most_in_flight.load(Ordering::SeqCst) <= 2);
+        // Five requests beyond the first token, 10ms apart
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_never_waits() {
+        let limiter = RateLimiter::new(RateLimit::unlimited());
+        let started = Instant::now();
+        for i in 0..100 {
+            assert_eq!(limiter.run(|| async move { i }).await, i);
+        }
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,191 @@
+/// Retry policy with jittered exponential backoff for transient failures
+use anyhow::Result;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tracing::warn;
+
+/// How often, and how patiently, a failing operation is retried
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one (1 disables retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on any single delay
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each retry
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Delay before the given retry (0 = first retry)
+    ///
+    /// The exponential delay is jittered between half and all of its value, so
+    /// many clients failing together don't retry in lockstep.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let growth = self.multiplier.max(1.0).powi(retry.min(64) as i32);
+        let ceiling = (self.initial_backoff.as_secs_f64() * growth).min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(ceiling * (0.5 + 0.5 * random_fraction()))
+    }
+
+    /// Run `attempt` until it succeeds, fails permanently, or attempts run out
+    ///
+    /// `transient` classifies errors: `None` means the error is permanent and is
+    /// returned immediately; `Some(min_delay)` means it may be retried after at
+    /// least `min_delay` (e.g. a server-provided retry-after).
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        operation: &str,
+        transient: impl Fn(&E) -> Option<Duration>,
+        mut attempt: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+            let error = match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            let min_delay = match transient(&error) {
+                None => {
+                    return Err(anyhow::Error::new(error).context(format!("{} failed", operation)))
+                }
+                Some(_) if attempts >= max_attempts => {
+                    return Err(anyhow::Error::new(error).context(format!(
+                        "{} failed after {} attempts",
+                        operation, attempts
+                    )))
+                }
+                Some(min_delay) => min_delay,
+            };
+
+            let delay = self.backoff(attempts - 1).max(min_delay);
+            warn!(
+                "{} failed (attempt {}/{}), retrying in {:?}: {}",
+                operation, attempts, max_attempts, delay, error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Uniform random value in [0, 1), seeded per call from the std hasher's random keys
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct TestError(&'static str);
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_max_attempts(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(2))
+    }
+
+    fn classify(error: &TestError) -> Option<Duration> {
+        (error.0 == "transient").then_some(Duration::ZERO)
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350));
+
+        for _ in 0..20 {
+            let first = policy.backoff(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+            let third = policy.backoff(2);
+            assert!(third >= Duration::from_millis(175) && third <= Duration::from_millis(350));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let calls = AtomicU32::new(0);
+        let result = fast_policy()
+            .run("Upsert", classify, || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(TestError("transient")),
+                    _ => Ok(42),
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_fail_immediately() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = fast_policy()
+            .run("Search", classify, || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(TestError("bad request"))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exhaustion_reports_attempts() {
+        let result: Result<()> = fast_policy()
+            .run("Delete", classify, || async { Err(TestError("transient")) })
+            .await;
+
+        let message = format!("{:#}", result.unwrap_err());
+        assert!(message.contains("Delete failed after 3 attempts"));
+        assert!(message.contains("transient"));
+    }
+}
@@ -0,0 +1,145 @@
+/// BM25-style sparse term vectors for hybrid search in Qdrant
+use crate::infrastructure::text::{is_cjk, KeywordTokenizer};
+use std::collections::HashMap;
+
+/// BM25 term-frequency saturation parameter
+const BM25_K1: f32 = 1.2;
+/// BM25 document length normalization parameter
+const BM25_B: f32 = 0.75;
+
+/// A sparse vector: parallel lists of term indices and weights
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseVector {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+/// Encodes text into BM25 term-frequency vectors
+///
+/// Only the term-frequency half of BM25 is computed here; the IDF half is
+/// applied server-side by Qdrant (collections are created with the IDF modifier),
+/// so document statistics never have to be tracked on the client.
+/// Terms are mapped to indices with a stable 32-bit FNV-1a hash, after the
+/// same [`KeywordTokenizer`] as keyword search splits the text.
+#[derive(Debug, Clone)]
+pub struct SparseEncoder {
+    avg_doc_len: f32,
+    tokenizer: KeywordTokenizer,
+}
+
+impl SparseEncoder {
+    /// Create an encoder assuming the given average document length in words
+    pub fn new(avg_doc_len: f32) -> Self {
+        SparseEncoder {
+            avg_doc_len: avg_doc_len.max(1.0),
+            tokenizer: KeywordTokenizer::new(),
+        }
+    }
+
+    /// Split text with another tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: KeywordTokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Encode a document (chunk) for indexing
+    pub fn encode_document(&self, text: &str) -> SparseVector {
+        let terms = self.tokenize(text);
+        let doc_len = terms.len() as f32;
+
+        let mut frequencies: HashMap<u32, f32> = HashMap::new();
+        for term in &terms {
+            *frequencies.entry(term_index(term)).or_insert(0.0) += 1.0;
+        }
+
+        let length_norm = 1.0 - BM25_B + BM25_B * doc_len / self.avg_doc_len;
+        Self::into_sparse(frequencies.into_iter().map(|(index, tf)| {
+            (index, tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * length_norm))
+        }))
+    }
+
+    /// Encode a query: every distinct term gets weight 1.0
+    pub fn encode_query(&self, text: &str) -> SparseVector {
+        let mut indices: Vec<u32> = self.tokenize(text).iter().map(|t| term_index(t)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        Self::into_sparse(indices.into_iter().map(|index| (index, 1.0)))
+    }
+
+    /// Terms of the text, ignoring single letters and digits (but not single
+    /// CJK characters, which are words)
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        self.tokenizer
+            .tokenize(text)
+            .into_iter()
+            .filter(|t| t.chars().count() > 1 || t.chars().all(is_cjk))
+            .collect()
+    }
+
+    fn into_sparse(entries: impl Iterator<Item = (u32, f32)>) -> SparseVector {
+        let mut entries: Vec<(u32, f32)> = entries.collect();
+        entries.sort_unstable_by_key(|(index, _)| *index);
+        let (indices, values) = entries.into_iter().unzip();
+        SparseVector { indices, values }
+    }
+}
+
+impl Default for SparseEncoder {
+    fn default() -> Self {
+        // Chunks are capped at ~150 words; most Logseq blocks are far shorter
+        Self::new(40.0)
+    }
+}
+
+/// Stable 32-bit FNV-1a hash of a term
+fn term_index(term: &str) -> u32 {
+    term.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_encoding_is_deterministic_and_deduplicated() {
+        let encoder = SparseEncoder::default();
+        let a = encoder.encode_query("Rust rust ownership");
+        let b = encoder.encode_query("ownership RUST");
+
+        assert_eq!(a, b);
+        assert_eq!(a.indices.len(), 2);
+        assert!(a.values.iter().all(|&v| v == 1.0));
+    }
+
+    #[test]
+    fn test_document_term_frequency_saturates() {
+        let encoder = SparseEncoder::new(4.0);
+        let doc = encoder.encode_document("rust rust rust rust borrow");
+
+        let rust = doc.indices.iter().position(|&i| i == term_index("rust")).unwrap();
+        let borrow = doc.indices.iter().position(|&i| i == term_index("borrow")).unwrap();
+
+        assert!(doc.values[rust] > doc.values[borrow]);
+        // Four occurrences must weigh less than four times a single one
+        assert!(doc.values[rust] < 4.0 * doc.values[borrow]);
+        assert!(doc.values[rust] < BM25_K1 + 1.0);
+    }
+
+    #[test]
+    fn test_cjk_text_is_indexed_by_bigram() {
+        let encoder = SparseEncoder::default();
+        let doc = encoder.encode_document("東京大学");
+        assert_eq!(doc.indices.len(), 3);
+        let query = encoder.encode_query("大学");
+        assert!(doc.indices.contains(&query.indices[0]));
+    }
+
+    #[test]
+    fn test_empty_text_encodes_to_empty_vector() {
+        let encoder = SparseEncoder::default();
+        assert!(encoder.encode_document("a - !").indices.is_empty());
+        assert!(encoder.encode_query("").indices.is_empty());
+    }
+}
@@ -1,25 +1,59 @@
 /// Text preprocessing for semantic search embeddings
-use regex::Regex;
+use super::preprocess::{PreprocessContext, PreprocessPipeline};
+use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
 
+/// How long text is split into chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkingStrategy {
+    /// Fixed word windows, cutting wherever the limit falls
+    Words,
+    /// Pack whole sentences (or clauses, for overlong sentences) into chunks
+    #[default]
+    Sentences,
+}
+
+/// Words kept from a neighbouring block when summarizing it as context
+const OUTLINE_SUMMARY_WORDS: usize = 12;
+
+/// Characters that end a sentence when followed by whitespace
+const SENTENCE_TERMINATORS: &[char] = &['.', '!', '?'];
+/// Full-width terminators used in CJK text, which isn't whitespace-separated
+const CJK_SENTENCE_TERMINATORS: &[char] = &['。', '！', '？'];
+/// Characters that end a clause
+const CLAUSE_TERMINATORS: &[char] = &[',', ';', ':', '，', '；', '：'];
+
+/// Measures and splits text in model tokens
+pub trait TokenCounter: Send + Sync {
+    /// Number of tokens in `text`, excluding special tokens
+    fn count_tokens(&self, text: &str) -> usize;
+
+    /// Split `text` into windows of at most `max_tokens` tokens, consecutive
+    /// windows sharing `overlap_tokens` tokens
+    fn split_tokens(&self, text: &str, max_tokens: usize, overlap_tokens: usize) -> Vec<String>;
+}
+
 /// Text preprocessor that cleans Logseq syntax while preserving context
+///
+/// What [`preprocess`](Self::preprocess) does is up to its
+/// [`PreprocessPipeline`]; the standard one is used unless another is given.
 #[derive(Debug)]
 pub struct TextPreprocessor {
-    page_ref_regex: Regex,
-    tag_regex: Regex,
-    todo_regex: Regex,
+    pipeline: PreprocessPipeline,
 }
 
 impl TextPreprocessor {
     pub fn new() -> Self {
-        TextPreprocessor {
-            // Matches [[page reference]] patterns
-            page_ref_regex: Regex::new(r"\[\[([^\]]+)\]\]").unwrap(),
-            // Matches #tag patterns (word boundaries to avoid matching URLs)
-            tag_regex: Regex::new(r"#(\w+)").unwrap(),
-            // Matches TODO/DONE/LATER/NOW markers at the start
-            todo_regex: Regex::new(r"^(TODO|DONE|LATER|NOW|IN-PROGRESS)\s+").unwrap(),
-        }
+        Self::with_pipeline(PreprocessPipeline::standard())
+    }
+
+    pub fn with_pipeline(pipeline: PreprocessPipeline) -> Self {
+        TextPreprocessor { pipeline }
+    }
+
+    pub fn pipeline(&self) -> &PreprocessPipeline {
+        &self.pipeline
     }
 
     /// Get a singleton instance (for efficiency in batch processing)
@@ -28,43 +62,44 @@ impl TextPreprocessor {
         INSTANCE.get_or_init(TextPreprocessor::new)
     }
 
-    /// Preprocess a block's content for embedding
-    /// Removes Logseq syntax but keeps semantic meaning
+    /// Preprocess a block's content for embedding by running the pipeline
     pub fn preprocess(&self, content: &str, page_title: &str, hierarchy_path: &[String]) -> String {
-        let mut text = content.to_string();
-
-        // Remove TODO/DONE markers
-        text = self.todo_regex.replace(&text, "").to_string();
-
-        // Replace [[page references]] with just the page name
-        text = self.page_ref_regex.replace_all(&text, "$1").to_string();
-
-        // Replace #tags with just the tag name
-        text = self.tag_regex.replace_all(&text, "$1").to_string();
-
-        // Add context: page title and hierarchy
-        let mut context_parts = vec![];
+        let context = PreprocessContext {
+            page_title,
+            hierarchy_path,
+        };
+        self.pipeline.run(content, &context)
+    }
 
-        // Add page title as context
-        if !page_title.is_empty() {
-            context_parts.push(format!("Page: {}", page_title));
+    /// Add outline context to a block's content: a summary line of its parent
+    /// and of up to `max_children` direct children
+    ///
+    /// Terse outline bullets ("Pros", "Ideas") say little on their own; their
+    /// neighbours carry the meaning. The result still needs [`preprocess`](Self::preprocess).
+    pub fn with_outline_context(
+        &self,
+        content: &str,
+        parent: Option<&str>,
+        children: &[&str],
+        max_children: usize,
+    ) -> String {
+        let mut text = content.trim().to_string();
+
+        if let Some(parent) = parent.map(summary_line).filter(|p| !p.is_empty()) {
+            text = format!("{}. Parent: {}", text, parent);
         }
 
-        // Add parent blocks as context (limit to last 2 for brevity)
-        if !hierarchy_path.is_empty() {
-            let parent_count = hierarchy_path.len().min(2);
-            let relevant_parents = &hierarchy_path[hierarchy_path.len() - parent_count..];
-            if !relevant_parents.is_empty() {
-                context_parts.push(format!("Context: {}", relevant_parents.join(" > ")));
-            }
+        let child_summaries: Vec<String> = children
+            .iter()
+            .map(|c| summary_line(c))
+            .filter(|c| !c.is_empty())
+            .take(max_children)
+            .collect();
+        if !child_summaries.is_empty() {
+            text = format!("{}. Children: {}", text, child_summaries.join("; "));
         }
 
-        // Combine context with content
-        if !context_parts.is_empty() {
-            format!("{}. {}", context_parts.join(". "), text.trim())
-        } else {
-            text.trim().to_string()
-        }
+        text
     }
 
     /// Chunk text into smaller pieces if it exceeds max_tokens
@@ -100,6 +135,203 @@ impl TextPreprocessor {
 
         chunks
     }
+
+    /// Chunk text using the given strategy
+    pub fn chunk(
+        &self,
+        text: &str,
+        strategy: ChunkingStrategy,
+        max_words: usize,
+        overlap_words: usize,
+    ) -> Vec<String> {
+        match strategy {
+            ChunkingStrategy::Words => self.chunk_text(text, max_words, overlap_words),
+            ChunkingStrategy::Sentences => self.chunk_sentences(text, max_words, overlap_words),
+        }
+    }
+
+    /// Chunk text using the given strategy, measuring length in model tokens
+    ///
+    /// Unlike word counts, token counts are exact, so no chunk ever exceeds
+    /// `max_tokens` (special tokens excluded) regardless of content.
+    pub fn chunk_by_tokens(
+        &self,
+        text: &str,
+        strategy: ChunkingStrategy,
+        counter: &dyn TokenCounter,
+        max_tokens: usize,
+        overlap_tokens: usize,
+    ) -> Vec<String> {
+        match strategy {
+            ChunkingStrategy::Words => counter.split_tokens(text, max_tokens, overlap_tokens),
+            ChunkingStrategy::Sentences => self.pack_sentences(
+                text,
+                max_tokens,
+                overlap_tokens,
+                &|t| counter.count_tokens(t),
+                &|t, max| counter.split_tokens(t, max, 0),
+            ),
+        }
+    }
+
+    /// Chunk text at sentence boundaries, with the same limits as [`chunk_text`](Self::chunk_text)
+    ///
+    /// Whole sentences are packed into chunks of at most `max_words` words.
+    /// Sentences that are too long on their own are split at clause boundaries,
+    /// and only then at raw word counts. Overlap between chunks is made of whole
+    /// trailing sentences totalling at most `overlap_words` words.
+    pub fn chunk_sentences(
+        &self,
+        text: &str,
+        max_words: usize,
+        overlap_words: usize,
+    ) -> Vec<String> {
+        self.pack_sentences(
+            text,
+            max_words,
+            overlap_words,
+            &|t| t.split_whitespace().count(),
+            &|t, max| self.chunk_text(t, max, 0),
+        )
+    }
+
+    /// Sentence packing shared by word- and token-measured chunking
+    ///
+    /// `measure` gives the length of a piece of text; `hard_split` cuts text that
+    /// has no usable boundaries into pieces of at most the given length.
+    fn pack_sentences(
+        &self,
+        text: &str,
+        max_len: usize,
+        overlap_len: usize,
+        measure: &dyn Fn(&str) -> usize,
+        hard_split: &dyn Fn(&str, usize) -> Vec<String>,
+    ) -> Vec<String> {
+        if measure(text) <= max_len {
+            return vec![text.to_string()];
+        }
+
+        let units: Vec<(String, usize)> = self
+            .sentence_units(text, max_len, measure, hard_split)
+            .into_iter()
+            .map(|unit| {
+                let len = measure(&unit);
+                (unit, len)
+            })
+            .collect();
+
+        let mut chunks = Vec::new();
+        let mut current: Vec<(String, usize)> = Vec::new();
+        let mut current_len = 0;
+
+        for (unit, len) in units {
+            if current_len + len > max_len && !current.is_empty() {
+                chunks.push(join_units(&current));
+
+                // Carry trailing sentences over as overlap, as long as the next unit still fits
+                let mut overlap = Vec::new();
+                let mut overlap_count = 0;
+                for (prev, prev_len) in current.iter().rev() {
+                    if overlap_count + prev_len > overlap_len
+                        || overlap_count + prev_len + len > max_len
+                    {
+                        break;
+                    }
+                    overlap_count += prev_len;
+                    overlap.push((prev.clone(), *prev_len));
+                }
+                overlap.reverse();
+
+                current = overlap;
+                current_len = overlap_count;
+            }
+
+            current.push((unit, len));
+            current_len += len;
+        }
+
+        if !current.is_empty() {
+            chunks.push(join_units(&current));
+        }
+
+        chunks
+    }
+
+    /// Split text into sentences no longer than `max_len`, falling back to
+    /// clauses and then `hard_split` for overlong sentences
+    fn sentence_units(
+        &self,
+        text: &str,
+        max_len: usize,
+        measure: &dyn Fn(&str) -> usize,
+        hard_split: &dyn Fn(&str, usize) -> Vec<String>,
+    ) -> Vec<String> {
+        let mut units = Vec::new();
+
+        for sentence in split_after(text, |c, next| {
+            c == '\n'
+                || CJK_SENTENCE_TERMINATORS.contains(&c)
+                || (SENTENCE_TERMINATORS.contains(&c) && next.is_none_or(char::is_whitespace))
+        }) {
+            if measure(sentence) <= max_len {
+                units.push(sentence.to_string());
+                continue;
+            }
+
+            for clause in split_after(sentence, |c, _| CLAUSE_TERMINATORS.contains(&c)) {
+                if measure(clause) <= max_len {
+                    units.push(clause.to_string());
+                } else {
+                    units.extend(hard_split(clause, max_len));
+                }
+            }
+        }
+
+        units
+    }
+}
+
+/// Split `text` after every character for which `is_boundary(char, next_char)`
+/// holds, returning trimmed, non-empty pieces
+fn split_after(text: &str, is_boundary: impl Fn(char, Option<char>) -> bool) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, n)| n);
+        if is_boundary(c, next) {
+            let end = i + c.len_utf8();
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+    }
+    pieces.push(&text[start..]);
+
+    pieces
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// First line of a block, cut to a few words
+fn summary_line(content: &str) -> String {
+    let first_line = content.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let words: Vec<&str> = first_line.split_whitespace().collect();
+    if words.len() > OUTLINE_SUMMARY_WORDS {
+        format!("{}...", words[..OUTLINE_SUMMARY_WORDS].join(" "))
+    } else {
+        words.join(" ")
+    }
+}
+
+fn join_units(units: &[(String, usize)]) -> String {
+    units
+        .iter()
+        .map(|(unit, _)| unit.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 impl Default for TextPreprocessor {
@@ -227,4 +459,108 @@ mod tests {
         assert_eq!(chunks[1], "d e f g");
         assert_eq!(chunks[2], "g h i j");
     }
+
+    #[test]
+    fn test_chunk_sentences_keeps_sentences_whole() {
+        let preprocessor = TextPreprocessor::new();
+        let text = "One two three four. Five six seven. Eight nine ten eleven twelve.";
+        let chunks = preprocessor.chunk_sentences(text, 8, 0);
+
+        assert_eq!(
+            chunks,
+            vec!["One two three four. Five six seven.", "Eight nine ten eleven twelve."]
+        );
+    }
+
+    #[test]
+    fn test_chunk_sentences_overlaps_whole_sentences() {
+        let preprocessor = TextPreprocessor::new();
+        let text = "Alpha beta gamma. Delta epsilon. Zeta eta theta iota.";
+        let chunks = preprocessor.chunk_sentences(text, 6, 2);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], "Alpha beta gamma. Delta epsilon.");
+        assert_eq!(chunks[1], "Delta epsilon. Zeta eta theta iota.");
+    }
+
+    #[test]
+    fn test_chunk_sentences_splits_long_sentences_at_clauses() {
+        let preprocessor = TextPreprocessor::new();
+        let text = "first clause has words, second clause has more words; third one";
+        let chunks = preprocessor.chunk_sentences(text, 5, 0);
+
+        assert_eq!(
+            chunks,
+            vec![
+                "first clause has words,",
+                "second clause has more words;",
+                "third one"
+            ]
+        );
+        assert!(chunks.iter().all(|c| c.split_whitespace().count() <= 5));
+    }
+
+    #[test]
+    fn test_chunk_sentences_ignores_inline_periods() {
+        let preprocessor = TextPreprocessor::new();
+        let text = "Version 1.2 of example.com shipped today. It works.";
+        let chunks = preprocessor.chunk_sentences(text, 7, 0);
+
+        assert_eq!(chunks, vec!["Version 1.2 of example.com shipped today.", "It works."]);
+    }
+
+    /// Counts each character as a token, splitting on character windows
+    struct CharCounter;
+
+    impl TokenCounter for CharCounter {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.chars().filter(|c| !c.is_whitespace()).count()
+        }
+
+        fn split_tokens(&self, text: &str, max_tokens: usize, _overlap: usize) -> Vec<String> {
+            let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+            chars.chunks(max_tokens).map(|c| c.iter().collect()).collect()
+        }
+    }
+
+    #[test]
+    fn test_chunk_by_tokens_respects_token_limit() {
+        let preprocessor = TextPreprocessor::new();
+        // Few words, many "tokens": word counting would keep this in one chunk
+        let text = "这是第一句话。这是第二句话。这是一个非常非常长的第三句话";
+        let chunks = preprocessor.chunk_by_tokens(
+            text,
+            ChunkingStrategy::Sentences,
+            &CharCounter,
+            8,
+            0,
+        );
+
+        assert_eq!(chunks[0], "这是第一句话。");
+        assert_eq!(chunks[1], "这是第二句话。");
+        assert!(chunks.len() > 3);
+        assert!(chunks.iter().all(|c| CharCounter.count_tokens(c) <= 8));
+    }
+
+    #[test]
+    fn test_with_outline_context() {
+        let preprocessor = TextPreprocessor::new();
+        let text = preprocessor.with_outline_context(
+            "Pros",
+            Some("Switching the backend to Rust"),
+            &["Memory safety", "Fast builds\nwith details", "Great tooling"],
+            2,
+        );
+
+        assert_eq!(
+            text,
+            "Pros. Parent: Switching the backend to Rust. Children: Memory safety; Fast builds"
+        );
+    }
+
+    #[test]
+    fn test_with_outline_context_without_neighbours() {
+        let preprocessor = TextPreprocessor::new();
+        assert_eq!(preprocessor.with_outline_context("Lonely", None, &[], 5), "Lonely");
+    }
 }
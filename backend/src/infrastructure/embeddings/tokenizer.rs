@@ -0,0 +1,140 @@
+/// Model tokenizer used to size chunks in tokens
+use tokenizers::{PostProcessor, Tokenizer};
+
+use super::text_preprocessor::TokenCounter;
+
+/// Wraps the embedding model's tokenizer for exact token counting
+///
+/// Truncation and padding are disabled so counts reflect the full text.
+/// Counts include the special tokens (`[CLS]`, `[SEP]`) the model adds to
+/// every input, so a chunk sized to the model's limit still fits once they
+/// are added.
+pub struct ModelTokenizer {
+    tokenizer: Tokenizer,
+    /// Tokens the post-processor adds around a single input
+    special_tokens: usize,
+}
+
+impl ModelTokenizer {
+    pub fn new(mut tokenizer: Tokenizer) -> Self {
+        // Disabling truncation can't fail; the error case only covers invalid params
+        let _ = tokenizer.with_truncation(None);
+        tokenizer.with_padding(None);
+        let special_tokens = tokenizer
+            .get_post_processor()
+            .map_or(0, |post_processor| post_processor.added_tokens(false));
+        ModelTokenizer {
+            tokenizer,
+            special_tokens,
+        }
+    }
+}
+
+impl TokenCounter for ModelTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.tokenizer
+            .encode(text, false)
+            .map(|encoding| encoding.len() + self.special_tokens)
+            // Fall back to a conservative estimate if encoding fails
+            .unwrap_or_else(|_| text.chars().count())
+    }
+
+    fn split_tokens(&self, text: &str, max_tokens: usize, overlap_tokens: usize) -> Vec<String> {
+        let Ok(encoding) = self.tokenizer.encode(text, false) else {
+            return vec![text.to_string()];
+        };
+
+        // Byte offsets of each token in the original text
+        let offsets = encoding.get_offsets();
+        let max_tokens = max_tokens.saturating_sub(self.special_tokens).max(1);
+        if offsets.len() <= max_tokens {
+            return vec![text.to_string()];
+        }
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < offsets.len() {
+            let end = (start + max_tokens).min(offsets.len());
+            if let Some(chunk) = text.get(offsets[start].0..offsets[end - 1].1) {
+                chunks.push(chunk.trim().to_string());
+            }
+
+            if end >= offsets.len() {
+                break;
+            }
+
+            // Step back for overlap, but always make progress
+            start = end.saturating_sub(overlap_tokens).max(start + 1);
+        }
+
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::pre_tokenizers::whitespace::Whitespace;
+    use tokenizers::processors::template::TemplateProcessing;
+
+    fn word_tokenizer() -> ModelTokenizer {
+        ModelTokenizer::new(words())
+    }
+
+    fn words() -> Tokenizer {
+        let vocab = ["[UNK]", "alpha", "beta", "gamma", "delta", "epsilon", "[CLS]", "[SEP]"]
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.to_string(), i as u32));
+        let model = WordLevel::builder()
+            .vocab(vocab.collect())
+            .unk_token("[UNK]".to_string())
+            .build()
+            .unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer
+    }
+
+    #[test]
+    fn test_count_tokens() {
+        let tokenizer = word_tokenizer();
+        assert_eq!(tokenizer.count_tokens("alpha beta, gamma"), 4);
+    }
+
+    #[test]
+    fn test_split_tokens_exactly_at_limit() {
+        let tokenizer = word_tokenizer();
+        let chunks = tokenizer.split_tokens("alpha beta gamma delta epsilon", 2, 1);
+
+        assert_eq!(
+            chunks,
+            vec!["alpha beta", "beta gamma", "gamma delta", "delta epsilon"]
+        );
+    }
+
+    #[test]
+    fn test_chunks_fit_the_limit_with_special_tokens() {
+        let mut words = words();
+        let template = TemplateProcessing::builder()
+            .try_single("[CLS] $A [SEP]")
+            .unwrap()
+            .special_tokens(vec![("[CLS]", 6), ("[SEP]", 7)])
+            .build()
+            .unwrap();
+        words.with_post_processor(Some(template));
+        let tokenizer = ModelTokenizer::new(words.clone());
+        let text = ["alpha beta gamma delta epsilon"; 120].join(" ");
+
+        let chunks = tokenizer.split_tokens(&text, 256, 32);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            let encoded = words.encode(chunk.as_str(), true).unwrap().len();
+            assert!(encoded <= 256);
+            assert_eq!(tokenizer.count_tokens(chunk), encoded);
+        }
+        assert_eq!(words.encode(chunks[0].as_str(), true).unwrap().len(), 256);
+    }
+}
@@ -0,0 +1,262 @@
+/// Reading Logseq graphs from zip archives without extracting them
+use super::ignore_rules::{IgnoreRules, IGNORE_FILES};
+use super::layout::{layout_from_config, CONFIG_FILE};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+/// A page or journal file read from an archive
+#[derive(Debug)]
+pub struct ArchiveEntry {
+    /// The archive's path joined with the file's path inside the graph,
+    /// e.g. `backup.zip/pages/rust.md`
+    pub path: PathBuf,
+    /// The file's contents, or why they couldn't be decompressed
+    pub bytes: io::Result<Vec<u8>>,
+}
+
+/// A zipped Logseq graph, read one page or journal file at a time
+///
+/// Backups usually wrap the graph in a top-level folder, so the graph root is
+/// found inside the archive: the folder holding `logseq/config.edn`, or else
+/// the one holding the shallowest `pages`/`journals` directory. The graph's
+/// config and ignore files are applied as they would be on disk.
+pub struct GraphArchive {
+    path: PathBuf,
+    archive: ZipArchive<File>,
+    /// Index in the archive and path relative to the graph root of each file to read
+    entries: Vec<(usize, PathBuf)>,
+    position: usize,
+}
+
+impl GraphArchive {
+    /// Open an archive and find the graph's page and journal files in it
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+
+        let names: Vec<(usize, PathBuf, bool)> = (0..archive.len())
+            .filter_map(|index| {
+                let name = archive.name_for_index(index)?;
+                let is_dir = name.ends_with('/');
+                safe_path(name).map(|path| (index, path, is_dir))
+            })
+            .collect();
+        let root = graph_root(names.iter().map(|(_, path, _)| path.as_path()));
+
+        // Paths relative to the graph root, leaving out anything outside it
+        let files: Vec<(usize, PathBuf, bool)> = names
+            .into_iter()
+            .filter_map(|(index, path, is_dir)| {
+                let relative = path.strip_prefix(&root).ok()?.to_path_buf();
+                (!relative.as_os_str().is_empty()).then_some((index, relative, is_dir))
+            })
+            .collect();
+
+        let directories: HashSet<&Path> = files
+            .iter()
+            .flat_map(|(_, relative, is_dir)| {
+                let parents = relative.ancestors().skip(1);
+                is_dir.then_some(relative.as_path()).into_iter().chain(parents)
+            })
+            .collect();
+
+        let find = |name: &Path| {
+            files
+                .iter()
+                .find(|(_, relative, _)| relative == name)
+                .map(|(index, _, _)| *index)
+        };
+        let config = match find(Path::new(CONFIG_FILE)) {
+            Some(index) => read_text(&mut archive, index)?,
+            None => String::new(),
+        };
+        let mut patterns = String::new();
+        for name in IGNORE_FILES {
+            if let Some(index) = find(Path::new(name)) {
+                patterns.push_str(&read_text(&mut archive, index)?);
+                patterns.push('\n');
+            }
+        }
+
+        let layout = layout_from_config(&config, |dir| directories.contains(dir));
+        let content_dirs: Vec<&Path> = std::iter::once(layout.pages_dir())
+            .chain(layout.journals_dir())
+            .collect();
+        let patterns: Vec<&str> = patterns.lines().collect();
+        let rules = IgnoreRules::with_patterns(path, &patterns);
+
+        let entries = files
+            .iter()
+            .filter(|(_, relative, is_dir)| {
+                !is_dir
+                    && relative.extension().and_then(|ext| ext.to_str()) == Some("md")
+                    && content_dirs.iter().any(|dir| relative.starts_with(dir))
+                    && !rules.is_ignored(relative)
+            })
+            .map(|(index, relative, _)| (*index, relative.clone()))
+            .collect();
+
+        Ok(GraphArchive {
+            path: path.to_path_buf(),
+            archive,
+            entries,
+            position: 0,
+        })
+    }
+
+    /// Number of page and journal files in the archive
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Paths of the page and journal files, as their entries will report them
+    pub fn paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.entries.iter().map(|(_, relative)| self.path.join(relative))
+    }
+}
+
+impl Iterator for GraphArchive {
+    type Item = ArchiveEntry;
+
+    /// Read the next page or journal file, decompressing only that entry
+    fn next(&mut self) -> Option<ArchiveEntry> {
+        let (index, relative) = self.entries.get(self.position)?.clone();
+        self.position += 1;
+
+        Some(ArchiveEntry {
+            path: self.path.join(relative),
+            bytes: read_bytes(&mut self.archive, index),
+        })
+    }
+}
+
+/// An entry name as a relative path, or `None` if it could escape the archive
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        .then(|| path.components().collect())
+}
+
+/// Where the graph starts inside the archive
+fn graph_root<'a>(paths: impl Iterator<Item = &'a Path> + Clone) -> PathBuf {
+    let config = Path::new(CONFIG_FILE);
+    let from_config = paths
+        .clone()
+        .filter(|path| path.ends_with(config))
+        .filter_map(|path| path.ancestors().nth(config.components().count()))
+        .min_by_key(|root| root.components().count());
+    if let Some(root) = from_config {
+        return root.to_path_buf();
+    }
+
+    paths
+        .filter_map(|path| {
+            let depth = path
+                .components()
+                .position(|component| matches!(component.as_os_str().to_str(), Some("pages" | "journals")))?;
+            Some(path.components().take(depth).collect::<PathBuf>())
+        })
+        .min_by_key(|root| root.components().count())
+        .unwrap_or_default()
+}
+
+fn read_bytes(archive: &mut ZipArchive<File>, index: usize) -> io::Result<Vec<u8>> {
+    let mut file = archive.by_index(index).map_err(io::Error::other)?;
+    let mut bytes = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_text(archive: &mut ZipArchive<File>, index: usize) -> io::Result<String> {
+    Ok(String::from_utf8_lossy(&read_bytes(archive, index)?).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            if name.ends_with('/') {
+                writer.add_directory(*name, SimpleFileOptions::default()).unwrap();
+            } else {
+                writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+                writer.write_all(content.as_bytes()).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+    }
+
+    fn entry_paths(archive: GraphArchive) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = archive.map(|entry| entry.path).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_finds_graph_inside_top_level_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("backup.zip");
+        write_zip(
+            &zip_path,
+            &[
+                ("my-graph/", ""),
+                ("my-graph/pages/rust.md", "- Ownership"),
+                ("my-graph/journals/2024_01_01.md", "- New year"),
+                ("my-graph/logseq/bak/pages/rust.md", "- Old"),
+                ("my-graph/pages/image.png", "binary"),
+                ("my-graph/README.md", "- Not a page"),
+            ],
+        );
+
+        let archive = GraphArchive::open(&zip_path).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(
+            entry_paths(archive),
+            vec![zip_path.join("journals/2024_01_01.md"), zip_path.join("pages/rust.md")]
+        );
+    }
+
+    #[test]
+    fn test_applies_config_and_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("backup.zip");
+        write_zip(
+            &zip_path,
+            &[
+                ("logseq/config.edn", "{:pages-directory \"notes\"}"),
+                (".logseqignore", "drafts/\n"),
+                ("notes/rust.md", "- Ownership"),
+                ("notes/drafts/wip.md", "- Unfinished"),
+                ("pages/stale.md", "- Not in the configured directory"),
+            ],
+        );
+
+        let archive = GraphArchive::open(&zip_path).unwrap();
+        assert_eq!(entry_paths(archive), vec![zip_path.join("notes/rust.md")]);
+    }
+
+    #[test]
+    fn test_skips_entries_escaping_the_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("backup.zip");
+        write_zip(&zip_path, &[("pages/../../evil.md", "- Escaped"), ("pages/ok.md", "- Fine")]);
+
+        let mut archive = GraphArchive::open(&zip_path).unwrap();
+        let entry = archive.next().unwrap();
+        assert_eq!(entry.path, zip_path.join("pages/ok.md"));
+        assert_eq!(entry.bytes.unwrap(), b"- Fine");
+        assert!(archive.next().is_none());
+    }
+}
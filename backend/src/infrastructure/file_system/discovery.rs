@@ -1,9 +1,56 @@
 /// File discovery utilities for finding Logseq markdown files
+use super::IgnoreRules;
+use crate::domain::value_objects::LogseqDirectoryPath;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// Discover all .md files in a directory recursively
 pub async fn discover_markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    discover_files(dir, None).await
+}
+
+/// Discover markdown files in both pages/ and journals/ subdirectories
+///
+/// Files matched by the graph's ignore rules (see [`IgnoreRules::for_graph`])
+/// are left out.
+pub async fn discover_logseq_files(logseq_dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let rules = IgnoreRules::for_graph(logseq_dir);
+    let mut all_files = Vec::new();
+
+    for dir in ["pages", "journals"] {
+        let dir = logseq_dir.join(dir);
+        if dir.exists() {
+            all_files.append(&mut discover_files(&dir, Some(&rules)).await?);
+        }
+    }
+
+    Ok(all_files)
+}
+
+/// Discover markdown files in a graph's content directories, skipping ignored paths
+///
+/// The graph's layout decides which directories are searched; each is
+/// searched recursively, so pages may be organized in nested folders.
+pub async fn discover_graph_files(
+    graph: &LogseqDirectoryPath,
+    rules: &IgnoreRules,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut all_files = Vec::new();
+
+    for dir in graph.content_dirs() {
+        if dir.exists() {
+            all_files.append(&mut discover_files(&dir, Some(rules)).await?);
+        }
+    }
+
+    Ok(all_files)
+}
+
+/// Recursive walk shared by the discovery functions; ignored directories are not entered
+async fn discover_files(
+    dir: &Path,
+    rules: Option<&IgnoreRules>,
+) -> Result<Vec<PathBuf>, std::io::Error> {
     Box::pin(async move {
         let mut files = Vec::new();
         let mut entries = fs::read_dir(dir).await?;
@@ -11,6 +58,10 @@ pub async fn discover_markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
 
+            if rules.is_some_and(|rules| rules.is_ignored(&path)) {
+                continue;
+            }
+
             if path.is_file() {
                 if let Some(extension) = path.extension() {
                     if extension == "md" {
@@ -21,7 +72,7 @@ pub async fn discover_markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io
                 // Skip hidden directories and logseq internal directories
                 if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
                     if !dir_name.starts_with('.') && dir_name != "logseq" {
-                        let mut sub_files = discover_markdown_files(&path).await?;
+                        let mut sub_files = discover_files(&path, rules).await?;
                         files.append(&mut sub_files);
                     }
                 }
@@ -32,30 +83,10 @@ pub async fn discover_markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io
     }).await
 }
 
-/// Discover markdown files in both pages/ and journals/ subdirectories
-pub async fn discover_logseq_files(logseq_dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
-    let mut all_files = Vec::new();
-
-    // Discover files in pages/
-    let pages_dir = logseq_dir.join("pages");
-    if pages_dir.exists() {
-        let mut pages_files = discover_markdown_files(&pages_dir).await?;
-        all_files.append(&mut pages_files);
-    }
-
-    // Discover files in journals/
-    let journals_dir = logseq_dir.join("journals");
-    if journals_dir.exists() {
-        let mut journals_files = discover_markdown_files(&journals_dir).await?;
-        all_files.append(&mut journals_files);
-    }
-
-    Ok(all_files)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::value_objects::GraphLayout;
     use std::fs;
     use tempfile::TempDir;
 
@@ -101,4 +132,47 @@ mod tests {
 
         assert_eq!(files.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_discover_logseq_files_skips_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let logseq_dir = temp_dir.path();
+
+        let pages_dir = logseq_dir.join("pages");
+        fs::create_dir_all(pages_dir.join("drafts")).unwrap();
+        fs::create_dir(logseq_dir.join("journals")).unwrap();
+
+        fs::write(pages_dir.join("page1.md"), "content").unwrap();
+        fs::write(pages_dir.join(".#page1.md"), "lock").unwrap();
+        fs::write(pages_dir.join("drafts").join("draft.md"), "content").unwrap();
+        fs::write(logseq_dir.join(".logseqignore"), "pages/drafts/\n").unwrap();
+
+        let files = discover_logseq_files(logseq_dir).await.unwrap();
+
+        assert_eq!(files, vec![pages_dir.join("page1.md")]);
+    }
+
+    #[tokio::test]
+    async fn test_discover_graph_files_with_custom_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let pages_dir = root.join("notes");
+        fs::create_dir_all(pages_dir.join("projects").join("2025")).unwrap();
+        fs::create_dir(root.join("pages")).unwrap();
+
+        fs::write(pages_dir.join("top.md"), "content").unwrap();
+        fs::write(pages_dir.join("projects").join("2025").join("deep.md"), "content").unwrap();
+        fs::write(root.join("pages").join("not-content.md"), "content").unwrap();
+
+        let layout = GraphLayout::new("notes", None).unwrap();
+        let graph = LogseqDirectoryPath::with_layout(root, layout).unwrap();
+        let mut files = discover_graph_files(&graph, &IgnoreRules::new(root)).await.unwrap();
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec![pages_dir.join("projects").join("2025").join("deep.md"), pages_dir.join("top.md")]
+        );
+    }
 }
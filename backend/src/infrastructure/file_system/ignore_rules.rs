@@ -0,0 +1,143 @@
+/// Gitignore-style rules for files that are never imported or synced
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+/// Patterns ignored in every graph
+///
+/// Logseq keeps trashed pages in `logseq/.recycle` and backups of conflicting
+/// edits in `logseq/bak` (whose layout mirrors `pages/`). The rest are editor
+/// swap, lock and temp files that can briefly carry a `.md` name.
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
+    "logseq/.recycle/",
+    "logseq/bak/",
+    "logseq/version-files/",
+    ".#*",
+    "*~",
+    "*.swp",
+    "*.swo",
+    "*.tmp",
+    ".DS_Store",
+];
+
+/// Files in the graph root whose rules apply on top of the defaults
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".logseqignore"];
+
+/// Decides which paths inside a graph are ignored
+///
+/// Rules use `.gitignore` syntax and are matched relative to the graph root,
+/// including negation (`!pattern`) to re-include something a default excludes.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Only the default rules
+    pub fn new(root: &Path) -> Self {
+        Self::build(root, &[], &[])
+    }
+
+    /// Default rules plus those from the graph's `.gitignore` / `.logseqignore`
+    pub fn for_graph(root: &Path) -> Self {
+        let files: Vec<PathBuf> = IGNORE_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+        Self::build(root, &files, &[])
+    }
+
+    /// Default rules plus the given patterns
+    pub fn with_patterns(root: &Path, patterns: &[&str]) -> Self {
+        Self::build(root, &[], patterns)
+    }
+
+    /// Whether a path (absolute, or relative to the graph root) is ignored
+    ///
+    /// A path is also ignored when one of its parent directories is.
+    /// Paths outside the graph are never ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return false,
+        };
+
+        self.matcher
+            .matched_path_or_any_parents(relative, path.is_dir())
+            .is_ignore()
+    }
+
+    /// Invalid patterns are skipped with a warning rather than failing the sync
+    fn build(root: &Path, files: &[PathBuf], patterns: &[&str]) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+
+        for pattern in DEFAULT_IGNORE_PATTERNS {
+            // Defaults are known to be valid
+            let _ = builder.add_line(None, pattern);
+        }
+        for file in files {
+            if let Some(e) = builder.add(file) {
+                tracing::warn!("Problem reading ignore rules from {}: {}", file.display(), e);
+            }
+        }
+        for pattern in patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                tracing::warn!("Skipping invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+
+        let matcher = builder.build().unwrap_or_else(|e| {
+            tracing::warn!("Failed to build ignore rules, using none: {}", e);
+            Gitignore::empty()
+        });
+
+        IgnoreRules {
+            root: root.to_path_buf(),
+            matcher,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_ignore_recycle_backups_and_temp_files() {
+        let rules = IgnoreRules::new(Path::new("/graph"));
+
+        assert!(rules.is_ignored(Path::new("/graph/logseq/.recycle/pages_old.md")));
+        assert!(rules.is_ignored(Path::new("/graph/logseq/bak/pages/note/2024-01-01.md")));
+        assert!(rules.is_ignored(Path::new("/graph/pages/.#note.md")));
+        assert!(rules.is_ignored(Path::new("/graph/pages/note.md~")));
+        assert!(rules.is_ignored(Path::new("/graph/pages/note.md.tmp")));
+
+        assert!(!rules.is_ignored(Path::new("/graph/pages/note.md")));
+        assert!(!rules.is_ignored(Path::new("/graph/journals/2025_01_01.md")));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/logseq/bak/pages/note.md")));
+    }
+
+    #[test]
+    fn test_graph_ignore_files_are_applied() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".logseqignore"), "pages/drafts/\n*.private.md\n").unwrap();
+
+        let rules = IgnoreRules::for_graph(temp_dir.path());
+
+        assert!(rules.is_ignored(&temp_dir.path().join("pages/drafts/idea.md")));
+        assert!(rules.is_ignored(&temp_dir.path().join("pages/diary.private.md")));
+        assert!(!rules.is_ignored(&temp_dir.path().join("pages/idea.md")));
+    }
+
+    #[test]
+    fn test_negation_overrides_defaults() {
+        let rules = IgnoreRules::with_patterns(Path::new("/graph"), &["!keep.tmp"]);
+
+        assert!(!rules.is_ignored(Path::new("/graph/pages/keep.tmp")));
+        assert!(rules.is_ignored(Path::new("/graph/pages/other.tmp")));
+    }
+}
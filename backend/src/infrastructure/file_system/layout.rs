@@ -0,0 +1,100 @@
+/// Detection of a graph's directory layout from its Logseq config
+use crate::domain::base::DomainResult;
+use crate::domain::value_objects::GraphLayout;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Location of the graph config, relative to the graph root
+pub const CONFIG_FILE: &str = "logseq/config.edn";
+
+/// Work out where a graph keeps its pages and journals
+///
+/// Reads `:pages-directory` and `:journals-directory` from `logseq/config.edn`,
+/// falling back to Logseq's defaults. Journals are left out of the layout when
+/// their directory doesn't exist, so graphs without journals still open.
+pub fn detect_layout(root: &Path) -> GraphLayout {
+    let config = std::fs::read_to_string(root.join(CONFIG_FILE)).unwrap_or_default();
+    layout_from_config(&config, |dir| root.join(dir).is_dir())
+}
+
+/// Like [`detect_layout`], for a graph whose config has already been read
+///
+/// `dir_exists` tells whether a directory, relative to the graph root, exists.
+pub fn layout_from_config(config: &str, dir_exists: impl Fn(&Path) -> bool) -> GraphLayout {
+    let pages_dir = config_string(config, "pages-directory");
+    let journals_dir = config_string(config, "journals-directory");
+
+    let configured = layout_for(
+        &dir_exists,
+        pages_dir.as_deref().unwrap_or("pages"),
+        journals_dir.as_deref().unwrap_or("journals"),
+    );
+    configured.unwrap_or_else(|e| {
+        tracing::warn!("Ignoring directory settings in {}: {}", CONFIG_FILE, e);
+        layout_for(&dir_exists, "pages", "journals").expect("default directories are valid")
+    })
+}
+
+fn layout_for(
+    dir_exists: &impl Fn(&Path) -> bool,
+    pages_dir: &str,
+    journals_dir: &str,
+) -> DomainResult<GraphLayout> {
+    let journals_dir = Some(PathBuf::from(journals_dir)).filter(|dir| dir_exists(dir));
+    GraphLayout::new(pages_dir, journals_dir)
+}
+
+/// Value of a top-level `:key "value"` string entry, skipping commented-out lines
+fn config_string(config: &str, key: &str) -> Option<String> {
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+    let entry = ENTRY.get_or_init(|| Regex::new(r#"^\s*\{?\s*:([\w-]+)\s+"([^"]*)""#).unwrap());
+
+    config
+        .lines()
+        .filter_map(|line| entry.captures(line))
+        .find(|captures| &captures[1] == key)
+        .map(|captures| captures[2].trim_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_without_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("journals")).unwrap();
+
+        assert_eq!(detect_layout(temp_dir.path()), GraphLayout::default());
+    }
+
+    #[test]
+    fn test_reads_directories_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("logseq")).unwrap();
+        fs::create_dir(root.join("daily")).unwrap();
+        fs::write(
+            root.join(CONFIG_FILE),
+            "{:meta/version 1\n ;; :pages-directory \"old\"\n :pages-directory \"notes/\"\n :journals-directory \"daily\"}\n",
+        )
+        .unwrap();
+
+        let layout = detect_layout(root);
+        assert_eq!(layout.pages_dir(), Path::new("notes"));
+        assert_eq!(layout.journals_dir(), Some(Path::new("daily")));
+    }
+
+    #[test]
+    fn test_missing_journals_directory_is_optional() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = detect_layout(temp_dir.path());
+
+        assert_eq!(layout.pages_dir(), Path::new("pages"));
+        assert_eq!(layout.journals_dir(), None);
+    }
+}
@@ -1,5 +1,13 @@
+pub mod archive;
 pub mod discovery;
+pub mod ignore_rules;
+pub mod layout;
+pub mod stable_read;
 pub mod watcher;
 
-pub use discovery::{discover_logseq_files, discover_markdown_files};
-pub use watcher::{FileEvent, FileEventKind, LogseqFileWatcher, WatcherError};
+pub use archive::{ArchiveEntry, GraphArchive};
+pub use discovery::{discover_logseq_files, discover_graph_files, discover_markdown_files};
+pub use ignore_rules::{IgnoreRules, DEFAULT_IGNORE_PATTERNS, IGNORE_FILES};
+pub use layout::{detect_layout, layout_from_config, CONFIG_FILE};
+pub use stable_read::StabilityCheck;
+pub use watcher::{coalesce_events, FileEvent, FileEventKind, LogseqFileWatcher, WatcherError};
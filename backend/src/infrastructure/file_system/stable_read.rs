@@ -0,0 +1,107 @@
+/// Reading files that may still be in the middle of being written
+use std::path::Path;
+use std::time::Duration;
+
+/// Re-reads a file until two consecutive reads agree
+///
+/// A watcher can fire while an editor or sync tool is still writing, and a
+/// single read may then see a truncated file. Reading again after a short
+/// delay and comparing the contents catches that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StabilityCheck {
+    /// Delay between consecutive reads
+    pub settle_delay: Duration,
+    /// Most reads before giving up and using the latest contents (1 disables the check)
+    pub max_reads: u32,
+}
+
+impl Default for StabilityCheck {
+    fn default() -> Self {
+        StabilityCheck {
+            settle_delay: Duration::from_millis(50),
+            max_reads: 5,
+        }
+    }
+}
+
+impl StabilityCheck {
+    /// Read once, without waiting for the file to settle
+    pub fn none() -> Self {
+        StabilityCheck {
+            settle_delay: Duration::ZERO,
+            max_reads: 1,
+        }
+    }
+
+    /// Read the file once its contents stop changing
+    ///
+    /// If it is still changing after `max_reads` reads, the latest contents are
+    /// returned; the next watcher event for the file will pick up the rest.
+    pub async fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        let mut previous = tokio::fs::read(path).await?;
+
+        for _ in 1..self.max_reads {
+            tokio::time::sleep(self.settle_delay).await;
+            let current = tokio::fs::read(path).await?;
+            if current == previous {
+                return Ok(current);
+            }
+            previous = current;
+        }
+
+        if self.max_reads > 1 {
+            tracing::warn!(
+                "{} was still changing after {} reads; using the latest contents",
+                path.display(),
+                self.max_reads
+            );
+        }
+        Ok(previous)
+    }
+
+    /// Like [`read`](Self::read), decoding the contents as UTF-8
+    pub async fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+        let bytes = self.read(path).await?;
+        String::from_utf8(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_waits_for_writer_to_finish() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("page.md");
+        std::fs::write(&path, "- Fir").unwrap();
+
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                std::fs::write(&path, "- First block\n- Second block").unwrap();
+            })
+        };
+
+        let check = StabilityCheck {
+            settle_delay: Duration::from_millis(40),
+            max_reads: 5,
+        };
+        let content = check.read_to_string(&path).await.unwrap();
+        writer.await.unwrap();
+
+        assert_eq!(content, "- First block\n- Second block");
+    }
+
+    #[tokio::test]
+    async fn test_single_read_when_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("page.md");
+        std::fs::write(&path, "- Block").unwrap();
+
+        assert_eq!(StabilityCheck::none().read(&path).await.unwrap(), b"- Block");
+    }
+}
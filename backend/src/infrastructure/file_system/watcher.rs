@@ -1,11 +1,19 @@
 /// File system watcher using the notify crate
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer, DebouncedEventKind};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
 use std::time::Duration;
 use thiserror::Error;
 
+/// Debounced batches buffered before the debouncer waits for the consumer
+///
+/// While it waits, further changes are merged into its next batch per path,
+/// so a burst such as a large `git pull` can't grow the queue without bound.
+const EVENT_QUEUE_CAPACITY: usize = 16;
+
 #[derive(Error, Debug)]
 pub enum WatcherError {
     #[error("Notify error: {0}")]
@@ -39,7 +47,7 @@ impl FileEvent {
             .unwrap_or(false)
     }
 
-    /// Check if this event is in pages/ or journals/ directories
+    /// Check if this event is in pages/ or journals/ directories (the default layout)
     pub fn is_in_logseq_dirs(&self) -> bool {
         self.path
             .ancestors()
@@ -53,10 +61,51 @@ impl FileEvent {
     }
 }
 
+/// Collapse a batch of events to at most one event per path
+///
+/// Editors that save via a temp file and rename fire bursts such as
+/// create+modify or delete+create for the same path. Only the net effect is
+/// kept: a file created and deleted within the batch disappears entirely, a
+/// deleted and recreated file becomes a modification, and a created file
+/// stays created however often it was modified. Paths keep the order in which
+/// they were first seen.
+pub fn coalesce_events(events: Vec<FileEvent>) -> Vec<FileEvent> {
+    let mut order: Vec<PathBuf> = Vec::new();
+    let mut net: HashMap<PathBuf, Option<FileEventKind>> = HashMap::new();
+
+    for event in events {
+        let Some(previous) = net.get(&event.path).cloned() else {
+            order.push(event.path.clone());
+            net.insert(event.path, Some(event.kind));
+            continue;
+        };
+
+        let merged = match (previous, event.kind) {
+            // Created and removed again: nothing happened as far as sync is concerned
+            (Some(FileEventKind::Created), FileEventKind::Deleted) => None,
+            (Some(FileEventKind::Created), _) => Some(FileEventKind::Created),
+            (Some(FileEventKind::Deleted), FileEventKind::Created) => Some(FileEventKind::Modified),
+            (None, FileEventKind::Deleted) => None,
+            (None, _) => Some(FileEventKind::Created),
+            (Some(_), kind) => Some(kind),
+        };
+        net.insert(event.path, merged);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|path| {
+            let kind = net.remove(&path).flatten()?;
+            Some(FileEvent { path, kind })
+        })
+        .collect()
+}
+
 /// File watcher with debouncing for Logseq directories
 pub struct LogseqFileWatcher {
     _debouncer: Debouncer<RecommendedWatcher>,
-    receiver: Receiver<DebounceEventResult>,
+    /// Behind a mutex so the watcher can be shared across tasks
+    receiver: Mutex<Receiver<DebounceEventResult>>,
 }
 
 impl LogseqFileWatcher {
@@ -66,9 +115,13 @@ impl LogseqFileWatcher {
         path: &Path,
         debounce_duration: Duration,
     ) -> Result<Self, WatcherError> {
-        let (tx, rx) = std::sync::mpsc::channel();
+        let (tx, rx) = std::sync::mpsc::sync_channel(EVENT_QUEUE_CAPACITY);
 
-        let mut debouncer = new_debouncer(debounce_duration, tx)?;
+        // Blocks the debouncer thread when the queue is full; fails only once
+        // the watcher (and with it the receiver) is dropped
+        let mut debouncer = new_debouncer(debounce_duration, move |result: DebounceEventResult| {
+            let _ = tx.send(result);
+        })?;
 
         // Watch the directory recursively
         debouncer
@@ -77,13 +130,13 @@ impl LogseqFileWatcher {
 
         Ok(LogseqFileWatcher {
             _debouncer: debouncer,
-            receiver: rx,
+            receiver: Mutex::new(rx),
         })
     }
 
     /// Get the next batch of file events (non-blocking)
     pub fn try_recv(&self) -> Option<Vec<FileEvent>> {
-        match self.receiver.try_recv() {
+        match self.receiver.lock().unwrap().try_recv() {
             Ok(Ok(events)) => {
                 let file_events: Vec<FileEvent> = events
                     .into_iter()
@@ -110,7 +163,7 @@ impl LogseqFileWatcher {
 
     /// Wait for the next batch of file events (blocking)
     pub fn recv(&self) -> Option<Vec<FileEvent>> {
-        match self.receiver.recv() {
+        match self.receiver.lock().unwrap().recv() {
             Ok(Ok(events)) => {
                 let file_events: Vec<FileEvent> = events
                     .into_iter()
@@ -150,8 +203,9 @@ impl LogseqFileWatcher {
 
         let event = FileEvent { path, kind: event_kind };
 
-        // Only return events for markdown files in pages/ or journals/
-        if event.is_markdown() && event.is_in_logseq_dirs() {
+        // Only return events for markdown files; which directories hold pages
+        // depends on the graph's layout and is decided by the consumer
+        if event.is_markdown() {
             Some(event)
         } else {
             None
@@ -198,4 +252,39 @@ mod tests {
         };
         assert!(!event3.is_in_logseq_dirs());
     }
+
+    fn event(path: &str, kind: FileEventKind) -> FileEvent {
+        FileEvent {
+            path: PathBuf::from(path),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_coalesce_events_keeps_net_effect_per_path() {
+        let events = vec![
+            event("/g/pages/a.md", FileEventKind::Created),
+            event("/g/pages/b.md", FileEventKind::Deleted),
+            event("/g/pages/a.md", FileEventKind::Modified),
+            event("/g/pages/c.md", FileEventKind::Created),
+            event("/g/pages/b.md", FileEventKind::Created),
+            event("/g/pages/c.md", FileEventKind::Deleted),
+            event("/g/pages/d.md", FileEventKind::Modified),
+            event("/g/pages/d.md", FileEventKind::Deleted),
+        ];
+
+        let coalesced: Vec<(String, FileEventKind)> = coalesce_events(events)
+            .into_iter()
+            .map(|e| (e.path.display().to_string(), e.kind))
+            .collect();
+
+        assert_eq!(
+            coalesced,
+            vec![
+                ("/g/pages/a.md".to_string(), FileEventKind::Created),
+                ("/g/pages/b.md".to_string(), FileEventKind::Modified),
+                ("/g/pages/d.md".to_string(), FileEventKind::Deleted),
+            ]
+        );
+    }
 }
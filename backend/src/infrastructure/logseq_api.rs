@@ -0,0 +1,280 @@
+/// Client of the HTTP API a running Logseq desktop app serves to plugins
+use crate::domain::aggregates::Page;
+use crate::infrastructure::parsers::{LogseqExportParser, ParseError};
+use serde_json::{json, Value};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where Logseq serves the API once "Settings > Features > HTTP APIs server"
+/// is on and the server is started
+pub const DEFAULT_LOGSEQ_API_URL: &str = "http://127.0.0.1:12315";
+
+/// Time allowed for one API call; a large page's block tree can take a while
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum LogseqApiError {
+    #[error("Cannot reach Logseq: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Logseq refused the token; create one under the API server's settings")]
+    Unauthorized,
+
+    #[error("Logseq answered {method} with {status}: {message}")]
+    Call { method: String, status: u16, message: String },
+
+    #[error("Invalid page from Logseq: {0}")]
+    Parse(#[from] ParseError),
+}
+
+pub type LogseqApiResult<T> = Result<T, LogseqApiError>;
+
+/// A page as listed by `logseq.Editor.getAllPages`, without its blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogseqApiPage {
+    /// Logseq's UUID of the page
+    pub uuid: String,
+    /// The title as written, e.g. `Rust Notes` for the page named `rust notes`
+    pub title: String,
+    pub journal: bool,
+}
+
+/// Client pulling pages and blocks straight from a running Logseq instance
+///
+/// Pages come with the UUIDs Logseq assigned to them and their blocks, like
+/// a graph export, so this is an alternative to parsing the graph's files
+/// that also sees changes not yet written to disk.
+pub struct LogseqApiClient {
+    url: String,
+    /// The `/api` endpoint under `url`
+    endpoint: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl LogseqApiClient {
+    /// Client of the API served at `url`, such as [`DEFAULT_LOGSEQ_API_URL`]
+    pub fn new(url: &str) -> Self {
+        let url = url.trim_end_matches('/');
+        LogseqApiClient {
+            url: url.to_string(),
+            endpoint: format!("{}/api", url),
+            token: None,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Authorize calls with one of the tokens set up in Logseq
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// The server's URL, without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Call a plugin API method, like `logseq.Editor.getPage`, returning its result
+    pub async fn call(&self, method: &str, args: Value) -> LogseqApiResult<Value> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json!({ "method": method, "args": args }).to_string());
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(LogseqApiError::Unauthorized);
+        }
+
+        let result = serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null);
+        let error = result.get("error").and_then(Value::as_str);
+        if !status.is_success() || error.is_some() {
+            return Err(LogseqApiError::Call {
+                method: method.to_string(),
+                status: status.as_u16(),
+                message: error.map_or_else(|| String::from_utf8_lossy(&body).into_owned(), str::to_string),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Name of the graph open in Logseq, if any
+    pub async fn graph_name(&self) -> LogseqApiResult<Option<String>> {
+        let graph = self.call("logseq.App.getCurrentGraph", json!([])).await?;
+        Ok(graph.get("name").and_then(Value::as_str).map(str::to_string))
+    }
+
+    /// Every page of the open graph, without blocks
+    pub async fn list_pages(&self) -> LogseqApiResult<Vec<LogseqApiPage>> {
+        let pages = self.call("logseq.Editor.getAllPages", json!([])).await?;
+        Ok(pages
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|page| {
+                let uuid = page.get("uuid").and_then(Value::as_str)?;
+                let title = ["originalName", "name"]
+                    .iter()
+                    .find_map(|key| page.get(*key).and_then(Value::as_str))?;
+                Some(LogseqApiPage {
+                    uuid: uuid.to_string(),
+                    title: title.to_string(),
+                    journal: page.get("journal?").and_then(Value::as_bool).unwrap_or(false),
+                })
+            })
+            .collect())
+    }
+
+    /// A page and its blocks, by UUID or name; `None` if Logseq has no such page
+    pub async fn fetch_page(&self, page: &str) -> LogseqApiResult<Option<Page>> {
+        let mut entity = self.call("logseq.Editor.getPage", json!([page])).await?;
+        let Value::Object(fields) = &mut entity else {
+            return Ok(None);
+        };
+        let blocks = self.call("logseq.Editor.getPageBlocksTree", json!([page])).await?;
+        fields.insert("children".to_string(), blocks);
+        Ok(Some(LogseqExportParser::parse_page_entity(&entity)?))
+    }
+
+    /// Every page of the open graph with its blocks, one request per page
+    /// after the listing; pages deleted meanwhile are skipped
+    pub async fn fetch_all_pages(&self) -> LogseqApiResult<Vec<Page>> {
+        let mut pages = Vec::new();
+        for listed in self.list_pages().await? {
+            match self.fetch_page(&listed.uuid).await? {
+                Some(page) => pages.push(page),
+                None => tracing::debug!("Page {} was deleted while pulling the graph", listed.title),
+            }
+        }
+        Ok(pages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::base::Entity;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A Logseq API server answering each method with `answer`, recording
+    /// the requests' bodies and `Authorization` headers
+    async fn logseq(answer: fn(&str, &Value) -> (u16, Value)) -> (String, Arc<Mutex<Vec<(Value, String)>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let (head, body) = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length: usize = head
+                            .lines()
+                            .filter_map(|line| line.split_once(':'))
+                            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                            .map(|(_, value)| value.trim().parse().unwrap())
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break (head.to_string(), body.to_string());
+                        }
+                    }
+                };
+                let authorization = head
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+                    .map(|(_, value)| value.trim().to_string())
+                    .unwrap_or_default();
+                let call: Value = serde_json::from_str(&body).unwrap();
+                let (status, result) = answer(call["method"].as_str().unwrap(), &call["args"]);
+                log.lock().unwrap().push((call, authorization));
+
+                let body = result.to_string();
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, received)
+    }
+
+    fn graph(method: &str, args: &Value) -> (u16, Value) {
+        let page = args[0].as_str().unwrap_or_default();
+        let result = match (method, page) {
+            ("logseq.Editor.getAllPages", _) => json!([
+                {"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000a", "name": "rust", "originalName": "Rust"},
+                {"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000f", "name": "gone"}
+            ]),
+            ("logseq.Editor.getPage", "6530a0f2-0000-4c1e-9b7e-00000000000a") => json!({
+                "uuid": "6530a0f2-0000-4c1e-9b7e-00000000000a", "name": "rust", "originalName": "Rust"
+            }),
+            ("logseq.Editor.getPageBlocksTree", "6530a0f2-0000-4c1e-9b7e-00000000000a") => json!([
+                {"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000b", "content": "Ownership [[memory]]",
+                 "children": [{"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000c", "content": "Borrowing"}]}
+            ]),
+            ("logseq.App.getCurrentGraph", _) => json!({"name": "notes", "path": "/home/me/notes"}),
+            _ => Value::Null,
+        };
+        (200, result)
+    }
+
+    #[tokio::test]
+    async fn test_fetches_pages_with_logseq_ids() {
+        let (url, received) = logseq(graph).await;
+        let client = LogseqApiClient::new(&url).with_token("s3cret");
+
+        assert_eq!(client.graph_name().await.unwrap().as_deref(), Some("notes"));
+        let pages = client.fetch_all_pages().await.unwrap();
+        assert_eq!(pages.len(), 1);
+        let page = &pages[0];
+        assert_eq!(page.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000a");
+        assert_eq!(page.title(), "Rust");
+        let root = page.root_blocks()[0];
+        assert_eq!(root.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000b");
+        assert_eq!(root.page_references().len(), 1);
+        assert_eq!(page.get_block(&root.child_ids()[0]).unwrap().content().as_str(), "Borrowing");
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[1].0["method"], "logseq.Editor.getAllPages");
+        assert!(received.iter().all(|(_, authorization)| authorization == "Bearer s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_reports_refused_calls() {
+        let (url, _) = logseq(|method, _| match method {
+            "logseq.Editor.getAllPages" => (401, json!({"error": "Unauthorized"})),
+            _ => (500, json!({"error": "MethodNotExist: getPage"})),
+        })
+        .await;
+        let client = LogseqApiClient::new(&url);
+
+        assert!(matches!(client.list_pages().await, Err(LogseqApiError::Unauthorized)));
+        match client.fetch_page("rust").await {
+            Err(LogseqApiError::Call { method, status, message }) => {
+                assert_eq!(method, "logseq.Editor.getPage");
+                assert_eq!(status, 500);
+                assert_eq!(message, "MethodNotExist: getPage");
+            }
+            other => panic!("expected a refused call, got {:?}", other.map(|page| page.is_some())),
+        }
+    }
+}
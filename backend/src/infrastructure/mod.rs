@@ -1,3 +1,12 @@
+#[cfg(feature = "qdrant")]
 pub mod embeddings;
+#[cfg(feature = "fs")]
 pub mod file_system;
+#[cfg(feature = "native")]
+pub mod logseq_api;
 pub mod parsers;
+#[cfg(feature = "native")]
+pub mod persistence;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+pub mod text;
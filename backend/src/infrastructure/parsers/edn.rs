@@ -0,0 +1,264 @@
+/// Minimal EDN reader for Logseq graph exports
+use super::{ParseError, ParseResult};
+use serde_json::{Map, Number, Value};
+
+/// Read a single EDN value into its JSON equivalent
+///
+/// Keywords become strings without the colon or namespace (`:block/content`
+/// reads as `"content"`), lists, vectors and sets become arrays, and tagged
+/// literals such as `#uuid "..."` read as their value.
+pub fn read(input: &str) -> ParseResult<Value> {
+    let mut reader = Reader {
+        chars: input.chars().collect(),
+        position: 0,
+    };
+    let value = reader.value()?.ok_or_else(|| reader.error("no value"))?;
+    reader.skip_whitespace();
+    if reader.position < reader.chars.len() {
+        return Err(reader.error("unexpected trailing input"));
+    }
+    Ok(value)
+}
+
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Reader {
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::InvalidExport(format!("EDN {} at character {}", message, self.position))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.peek() {
+            if ch.is_whitespace() || ch == ',' {
+                self.position += 1;
+            } else if ch == ';' {
+                while self.peek().is_some_and(|ch| ch != '\n') {
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// The next value, or `None` at a closing delimiter or the end of input
+    fn value(&mut self) -> ParseResult<Option<Value>> {
+        self.skip_whitespace();
+        let Some(ch) = self.peek() else {
+            return Ok(None);
+        };
+
+        let value = match ch {
+            ')' | ']' | '}' => return Ok(None),
+            '(' | '[' => {
+                self.position += 1;
+                Value::Array(self.sequence(if ch == '(' { ')' } else { ']' })?)
+            }
+            '{' => {
+                self.position += 1;
+                self.map()?
+            }
+            '"' => {
+                self.position += 1;
+                Value::String(self.string()?)
+            }
+            '\\' => {
+                self.position += 1;
+                Value::String(self.character())
+            }
+            '#' => {
+                self.position += 1;
+                match self.peek() {
+                    Some('{') => {
+                        self.position += 1;
+                        Value::Array(self.sequence('}')?)
+                    }
+                    Some('_') => {
+                        // Discarded form
+                        self.position += 1;
+                        self.value()?.ok_or_else(|| self.error("nothing to discard"))?;
+                        return self.value();
+                    }
+                    _ => {
+                        // Tagged literal: keep the value, drop the tag
+                        self.token();
+                        return self.value()?.ok_or_else(|| self.error("tag without value")).map(Some);
+                    }
+                }
+            }
+            ':' => {
+                self.position += 1;
+                Value::String(keyword_name(&self.token()).to_string())
+            }
+            _ => atom(&self.token()).ok_or_else(|| self.error("invalid token"))?,
+        };
+        Ok(Some(value))
+    }
+
+    fn sequence(&mut self, close: char) -> ParseResult<Vec<Value>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.value()? {
+            items.push(item);
+        }
+        self.expect(close)?;
+        Ok(items)
+    }
+
+    fn map(&mut self) -> ParseResult<Value> {
+        let mut map = Map::new();
+        while let Some(key) = self.value()? {
+            let value = self.value()?.ok_or_else(|| self.error("map key without value"))?;
+            let key = match key {
+                Value::String(key) => key,
+                other => other.to_string(),
+            };
+            map.insert(key, value);
+        }
+        self.expect('}')?;
+        Ok(Value::Object(map))
+    }
+
+    fn expect(&mut self, close: char) -> ParseResult<()> {
+        if self.peek() != Some(close) {
+            return Err(self.error(&format!("expected '{}'", close)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        let mut text = String::new();
+        loop {
+            let ch = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+            self.position += 1;
+            match ch {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += 1;
+                    match escaped {
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        'r' => text.push('\r'),
+                        'u' => {
+                            let hex: String = self.chars.iter().skip(self.position).take(4).collect();
+                            let code = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            text.push(code);
+                            self.position += 4;
+                        }
+                        other => text.push(other),
+                    }
+                }
+                other => text.push(other),
+            }
+        }
+    }
+
+    /// A character literal, after its backslash
+    fn character(&mut self) -> String {
+        let name = self.token();
+        match name.as_str() {
+            "newline" => "\n".to_string(),
+            "space" => " ".to_string(),
+            "tab" => "\t".to_string(),
+            "return" => "\r".to_string(),
+            // A delimiter such as `\(` ends the token before it starts
+            "" => self
+                .peek()
+                .map(|ch| {
+                    self.position += 1;
+                    ch.to_string()
+                })
+                .unwrap_or_default(),
+            _ => name,
+        }
+    }
+
+    /// Characters up to the next delimiter
+    fn token(&mut self) -> String {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, ',' | '(' | ')' | '[' | ']' | '{' | '}' | '"' | ';'))
+        {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+}
+
+/// `block/content` reads as `content`
+fn keyword_name(keyword: &str) -> &str {
+    keyword.rsplit('/').next().unwrap_or(keyword)
+}
+
+/// nil, booleans, numbers and symbols
+fn atom(token: &str) -> Option<Value> {
+    match token {
+        "" => None,
+        "nil" => Some(Value::Null),
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => {
+            let number = token.trim_end_matches(['N', 'M']);
+            if let Ok(int) = number.parse::<i64>() {
+                Some(Value::Number(int.into()))
+            } else if let Some(float) = number.parse::<f64>().ok().and_then(Number::from_f64) {
+                Some(Value::Number(float))
+            } else {
+                Some(Value::String(token.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_nested_structures() {
+        let edn = r#"
+            ; exported graph
+            {:version 1,
+             :blocks ({:block/uuid #uuid "6530a0f2-1111-4c1e-9b7e-000000000001"
+                       :block/page-name "foo"
+                       :block/properties {:tags #{"a" "b"}, :rating 4.5}
+                       :block/children [{:block/content "Line \"quoted\"\nnext" :pre-block? false}]
+                       #_ :ignored #_ "value"
+                       :block/format :markdown})}
+        "#;
+
+        assert_eq!(
+            read(edn).unwrap(),
+            json!({
+                "version": 1,
+                "blocks": [{
+                    "uuid": "6530a0f2-1111-4c1e-9b7e-000000000001",
+                    "page-name": "foo",
+                    "properties": {"tags": ["a", "b"], "rating": 4.5},
+                    "children": [{"content": "Line \"quoted\"\nnext", "pre-block?": false}],
+                    "format": "markdown"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_unbalanced_input() {
+        assert!(read("{:a [1 2}").is_err());
+        assert!(read("\"open").is_err());
+        assert!(read("[1] 2").is_err());
+    }
+}
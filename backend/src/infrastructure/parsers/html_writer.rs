@@ -0,0 +1,259 @@
+/// Static HTML writer - renders Page aggregates as a browsable, read-only site
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::entities::Block;
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+/// File every site gets, listing its pages
+pub const INDEX_FILE: &str = "index.html";
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:48rem;margin:2rem auto;padding:0 1rem;line-height:1.5}\
+a.page-ref{text-decoration:none}span.page-ref{color:#777}\
+ul.blocks ul{border-left:1px solid #ddd}dl.properties{color:#555}\
+section.backlinks{margin-top:3rem;border-top:1px solid #ddd}";
+
+/// Writer producing one HTML file per page, named by [`HtmlWriter::file_name`]
+///
+/// Blocks become nested lists; `[[page]]` links and `#tags` link to the
+/// pages they name, or are left as plain text when the graph has no such
+/// page, and URLs become links.
+pub struct HtmlWriter {
+    /// File name of each page, by lowercased title
+    files: HashMap<String, String>,
+}
+
+impl HtmlWriter {
+    /// Name the files of `pages`, which references are linked to
+    ///
+    /// Pages are named in title order, so a page whose name is taken gets a
+    /// numbered one the same way on every export.
+    pub fn new<'a>(pages: impl IntoIterator<Item = &'a Page>) -> Self {
+        let mut titles: Vec<String> = pages.into_iter().map(|page| page.title().to_lowercase()).collect();
+        titles.sort();
+        titles.dedup();
+
+        let mut taken: HashSet<String> = HashSet::from([INDEX_FILE.to_string()]);
+        let mut files = HashMap::new();
+        for title in titles {
+            let stem = slug(&title);
+            let file = (1..)
+                .map(|n| if n == 1 { format!("{}.html", stem) } else { format!("{}-{}.html", stem, n) })
+                .find(|file| !taken.contains(file))
+                .expect("unbounded");
+            taken.insert(file.clone());
+            files.insert(title, file);
+        }
+        Self { files }
+    }
+
+    /// The file a page is written to, if it was among the writer's pages
+    pub fn file_name(&self, title: &str) -> Option<&str> {
+        self.files.get(&title.to_lowercase()).map(String::as_str)
+    }
+
+    /// Render a page with its backlinks: the blocks of other pages that
+    /// reference it, with the page each is on
+    pub fn render_page(&self, page: &Page, backlinks: &[(&Page, &Block)]) -> String {
+        let mut body = format!("<h1>{}</h1>\n", escape(page.title()));
+        if !page.properties().is_empty() {
+            body.push_str("<dl class=\"properties\">\n");
+            for (key, value) in page.properties() {
+                let _ = writeln!(body, "<dt>{}</dt><dd>{}</dd>", escape(key), self.render_inline(value));
+            }
+            body.push_str("</dl>\n");
+        }
+
+        let roots = page.root_blocks();
+        if !roots.is_empty() {
+            body.push_str("<ul class=\"blocks\">\n");
+            for block in roots {
+                self.render_block(page, block, &mut body);
+            }
+            body.push_str("</ul>\n");
+        }
+
+        if !backlinks.is_empty() {
+            let _ = writeln!(body, "<section class=\"backlinks\">\n<h2>{} Linked References</h2>", backlinks.len());
+            let mut current: Option<&Page> = None;
+            for (other, block) in backlinks {
+                if current.is_none_or(|current| current.id() != other.id()) {
+                    if current.is_some() {
+                        body.push_str("</ul>\n");
+                    }
+                    let _ = writeln!(body, "<h3>{}</h3>\n<ul>", self.page_link(other.title(), other.title()));
+                    current = Some(other);
+                }
+                let _ = writeln!(body, "<li>{}</li>", self.render_inline(block.content().as_str()));
+            }
+            body.push_str("</ul>\n</section>\n");
+        }
+
+        document(page.title(), &body)
+    }
+
+    /// Render the index, linking every page in the order given
+    pub fn render_index(&self, title: &str, pages: &[&Page]) -> String {
+        let mut body = format!("<h1>{}</h1>\n<ul class=\"pages\">\n", escape(title));
+        for page in pages {
+            let _ = writeln!(body, "<li>{}</li>", self.page_link(page.title(), page.title()));
+        }
+        body.push_str("</ul>\n");
+        document(title, &body)
+    }
+
+    fn render_block(&self, page: &Page, block: &Block, output: &mut String) {
+        output.push_str("<li>");
+        output.push_str(&self.render_inline(block.content().as_str()));
+
+        let children: Vec<&Block> = block.child_ids().iter().filter_map(|id| page.get_block(id)).collect();
+        if !children.is_empty() {
+            output.push_str("\n<ul>\n");
+            for child in children {
+                self.render_block(page, child, output);
+            }
+            output.push_str("</ul>\n");
+        }
+        output.push_str("</li>\n");
+    }
+
+    /// Escape block text, turning references and URLs into links
+    fn render_inline(&self, content: &str) -> String {
+        static INLINE: OnceLock<Regex> = OnceLock::new();
+        // Tags end at whitespace or punctuation, as the parser reads them
+        let inline = INLINE.get_or_init(|| {
+            Regex::new(r"\[\[(?P<link>[^\]]+)\]\]|(?P<before>^|\s)#(?P<tag>[^\s[:punct:]]+)|(?P<url>https?://\S+)")
+                .expect("valid regex")
+        });
+
+        let mut html = String::new();
+        let mut last = 0;
+        for captures in inline.captures_iter(content) {
+            let whole = captures.get(0).expect("whole match");
+            html.push_str(&escape(&content[last..whole.start()]));
+            last = whole.end();
+            html.push_str(&self.render_match(&captures));
+        }
+        html.push_str(&escape(&content[last..]));
+        html.replace('\n', "<br>\n")
+    }
+
+    fn render_match(&self, captures: &Captures) -> String {
+        if let Some(title) = captures.name("link") {
+            return self.page_link(title.as_str(), title.as_str());
+        }
+        if let Some(tag) = captures.name("tag") {
+            let before = captures.name("before").map_or("", |before| before.as_str());
+            return format!("{}{}", escape(before), self.page_link(tag.as_str(), &format!("#{}", tag.as_str())));
+        }
+        let url = captures.name("url").map_or("", |url| url.as_str());
+        // Trailing punctuation ends a sentence rather than the URL
+        let link = url.trim_end_matches(|c: char| c.is_ascii_punctuation());
+        format!(
+            "<a href=\"{}\">{}</a>{}",
+            escape(link),
+            escape(link),
+            escape(&url[link.len()..])
+        )
+    }
+
+    fn page_link(&self, title: &str, text: &str) -> String {
+        match self.file_name(title) {
+            Some(file) => format!("<a class=\"page-ref\" href=\"{}\">{}</a>", escape(file), escape(text)),
+            None => format!("<span class=\"page-ref\">{}</span>", escape(text)),
+        }
+    }
+}
+
+/// A page title as a file name stem: lowercase letters and digits, other
+/// characters (like namespace slashes) becoming single dashes
+fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "page".to_string() } else { slug.to_string() }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <nav><a href=\"{}\">All pages</a></nav>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        INDEX_FILE,
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    fn page(id: &str, title: &str, content: &str) -> Page {
+        LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.into()).unwrap()
+    }
+
+    #[test]
+    fn test_file_names_are_slugs_unique_across_the_graph() {
+        let pages = [
+            page("a", "Projects/Logjam", ""),
+            page("b", "projects logjam", ""),
+            page("c", "Index", ""),
+            page("d", "???", ""),
+        ];
+        let writer = HtmlWriter::new(&pages);
+
+        assert_eq!(writer.file_name("Projects Logjam"), Some("projects-logjam.html"));
+        assert_eq!(writer.file_name("projects/logjam"), Some("projects-logjam-2.html"));
+        assert_eq!(writer.file_name("Index"), Some("index-2.html"));
+        assert_eq!(writer.file_name("???"), Some("page.html"));
+        assert_eq!(writer.file_name("Missing"), None);
+    }
+
+    #[test]
+    fn test_render_page_nests_blocks_and_links_references() {
+        let rust = page("rust", "Rust", "- Read [[Book]] <now> #learning\n\t- See https://rust-lang.org.");
+        let book = page("book", "Book", "- Chapter one");
+        let writer = HtmlWriter::new([&rust, &book]);
+
+        let html = writer.render_page(&rust, &[]);
+        assert!(html.contains("<title>Rust</title>"));
+        assert!(html.contains(
+            "<li>Read <a class=\"page-ref\" href=\"book.html\">Book</a> &lt;now&gt; \
+             <span class=\"page-ref\">#learning</span>\n<ul>\n\
+             <li>See <a href=\"https://rust-lang.org\">https://rust-lang.org</a>.</li>\n</ul>\n</li>"
+        ));
+        assert!(!html.contains("<section"));
+
+        let block = rust.root_blocks()[0];
+        let html = writer.render_page(&book, &[(&rust, block)]);
+        assert!(html.contains("<h2>1 Linked References</h2>\n<h3><a class=\"page-ref\" href=\"rust.html\">Rust</a></h3>"));
+    }
+}
@@ -0,0 +1,318 @@
+/// Logseq graph export parser - converts JSON/EDN exports into Page and Block domain objects
+use super::{edn, LogseqMarkdownParser, ParseError, ParseResult};
+use crate::domain::aggregates::Page;
+use crate::domain::entities::Block;
+use crate::domain::value_objects::{BlockContent, BlockId, IndentLevel, PageId};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Parser for the whole-graph exports Logseq writes via "Export graph"
+///
+/// Unlike markdown parsing, block and page IDs are the UUIDs Logseq assigned,
+/// so block references stay resolvable, and properties come from the
+/// export's structured `properties` maps rather than from block text.
+pub struct LogseqExportParser;
+
+impl LogseqExportParser {
+    /// Parse an export file; `.edn` files are read as EDN, anything else as JSON
+    #[cfg(feature = "fs")]
+    pub async fn parse_file(path: &Path) -> ParseResult<Vec<Page>> {
+        let bytes = tokio::fs::read(path).await?;
+        let content = LogseqMarkdownParser::decode(&bytes);
+
+        let is_edn = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("edn"));
+        if is_edn {
+            Self::parse_edn(&content)
+        } else {
+            Self::parse_json(&content)
+        }
+    }
+
+    /// Parse a JSON graph export
+    pub fn parse_json(content: &str) -> ParseResult<Vec<Page>> {
+        let export: Value = serde_json::from_str(content)
+            .map_err(|e| ParseError::InvalidExport(format!("Invalid JSON: {}", e)))?;
+        Self::parse_value(&export)
+    }
+
+    /// Parse an EDN graph export
+    pub fn parse_edn(content: &str) -> ParseResult<Vec<Page>> {
+        Self::parse_value(&edn::read(content)?)
+    }
+
+    /// Parse one page entity holding its block tree under `children`, as
+    /// Logseq's plugin API returns them (with camelCase keys)
+    pub fn parse_page_entity(page: &Value) -> ParseResult<Page> {
+        match page {
+            Value::Object(fields) => Self::parse_page(fields),
+            _ => Err(ParseError::InvalidExport("Page entry is not a map".to_string())),
+        }
+    }
+
+    /// Convert an export (`{"blocks": [page, ...]}` or a bare list of pages) into pages
+    fn parse_value(export: &Value) -> ParseResult<Vec<Page>> {
+        let pages = match export {
+            Value::Array(pages) => pages,
+            Value::Object(fields) => match fields.get("blocks") {
+                Some(Value::Array(pages)) => pages,
+                _ => return Err(ParseError::InvalidExport("Missing \"blocks\" list".to_string())),
+            },
+            _ => return Err(ParseError::InvalidExport("Expected a map or list".to_string())),
+        };
+
+        pages.iter().map(Self::parse_page_entity).collect()
+    }
+
+    fn parse_page(fields: &Map<String, Value>) -> ParseResult<Page> {
+        let title = ["original-name", "originalName", "page-name", "name", "title"]
+            .iter()
+            .find_map(|key| fields.get(*key).and_then(Value::as_str))
+            .ok_or_else(|| ParseError::InvalidExport("Page without a name".to_string()))?;
+        let page_id = match uuid_of(fields) {
+            Some(uuid) => PageId::new(uuid)?,
+            None => PageId::new(format!("page-{}", uuid::Uuid::new_v4()))?,
+        };
+
+        let mut page = Page::new(page_id, title.to_string());
+        for (key, value) in properties_of(fields) {
+            page.set_property(key, value);
+        }
+
+        for child in children_of(fields) {
+            // The pre-block holds the page's properties; it has no content of its own
+            let pre_block = ["pre-block?", "preBlock?"].iter().find_map(|key| child.get(*key));
+            if pre_block.and_then(Value::as_bool) == Some(true) {
+                for (key, value) in properties_of(child) {
+                    if !page.properties().contains_key(&key) {
+                        page.set_property(key, value);
+                    }
+                }
+                continue;
+            }
+            Self::add_block(&mut page, child, None, 0)?;
+        }
+        Ok(page)
+    }
+
+    fn add_block(
+        page: &mut Page,
+        fields: &Map<String, Value>,
+        parent_id: Option<&BlockId>,
+        depth: usize,
+    ) -> ParseResult<()> {
+        let block_id = match uuid_of(fields) {
+            Some(uuid) => BlockId::new(uuid)?,
+            None => BlockId::new(format!("block-{}", uuid::Uuid::new_v4()))?,
+        };
+
+        let raw_content = fields.get("content").and_then(Value::as_str).unwrap_or_default();
+        let (content, inline_properties) = split_property_lines(raw_content);
+
+        let mut block = match parent_id {
+            None => Block::new_root(block_id.clone(), BlockContent::new(content.clone())),
+            Some(parent_id) => Block::new_child(
+                block_id.clone(),
+                BlockContent::new(content.clone()),
+                parent_id.clone(),
+                IndentLevel::new(depth),
+            ),
+        };
+        for url in LogseqMarkdownParser::extract_urls(&content) {
+            block.add_url(url);
+        }
+        for reference in LogseqMarkdownParser::extract_page_references(&content) {
+            block.add_page_reference(reference);
+        }
+
+        // Structured properties win over the `key:: value` lines they were written as
+        for (key, value) in inline_properties.into_iter().chain(properties_of(fields)) {
+            block.set_property(key, value);
+        }
+
+        page.add_block(block)?;
+
+        for child in children_of(fields) {
+            Self::add_block(page, child, Some(&block_id), depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// The entry's UUID, under either of the keys exports use for it
+fn uuid_of(fields: &Map<String, Value>) -> Option<&str> {
+    ["id", "uuid"]
+        .iter()
+        .find_map(|key| fields.get(*key).and_then(Value::as_str))
+        .filter(|id| !id.is_empty())
+}
+
+fn children_of(fields: &Map<String, Value>) -> impl Iterator<Item = &Map<String, Value>> {
+    fields
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+}
+
+/// Properties as text; list values (e.g. `tags`) are joined with commas
+///
+/// The plugin API camelCases keys (`createdAt`), so they're turned back into
+/// the `created-at` written in files.
+fn properties_of(fields: &Map<String, Value>) -> BTreeMap<String, String> {
+    let Some(Value::Object(properties)) = fields.get("properties") else {
+        return BTreeMap::new();
+    };
+
+    properties
+        .iter()
+        .map(|(key, value)| (kebab_case(key), property_text(value)))
+        .collect()
+}
+
+fn kebab_case(key: &str) -> String {
+    let mut kebab = String::with_capacity(key.len());
+    for ch in key.chars() {
+        if ch.is_uppercase() {
+            kebab.push('-');
+            kebab.extend(ch.to_lowercase());
+        } else {
+            kebab.push(ch);
+        }
+    }
+    kebab
+}
+
+fn property_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(property_text).collect::<Vec<_>>().join(", "),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Separate `key:: value` lines from the rest of a block's text
+fn split_property_lines(content: &str) -> (String, BTreeMap<String, String>) {
+    let mut text = Vec::new();
+    let mut properties = BTreeMap::new();
+
+    for line in content.lines() {
+        let property = line.trim().split_once(":: ").filter(|(key, _)| {
+            !key.is_empty() && key.chars().all(|ch| ch.is_alphanumeric() || ch == '-' || ch == '_')
+        });
+        match property {
+            Some((key, value)) => {
+                properties.insert(key.to_lowercase(), value.trim().to_string());
+            }
+            None => text.push(line),
+        }
+    }
+
+    (text.join("\n").trim().to_string(), properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::base::Entity;
+
+    const EXPORT_JSON: &str = r#"{
+        "version": 1,
+        "blocks": [{
+            "id": "6530a0f2-0000-4c1e-9b7e-00000000000a",
+            "page-name": "rust",
+            "properties": {"tags": ["lang", "systems"]},
+            "format": "markdown",
+            "children": [
+                {"id": "6530a0f2-0000-4c1e-9b7e-00000000000b", "pre-block?": true,
+                 "properties": {"alias": "rustlang"}, "content": "alias:: rustlang"},
+                {"id": "6530a0f2-0000-4c1e-9b7e-00000000000c",
+                 "content": "Ownership notes [[memory]]\nid:: 6530a0f2-0000-4c1e-9b7e-00000000000c\nstatus:: draft",
+                 "properties": {"status": "reviewed"},
+                 "children": [
+                     {"id": "6530a0f2-0000-4c1e-9b7e-00000000000d",
+                      "content": "See https://doc.rust-lang.org", "children": []}
+                 ]}
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn test_parse_json_keeps_ids_and_properties() {
+        let pages = LogseqExportParser::parse_json(EXPORT_JSON).unwrap();
+        assert_eq!(pages.len(), 1);
+
+        let page = &pages[0];
+        assert_eq!(page.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000a");
+        assert_eq!(page.title(), "rust");
+        assert_eq!(page.properties()["tags"], "lang, systems");
+        assert_eq!(page.properties()["alias"], "rustlang");
+
+        let roots = page.root_blocks();
+        assert_eq!(roots.len(), 1);
+        let block = roots[0];
+        assert_eq!(block.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000c");
+        assert_eq!(block.content().as_str(), "Ownership notes [[memory]]");
+        assert_eq!(block.properties()["status"], "reviewed");
+        assert_eq!(block.properties()["id"], "6530a0f2-0000-4c1e-9b7e-00000000000c");
+        assert_eq!(block.page_references().len(), 1);
+
+        let child = page.get_block(&block.child_ids()[0]).unwrap();
+        assert_eq!(child.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000d");
+        assert_eq!(child.indent_level().value(), 1);
+        assert_eq!(child.urls().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_edn_matches_json() {
+        let edn = r#"
+            {:version 1
+             :blocks ({:block/uuid #uuid "6530a0f2-0000-4c1e-9b7e-00000000000a"
+                       :block/page-name "rust"
+                       :block/children [{:block/uuid #uuid "6530a0f2-0000-4c1e-9b7e-00000000000c"
+                                         :block/content "Ownership notes"
+                                         :block/properties {:status "draft"}
+                                         :block/children []}]})}
+        "#;
+
+        let pages = LogseqExportParser::parse_edn(edn).unwrap();
+        let block = pages[0].root_blocks()[0];
+        assert_eq!(pages[0].id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000a");
+        assert_eq!(block.id().as_str(), "6530a0f2-0000-4c1e-9b7e-00000000000c");
+        assert_eq!(block.properties()["status"], "draft");
+    }
+
+    #[test]
+    fn test_parse_page_entity_reads_plugin_api_keys() {
+        let entity = serde_json::json!({
+            "uuid": "6530a0f2-0000-4c1e-9b7e-00000000000a",
+            "name": "rust notes",
+            "originalName": "Rust Notes",
+            "children": [
+                {"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000b", "preBlock?": true,
+                 "content": "tags:: lang", "properties": {"tags": ["lang"]}},
+                {"uuid": "6530a0f2-0000-4c1e-9b7e-00000000000c", "content": "Ownership",
+                 "properties": {"createdAt": 1700000000000u64}}
+            ]
+        });
+
+        let page = LogseqExportParser::parse_page_entity(&entity).unwrap();
+        assert_eq!(page.title(), "Rust Notes");
+        assert_eq!(page.properties()["tags"], "lang");
+        let roots = page.root_blocks();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].properties()["created-at"], "1700000000000");
+    }
+
+    #[test]
+    fn test_rejects_exports_without_pages() {
+        assert!(LogseqExportParser::parse_json("{\"version\": 1}").is_err());
+        assert!(LogseqExportParser::parse_json("[{\"children\": []}]").is_err());
+    }
+}
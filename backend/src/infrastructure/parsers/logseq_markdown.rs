@@ -7,6 +7,7 @@ use crate::domain::value_objects::{
 use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
+use tracing::instrument;
 
 #[derive(Error, Debug)]
 pub enum ParseError {
@@ -16,6 +17,9 @@ pub enum ParseError {
     #[error("Invalid markdown structure: {0}")]
     InvalidMarkdown(String),
 
+    #[error("Invalid graph export: {0}")]
+    InvalidExport(String),
+
     #[error("Domain error: {0}")]
     Domain(#[from] crate::domain::base::DomainError),
 }
@@ -27,26 +31,100 @@ pub struct LogseqMarkdownParser;
 
 impl LogseqMarkdownParser {
     /// Parse a markdown file from the given path
+    #[cfg(feature = "fs")]
     pub async fn parse_file(path: &Path) -> ParseResult<Page> {
-        let content = tokio::fs::read_to_string(path).await?;
+        let bytes = tokio::fs::read(path).await?;
+        Self::parse_file_content(path, &Self::decode(&bytes))
+    }
 
+    /// Decode file contents to text, whatever encoding an editor saved them in
+    ///
+    /// Byte order marks are stripped and UTF-16 (with or without a BOM) is
+    /// transcoded. Bytes that are not valid UTF-8 are read as Windows-1252,
+    /// the usual encoding of legacy Windows editors.
+    pub fn decode(bytes: &[u8]) -> String {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, rest @ ..] => Self::decode_utf8_or_legacy(rest),
+            [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+            [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+            _ => match looks_like_utf16(bytes) {
+                Some(true) => decode_utf16(bytes, u16::from_le_bytes),
+                Some(false) => decode_utf16(bytes, u16::from_be_bytes),
+                None => Self::decode_utf8_or_legacy(bytes),
+            },
+        }
+    }
+
+    fn decode_utf8_or_legacy(bytes: &[u8]) -> String {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&byte| windows_1252_char(byte)).collect(),
+        }
+    }
+
+    /// Parse markdown already read from the file at the given path
+    pub fn parse_file_content(path: &Path, content: &str) -> ParseResult<Page> {
+        let page_id = PageId::new(format!("page-{}", uuid::Uuid::new_v4()))?;
+        Self::parse_file_content_with_id(path, content, page_id)
+    }
+
+    /// Parse markdown read from the file at the given path into the page
+    /// with the given ID, as when the file was parsed before
+    #[instrument(name = "parse", skip(content), fields(path = %path.display(), bytes = content.len()))]
+    pub fn parse_file_content_with_id(path: &Path, content: &str, page_id: PageId) -> ParseResult<Page> {
         // Extract title from filename (without .md extension)
         let title = path
             .file_stem()
             .and_then(|s| s.to_str())
-            .ok_or_else(|| ParseError::InvalidMarkdown("Invalid filename".to_string()))?
-            .to_string();
+            .map(Self::title_from_file_stem)
+            .ok_or_else(|| ParseError::InvalidMarkdown("Invalid filename".to_string()))?;
 
-        // Generate page ID from title (could be more sophisticated)
-        let page_id = PageId::new(format!("page-{}", uuid::Uuid::new_v4()))?;
+        let mut page = Self::parse_content(content, page_id, title)?;
+        page.set_file_path(path.to_path_buf());
+        Ok(page)
+    }
+
+    /// Recover a page title from a file name without its extension
+    ///
+    /// Logseq escapes characters that aren't allowed in file names: namespace
+    /// separators become `___` (the `:triple-lowbar` file name format) and
+    /// other reserved characters are percent-encoded, e.g. `foo%2Fbar` or
+    /// `10%3A30 meeting`. Malformed escapes are kept as written.
+    pub fn title_from_file_stem(stem: &str) -> String {
+        let stem = stem.replace("___", "/");
+        let bytes = stem.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let escaped = match bytes.get(i..i + 3) {
+                Some([b'%', high, low]) if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                    Some(hex_value(*high) << 4 | hex_value(*low))
+                }
+                _ => None,
+            };
+            match escaped {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                None => {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+        }
 
-        Self::parse_content(&content, page_id, title)
+        String::from_utf8(decoded).unwrap_or(stem)
     }
 
     /// Parse markdown content into a Page with Blocks
     pub fn parse_content(content: &str, page_id: PageId, title: String) -> ParseResult<Page> {
         let mut page = Page::new(page_id, title);
 
+        // A BOM left in the text would otherwise be glued to the first block
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
         // Parse lines into blocks
         let lines: Vec<&str> = content.lines().collect();
         let blocks = Self::parse_blocks(&lines)?;
@@ -180,7 +258,7 @@ impl LogseqMarkdownParser {
     }
 
     /// Extract URLs from content (http:// and https://)
-    fn extract_urls(content: &str) -> Vec<Url> {
+    pub(crate) fn extract_urls(content: &str) -> Vec<Url> {
         let mut urls = Vec::new();
 
         // Simple regex-like extraction (in production, use a proper URL parser)
@@ -201,7 +279,7 @@ impl LogseqMarkdownParser {
     }
 
     /// Extract page references from content ([[page]] and #tag)
-    fn extract_page_references(content: &str) -> Vec<PageReference> {
+    pub(crate) fn extract_page_references(content: &str) -> Vec<PageReference> {
         let mut references = Vec::new();
         let mut position = 0;
         let chars: Vec<char> = content.chars().collect();
@@ -264,6 +342,58 @@ impl LogseqMarkdownParser {
     }
 }
 
+/// Decode UTF-16 code units, replacing unpaired surrogates
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Guess whether BOM-less text is UTF-16: `Some(true)` for little-endian,
+/// `Some(false)` for big-endian, `None` if it doesn't look like UTF-16
+///
+/// Markdown is mostly ASCII, so UTF-16 text has a zero in (nearly) every
+/// other byte, which UTF-8 text never has.
+fn looks_like_utf16(bytes: &[u8]) -> Option<bool> {
+    if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let zero_high = bytes.chunks_exact(2).filter(|pair| pair[1] == 0 && pair[0] != 0).count();
+    let zero_low = bytes.chunks_exact(2).filter(|pair| pair[0] == 0 && pair[1] != 0).count();
+
+    if zero_high * 10 >= pairs * 7 {
+        Some(true)
+    } else if zero_low * 10 >= pairs * 7 {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Windows-1252 decoding of a single byte
+fn windows_1252_char(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+        '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// Value of an ASCII hex digit
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,6 +416,52 @@ mod tests {
         assert_eq!(LogseqMarkdownParser::extract_content("Text without bullet"), "Text without bullet");
     }
 
+    #[test]
+    fn test_title_from_file_stem() {
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("plain page"), "plain page");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("foo%2Fbar"), "foo/bar");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("10%3A30 meeting"), "10:30 meeting");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("aaa___bbb___ccc"), "aaa/bbb/ccc");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("caf%C3%A9"), "café");
+        // Malformed or incomplete escapes stay as they are
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("100%"), "100%");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("50%+off"), "50%+off");
+        assert_eq!(LogseqMarkdownParser::title_from_file_stem("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_decode_handles_boms_and_legacy_encodings() {
+        let text = "- Café\n\t- “quoted”";
+
+        let mut utf8_bom = vec![0xEF, 0xBB, 0xBF];
+        utf8_bom.extend_from_slice(text.as_bytes());
+        assert_eq!(LogseqMarkdownParser::decode(&utf8_bom), text);
+
+        let mut utf16_le = vec![0xFF, 0xFE];
+        utf16_le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(LogseqMarkdownParser::decode(&utf16_le), text);
+
+        let mut utf16_be = vec![0xFE, 0xFF];
+        utf16_be.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(LogseqMarkdownParser::decode(&utf16_be), text);
+
+        let bomless: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(LogseqMarkdownParser::decode(&bomless), text);
+
+        // "Café “quoted”" in Windows-1252
+        let legacy = b"- Caf\xe9\n\t- \x93quoted\x94";
+        assert_eq!(LogseqMarkdownParser::decode(legacy), text);
+    }
+
+    #[test]
+    fn test_bom_is_not_part_of_first_block() {
+        let page_id = PageId::new("test-page").unwrap();
+        let page =
+            LogseqMarkdownParser::parse_content("\u{feff}- First", page_id, "Test".to_string()).unwrap();
+
+        assert_eq!(page.root_blocks()[0].content().as_str(), "First");
+    }
+
     #[test]
     fn test_extract_urls() {
         let content = "Check out https://example.com and http://test.org for more info.";
@@ -0,0 +1,103 @@
+/// Logseq markdown writer - renders Page aggregates back into .md files
+use crate::domain::aggregates::Page;
+use crate::domain::entities::Block;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Writer producing markdown that [`LogseqMarkdownParser`](super::LogseqMarkdownParser)
+/// parses back into the same block tree
+pub struct LogseqMarkdownWriter;
+
+impl LogseqMarkdownWriter {
+    /// Render a page as Logseq outline markdown (tab-indented `- ` bullets)
+    pub fn render(page: &Page) -> String {
+        let mut output = String::new();
+        for block in page.root_blocks() {
+            Self::render_block(page, block, 0, &mut output);
+        }
+        output
+    }
+
+    /// Write a page to `path`, replacing the file atomically
+    ///
+    /// The content goes to a temporary sibling first and is renamed over the
+    /// target, so readers (and the file watcher) never see a half-written file.
+    #[cfg(feature = "fs")]
+    pub async fn write_file(page: &Page, path: &Path) -> std::io::Result<()> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid filename"))?;
+        // Matches the default `*.tmp` ignore rule, so the watcher skips it
+        let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+        tokio::fs::write(&temp_path, Self::render(page)).await?;
+        if let Err(e) = tokio::fs::rename(&temp_path, path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn render_block(page: &Page, block: &Block, depth: usize, output: &mut String) {
+        for _ in 0..depth {
+            output.push('\t');
+        }
+        output.push_str("- ");
+        output.push_str(block.content().as_str());
+        output.push('\n');
+
+        for child_id in block.child_ids() {
+            if let Some(child) = page.get_block(child_id) {
+                Self::render_block(page, child, depth + 1, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    fn outline(page: &Page) -> Vec<(usize, String)> {
+        fn visit(page: &Page, block: &Block, depth: usize, out: &mut Vec<(usize, String)>) {
+            out.push((depth, block.content().as_str().to_string()));
+            for child_id in block.child_ids() {
+                visit(page, page.get_block(child_id).unwrap(), depth + 1, out);
+            }
+        }
+        let mut out = Vec::new();
+        for block in page.root_blocks() {
+            visit(page, block, 0, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn test_render_uses_tab_indented_bullets() {
+        let content = "- First\n  - Nested [[link]]\n    - Deeper\n- Second #tag";
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("p").unwrap(), "P".into())
+            .unwrap();
+
+        assert_eq!(
+            LogseqMarkdownWriter::render(&page),
+            "- First\n\t- Nested [[link]]\n\t\t- Deeper\n- Second #tag\n"
+        );
+    }
+
+    #[test]
+    fn test_render_round_trips_through_parser() {
+        let content = "- One\n\t- Two\n\t\t- Three\n\t- Four https://example.com\n- Five";
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("p").unwrap(), "P".into())
+            .unwrap();
+
+        let rendered = LogseqMarkdownWriter::render(&page);
+        let reparsed =
+            LogseqMarkdownParser::parse_content(&rendered, PageId::new("p").unwrap(), "P".into())
+                .unwrap();
+
+        assert_eq!(outline(&reparsed), outline(&page));
+    }
+}
@@ -1,3 +1,10 @@
+mod edn;
+pub mod html_writer;
+pub mod logseq_export;
 pub mod logseq_markdown;
+pub mod markdown_writer;
 
+pub use html_writer::HtmlWriter;
+pub use logseq_export::LogseqExportParser;
 pub use logseq_markdown::{LogseqMarkdownParser, ParseError, ParseResult};
+pub use markdown_writer::LogseqMarkdownWriter;
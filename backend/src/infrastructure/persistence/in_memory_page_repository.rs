@@ -0,0 +1,107 @@
+/// Page repository kept in memory
+use crate::application::dto::PageSummary;
+use crate::application::repositories::PageRepository;
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Pages held in memory, for tools that load a graph for a single run
+///
+/// Clones share the same pages, so one clone can be handed to a service that
+/// takes ownership of its repository (e.g. [`ImportService`](crate::application::ImportService))
+/// while another is used to read what it saved.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryPageRepository {
+    pages: Arc<RwLock<HashMap<PageId, Page>>>,
+}
+
+impl InMemoryPageRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pages held
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<PageId, Page>> {
+        self.pages.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<PageId, Page>> {
+        self.pages.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl PageRepository for InMemoryPageRepository {
+    fn save(&mut self, page: Page) -> DomainResult<()> {
+        self.write().insert(page.id().clone(), page);
+        Ok(())
+    }
+
+    fn save_all(&mut self, pages: Vec<Page>) -> DomainResult<()> {
+        let mut stored = self.write();
+        for page in pages {
+            stored.insert(page.id().clone(), page);
+        }
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+        Ok(self.read().get(id).cloned())
+    }
+
+    fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+        Ok(self.read().values().find(|page| page.title() == title).cloned())
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<Page>> {
+        Ok(self.read().values().cloned().collect())
+    }
+
+    fn find_summaries(&self) -> DomainResult<Vec<PageSummary>> {
+        Ok(self.read().values().map(PageSummary::from).collect())
+    }
+
+    fn find_by_file_path(&self, file_path: &Path) -> DomainResult<Option<Page>> {
+        Ok(self.read().values().find(|page| page.file_path() == Some(file_path)).cloned())
+    }
+
+    fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+        Ok(self.write().remove(id).is_some())
+    }
+
+    fn delete_all(&mut self, ids: &[PageId]) -> DomainResult<usize> {
+        let mut stored = self.write();
+        Ok(ids.iter().filter(|id| stored.remove(*id).is_some()).count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_pages() {
+        let repository = InMemoryPageRepository::new();
+        let mut writer = repository.clone();
+
+        let page_id = PageId::new("rust").unwrap();
+        writer.save(Page::new(page_id.clone(), "Rust".to_string())).unwrap();
+
+        assert_eq!(repository.len(), 1);
+        assert_eq!(repository.find_by_title("Rust").unwrap().unwrap().id(), &page_id);
+
+        assert!(writer.delete(&page_id).unwrap());
+        assert!(repository.is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+/// SQLite-backed persistence, and an in-memory page store
+mod in_memory_page_repository;
+mod sqlite_chunk_repository;
+mod sqlite_import_checkpoints;
+mod sqlite_job_queue;
+mod sqlite_link_graph;
+mod sqlite_page_history;
+mod sqlite_page_timestamps;
+mod sqlite_url_checks;
+mod sqlite_url_metadata;
+mod sqlite_web_archives;
+
+pub use in_memory_page_repository::InMemoryPageRepository;
+pub use sqlite_chunk_repository::SqliteChunkRepository;
+pub use sqlite_import_checkpoints::SqliteImportCheckpointRepository;
+pub use sqlite_job_queue::SqliteEmbeddingJobRepository;
+pub use sqlite_link_graph::SqliteLinkGraphRepository;
+pub use sqlite_page_history::SqlitePageHistoryRepository;
+pub use sqlite_page_timestamps::SqlitePageTimestampRepository;
+pub use sqlite_url_checks::SqliteUrlCheckRepository;
+pub use sqlite_url_metadata::SqliteUrlMetadataRepository;
+pub use sqlite_web_archives::SqliteWebArchiveRepository;
+
+use crate::domain::base::{DomainError, DomainResult, RepositoryError};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Tables of `expected` that the SQLite database at `path` lacks
+///
+/// The database is opened read-only, so a missing file is an error rather
+/// than being created.
+pub fn missing_tables(path: &Path, expected: &[&str]) -> DomainResult<Vec<String>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_error)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .map_err(sqlite_error)?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(sqlite_error)?
+        .collect::<Result<HashSet<String>, _>>()
+        .map_err(sqlite_error)?;
+    Ok(expected
+        .iter()
+        .filter(|table| !tables.contains(**table))
+        .map(|table| table.to_string())
+        .collect())
+}
+
+fn sqlite_error(e: rusqlite::Error) -> DomainError {
+    RepositoryError::Storage {
+        backend: "SQLite",
+        source: Box::new(e),
+    }
+    .into()
+}
+
+/// A stored value that doesn't read back as what was written
+fn invalid_data(message: String) -> DomainError {
+    RepositoryError::InvalidData(message).into()
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
@@ -0,0 +1,216 @@
+/// SQLite implementation of the local chunk index
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row, Transaction};
+use std::path::Path;
+
+use super::sqlite_error;
+use crate::application::dto::ChunkRecord;
+use crate::application::repositories::ChunkRepository;
+use crate::domain::{
+    value_objects::{BlockId, PageId},
+    DomainResult,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS chunks (
+        chunk_id TEXT PRIMARY KEY,
+        block_id TEXT NOT NULL,
+        page_id TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        model TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_chunks_page_id ON chunks (page_id);
+";
+
+/// Chunk index stored in a SQLite `chunks` table
+pub struct SqliteChunkRepository {
+    conn: Connection,
+}
+
+impl SqliteChunkRepository {
+    /// Open (or create) the chunk index database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create an index that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteChunkRepository { conn })
+    }
+}
+
+impl ChunkRepository for SqliteChunkRepository {
+    fn upsert(&mut self, chunks: &[ChunkRecord]) -> DomainResult<()> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        insert_chunks(&tx, chunks)?;
+        tx.commit().map_err(sqlite_error)
+    }
+
+    fn find_by_page(&self, page_id: &PageId) -> DomainResult<Vec<ChunkRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT chunk_id, block_id, page_id, hash, model, created_at
+                 FROM chunks WHERE page_id = ?1 ORDER BY chunk_id",
+            )
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map(params![page_id.as_str()], read_chunk)
+            .map_err(sqlite_error)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(sqlite_error)
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<ChunkRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT chunk_id, block_id, page_id, hash, model, created_at FROM chunks ORDER BY chunk_id")
+            .map_err(sqlite_error)?;
+        let rows = stmt.query_map([], read_chunk).map_err(sqlite_error)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(sqlite_error)
+    }
+
+    fn replace_all(&mut self, chunks: &[ChunkRecord]) -> DomainResult<()> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        tx.execute("DELETE FROM chunks", []).map_err(sqlite_error)?;
+        insert_chunks(&tx, chunks)?;
+        tx.commit().map_err(sqlite_error)
+    }
+
+    fn delete(&mut self, chunk_ids: &[String]) -> DomainResult<usize> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let mut deleted = 0;
+        for chunk_id in chunk_ids {
+            deleted += tx
+                .execute("DELETE FROM chunks WHERE chunk_id = ?1", params![chunk_id])
+                .map_err(sqlite_error)?;
+        }
+        tx.commit().map_err(sqlite_error)?;
+        Ok(deleted)
+    }
+
+    fn delete_by_page(&mut self, page_id: &PageId) -> DomainResult<usize> {
+        self.conn
+            .execute("DELETE FROM chunks WHERE page_id = ?1", params![page_id.as_str()])
+            .map_err(sqlite_error)
+    }
+
+    fn delete_by_block(&mut self, block_id: &BlockId) -> DomainResult<usize> {
+        self.conn
+            .execute("DELETE FROM chunks WHERE block_id = ?1", params![block_id.as_str()])
+            .map_err(sqlite_error)
+    }
+
+    fn count(&self) -> DomainResult<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get::<_, i64>(0))
+            .map(|count| count as usize)
+            .map_err(sqlite_error)
+    }
+}
+
+/// Insert or replace `chunks` within the transaction `tx`
+fn insert_chunks(tx: &Transaction<'_>, chunks: &[ChunkRecord]) -> DomainResult<()> {
+    let mut stmt = tx
+        .prepare(
+            "INSERT OR REPLACE INTO chunks
+             (chunk_id, block_id, page_id, hash, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .map_err(sqlite_error)?;
+    for chunk in chunks {
+        stmt.execute(params![
+            chunk.chunk_id,
+            chunk.block_id,
+            chunk.page_id,
+            chunk.content_hash,
+            chunk.model,
+            chunk.created_at.to_rfc3339(),
+        ])
+        .map_err(sqlite_error)?;
+    }
+    Ok(())
+}
+
+fn read_chunk(row: &Row<'_>) -> rusqlite::Result<ChunkRecord> {
+    let created_at: String = row.get(5)?;
+    Ok(ChunkRecord {
+        chunk_id: row.get(0)?,
+        block_id: row.get(1)?,
+        page_id: row.get(2)?,
+        content_hash: row.get(3)?,
+        model: row.get(4)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+            })?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(chunk_id: &str, page_id: &str, hash: &str) -> ChunkRecord {
+        ChunkRecord {
+            chunk_id: chunk_id.to_string(),
+            block_id: format!("{}-block", chunk_id),
+            page_id: page_id.to_string(),
+            content_hash: hash.to_string(),
+            model: "test-model".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_find_by_page() {
+        let mut repo = SqliteChunkRepository::open_in_memory().unwrap();
+        repo.upsert(&[record("a", "page-1", "h1"), record("b", "page-2", "h2")])
+            .unwrap();
+        // Re-embedding replaces the record
+        repo.upsert(&[record("a", "page-1", "h1-new")]).unwrap();
+
+        let chunks = repo.find_by_page(&PageId::new("page-1").unwrap()).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content_hash, "h1-new");
+        assert_eq!(repo.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut repo = SqliteChunkRepository::open_in_memory().unwrap();
+        repo.upsert(&[
+            record("a", "page-1", "h1"),
+            record("b", "page-1", "h2"),
+            record("c", "page-2", "h3"),
+        ])
+        .unwrap();
+
+        assert_eq!(repo.delete(&["a".to_string(), "missing".to_string()]).unwrap(), 1);
+        assert_eq!(repo.delete_by_page(&PageId::new("page-1").unwrap()).unwrap(), 1);
+        assert_eq!(repo.delete_by_block(&BlockId::new("c-block").unwrap()).unwrap(), 1);
+        assert_eq!(repo.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let mut repo = SqliteChunkRepository::open_in_memory().unwrap();
+        repo.upsert(&[record("a", "page-1", "h1"), record("b", "page-2", "h2")])
+            .unwrap();
+
+        repo.replace_all(&[record("c", "page-1", "h3")]).unwrap();
+        let chunks = repo.find_all().unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].chunk_id, "c");
+    }
+}
@@ -0,0 +1,150 @@
+/// SQLite implementation of import checkpoints
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{now, sqlite_error};
+use crate::application::repositories::ImportCheckpointRepository;
+use crate::domain::DomainResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS import_checkpoints (
+        graph_root TEXT NOT NULL,
+        file_path TEXT NOT NULL,
+        content_hash TEXT NOT NULL,
+        imported_at TEXT NOT NULL,
+        PRIMARY KEY (graph_root, file_path)
+    );
+";
+
+/// Import checkpoints stored in a SQLite table
+pub struct SqliteImportCheckpointRepository {
+    conn: Connection,
+}
+
+impl SqliteImportCheckpointRepository {
+    /// Open (or create) the checkpoint database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a checkpoint store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteImportCheckpointRepository { conn })
+    }
+}
+
+impl ImportCheckpointRepository for SqliteImportCheckpointRepository {
+    fn mark_imported(&mut self, graph_root: &Path, files: &[(PathBuf, String)]) -> DomainResult<()> {
+        let graph_root = graph_root.to_string_lossy();
+        let timestamp = now();
+
+        // One transaction per batch keeps checkpointing cheap on large imports
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        for (file_path, content_hash) in files {
+            tx.execute(
+                "INSERT OR REPLACE INTO import_checkpoints
+                 (graph_root, file_path, content_hash, imported_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![graph_root, file_path.to_string_lossy(), content_hash, timestamp],
+            )
+            .map_err(sqlite_error)?;
+        }
+        tx.commit().map_err(sqlite_error)
+    }
+
+    fn imported_files(&self, graph_root: &Path) -> DomainResult<HashMap<PathBuf, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT file_path, content_hash FROM import_checkpoints WHERE graph_root = ?1")
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map(params![graph_root.to_string_lossy()], |row| {
+                Ok((PathBuf::from(row.get::<_, String>(0)?), row.get::<_, String>(1)?))
+            })
+            .map_err(sqlite_error)?;
+
+        rows.collect::<rusqlite::Result<_>>().map_err(sqlite_error)
+    }
+
+    fn forget(&mut self, graph_root: &Path, files: &[PathBuf]) -> DomainResult<usize> {
+        let graph_root = graph_root.to_string_lossy();
+
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let mut removed = 0;
+        for file_path in files {
+            removed += tx
+                .execute(
+                    "DELETE FROM import_checkpoints WHERE graph_root = ?1 AND file_path = ?2",
+                    params![graph_root, file_path.to_string_lossy()],
+                )
+                .map_err(sqlite_error)?;
+        }
+        tx.commit().map_err(sqlite_error)?;
+        Ok(removed)
+    }
+
+    fn clear(&mut self, graph_root: &Path) -> DomainResult<usize> {
+        self.conn
+            .execute(
+                "DELETE FROM import_checkpoints WHERE graph_root = ?1",
+                params![graph_root.to_string_lossy()],
+            )
+            .map_err(sqlite_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(path: &str, hash: &str) -> (PathBuf, String) {
+        (PathBuf::from(path), hash.to_string())
+    }
+
+    #[test]
+    fn test_checkpoints_are_kept_per_graph() {
+        let mut repo = SqliteImportCheckpointRepository::open_in_memory().unwrap();
+        let work = Path::new("/graphs/work");
+        let personal = Path::new("/graphs/personal");
+
+        repo.mark_imported(work, &[checkpoint("/graphs/work/pages/a.md", "h1")]).unwrap();
+        repo.mark_imported(personal, &[checkpoint("/graphs/personal/pages/b.md", "h2")])
+            .unwrap();
+        // Re-importing a file replaces its hash
+        repo.mark_imported(work, &[checkpoint("/graphs/work/pages/a.md", "h3")]).unwrap();
+
+        let imported = repo.imported_files(work).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[Path::new("/graphs/work/pages/a.md")], "h3");
+
+        repo.mark_imported(work, &[checkpoint("/graphs/work/pages/c.md", "h4")]).unwrap();
+        assert_eq!(repo.forget(work, &[PathBuf::from("/graphs/work/pages/c.md")]).unwrap(), 1);
+
+        assert_eq!(repo.clear(work).unwrap(), 1);
+        assert!(repo.imported_files(work).unwrap().is_empty());
+        assert_eq!(repo.imported_files(personal).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_checkpoints_survive_reopening() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("checkpoints.db");
+        let graph = Path::new("/graph");
+
+        {
+            let mut repo = SqliteImportCheckpointRepository::open(&db_path).unwrap();
+            repo.mark_imported(graph, &[checkpoint("/graph/pages/a.md", "h1")]).unwrap();
+        }
+
+        let repo = SqliteImportCheckpointRepository::open(&db_path).unwrap();
+        assert_eq!(repo.imported_files(graph).unwrap().len(), 1);
+    }
+}
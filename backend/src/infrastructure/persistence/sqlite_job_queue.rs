@@ -0,0 +1,362 @@
+/// SQLite implementation of the durable embedding job queue
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+
+use crate::application::dto::{
+    EmbeddingJob, EmbeddingJobKind, EmbeddingJobStatus, EmbeddingQueueStats,
+};
+use super::{invalid_data, now, sqlite_error};
+use crate::application::repositories::EmbeddingJobRepository;
+use crate::domain::{base::DomainError, value_objects::PageId, DomainResult};
+
+pub(super) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS embedding_jobs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind TEXT NOT NULL,
+        page_id TEXT NOT NULL,
+        status TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_embedding_jobs_status ON embedding_jobs (status, id);
+";
+
+const JOB_COLUMNS: &str = "id, kind, page_id, status, attempts, last_error";
+
+/// Embedding job queue stored in a SQLite table
+pub struct SqliteEmbeddingJobRepository {
+    conn: Connection,
+}
+
+impl SqliteEmbeddingJobRepository {
+    /// Open (or create) the queue database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a queue that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteEmbeddingJobRepository { conn })
+    }
+
+    fn set_status(&self, id: i64, status: EmbeddingJobStatus) -> DomainResult<()> {
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE embedding_jobs SET status = ?1, updated_at = ?2 WHERE id = ?3",
+                params![status.as_str(), now(), id],
+            )
+            .map_err(sqlite_error)?;
+
+        if updated == 0 {
+            return Err(DomainError::NotFound(format!("Embedding job {}", id)));
+        }
+        Ok(())
+    }
+}
+
+impl EmbeddingJobRepository for SqliteEmbeddingJobRepository {
+    fn enqueue(&mut self, kind: EmbeddingJobKind, page_id: &PageId) -> DomainResult<i64> {
+        enqueue(&self.conn, kind, page_id)
+    }
+
+    fn claim_next(&mut self) -> DomainResult<Option<EmbeddingJob>> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+
+        let job = tx
+            .query_row(
+                &format!(
+                    "SELECT {} FROM embedding_jobs WHERE status = ?1 ORDER BY id LIMIT 1",
+                    JOB_COLUMNS
+                ),
+                params![EmbeddingJobStatus::Pending.as_str()],
+                read_job,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .transpose()?;
+
+        let Some(mut job) = job else {
+            return Ok(None);
+        };
+
+        tx.execute(
+            "UPDATE embedding_jobs SET status = ?1, attempts = attempts + 1, updated_at = ?2
+             WHERE id = ?3",
+            params![EmbeddingJobStatus::Running.as_str(), now(), job.id],
+        )
+        .map_err(sqlite_error)?;
+        tx.commit().map_err(sqlite_error)?;
+
+        job.status = EmbeddingJobStatus::Running;
+        job.attempts += 1;
+        Ok(Some(job))
+    }
+
+    fn complete(&mut self, id: i64) -> DomainResult<()> {
+        self.set_status(id, EmbeddingJobStatus::Completed)
+    }
+
+    fn fail(&mut self, id: i64, error: &str, max_attempts: u32) -> DomainResult<()> {
+        let attempts: u32 = self
+            .conn
+            .query_row(
+                "SELECT attempts FROM embedding_jobs WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .ok_or_else(|| DomainError::NotFound(format!("Embedding job {}", id)))?;
+
+        let status = if attempts >= max_attempts {
+            EmbeddingJobStatus::Failed
+        } else {
+            EmbeddingJobStatus::Pending
+        };
+
+        self.conn
+            .execute(
+                "UPDATE embedding_jobs SET status = ?1, last_error = ?2, updated_at = ?3
+                 WHERE id = ?4",
+                params![status.as_str(), error, now(), id],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn retry_failed(&mut self) -> DomainResult<usize> {
+        self.conn
+            .execute(
+                "UPDATE embedding_jobs SET status = ?1, attempts = 0, updated_at = ?2
+                 WHERE status = ?3",
+                params![
+                    EmbeddingJobStatus::Pending.as_str(),
+                    now(),
+                    EmbeddingJobStatus::Failed.as_str()
+                ],
+            )
+            .map_err(sqlite_error)
+    }
+
+    fn recover_running(&mut self) -> DomainResult<usize> {
+        self.conn
+            .execute(
+                "UPDATE embedding_jobs SET status = ?1, updated_at = ?2 WHERE status = ?3",
+                params![
+                    EmbeddingJobStatus::Pending.as_str(),
+                    now(),
+                    EmbeddingJobStatus::Running.as_str()
+                ],
+            )
+            .map_err(sqlite_error)
+    }
+
+    fn find_by_id(&self, id: i64) -> DomainResult<Option<EmbeddingJob>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM embedding_jobs WHERE id = ?1", JOB_COLUMNS),
+                params![id],
+                read_job,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .transpose()
+    }
+
+    fn stats(&self) -> DomainResult<EmbeddingQueueStats> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT status, COUNT(*) FROM embedding_jobs GROUP BY status")
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))
+            .map_err(sqlite_error)?;
+
+        let mut stats = EmbeddingQueueStats::default();
+        for row in rows {
+            let (status, count) = row.map_err(sqlite_error)?;
+            match EmbeddingJobStatus::parse(&status) {
+                Some(EmbeddingJobStatus::Pending) => stats.pending = count,
+                Some(EmbeddingJobStatus::Running) => stats.running = count,
+                Some(EmbeddingJobStatus::Completed) => stats.completed = count,
+                Some(EmbeddingJobStatus::Failed) => stats.failed = count,
+                None => {}
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Queue a job on `conn`, or return the pending job doing the same; within
+/// a transaction, the job is only queued if the transaction commits
+///
+/// A pending job of the other kind for the page is dropped: the latest
+/// intent wins, so embed, delete, embed leaves the page embedded.
+pub(super) fn enqueue(conn: &Connection, kind: EmbeddingJobKind, page_id: &PageId) -> DomainResult<i64> {
+    conn.execute(
+        "DELETE FROM embedding_jobs WHERE kind != ?1 AND page_id = ?2 AND status = ?3",
+        params![kind.as_str(), page_id.as_str(), EmbeddingJobStatus::Pending.as_str()],
+    )
+    .map_err(sqlite_error)?;
+
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM embedding_jobs
+             WHERE kind = ?1 AND page_id = ?2 AND status = ?3",
+            params![kind.as_str(), page_id.as_str(), EmbeddingJobStatus::Pending.as_str()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sqlite_error)?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let timestamp = now();
+    conn.execute(
+        "INSERT INTO embedding_jobs (kind, page_id, status, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?4)",
+        params![
+            kind.as_str(),
+            page_id.as_str(),
+            EmbeddingJobStatus::Pending.as_str(),
+            timestamp
+        ],
+    )
+    .map_err(sqlite_error)?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Map a row to a job; the outer result is SQLite's, the inner one ours
+fn read_job(row: &Row<'_>) -> rusqlite::Result<DomainResult<EmbeddingJob>> {
+    let id: i64 = row.get(0)?;
+    let kind: String = row.get(1)?;
+    let page_id: String = row.get(2)?;
+    let status: String = row.get(3)?;
+    let attempts: u32 = row.get(4)?;
+    let last_error: Option<String> = row.get(5)?;
+
+    Ok((|| {
+        Ok(EmbeddingJob {
+            id,
+            kind: EmbeddingJobKind::parse(&kind).ok_or_else(|| {
+                invalid_data(format!("Unknown embedding job kind: {}", kind))
+            })?,
+            page_id: PageId::new(page_id)?,
+            status: EmbeddingJobStatus::parse(&status).ok_or_else(|| {
+                invalid_data(format!("Unknown embedding job status: {}", status))
+            })?,
+            attempts,
+            last_error,
+        })
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str) -> PageId {
+        PageId::new(id).unwrap()
+    }
+
+    #[test]
+    fn test_enqueue_and_claim_in_order() {
+        let mut queue = SqliteEmbeddingJobRepository::open_in_memory().unwrap();
+        let first = queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+        queue.enqueue(EmbeddingJobKind::Delete, &page("b")).unwrap();
+
+        let job = queue.claim_next().unwrap().unwrap();
+        assert_eq!(job.id, first);
+        assert_eq!(job.kind, EmbeddingJobKind::Embed);
+        assert_eq!(job.status, EmbeddingJobStatus::Running);
+        assert_eq!(job.attempts, 1);
+
+        let stats = queue.stats().unwrap();
+        assert_eq!(stats.pending, 1);
+        assert_eq!(stats.running, 1);
+    }
+
+    #[test]
+    fn test_enqueue_deduplicates_pending_jobs() {
+        let mut queue = SqliteEmbeddingJobRepository::open_in_memory().unwrap();
+        let first = queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+        let second = queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+        assert_eq!(first, second);
+
+        let delete = queue.enqueue(EmbeddingJobKind::Delete, &page("a")).unwrap();
+        assert_ne!(first, delete);
+    }
+
+    #[test]
+    fn test_enqueue_supersedes_pending_jobs_of_the_other_kind() {
+        let mut queue = SqliteEmbeddingJobRepository::open_in_memory().unwrap();
+        queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+        queue.enqueue(EmbeddingJobKind::Delete, &page("a")).unwrap();
+        queue.enqueue(EmbeddingJobKind::Embed, &page("b")).unwrap();
+        queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+
+        let jobs: Vec<(String, EmbeddingJobKind)> = std::iter::from_fn(|| queue.claim_next().unwrap())
+            .map(|job| (job.page_id.as_str().to_string(), job.kind))
+            .collect();
+        assert_eq!(
+            jobs,
+            [("b".to_string(), EmbeddingJobKind::Embed), ("a".to_string(), EmbeddingJobKind::Embed)]
+        );
+    }
+
+    #[test]
+    fn test_fail_retries_until_max_attempts() {
+        let mut queue = SqliteEmbeddingJobRepository::open_in_memory().unwrap();
+        let id = queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+
+        queue.claim_next().unwrap();
+        queue.fail(id, "qdrant unavailable", 2).unwrap();
+        let job = queue.find_by_id(id).unwrap().unwrap();
+        assert_eq!(job.status, EmbeddingJobStatus::Pending);
+        assert_eq!(job.last_error.as_deref(), Some("qdrant unavailable"));
+
+        queue.claim_next().unwrap();
+        queue.fail(id, "qdrant unavailable", 2).unwrap();
+        assert_eq!(
+            queue.find_by_id(id).unwrap().unwrap().status,
+            EmbeddingJobStatus::Failed
+        );
+        assert!(queue.claim_next().unwrap().is_none());
+
+        assert_eq!(queue.retry_failed().unwrap(), 1);
+        assert_eq!(queue.claim_next().unwrap().unwrap().attempts, 1);
+    }
+
+    #[test]
+    fn test_jobs_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.sqlite");
+
+        {
+            let mut queue = SqliteEmbeddingJobRepository::open(&path).unwrap();
+            queue.enqueue(EmbeddingJobKind::Embed, &page("a")).unwrap();
+            queue.enqueue(EmbeddingJobKind::Embed, &page("b")).unwrap();
+            // Simulate a crash mid-job
+            queue.claim_next().unwrap();
+        }
+
+        let mut queue = SqliteEmbeddingJobRepository::open(&path).unwrap();
+        assert_eq!(queue.recover_running().unwrap(), 1);
+        let stats = queue.stats().unwrap();
+        assert_eq!(stats.pending, 2);
+        assert!(stats.has_outstanding_work());
+    }
+}
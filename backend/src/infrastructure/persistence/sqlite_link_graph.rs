@@ -0,0 +1,188 @@
+/// SQLite implementation of link graph storage
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use super::{invalid_data, now, sqlite_error};
+use crate::application::repositories::LinkGraphRepository;
+use crate::application::services::{LinkEdge, LinkGraph, LinkNode};
+use crate::domain::value_objects::{GraphId, PageId};
+use crate::domain::DomainResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS link_graphs (
+        graph_id TEXT PRIMARY KEY,
+        saved_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS link_nodes (
+        graph_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        page_id TEXT,
+        PRIMARY KEY (graph_id, title)
+    );
+    CREATE TABLE IF NOT EXISTS link_edges (
+        graph_id TEXT NOT NULL,
+        source TEXT NOT NULL,
+        target TEXT NOT NULL,
+        edge_type TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        PRIMARY KEY (graph_id, source, target, edge_type)
+    );
+";
+
+/// Link graphs stored in SQLite `link_graphs`, `link_nodes` and `link_edges` tables
+pub struct SqliteLinkGraphRepository {
+    conn: Connection,
+}
+
+impl SqliteLinkGraphRepository {
+    /// Open (or create) the link graph database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteLinkGraphRepository { conn })
+    }
+}
+
+fn delete_graph(conn: &Connection, graph_id: &str) -> rusqlite::Result<usize> {
+    conn.execute("DELETE FROM link_edges WHERE graph_id = ?1", params![graph_id])?;
+    conn.execute("DELETE FROM link_nodes WHERE graph_id = ?1", params![graph_id])?;
+    conn.execute("DELETE FROM link_graphs WHERE graph_id = ?1", params![graph_id])
+}
+
+impl LinkGraphRepository for SqliteLinkGraphRepository {
+    fn save(&mut self, graph_id: &GraphId, graph: &LinkGraph) -> DomainResult<()> {
+        let graph_id = graph_id.as_str();
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        delete_graph(&tx, graph_id).map_err(sqlite_error)?;
+        tx.execute(
+            "INSERT INTO link_graphs (graph_id, saved_at) VALUES (?1, ?2)",
+            params![graph_id, now()],
+        )
+        .map_err(sqlite_error)?;
+        {
+            let mut stmt = tx
+                .prepare("INSERT INTO link_nodes (graph_id, title, page_id) VALUES (?1, ?2, ?3)")
+                .map_err(sqlite_error)?;
+            for node in graph.nodes() {
+                let page_id = node.page_id.as_ref().map(PageId::as_str);
+                stmt.execute(params![graph_id, node.title, page_id]).map_err(sqlite_error)?;
+            }
+
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO link_edges (graph_id, source, target, edge_type, count)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(sqlite_error)?;
+            for edge in graph.edges() {
+                stmt.execute(params![graph_id, edge.source, edge.target, edge.edge_type.as_str(), edge.count as i64])
+                    .map_err(sqlite_error)?;
+            }
+        }
+        tx.commit().map_err(sqlite_error)
+    }
+
+    fn load(&self, graph_id: &GraphId) -> DomainResult<Option<LinkGraph>> {
+        let graph_id = graph_id.as_str();
+        let saved: bool = self
+            .conn
+            .query_row("SELECT EXISTS(SELECT 1 FROM link_graphs WHERE graph_id = ?1)", params![graph_id], |row| {
+                row.get(0)
+            })
+            .map_err(sqlite_error)?;
+        if !saved {
+            return Ok(None);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT title, page_id FROM link_nodes WHERE graph_id = ?1")
+            .map_err(sqlite_error)?;
+        let nodes = stmt
+            .query_map(params![graph_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))
+            .map_err(sqlite_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error)?
+            .into_iter()
+            .map(|(title, page_id)| {
+                Ok(LinkNode {
+                    title,
+                    page_id: page_id.map(PageId::new).transpose()?,
+                })
+            })
+            .collect::<DomainResult<Vec<_>>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source, target, edge_type, count FROM link_edges WHERE graph_id = ?1")
+            .map_err(sqlite_error)?;
+        let edges = stmt
+            .query_map(params![graph_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?))
+            })
+            .map_err(sqlite_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error)?
+            .into_iter()
+            .map(|(source, target, edge_type, count)| {
+                Ok(LinkEdge {
+                    source,
+                    target,
+                    edge_type: edge_type.parse().map_err(invalid_data)?,
+                    count: count as usize,
+                })
+            })
+            .collect::<DomainResult<Vec<_>>>()?;
+
+        Ok(Some(LinkGraph::from_parts(nodes, edges)))
+    }
+
+    fn delete(&mut self, graph_id: &GraphId) -> DomainResult<bool> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let deleted = delete_graph(&tx, graph_id.as_str()).map_err(sqlite_error)?;
+        tx.commit().map_err(sqlite_error)?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    #[test]
+    fn test_link_graphs_round_trip_per_graph() {
+        let pages: Vec<_> = [("rust", "Rust", "- Tagged #lang"), ("notes", "Notes", "- About [[Rust]]")]
+            .into_iter()
+            .map(|(id, title, content)| {
+                LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap()
+            })
+            .collect();
+        let graph = LinkGraph::build(&pages);
+        let work = GraphId::new("work").unwrap();
+        let personal = GraphId::new("personal").unwrap();
+
+        let mut repo = SqliteLinkGraphRepository::open_in_memory().unwrap();
+        assert_eq!(repo.load(&work).unwrap(), None);
+        repo.save(&work, &graph).unwrap();
+        repo.save(&personal, &LinkGraph::default()).unwrap();
+        // Saving again replaces the graph rather than adding to it
+        repo.save(&work, &graph).unwrap();
+
+        assert_eq!(repo.load(&work).unwrap(), Some(graph));
+        assert_eq!(repo.load(&personal).unwrap(), Some(LinkGraph::default()));
+        assert!(repo.delete(&work).unwrap());
+        assert!(!repo.delete(&work).unwrap());
+        assert_eq!(repo.load(&work).unwrap(), None);
+    }
+}
@@ -0,0 +1,195 @@
+/// SQLite implementation of page version history
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{invalid_data, now, sqlite_error};
+use crate::application::dto::{PageSnapshot, PageVersion};
+use crate::application::repositories::PageHistoryRepository;
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainResult;
+
+/// Snapshots are stored once per content hash, however many versions (of
+/// however many pages) have that content
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS page_snapshots (
+        content_hash TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        properties TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS page_versions (
+        page_id TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        content_hash TEXT NOT NULL REFERENCES page_snapshots (content_hash),
+        recorded_at TEXT NOT NULL,
+        PRIMARY KEY (page_id, version)
+    );
+";
+
+const COLUMNS: &str = "v.page_id, v.version, v.recorded_at, s.content_hash, s.title, s.content, s.properties";
+
+const FROM: &str = "page_versions v JOIN page_snapshots s ON s.content_hash = v.content_hash";
+
+/// Page versions stored in SQLite `page_versions` and `page_snapshots` tables
+pub struct SqlitePageHistoryRepository {
+    conn: Connection,
+}
+
+impl SqlitePageHistoryRepository {
+    /// Open (or create) the page history database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqlitePageHistoryRepository { conn })
+    }
+
+    fn latest(&self, page_id: &PageId) -> DomainResult<Option<PageVersion>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM {} WHERE v.page_id = ?1 ORDER BY v.version DESC LIMIT 1", COLUMNS, FROM),
+                params![page_id.as_str()],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(to_version)
+            .transpose()
+    }
+}
+
+type VersionRow = (String, u32, String, String, String, String, String);
+
+fn read_row(row: &Row) -> rusqlite::Result<VersionRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+fn to_version(
+    (page_id, version, recorded_at, content_hash, title, content, properties): VersionRow,
+) -> DomainResult<PageVersion> {
+    let recorded_at = DateTime::parse_from_rfc3339(&recorded_at)
+        .map_err(|e| invalid_data(format!("Invalid version time '{}': {}", recorded_at, e)))?
+        .with_timezone(&Utc);
+    let properties: BTreeMap<String, String> = serde_json::from_str(&properties)
+        .map_err(|e| invalid_data(format!("Invalid properties of snapshot {}: {}", content_hash, e)))?;
+    Ok(PageVersion {
+        version,
+        recorded_at,
+        snapshot: PageSnapshot {
+            page_id: PageId::new(page_id)?,
+            title,
+            content,
+            properties,
+            content_hash,
+        },
+    })
+}
+
+impl PageHistoryRepository for SqlitePageHistoryRepository {
+    fn record(&mut self, snapshot: &PageSnapshot) -> DomainResult<PageVersion> {
+        if let Some(latest) = self.latest(&snapshot.page_id)? {
+            if latest.snapshot.content_hash == snapshot.content_hash {
+                return Ok(latest);
+            }
+        }
+
+        let properties = serde_json::to_string(&snapshot.properties)
+            .map_err(|e| invalid_data(format!("Cannot store page properties: {}", e)))?;
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO page_snapshots (content_hash, title, content, properties) VALUES (?1, ?2, ?3, ?4)",
+            params![snapshot.content_hash, snapshot.title, snapshot.content, properties],
+        )
+        .map_err(sqlite_error)?;
+        tx.execute(
+            "INSERT INTO page_versions (page_id, version, content_hash, recorded_at)
+             SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2, ?3 FROM page_versions WHERE page_id = ?1",
+            params![snapshot.page_id.as_str(), snapshot.content_hash, now()],
+        )
+        .map_err(sqlite_error)?;
+        tx.commit().map_err(sqlite_error)?;
+
+        self.latest(&snapshot.page_id)?
+            .ok_or_else(|| invalid_data(format!("Version of page {} wasn't stored", snapshot.page_id)))
+    }
+
+    fn versions(&self, page_id: &PageId) -> DomainResult<Vec<PageVersion>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM {} WHERE v.page_id = ?1 ORDER BY v.version", COLUMNS, FROM))
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map(params![page_id.as_str()], read_row)
+            .map_err(sqlite_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error)?;
+        rows.into_iter().map(to_version).collect()
+    }
+
+    fn version(&self, page_id: &PageId, version: u32) -> DomainResult<Option<PageVersion>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM {} WHERE v.page_id = ?1 AND v.version = ?2", COLUMNS, FROM),
+                params![page_id.as_str(), version],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(to_version)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+
+    fn snapshot(content: &str) -> PageSnapshot {
+        let page = LogseqMarkdownParser::parse_content(content, PageId::new("notes").unwrap(), "Notes".to_string()).unwrap();
+        PageSnapshot::from(&page)
+    }
+
+    #[test]
+    fn test_versions_are_numbered_and_deduplicated() {
+        let mut repo = SqlitePageHistoryRepository::open_in_memory().unwrap();
+        let page_id = PageId::new("notes").unwrap();
+
+        assert_eq!(repo.record(&snapshot("- First")).unwrap().version, 1);
+        assert_eq!(repo.record(&snapshot("- First")).unwrap().version, 1);
+        assert_eq!(repo.record(&snapshot("- Second")).unwrap().version, 2);
+        // Going back to earlier content is a new version sharing the stored snapshot
+        assert_eq!(repo.record(&snapshot("- First")).unwrap().version, 3);
+
+        let versions = repo.versions(&page_id).unwrap();
+        let contents: Vec<&str> = versions.iter().map(|version| version.snapshot.content.as_str()).collect();
+        assert_eq!(contents, vec!["- First\n", "- Second\n", "- First\n"]);
+        assert_eq!(repo.version(&page_id, 2).unwrap().unwrap().snapshot, snapshot("- Second"));
+        assert_eq!(repo.version(&page_id, 4).unwrap(), None);
+
+        let stored: i64 = repo
+            .conn
+            .query_row("SELECT COUNT(*) FROM page_snapshots", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, 2);
+    }
+}
@@ -0,0 +1,208 @@
+/// SQLite implementation of page timestamps
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+
+use super::{invalid_data, sqlite_error, sqlite_job_queue};
+use crate::application::dto::{EmbeddingJobKind, PageTimestamps};
+use crate::application::repositories::PageTimestampRepository;
+use crate::domain::{value_objects::PageId, DomainResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS page_timestamps (
+        page_id TEXT PRIMARY KEY,
+        content_hash TEXT,
+        indexed_at TEXT,
+        embedded_at TEXT
+    );
+";
+
+/// Page timestamps stored in a SQLite `page_timestamps` table
+///
+/// With an [embedding outbox](Self::with_embedding_outbox), recording that a
+/// page changed or is gone queues its embedding job in the same transaction.
+pub struct SqlitePageTimestampRepository {
+    conn: Connection,
+    outbox: bool,
+}
+
+impl SqlitePageTimestampRepository {
+    /// Open (or create) the timestamps database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqlitePageTimestampRepository { conn, outbox: false })
+    }
+
+    /// Queue an embed job for every page whose content changes, and a delete
+    /// job for every page forgotten, in the `embedding_jobs` table of the same
+    /// database (see [`SqliteEmbeddingJobRepository`](super::SqliteEmbeddingJobRepository))
+    ///
+    /// The job commits with the timestamp recording the change, so a process
+    /// dying in between can't leave a saved page that is never embedded.
+    pub fn with_embedding_outbox(mut self) -> DomainResult<Self> {
+        self.conn.execute_batch(sqlite_job_queue::SCHEMA).map_err(sqlite_error)?;
+        self.outbox = true;
+        Ok(self)
+    }
+}
+
+impl PageTimestampRepository for SqlitePageTimestampRepository {
+    fn mark_indexed(&mut self, page_id: &PageId, content_hash: &str, at: DateTime<Utc>) -> DomainResult<bool> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let changed = tx
+            .execute(
+                "INSERT INTO page_timestamps (page_id, content_hash, indexed_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (page_id) DO UPDATE SET content_hash = ?2, indexed_at = ?3
+                 WHERE content_hash IS NOT ?2",
+                params![page_id.as_str(), content_hash, at.to_rfc3339()],
+            )
+            .map_err(sqlite_error)?
+            > 0;
+        if changed && self.outbox {
+            sqlite_job_queue::enqueue(&tx, EmbeddingJobKind::Embed, page_id)?;
+        }
+        tx.commit().map_err(sqlite_error)?;
+        Ok(changed)
+    }
+
+    fn mark_embedded(&mut self, page_id: &PageId, at: DateTime<Utc>) -> DomainResult<()> {
+        self.conn
+            .execute(
+                "INSERT INTO page_timestamps (page_id, embedded_at) VALUES (?1, ?2)
+                 ON CONFLICT (page_id) DO UPDATE SET embedded_at = ?2",
+                params![page_id.as_str(), at.to_rfc3339()],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn find(&self, page_id: &PageId) -> DomainResult<Option<PageTimestamps>> {
+        self.conn
+            .query_row(
+                "SELECT page_id, indexed_at, embedded_at FROM page_timestamps WHERE page_id = ?1",
+                params![page_id.as_str()],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(into_timestamps)
+            .transpose()
+    }
+
+    fn find_all(&self) -> DomainResult<Vec<PageTimestamps>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT page_id, indexed_at, embedded_at FROM page_timestamps ORDER BY page_id")
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([], read_row)
+            .map_err(sqlite_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sqlite_error)?;
+        rows.into_iter().map(into_timestamps).collect()
+    }
+
+    fn forget(&mut self, page_ids: &[PageId]) -> DomainResult<usize> {
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let mut forgotten = 0;
+        for page_id in page_ids {
+            let deleted = tx
+                .execute("DELETE FROM page_timestamps WHERE page_id = ?1", params![page_id.as_str()])
+                .map_err(sqlite_error)?;
+            if deleted > 0 && self.outbox {
+                sqlite_job_queue::enqueue(&tx, EmbeddingJobKind::Delete, page_id)?;
+            }
+            forgotten += deleted;
+        }
+        tx.commit().map_err(sqlite_error)?;
+        Ok(forgotten)
+    }
+}
+
+type TimestampRow = (String, Option<String>, Option<String>);
+
+fn read_row(row: &Row<'_>) -> rusqlite::Result<TimestampRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+}
+
+fn into_timestamps((page_id, indexed_at, embedded_at): TimestampRow) -> DomainResult<PageTimestamps> {
+    let time = |value: Option<String>| {
+        value
+            .map(|value| {
+                DateTime::parse_from_rfc3339(&value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|e| invalid_data(format!("Invalid timestamp '{}' of page {}: {}", value, page_id, e)))
+            })
+            .transpose()
+    };
+    Ok(PageTimestamps {
+        indexed_at: time(indexed_at)?,
+        embedded_at: time(embedded_at)?,
+        page_id: PageId::new(page_id.clone())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::repositories::EmbeddingJobRepository;
+    use crate::infrastructure::persistence::SqliteEmbeddingJobRepository;
+    use chrono::Duration;
+
+    #[test]
+    fn test_indexed_time_moves_only_when_the_content_changes() {
+        let mut repo = SqlitePageTimestampRepository::open_in_memory().unwrap();
+        let page = PageId::new("rust").unwrap();
+        let start = Utc::now();
+
+        assert!(repo.mark_indexed(&page, "h1", start).unwrap());
+        repo.mark_embedded(&page, start + Duration::seconds(1)).unwrap();
+        assert!(!repo.mark_indexed(&page, "h1", start + Duration::seconds(2)).unwrap());
+        let timestamps = repo.find(&page).unwrap().unwrap();
+        assert_eq!(timestamps.indexed_at.unwrap().timestamp(), start.timestamp());
+        assert!(!timestamps.embeddings_out_of_date());
+        assert!(repo.find_out_of_date().unwrap().is_empty());
+
+        assert!(repo.mark_indexed(&page, "h2", start + Duration::seconds(3)).unwrap());
+        let out_of_date = repo.find_out_of_date().unwrap();
+        assert_eq!(out_of_date.len(), 1);
+        assert_eq!(out_of_date[0].page_id, page);
+
+        assert_eq!(repo.forget(std::slice::from_ref(&page)).unwrap(), 1);
+        assert_eq!(repo.find(&page).unwrap(), None);
+    }
+
+    #[test]
+    fn test_outbox_queues_the_jobs_of_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logjam.db");
+        let mut repo = SqlitePageTimestampRepository::open(&path).unwrap().with_embedding_outbox().unwrap();
+        let page = PageId::new("rust").unwrap();
+        let now = Utc::now();
+
+        let removed = PageId::new("go").unwrap();
+
+        repo.mark_indexed(&page, "h1", now).unwrap();
+        repo.mark_indexed(&page, "h1", now).unwrap();
+        repo.mark_indexed(&removed, "h1", now).unwrap();
+        repo.forget(&[page.clone(), removed.clone(), PageId::new("never-indexed").unwrap()]).unwrap();
+        // Indexed again after being forgotten: it must end up embedded
+        repo.mark_indexed(&page, "h2", now).unwrap();
+
+        let mut jobs = SqliteEmbeddingJobRepository::open(&path).unwrap();
+        let queued: Vec<(PageId, EmbeddingJobKind)> =
+            std::iter::from_fn(|| jobs.claim_next().unwrap()).map(|job| (job.page_id, job.kind)).collect();
+        assert_eq!(queued, [(removed, EmbeddingJobKind::Delete), (page, EmbeddingJobKind::Embed)]);
+    }
+}
@@ -0,0 +1,146 @@
+/// SQLite implementation of URL check storage
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+
+use super::{invalid_data, sqlite_error};
+use crate::application::repositories::UrlCheckRepository;
+use crate::application::services::UrlCheck;
+use crate::domain::DomainResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS url_checks (
+        url TEXT PRIMARY KEY,
+        status INTEGER,
+        redirected_to TEXT,
+        error TEXT,
+        checked_at TEXT NOT NULL
+    );
+";
+
+const COLUMNS: &str = "url, status, redirected_to, error, checked_at";
+
+/// URL checks stored in a SQLite `url_checks` table
+pub struct SqliteUrlCheckRepository {
+    conn: Connection,
+}
+
+impl SqliteUrlCheckRepository {
+    /// Open (or create) the URL check database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteUrlCheckRepository { conn })
+    }
+}
+
+type CheckRow = (String, Option<i64>, Option<String>, Option<String>, String);
+
+fn read_row(row: &Row) -> rusqlite::Result<CheckRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+}
+
+fn to_check((url, status, redirected_to, error, checked_at): CheckRow) -> DomainResult<UrlCheck> {
+    let checked_at = DateTime::parse_from_rfc3339(&checked_at)
+        .map_err(|e| invalid_data(format!("Invalid check time '{}': {}", checked_at, e)))?
+        .with_timezone(&Utc);
+    Ok(UrlCheck {
+        url,
+        status: status.map(|status| status as u16),
+        redirected_to,
+        error,
+        checked_at,
+    })
+}
+
+impl UrlCheckRepository for SqliteUrlCheckRepository {
+    fn save(&mut self, check: &UrlCheck) -> DomainResult<()> {
+        self.conn
+            .execute(
+                &format!("INSERT OR REPLACE INTO url_checks ({}) VALUES (?1, ?2, ?3, ?4, ?5)", COLUMNS),
+                params![
+                    check.url,
+                    check.status.map(i64::from),
+                    check.redirected_to,
+                    check.error,
+                    check.checked_at.to_rfc3339()
+                ],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> DomainResult<Option<UrlCheck>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM url_checks WHERE url = ?1", COLUMNS),
+                params![url],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(to_check)
+            .transpose()
+    }
+
+    fn all(&self) -> DomainResult<Vec<UrlCheck>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM url_checks ORDER BY url", COLUMNS))
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([], read_row)
+            .map_err(sqlite_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error)?;
+        rows.into_iter().map(to_check).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_checks_round_trip_and_replace() {
+        let mut repo = SqliteUrlCheckRepository::open_in_memory().unwrap();
+        let checked_at = DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z").unwrap().with_timezone(&Utc);
+        let gone = UrlCheck {
+            url: "https://example.com/gone".to_string(),
+            status: Some(404),
+            redirected_to: Some("https://example.com/missing".to_string()),
+            error: None,
+            checked_at,
+        };
+        let down = UrlCheck {
+            url: "https://down.example.com".to_string(),
+            status: None,
+            redirected_to: None,
+            error: Some("connection refused".to_string()),
+            checked_at,
+        };
+
+        assert_eq!(repo.get(&gone.url).unwrap(), None);
+        repo.save(&gone).unwrap();
+        repo.save(&down).unwrap();
+        assert_eq!(repo.get(&gone.url).unwrap(), Some(gone.clone()));
+
+        let fixed = UrlCheck {
+            status: Some(200),
+            redirected_to: None,
+            ..gone
+        };
+        repo.save(&fixed).unwrap();
+        assert_eq!(repo.all().unwrap(), vec![down, fixed]);
+    }
+}
@@ -0,0 +1,122 @@
+/// SQLite implementation of URL metadata storage
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+use super::{invalid_data, sqlite_error};
+use crate::application::repositories::UrlMetadataRepository;
+use crate::application::services::UrlMetadata;
+use crate::domain::DomainResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS url_metadata (
+        url TEXT PRIMARY KEY,
+        title TEXT,
+        description TEXT,
+        favicon TEXT,
+        fetched_at TEXT NOT NULL
+    );
+";
+
+/// URL metadata stored in a SQLite `url_metadata` table
+pub struct SqliteUrlMetadataRepository {
+    conn: Connection,
+}
+
+impl SqliteUrlMetadataRepository {
+    /// Open (or create) the URL metadata database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteUrlMetadataRepository { conn })
+    }
+}
+
+impl UrlMetadataRepository for SqliteUrlMetadataRepository {
+    fn save(&mut self, metadata: &UrlMetadata) -> DomainResult<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO url_metadata (url, title, description, favicon, fetched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    metadata.url,
+                    metadata.title,
+                    metadata.description,
+                    metadata.favicon,
+                    metadata.fetched_at.to_rfc3339()
+                ],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> DomainResult<Option<UrlMetadata>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT title, description, favicon, fetched_at FROM url_metadata WHERE url = ?1",
+                params![url],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        let Some((title, description, favicon, fetched_at)) = row else {
+            return Ok(None);
+        };
+        let fetched_at = DateTime::parse_from_rfc3339(&fetched_at)
+            .map_err(|e| invalid_data(format!("Invalid fetch time '{}': {}", fetched_at, e)))?
+            .with_timezone(&Utc);
+        Ok(Some(UrlMetadata {
+            url: url.to_string(),
+            title,
+            description,
+            favicon,
+            fetched_at,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_metadata_round_trip_and_replace() {
+        let mut repo = SqliteUrlMetadataRepository::open_in_memory().unwrap();
+        let metadata = UrlMetadata {
+            url: "https://example.com/rust".to_string(),
+            title: Some("Rust".to_string()),
+            description: None,
+            favicon: Some("https://example.com/favicon.ico".to_string()),
+            fetched_at: DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z").unwrap().with_timezone(&Utc),
+        };
+
+        assert_eq!(repo.get(&metadata.url).unwrap(), None);
+        repo.save(&metadata).unwrap();
+        assert_eq!(repo.get(&metadata.url).unwrap(), Some(metadata.clone()));
+
+        let renamed = UrlMetadata {
+            title: Some("The Rust Book".to_string()),
+            ..metadata
+        };
+        repo.save(&renamed).unwrap();
+        assert_eq!(repo.get(&renamed.url).unwrap(), Some(renamed));
+    }
+}
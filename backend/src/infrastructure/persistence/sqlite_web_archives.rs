@@ -0,0 +1,135 @@
+/// SQLite implementation of archived web page storage
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+
+use super::{invalid_data, sqlite_error};
+use crate::application::repositories::WebArchiveRepository;
+use crate::application::services::WebArchive;
+use crate::domain::DomainResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS web_archives (
+        url TEXT PRIMARY KEY,
+        title TEXT,
+        text TEXT NOT NULL,
+        archived_at TEXT NOT NULL
+    );
+";
+
+const COLUMNS: &str = "url, title, text, archived_at";
+
+/// Archived web pages stored in a SQLite `web_archives` table
+pub struct SqliteWebArchiveRepository {
+    conn: Connection,
+}
+
+impl SqliteWebArchiveRepository {
+    /// Open (or create) the web archive database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a store that lives only in memory (useful for testing)
+    pub fn open_in_memory() -> DomainResult<Self> {
+        let conn = Connection::open_in_memory().map_err(sqlite_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteWebArchiveRepository { conn })
+    }
+}
+
+type ArchiveRow = (String, Option<String>, String, String);
+
+fn read_row(row: &Row) -> rusqlite::Result<ArchiveRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+fn to_archive((url, title, text, archived_at): ArchiveRow) -> DomainResult<WebArchive> {
+    let archived_at = DateTime::parse_from_rfc3339(&archived_at)
+        .map_err(|e| invalid_data(format!("Invalid archive time '{}': {}", archived_at, e)))?
+        .with_timezone(&Utc);
+    Ok(WebArchive {
+        url,
+        title,
+        text,
+        archived_at,
+    })
+}
+
+impl WebArchiveRepository for SqliteWebArchiveRepository {
+    fn save(&mut self, archive: &WebArchive) -> DomainResult<()> {
+        self.conn
+            .execute(
+                &format!("INSERT OR REPLACE INTO web_archives ({}) VALUES (?1, ?2, ?3, ?4)", COLUMNS),
+                params![archive.url, archive.title, archive.text, archive.archived_at.to_rfc3339()],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> DomainResult<Option<WebArchive>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM web_archives WHERE url = ?1", COLUMNS),
+                params![url],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(to_archive)
+            .transpose()
+    }
+
+    fn all(&self) -> DomainResult<Vec<WebArchive>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM web_archives ORDER BY url", COLUMNS))
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([], read_row)
+            .map_err(sqlite_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error)?;
+        rows.into_iter().map(to_archive).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_archives_round_trip_and_replace() {
+        let mut repo = SqliteWebArchiveRepository::open_in_memory().unwrap();
+        let archived_at = DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z").unwrap().with_timezone(&Utc);
+        let rust = WebArchive {
+            url: "https://example.com/rust".to_string(),
+            title: Some("Rust".to_string()),
+            text: "Ownership and borrowing.".to_string(),
+            archived_at,
+        };
+        let untitled = WebArchive {
+            url: "https://example.com/notes".to_string(),
+            title: None,
+            text: "Plain notes.".to_string(),
+            archived_at,
+        };
+
+        assert_eq!(repo.get(&rust.url).unwrap(), None);
+        repo.save(&rust).unwrap();
+        repo.save(&untitled).unwrap();
+        assert_eq!(repo.get(&rust.url).unwrap(), Some(rust.clone()));
+
+        let updated = WebArchive {
+            text: "Ownership, borrowing and lifetimes.".to_string(),
+            ..rust
+        };
+        repo.save(&updated).unwrap();
+        assert_eq!(repo.all().unwrap(), vec![untitled, updated]);
+    }
+}
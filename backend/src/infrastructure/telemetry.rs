@@ -0,0 +1,55 @@
+/// OpenTelemetry export of `tracing` spans over OTLP/gRPC
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::error::OTelSdkError;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use thiserror::Error;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("Cannot create the OTLP exporter: {0}")]
+    Exporter(#[from] ExporterBuildError),
+
+    #[error("Cannot flush spans to the collector: {0}")]
+    Shutdown(#[from] OTelSdkError),
+}
+
+/// Spans sent in batches to an OpenTelemetry collector
+///
+/// Add [`OtlpTracing::layer`] to the `tracing` subscriber, then call
+/// [`OtlpTracing::shutdown`] before exiting so the last batch isn't lost.
+/// The gRPC client needs a Tokio runtime, both to start and to export.
+pub struct OtlpTracing {
+    provider: SdkTracerProvider,
+}
+
+impl OtlpTracing {
+    /// Export to the collector at `endpoint`, e.g. `http://localhost:4317`,
+    /// reporting spans as coming from `service_name`
+    pub fn start(endpoint: &str, service_name: &str) -> Result<Self, TelemetryError> {
+        let exporter = SpanExporter::builder().with_tonic().with_endpoint(endpoint).build()?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+            .build();
+        Ok(Self { provider })
+    }
+
+    /// A subscriber layer turning `tracing` spans into exported ones
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, SdkTracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.provider.tracer("logjam"))
+    }
+
+    /// Export the spans still queued and stop exporting
+    pub fn shutdown(self) -> Result<(), TelemetryError> {
+        self.provider.shutdown()?;
+        Ok(())
+    }
+}
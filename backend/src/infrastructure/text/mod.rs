@@ -0,0 +1,5 @@
+pub mod language;
+pub mod tokenizer;
+
+pub use language::Language;
+pub use tokenizer::{is_cjk, KeywordTokenizer};
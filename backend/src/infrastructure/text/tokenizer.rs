@@ -0,0 +1,153 @@
+/// Word segmentation for keyword search and sparse (BM25) indexing
+use super::Language;
+use rust_stemmers::Stemmer;
+use std::collections::HashSet;
+
+/// Splits text into lowercase search terms
+///
+/// Latin, Cyrillic and other space-separated scripts split into words at
+/// anything that isn't a letter or digit. Chinese and Japanese don't separate
+/// words, so runs of CJK characters become overlapping bigrams ("東京大学" is
+/// "東京", "京大", "大学"), which finds words without a dictionary; a lone CJK
+/// character is kept as a term of its own.
+///
+/// Optionally, words are reduced to their stem ("embeddings" and "embedding"
+/// are both "embed") and a language's stop words are dropped. Text has to be
+/// indexed with the same settings it is searched with, so a hybrid
+/// collection must be reindexed after changing them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeywordTokenizer {
+    stemming: Option<Language>,
+    stop_words: Option<Language>,
+}
+
+impl KeywordTokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reduce words to their stem in `language`
+    pub fn with_stemming(mut self, language: Language) -> Self {
+        self.stemming = Some(language);
+        self
+    }
+
+    /// Leave out the stop words of `language`
+    pub fn with_stop_words(mut self, language: Language) -> Self {
+        self.stop_words = Some(language);
+        self
+    }
+
+    /// Whether terms are changed or dropped, not just lowercased and split
+    pub fn normalizes_words(&self) -> bool {
+        self.stemming.is_some() || self.stop_words.is_some()
+    }
+
+    /// The terms of `text`, in order and with repeats
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        let terms = self.segment(text);
+        let stop_words = self.stop_words.map_or(&[][..], Language::stop_words);
+        let stemmer = self.stemming.map(|language| Stemmer::create(language.algorithm()));
+        terms
+            .into_iter()
+            .filter(|term| stop_words.binary_search(&term.as_str()).is_err())
+            .map(|term| match &stemmer {
+                Some(stemmer) if !term.chars().any(is_cjk) => stemmer.stem(&term).into_owned(),
+                _ => term,
+            })
+            .collect()
+    }
+
+    /// Words and CJK bigrams, lowercased
+    fn segment(&self, text: &str) -> Vec<String> {
+        let mut terms = Vec::new();
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+            let chars: Vec<char> = word.to_lowercase().chars().collect();
+            let mut start = 0;
+            while start < chars.len() {
+                let cjk = is_cjk(chars[start]);
+                let end = chars[start..]
+                    .iter()
+                    .position(|&c| is_cjk(c) != cjk)
+                    .map_or(chars.len(), |length| start + length);
+                let run = &chars[start..end];
+                if !cjk {
+                    terms.push(run.iter().collect());
+                } else if run.len() == 1 {
+                    terms.push(run[0].to_string());
+                } else {
+                    terms.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+                }
+                start = end;
+            }
+        }
+        terms
+    }
+
+    /// Whether `text` has every one of `query_terms`, in any order
+    ///
+    /// A query term matches the start of a word, so "own" finds "ownership";
+    /// a lone CJK character matches anywhere in a bigram.
+    pub fn contains_all(&self, text: &str, query_terms: &[String]) -> bool {
+        let terms: HashSet<String> = self.tokenize(text).into_iter().collect();
+        query_terms.iter().all(|query_term| {
+            terms.contains(query_term)
+                || terms.iter().any(|term| {
+                    term.starts_with(query_term.as_str())
+                        || (query_term.chars().count() == 1 && query_term.chars().all(is_cjk) && term.contains(query_term.as_str()))
+                })
+        })
+    }
+}
+
+/// Whether `c` is written without spaces between words: Han ideographs,
+/// kana and Hangul
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'     // Hangul Jamo
+        | '\u{3005}'                // Ideographic iteration mark
+        | '\u{3040}'..='\u{30FF}'   // Hiragana and Katakana
+        | '\u{3130}'..='\u{318F}'   // Hangul Compatibility Jamo
+        | '\u{31F0}'..='\u{31FF}'   // Katakana Phonetic Extensions
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul Syllables
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FA1F}' // CJK Extensions B to F and supplements
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cjk_runs_become_bigrams_between_words() {
+        let tokenizer = KeywordTokenizer::new();
+        assert_eq!(tokenizer.tokenize("Rust's ownership"), vec!["rust", "s", "ownership"]);
+        assert_eq!(tokenizer.tokenize("東京大学で学ぶ"), vec!["東京", "京大", "大学", "学で", "で学", "学ぶ"]);
+        assert_eq!(tokenizer.tokenize("Rustの所有権, 本"), vec!["rust", "の所", "所有", "有権", "本"]);
+    }
+
+    #[test]
+    fn test_query_terms_match_in_any_order() {
+        let tokenizer = KeywordTokenizer::new();
+        let query = tokenizer.tokenize("所有権 rust");
+        assert!(tokenizer.contains_all("Rustの所有権について", &query));
+        assert!(!tokenizer.contains_all("所有者について", &query));
+        assert!(tokenizer.contains_all("Ownership rules", &tokenizer.tokenize("own rules")));
+        assert!(tokenizer.contains_all("東京大学", &tokenizer.tokenize("京")));
+    }
+
+    #[test]
+    fn test_stemming_and_stop_words() {
+        let tokenizer = KeywordTokenizer::new()
+            .with_stemming(Language::English)
+            .with_stop_words(Language::English);
+        assert_eq!(tokenizer.tokenize("The embeddings of a page"), vec!["embed", "page"]);
+        assert_eq!(tokenizer.tokenize("embedding"), vec!["embed"]);
+        assert_eq!(tokenizer.tokenize("検索 searching"), vec!["検索", "search"]);
+        assert!(tokenizer.contains_all("Pages with embeddings", &tokenizer.tokenize("embedding pages")));
+    }
+}
@@ -1,3 +1,22 @@
+//! Logseq graphs as a searchable knowledge base
+//!
+//! The domain model and parsers ([`domain`], [`infrastructure::parsers`])
+//! build everywhere, wasm32 included, so a browser can parse and preview
+//! markdown exactly as the backend does. Everything else needs the default
+//! `native` feature: build with `--no-default-features` for wasm32.
+#[cfg(feature = "native")]
 pub mod application;
+#[cfg(feature = "native")]
+pub mod config;
 pub mod domain;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod infrastructure;
+#[cfg(feature = "native")]
+pub mod mcp;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "native")]
+pub mod tauri_commands;
@@ -0,0 +1,6 @@
+pub mod protocol;
+pub mod server;
+mod tools;
+
+pub use protocol::{RpcError, PROTOCOL_VERSIONS};
+pub use server::McpServer;
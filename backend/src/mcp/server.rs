@@ -0,0 +1,236 @@
+/// MCP server answering JSON-RPC requests over a line-delimited stream
+use super::protocol::{negotiate_version, Request, Response, RpcError};
+use super::tools::{self, ToolError};
+use crate::application::repositories::PageRepository;
+use crate::application::services::{EmbeddingService, VisibilityPolicy, VisiblePages};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+/// Serves the graph to MCP clients through the `search_notes`, `get_page`,
+/// `get_backlinks` and `get_rag_context` tools
+///
+/// Messages are newline-delimited JSON, as in the protocol's stdio transport.
+/// Searches run semantically when an embedding service is configured.
+pub struct McpServer<R: PageRepository> {
+    repository: Arc<Mutex<R>>,
+    embedding_service: Option<Arc<EmbeddingService>>,
+    visibility: Option<VisibilityPolicy>,
+}
+
+impl<R: PageRepository> McpServer<R> {
+    pub fn new(repository: R) -> Self {
+        McpServer {
+            repository: Arc::new(Mutex::new(repository)),
+            embedding_service: None,
+            visibility: None,
+        }
+    }
+
+    /// Enable semantic search
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Hide the pages and blocks `visibility` keeps private from every tool
+    pub fn with_visibility(mut self, visibility: VisibilityPolicy) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Serve over stdin and stdout until stdin closes
+    pub async fn serve_stdio(&self) -> std::io::Result<()> {
+        self.serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await
+    }
+
+    /// Answer each message line read from `input` on `output` until `input` ends
+    pub async fn serve<I, O>(&self, input: I, mut output: O) -> std::io::Result<()>
+    where
+        I: AsyncBufRead + Unpin,
+        O: AsyncWrite + Unpin,
+    {
+        let mut lines = input.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line).await {
+                let mut bytes = serde_json::to_vec(&response)?;
+                bytes.push(b'\n');
+                output.write_all(&bytes).await?;
+                output.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC message, returning the response to send, if any
+    ///
+    /// Notifications get no response, even when they fail.
+    pub async fn handle_message(&self, message: &str) -> Option<Response> {
+        let value: Value = match serde_json::from_str(message) {
+            Ok(value) => value,
+            Err(e) => return Some(Response::failure(Value::Null, RpcError::parse_error(e.to_string()))),
+        };
+        let id = value.get("id").cloned().unwrap_or(Value::Null);
+        let request = match serde_json::from_value::<Request>(value) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => return Some(Response::failure(id, RpcError::invalid_request("Expected JSON-RPC 2.0"))),
+            Err(e) => return Some(Response::failure(id, RpcError::invalid_request(e.to_string()))),
+        };
+
+        let id = request.id?;
+        Some(match self.dispatch(&request.method, request.params).await {
+            Ok(result) => Response::success(id, result),
+            Err(error) => Response::failure(id, error),
+        })
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": negotiate_version(params.get("protocolVersion").and_then(Value::as_str)),
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": "logjam", "version": env!("CARGO_PKG_VERSION") },
+                "instructions": "Tools for searching and reading the user's Logseq notes.",
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+            "tools/call" => self.call_tool(params).await,
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, RpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("Missing tool name"))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+
+        let repository = self.repository.lock().await;
+        let repository = VisiblePages::new(&*repository, self.visibility);
+        let output = tools::call(name, arguments, &repository, self.embedding_service.as_ref()).await;
+        let (text, is_error) = match output {
+            Ok(text) => (text, false),
+            Err(ToolError::Failed(message)) => (message, true),
+            Err(ToolError::InvalidCall(message)) => return Err(RpcError::invalid_params(message)),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::PageId;
+    use crate::infrastructure::parsers::LogseqMarkdownParser;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+
+    fn server() -> McpServer<InMemoryPageRepository> {
+        let mut repo = InMemoryPageRepository::new();
+        for (id, title, content) in [
+            ("rust", "Rust", "- Ownership moves values\n\t- Borrowing lends them"),
+            ("notes", "Notes", "- Learning [[Rust]] this week"),
+        ] {
+            let page = LogseqMarkdownParser::parse_content(content, PageId::new(id).unwrap(), title.to_string()).unwrap();
+            repo.save(page).unwrap();
+        }
+        McpServer::new(repo)
+    }
+
+    async fn call(server: &McpServer<InMemoryPageRepository>, method: &str, params: Value) -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        serde_json::to_value(server.handle_message(&message.to_string()).await.unwrap()).unwrap()
+    }
+
+    fn text(response: &Value) -> &str {
+        response["result"]["content"][0]["text"].as_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools() {
+        let server = server();
+
+        let response = call(&server, "initialize", json!({ "protocolVersion": "2024-11-05" })).await;
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "logjam");
+        let response = call(&server, "initialize", json!({ "protocolVersion": "1999-01-01" })).await;
+        assert_eq!(response["result"]["protocolVersion"], "2025-06-18");
+
+        let response = call(&server, "tools/list", json!({})).await;
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["search_notes", "get_page", "get_backlinks", "get_rag_context"]);
+    }
+
+    #[tokio::test]
+    async fn test_tools_answer_with_text() {
+        let server = server();
+
+        let response = call(&server, "tools/call", json!({ "name": "search_notes", "arguments": { "query": "ownership" } })).await;
+        assert_eq!(response["result"]["isError"], false);
+        assert!(text(&response).contains("Ownership moves values"));
+
+        let response = call(&server, "tools/call", json!({ "name": "get_page", "arguments": { "title": "rust" } })).await;
+        assert!(text(&response).starts_with("# Rust\n"));
+        assert!(text(&response).contains("\t- Borrowing lends them"));
+
+        let response = call(&server, "tools/call", json!({ "name": "get_backlinks", "arguments": { "id": "rust" } })).await;
+        assert!(text(&response).contains("Learning [[Rust]] this week"));
+
+        let response = call(&server, "tools/call", json!({ "name": "get_rag_context", "arguments": { "query": "ownership" } })).await;
+        assert!(text(&response).contains("- Ownership moves values\n\t- Borrowing lends them"));
+    }
+
+    #[tokio::test]
+    async fn test_reports_errors() {
+        let server = server();
+
+        // A missing page is a tool failure the model can read
+        let response = call(&server, "tools/call", json!({ "name": "get_page", "arguments": { "title": "Go" } })).await;
+        assert_eq!(response["result"]["isError"], true);
+
+        let response = call(&server, "tools/call", json!({ "name": "delete_page", "arguments": {} })).await;
+        assert_eq!(response["error"]["code"], RpcError::INVALID_PARAMS);
+        let response = call(&server, "tools/call", json!({ "name": "search_notes", "arguments": {} })).await;
+        assert_eq!(response["error"]["code"], RpcError::INVALID_PARAMS);
+        let response = call(&server, "resources/list", json!({})).await;
+        assert_eq!(response["error"]["code"], RpcError::METHOD_NOT_FOUND);
+
+        let response = server.handle_message("{not json").await.unwrap();
+        assert_eq!(response.error.unwrap().code, RpcError::PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_serve_skips_notifications() {
+        let server = server();
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).await.unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1], json!({ "jsonrpc": "2.0", "id": 2, "result": {} }));
+    }
+}
@@ -0,0 +1,260 @@
+/// MCP tools over the page repository and their text renderings
+use crate::application::dto::{SearchItem, SearchRequest, SearchResult, SearchType};
+use crate::application::repositories::PageRepository;
+use crate::application::services::EmbeddingService;
+use crate::application::use_cases::{GetBacklinks, GetRagContext, SearchError, SearchPagesAndBlocks};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::value_objects::PageId;
+use crate::domain::DomainError;
+use crate::infrastructure::parsers::LogseqMarkdownWriter;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Why a tool call produced no result
+#[derive(Debug)]
+pub(crate) enum ToolError {
+    /// The call itself is wrong (unknown tool, bad arguments): a protocol error
+    InvalidCall(String),
+    /// The call ran and failed: reported to the model as a tool result
+    Failed(String),
+}
+
+impl From<DomainError> for ToolError {
+    fn from(error: DomainError) -> Self {
+        ToolError::Failed(error.to_string())
+    }
+}
+
+impl From<SearchError> for ToolError {
+    fn from(error: SearchError) -> Self {
+        ToolError::Failed(error.to_string())
+    }
+}
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+const DEFAULT_CONTEXT_LIMIT: usize = 5;
+
+/// The `tools/list` entries
+pub(crate) fn definitions() -> Value {
+    let page_selector = json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string", "description": "Page title (case-insensitive)" },
+            "id": { "type": "string", "description": "Page id" }
+        }
+    });
+
+    json!([
+        {
+            "name": "search_notes",
+            "description": "Search the Logseq graph's pages, blocks and URLs. Block results include the outline path leading to them. Dates in the query, like 'last week' or 'since 2024-03-01', limit the search to journals of those days.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to search for" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Maximum number of results (default 10)" },
+                    "semantic": { "type": "boolean", "description": "Search by meaning instead of keywords (default: when embeddings are available)" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_page",
+            "description": "Get a page of the Logseq graph as outline markdown, by title or id.",
+            "inputSchema": page_selector
+        },
+        {
+            "name": "get_backlinks",
+            "description": "List the blocks on other pages that reference a page, by title or id.",
+            "inputSchema": page_selector
+        },
+        {
+            "name": "get_rag_context",
+            "description": "Gather the passages of the Logseq graph most relevant to a question, each with its parent blocks and child outline.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "The question or topic" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Maximum number of passages (default 5)" }
+                },
+                "required": ["query"]
+            }
+        }
+    ])
+}
+
+#[derive(Deserialize)]
+struct SearchArgs {
+    query: String,
+    limit: Option<usize>,
+    semantic: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct PageArgs {
+    title: Option<String>,
+    id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ContextArgs {
+    query: String,
+    limit: Option<usize>,
+}
+
+/// Run a tool, returning its text output
+pub(crate) async fn call<R: PageRepository>(
+    name: &str,
+    arguments: Value,
+    repository: &R,
+    embedding_service: Option<&Arc<EmbeddingService>>,
+) -> Result<String, ToolError> {
+    match name {
+        "search_notes" => {
+            let args: SearchArgs = parse_arguments(arguments)?;
+            let semantic = args.semantic.unwrap_or(embedding_service.is_some());
+            let request = SearchRequest::new(args.query.as_str()).with_date_expressions();
+            let mut results = match embedding_service {
+                Some(embedding_service) if semantic => {
+                    SearchPagesAndBlocks::with_embedding_service(repository, embedding_service.clone())
+                        .execute(request.with_search_type(SearchType::Semantic))
+                        .await?
+                }
+                _ => SearchPagesAndBlocks::new(repository).execute(request).await?,
+            };
+            results.truncate(args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+            Ok(search_text(&args.query, &results))
+        }
+        "get_page" => {
+            let page = find_page(repository, parse_arguments(arguments)?)?;
+            Ok(page_text(&page))
+        }
+        "get_backlinks" => {
+            let page = find_page(repository, parse_arguments(arguments)?)?;
+            let backlinks = GetBacklinks::new(repository).execute(page.id())?;
+            if backlinks.is_empty() {
+                return Ok(format!("No blocks reference \"{}\".", page.title()));
+            }
+            let mut text = format!("{} blocks reference \"{}\":\n", backlinks.len(), page.title());
+            for backlink in backlinks {
+                let _ = write!(
+                    text,
+                    "\n- {} (page id {}): {}",
+                    backlink.page_title,
+                    backlink.page_id.as_str(),
+                    backlink.hierarchy_path.join(" > ")
+                );
+            }
+            Ok(text)
+        }
+        "get_rag_context" => {
+            let args: ContextArgs = parse_arguments(arguments)?;
+            let use_case = match embedding_service {
+                Some(embedding_service) => GetRagContext::with_embedding_service(repository, embedding_service.clone()),
+                None => GetRagContext::new(repository),
+            };
+            let passages = use_case
+                .execute(&args.query, args.limit.unwrap_or(DEFAULT_CONTEXT_LIMIT))
+                .await?;
+            if passages.is_empty() {
+                return Ok(format!("Nothing in the graph matches \"{}\".", args.query));
+            }
+            let mut text = String::new();
+            for passage in passages {
+                let _ = writeln!(text, "## {} (score {:.2})", passage.page_title, passage.score.value());
+                if !passage.breadcrumbs.is_empty() {
+                    let _ = writeln!(text, "Under: {}", passage.breadcrumbs.join(" > "));
+                }
+                let _ = writeln!(text, "{}", passage.content);
+            }
+            Ok(text)
+        }
+        _ => Err(ToolError::InvalidCall(format!("Unknown tool: {}", name))),
+    }
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Value) -> Result<T, ToolError> {
+    // Clients may leave out `arguments` for tools without required ones
+    let arguments = if arguments.is_null() { json!({}) } else { arguments };
+    serde_json::from_value(arguments).map_err(|e| ToolError::InvalidCall(format!("Invalid arguments: {}", e)))
+}
+
+/// Look a page up by id, or by title, exactly first and then ignoring case
+fn find_page<R: PageRepository>(repository: &R, args: PageArgs) -> Result<Page, ToolError> {
+    let page = match (args.id, args.title) {
+        (Some(id), _) => {
+            let page_id = PageId::new(id).map_err(|e| ToolError::InvalidCall(e.to_string()))?;
+            repository.find_by_id(&page_id)?
+        }
+        (None, Some(title)) => match repository.find_by_title(&title)? {
+            Some(page) => Some(page),
+            None => {
+                let title = title.to_lowercase();
+                repository
+                    .find_all()?
+                    .into_iter()
+                    .find(|page| page.title().to_lowercase() == title)
+            }
+        },
+        (None, None) => return Err(ToolError::InvalidCall("Pass a page 'title' or 'id'".to_string())),
+    };
+    page.ok_or_else(|| ToolError::Failed("No such page".to_string()))
+}
+
+fn page_text(page: &Page) -> String {
+    let mut text = format!("# {}\n", page.title());
+    let _ = writeln!(text, "page id: {}", page.id().as_str());
+    for (key, value) in page.properties() {
+        let _ = writeln!(text, "{}:: {}", key, value);
+    }
+    text.push('\n');
+    text.push_str(&LogseqMarkdownWriter::render(page));
+    text
+}
+
+fn search_text(query: &str, results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return format!("No results for \"{}\".", query);
+    }
+    let mut text = format!("{} results for \"{}\":\n", results.len(), query);
+    for (rank, result) in results.iter().enumerate() {
+        let _ = write!(text, "\n{}. ", rank + 1);
+        let _ = match &result.item {
+            SearchItem::Page(page) => write!(
+                text,
+                "Page \"{}\" ({} blocks, page id {})",
+                page.title,
+                page.block_count,
+                page.page_id.as_str()
+            ),
+            SearchItem::Block(block) => write!(
+                text,
+                "Block on \"{}\" (page id {}): {}",
+                block.page_title,
+                block.page_id.as_str(),
+                block.hierarchy_path.join(" > ")
+            ),
+            SearchItem::Url(url) => write!(
+                text,
+                "URL {} on \"{}\" (page id {}): {}",
+                url.url.as_str(),
+                url.page_title,
+                url.page_id.as_str(),
+                url.containing_block_content
+            ),
+            SearchItem::Archive(archive) => write!(
+                text,
+                "Archived web page {} \"{}\": {}",
+                archive.url.as_str(),
+                archive.title.as_deref().unwrap_or("untitled"),
+                archive.excerpt
+            ),
+        };
+        let _ = write!(text, " [score {:.2}]", result.score.value());
+    }
+    text
+}
@@ -0,0 +1,318 @@
+/// Bearer-token authentication and per-route scopes for the HTTP API
+use super::error::ApiError;
+use axum::extract::{Query, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// How long an introspection answer is trusted before asking again
+const INTROSPECTION_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Cached introspection answers kept before the cache is cleared
+const INTROSPECTION_CACHE_CAPACITY: usize = 10_000;
+
+/// What a token may do
+///
+/// `write` includes `read`, so a token for an editor needs only `write`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Search, read pages and follow sync and import events
+    Read,
+    /// Create, update and delete pages and start imports
+    Write,
+}
+
+impl Scope {
+    /// Parse a scope name, bare or namespaced as identity providers often
+    /// require (`read` or `logjam:read`)
+    pub fn parse(name: &str) -> Option<Scope> {
+        match name.strip_prefix("logjam:").unwrap_or(name) {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+
+    fn granted_by(&self, scopes: &HashSet<Scope>) -> bool {
+        scopes.contains(self) || scopes.contains(&Scope::Write)
+    }
+}
+
+/// The scope a request needs: reading needs `read`, anything that changes
+/// the graph or starts work needs `write`
+pub fn required_scope(method: &Method, path: &str) -> Scope {
+    match (method, path) {
+        // GraphQL requests are POSTed, but the schema only has queries
+        (&Method::POST, "/api/graphql") => Scope::Read,
+        (&Method::GET | &Method::HEAD | &Method::OPTIONS, _) => Scope::Read,
+        _ => Scope::Write,
+    }
+}
+
+/// Which bearer tokens the API accepts and what they may do
+///
+/// Tokens are checked against the static tokens first, then, if
+/// configured, against an OAuth 2.0 / OIDC introspection endpoint.
+#[derive(Default)]
+pub struct ApiAuth {
+    tokens: Vec<(String, HashSet<Scope>)>,
+    introspection: Option<TokenIntrospection>,
+}
+
+impl ApiAuth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept a fixed token with the given scopes
+    pub fn with_token(mut self, token: impl Into<String>, scopes: impl IntoIterator<Item = Scope>) -> Self {
+        self.tokens.push((token.into(), scopes.into_iter().collect()));
+        self
+    }
+
+    /// Ask an introspection endpoint about tokens that aren't static tokens
+    pub fn with_introspection(mut self, introspection: TokenIntrospection) -> Self {
+        self.introspection = Some(introspection);
+        self
+    }
+
+    /// The scopes a token grants, or `None` for a token that isn't valid
+    pub async fn scopes(&self, token: &str) -> Result<Option<HashSet<Scope>>, ApiError> {
+        // Every static token is compared, in constant time, so response
+        // timing doesn't tell how much of a guess was right
+        let mut granted = None;
+        for (candidate, scopes) in &self.tokens {
+            if constant_time_eq(candidate.as_bytes(), token.as_bytes()) {
+                granted = Some(scopes.clone());
+            }
+        }
+        match (granted, &self.introspection) {
+            (Some(scopes), _) => Ok(Some(scopes)),
+            (None, Some(introspection)) => introspection.scopes(token).await,
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Token introspection (RFC 7662) against an OAuth 2.0 / OIDC provider
+///
+/// Active tokens grant the `read` and `write` scopes (see [`Scope::parse`])
+/// of their `scope` claim. Answers are cached for a minute, or until the
+/// token expires if that's sooner.
+pub struct TokenIntrospection {
+    endpoint: String,
+    client_id: String,
+    client_secret: String,
+    client: reqwest::Client,
+    cache: Mutex<HashMap<String, CachedIntrospection>>,
+}
+
+struct CachedIntrospection {
+    scopes: Option<HashSet<Scope>>,
+    expires: Instant,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    #[serde(default)]
+    scope: Option<String>,
+    /// Expiry, in seconds since the Unix epoch
+    #[serde(default)]
+    exp: Option<u64>,
+}
+
+impl TokenIntrospection {
+    /// Introspect at `endpoint`, authenticating as the given client
+    pub fn new(endpoint: impl Into<String>, client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        TokenIntrospection {
+            endpoint: endpoint.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            client: reqwest::Client::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn scopes(&self, token: &str) -> Result<Option<HashSet<Scope>>, ApiError> {
+        let now = Instant::now();
+        if let Some(cached) = self.cache.lock().await.get(token) {
+            if cached.expires > now {
+                return Ok(cached.scopes.clone());
+            }
+        }
+
+        let answer = self.introspect(token).await.map_err(|e| {
+            tracing::error!("Token introspection at {} failed: {}", self.endpoint, e);
+            ApiError::Unavailable("Cannot verify the token right now".to_string())
+        })?;
+        let scopes = answer.active.then(|| {
+            answer
+                .scope
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(Scope::parse)
+                .collect::<HashSet<_>>()
+        });
+
+        let mut ttl = INTROSPECTION_CACHE_TTL;
+        if let Some(exp) = answer.exp {
+            let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            ttl = ttl.min(Duration::from_secs(exp).saturating_sub(unix_now));
+        }
+        let mut cache = self.cache.lock().await;
+        if cache.len() >= INTROSPECTION_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(
+            token.to_string(),
+            CachedIntrospection {
+                scopes: scopes.clone(),
+                expires: now + ttl,
+            },
+        );
+        Ok(scopes)
+    }
+
+    async fn introspect(&self, token: &str) -> Result<IntrospectionResponse, String> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("the endpoint answered {}", status));
+        }
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+        serde_json::from_slice(&body).map_err(|e| format!("invalid response: {}", e))
+    }
+}
+
+/// Reject requests without a token granting the route's scope
+///
+/// Tokens come in an `Authorization: Bearer` header, or, for
+/// `GET /api/events` only, an `access_token` query parameter, since
+/// browsers' `EventSource` can't send headers.
+pub(crate) async fn authorize(State(auth): State<Arc<ApiAuth>>, request: Request, next: Next) -> Response {
+    let scope = required_scope(request.method(), request.uri().path());
+    let Some(token) = bearer_token(&request) else {
+        return ApiError::Unauthorized("Missing bearer token".to_string()).into_response();
+    };
+    match auth.scopes(&token).await {
+        Ok(Some(scopes)) if scope.granted_by(&scopes) => next.run(request).await,
+        Ok(Some(_)) => ApiError::Forbidden(format!("The token lacks the '{}' scope", scope.as_str())).into_response(),
+        Ok(None) => ApiError::Unauthorized("Invalid or expired token".to_string()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+fn bearer_token(request: &Request) -> Option<String> {
+    if let Some(header) = request.headers().get(AUTHORIZATION) {
+        let (scheme, token) = header.to_str().ok()?.split_once(' ')?;
+        return scheme.eq_ignore_ascii_case("bearer").then(|| token.trim().to_string());
+    }
+    if request.method() == Method::GET && request.uri().path() == "/api/events" {
+        let Query(query) = Query::<HashMap<String, String>>::try_from_uri(request.uri()).ok()?;
+        return query.get("access_token").cloned();
+    }
+    None
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use crate::server::{router, ApiState};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::post;
+    use axum::{Form, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, method: Method, uri: &str, token: Option<&str>) -> Response {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json");
+        if let Some(token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let body = Body::from(json!({ "title": "Rust", "content": "- Ownership" }).to_string());
+        app.clone().oneshot(request.body(body).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routes_need_a_token_with_their_scope() {
+        let auth = ApiAuth::new()
+            .with_token("reader", [Scope::Read])
+            .with_token("editor", [Scope::Write]);
+        let app = router(ApiState::new(InMemoryPageRepository::new()).with_auth(auth));
+
+        let response = send(&app, Method::GET, "/api/pages", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()["www-authenticate"], "Bearer");
+        assert_eq!(send(&app, Method::GET, "/api/pages", Some("guess")).await.status(), StatusCode::UNAUTHORIZED);
+
+        assert_eq!(send(&app, Method::GET, "/api/pages", Some("reader")).await.status(), StatusCode::OK);
+        assert_eq!(send(&app, Method::POST, "/api/pages", Some("reader")).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(send(&app, Method::POST, "/api/pages", Some("editor")).await.status(), StatusCode::CREATED);
+        assert_eq!(send(&app, Method::GET, "/api/pages", Some("editor")).await.status(), StatusCode::OK);
+        assert_eq!(send(&app, Method::GET, "/health", None).await.status(), StatusCode::OK);
+
+        assert_eq!(required_scope(&Method::POST, "/api/graphql"), Scope::Read);
+        assert_eq!(required_scope(&Method::DELETE, "/api/pages/rust"), Scope::Write);
+    }
+
+    #[tokio::test]
+    async fn test_introspected_tokens_are_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let provider = Router::new().route(
+            "/introspect",
+            post(move |Form(form): Form<HashMap<String, String>>| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let answer: Value = match form["token"].as_str() {
+                        "sso-token" => json!({ "active": true, "scope": "openid logjam:read" }),
+                        _ => json!({ "active": false }),
+                    };
+                    Json(answer)
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/introspect", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, provider).await });
+
+        let auth = ApiAuth::new().with_introspection(TokenIntrospection::new(endpoint, "logjam", "secret"));
+        let app = router(ApiState::new(InMemoryPageRepository::new()).with_auth(auth));
+
+        assert_eq!(send(&app, Method::GET, "/api/pages", Some("sso-token")).await.status(), StatusCode::OK);
+        assert_eq!(send(&app, Method::POST, "/api/pages", Some("sso-token")).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(send(&app, Method::GET, "/api/pages", Some("revoked")).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
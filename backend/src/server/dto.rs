@@ -0,0 +1,618 @@
+/// JSON request and response bodies of the HTTP API
+use crate::application::dto::{
+    unix_seconds, Backlink, BlockAddress, OutlineBlock, PageConnection, PageFilter, PageOutline, PageSummary, ResultType, ScoreExplanation, SearchItem, SearchResult, SearchType,
+    UrlWithContext,
+};
+pub use crate::application::dto::{DuplicateTitleDto, FileErrorDto, ImportSummaryDto, SyncEventDto, SyncEventKind};
+use crate::application::services::{ImportProgressEvent, SyncStatus, UrlMetadata};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::entities::Block;
+use crate::domain::value_objects::PageReference;
+use crate::infrastructure::parsers::LogseqMarkdownWriter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Query string of `GET /api/search`
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub results: ResultFilter,
+    /// Minimum score (0.0-1.0) of any result
+    pub threshold: Option<f32>,
+    /// Graph to search; semantic search rejects graphs it doesn't index
+    pub graph: Option<String>,
+    /// Give URL results the page references of their own block too
+    #[serde(default)]
+    pub same_block_refs: bool,
+    /// Attach a breakdown of each result's score
+    #[serde(default)]
+    pub explain: bool,
+    /// Search journals of the dates `q` mentions, like "last week", for
+    /// the rest of `q`
+    #[serde(default)]
+    pub dates: bool,
+    /// Cut long blocks down to this many characters around the match
+    pub excerpt: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Traditional,
+    Semantic,
+}
+
+impl From<SearchMode> for SearchType {
+    fn from(mode: SearchMode) -> Self {
+        match mode {
+            SearchMode::Traditional => SearchType::Traditional,
+            SearchMode::Semantic => SearchType::Semantic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFilter {
+    #[default]
+    All,
+    Pages,
+    Blocks,
+    Urls,
+}
+
+impl From<ResultFilter> for ResultType {
+    fn from(filter: ResultFilter) -> Self {
+        match filter {
+            ResultFilter::All => ResultType::All,
+            ResultFilter::Pages => ResultType::PagesOnly,
+            ResultFilter::Blocks => ResultType::BlocksOnly,
+            ResultFilter::Urls => ResultType::UrlsOnly,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResultDto {
+    pub score: f64,
+    #[serde(flatten)]
+    pub item: SearchItemDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// A search hit, tagged with its `type`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchItemDto {
+    Page {
+        page_id: String,
+        title: String,
+        block_count: usize,
+        urls: Vec<String>,
+        page_references: Vec<String>,
+    },
+    Block {
+        block_id: String,
+        content: String,
+        page_id: String,
+        page_title: String,
+        hierarchy_path: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        block_path: Option<String>,
+        related_pages: Vec<String>,
+        related_urls: Vec<String>,
+        /// Whether `content` is an excerpt; `GET /api/blocks?id=..` has it whole
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        truncated: bool,
+    },
+    Url {
+        url: String,
+        block_id: String,
+        block_content: String,
+        page_id: String,
+        page_title: String,
+        ancestor_page_refs: Vec<String>,
+        descendant_page_refs: Vec<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        same_block_page_refs: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        metadata: Option<UrlMetadataDto>,
+    },
+    Archive {
+        url: String,
+        title: Option<String>,
+        excerpt: String,
+        /// Seconds since the Unix epoch
+        archived_at: u64,
+    },
+}
+
+impl From<SearchResult> for SearchResultDto {
+    fn from(result: SearchResult) -> Self {
+        let item = match result.item {
+            SearchItem::Page(page) => SearchItemDto::Page {
+                page_id: page.page_id.as_str().to_string(),
+                title: page.title,
+                block_count: page.block_count,
+                urls: page.urls.iter().map(|url| url.as_str().to_string()).collect(),
+                page_references: titles(&page.page_references),
+            },
+            SearchItem::Block(block) => SearchItemDto::Block {
+                block_id: block.block_id.as_str().to_string(),
+                content: block.content,
+                page_id: block.page_id.as_str().to_string(),
+                page_title: block.page_title,
+                hierarchy_path: block.hierarchy_path,
+                block_path: block.block_path.map(|path| path.to_string()),
+                related_pages: titles(&block.related_pages),
+                related_urls: block.related_urls.iter().map(|url| url.as_str().to_string()).collect(),
+                truncated: block.truncated,
+            },
+            SearchItem::Url(url) => SearchItemDto::Url {
+                url: url.url.as_str().to_string(),
+                block_id: url.containing_block_id.as_str().to_string(),
+                block_content: url.containing_block_content,
+                page_id: url.page_id.as_str().to_string(),
+                page_title: url.page_title,
+                ancestor_page_refs: titles(&url.ancestor_page_refs),
+                descendant_page_refs: titles(&url.descendant_page_refs),
+                same_block_page_refs: titles(&url.same_block_page_refs),
+                metadata: url.metadata.map(Into::into),
+            },
+            SearchItem::Archive(archive) => SearchItemDto::Archive {
+                url: archive.url.as_str().to_string(),
+                title: archive.title,
+                excerpt: archive.excerpt,
+                archived_at: archive.archived_at.timestamp().max(0) as u64,
+            },
+        };
+        SearchResultDto {
+            score: f64::from(result.score.value()),
+            item,
+            explanation: result.explanation,
+        }
+    }
+}
+
+/// Body of `POST /api/pages` and `PUT /api/pages/{id}`
+#[derive(Debug, Deserialize)]
+pub struct PageInput {
+    pub title: String,
+    /// The page's blocks as Logseq markdown
+    #[serde(default)]
+    pub content: String,
+}
+
+/// An entry of `GET /api/pages`
+#[derive(Debug, Serialize)]
+pub struct PageSummaryDto {
+    pub id: String,
+    pub title: String,
+    pub block_count: usize,
+    /// Seconds since the Unix epoch of the latest `updated-at` property
+    pub updated_at: Option<i64>,
+    /// The first few top-level blocks, one line each
+    pub preview: Vec<String>,
+    /// Seconds since the Unix epoch of the last change indexed, if timestamps are kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<i64>,
+    /// Seconds since the Unix epoch of the last embedding, if timestamps are kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedded_at: Option<i64>,
+}
+
+impl From<PageSummary> for PageSummaryDto {
+    fn from(summary: PageSummary) -> Self {
+        PageSummaryDto {
+            id: summary.page_id.as_str().to_string(),
+            title: summary.title,
+            block_count: summary.block_count,
+            updated_at: summary.updated_at.map(|time| time.timestamp()),
+            preview: summary.preview,
+            indexed_at: summary.indexed_at.map(|time| time.timestamp()),
+            embedded_at: summary.embedded_at.map(|time| time.timestamp()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageDto {
+    pub id: String,
+    pub title: String,
+    pub file_path: Option<PathBuf>,
+    pub properties: BTreeMap<String, String>,
+    /// The page's blocks as Logseq markdown
+    pub content: String,
+    pub blocks: Vec<BlockDto>,
+}
+
+impl From<&Page> for PageDto {
+    fn from(page: &Page) -> Self {
+        PageDto {
+            id: page.id().as_str().to_string(),
+            title: page.title().to_string(),
+            file_path: page.file_path().map(PathBuf::from),
+            properties: page.properties().clone(),
+            content: LogseqMarkdownWriter::render(page),
+            blocks: page
+                .root_blocks()
+                .into_iter()
+                .map(|block| BlockDto::new(page, block))
+                .collect(),
+        }
+    }
+}
+
+/// A block and, nested under it, its children
+#[derive(Debug, Serialize)]
+pub struct BlockDto {
+    pub id: String,
+    pub content: String,
+    pub properties: BTreeMap<String, String>,
+    pub page_references: Vec<String>,
+    pub urls: Vec<String>,
+    pub children: Vec<BlockDto>,
+}
+
+impl BlockDto {
+    fn new(page: &Page, block: &Block) -> Self {
+        BlockDto {
+            id: block.id().as_str().to_string(),
+            content: block.content().as_str().to_string(),
+            properties: block.properties().clone(),
+            page_references: titles(block.page_references()),
+            urls: block.urls().iter().map(|url| url.as_str().to_string()).collect(),
+            children: block
+                .child_ids()
+                .iter()
+                .filter_map(|child_id| page.get_block(child_id))
+                .map(|child| BlockDto::new(page, child))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BacklinkDto {
+    pub page_id: String,
+    pub page_title: String,
+    pub block_id: String,
+    pub block_content: String,
+    pub hierarchy_path: Vec<String>,
+    /// `Page > parent > block`, usable in place of `block_id`
+    pub block_path: Option<String>,
+}
+
+impl From<Backlink> for BacklinkDto {
+    fn from(backlink: Backlink) -> Self {
+        BacklinkDto {
+            page_id: backlink.page_id.as_str().to_string(),
+            page_title: backlink.page_title,
+            block_id: backlink.block_id.as_str().to_string(),
+            block_content: backlink.block_content,
+            hierarchy_path: backlink.hierarchy_path,
+            block_path: backlink.block_path.map(|path| path.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkDto {
+    pub url: String,
+    pub block_id: String,
+    pub block_content: String,
+    pub hierarchy_path: Vec<String>,
+    pub related_page_refs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub same_block_page_refs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<UrlMetadataDto>,
+}
+
+impl From<UrlWithContext> for LinkDto {
+    fn from(link: UrlWithContext) -> Self {
+        LinkDto {
+            url: link.url.as_str().to_string(),
+            block_id: link.block_id.as_str().to_string(),
+            block_content: link.block_content,
+            hierarchy_path: link.hierarchy_path,
+            related_page_refs: titles(&link.related_page_refs),
+            same_block_page_refs: titles(&link.same_block_page_refs),
+            metadata: link.metadata.map(Into::into),
+        }
+    }
+}
+
+/// What a URL's page says about itself, when it was fetched
+#[derive(Debug, Serialize)]
+pub struct UrlMetadataDto {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub favicon: Option<String>,
+}
+
+impl From<UrlMetadata> for UrlMetadataDto {
+    fn from(metadata: UrlMetadata) -> Self {
+        UrlMetadataDto {
+            title: metadata.title,
+            description: metadata.description,
+            favicon: metadata.favicon,
+        }
+    }
+}
+
+/// Query string of `GET /api/pages/{id}/links`
+#[derive(Debug, Deserialize)]
+pub struct LinksQuery {
+    /// Include the page references of the block holding each URL
+    #[serde(default)]
+    pub same_block_refs: bool,
+}
+
+/// Query string of `DELETE /api/pages`: which pages to delete, at least one
+/// criterion being needed
+#[derive(Debug, Deserialize)]
+pub struct DeletePagesQuery {
+    pub namespace: Option<String>,
+    pub tag: Option<String>,
+    /// Title glob, where `*` is any text and `?` any one character
+    pub title: Option<String>,
+    /// List the matching pages without deleting them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl From<&DeletePagesQuery> for PageFilter {
+    fn from(query: &DeletePagesQuery) -> Self {
+        PageFilter {
+            namespace: query.namespace.clone(),
+            tag: query.tag.clone(),
+            title_pattern: query.title.clone(),
+        }
+    }
+}
+
+/// Response of `DELETE /api/pages`
+#[derive(Debug, Serialize)]
+pub struct DeletedPagesDto {
+    pub dry_run: bool,
+    /// The pages deleted, or that would be on a dry run
+    pub pages: Vec<PageSummaryDto>,
+    /// Pages whose embeddings are left for garbage collection
+    pub embedding_errors: usize,
+}
+
+/// Query string of `GET /api/pages/{id}/outline`
+#[derive(Debug, Deserialize)]
+pub struct OutlineQuery {
+    /// Levels of blocks included; all of them when unset
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageOutlineDto {
+    pub id: String,
+    pub title: String,
+    pub blocks: Vec<OutlineBlockDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutlineBlockDto {
+    pub id: String,
+    pub content: String,
+    pub children: Vec<OutlineBlockDto>,
+    /// Children below the requested depth
+    #[serde(skip_serializing_if = "is_zero")]
+    pub hidden_children: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl From<PageOutline> for PageOutlineDto {
+    fn from(outline: PageOutline) -> Self {
+        PageOutlineDto {
+            id: outline.page_id.as_str().to_string(),
+            title: outline.title,
+            blocks: outline.blocks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<OutlineBlock> for OutlineBlockDto {
+    fn from(block: OutlineBlock) -> Self {
+        OutlineBlockDto {
+            id: block.block_id.as_str().to_string(),
+            content: block.content,
+            children: block.children.into_iter().map(Into::into).collect(),
+            hidden_children: block.hidden_children,
+        }
+    }
+}
+
+/// Query string of `GET /api/blocks`: a block path or a block ID
+#[derive(Debug, Deserialize)]
+pub struct BlockQuery {
+    pub path: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockAddressDto {
+    pub page_id: String,
+    pub block_id: String,
+    pub block_path: String,
+    pub content: String,
+}
+
+impl From<BlockAddress> for BlockAddressDto {
+    fn from(address: BlockAddress) -> Self {
+        BlockAddressDto {
+            page_id: address.page_id.as_str().to_string(),
+            block_id: address.block_id.as_str().to_string(),
+            block_path: address.block_path.to_string(),
+            content: address.content,
+        }
+    }
+}
+
+/// Query string of `GET /api/urls`
+#[derive(Debug, Deserialize)]
+pub struct UrlQuery {
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageConnectionDto {
+    pub page_id: String,
+    pub page_title: String,
+    pub blocks_with_url: Vec<String>,
+}
+
+impl From<PageConnection> for PageConnectionDto {
+    fn from(connection: PageConnection) -> Self {
+        PageConnectionDto {
+            page_id: connection.page_id.as_str().to_string(),
+            page_title: connection.page_title,
+            blocks_with_url: connection
+                .blocks_with_url
+                .iter()
+                .map(|block_id| block_id.as_str().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Body of `POST /api/import`
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    /// A graph directory, a `.zip` backup, or a `.json`/`.edn` graph export
+    pub path: PathBuf,
+    /// Only import files changed since the last import of the directory
+    #[serde(default)]
+    pub incremental: bool,
+}
+
+/// Sync health of one graph
+#[derive(Debug, Serialize)]
+pub struct SyncStatusDto {
+    pub graph_id: String,
+    pub watching: bool,
+    /// Seconds since the Unix epoch
+    pub last_sync: Option<u64>,
+    pub pending_operations: usize,
+    pub files_created: usize,
+    pub files_updated: usize,
+    pub files_deleted: usize,
+    pub files_renamed: usize,
+    pub conflicts: usize,
+    pub errors: usize,
+    pub recent_errors: Vec<FileErrorDto>,
+}
+
+impl SyncStatusDto {
+    pub fn new(graph_id: String, status: SyncStatus) -> Self {
+        SyncStatusDto {
+            graph_id,
+            watching: status.watching,
+            last_sync: status.last_sync.map(unix_seconds),
+            pending_operations: status.pending_operations,
+            files_created: status.files_created,
+            files_updated: status.files_updated,
+            files_deleted: status.files_deleted,
+            files_renamed: status.files_renamed,
+            conflicts: status.conflicts,
+            errors: status.errors,
+            recent_errors: status.recent_errors.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Query string of `GET /api/events`
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Only sync events of this graph; import events are always sent
+    pub graph: Option<String>,
+}
+
+/// Data of an `import` server-sent event
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImportEventDto {
+    Started {
+        total_files: usize,
+    },
+    FileProcessed {
+        file_path: PathBuf,
+        files_processed: usize,
+        total_files: usize,
+        percentage: f64,
+    },
+    QueuedForEmbedding {
+        pages_queued: usize,
+        total_queued: usize,
+    },
+    Completed {
+        pages_imported: usize,
+        duration_ms: u64,
+    },
+    Failed {
+        error: String,
+        files_processed: usize,
+    },
+    Cancelled {
+        pages_imported: usize,
+        files_processed: usize,
+    },
+}
+
+impl From<ImportProgressEvent> for ImportEventDto {
+    fn from(event: ImportProgressEvent) -> Self {
+        match event {
+            ImportProgressEvent::Started { total_files } => ImportEventDto::Started { total_files },
+            ImportProgressEvent::FileProcessed { file_path, progress } => ImportEventDto::FileProcessed {
+                file_path,
+                files_processed: progress.files_processed(),
+                total_files: progress.total_files(),
+                percentage: progress.percentage(),
+            },
+            ImportProgressEvent::QueuedForEmbedding {
+                pages_queued,
+                total_queued,
+            } => ImportEventDto::QueuedForEmbedding {
+                pages_queued,
+                total_queued,
+            },
+            ImportProgressEvent::Completed {
+                pages_imported,
+                duration_ms,
+            } => ImportEventDto::Completed {
+                pages_imported,
+                duration_ms,
+            },
+            ImportProgressEvent::Failed { error, files_processed } => ImportEventDto::Failed {
+                error,
+                files_processed,
+            },
+            ImportProgressEvent::Cancelled {
+                pages_imported,
+                files_processed,
+            } => ImportEventDto::Cancelled {
+                pages_imported,
+                files_processed,
+            },
+        }
+    }
+}
+
+fn titles(references: &[PageReference]) -> Vec<String> {
+    references.iter().map(|reference| reference.title().to_string()).collect()
+}
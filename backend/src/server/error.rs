@@ -0,0 +1,122 @@
+/// Mapping of application errors to HTTP responses
+use crate::application::services::{EmbeddingError, ImportError};
+use crate::application::use_cases::{DeletePagesError, SearchError};
+use crate::domain::DomainError;
+use axum::http::header::WWW_AUTHENTICATE;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+
+    /// No token, or one that isn't valid
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// A valid token without the route's scope
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The server was started without the service a route needs
+    #[error("{0}")]
+    Unavailable(String),
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+
+    #[error(transparent)]
+    Import(#[from] ImportError),
+
+    #[error(transparent)]
+    Search(#[from] SearchError),
+
+    #[error(transparent)]
+    DeletePages(#[from] DeletePagesError),
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// JSON body of every error response
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Domain(e) => domain_status(e),
+            ApiError::Import(e) => match e {
+                ImportError::InvalidDirectory(_) | ImportError::CheckpointsUnavailable => {
+                    StatusCode::BAD_REQUEST
+                }
+                ImportError::FileSystem(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    StatusCode::NOT_FOUND
+                }
+                ImportError::Parse(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                // Logseq isn't running, or refused the call
+                ImportError::LogseqApi(_) => StatusCode::BAD_GATEWAY,
+                ImportError::DuplicateTitle { .. } => StatusCode::CONFLICT,
+                ImportError::Repository(e) => domain_status(e),
+                ImportError::FileSystem(_) | ImportError::Domain(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            ApiError::Search(e) => match e {
+                SearchError::GraphNotIndexed(_) => StatusCode::NOT_FOUND,
+                SearchError::Domain(e) | SearchError::Embedding(EmbeddingError::Domain(e)) => {
+                    domain_status(e)
+                }
+                // Qdrant is down or refused the request
+                SearchError::Embedding(EmbeddingError::VectorStore(_)) => StatusCode::BAD_GATEWAY,
+                SearchError::Embedding(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            ApiError::DeletePages(e) => match e {
+                DeletePagesError::EmptyFilter => StatusCode::BAD_REQUEST,
+                DeletePagesError::Domain(e) => domain_status(e),
+            },
+        }
+    }
+}
+
+fn domain_status(error: &DomainError) -> StatusCode {
+    match error {
+        DomainError::InvalidValue(_) => StatusCode::BAD_REQUEST,
+        DomainError::NotFound(_) => StatusCode::NOT_FOUND,
+        DomainError::BusinessRuleViolation(_) => StatusCode::CONFLICT,
+        DomainError::InvalidOperation(_) | DomainError::Repository(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            tracing::error!("API request failed: {}", self);
+        }
+        let mut response = (status, Json(ErrorBody { error: self.to_string() })).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
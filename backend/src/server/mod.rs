@@ -0,0 +1,10 @@
+pub mod auth;
+pub mod dto;
+pub mod error;
+pub mod routes;
+pub mod state;
+
+pub use auth::{ApiAuth, Scope, TokenIntrospection};
+pub use error::{ApiError, ApiResult};
+pub use routes::{router, serve};
+pub use state::ApiState;
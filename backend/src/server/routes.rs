@@ -0,0 +1,823 @@
+/// HTTP routes of the REST API and their handlers
+use super::dto::{
+    BacklinkDto, BlockAddressDto, BlockQuery, DeletePagesQuery, DeletedPagesDto, EventsQuery, ImportEventDto,
+    ImportRequest, ImportSummaryDto, LinkDto, LinksQuery, OutlineQuery, PageConnectionDto, PageDto, PageInput,
+    PageOutlineDto, PageSummaryDto, SearchQuery, SearchResultDto, SyncEventDto, SyncStatusDto, UrlQuery,
+};
+use super::auth::authorize;
+use super::error::{ApiError, ApiResult};
+use super::state::ApiState;
+use crate::application::dto::SearchRequest;
+use crate::application::repositories::PageRepository;
+use crate::application::services::{HealthReport, ProgressCallback};
+use crate::application::use_cases::{
+    DeletePages, GetBacklinks, GetLinksForPage, GetPageOutline, GetPagesForUrl, ResolveBlockPath,
+    SearchPagesAndBlocks,
+};
+use crate::domain::aggregates::Page;
+use crate::domain::base::Entity;
+use crate::domain::events::{PageCreated, PageDeleted, PageUpdated};
+use crate::domain::value_objects::{BlockId, BlockPath, GraphId, LogseqDirectoryPath, PageId, Url};
+use crate::infrastructure::file_system::detect_layout;
+use crate::infrastructure::parsers::LogseqMarkdownParser;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{middleware, Json, Router};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Build the API's routes
+///
+/// - `GET /api/search?q=..&mode=traditional|semantic&results=all|pages|blocks|urls&threshold=..&graph=..`,
+///   where `same_block_refs=true` and `explain=true` add detail to results,
+///   `dates=true` searches journals of the dates `q` mentions, and `excerpt=..`
+///   cuts long blocks to that many characters around the match
+/// - `GET /api/pages`, `POST /api/pages`
+/// - `DELETE /api/pages?namespace=..&tag=..&title=..&dry_run=true`: every page
+///   matching the filter, with its embeddings
+/// - `GET`/`PUT`/`DELETE /api/pages/{id}`
+/// - `GET /api/pages/{id}/backlinks`, `GET /api/pages/{id}/links?same_block_refs=true`
+/// - `GET /api/pages/{id}/outline?depth=..`: the block tree, contents only
+/// - `GET /api/blocks?path=..` or `GET /api/blocks?id=..`: a block's ID, its
+///   `Page > parent > block` path and its whole content, from either
+/// - `GET /api/urls?url=..`: pages linking to a URL
+/// - `POST /api/import`
+/// - `GET /api/sync/status`
+/// - `GET /api/events?graph=..`: server-sent `sync` and `import` events
+/// - `POST /api/graphql`, with the `graphql` feature
+/// - `GET /health`: each component's status, `503` if any is unhealthy
+///
+/// With [`ApiState::with_auth`], every route but `/health` needs a bearer
+/// token; see [`required_scope`](super::auth::required_scope) for the scope
+/// each needs.
+pub fn router<R>(state: ApiState<R>) -> Router
+where
+    R: PageRepository + Send + Sync + 'static,
+{
+    #[cfg(feature = "graphql")]
+    let graphql_route = post(graphql::<R>).with_state(crate::graphql::build_schema(
+        state.repository.clone(),
+        state.embedding_service.clone(),
+        state.visibility,
+    ));
+
+    let router = Router::new()
+        .route("/api/search", get(search::<R>))
+        .route(
+            "/api/pages",
+            get(list_pages::<R>).post(create_page::<R>).delete(delete_pages::<R>),
+        )
+        .route(
+            "/api/pages/{id}",
+            get(get_page::<R>).put(update_page::<R>).delete(delete_page::<R>),
+        )
+        .route("/api/pages/{id}/backlinks", get(backlinks::<R>))
+        .route("/api/pages/{id}/links", get(links::<R>))
+        .route("/api/pages/{id}/outline", get(outline::<R>))
+        .route("/api/blocks", get(block_address::<R>))
+        .route("/api/urls", get(pages_for_url::<R>))
+        .route("/api/import", post(import::<R>))
+        .route("/api/sync/status", get(sync_status::<R>))
+        .route("/api/events", get(events::<R>));
+    #[cfg(feature = "graphql")]
+    let router = router.route("/api/graphql", graphql_route);
+    let router = match state.auth.clone() {
+        Some(auth) => router.layer(middleware::from_fn_with_state(auth, authorize)),
+        None => router,
+    };
+    // Added after the auth layer so probes need no token
+    router.route("/health", get(health::<R>)).with_state(state)
+}
+
+/// Serve the API on an address until the process stops
+pub async fn serve<R>(address: SocketAddr, state: ApiState<R>) -> std::io::Result<()>
+where
+    R: PageRepository + Send + Sync + 'static,
+{
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    tracing::info!("Serving the API on {}", listener.local_addr()?);
+    axum::serve(listener, router(state)).await
+}
+
+/// Execute a GraphQL request; errors are reported in the response body
+#[cfg(feature = "graphql")]
+async fn graphql<R: PageRepository + Send + Sync + 'static>(
+    State(schema): State<crate::graphql::LogjamSchema<R>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn search<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Json<Vec<SearchResultDto>>> {
+    let mut request = SearchRequest::new(query.q)
+        .with_search_type(query.mode.into())
+        .with_result_type(query.results.into());
+    if let Some(threshold) = query.threshold {
+        request = request.with_score_threshold(threshold);
+    }
+    if let Some(graph) = query.graph {
+        request = request.with_graph(GraphId::new(graph)?);
+    }
+    if query.same_block_refs {
+        request = request.with_same_block_refs();
+    }
+    if query.explain {
+        request = request.with_explain();
+    }
+    if query.dates {
+        request = request.with_date_expressions();
+    }
+    if let Some(excerpt) = query.excerpt {
+        request = request.with_excerpt_length(excerpt);
+    }
+
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let mut use_case = match &state.embedding_service {
+        Some(embedding_service) => {
+            SearchPagesAndBlocks::with_embedding_service(&repository, embedding_service.clone())
+        }
+        None => SearchPagesAndBlocks::new(&repository),
+    }
+    .with_tokenizer(state.keyword_tokenizer.clone())
+    .with_ranking(state.ranking);
+    if let Some(url_metadata) = &state.url_metadata {
+        use_case = use_case.with_url_metadata(url_metadata);
+    }
+    if let Some(web_archiver) = &state.web_archiver {
+        use_case = use_case.with_web_archiver(web_archiver);
+    }
+    if let Some(search_cache) = &state.search_cache {
+        use_case = use_case.with_cache(search_cache);
+    }
+    let results = use_case.execute(request).await?;
+    Ok(Json(results.into_iter().map(Into::into).collect()))
+}
+
+async fn list_pages<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+) -> ApiResult<Json<Vec<PageSummaryDto>>> {
+    let mut pages = state.visible(&*state.repository.lock().await).find_summaries()?;
+    pages.sort_by(|a, b| a.title.cmp(&b.title));
+    Ok(Json(pages.into_iter().map(Into::into).collect()))
+}
+
+async fn get_page<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<PageDto>> {
+    let page = find_page(&state.visible(&*state.repository.lock().await), &PageId::new(id)?)?;
+    Ok(Json(PageDto::from(&page)))
+}
+
+async fn create_page<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Json(input): Json<PageInput>,
+) -> ApiResult<(StatusCode, Json<PageDto>)> {
+    let page_id = PageId::new(format!("page-{}", uuid::Uuid::new_v4()))?;
+    let page = parse_page(&input, page_id)?;
+
+    let mut repository = state.repository.lock().await;
+    ensure_title_free(&state.visible(&repository), &page)?;
+    let event = PageCreated {
+        page_id: page.id().clone(),
+        title: page.title().to_string(),
+    };
+    let dto = PageDto::from(&page);
+    repository.save(page)?;
+    state.notify(&event);
+    Ok((StatusCode::CREATED, Json(dto)))
+}
+
+/// Replace a page's title and blocks, keeping its ID, file and page properties
+///
+/// A page the visibility policy hides answers `404`, as it does for reads.
+/// A page with private blocks answers `409 Conflict`: the client never saw
+/// those blocks, so replacing the page would silently delete them.
+async fn update_page<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+    Json(input): Json<PageInput>,
+) -> ApiResult<Json<PageDto>> {
+    let mut repository = state.repository.lock().await;
+    let page_id = PageId::new(id)?;
+    let existing = find_page(&*repository, &page_id)?;
+    if state.visibility.is_some() {
+        let visible = find_page(&state.visible(&repository), &page_id)?;
+        if visible.all_blocks().count() < existing.all_blocks().count() {
+            return Err(ApiError::Conflict(format!(
+                "Page {} has private blocks; edit it in the graph instead",
+                page_id.as_str()
+            )));
+        }
+    }
+
+    let mut page = parse_page(&input, existing.id().clone())?;
+    ensure_title_free(&state.visible(&repository), &page)?;
+    if let Some(file_path) = existing.file_path() {
+        page.set_file_path(file_path.to_path_buf());
+    }
+    for (key, value) in existing.properties() {
+        page.set_property(key.clone(), value.clone());
+    }
+
+    let event = PageUpdated {
+        page_id: page.id().clone(),
+        title: Some(page.title().to_string()),
+    };
+    let dto = PageDto::from(&page);
+    repository.save(page)?;
+    state.notify(&event);
+    Ok(Json(dto))
+}
+
+async fn delete_page<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let page_id = PageId::new(id)?;
+    let mut repository = state.repository.lock().await;
+    if state.visibility.is_some() && state.visible(&repository).find_by_id(&page_id)?.is_none() {
+        return Err(not_found(&page_id));
+    }
+    if repository.delete(&page_id)? {
+        drop(repository);
+        state.notify(&PageDeleted { page_id });
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(not_found(&page_id))
+    }
+}
+
+/// Delete the pages matching the query's filter, or list them on a dry run
+async fn delete_pages<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Query(query): Query<DeletePagesQuery>,
+) -> ApiResult<Json<DeletedPagesDto>> {
+    let mut repository = state.repository.lock().await;
+    let mut use_case = DeletePages::new(&mut *repository, (&query).into());
+    if let Some(embedding_service) = &state.embedding_service {
+        use_case = use_case.with_embedding_service(embedding_service.clone());
+    }
+    if let Some(visibility) = state.visibility {
+        use_case = use_case.with_visibility(visibility);
+    }
+    if query.dry_run {
+        let pages = use_case.dry_run()?;
+        return Ok(Json(DeletedPagesDto {
+            dry_run: true,
+            pages: pages.into_iter().map(Into::into).collect(),
+            embedding_errors: 0,
+        }));
+    }
+
+    let deleted = use_case.execute().await?;
+    for event in deleted.events() {
+        state.notify(&event);
+    }
+    Ok(Json(DeletedPagesDto {
+        dry_run: false,
+        pages: deleted.pages.into_iter().map(Into::into).collect(),
+        embedding_errors: deleted.embedding_errors,
+    }))
+}
+
+async fn backlinks<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Vec<BacklinkDto>>> {
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let backlinks = GetBacklinks::new(&repository).execute(&PageId::new(id)?)?;
+    Ok(Json(backlinks.into_iter().map(Into::into).collect()))
+}
+
+async fn links<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+    Query(query): Query<LinksQuery>,
+) -> ApiResult<Json<Vec<LinkDto>>> {
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let mut use_case = GetLinksForPage::new(&repository);
+    if let Some(url_metadata) = &state.url_metadata {
+        use_case = use_case.with_url_metadata(url_metadata);
+    }
+    if query.same_block_refs {
+        use_case = use_case.with_same_block_refs();
+    }
+    let links = use_case.execute(&PageId::new(id)?)?;
+    Ok(Json(links.into_iter().map(Into::into).collect()))
+}
+
+async fn outline<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Path(id): Path<String>,
+    Query(query): Query<OutlineQuery>,
+) -> ApiResult<Json<PageOutlineDto>> {
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let mut use_case = GetPageOutline::new(&repository);
+    if let Some(depth) = query.depth {
+        use_case = use_case.with_max_depth(depth);
+    }
+    Ok(Json(use_case.execute(&PageId::new(id)?)?.into()))
+}
+
+/// Resolve a block path to the block's ID, or a block ID to its path
+async fn block_address<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Query(query): Query<BlockQuery>,
+) -> ApiResult<Json<BlockAddressDto>> {
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let resolve = ResolveBlockPath::new(&repository);
+    let (address, wanted) = match (query.path, query.id) {
+        (Some(path), None) => (resolve.resolve(&BlockPath::parse(&path)?)?, path),
+        (None, Some(id)) => (resolve.path_of(&BlockId::new(id.clone())?)?, id),
+        _ => return Err(ApiError::BadRequest("Give either a block path or a block ID".to_string())),
+    };
+    let address = address.ok_or_else(|| ApiError::NotFound(format!("Block {} not found", wanted)))?;
+    Ok(Json(address.into()))
+}
+
+async fn pages_for_url<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Query(query): Query<UrlQuery>,
+) -> ApiResult<Json<Vec<PageConnectionDto>>> {
+    let store = state.repository.lock().await;
+    let repository = state.visible(&store);
+    let connections = GetPagesForUrl::new(&repository).execute(&Url::new(query.url)?)?;
+    Ok(Json(connections.into_iter().map(Into::into).collect()))
+}
+
+/// Import a graph directory, zip backup or graph export, answering once it's done
+async fn import<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Json(request): Json<ImportRequest>,
+) -> ApiResult<Json<ImportSummaryDto>> {
+    let import_service = state
+        .import_service
+        .as_ref()
+        .ok_or_else(|| ApiError::Unavailable("Imports are not enabled on this server".to_string()))?;
+
+    let path = request.path;
+    if !path.exists() {
+        return Err(ApiError::NotFound(format!("{} does not exist", path.display())));
+    }
+
+    let import_events = state.import_events.clone();
+    let progress: ProgressCallback = Arc::new(move |event| {
+        // No listeners is fine
+        let _ = import_events.send(event);
+    });
+
+    let mut import_service = import_service.lock().await;
+    let summary = if request.incremental {
+        if !path.is_dir() {
+            return Err(ApiError::BadRequest(
+                "Incremental imports need a graph directory".to_string(),
+            ));
+        }
+        let directory = LogseqDirectoryPath::with_layout(&path, detect_layout(&path))?;
+        import_service.import_directory_incremental(directory, Some(progress)).await?
+    } else {
+        import_service.import_path(&path, Some(progress)).await?
+    };
+    state.pages_changed();
+
+    Ok(Json(summary.into()))
+}
+
+async fn health<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+) -> (StatusCode, Json<HealthReport>) {
+    let report = state.health_service().check().await;
+    let status = if report.is_healthy() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn sync_status<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+) -> ApiResult<Json<Vec<SyncStatusDto>>> {
+    let graphs = state
+        .graphs
+        .as_ref()
+        .ok_or_else(|| ApiError::Unavailable("Sync is not enabled on this server".to_string()))?;
+
+    let mut statuses = Vec::new();
+    for graph_id in graphs.graph_ids().await {
+        // A graph removed since the IDs were listed is left out
+        if let Some(service) = graphs.service(&graph_id).await {
+            statuses.push(SyncStatusDto::new(graph_id, service.status()));
+        }
+    }
+    Ok(Json(statuses))
+}
+
+/// Stream sync events of the graph manager's graphs and progress of API
+/// imports as server-sent events named `sync` and `import`
+///
+/// A client too slow to keep up misses events rather than the stream ending;
+/// `/api/sync/status` has the totals. With a visibility policy, events about
+/// hidden pages' files are left out.
+async fn events<R: PageRepository + Send + Sync + 'static>(
+    State(state): State<ApiState<R>>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let imports = BroadcastStream::new(state.import_events.subscribe())
+        .filter_map(|event| sse_event("import", &ImportEventDto::from(event.ok()?)));
+
+    let sync: EventStream = match &state.graphs {
+        Some(graphs) => {
+            let state = state.clone();
+            let events = BroadcastStream::new(graphs.subscribe()).then(move |event| {
+                let state = state.clone();
+                let graph = query.graph.clone();
+                async move {
+                    let event = event.ok()?;
+                    let other_graph = graph.is_some_and(|graph| graph != event.graph_id);
+                    if other_graph || !state.shows_sync_event(&event.event).await {
+                        return None;
+                    }
+                    sse_event("sync", &SyncEventDto::from(event))
+                }
+            });
+            Box::pin(events.filter_map(|event| event))
+        }
+        None => Box::pin(tokio_stream::pending()),
+    };
+
+    Sse::new(imports.merge(sync).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Event> + Send>>;
+
+fn sse_event(name: &str, data: &impl Serialize) -> Option<Event> {
+    Event::default().event(name).json_data(data).ok()
+}
+
+fn find_page<R: PageRepository>(repository: &R, page_id: &PageId) -> ApiResult<Page> {
+    repository.find_by_id(page_id)?.ok_or_else(|| not_found(page_id))
+}
+
+fn not_found(page_id: &PageId) -> ApiError {
+    ApiError::NotFound(format!("Page {} not found", page_id.as_str()))
+}
+
+fn parse_page(input: &PageInput, page_id: PageId) -> ApiResult<Page> {
+    let title = input.title.trim();
+    if title.is_empty() {
+        return Err(ApiError::BadRequest("Page title must not be empty".to_string()));
+    }
+    LogseqMarkdownParser::parse_content(&input.content, page_id, title.to_string())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid page content: {}", e)))
+}
+
+/// Titles are unique, compared case-insensitively as Logseq does
+///
+/// Handlers check against the pages the visibility policy shows, so a
+/// conflict never gives away a hidden page's title.
+fn ensure_title_free<R: PageRepository>(repository: &R, page: &Page) -> ApiResult<()> {
+    let title = page.title().to_lowercase();
+    let taken = repository
+        .find_all()?
+        .into_iter()
+        .any(|other| other.id() != page.id() && other.title().to_lowercase() == title);
+    if taken {
+        return Err(ApiError::Conflict(format!(
+            "A page titled \"{}\" already exists",
+            page.title()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{ImportService, SearchCache, VisibilityPolicy};
+    use crate::domain::DomainResult;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Method, Request};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    #[derive(Default)]
+    struct InMemoryPageRepository {
+        pages: HashMap<PageId, Page>,
+    }
+
+    impl PageRepository for InMemoryPageRepository {
+        fn save(&mut self, page: Page) -> DomainResult<()> {
+            self.pages.insert(page.id().clone(), page);
+            Ok(())
+        }
+
+        fn find_by_id(&self, id: &PageId) -> DomainResult<Option<Page>> {
+            Ok(self.pages.get(id).cloned())
+        }
+
+        fn find_by_title(&self, title: &str) -> DomainResult<Option<Page>> {
+            Ok(self.pages.values().find(|p| p.title() == title).cloned())
+        }
+
+        fn find_all(&self) -> DomainResult<Vec<Page>> {
+            Ok(self.pages.values().cloned().collect())
+        }
+
+        fn delete(&mut self, id: &PageId) -> DomainResult<bool> {
+            Ok(self.pages.remove(id).is_some())
+        }
+    }
+
+    fn app() -> Router {
+        router(ApiState::new(InMemoryPageRepository::default()).with_search_cache(Arc::new(SearchCache::default())))
+    }
+
+    async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json");
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+        let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, json)
+    }
+
+    async fn create(app: &Router, title: &str, content: &str) -> String {
+        let (status, page) = send(
+            app,
+            Method::POST,
+            "/api/pages",
+            Some(json!({ "title": title, "content": content })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        page["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_page_crud() {
+        let app = app();
+        let id = create(&app, "Rust", "- Ownership\n\t- Borrowing").await;
+
+        let (status, page) = send(&app, Method::GET, &format!("/api/pages/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["title"], "Rust");
+        assert_eq!(page["blocks"][0]["content"], "Ownership");
+        assert_eq!(page["blocks"][0]["children"][0]["content"], "Borrowing");
+
+        let (_, outline) = send(&app, Method::GET, &format!("/api/pages/{}/outline?depth=1", id), None).await;
+        assert_eq!(outline["blocks"][0]["content"], "Ownership");
+        assert_eq!(outline["blocks"][0]["children"], json!([]));
+        assert_eq!(outline["blocks"][0]["hidden_children"], 1);
+
+        let (status, _) = send(&app, Method::POST, "/api/pages", Some(json!({ "title": "rust" }))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, page) = send(
+            &app,
+            Method::PUT,
+            &format!("/api/pages/{}", id),
+            Some(json!({ "title": "Rust", "content": "- Lifetimes" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["content"], "- Lifetimes\n");
+
+        let (_, pages) = send(&app, Method::GET, "/api/pages", None).await;
+        assert_eq!(pages.as_array().unwrap().len(), 1);
+
+        let (status, _) = send(&app, Method::DELETE, &format!("/api/pages/{}", id), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, error) = send(&app, Method::GET, &format!("/api/pages/{}", id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(error["error"].as_str().unwrap().contains(&id));
+    }
+
+    #[tokio::test]
+    async fn test_visibility_hides_private_pages_and_blocks() {
+        let state = ApiState::new(InMemoryPageRepository::default()).with_visibility(VisibilityPolicy::new());
+        let app = router(state.clone());
+        let diary = create(&app, "Diary", "- private:: true\n- Dear diary").await;
+        let rust = create(&app, "Rust", "- Ownership\n- Salary\n  private:: true").await;
+
+        let (_, pages) = send(&app, Method::GET, "/api/pages", None).await;
+        assert_eq!(pages.as_array().unwrap().len(), 1);
+        let (_, page) = send(&app, Method::GET, &format!("/api/pages/{}", rust), None).await;
+        assert_eq!(page["blocks"].as_array().unwrap().len(), 1);
+        let (_, results) = send(&app, Method::GET, "/api/search?q=diary", None).await;
+        assert_eq!(results, json!([]));
+
+        let (status, _) = send(&app, Method::GET, &format!("/api/pages/{}", diary), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, Method::DELETE, &format!("/api/pages/{}", diary), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (_, preview) = send(&app, Method::DELETE, "/api/pages?title=Diary&dry_run=true", None).await;
+        assert_eq!(preview["pages"], json!([]));
+        let (_, deleted) = send(&app, Method::DELETE, "/api/pages?title=Diary", None).await;
+        assert_eq!(deleted["pages"], json!([]));
+        let repository = state.repository.lock().await;
+        assert!(repository.find_by_id(&PageId::new(diary).unwrap()).unwrap().is_some());
+        drop(repository);
+
+        // Putting back what GET showed would drop the private block
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            &format!("/api/pages/{}", rust),
+            Some(json!({ "title": "Rust", "content": "- Ownership" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let go = create(&app, "Go", "- Goroutines").await;
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            &format!("/api/pages/{}", go),
+            Some(json!({ "title": "Go", "content": "- Channels" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_delete_pages_by_filter() {
+        let app = app();
+        create(&app, "imports/One", "- a").await;
+        create(&app, "imports/Two", "- b").await;
+        create(&app, "Rust", "- c").await;
+
+        let (status, _) = send(&app, Method::DELETE, "/api/pages", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, preview) = send(&app, Method::DELETE, "/api/pages?namespace=imports&dry_run=true", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(preview["pages"].as_array().unwrap().len(), 2);
+        let (_, pages) = send(&app, Method::GET, "/api/pages", None).await;
+        assert_eq!(pages.as_array().unwrap().len(), 3);
+
+        let (_, deleted) = send(&app, Method::DELETE, "/api/pages?title=imports/*", None).await;
+        assert_eq!(deleted["dry_run"], false);
+        assert_eq!(deleted["pages"][0]["title"], "imports/One");
+        let (_, pages) = send(&app, Method::GET, "/api/pages", None).await;
+        assert_eq!(pages[0]["title"], "Rust");
+    }
+
+    #[tokio::test]
+    async fn test_search_backlinks_and_links() {
+        let app = app();
+        let rust = create(&app, "Rust", "- Systems language").await;
+        create(&app, "Reading", "- The book [[Rust]]\n\t- https://doc.rust-lang.org/book").await;
+
+        let (status, results) = send(&app, Method::GET, "/api/search?q=book&results=blocks", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let results = results.as_array().unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|result| result["type"] == "block" && result.get("explanation").is_none()));
+        let count = results.len();
+
+        let (_, cut) = send(&app, Method::GET, "/api/search?q=book&results=blocks&excerpt=4", None).await;
+        let cut = cut.as_array().unwrap();
+        assert!(cut.iter().all(|result| result["truncated"] == true));
+        assert!(cut.iter().any(|result| result["content"] == "…book…"));
+
+        let (_, explained) = send(&app, Method::GET, "/api/search?q=book&results=blocks&explain=true", None).await;
+        let explanation = &explained[0]["explanation"];
+        assert_eq!(explanation["field"], "content");
+        assert_eq!(explanation["kind"], "contains");
+        assert_eq!(explanation["adjustments"].as_array().unwrap().len(), 0);
+
+        let (_, backlinks) = send(&app, Method::GET, &format!("/api/pages/{}/backlinks", rust), None).await;
+        assert_eq!(backlinks[0]["page_title"], "Reading");
+        assert_eq!(backlinks[0]["block_path"], "Reading > The book [[Rust]]");
+
+        let (_, block) = send(&app, Method::GET, "/api/blocks?path=reading%20%3E%20The%20book%20[[Rust]]", None).await;
+        assert_eq!(block["block_id"], backlinks[0]["block_id"]);
+        let by_id = format!("/api/blocks?id={}", block["block_id"].as_str().unwrap());
+        let (_, same) = send(&app, Method::GET, &by_id, None).await;
+        assert_eq!(same["block_path"], "Reading > The book [[Rust]]");
+        assert_eq!(same["content"], "The book [[Rust]]");
+        let (status, _) = send(&app, Method::GET, "/api/blocks?path=Reading%20%3E%20Nothing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, pages) = send(&app, Method::GET, "/api/pages", None).await;
+        let reading = pages[0]["id"].as_str().unwrap();
+        let (_, links) = send(&app, Method::GET, &format!("/api/pages/{}/links", reading), None).await;
+        assert_eq!(links[0]["url"], "https://doc.rust-lang.org/book");
+
+        let (status, _) = send(&app, Method::GET, "/api/search?q=book&mode=fuzzy", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Writes move the search cache on
+        create(&app, "Library", "- Another book").await;
+        let (_, more) = send(&app, Method::GET, "/api/search?q=book&results=blocks", None).await;
+        assert_eq!(more.as_array().unwrap().len(), count + 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_and_sync_status_need_their_services() {
+        let app = app();
+        let (status, _) = send(&app, Method::GET, "/api/sync/status", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (status, _) = send(&app, Method::POST, "/api/import", Some(json!({ "path": "/tmp" }))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (status, health) = send(&app, Method::GET, "/health", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["status"], "healthy");
+        assert_eq!(health["components"][3]["status"], "disabled");
+
+        let missing = TempDir::new().unwrap();
+        let app = router(ApiState::new(InMemoryPageRepository::default()).with_database(missing.path().join("gone.db")));
+        let (status, health) = send(&app, Method::GET, "/health", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(health["components"][0]["component"], "database");
+        assert_eq!(health["components"][0]["status"], "unhealthy");
+
+        let graph = TempDir::new().unwrap();
+        std::fs::create_dir(graph.path().join("pages")).unwrap();
+        std::fs::write(graph.path().join("pages/rust.md"), "- Ownership").unwrap();
+
+        let state = ApiState::new(InMemoryPageRepository::default())
+            .with_import_service(ImportService::new(InMemoryPageRepository::default()));
+        let app = router(state);
+        let (status, summary) = send(
+            &app,
+            Method::POST,
+            "/api/import",
+            Some(json!({ "path": graph.path() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(summary["pages_imported"], 1);
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/import",
+            Some(json!({ "path": graph.path().join("pages/rust.md") })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_events_stream_import_progress() {
+        let graph = TempDir::new().unwrap();
+        std::fs::create_dir(graph.path().join("pages")).unwrap();
+        std::fs::write(graph.path().join("pages/rust.md"), "- Ownership").unwrap();
+
+        let state = ApiState::new(InMemoryPageRepository::default())
+            .with_import_service(ImportService::new(InMemoryPageRepository::default()));
+        let app = router(state);
+
+        let request = Request::builder().uri("/api/events").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let (status, _) = send(&app, Method::POST, "/api/import", Some(json!({ "path": graph.path() }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let mut body = response.into_body().into_data_stream();
+        let mut stream = String::new();
+        while !stream.contains(r#""type":"completed""#) {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+                .await
+                .expect("no completed event")
+                .unwrap()
+                .unwrap();
+            stream.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        assert!(stream.starts_with("event: import\ndata: {\"type\":\"started\",\"total_files\":1}"));
+        assert!(stream.contains(r#""type":"file_processed""#));
+    }
+
+    #[cfg(feature = "graphql")]
+    #[tokio::test]
+    async fn test_graphql_sees_pages_created_through_rest() {
+        let app = app();
+        create(&app, "Rust", "- Ownership").await;
+
+        let query = json!({ "query": "{ page(title: \"rust\") { blocks { content } } }" });
+        let (status, response) = send(&app, Method::POST, "/api/graphql", Some(query)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["data"]["page"]["blocks"][0]["content"], "Ownership");
+    }
+}
@@ -0,0 +1,239 @@
+/// Services shared by the HTTP API's request handlers
+use crate::application::repositories::PageRepository;
+use crate::application::services::{
+    EmbeddingService, GraphManager, HealthService, ImportProgressEvent, ImportService, SearchCache, SyncEvent,
+    UrlMetadataService, VisibilityPolicy, VisiblePages, WebArchiver, WebhookDispatcher, WebhookEvent,
+};
+use crate::application::use_cases::RankingConfig;
+use crate::infrastructure::text::KeywordTokenizer;
+use super::auth::ApiAuth;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+/// Import events buffered per `/api/events` client before it starts lagging
+const IMPORT_EVENT_CAPACITY: usize = 1024;
+
+/// What the API serves: the page repository, plus the optional services
+/// behind semantic search, imports and sync status
+///
+/// Routes whose service isn't configured answer `503 Service Unavailable`,
+/// except semantic search, which falls back to keyword search as
+/// [`SearchPagesAndBlocks`](crate::application::SearchPagesAndBlocks) does.
+/// Imports write through the import service's own repository; for the
+/// imported pages to be served, it must share its store with this one.
+pub struct ApiState<R: PageRepository> {
+    pub(crate) repository: Arc<Mutex<R>>,
+    pub(crate) embedding_service: Option<Arc<EmbeddingService>>,
+    /// One import runs at a time
+    pub(crate) import_service: Option<Arc<Mutex<ImportService<R>>>>,
+    pub(crate) graphs: Option<Arc<GraphManager<R>>>,
+    /// Progress of imports started through the API, for `/api/events`
+    pub(crate) import_events: broadcast::Sender<ImportProgressEvent>,
+    pub(crate) webhooks: Option<Arc<WebhookDispatcher>>,
+    pub(crate) url_metadata: Option<Arc<UrlMetadataService>>,
+    pub(crate) web_archiver: Option<Arc<WebArchiver>>,
+    pub(crate) search_cache: Option<Arc<SearchCache>>,
+    pub(crate) keyword_tokenizer: KeywordTokenizer,
+    pub(crate) ranking: RankingConfig,
+    /// Without it, every route is open to anyone who can reach the server
+    pub(crate) auth: Option<Arc<ApiAuth>>,
+    /// Without it, private pages and blocks are served like the others
+    pub(crate) visibility: Option<VisibilityPolicy>,
+    /// SQLite database `/health` checks
+    pub(crate) database: Option<PathBuf>,
+}
+
+impl<R: PageRepository> ApiState<R> {
+    pub fn new(repository: R) -> Self {
+        ApiState {
+            repository: Arc::new(Mutex::new(repository)),
+            embedding_service: None,
+            import_service: None,
+            graphs: None,
+            import_events: broadcast::channel(IMPORT_EVENT_CAPACITY).0,
+            webhooks: None,
+            url_metadata: None,
+            web_archiver: None,
+            search_cache: None,
+            keyword_tokenizer: KeywordTokenizer::default(),
+            ranking: RankingConfig::default(),
+            auth: None,
+            visibility: None,
+            database: None,
+        }
+    }
+
+    /// Enable semantic search
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        if let Some(search_cache) = &self.search_cache {
+            embedding_service.bump_on_write(search_cache.generation().clone());
+        }
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Enable `POST /api/import`
+    pub fn with_import_service(mut self, import_service: ImportService<R>) -> Self {
+        self.import_service = Some(Arc::new(Mutex::new(import_service)));
+        self
+    }
+
+    /// Report the sync status of the manager's graphs
+    pub fn with_graph_manager(mut self, graphs: Arc<GraphManager<R>>) -> Self
+    where
+        R: Send + Sync + 'static,
+    {
+        if let Some(search_cache) = &self.search_cache {
+            search_cache.follow_graph_events(graphs.subscribe());
+        }
+        self.graphs = Some(graphs);
+        self
+    }
+
+    /// Send webhooks for pages created, updated and deleted through the API
+    ///
+    /// Sync events of the graph manager's graphs aren't sent from here; see
+    /// [`WebhookDispatcher::forward_graph_events`].
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Include URLs' stored titles and descriptions in links and search results
+    pub fn with_url_metadata(mut self, url_metadata: Arc<UrlMetadataService>) -> Self {
+        self.url_metadata = Some(url_metadata);
+        self
+    }
+
+    /// Include archived web pages in searches for all result types
+    pub fn with_web_archiver(mut self, web_archiver: Arc<WebArchiver>) -> Self {
+        self.web_archiver = Some(web_archiver);
+        self
+    }
+
+    /// Answer repeated searches from a cache
+    ///
+    /// Pages written and imported through the API, synced by the graph
+    /// manager or embedded by the embedding service move the cache's
+    /// generation on (following the graph manager's events needs a Tokio
+    /// runtime). Whatever else writes the store must bump it too, or
+    /// searches may miss its changes.
+    pub fn with_search_cache(mut self, search_cache: Arc<SearchCache>) -> Self
+    where
+        R: Send + Sync + 'static,
+    {
+        if let Some(graphs) = &self.graphs {
+            search_cache.follow_graph_events(graphs.subscribe());
+        }
+        if let Some(embedding_service) = &self.embedding_service {
+            embedding_service.bump_on_write(search_cache.generation().clone());
+        }
+        self.search_cache = Some(search_cache);
+        self
+    }
+
+    /// Split keyword searches into terms with stemming or stop words (see
+    /// [`Config::keyword_tokenizer`](crate::config::Config::keyword_tokenizer))
+    pub fn with_keyword_tokenizer(mut self, keyword_tokenizer: KeywordTokenizer) -> Self {
+        self.keyword_tokenizer = keyword_tokenizer;
+        self
+    }
+
+    /// Score keyword search results with other weights (see
+    /// [`SearchConfig::ranking`](crate::config::SearchConfig::ranking))
+    pub fn with_ranking(mut self, ranking: RankingConfig) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Require a bearer token on every route, with the route's scope (see
+    /// [`required_scope`](super::auth::required_scope))
+    pub fn with_auth(mut self, auth: ApiAuth) -> Self {
+        self.auth = Some(Arc::new(auth));
+        self
+    }
+
+    /// Leave pages and blocks the policy hides out of every response; hidden
+    /// pages answer `404 Not Found`, as if they didn't exist
+    pub fn with_visibility(mut self, visibility: VisibilityPolicy) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Include the SQLite database at `path` in `/health`
+    pub fn with_database(mut self, path: impl Into<PathBuf>) -> Self {
+        self.database = Some(path.into());
+        self
+    }
+
+    /// `repository` as the API's visibility policy lets it be served
+    pub(crate) fn visible<'a>(&self, repository: &'a R) -> VisiblePages<'a, R> {
+        VisiblePages::new(repository, self.visibility)
+    }
+
+    /// Whether a sync event names only files whose pages the visibility
+    /// policy shows (see [`VisiblePages::shows_sync_event`])
+    pub(crate) async fn shows_sync_event(&self, event: &SyncEvent) -> bool {
+        if self.visibility.is_none() {
+            return true;
+        }
+        let store = self.repository.lock().await;
+        self.visible(&store).shows_sync_event(event)
+    }
+
+    /// The checks `/health` runs: the database, the embedding service and
+    /// the graph manager's watchers, whichever are configured
+    pub(crate) fn health_service(&self) -> HealthService<R>
+    where
+        R: Send + Sync + 'static,
+    {
+        let mut health = HealthService::new();
+        if let Some(database) = &self.database {
+            health = health.with_database(database);
+        }
+        if let Some(embedding_service) = &self.embedding_service {
+            health = health.with_embedding_service(embedding_service.clone());
+        }
+        if let Some(graphs) = &self.graphs {
+            health = health.with_graph_manager(graphs.clone());
+        }
+        health
+    }
+
+    /// Record a page written through the API
+    pub(crate) fn notify(&self, event: impl Into<WebhookEvent>) {
+        self.pages_changed();
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.dispatch(event.into());
+        }
+    }
+
+    /// Stop serving searches cached before pages changed
+    pub(crate) fn pages_changed(&self) {
+        if let Some(search_cache) = &self.search_cache {
+            search_cache.generation().bump();
+        }
+    }
+}
+
+impl<R: PageRepository> Clone for ApiState<R> {
+    fn clone(&self) -> Self {
+        ApiState {
+            repository: Arc::clone(&self.repository),
+            embedding_service: self.embedding_service.clone(),
+            import_service: self.import_service.clone(),
+            graphs: self.graphs.clone(),
+            import_events: self.import_events.clone(),
+            webhooks: self.webhooks.clone(),
+            url_metadata: self.url_metadata.clone(),
+            web_archiver: self.web_archiver.clone(),
+            search_cache: self.search_cache.clone(),
+            keyword_tokenizer: self.keyword_tokenizer.clone(),
+            ranking: self.ranking,
+            auth: self.auth.clone(),
+            visibility: self.visibility,
+            database: self.database.clone(),
+        }
+    }
+}
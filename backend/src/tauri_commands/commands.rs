@@ -0,0 +1,281 @@
+/// The desktop commands: search, import, sync control and stats
+use super::dto::{GraphStatusDto, ImportSummaryDto, SearchArgs, SearchResultDto, StatsDto, SyncSummaryDto};
+use super::error::{CommandError, CommandResult};
+use super::state::AppState;
+use crate::application::dto::{ResultType, SearchRequest, SearchType};
+use crate::application::repositories::PageRepository;
+use crate::application::services::{GraphError, ProgressCallback, SyncService};
+use crate::application::use_cases::SearchPagesAndBlocks;
+use crate::domain::value_objects::{GraphId, LogseqDirectoryPath};
+use crate::infrastructure::file_system::detect_layout;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Search pages, blocks and URLs, best match first
+pub async fn search<R>(state: &AppState<R>, args: SearchArgs) -> CommandResult<Vec<SearchResultDto>>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    let result_type = match args.results.as_deref() {
+        None | Some("all") => ResultType::All,
+        Some("pages") => ResultType::PagesOnly,
+        Some("blocks") => ResultType::BlocksOnly,
+        Some("urls") => ResultType::UrlsOnly,
+        Some(other) => {
+            return Err(CommandError::InvalidArgument(format!(
+                "Unknown result type '{}': expected all, pages, blocks or urls",
+                other
+            )))
+        }
+    };
+    let search_type = if args.semantic {
+        SearchType::Semantic
+    } else {
+        SearchType::Traditional
+    };
+    let request = SearchRequest::new(args.query)
+        .with_search_type(search_type)
+        .with_result_type(result_type);
+
+    let repository = state.visible();
+    let use_case = match &state.embedding_service {
+        Some(embedding_service) => {
+            SearchPagesAndBlocks::with_embedding_service(&repository, embedding_service.clone())
+        }
+        None => SearchPagesAndBlocks::new(&repository),
+    };
+    let mut results = use_case.execute(request).await?;
+    if let Some(limit) = args.limit {
+        results.truncate(limit);
+    }
+    Ok(results.into_iter().map(Into::into).collect())
+}
+
+/// Import a graph directory, .zip backup or .json/.edn export
+///
+/// `progress` receives the import's events as it runs; the app usually
+/// emits them to the window that started the import.
+pub async fn import_graph<R>(
+    state: &AppState<R>,
+    path: PathBuf,
+    incremental: bool,
+    progress: Option<ProgressCallback>,
+) -> CommandResult<ImportSummaryDto>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    if !path.exists() {
+        return Err(CommandError::InvalidArgument(format!("{} does not exist", path.display())));
+    }
+    let mut import_service = state.import_service.lock().await;
+    let summary = if incremental {
+        if !path.is_dir() {
+            return Err(CommandError::InvalidArgument(
+                "Incremental imports need a graph directory".to_string(),
+            ));
+        }
+        import_service
+            .import_directory_incremental(graph_directory(&path)?, progress)
+            .await?
+    } else {
+        import_service.import_path(&path, progress).await?
+    };
+    Ok(summary.into())
+}
+
+/// Start syncing a graph directory, returning the graph's ID
+///
+/// The graph is synced once in the background, then watched for changes
+/// until [`remove_graph`] or [`AppState::shutdown`].
+pub async fn add_graph<R>(state: &AppState<R>, path: PathBuf) -> CommandResult<String>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    let directory = graph_directory(&path)?;
+    let graph_id = GraphId::from_directory(&directory)?.as_str().to_string();
+    let mut service = SyncService::new(state.repository.clone(), directory.clone(), None)?;
+    if let Some(embedding_service) = &state.embedding_service {
+        service = service.with_embedding_service(embedding_service.clone());
+    }
+    state.graphs.add_graph(graph_id.clone(), service).await?;
+    state.directories.lock().await.insert(graph_id.clone(), directory);
+    Ok(graph_id)
+}
+
+/// Stop syncing a graph; its pages stay in the repository
+pub async fn remove_graph<R>(state: &AppState<R>, graph_id: String) -> CommandResult<()>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    state.graphs.remove_graph(&graph_id).await?;
+    state.directories.lock().await.remove(&graph_id);
+    Ok(())
+}
+
+/// Sync a graph now, without waiting for the watcher
+pub async fn sync_now<R>(state: &AppState<R>, graph_id: String) -> CommandResult<SyncSummaryDto>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    let service = state
+        .graphs
+        .service(&graph_id)
+        .await
+        .ok_or(GraphError::NotFound(graph_id))?;
+    Ok((&service.sync_once(None).await?).into())
+}
+
+/// Sync status of every synced graph, by graph ID
+pub async fn sync_status<R>(state: &AppState<R>) -> CommandResult<Vec<GraphStatusDto>>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    let mut statuses = Vec::new();
+    for graph_id in state.graphs.graph_ids().await {
+        // A graph removed meanwhile is left out
+        if let Some(service) = state.graphs.service(&graph_id).await {
+            statuses.push(GraphStatusDto::new(graph_id, service.status()));
+        }
+    }
+    Ok(statuses)
+}
+
+/// Page, block and link counts across the repository
+pub async fn stats<R>(state: &AppState<R>) -> CommandResult<StatsDto>
+where
+    R: PageRepository + Clone + Send + Sync + 'static,
+{
+    let pages = state.visible().find_all()?;
+    let directories = state.directories.lock().await;
+    let journals_dirs: Vec<PathBuf> = directories.values().filter_map(|dir| dir.journals_dir()).collect();
+
+    let journal_pages = pages
+        .iter()
+        .filter(|page| {
+            page.file_path()
+                .is_some_and(|file_path| journals_dirs.iter().any(|dir| file_path.starts_with(dir)))
+        })
+        .count();
+    let urls: HashSet<&str> = pages
+        .iter()
+        .flat_map(|page| page.all_urls())
+        .map(|url| url.as_str())
+        .collect();
+    let referenced: HashSet<String> = pages
+        .iter()
+        .flat_map(|page| page.all_page_references())
+        .map(|reference| reference.title().to_lowercase())
+        .collect();
+
+    Ok(StatsDto {
+        pages: pages.len(),
+        journal_pages,
+        blocks: pages.iter().map(|page| page.all_blocks().count()).sum(),
+        urls: urls.len(),
+        referenced_pages: referenced.len(),
+        graphs: directories.len(),
+    })
+}
+
+/// A graph directory, canonical so its ID doesn't depend on how it's written
+fn graph_directory(path: &Path) -> CommandResult<LogseqDirectoryPath> {
+    let root = std::fs::canonicalize(path)
+        .map_err(|e| CommandError::InvalidArgument(format!("Cannot open graph {}: {}", path.display(), e)))?;
+    Ok(LogseqDirectoryPath::with_layout(&root, detect_layout(&root))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::InMemoryPageRepository;
+    use crate::tauri_commands::ImportProgressDto;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    fn graph() -> TempDir {
+        let graph = TempDir::new().unwrap();
+        std::fs::create_dir(graph.path().join("pages")).unwrap();
+        std::fs::create_dir(graph.path().join("journals")).unwrap();
+        std::fs::write(graph.path().join("pages/rust.md"), "- Ownership moves values #memory").unwrap();
+        std::fs::write(graph.path().join("journals/2024_01_01.md"), "- Learning [[Rust]] https://rust-lang.org").unwrap();
+        graph
+    }
+
+    fn args(query: &str) -> SearchArgs {
+        SearchArgs {
+            query: query.to_string(),
+            semantic: false,
+            results: None,
+            limit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_progress_then_search_and_stats() {
+        let graph = graph();
+        let state = AppState::new(InMemoryPageRepository::new());
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let progress: ProgressCallback = {
+            let events = events.clone();
+            Arc::new(move |event| events.lock().unwrap().push(ImportProgressDto::from(event)))
+        };
+        let summary = import_graph(&state, graph.path().to_path_buf(), false, Some(progress))
+            .await
+            .unwrap();
+        assert_eq!(summary.pages_imported, 2);
+        let events = events.lock().unwrap().clone();
+        assert!(matches!(events.first(), Some(ImportProgressDto::Started { total_files: 2 })));
+        assert_eq!(
+            serde_json::to_value(events.first().unwrap()).unwrap(),
+            serde_json::json!({ "type": "started", "totalFiles": 2 })
+        );
+
+        let results = search(&state, args("ownership")).await.unwrap();
+        assert_eq!(results[0].page_title, "rust");
+        assert_eq!(serde_json::to_value(&results[0]).unwrap()["pageTitle"], "rust");
+
+        let stats = stats(&state).await.unwrap();
+        assert_eq!((stats.pages, stats.blocks, stats.urls, stats.referenced_pages), (2, 2, 1, 2));
+        // Journals are only known for synced graphs
+        assert_eq!(stats.journal_pages, 0);
+    }
+
+    #[tokio::test]
+    async fn test_synced_graph_status_and_removal() {
+        let graph = graph();
+        let state = AppState::new(InMemoryPageRepository::new());
+
+        let graph_id = add_graph(&state, graph.path().to_path_buf()).await.unwrap();
+        let summary = sync_now(&state, graph_id.clone()).await.unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(stats(&state).await.unwrap().journal_pages, 1);
+
+        let statuses = sync_status(&state).await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].graph_id, graph_id);
+
+        remove_graph(&state, graph_id.clone()).await.unwrap();
+        assert!(sync_status(&state).await.unwrap().is_empty());
+        let error = sync_now(&state, graph_id).await.unwrap_err();
+        assert!(matches!(error, CommandError::Graph(GraphError::NotFound(_))));
+        state.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments_serialize_as_messages() {
+        let state = AppState::new(InMemoryPageRepository::new());
+        let mut args = args("rust");
+        args.results = Some("tags".to_string());
+        let error = search(&state, args).await.unwrap_err();
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            "Unknown result type 'tags': expected all, pages, blocks or urls"
+        );
+
+        let error = import_graph(&state, PathBuf::from("/no/such/graph"), false, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CommandError::InvalidArgument(_)));
+    }
+}
@@ -0,0 +1,237 @@
+/// Serializable arguments and results of the desktop commands
+///
+/// Fields are camelCase, as the frontend's `invoke` calls expect, except in
+/// the import summaries, file errors and sync events shared with the HTTP API.
+use crate::application::dto::{SearchItem, SearchResult};
+pub use crate::application::dto::{FileErrorDto, ImportSummaryDto, SyncEventDto};
+use crate::application::services::{ImportProgressEvent, SyncStatus, SyncSummary};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Arguments of the `search` command
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchArgs {
+    pub query: String,
+    /// Semantic search, falling back to keyword search without embeddings
+    #[serde(default)]
+    pub semantic: bool,
+    /// "all" (the default), "pages", "blocks" or "urls"
+    #[serde(default)]
+    pub results: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultDto {
+    pub score: f64,
+    /// "page", "block", "url" or "archive"
+    pub kind: &'static str,
+    pub page_id: String,
+    pub page_title: String,
+    pub block_id: Option<String>,
+    /// Block content, the URL of a URL match, or an archived page's excerpt
+    pub text: Option<String>,
+    /// Enclosing block contents, from the top level down
+    pub hierarchy_path: Vec<String>,
+}
+
+impl From<SearchResult> for SearchResultDto {
+    fn from(result: SearchResult) -> Self {
+        let score = f64::from(result.score.value());
+        match result.item {
+            SearchItem::Page(page) => SearchResultDto {
+                score,
+                kind: "page",
+                page_id: page.page_id.as_str().to_string(),
+                page_title: page.title,
+                block_id: None,
+                text: None,
+                hierarchy_path: Vec::new(),
+            },
+            SearchItem::Block(block) => SearchResultDto {
+                score,
+                kind: "block",
+                page_id: block.page_id.as_str().to_string(),
+                page_title: block.page_title,
+                block_id: Some(block.block_id.as_str().to_string()),
+                text: Some(block.content),
+                hierarchy_path: block.hierarchy_path,
+            },
+            SearchItem::Url(url) => SearchResultDto {
+                score,
+                kind: "url",
+                page_id: url.page_id.as_str().to_string(),
+                page_title: url.page_title,
+                block_id: Some(url.containing_block_id.as_str().to_string()),
+                text: Some(url.url.as_str().to_string()),
+                hierarchy_path: Vec::new(),
+            },
+            // An archived web page isn't a page of the graph: its URL stands in for the page
+            SearchItem::Archive(archive) => SearchResultDto {
+                score,
+                kind: "archive",
+                page_id: archive.url.as_str().to_string(),
+                page_title: archive.title.unwrap_or_else(|| archive.url.as_str().to_string()),
+                block_id: None,
+                text: Some(archive.excerpt),
+                hierarchy_path: Vec::new(),
+            },
+        }
+    }
+}
+
+/// An import progress event, for the app to emit to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ImportProgressDto {
+    Started {
+        total_files: usize,
+    },
+    FileProcessed {
+        file_path: PathBuf,
+        files_processed: usize,
+        total_files: usize,
+        percentage: f64,
+    },
+    QueuedForEmbedding {
+        pages_queued: usize,
+        total_queued: usize,
+    },
+    Completed {
+        pages_imported: usize,
+        duration_ms: u64,
+    },
+    Failed {
+        error: String,
+        files_processed: usize,
+    },
+    Cancelled {
+        pages_imported: usize,
+        files_processed: usize,
+    },
+}
+
+impl From<ImportProgressEvent> for ImportProgressDto {
+    fn from(event: ImportProgressEvent) -> Self {
+        match event {
+            ImportProgressEvent::Started { total_files } => ImportProgressDto::Started { total_files },
+            ImportProgressEvent::FileProcessed { file_path, progress } => ImportProgressDto::FileProcessed {
+                file_path,
+                files_processed: progress.files_processed(),
+                total_files: progress.total_files(),
+                percentage: progress.percentage(),
+            },
+            ImportProgressEvent::QueuedForEmbedding {
+                pages_queued,
+                total_queued,
+            } => ImportProgressDto::QueuedForEmbedding {
+                pages_queued,
+                total_queued,
+            },
+            ImportProgressEvent::Completed {
+                pages_imported,
+                duration_ms,
+            } => ImportProgressDto::Completed {
+                pages_imported,
+                duration_ms,
+            },
+            ImportProgressEvent::Failed { error, files_processed } => {
+                ImportProgressDto::Failed { error, files_processed }
+            }
+            ImportProgressEvent::Cancelled {
+                pages_imported,
+                files_processed,
+            } => ImportProgressDto::Cancelled {
+                pages_imported,
+                files_processed,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummaryDto {
+    pub files_created: usize,
+    pub files_updated: usize,
+    pub files_deleted: usize,
+    pub files_renamed: usize,
+    pub files_unchanged: usize,
+    pub conflicts: usize,
+    pub errors: Vec<FileErrorDto>,
+}
+
+impl From<&SyncSummary> for SyncSummaryDto {
+    fn from(summary: &SyncSummary) -> Self {
+        SyncSummaryDto {
+            files_created: summary.files_created,
+            files_updated: summary.files_updated,
+            files_deleted: summary.files_deleted,
+            files_renamed: summary.files_renamed,
+            files_unchanged: summary.files_unchanged,
+            conflicts: summary.conflicts,
+            errors: file_errors(&summary.errors),
+        }
+    }
+}
+
+fn file_errors(errors: &[(PathBuf, String)]) -> Vec<FileErrorDto> {
+    errors
+        .iter()
+        .map(|(file_path, error)| FileErrorDto {
+            file_path: file_path.clone(),
+            error: error.clone(),
+            occurred_at: None,
+        })
+        .collect()
+}
+
+/// A synced graph and how its sync is going
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphStatusDto {
+    pub graph_id: String,
+    pub watching: bool,
+    /// Milliseconds since the Unix epoch, as JavaScript dates count
+    pub last_sync: Option<u64>,
+    pub pending_operations: usize,
+    pub conflicts: usize,
+    pub errors: usize,
+    pub recent_errors: Vec<FileErrorDto>,
+}
+
+impl GraphStatusDto {
+    pub fn new(graph_id: String, status: SyncStatus) -> Self {
+        GraphStatusDto {
+            graph_id,
+            watching: status.watching,
+            last_sync: status.last_sync.map(unix_millis),
+            pending_operations: status.pending_operations,
+            conflicts: status.conflicts,
+            errors: status.errors,
+            recent_errors: status.recent_errors.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or(0)
+}
+
+/// Counts across the repository, as `logjam stats` shows them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsDto {
+    pub pages: usize,
+    pub journal_pages: usize,
+    pub blocks: usize,
+    /// Distinct URLs
+    pub urls: usize,
+    /// Distinct titles of linked or tagged pages
+    pub referenced_pages: usize,
+    pub graphs: usize,
+}
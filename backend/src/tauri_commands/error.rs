@@ -0,0 +1,36 @@
+/// Errors returned by the desktop commands
+use crate::application::services::{GraphError, ImportError, SyncError};
+use crate::application::use_cases::SearchError;
+use crate::domain::DomainError;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+
+    #[error(transparent)]
+    Import(#[from] ImportError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+
+    #[error(transparent)]
+    Search(#[from] SearchError),
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+/// Commands reject with the error's message, which is what the frontend shows
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
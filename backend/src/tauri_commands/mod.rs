@@ -0,0 +1,28 @@
+/// Commands for the Tauri desktop app
+///
+/// Tauri commands can't be generic, so the app wraps each function here in
+/// a `#[tauri::command]` for its repository type and manages an
+/// [`AppState`]. Arguments, results and errors all (de)serialize for the
+/// frontend's `invoke` calls:
+///
+/// ```ignore
+/// #[tauri::command]
+/// async fn search(
+///     state: tauri::State<'_, AppState<InMemoryPageRepository>>,
+///     args: SearchArgs,
+/// ) -> CommandResult<Vec<SearchResultDto>> {
+///     tauri_commands::search(&state, args).await
+/// }
+/// ```
+pub mod commands;
+pub mod dto;
+pub mod error;
+pub mod state;
+
+pub use commands::{add_graph, import_graph, remove_graph, search, stats, sync_now, sync_status};
+pub use dto::{
+    FileErrorDto, GraphStatusDto, ImportProgressDto, ImportSummaryDto, SearchArgs, SearchResultDto, StatsDto,
+    SyncEventDto, SyncSummaryDto,
+};
+pub use error::{CommandError, CommandResult};
+pub use state::AppState;
@@ -0,0 +1,75 @@
+/// Services shared by the desktop commands
+use crate::application::repositories::PageRepository;
+use crate::application::services::{
+    EmbeddingService, GraphEvent, GraphManager, ImportService, VisibilityPolicy, VisiblePages,
+};
+use crate::domain::value_objects::LogseqDirectoryPath;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+/// What the commands work on, managed by the desktop app
+/// (`tauri::Builder::manage`)
+///
+/// Imports and synced graphs write through clones of the repository, so its
+/// clones must share one store, as
+/// [`InMemoryPageRepository`](crate::infrastructure::persistence::InMemoryPageRepository)'s do.
+pub struct AppState<R: PageRepository + Clone> {
+    pub(crate) repository: R,
+    pub(crate) embedding_service: Option<Arc<EmbeddingService>>,
+    /// What the commands may show; everything when unset
+    pub(crate) visibility: Option<VisibilityPolicy>,
+    /// One import runs at a time
+    pub(crate) import_service: Mutex<ImportService<R>>,
+    pub(crate) graphs: GraphManager<R>,
+    /// Directories of the synced graphs, by graph ID
+    pub(crate) directories: Mutex<HashMap<String, LogseqDirectoryPath>>,
+}
+
+impl<R: PageRepository + Clone + Send + Sync + 'static> AppState<R> {
+    pub fn new(repository: R) -> Self {
+        AppState {
+            import_service: Mutex::new(ImportService::new(repository.clone())),
+            repository,
+            embedding_service: None,
+            visibility: None,
+            graphs: GraphManager::new(),
+            directories: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Enable semantic search
+    pub fn with_embedding_service(mut self, embedding_service: Arc<EmbeddingService>) -> Self {
+        self.embedding_service = Some(embedding_service);
+        self
+    }
+
+    /// Hide the pages and blocks `visibility` keeps private from the commands
+    pub fn with_visibility(mut self, visibility: VisibilityPolicy) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// The repository as the commands may show it
+    pub(crate) fn visible(&self) -> VisiblePages<'_, R> {
+        VisiblePages::new(&self.repository, self.visibility)
+    }
+
+    /// Whether `event` may be forwarded to the frontend: its files' pages
+    /// aren't hidden
+    pub fn shows(&self, event: &GraphEvent) -> bool {
+        self.visible().shows_sync_event(&event.event)
+    }
+
+    /// Sync events of every graph the app added, to forward to the frontend
+    /// (see [`SyncEventDto`](super::SyncEventDto)) when [`shows`](Self::shows)
+    /// allows
+    pub fn subscribe(&self) -> broadcast::Receiver<GraphEvent> {
+        self.graphs.subscribe()
+    }
+
+    /// Stop syncing every graph, before the app exits
+    pub async fn shutdown(&self) {
+        self.graphs.shutdown().await;
+    }
+}